@@ -0,0 +1,908 @@
+// Copyright (c) 2016-2020 Fabian Schuiki
+
+//! Export of the synthesizable subset of generated code as AIGER.
+//!
+//! The top-level entity of a module is flattened and bit-blasted into an
+//! and-inverter graph, which is written in the ASCII variant of the AIGER
+//! format understood by ABC and most hardware model checkers. Input ports
+//! become inputs of the graph, output ports its outputs. Instantiated entities
+//! are inlined.
+//!
+//! Registers triggered on a single clock edge become latches. AIGER has an
+//! implicit global clock, so all registers of the design must be triggered by
+//! the same signal. Processes, level-sensitive registers, and operations
+//! without a reasonable gate-level equivalent such as division prevent the
+//! export. Rather than stopping at the first such construct, all of them are
+//! collected in a report.
+
+use llhd::ir::{Inst, Module, Opcode, RegMode, Unit, UnitName, Value};
+use llhd::TypeKind;
+use std::collections::{HashMap, HashSet};
+use std::fmt;
+
+/// A construct that prevented the export.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Issue {
+    /// The unit containing the construct.
+    pub unit: UnitName,
+    /// A description of the construct.
+    pub message: String,
+}
+
+impl fmt::Display for Issue {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{}: {}", self.unit, self.message)
+    }
+}
+
+/// Export the top-level entity of a module as ASCII AIGER.
+///
+/// Returns the constructs that prevented the export if the design is not
+/// within the synthesizable subset.
+pub fn export(module: &Module) -> Result<String, Vec<Issue>> {
+    let top = find_top(module)?;
+
+    let mut exporter = Exporter {
+        module,
+        graph: Graph::default(),
+        frames: vec![Frame {
+            unit: top,
+            parent: None,
+        }],
+        instances: HashMap::new(),
+        bits: HashMap::new(),
+        active: HashSet::new(),
+        pending: vec![],
+        clock: None,
+        issues: vec![],
+    };
+    for output in top.output_args() {
+        let bits = exporter.signal(0, output);
+        let name = name_of(top, output);
+        for (index, &bit) in bits.iter().enumerate() {
+            exporter
+                .graph
+                .outputs
+                .push((bit, bit_name(&name, index, bits.len())));
+        }
+    }
+    while let Some((frame, reg, first)) = exporter.pending.pop() {
+        exporter.latch_next(frame, reg, first);
+    }
+
+    if exporter.issues.is_empty() {
+        Ok(exporter.graph.to_string())
+    } else {
+        Err(exporter.issues)
+    }
+}
+
+/// Find the top-level entity of a module, which is the only entity not
+/// instantiated by any other unit.
+pub fn find_top(module: &Module) -> Result<Unit<'_>, Vec<Issue>> {
+    let instantiated: HashSet<&UnitName> = module
+        .units()
+        .flat_map(|unit| {
+            unit.all_insts()
+                .filter(move |&inst| unit[inst].opcode() == Opcode::Inst)
+                .map(move |inst| unit.extern_name(unit[inst].get_ext_unit().unwrap()))
+        })
+        .collect();
+    let tops: Vec<_> = module
+        .units()
+        .filter(|unit| unit.is_entity() && !instantiated.contains(unit.name()))
+        .collect();
+    match tops.len() {
+        1 => Ok(tops[0]),
+        0 => Err(module
+            .units()
+            .map(|unit| Issue {
+                unit: unit.name().clone(),
+                message: "is not an entity".to_string(),
+            })
+            .collect()),
+        _ => Err(tops
+            .iter()
+            .map(|unit| Issue {
+                unit: unit.name().clone(),
+                message: "is one of several top-level entities".to_string(),
+            })
+            .collect()),
+    }
+}
+
+/// A literal of the graph, i.e. a variable index shifted left by one, with the
+/// lowest bit indicating negation.
+type Lit = u32;
+
+const FALSE: Lit = 0;
+const TRUE: Lit = 1;
+
+/// An and-inverter graph.
+#[derive(Default)]
+struct Graph {
+    num_vars: u32,
+    inputs: Vec<(Lit, String)>,
+    latches: Vec<Latch>,
+    outputs: Vec<(Lit, String)>,
+    ands: Vec<(Lit, Lit, Lit)>,
+    /// The and gates already created, for structural hashing.
+    strash: HashMap<(Lit, Lit), Lit>,
+}
+
+/// A latch of the graph.
+struct Latch {
+    lit: Lit,
+    next: Lit,
+    init: bool,
+    name: String,
+}
+
+impl Graph {
+    /// Allocate a fresh variable.
+    fn var(&mut self) -> Lit {
+        self.num_vars += 1;
+        self.num_vars * 2
+    }
+
+    fn and(&mut self, a: Lit, b: Lit) -> Lit {
+        let (a, b) = if a < b { (b, a) } else { (a, b) };
+        if b == FALSE || a == b ^ 1 {
+            return FALSE;
+        }
+        if b == TRUE || a == b {
+            return a;
+        }
+        if let Some(&lit) = self.strash.get(&(a, b)) {
+            return lit;
+        }
+        let lit = self.var();
+        self.ands.push((lit, a, b));
+        self.strash.insert((a, b), lit);
+        lit
+    }
+
+    fn or(&mut self, a: Lit, b: Lit) -> Lit {
+        self.and(a ^ 1, b ^ 1) ^ 1
+    }
+
+    fn xor(&mut self, a: Lit, b: Lit) -> Lit {
+        let x = self.and(a, b ^ 1);
+        let y = self.and(a ^ 1, b);
+        self.or(x, y)
+    }
+
+    fn mux(&mut self, sel: Lit, a: Lit, b: Lit) -> Lit {
+        let x = self.and(sel, a);
+        let y = self.and(sel ^ 1, b);
+        self.or(x, y)
+    }
+
+    /// Add two bit vectors and a carry, returning the sum and carry out.
+    fn add(&mut self, a: &[Lit], b: &[Lit], mut carry: Lit) -> (Vec<Lit>, Lit) {
+        let mut sum = vec![];
+        for (&a, &b) in a.iter().zip(b) {
+            let x = self.xor(a, b);
+            sum.push(self.xor(x, carry));
+            let c0 = self.and(a, b);
+            let c1 = self.and(x, carry);
+            carry = self.or(c0, c1);
+        }
+        (sum, carry)
+    }
+
+    /// Check two bit vectors for equality.
+    fn eq(&mut self, a: &[Lit], b: &[Lit]) -> Lit {
+        let mut result = TRUE;
+        for (&a, &b) in a.iter().zip(b) {
+            let x = self.xor(a, b);
+            result = self.and(result, x ^ 1);
+        }
+        result
+    }
+
+    /// Check whether one bit vector is less than another, as unsigned numbers.
+    fn ult(&mut self, a: &[Lit], b: &[Lit]) -> Lit {
+        let not_b: Vec<_> = b.iter().map(|&b| b ^ 1).collect();
+        self.add(a, &not_b, TRUE).1 ^ 1
+    }
+
+    /// Select between two bit vectors.
+    fn mux_vec(&mut self, sel: Lit, a: &[Lit], b: &[Lit]) -> Vec<Lit> {
+        a.iter()
+            .zip(b)
+            .map(|(&a, &b)| self.mux(sel, a, b))
+            .collect()
+    }
+}
+
+impl fmt::Display for Graph {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        writeln!(
+            f,
+            "aag {} {} {} {} {}",
+            self.num_vars,
+            self.inputs.len(),
+            self.latches.len(),
+            self.outputs.len(),
+            self.ands.len()
+        )?;
+        for &(lit, _) in &self.inputs {
+            writeln!(f, "{}", lit)?;
+        }
+        for latch in &self.latches {
+            if latch.init {
+                writeln!(f, "{} {} 1", latch.lit, latch.next)?;
+            } else {
+                writeln!(f, "{} {}", latch.lit, latch.next)?;
+            }
+        }
+        for &(lit, _) in &self.outputs {
+            writeln!(f, "{}", lit)?;
+        }
+        for &(lhs, a, b) in &self.ands {
+            writeln!(f, "{} {} {}", lhs, a, b)?;
+        }
+        for (index, (_, name)) in self.inputs.iter().enumerate() {
+            writeln!(f, "i{} {}", index, name)?;
+        }
+        for (index, latch) in self.latches.iter().enumerate() {
+            writeln!(f, "l{} {}", index, latch.name)?;
+        }
+        for (index, (_, name)) in self.outputs.iter().enumerate() {
+            writeln!(f, "o{} {}", index, name)?;
+        }
+        Ok(())
+    }
+}
+
+/// An entity being inlined into the graph.
+struct Frame<'a> {
+    unit: Unit<'a>,
+    /// The frame and instruction which instantiates the entity, or `None` for
+    /// the top-level entity.
+    parent: Option<(usize, Inst)>,
+}
+
+/// The state of an export.
+struct Exporter<'a> {
+    module: &'a Module,
+    graph: Graph,
+    frames: Vec<Frame<'a>>,
+    /// The frame created for each instantiation.
+    instances: HashMap<(usize, Inst), usize>,
+    /// The bits of each value and signal already visited.
+    bits: HashMap<(usize, Value), Vec<Lit>>,
+    /// The signals currently being visited, to detect combinational loops.
+    active: HashSet<(usize, Value)>,
+    /// The registers whose latches still need their next state, together with
+    /// the index of their first latch.
+    pending: Vec<(usize, Inst, usize)>,
+    /// The signal and edge triggering the registers of the design.
+    clock: Option<(usize, Value, RegMode)>,
+    issues: Vec<Issue>,
+}
+
+impl<'a> Exporter<'a> {
+    fn issue(&mut self, frame: usize, message: String) {
+        self.issues.push(Issue {
+            unit: self.frames[frame].unit.name().clone(),
+            message,
+        });
+    }
+
+    /// Determine the bits carried on a signal.
+    fn signal(&mut self, frame: usize, signal: Value) -> Vec<Lit> {
+        if let Some(bits) = self.bits.get(&(frame, signal)) {
+            return bits.clone();
+        }
+        let unit = self.frames[frame].unit;
+        let name = name_of(unit, signal);
+        let width = match signal_width(unit, signal) {
+            Some(width) => width,
+            None => {
+                let ty = unit.value_type(signal);
+                self.issue(frame, format!("signal `{}` of type {}", name, ty));
+                return vec![];
+            }
+        };
+        if !self.active.insert((frame, signal)) {
+            self.issue(frame, format!("combinational loop through `{}`", name));
+            return vec![FALSE; width];
+        }
+        let bits = self.signal_uncached(frame, signal, &name, width);
+        let bits = resize(bits, width);
+        self.active.remove(&(frame, signal));
+        self.bits.insert((frame, signal), bits.clone());
+        bits
+    }
+
+    fn signal_uncached(
+        &mut self,
+        frame: usize,
+        signal: Value,
+        name: &str,
+        width: usize,
+    ) -> Vec<Lit> {
+        let unit = self.frames[frame].unit;
+
+        // Input ports are either inputs of the graph, or connected to a signal
+        // of the instantiating entity.
+        if let Some(pos) = unit.input_args().position(|arg| arg == signal) {
+            return match self.frames[frame].parent {
+                Some((parent, inst)) => {
+                    let outer = self.frames[parent].unit[inst].input_args()[pos];
+                    self.signal(parent, outer)
+                }
+                None => (0..width)
+                    .map(|index| {
+                        let lit = self.graph.var();
+                        self.graph.inputs.push((lit, bit_name(name, index, width)));
+                        lit
+                    })
+                    .collect(),
+            };
+        }
+        if unit.get_value_arg(signal).is_none() {
+            match unit.get_value_inst(signal) {
+                Some(inst) if unit[inst].opcode() == Opcode::Sig => (),
+                _ => {
+                    self.issue(frame, format!("signal projection `{}`", name));
+                    return vec![];
+                }
+            }
+        }
+
+        // Find the instructions that assign the signal.
+        let mut drivers = vec![];
+        for &inst in unit.uses(signal) {
+            let data = &unit[inst];
+            match data.opcode() {
+                Opcode::Drv | Opcode::DrvCond | Opcode::Reg if data.args()[0] == signal => {
+                    drivers.push(inst)
+                }
+                Opcode::Inst if data.output_args().contains(&signal) => drivers.push(inst),
+                _ => (),
+            }
+        }
+        let inst = match drivers.len() {
+            1 => drivers[0],
+            0 => {
+                return match unit.get_value_inst(signal) {
+                    Some(inst) => self.value(frame, unit[inst].args()[0]),
+                    None => {
+                        self.issue(frame, format!("output `{}` is never driven", name));
+                        vec![]
+                    }
+                }
+            }
+            _ => {
+                self.issue(frame, format!("signal `{}` has multiple drivers", name));
+                return vec![];
+            }
+        };
+
+        let data = &unit[inst];
+        match data.opcode() {
+            Opcode::Drv => self.value(frame, data.args()[1]),
+            Opcode::Reg => {
+                let first = self.graph.latches.len();
+                let init = self.initial_value(frame, signal, width);
+                let bits: Vec<_> = (0..width)
+                    .map(|index| {
+                        let lit = self.graph.var();
+                        self.graph.latches.push(Latch {
+                            lit,
+                            next: FALSE,
+                            init: init[index] == TRUE,
+                            name: bit_name(name, index, width),
+                        });
+                        lit
+                    })
+                    .collect();
+                self.pending.push((frame, inst, first));
+                bits
+            }
+            Opcode::Inst => {
+                let callee_name = unit.extern_name(data.get_ext_unit().unwrap());
+                let callee = match self.module.units().find(|u| u.name() == callee_name) {
+                    Some(callee) if callee.is_entity() => callee,
+                    Some(_) => {
+                        self.issue(
+                            frame,
+                            format!("`{}` is driven by process {}", name, callee_name),
+                        );
+                        return vec![];
+                    }
+                    None => {
+                        self.issue(
+                            frame,
+                            format!("`{}` is driven by undefined unit {}", name, callee_name),
+                        );
+                        return vec![];
+                    }
+                };
+                let pos = data
+                    .output_args()
+                    .iter()
+                    .position(|&arg| arg == signal)
+                    .unwrap();
+                let child = match self.instances.get(&(frame, inst)) {
+                    Some(&child) => child,
+                    None => {
+                        self.frames.push(Frame {
+                            unit: callee,
+                            parent: Some((frame, inst)),
+                        });
+                        let child = self.frames.len() - 1;
+                        self.instances.insert((frame, inst), child);
+                        child
+                    }
+                };
+                self.signal(child, callee.output_arg(pos))
+            }
+            _ => {
+                self.issue(frame, format!("conditional drive of `{}`", name));
+                vec![]
+            }
+        }
+    }
+
+    /// Determine the initial value of a register, from the declaration of the
+    /// signal it drives.
+    fn initial_value(&mut self, frame: usize, signal: Value, width: usize) -> Vec<Lit> {
+        let unit = self.frames[frame].unit;
+        let init = unit
+            .get_value_inst(signal)
+            .map(|inst| unit[inst].args()[0])
+            .and_then(|init| unit.get_const_int(init));
+        match init {
+            Some(int) => const_bits(&int.value.to_bytes_le(), width),
+            None => vec![FALSE; width],
+        }
+    }
+
+    /// Compute the next state of the latches of a register.
+    fn latch_next(&mut self, frame: usize, reg: Inst, first: usize) {
+        let unit = self.frames[frame].unit;
+        let data = &unit[reg];
+        let name = name_of(unit, data.args()[0]);
+        let modes: Vec<_> = data.mode_args().collect();
+        let mode = match modes.as_slice() {
+            [mode @ RegMode::Rise] | [mode @ RegMode::Fall] => *mode,
+            _ => {
+                let modes: Vec<_> = modes.iter().map(|m| m.to_string()).collect();
+                self.issue(
+                    frame,
+                    format!("register `{}` with triggers [{}]", name, modes.join(", ")),
+                );
+                return;
+            }
+        };
+
+        // All registers must share the implicit clock of the graph.
+        let trigger = data.trigger_args().next().unwrap();
+        let (clock_frame, clock_signal) = match unit.get_value_inst(trigger) {
+            Some(inst) if unit[inst].opcode() == Opcode::Prb => {
+                self.root_signal(frame, unit[inst].args()[0])
+            }
+            _ => {
+                self.issue(
+                    frame,
+                    format!("register `{}` is not triggered by a signal", name),
+                );
+                return;
+            }
+        };
+        let clock = (clock_frame, clock_signal, mode);
+        match self.clock {
+            None => self.clock = Some(clock),
+            Some(existing) if existing == clock => (),
+            Some((clock_frame, clock_signal, mode)) => {
+                let clock_name = name_of(self.frames[clock_frame].unit, clock_signal);
+                self.issue(
+                    frame,
+                    format!(
+                        "register `{}` is not triggered on the {} edge of `{}` like the other registers",
+                        name, mode, clock_name
+                    ),
+                );
+                return;
+            }
+        }
+
+        let width = signal_width(unit, data.args()[0]).unwrap();
+        let current: Vec<_> = self.graph.latches[first..first + width]
+            .iter()
+            .map(|latch| latch.lit)
+            .collect();
+        let mut next = resize(self.value(frame, data.data_args().next().unwrap()), width);
+        if let Some(gate) = data.gating_args().next().unwrap() {
+            let gate = resize(self.value(frame, gate), 1)[0];
+            next = self.graph.mux_vec(gate, &next, &current);
+        }
+        for (latch, next) in self.graph.latches[first..first + width]
+            .iter_mut()
+            .zip(next)
+        {
+            latch.next = next;
+        }
+    }
+
+    /// Follow a signal through the input ports of inlined entities to the
+    /// signal it is connected to.
+    fn root_signal(&self, mut frame: usize, mut signal: Value) -> (usize, Value) {
+        loop {
+            let unit = self.frames[frame].unit;
+            let pos = match unit.input_args().position(|arg| arg == signal) {
+                Some(pos) => pos,
+                None => return (frame, signal),
+            };
+            match self.frames[frame].parent {
+                Some((parent, inst)) => {
+                    signal = self.frames[parent].unit[inst].input_args()[pos];
+                    frame = parent;
+                }
+                None => return (frame, signal),
+            }
+        }
+    }
+
+    /// Determine the bits of a value.
+    fn value(&mut self, frame: usize, value: Value) -> Vec<Lit> {
+        if let Some(bits) = self.bits.get(&(frame, value)) {
+            return bits.clone();
+        }
+        let unit = self.frames[frame].unit;
+        let width = match *unit.value_type(value) {
+            TypeKind::IntType(width) => width,
+            ref ty => {
+                self.issue(
+                    frame,
+                    format!("value `{}` of type {}", value.dump(&unit), ty),
+                );
+                return vec![];
+            }
+        };
+        let inst = match unit.get_value_inst(value) {
+            Some(inst) => inst,
+            None => return vec![FALSE; width],
+        };
+        let bits = self.value_uncached(frame, inst, width);
+        let bits = resize(bits, width);
+        self.bits.insert((frame, value), bits.clone());
+        bits
+    }
+
+    fn value_uncached(&mut self, frame: usize, inst: Inst, width: usize) -> Vec<Lit> {
+        let unit = self.frames[frame].unit;
+        let data = &unit[inst];
+        let args = data.args();
+        let opcode = data.opcode();
+        match opcode {
+            Opcode::ConstInt => {
+                let int = data.get_const_int().unwrap();
+                const_bits(&int.value.to_bytes_le(), width)
+            }
+            Opcode::Alias => self.value(frame, args[0]),
+            Opcode::Prb => self.signal(frame, args[0]),
+            Opcode::Not => self.value(frame, args[0]).iter().map(|&a| a ^ 1).collect(),
+            Opcode::And | Opcode::Or | Opcode::Xor => {
+                let a = self.value(frame, args[0]);
+                let b = self.value(frame, args[1]);
+                a.iter()
+                    .zip(&b)
+                    .map(|(&a, &b)| match opcode {
+                        Opcode::And => self.graph.and(a, b),
+                        Opcode::Or => self.graph.or(a, b),
+                        _ => self.graph.xor(a, b),
+                    })
+                    .collect()
+            }
+            Opcode::Neg => {
+                let a: Vec<_> = self.value(frame, args[0]).iter().map(|&a| a ^ 1).collect();
+                self.graph.add(&a, &vec![FALSE; width], TRUE).0
+            }
+            Opcode::Add => {
+                let a = self.value(frame, args[0]);
+                let b = self.value(frame, args[1]);
+                self.graph.add(&a, &b, FALSE).0
+            }
+            Opcode::Sub => {
+                let a = self.value(frame, args[0]);
+                let b: Vec<_> = self.value(frame, args[1]).iter().map(|&b| b ^ 1).collect();
+                self.graph.add(&a, &b, TRUE).0
+            }
+            Opcode::Umul | Opcode::Smul => {
+                let a = self.value(frame, args[0]);
+                let b = self.value(frame, args[1]);
+                let mut product = vec![FALSE; width];
+                for (shift, &bit) in b.iter().enumerate() {
+                    let mut partial = vec![FALSE; shift];
+                    partial.extend(
+                        a.iter()
+                            .take(width - shift)
+                            .map(|&a| self.graph.and(a, bit)),
+                    );
+                    product = self.graph.add(&product, &partial, FALSE).0;
+                }
+                product
+            }
+            Opcode::Eq | Opcode::Neq => {
+                let a = self.value(frame, args[0]);
+                let b = self.value(frame, args[1]);
+                let eq = self.graph.eq(&a, &b);
+                vec![if opcode == Opcode::Eq { eq } else { eq ^ 1 }]
+            }
+            Opcode::Ult | Opcode::Ugt | Opcode::Ule | Opcode::Uge => {
+                let a = self.value(frame, args[0]);
+                let b = self.value(frame, args[1]);
+                vec![self.compare(opcode, a, b)]
+            }
+            Opcode::Slt | Opcode::Sgt | Opcode::Sle | Opcode::Sge => {
+                // Flipping the sign bits maps signed to unsigned order.
+                let mut a = self.value(frame, args[0]);
+                let mut b = self.value(frame, args[1]);
+                if let (Some(a), Some(b)) = (a.last_mut(), b.last_mut()) {
+                    *a ^= 1;
+                    *b ^= 1;
+                }
+                let opcode = match opcode {
+                    Opcode::Slt => Opcode::Ult,
+                    Opcode::Sgt => Opcode::Ugt,
+                    Opcode::Sle => Opcode::Ule,
+                    _ => Opcode::Uge,
+                };
+                vec![self.compare(opcode, a, b)]
+            }
+            Opcode::ExtSlice => {
+                let a = self.value(frame, args[0]);
+                let offset = data.imms()[0];
+                a.into_iter().skip(offset).take(width).collect()
+            }
+            Opcode::InsSlice => {
+                let mut a = self.value(frame, args[0]);
+                let b = self.value(frame, args[1]);
+                let offset = data.imms()[0];
+                for (bit, b) in a.iter_mut().skip(offset).zip(b) {
+                    *bit = b;
+                }
+                a
+            }
+            Opcode::Shl | Opcode::Shr => {
+                let base = self.value(frame, args[0]);
+                let hidden = self.value(frame, args[1]);
+                let amount = self.value(frame, args[2]);
+                self.shift(opcode == Opcode::Shl, base, hidden, amount)
+            }
+            Opcode::Mux => {
+                let choices = match unit.get_value_inst(args[0]) {
+                    Some(inst) if unit[inst].opcode() == Opcode::Array => {
+                        unit[inst].args().to_vec()
+                    }
+                    _ => {
+                        self.issue(frame, "`mux` over a computed array".to_string());
+                        return vec![];
+                    }
+                };
+                let select = self.value(frame, args[1]);
+                let mut result = self.value(frame, *choices.last().unwrap());
+                for (index, &choice) in choices.iter().enumerate().rev().skip(1) {
+                    let index = const_bits(&index.to_le_bytes(), select.len());
+                    let hit = self.graph.eq(&select, &index);
+                    let choice = self.value(frame, choice);
+                    result = self.graph.mux_vec(hit, &choice, &result);
+                }
+                result
+            }
+            _ => {
+                let result = unit.inst_result(inst).dump(&unit).to_string();
+                self.issue(
+                    frame,
+                    format!("`{}` instruction computing `{}`", opcode, result),
+                );
+                vec![]
+            }
+        }
+    }
+
+    /// Compare two bit vectors as unsigned numbers.
+    fn compare(&mut self, opcode: Opcode, a: Vec<Lit>, b: Vec<Lit>) -> Lit {
+        match opcode {
+            Opcode::Ult => self.graph.ult(&a, &b),
+            Opcode::Ugt => self.graph.ult(&b, &a),
+            Opcode::Ule => self.graph.ult(&b, &a) ^ 1,
+            _ => self.graph.ult(&a, &b) ^ 1,
+        }
+    }
+
+    /// Shift a bit vector by a computed amount, shifting in the bits of the
+    /// hidden value.
+    fn shift(
+        &mut self,
+        left: bool,
+        base: Vec<Lit>,
+        hidden: Vec<Lit>,
+        amount: Vec<Lit>,
+    ) -> Vec<Lit> {
+        let width = base.len();
+        // Shift the concatenation of both values towards the least significant
+        // bit, which for left shifts means reversing the bit order.
+        let mut bits: Vec<_> = if left {
+            base.iter()
+                .rev()
+                .chain(hidden.iter().rev())
+                .cloned()
+                .collect()
+        } else {
+            base.iter().chain(hidden.iter()).cloned().collect()
+        };
+        let total = bits.len();
+        for (stage, &bit) in amount.iter().enumerate() {
+            let distance = 1usize.checked_shl(stage as u32).unwrap_or(std::usize::MAX);
+            let shifted: Vec<_> = (0..total)
+                .map(|index| match index.checked_add(distance) {
+                    Some(from) if from < total => bits[from],
+                    _ => FALSE,
+                })
+                .collect();
+            bits = self.graph.mux_vec(bit, &shifted, &bits);
+        }
+        bits.truncate(width);
+        if left {
+            bits.reverse();
+        }
+        bits
+    }
+}
+
+/// The name of a signal or value as it appears in the assembly, without the
+/// leading `%`.
+fn name_of(unit: Unit, value: Value) -> String {
+    match unit.get_name(value) {
+        Some(name) => name.to_string(),
+        None => value.dump(&unit).to_string()[1..].to_string(),
+    }
+}
+
+/// The symbol of a bit of a signal.
+fn bit_name(name: &str, index: usize, width: usize) -> String {
+    if width == 1 {
+        name.to_string()
+    } else {
+        format!("{}[{}]", name, index)
+    }
+}
+
+/// The bit width of a signal of integer type.
+fn signal_width(unit: Unit, signal: Value) -> Option<usize> {
+    match *unit.value_type(signal) {
+        TypeKind::SignalType(ref ty) => match **ty {
+            TypeKind::IntType(width) => Some(width),
+            _ => None,
+        },
+        _ => None,
+    }
+}
+
+/// The bits of a constant given as little-endian bytes.
+fn const_bits(bytes: &[u8], width: usize) -> Vec<Lit> {
+    (0..width)
+        .map(|index| match bytes.get(index / 8) {
+            Some(byte) if byte >> (index % 8) & 1 == 1 => TRUE,
+            _ => FALSE,
+        })
+        .collect()
+}
+
+/// Truncate or zero-extend a bit vector to a width. This keeps the export
+/// going after an issue has been reported for a value.
+fn resize(mut bits: Vec<Lit>, width: usize) -> Vec<Lit> {
+    bits.resize(width, FALSE);
+    bits
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn export_text(text: &str) -> Result<String, Vec<String>> {
+        let module = llhd::assembly::parse_module(text).unwrap();
+        export(&module).map_err(|issues| issues.iter().map(|i| i.to_string()).collect())
+    }
+
+    #[test]
+    fn and_gate() {
+        let aag = export_text(
+            "entity @top (i1$ %a, i1$ %b) -> (i1$ %z) {
+                %ap = prb i1$ %a
+                %bp = prb i1$ %b
+                %y = and i1 %ap, %bp
+                %t = const time 0s
+                drv i1$ %z, %y, %t
+            }",
+        );
+        assert_eq!(
+            aag.unwrap(),
+            "aag 3 2 0 1 1\n2\n4\n6\n6 4 2\ni0 a\ni1 b\no0 z\n"
+        );
+    }
+
+    #[test]
+    fn register_becomes_latch() {
+        let aag = export_text(
+            "entity @top (i1$ %clk, i2$ %d) -> (i2$ %q) {
+                %init = const i2 2
+                %s = sig i2 %init
+                %clkp = prb i1$ %clk
+                %dp = prb i2$ %d
+                reg i2$ %s, [%dp, rise %clkp]
+                %sp = prb i2$ %s
+                %t = const time 0s
+                drv i2$ %q, %sp, %t
+            }",
+        );
+        assert_eq!(
+            aag.unwrap(),
+            "aag 4 2 2 2 0\n6\n8\n2 6\n4 8 1\n2\n4\ni0 d[0]\ni1 d[1]\nl0 s[0]\nl1 s[1]\no0 q[0]\no1 q[1]\n"
+        );
+    }
+
+    #[test]
+    fn instances_are_inlined() {
+        let aag = export_text(
+            "entity @inv (i1$ %a) -> (i1$ %z) {
+                %ap = prb i1$ %a
+                %y = not i1 %ap
+                %t = const time 0s
+                drv i1$ %z, %y, %t
+            }
+            entity @top (i1$ %a) -> (i1$ %z) {
+                %init = const i1 0
+                %m = sig i1 %init
+                inst @inv (i1$ %a) -> (i1$ %m)
+                inst @inv (i1$ %m) -> (i1$ %z)
+            }",
+        );
+        assert_eq!(aag.unwrap(), "aag 1 1 0 1 0\n2\n2\ni0 a\no0 z\n");
+    }
+
+    #[test]
+    fn unsupported_constructs_are_collected() {
+        let issues = export_text(
+            "entity @top (i4$ %a, i1$ %clk, i1$ %clk2) -> (i4$ %z, i4$ %w) {
+                %ap = prb i4$ %a
+                %y = udiv i4 %ap, %ap
+                %t = const time 0s
+                drv i4$ %z, %y, %t
+                %clkp = prb i1$ %clk
+                %clk2p = prb i1$ %clk2
+                reg i4$ %w, [%ap, rise %clkp], [%ap, fall %clk2p]
+            }",
+        );
+        assert_eq!(
+            issues.unwrap_err(),
+            vec![
+                "@top: `udiv` instruction computing `%y`",
+                "@top: register `w` with triggers [rise, fall]",
+            ]
+        );
+    }
+
+    #[test]
+    fn several_tops() {
+        let issues = export_text(
+            "entity @a () -> () {
+            }
+            entity @b () -> () {
+            }",
+        );
+        assert_eq!(
+            issues.unwrap_err(),
+            vec![
+                "@a: is one of several top-level entities",
+                "@b: is one of several top-level entities",
+            ]
+        );
+    }
+}
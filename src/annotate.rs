@@ -0,0 +1,56 @@
+// Copyright (c) 2016-2020 Fabian Schuiki
+
+//! Annotation of generated LLHD assembly.
+//!
+//! LLHD has no metadata attached to its units, so information gathered during
+//! code generation or by later analyses is passed on to downstream tools as
+//! comments preceding the unit it relates to.
+
+use llhd::ir::{Module, UnitName};
+
+/// Render the LLHD assembly of a module, with each note emitted as a comment
+/// line before the unit it refers to.
+pub fn write_module(module: &Module, notes: &[(UnitName, String)]) -> String {
+    let asm = llhd::assembly::write_module_string(module);
+    let mut output = String::with_capacity(asm.len());
+    for line in asm.lines() {
+        if let Some(unit) = unit_header(line) {
+            for (name, note) in notes {
+                if name.to_string() == unit {
+                    output.push_str(&format!("; {}\n", note));
+                }
+            }
+        }
+        output.push_str(line);
+        output.push('\n');
+    }
+    output
+}
+
+/// Parse LLHD assembly as written by `write_module`, recovering the notes
+/// from the comment lines before each unit.
+pub fn read_module(text: &str) -> Result<(Module, Vec<(UnitName, String)>), String> {
+    let module = llhd::assembly::parse_module(text)?;
+    let mut notes = vec![];
+    let mut pending = vec![];
+    for line in text.lines() {
+        if line.starts_with("; ") {
+            pending.push(line[2..].to_string());
+        } else if let Some(unit) = unit_header(line) {
+            if let Some(unit) = module.units().find(|u| u.name().to_string() == unit) {
+                notes.extend(pending.drain(..).map(|note| (unit.name().clone(), note)));
+            }
+            pending.clear();
+        }
+    }
+    Ok((module, notes))
+}
+
+/// Extract the unit name from the first line of a unit's assembly.
+fn unit_header(line: &str) -> Option<&str> {
+    ["entity ", "proc ", "func "]
+        .iter()
+        .find(|kw| line.starts_with(*kw))
+        .map(|kw| &line[kw.len()..])
+        .and_then(|rest| rest.split(' ').next())
+}
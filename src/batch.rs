@@ -0,0 +1,108 @@
+// Copyright (c) 2016-2020 Fabian Schuiki
+
+//! Parallel elaboration of several top-level units.
+//!
+//! The compiler keeps its state in thread-local tables and single-threaded
+//! scoreboards, such that one compilation cannot be shared among threads.
+//! Batch mode therefore elaborates each of the units given with `-e` in a
+//! separate process with otherwise unchanged arguments, running up to a given
+//! number of processes at once. The outputs are collected and reported in the
+//! order in which the units were given, such that the result does not depend
+//! on which process finishes first.
+
+use std::collections::VecDeque;
+use std::ffi::OsString;
+use std::path::PathBuf;
+use std::process::{Command, Output};
+use std::sync::{mpsc, Arc, Mutex};
+
+/// The options removed from the arguments passed on to each process, given as
+/// their short and long form.
+const BATCH_OPTIONS: &[(&str, &str)] = &[("-e", "--elaborate"), ("-j", "--jobs")];
+
+/// Remove the units to elaborate and the number of jobs from a list of
+/// command-line arguments.
+///
+/// Handles both the separate (`-e top`, `--elaborate top`) and the attached
+/// (`-etop`, `--elaborate=top`) form of the options.
+pub fn strip_batch_args(args: impl IntoIterator<Item = OsString>) -> Vec<OsString> {
+    let mut result = vec![];
+    let mut args = args.into_iter();
+    while let Some(arg) = args.next() {
+        let kind = arg.to_str().and_then(|arg| {
+            BATCH_OPTIONS.iter().find_map(|&(short, long)| {
+                if arg == short || arg == long {
+                    Some(true)
+                } else if (arg.starts_with(short) && !arg.starts_with("--"))
+                    || arg.starts_with(&format!("{}=", long))
+                {
+                    Some(false)
+                } else {
+                    None
+                }
+            })
+        });
+        match kind {
+            // The value follows as a separate argument.
+            Some(true) => {
+                args.next();
+            }
+            // The value is attached to the option.
+            Some(false) => (),
+            None => result.push(arg),
+        }
+    }
+    result
+}
+
+/// Elaborate each of a list of units in a separate compiler process.
+///
+/// Runs `exe` with `args` and `-e UNIT` for every unit, at most `jobs` at a
+/// time. Returns the output of each process, in the order of `units`.
+pub fn run(
+    exe: PathBuf,
+    args: Vec<OsString>,
+    units: Vec<String>,
+    jobs: usize,
+) -> Vec<std::io::Result<Output>> {
+    let num_units = units.len();
+    let queue = Arc::new(Mutex::new(
+        units.into_iter().enumerate().collect::<VecDeque<_>>(),
+    ));
+    let args = Arc::new(args);
+    let (tx, rx) = mpsc::channel();
+    let workers: Vec<_> = (0..jobs.max(1).min(num_units))
+        .map(|_| {
+            let exe = exe.clone();
+            let args = args.clone();
+            let queue = queue.clone();
+            let tx = tx.clone();
+            std::thread::spawn(move || loop {
+                let next = queue.lock().unwrap().pop_front();
+                let (index, unit) = match next {
+                    Some(x) => x,
+                    None => break,
+                };
+                let out = Command::new(&exe)
+                    .args(args.iter())
+                    .arg("-e")
+                    .arg(&unit)
+                    .output();
+                if tx.send((index, out)).is_err() {
+                    break;
+                }
+            })
+        })
+        .collect();
+    drop(tx);
+
+    // Put the outputs back into the order of the units.
+    let mut outputs: Vec<_> = (0..num_units).map(|_| None).collect();
+    for (index, out) in rx {
+        outputs[index] = Some(out);
+    }
+    for worker in workers {
+        worker.join().unwrap();
+    }
+    outputs.into_iter().map(Option::unwrap).collect()
+}
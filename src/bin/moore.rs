@@ -5,23 +5,21 @@
 #[macro_use]
 extern crate log;
 
-use clap::{App, Arg, ArgMatches};
+use clap::{App, AppSettings, Arg, ArgMatches, SubCommand};
 use llhd;
 use llhd::opt::{Pass, PassContext};
-use moore::common::score::NodeRef;
+use moore::common::score::{CancelToken, Error, NodeRef};
 use moore::errors::*;
+use moore::inputs::{self, Language};
 use moore::name::Name;
 use moore::score::{ScoreBoard, ScoreContext};
+use moore::select;
+use moore::stdlib;
 use moore::svlog::{hir::Visitor as _, QueryDatabase as _};
 use moore::*;
-use std::path::Path;
-
-#[derive(Debug)]
-enum Language {
-    Verilog,
-    SystemVerilog,
-    Vhdl,
-}
+use std::collections::HashSet;
+use std::path::{Path, PathBuf};
+use std::time::Duration;
 
 fn main() {
     // Configure the logger.
@@ -35,6 +33,19 @@ fn main() {
     );
     builder.try_init().unwrap();
 
+    // Extract the `+libext+` arguments, which do not follow the usual option
+    // syntax, before parsing the remaining command-line arguments.
+    let mut libexts = vec![];
+    let args =
+        std::env::args_os().filter(|arg| match arg.to_str().and_then(libdirs::parse_libext) {
+            Some(exts) => {
+                libexts.extend(exts);
+                false
+            }
+            None => true,
+        });
+    let args: Vec<_> = args.collect();
+
     // Parse the command-line arguments.
     let matches = App::new(env!("CARGO_PKG_NAME"))
         .version(clap::crate_version!())
@@ -45,6 +56,30 @@ fn main() {
                 .long("trace-scoreboard")
                 .global(true),
         )
+        .arg(
+            Arg::with_name("trace-passes")
+                .long("trace-passes")
+                .value_name("FILTER")
+                .help("Trace the lazy VHDL passes, e.g. `hir,typeck:SignalDecl`")
+                .takes_value(true)
+                .number_of_values(1)
+                .global(true),
+        )
+        .arg(
+            Arg::with_name("trace-chrome")
+                .long("trace-chrome")
+                .value_name("FILE")
+                .help("Write the trace of the lazy VHDL passes to FILE in Chrome trace format")
+                .takes_value(true)
+                .number_of_values(1)
+                .global(true),
+        )
+        .arg(
+            Arg::with_name("audit-lazy")
+                .long("audit-lazy")
+                .help("Report VHDL type checks that were scheduled but never run")
+                .global(true),
+        )
         .arg(
             Arg::with_name("verbosity-opts")
                 .short("V")
@@ -72,6 +107,19 @@ fn main() {
                 .help("Add a search path for SystemVerilog includes")
                 .multiple(true)
                 .takes_value(true)
+                .number_of_values(1)
+                .global(true),
+        )
+        .arg(
+            Arg::with_name("libdir")
+                .short("y")
+                .value_name("DIR")
+                .help(
+                    "Search DIR for files named after modules that are instantiated but not \
+                     defined, with the extensions given as `+libext+.v+.sv`",
+                )
+                .multiple(true)
+                .takes_value(true)
                 .number_of_values(1),
         )
         .arg(
@@ -81,7 +129,8 @@ fn main() {
                 .help("Define a preprocesor macro")
                 .multiple(true)
                 .takes_value(true)
-                .number_of_values(1),
+                .number_of_values(1)
+                .global(true),
         )
         .arg(
             Arg::with_name("preproc")
@@ -112,6 +161,152 @@ fn main() {
                 .takes_value(true)
                 .number_of_values(1),
         )
+        .arg(
+            Arg::with_name("llhd-passes")
+                .long("llhd-passes")
+                .value_name("PASSES")
+                .help(
+                    "Run the comma-separated LLHD passes on the output instead of those implied \
+                     by the optimization level, e.g. `cf,dce,cse`",
+                )
+                .takes_value(true)
+                .number_of_values(1),
+        )
+        .arg(
+            Arg::with_name("warnings-as-errors")
+                .long("warnings-as-errors")
+                .help("Treat all warnings as errors")
+                .global(true),
+        )
+        .arg(
+            Arg::with_name("max-errors")
+                .long("max-errors")
+                .value_name("N")
+                .help("Abort after N errors have been emitted")
+                .takes_value(true)
+                .number_of_values(1)
+                .global(true),
+        )
+        .arg(
+            Arg::with_name("encoding")
+                .long("encoding")
+                .value_name("ENC")
+                .help("Read source files as ENC [default: auto]")
+                .takes_value(true)
+                .number_of_values(1)
+                .possible_values(&["auto", "utf-8", "latin-1"])
+                .global(true),
+        )
+        .arg(
+            Arg::with_name("size-report")
+                .long("size-report")
+                .help("Print the size of the generated code per unit to stderr"),
+        )
+        .arg(
+            Arg::with_name("infer-memories")
+                .long("infer-memories")
+                .help("Annotate arrays that can be mapped to memories in the generated code"),
+        )
+        .arg(
+            Arg::with_name("debug-info")
+                .short("g")
+                .long("debug-info")
+                .help("Annotate the generated code with the source location of each unit"),
+        )
+        .arg(
+            Arg::with_name("emit")
+                .long("emit")
+                .value_name("FORMAT")
+                .help("Format of the generated code")
+                .takes_value(true)
+                .number_of_values(1)
+                .possible_values(&["llhd", "smt", "aiger", "c-header", "rust"])
+                .requires_if("smt", "cone"),
+        )
+        .arg(
+            Arg::with_name("cone")
+                .long("cone")
+                .value_name("SIGNAL")
+                .help("Output or signal whose combinational cone to emit with `--emit smt`")
+                .takes_value(true)
+                .number_of_values(1),
+        )
+        .arg(
+            Arg::with_name("save-snapshot")
+                .long("save-snapshot")
+                .value_name("FILE")
+                .help("Save the elaborated design to a snapshot file")
+                .takes_value(true)
+                .number_of_values(1),
+        )
+        .arg(
+            Arg::with_name("load-snapshot")
+                .long("load-snapshot")
+                .value_name("FILE")
+                .help("Emit a design saved with `--save-snapshot` instead of compiling inputs")
+                .takes_value(true)
+                .number_of_values(1)
+                .conflicts_with_all(&["INPUT", "save-snapshot"]),
+        )
+        .arg(
+            Arg::with_name("two-state")
+                .long("two-state")
+                .help("Lower std_logic and logic to two-state bits for synthesis"),
+        )
+        .arg(
+            Arg::with_name("x-policy")
+                .long("x-policy")
+                .value_name("POLICY")
+                .help("How `--two-state` maps unknown and high-impedance values")
+                .takes_value(true)
+                .number_of_values(1)
+                .possible_values(&["zero", "one", "error"])
+                .requires("two-state"),
+        )
+        .arg(
+            Arg::with_name("severity")
+                .long("severity")
+                .value_name("FROM=TO")
+                .help("Treat messages of the design with severity FROM as TO, e.g. `warning=error`")
+                .multiple(true)
+                .takes_value(true)
+                .number_of_values(1)
+                .global(true),
+        )
+        .arg(
+            Arg::with_name("stop-on")
+                .long("stop-on")
+                .value_name("SEVERITY[:N]")
+                .help("Stop after N messages of the design with SEVERITY or worse [default: failure:1]")
+                .takes_value(true)
+                .number_of_values(1)
+                .global(true),
+        )
+        .arg(
+            Arg::with_name("seed")
+                .long("seed")
+                .value_name("N")
+                .help("Seed the random number generators of the simulation with N [default: 0]")
+                .takes_value(true)
+                .number_of_values(1)
+                .global(true),
+        )
+        .arg(
+            Arg::with_name("permissive-connections")
+                .long("permissive-connections")
+                .help("Truncate or extend signals connected to ports of a different width, with a warning")
+                .global(true),
+        )
+        .arg(
+            Arg::with_name("scheduling")
+                .long("scheduling")
+                .value_name("MODE")
+                .help("Order signal updates across languages as MODE [default: native]")
+                .takes_value(true)
+                .number_of_values(1)
+                .possible_values(&["native", "delta", "stratified"])
+                .global(true),
+        )
         .arg(
             Arg::with_name("lib")
                 .short("l")
@@ -121,6 +316,41 @@ fn main() {
                 .takes_value(true)
                 .number_of_values(1),
         )
+        .arg(
+            Arg::with_name("vhdl-std")
+                .long("vhdl-std")
+                .value_name("YEAR")
+                .help("Revision of the VHDL standard to compile against")
+                .takes_value(true)
+                .number_of_values(1)
+                .possible_values(&[
+                    "87", "93", "00", "02", "08", "1987", "1993", "2000", "2002", "2008",
+                ]),
+        )
+        .arg(
+            Arg::with_name("ieee-path")
+                .long("ieee-path")
+                .value_name("DIR")
+                .help("Compile the `ieee` library from the sources in DIR")
+                .takes_value(true)
+                .number_of_values(1),
+        )
+        .arg(
+            Arg::with_name("std-path")
+                .long("std-path")
+                .value_name("DIR")
+                .help("Compile the `std` library from the sources in DIR")
+                .takes_value(true)
+                .number_of_values(1),
+        )
+        .arg(
+            Arg::with_name("unit")
+                .long("unit")
+                .value_name("UNIT")
+                .help("Only process UNIT and the units it depends on")
+                .takes_value(true)
+                .number_of_values(1),
+        )
         .arg(
             Arg::with_name("elaborate")
                 .short("e")
@@ -131,13 +361,237 @@ fn main() {
                 .takes_value(true)
                 .number_of_values(1),
         )
+        .arg(
+            Arg::with_name("jobs")
+                .short("j")
+                .long("jobs")
+                .value_name("N")
+                .help("Elaborate up to N of the entities given with `-e` in parallel")
+                .takes_value(true)
+                .number_of_values(1),
+        )
+        .arg(
+            Arg::with_name("keep-going")
+                .long("keep-going")
+                .help("Emit the units that were generated successfully and list the ones that failed"),
+        )
+        .arg(
+            Arg::with_name("timeout")
+                .long("timeout")
+                .value_name("SECONDS")
+                .help("Abort elaboration if it takes longer than SECONDS")
+                .takes_value(true)
+                .number_of_values(1),
+        )
+        .arg(
+            Arg::with_name("watch")
+                .long("watch")
+                .help("Recompile whenever an input changes, showing only new diagnostics"),
+        )
+        .arg(
+            Arg::with_name("param")
+                .short("P")
+                .long("param")
+                .value_name("NAME=VALUES")
+                .help(
+                    "Override a parameter or generic of the elaborated unit; comma-separated \
+                     values sweep over all combinations",
+                )
+                .multiple(true)
+                .takes_value(true)
+                .number_of_values(1),
+        )
+        .arg(
+            Arg::with_name("lang-map")
+                .long("lang-map")
+                .value_name("EXT=LANG")
+                .help("Map a file extension to a language (vhdl, sv, or v)")
+                .multiple(true)
+                .takes_value(true)
+                .number_of_values(1)
+                .global(true),
+        )
+        .arg(
+            Arg::with_name("manifest")
+                .long("manifest")
+                .value_name("FILE")
+                .help("Compile the libraries listed in a manifest, each with its own includes and defines")
+                .takes_value(true)
+                .number_of_values(1)
+                .global(true),
+        )
         .arg(
             Arg::with_name("INPUT")
-                .help("The input files to compile")
+                .help("The input files, directories, or glob patterns to compile")
                 .multiple(true)
-                .required(true),
+                .required_unless_one(&["load-snapshot", "manifest"]),
+        )
+        .setting(AppSettings::SubcommandsNegateReqs)
+        .subcommand(
+            SubCommand::with_name("query")
+                .about("Query the design for instances, drivers, users, and signals; prints JSON")
+                .arg(
+                    Arg::with_name("KIND")
+                        .help("The kind of query to run")
+                        .possible_values(QUERY_KINDS)
+                        .required(true),
+                )
+                .arg(
+                    Arg::with_name("NAME")
+                        .help(
+                            "The entity, module, process, or package to query for, or a \
+                             hierarchical signal path such as `top.*.state`",
+                        )
+                        .required(true),
+                )
+                .arg(
+                    Arg::with_name("INPUT")
+                        .help("The input files to query")
+                        .multiple(true)
+                        .required(true),
+                ),
         )
-        .get_matches();
+        .subcommand(
+            SubCommand::with_name("reduce")
+                .about("Minimize inputs that trigger a compiler bug into a small reproducer")
+                .arg(
+                    Arg::with_name("match")
+                        .short("m")
+                        .long("match")
+                        .value_name("TEXT")
+                        .help("Text in the compiler output that identifies the bug")
+                        .takes_value(true)
+                        .required(true),
+                )
+                .arg(
+                    Arg::with_name("output")
+                        .short("o")
+                        .long("output")
+                        .value_name("DIR")
+                        .help("Directory to write the reduced inputs to")
+                        .takes_value(true)
+                        .default_value("reduced"),
+                )
+                .arg(
+                    Arg::with_name("INPUT")
+                        .help("The input files to reduce")
+                        .multiple(true)
+                        .required(true),
+                )
+                .arg(
+                    Arg::with_name("ARGS")
+                        .help("Additional arguments to pass to the compiler, e.g. `-e top`")
+                        .multiple(true)
+                        .last(true),
+                ),
+        )
+        .subcommand(
+            SubCommand::with_name("conformance")
+                .about("Run an external conformance test suite and compare against a baseline")
+                .arg(
+                    Arg::with_name("baseline")
+                        .short("b")
+                        .long("baseline")
+                        .value_name("FILE")
+                        .help("Compare the results against the baseline in FILE")
+                        .takes_value(true),
+                )
+                .arg(
+                    Arg::with_name("update-baseline")
+                        .long("update-baseline")
+                        .help("Write the results to the baseline file")
+                        .requires("baseline"),
+                )
+                .arg(
+                    Arg::with_name("MANIFEST")
+                        .help("The manifest listing the tests and their expected outcome")
+                        .required(true),
+                )
+                .arg(
+                    Arg::with_name("ARGS")
+                        .help("Additional arguments to pass to the compiler for every test")
+                        .multiple(true)
+                        .last(true),
+                ),
+        )
+        .subcommand(
+            SubCommand::with_name("diff")
+                .about("Compare the instances, ports, and processes of two design snapshots")
+                .arg(
+                    Arg::with_name("OLD")
+                        .help("The snapshot saved with `--save-snapshot` before the change")
+                        .required(true),
+                )
+                .arg(
+                    Arg::with_name("NEW")
+                        .help("The snapshot saved with `--save-snapshot` after the change")
+                        .required(true),
+                ),
+        )
+        .subcommand(
+            SubCommand::with_name("wrapper")
+                .about("Generate a wrapper that instantiates an entity or module")
+                .args(&unit_generator_args()),
+        )
+        .subcommand(
+            SubCommand::with_name("testbench")
+                .about("Generate a skeleton testbench for an entity or module")
+                .args(&unit_generator_args()),
+        )
+        .subcommand(
+            SubCommand::with_name("interface")
+                .about(
+                    "Print the generics and ports of an entity or module as JSON, with each \
+                     port classified as clock, reset, or data",
+                )
+                .arg(
+                    Arg::with_name("NAME")
+                        .help("The entity or module, e.g. `work.fifo`")
+                        .required(true),
+                )
+                .arg(
+                    Arg::with_name("INPUT")
+                        .help("The input files declaring and implementing the entity or module")
+                        .multiple(true)
+                        .required(true),
+                ),
+        )
+        .subcommand(
+            SubCommand::with_name("dpi-header")
+                .about("Generate a C header for DPI imports, exports, and foreign subprograms")
+                .arg(
+                    Arg::with_name("guard")
+                        .long("guard")
+                        .value_name("NAME")
+                        .help("Name of the include guard")
+                        .takes_value(true)
+                        .default_value("MOORE_DPI_H"),
+                )
+                .arg(
+                    Arg::with_name("INPUT")
+                        .help("The input files declaring the subprograms")
+                        .multiple(true)
+                        .required(true),
+                ),
+        )
+        .subcommand(
+            SubCommand::with_name("serve")
+                .about("Keep the design in memory and compile it on request; speaks JSON-RPC")
+                .arg(
+                    Arg::with_name("listen")
+                        .long("listen")
+                        .value_name("ADDR")
+                        .help("Address to listen on for requests")
+                        .takes_value(true)
+                        .default_value("127.0.0.1:7878"),
+                )
+                .arg(
+                    Arg::with_name("INPUT")
+                        .help("The input files to compile until a request replaces them")
+                        .multiple(true),
+                ),
+        )
+        .get_matches_from(args);
 
     // Configure the session.
     let mut session = Session::new();
@@ -147,88 +601,1809 @@ fn main() {
         .into_iter()
         .flat_map(|v| v)
     {
-        session.opts.verbosity |= match v {
-            "types" => Verbosity::TYPES,
-            "expr-types" => Verbosity::EXPR_TYPES,
-            "type-contexts" => Verbosity::TYPE_CONTEXTS,
-            "typeck" => Verbosity::TYPECK,
-            "names" => Verbosity::NAMES,
-            "casts" => Verbosity::CASTS,
-            "ports" => Verbosity::PORTS,
-            "consts" => Verbosity::CONSTS,
-            "insts" => Verbosity::INSTS,
-            _ => unreachable!(),
+        session.opts.verbosity |= match v {
+            "types" => Verbosity::TYPES,
+            "expr-types" => Verbosity::EXPR_TYPES,
+            "type-contexts" => Verbosity::TYPE_CONTEXTS,
+            "typeck" => Verbosity::TYPECK,
+            "names" => Verbosity::NAMES,
+            "casts" => Verbosity::CASTS,
+            "ports" => Verbosity::PORTS,
+            "consts" => Verbosity::CONSTS,
+            "insts" => Verbosity::INSTS,
+            _ => unreachable!(),
+        };
+    }
+    session.opts.opt_level = matches.value_of("opt-level").unwrap().parse().unwrap();
+    if let Some(pipeline) = matches.value_of("llhd-passes") {
+        session.opts.llhd_passes = match LlhdPass::parse_pipeline(pipeline) {
+            Ok(passes) => Some(passes),
+            Err(name) => {
+                let names: Vec<_> = LlhdPass::ALL.iter().map(|&(n, _)| n).collect();
+                session.emit(
+                    DiagBuilder2::fatal(format!("unknown LLHD pass `{}`", name))
+                        .add_note(format!("Available passes are {}", names.join(", "))),
+                );
+                std::process::exit(1);
+            }
+        };
+    }
+    session.opts.size_report = matches.is_present("size-report");
+    session.opts.infer_memories = matches.is_present("infer-memories");
+    session.opts.debug_info = matches.is_present("debug-info");
+    session.opts.emit = match matches.value_of("emit") {
+        Some("smt") => EmitFormat::Smt,
+        Some("aiger") => EmitFormat::Aiger,
+        Some("c-header") => EmitFormat::CHeader,
+        Some("rust") => EmitFormat::Rust,
+        _ => EmitFormat::Llhd,
+    };
+    session.opts.cone = matches.value_of("cone").map(String::from);
+    session.opts.save_snapshot = matches.value_of("save-snapshot").map(String::from);
+    if matches.is_present("two-state") {
+        let policy = matches.value_of("x-policy").unwrap_or("zero");
+        session.opts.two_state = XPolicy::from_name(policy);
+    }
+    session.opts.warnings_as_errors = matches.is_present("warnings-as-errors");
+    if let Some(std) = matches.value_of("vhdl-std") {
+        session.opts.vhdl_std = VhdlStandard::from_year(std).unwrap();
+    }
+    for mapping in matches.values_of("severity").into_iter().flatten() {
+        let mut parts = mapping.splitn(2, '=').map(MessageSeverity::from_name);
+        match (parts.next(), parts.next()) {
+            (Some(Some(from)), Some(Some(to))) => session.opts.severity_map.push((from, to)),
+            _ => {
+                session.emit(
+                    DiagBuilder2::fatal(format!("invalid severity mapping `{}`", mapping))
+                        .add_note("Expected `FROM=TO`, e.g. `warning=error`"),
+                );
+                std::process::exit(1);
+            }
+        }
+    }
+    if let Some(stop_on) = matches.value_of("stop-on") {
+        let mut parts = stop_on.splitn(2, ':');
+        let severity = parts.next().and_then(MessageSeverity::from_name);
+        let count = parts.next().map(|s| s.parse()).unwrap_or(Ok(1));
+        session.opts.stop_on = match (severity, count) {
+            (Some(severity), Ok(count)) if count > 0 => Some((severity, count)),
+            _ => {
+                session.emit(
+                    DiagBuilder2::fatal(format!("invalid `--stop-on` value `{}`", stop_on))
+                        .add_note("Expected a severity and optional count, e.g. `error:10`"),
+                );
+                std::process::exit(1);
+            }
+        };
+    }
+    if let Some(seed) = matches.value_of("seed") {
+        session.opts.seed = match seed.parse() {
+            Ok(seed) => seed,
+            Err(_) => {
+                session.emit(DiagBuilder2::fatal(format!(
+                    "invalid `--seed` value `{}`; expected a 32 bit unsigned number",
+                    seed
+                )));
+                std::process::exit(1);
+            }
+        };
+    }
+    session.opts.permissive_connections = matches.is_present("permissive-connections");
+    session.opts.keep_going = matches.is_present("keep-going");
+    if let Some(mode) = matches.value_of("scheduling") {
+        session.opts.scheduling = Scheduling::from_name(mode).unwrap();
+    }
+    session.opts.max_errors = match matches.value_of("max-errors").map(|s| s.parse()) {
+        Some(Ok(0)) | None => None,
+        Some(Ok(n)) => Some(n),
+        Some(Err(_)) => {
+            session.emit(DiagBuilder2::fatal("`--max-errors` expects a number"));
+            std::process::exit(1);
+        }
+    };
+
+    if let Some(encoding) = matches.value_of("encoding") {
+        source::get_source_manager().set_encoding(source::Encoding::from_name(encoding).unwrap());
+    }
+
+    // Run a design query if requested.
+    if let Some(matches) = matches.subcommand_matches("query") {
+        query(&session, matches);
+    } else if let Some(matches) = matches.subcommand_matches("reduce") {
+        reduce(&session, matches);
+    } else if let Some(matches) = matches.subcommand_matches("conformance") {
+        conformance(&session, matches);
+    } else if let Some(matches) = matches.subcommand_matches("diff") {
+        diff(&session, matches);
+    } else if let Some(matches) = matches.subcommand_matches("wrapper") {
+        generate_unit(&session, matches, false);
+    } else if let Some(matches) = matches.subcommand_matches("testbench") {
+        generate_unit(&session, matches, true);
+    } else if let Some(matches) = matches.subcommand_matches("interface") {
+        interface(&session, matches);
+    } else if let Some(matches) = matches.subcommand_matches("dpi-header") {
+        dpi_header(&session, matches);
+    } else if let Some(matches) = matches.subcommand_matches("serve") {
+        serve(&session, matches);
+    } else if let Some(path) = matches.value_of("load-snapshot") {
+        load_snapshot(&session, path);
+    } else if matches.is_present("watch") {
+        watch(&session, &matches, libexts);
+    } else if matches.is_present("jobs") && matches.is_present("elaborate") {
+        batch(&session, &matches);
+    } else {
+        // Invoke the compiler.
+        score(&session, &matches, libexts);
+    }
+    exit(&session, 0);
+}
+
+/// Print a summary of the emitted diagnostics and exit.
+fn exit(sess: &Session, code: i32) -> ! {
+    sess.flush();
+    if sess.error_limit_reached() {
+        eprintln!(
+            "{}",
+            DiagBuilder2::fatal(format!("aborting after {} errors", sess.num_errors()))
+                .add_note("Use `--max-errors` to change this limit")
+        );
+    }
+    if let Some(summary) = sess.summary() {
+        eprintln!("{} emitted", summary);
+    }
+    std::process::exit(if sess.failed() { 1 } else { code });
+}
+
+/// The kinds of where-used queries.
+const QUERY_KINDS: &[&str] = &["instances", "drivers", "users", "signals"];
+
+/// Run a where-used query over the input files and print the results as JSON.
+fn query(sess: &Session, matches: &ArgMatches) {
+    let svlog_arenas = svlog::GlobalArenas::default();
+    let kind = matches.value_of("KIND").unwrap();
+    let name = matches.value_of("NAME").unwrap();
+    let (vhdl_units, svlog_files) = parse_syntax(sess, matches, &svlog_arenas.ast);
+    let results = match run_query(kind, name, &vhdl_units, &svlog_files) {
+        Ok(x) => x,
+        Err(msg) => {
+            sess.emit(DiagBuilder2::error(msg));
+            exit(sess, 1);
+        }
+    };
+
+    // Print the results.
+    println!("[");
+    for (i, (unit, name, span)) in results.iter().enumerate() {
+        let begin = span.begin();
+        println!(
+            "  {{\"unit\": {}, \"name\": {}, \"file\": {}, \"line\": {}, \"column\": {}}}{}",
+            json_string(&unit.as_str()),
+            name.map(|n| json_string(&n.as_str()))
+                .unwrap_or_else(|| "null".to_string()),
+            json_string(&span.source.get_path()),
+            begin.human_line(),
+            begin.human_column(),
+            if i + 1 < results.len() { "," } else { "" }
+        );
+    }
+    println!("]");
+}
+
+/// Run a where-used query of one of the `QUERY_KINDS`.
+///
+/// VHDL names are case-insensitive, SystemVerilog names are not. Fails if a
+/// signal path cannot be resolved on the syntax alone.
+fn run_query<'a>(
+    kind: &str,
+    name: &str,
+    vhdl_units: &[vhdl::syntax::ast::DesignUnit],
+    svlog_files: impl IntoIterator<Item = &'a svlog::ast::SourceFile<'a>>,
+) -> Result<Vec<(Name, Option<Name>, source::Span)>, String> {
+    use crate::name::get_name_table;
+    let vhdl_name = get_name_table().intern(name, false);
+    let svlog_name = get_name_table().intern(name, true);
+    let mut results = vec![];
+    match kind {
+        "instances" => {
+            for m in vhdl::syntax::query::instances_of(vhdl_units, vhdl_name) {
+                results.push((m.unit, m.name, m.span));
+            }
+            for m in svlog::query::instances_of(svlog_files, svlog_name) {
+                results.push((m.unit, m.name, m.span));
+            }
+        }
+        "drivers" => {
+            for m in vhdl::syntax::query::drivers_of(vhdl_units, vhdl_name) {
+                results.push((m.unit, m.name, m.span));
+            }
+            for m in svlog::query::drivers_of(svlog_files, svlog_name) {
+                results.push((m.unit, m.name, m.span));
+            }
+        }
+        "users" => {
+            for m in vhdl::syntax::query::users_of(vhdl_units, vhdl_name) {
+                results.push((m.unit, m.name, m.span));
+            }
+            for m in svlog::query::users_of(svlog_files, svlog_name) {
+                results.push((m.unit, m.name, m.span));
+            }
+        }
+        "signals" => {
+            for m in vhdl::syntax::query::signals_at(vhdl_units, name)? {
+                results.push((m.unit, m.name, m.span));
+            }
+            for m in svlog::query::signals_at(svlog_files, name)? {
+                results.push((m.unit, m.name, m.span));
+            }
+        }
+        _ => unreachable!(),
+    }
+    Ok(results)
+}
+
+/// Parse the input files of a subcommand that only needs the syntax trees,
+/// together with the libraries listed in the manifest, exiting on failure.
+fn parse_syntax<'a>(
+    sess: &Session,
+    matches: &ArgMatches,
+    arena: &'a svlog::ast::Arena<'a>,
+) -> (
+    Vec<vhdl::syntax::ast::DesignUnit>,
+    Vec<svlog::ast::SourceFile<'a>>,
+) {
+    let include_paths = include_paths(matches);
+    let defines = defines(matches);
+    let mut failed = false;
+    let mut asts = parse_files(
+        sess,
+        matches,
+        input_files(sess, matches),
+        &include_paths,
+        &defines,
+        arena,
+    )
+    .unwrap_or_else(|()| {
+        failed = true;
+        vec![]
+    });
+    for library in read_manifest(sess, matches) {
+        let (lib_include_paths, lib_defines) = library_options(&library, &include_paths, &defines);
+        let files = expand_files(sess, matches, library.files.iter().map(String::as_str));
+        match parse_files(
+            sess,
+            matches,
+            files,
+            &lib_include_paths,
+            &lib_defines,
+            arena,
+        ) {
+            Ok(x) => asts.extend(x),
+            Err(()) => failed = true,
+        }
+    }
+    if failed || sess.failed() {
+        exit(sess, 1);
+    }
+    let mut vhdl_units = Vec::new();
+    let mut svlog_files = Vec::new();
+    for ast in asts {
+        match ast {
+            score::Ast::Vhdl(x) => vhdl_units.extend(x),
+            score::Ast::Svlog(x) => svlog_files.push(x),
+        }
+    }
+    (vhdl_units, svlog_files)
+}
+
+/// The arguments of the `wrapper` and `testbench` subcommands.
+fn unit_generator_args() -> Vec<Arg<'static, 'static>> {
+    vec![
+        Arg::with_name("lang")
+            .long("lang")
+            .value_name("LANG")
+            .help("Language of the generated code; defaults to that of the unit")
+            .takes_value(true)
+            .possible_values(&["vhdl", "sv"]),
+        Arg::with_name("NAME")
+            .help("The entity or module, e.g. `work.fifo`")
+            .required(true),
+        Arg::with_name("INPUT")
+            .help("The input files declaring the entity or module")
+            .multiple(true)
+            .required(true),
+    ]
+}
+
+/// Generate a wrapper or a testbench for an entity or module.
+fn generate_unit(sess: &Session, matches: &ArgMatches, testbench: bool) {
+    let svlog_arenas = svlog::GlobalArenas::default();
+    let (vhdl_units, svlog_files) = parse_syntax(sess, matches, &svlog_arenas.ast);
+
+    // Drop the library of names such as `work.fifo`.
+    let name = matches.value_of("NAME").unwrap();
+    let name = name.rsplit('.').next().unwrap();
+    let intf = match moore::wrapper::find_interface(&vhdl_units, &svlog_files, name) {
+        Some(intf) => intf,
+        None => {
+            sess.emit(DiagBuilder2::error(format!(
+                "no entity or module named `{}`",
+                name
+            )));
+            exit(sess, 1);
+        }
+    };
+    let lang = match matches.value_of("lang") {
+        Some(lang) => Language::from_name(lang).unwrap(),
+        None => intf.lang,
+    };
+    let errors = moore::wrapper::bridge_errors(&intf, lang);
+    for msg in &errors {
+        sess.emit(DiagBuilder2::error(msg.clone()));
+    }
+    if !errors.is_empty() {
+        exit(sess, 1);
+    }
+    if testbench {
+        print!("{}", moore::wrapper::render_testbench(&intf, lang));
+    } else {
+        print!("{}", moore::wrapper::render(&intf, lang));
+    }
+}
+
+/// Print the interface of an entity or module as JSON.
+fn interface(sess: &Session, matches: &ArgMatches) {
+    use moore::signal_kind;
+    use moore::wrapper::PortDir;
+    let svlog_arenas = svlog::GlobalArenas::default();
+    let (vhdl_units, svlog_files) = parse_syntax(sess, matches, &svlog_arenas.ast);
+    let name = matches.value_of("NAME").unwrap();
+    let name = name.rsplit('.').next().unwrap();
+    let intf = match moore::wrapper::find_interface(&vhdl_units, &svlog_files, name) {
+        Some(intf) => intf,
+        None => {
+            sess.emit(DiagBuilder2::error(format!(
+                "no entity or module named `{}`",
+                name
+            )));
+            exit(sess, 1);
+        }
+    };
+    let kinds = signal_kind::infer(&vhdl_units, &svlog_files, &intf);
+    let type_name = |ty| json_string(&moore::wrapper::type_name(ty, intf.lang));
+
+    println!("{{");
+    println!("  \"name\": {},", json_string(&intf.name));
+    println!(
+        "  \"language\": {},",
+        json_string(match intf.lang {
+            Language::Vhdl => "vhdl",
+            Language::Verilog => "verilog",
+            Language::SystemVerilog => "systemverilog",
+        })
+    );
+    println!("  \"generics\": [");
+    for (i, g) in intf.generics.iter().enumerate() {
+        println!(
+            "    {{\"name\": {}, \"type\": {}, \"default\": {}}}{}",
+            json_string(&g.name),
+            type_name(&g.ty),
+            g.default
+                .as_ref()
+                .map(|d| json_string(d))
+                .unwrap_or_else(|| "null".to_string()),
+            if i + 1 < intf.generics.len() { "," } else { "" }
+        );
+    }
+    println!("  ],");
+    println!("  \"ports\": [");
+    for (i, (p, kind)) in intf.ports.iter().zip(kinds).enumerate() {
+        println!(
+            "    {{\"name\": {}, \"direction\": {}, \"type\": {}, \"kind\": {}}}{}",
+            json_string(&p.name),
+            json_string(match p.dir {
+                PortDir::In => "in",
+                PortDir::Out => "out",
+                PortDir::Inout => "inout",
+            }),
+            type_name(&p.ty),
+            json_string(&kind.to_string()),
+            if i + 1 < intf.ports.len() { "," } else { "" }
+        );
+    }
+    println!("  ]");
+    println!("}}");
+}
+
+/// Generate a C header for the foreign subprograms in the input files.
+fn dpi_header(sess: &Session, matches: &ArgMatches) {
+    let svlog_arenas = svlog::GlobalArenas::default();
+    let (vhdl_units, svlog_files) = parse_syntax(sess, matches, &svlog_arenas.ast);
+    let mut protos = moore::c_header::svlog_prototypes(&svlog_files);
+    let dpi = !protos.is_empty();
+    protos.extend(moore::c_header::vhdl_prototypes(&vhdl_units));
+    for proto in &protos {
+        if let Some(ty) = proto.unmapped() {
+            sess.emit(DiagBuilder2::warning(format!(
+                "`{}` omitted from header; type `{}` has no C mapping",
+                proto.name, ty
+            )));
+        }
+    }
+    let guard = matches.value_of("guard").unwrap();
+    print!("{}", moore::c_header::render(&protos, guard, dpi));
+}
+
+/// Quote and escape a string for inclusion in JSON output.
+fn json_string(s: &str) -> String {
+    moore::server::Json::String(s.to_string()).to_string()
+}
+
+/// Compare two snapshots and print the structural changes between them.
+fn diff(sess: &Session, matches: &ArgMatches) {
+    let (old, _) = read_snapshot(sess, matches.value_of("OLD").unwrap());
+    let (new, _) = read_snapshot(sess, matches.value_of("NEW").unwrap());
+    let changes = moore::design_diff::diff(&old, &new);
+    for change in &changes {
+        println!("{}", change);
+    }
+    if changes.is_empty() {
+        eprintln!("no structural changes");
+    }
+}
+
+fn reduce(sess: &Session, matches: &ArgMatches) {
+    use moore::reduce::{self, ReduceFile};
+    let signature = matches.value_of("match").unwrap();
+    let output = Path::new(matches.value_of("output").unwrap());
+    let args: Vec<_> = matches.values_of("ARGS").into_iter().flatten().collect();
+
+    // Read the input files.
+    let mut files = vec![];
+    for (filename, language) in input_files(sess, matches) {
+        match std::fs::read_to_string(&filename) {
+            Ok(text) => files.push(ReduceFile {
+                path: filename.into(),
+                language,
+                text,
+            }),
+            Err(e) => sess.emit(DiagBuilder2::error(format!(
+                "cannot read `{}`: {}",
+                filename, e
+            ))),
+        }
+    }
+    if sess.failed() {
+        exit(sess, 1);
+    }
+
+    // Each candidate is compiled in a separate process, such that crashes of
+    // the compiler can be observed.
+    let exe = std::env::current_exe().expect("cannot determine compiler executable");
+    let scratch = std::env::temp_dir().join(format!("moore-reduce-{}", std::process::id()));
+    let mut num_tests = 0;
+    let mut test = |files: &[ReduceFile]| -> bool {
+        num_tests += 1;
+        let paths = match write_reduce_files(&scratch, files) {
+            Ok(x) => x,
+            Err(_) => return false,
+        };
+        let out = match std::process::Command::new(&exe)
+            .args(&args)
+            .args(&paths)
+            .output()
+        {
+            Ok(x) => x,
+            Err(_) => return false,
+        };
+        String::from_utf8_lossy(&out.stderr).contains(signature)
+            || String::from_utf8_lossy(&out.stdout).contains(signature)
+    };
+
+    // Make sure the problem reproduces before reducing.
+    if !test(&files) {
+        sess.emit(
+            DiagBuilder2::error("the inputs do not reproduce the problem").add_note(format!(
+                "The compiler output does not contain `{}`",
+                signature
+            )),
+        );
+        let _ = std::fs::remove_dir_all(&scratch);
+        exit(sess, 1);
+    }
+    let size_before: usize = files.iter().map(|f| f.text.len()).sum();
+    let reduced = reduce::reduce(files, &mut test);
+    let _ = std::fs::remove_dir_all(&scratch);
+
+    // Write the result.
+    let size_after: usize = reduced.iter().map(|f| f.text.len()).sum();
+    match write_reduce_files(output, &reduced) {
+        Ok(paths) => {
+            eprintln!(
+                "reduced from {} to {} bytes in {} runs; reproduce with:",
+                size_before, size_after, num_tests
+            );
+            let mut cmd = vec!["moore".to_string()];
+            cmd.extend(args.iter().map(|s| s.to_string()));
+            cmd.extend(paths.iter().map(|p| p.display().to_string()));
+            println!("{}", cmd.join(" "));
+        }
+        Err(e) => sess.emit(DiagBuilder2::error(format!(
+            "cannot write to `{}`: {}",
+            output.display(),
+            e
+        ))),
+    }
+}
+
+/// Write a set of files being reduced into a directory.
+///
+/// Returns the paths of the written files.
+fn write_reduce_files(
+    dir: &Path,
+    files: &[moore::reduce::ReduceFile],
+) -> std::io::Result<Vec<std::path::PathBuf>> {
+    std::fs::create_dir_all(dir)?;
+    let mut paths: Vec<std::path::PathBuf> = vec![];
+    for (i, file) in files.iter().enumerate() {
+        let name = file.path.file_name().unwrap_or_default().to_string_lossy();
+        let mut path = dir.join(&*name);
+        if paths.contains(&path) {
+            path = dir.join(format!("{}-{}", i, name));
+        }
+        std::fs::write(&path, &file.text)?;
+        paths.push(path);
+    }
+    Ok(paths)
+}
+
+/// Run an external conformance test suite.
+///
+/// Each test is compiled in a separate process, such that crashes can be
+/// counted as such.
+fn conformance(sess: &Session, matches: &ArgMatches) {
+    use moore::conformance::{self, Outcome, Results};
+    let manifest = Path::new(matches.value_of("MANIFEST").unwrap());
+    let baseline_path = matches.value_of("baseline").map(Path::new);
+    let update = matches.is_present("update-baseline");
+    let args: Vec<_> = matches
+        .values_of("ARGS")
+        .into_iter()
+        .flat_map(|v| v)
+        .collect();
+
+    // Read the manifest and the baseline.
+    let text = match std::fs::read_to_string(manifest) {
+        Ok(x) => x,
+        Err(e) => {
+            sess.emit(DiagBuilder2::fatal(format!(
+                "cannot read `{}`: {}",
+                manifest.display(),
+                e
+            )));
+            exit(sess, 1);
+        }
+    };
+    let dir = manifest.parent().unwrap_or_else(|| Path::new("."));
+    let tests = match conformance::parse_manifest(&text, dir) {
+        Ok(x) => x,
+        Err(msg) => {
+            sess.emit(DiagBuilder2::fatal(format!(
+                "invalid manifest `{}`: {}",
+                manifest.display(),
+                msg
+            )));
+            exit(sess, 1);
+        }
+    };
+    let baseline = match baseline_path {
+        Some(path) if path.exists() => {
+            let parsed = std::fs::read_to_string(path)
+                .map_err(|e| e.to_string())
+                .and_then(|text| conformance::parse_baseline(&text));
+            match parsed {
+                Ok(x) => Some(x),
+                Err(msg) => {
+                    sess.emit(DiagBuilder2::fatal(format!(
+                        "invalid baseline `{}`: {}",
+                        path.display(),
+                        msg
+                    )));
+                    exit(sess, 1);
+                }
+            }
+        }
+        _ => None,
+    };
+
+    // Run the tests.
+    let exe = std::env::current_exe().expect("cannot determine compiler executable");
+    let mut results = Results::new();
+    for test in &tests {
+        let out = std::process::Command::new(&exe)
+            .args(&args)
+            .args(&test.args)
+            .arg(&test.path)
+            .output();
+        let outcome = match out {
+            Ok(out) => Outcome::from_exit_code(out.status.code()),
+            Err(e) => {
+                sess.emit(DiagBuilder2::fatal(format!(
+                    "cannot run `{}`: {}",
+                    exe.display(),
+                    e
+                )));
+                exit(sess, 1);
+            }
+        };
+        if outcome != test.expected {
+            println!("{}: expected {}, got {}", test.name, test.expected, outcome);
+        }
+        results.insert(test.name.clone(), outcome);
+    }
+
+    // Summarize the results.
+    let conforming = tests
+        .iter()
+        .filter(|t| results[&t.name] == t.expected)
+        .count();
+    let crashes = results.values().filter(|&&o| o == Outcome::Crash).count();
+    println!(
+        "{} of {} tests conform ({:.1}%), {} crashed",
+        conforming,
+        tests.len(),
+        100.0 * conforming as f64 / tests.len().max(1) as f64,
+        crashes
+    );
+    if let Some(ref baseline) = baseline {
+        let delta = conformance::compare(&tests, baseline, &results);
+        for name in &delta.fixed {
+            println!("fixed: {}", name);
+        }
+        for name in &delta.regressed {
+            println!("regressed: {}", name);
+        }
+        println!(
+            "{} fixed, {} regressed, {} new since the baseline",
+            delta.fixed.len(),
+            delta.regressed.len(),
+            delta.added.len()
+        );
+        if !delta.regressed.is_empty() && !update {
+            sess.emit(DiagBuilder2::error(format!(
+                "{} tests regressed since the baseline",
+                delta.regressed.len()
+            )));
+        }
+    } else if !update && conforming != tests.len() {
+        sess.emit(DiagBuilder2::error(format!(
+            "{} tests do not conform",
+            tests.len() - conforming
+        )));
+    }
+
+    // Record the new baseline if requested.
+    if let (true, Some(path)) = (update, baseline_path) {
+        if let Err(e) = std::fs::write(path, conformance::write_baseline(&results)) {
+            sess.emit(DiagBuilder2::error(format!(
+                "cannot write `{}`: {}",
+                path.display(),
+                e
+            )));
+        }
+    }
+}
+
+/// Recompile the design whenever one of its inputs changes.
+///
+/// The compiler stays resident between runs, like the compile server. Only the
+/// VHDL files that changed are parsed again, and an entity given with `-e` is
+/// only elaborated again if one of the files it depends on changed. Only the
+/// diagnostics that were not present in the previous run are printed.
+fn watch(sess: &Session, matches: &ArgMatches, libexts: Vec<String>) -> ! {
+    use crate::name::get_name_table;
+    use moore::server::VhdlCache;
+    use moore::watch::{self, DiagnosticLog, Snapshot};
+    use std::collections::HashMap;
+
+    let map = language_map(sess, matches);
+    let include_paths = include_paths(matches);
+    let defines = defines(matches);
+    let lib = get_name_table().intern(matches.value_of("lib").unwrap_or("work"), true);
+    let manifest = read_manifest(sess, matches);
+    let params = match param_overrides(sess, matches, &manifest, lib) {
+        Ok(x) => x,
+        Err(()) => exit(sess, 1),
+    };
+    let files = input_files(sess, matches);
+    let manifest: Vec<_> = manifest
+        .into_iter()
+        .map(|library| {
+            let files = expand_files(sess, matches, library.files.iter().map(String::as_str));
+            (library, files)
+        })
+        .collect();
+    let search = library_search(matches, libexts);
+
+    // The standard libraries do not change, so they are only parsed once.
+    let mut std_units = Vec::new();
+    for &(arg, name) in &[("std-path", "std"), ("ieee-path", "ieee")] {
+        if let Some(root) = matches.value_of(arg) {
+            let name = get_name_table().intern(name, false);
+            if let Ok(asts) = parse_std_library(sess, name, Path::new(root)) {
+                let units: Vec<_> = asts
+                    .into_iter()
+                    .flat_map(|ast| match ast {
+                        score::Ast::Vhdl(x) => x,
+                        score::Ast::Svlog(_) => vec![],
+                    })
+                    .collect();
+                std_units.push((name, units));
+            }
+        }
+    }
+    if sess.failed() {
+        exit(sess, 1);
+    }
+
+    let inputs: Vec<_> = matches
+        .values_of("INPUT")
+        .into_iter()
+        .flatten()
+        .chain(matches.value_of("manifest"))
+        .chain(
+            manifest
+                .iter()
+                .flat_map(|(lib, _)| lib.files.iter().map(String::as_str)),
+        )
+        .collect();
+    let plural = |n: usize, what: &str| match n {
+        1 => format!("1 {}", what),
+        n => format!("{} {}s", n, what),
+    };
+
+    let mut cache = VhdlCache::default();
+    let mut log = DiagnosticLog::default();
+
+    // The files each entity depended on when it was last elaborated, and the
+    // diagnostics the elaboration produced.
+    let mut elaborated: HashMap<&str, (Snapshot, Vec<String>)> = HashMap::new();
+    loop {
+        let paths = watch::watched_paths(inputs.iter().cloned(), &map);
+        let snapshot = Snapshot::take(&paths);
+        sess.reset();
+        let diags = sess.buffered(|| {
+            // Parse and analyze the design anew. The VHDL units come from the
+            // cache, and only changed files are parsed again.
+            let svlog_arenas = svlog::GlobalArenas::default();
+            let mut asts = parse_cached(
+                sess,
+                &mut cache,
+                &files,
+                &include_paths,
+                &defines,
+                &svlog_arenas.ast,
+            );
+            let mut manifest_libs = vec![];
+            for (library, lib_files) in &manifest {
+                let (lib_include_paths, lib_defines) =
+                    library_options(library, &include_paths, &defines);
+                let lib_asts = parse_cached(
+                    sess,
+                    &mut cache,
+                    lib_files,
+                    &lib_include_paths,
+                    &lib_defines,
+                    &svlog_arenas.ast,
+                );
+                let name = get_name_table().intern(&library.name, true);
+                if name == lib {
+                    asts.extend(lib_asts);
+                } else {
+                    manifest_libs.push((name, lib_asts));
+                }
+            }
+            let mut tried = HashSet::new();
+            loop {
+                let design = asts.iter().chain(manifest_libs.iter().flat_map(|(_, x)| x));
+                let files = library_files(&search, &map, design, &mut tried);
+                if files.is_empty() {
+                    break;
+                }
+                asts.extend(parse_cached(
+                    sess,
+                    &mut cache,
+                    &files,
+                    &include_paths,
+                    &defines,
+                    &svlog_arenas.ast,
+                ));
+            }
+            let mut diags = sess.diags.take();
+            if sess.failed() {
+                return diags;
+            }
+
+            let std_libs: Vec<_> = std_units
+                .iter()
+                .map(|(name, units)| (*name, vec![score::Ast::Vhdl(units.clone())]))
+                .collect();
+            let arenas = score::Arenas::new();
+            let sb = ScoreBoard::new(&arenas);
+            let vhdl_sb = vhdl::score::ScoreBoard::new(&arenas.vhdl);
+            let mut svlog_sb = svlog::GlobalContext::new(&sess, &svlog_arenas);
+            let vhdl_phases = vhdl::lazy::LazyPhaseTable::new(&vhdl_sb);
+            let cancel = CancelToken::new();
+            svlog_sb.cancel = cancel.clone();
+            let ctx = ScoreContext {
+                sess: sess,
+                sb: &sb,
+                vhdl: &vhdl_sb,
+                vhdl_phases: &vhdl_phases,
+                svlog: &svlog_sb,
+                cancel: &cancel,
+            };
+            for (name, asts) in std_libs.iter().chain(&manifest_libs) {
+                ctx.add_library(*name, asts);
+            }
+            let lib_id = ctx.add_library(lib, &asts);
+            for name in matches.values_of("elaborate").into_iter().flatten() {
+                // Reuse the diagnostics of the previous elaboration if none of
+                // the files the entity depends on changed since.
+                let deps = parse_elaborate_name(name)
+                    .ok()
+                    .and_then(|(_, unit, _)| {
+                        let design = asts.iter().chain(manifest_libs.iter().flat_map(|(_, x)| x));
+                        select::unit_sources(design, unit)
+                    })
+                    .map(Snapshot::take);
+                if let (Some(deps), Some((prev, prev_diags))) = (&deps, elaborated.get(name)) {
+                    if deps == prev {
+                        diags.extend(prev_diags.iter().cloned());
+                        continue;
+                    }
+                }
+                if let Err(Error::Internal(msg)) =
+                    elaborate_name(&ctx, lib_id, name, &params, &mut std::io::sink())
+                {
+                    sess.emit(DiagBuilder2::bug(format!(
+                        "elaboration of `{}` failed: {}",
+                        name, msg
+                    )));
+                }
+                let unit_diags = sess.diags.take();
+                diags.extend(unit_diags.iter().cloned());
+                match deps {
+                    Some(deps) => elaborated.insert(name, (deps, unit_diags)),
+                    None => elaborated.remove(name),
+                };
+                if sess.error_limit_reached() {
+                    break;
+                }
+            }
+            diags
+        });
+
+        let diff = log.update(diags);
+        for diag in &diff.new {
+            eprintln!("{}", diag);
+        }
+        eprintln!(
+            "\x1B[1m[watch]\x1B[m {}, {} new, {} resolved; waiting for changes",
+            plural(diff.total, "diagnostic"),
+            diff.new.len(),
+            diff.resolved
+        );
+        while Snapshot::take(&paths) == snapshot {
+            std::thread::sleep(Duration::from_millis(250));
+        }
+    }
+}
+
+/// Elaborate the entities given with `-e` in parallel.
+///
+/// Each entity is elaborated by a separate process with the same arguments.
+/// The generated code and diagnostics of the processes are printed in the order
+/// in which the entities were given.
+fn batch(sess: &Session, matches: &ArgMatches) {
+    use std::io::Write;
+    let jobs = matches.value_of("jobs").unwrap();
+    let jobs = match jobs.parse::<usize>() {
+        Ok(n) if n > 0 => n,
+        _ => {
+            sess.emit(DiagBuilder2::fatal(format!(
+                "invalid `--jobs` value `{}`; expected a positive number",
+                jobs
+            )));
+            exit(sess, 1);
+        }
+    };
+    let units: Vec<String> = matches
+        .values_of("elaborate")
+        .into_iter()
+        .flatten()
+        .map(String::from)
+        .collect();
+    if units.len() > 1 && sess.opts.save_snapshot.is_some() {
+        sess.emit(
+            DiagBuilder2::error("cannot save a snapshot of several entities")
+                .add_note("Elaborate one entity at a time with `--save-snapshot`"),
+        );
+        exit(sess, 1);
+    }
+
+    let exe = std::env::current_exe().expect("cannot determine compiler executable");
+    let args = moore::batch::strip_batch_args(std::env::args_os().skip(1));
+    let outputs = moore::batch::run(exe.clone(), args, units.clone(), jobs);
+    let mut failed = vec![];
+    for (unit, out) in units.iter().zip(outputs) {
+        let out = match out {
+            Ok(x) => x,
+            Err(e) => {
+                sess.emit(DiagBuilder2::fatal(format!(
+                    "cannot run `{}`: {}",
+                    exe.display(),
+                    e
+                )));
+                exit(sess, 1);
+            }
+        };
+        std::io::stdout().write_all(&out.stdout).unwrap();
+        std::io::stderr().write_all(&out.stderr).unwrap();
+        match out.status.code() {
+            Some(0) => (),
+            Some(1) => failed.push(unit.as_str()),
+            _ => {
+                sess.emit(DiagBuilder2::bug(format!(
+                    "elaboration of `{}` crashed ({})",
+                    unit, out.status
+                )));
+            }
+        }
+    }
+    if !failed.is_empty() {
+        sess.emit(DiagBuilder2::error(format!(
+            "elaboration of {} failed",
+            failed
+                .iter()
+                .map(|unit| format!("`{}`", unit))
+                .collect::<Vec<_>>()
+                .join(", ")
+        )));
+    }
+}
+
+/// Serve compilation requests from build scripts over a socket.
+///
+/// See the `server` module for the protocol. VHDL files are only reparsed once
+/// they change. The analyzed design is kept until one of its files changes,
+/// such that repeated elaborations reuse what earlier requests computed. The
+/// diagnostics are held back and sent to the client whose request caused them.
+fn serve(sess: &Session, matches: &ArgMatches) {
+    use crate::name::get_name_table;
+    use moore::server::{self, Connection, Json, Request, VhdlCache};
+    use moore::watch::Snapshot;
+
+    let addr = matches.value_of("listen").unwrap();
+    let mut conn = match Connection::bind(addr) {
+        Ok(x) => x,
+        Err(e) => {
+            sess.emit(DiagBuilder2::fatal(format!(
+                "cannot listen on `{}`: {}",
+                addr, e
+            )));
+            exit(sess, 1);
+        }
+    };
+    if let Ok(addr) = conn.local_addr() {
+        eprintln!("listening on {}", addr);
+    }
+    let include_paths = include_paths(matches);
+    let defines = defines(matches);
+
+    sess.buffered(|| {
+        let mut files = input_files(sess, matches);
+        let manifest: Vec<_> = read_manifest(sess, matches)
+            .into_iter()
+            .map(|library| {
+                let files = expand_files(sess, matches, library.files.iter().map(String::as_str));
+                (library, files)
+            })
+            .collect();
+        let mut lib = get_name_table().intern("work", true);
+        let mut cache = VhdlCache::default();
+
+        // The number of errors and warnings that have already been reported to
+        // a client.
+        let mut reported = (0, 0);
+
+        // A request that arrived after the design changed.
+        let mut pending: Option<Request> = None;
+        loop {
+            // Parse and analyze the design anew. The VHDL units come from the
+            // cache, and only changed files are parsed again.
+            let watched = || {
+                files
+                    .iter()
+                    .chain(manifest.iter().flat_map(|(_, files)| files))
+                    .map(|(f, _)| f.clone())
+                    .collect::<Vec<_>>()
+            };
+            let snapshot = Snapshot::take(watched());
+            let svlog_arenas = svlog::GlobalArenas::default();
+            let mut asts = parse_cached(
+                sess,
+                &mut cache,
+                &files,
+                &include_paths,
+                &defines,
+                &svlog_arenas.ast,
+            );
+            let mut manifest_libs = vec![];
+            for (library, lib_files) in &manifest {
+                let (lib_include_paths, lib_defines) =
+                    library_options(library, &include_paths, &defines);
+                let lib_asts = parse_cached(
+                    sess,
+                    &mut cache,
+                    lib_files,
+                    &lib_include_paths,
+                    &lib_defines,
+                    &svlog_arenas.ast,
+                );
+                let name = get_name_table().intern(&library.name, true);
+                if name == lib {
+                    asts.extend(lib_asts);
+                } else {
+                    manifest_libs.push((name, lib_asts));
+                }
+            }
+            let arenas = score::Arenas::new();
+            let sb = ScoreBoard::new(&arenas);
+            let vhdl_sb = vhdl::score::ScoreBoard::new(&arenas.vhdl);
+            // Record the resolved VHDL names for `uses` requests.
+            vhdl_sb.record_names.set(true);
+            let mut svlog_sb = svlog::GlobalContext::new(&sess, &svlog_arenas);
+            let vhdl_phases = vhdl::lazy::LazyPhaseTable::new(&vhdl_sb);
+            let cancel = CancelToken::new();
+            svlog_sb.cancel = cancel.clone();
+            let ctx = ScoreContext {
+                sess: sess,
+                sb: &sb,
+                vhdl: &vhdl_sb,
+                vhdl_phases: &vhdl_phases,
+                svlog: &svlog_sb,
+                cancel: &cancel,
+            };
+            let mut libs = vec![];
+            for (name, asts) in &manifest_libs {
+                libs.push((ctx.add_library(*name, asts), asts.as_slice()));
+            }
+            let lib_id = ctx.add_library(lib, &asts);
+            libs.push((lib_id, asts.as_slice()));
+
+            // Answer requests until the design changes.
+            let mut fresh = true;
+            loop {
+                let request = match pending.take() {
+                    Some(x) => x,
+                    None => match conn.next_request() {
+                        Ok(x) => x,
+                        Err(e) => {
+                            sess.emit(DiagBuilder2::fatal(format!(
+                                "cannot accept connection: {}",
+                                e
+                            )));
+                            exit(sess, 1);
+                        }
+                    },
+                };
+                if !fresh && Snapshot::take(watched()) != snapshot {
+                    pending = Some(request);
+                    break;
+                }
+                fresh = false;
+                let mut diagnostics = || {
+                    let counts = (sess.num_errors(), sess.num_warnings());
+                    let result = server::diagnostics_result(
+                        counts.0 - reported.0,
+                        counts.1 - reported.1,
+                        sess.diags.take(),
+                    );
+                    reported = counts;
+                    result
+                };
+                let result = match request.method.as_str() {
+                    "compile" => {
+                        let new_files = match request.params.get("files") {
+                            Some(list) => match list.as_array().and_then(|l| {
+                                l.iter().map(Json::as_str).collect::<Option<Vec<_>>>()
+                            }) {
+                                Some(list) => Some(expand_files(sess, matches, list)),
+                                None => {
+                                    let msg = "`files` must be a list of strings";
+                                    conn.reply(&server::error_response(
+                                        &request.id,
+                                        server::INVALID_PARAMS,
+                                        msg,
+                                    ));
+                                    continue;
+                                }
+                            },
+                            None => None,
+                        };
+                        let new_lib = request
+                            .param_str("lib")
+                            .map(|name| get_name_table().intern(name, true));
+                        if new_files.as_ref().map_or(false, |x| *x != files)
+                            || new_lib.map_or(false, |x| x != lib)
+                        {
+                            files = new_files.unwrap_or(files);
+                            lib = new_lib.unwrap_or(lib);
+                            pending = Some(request);
+                            break;
+                        }
+                        Ok(diagnostics())
+                    }
+                    "elaborate" => match request.param_str("name") {
+                        Some(name) => {
+                            let mut output = Vec::new();
+                            match elaborate_name(&ctx, lib_id, name, &[], &mut output) {
+                                Ok(()) => {
+                                    let _ = write_vhdl_module(sess, &vhdl_sb, &mut output);
+                                }
+                                Err(Error::Internal(msg)) => {
+                                    sess.emit(DiagBuilder2::bug(format!(
+                                        "elaboration of `{}` failed: {}",
+                                        name, msg
+                                    )));
+                                }
+                                Err(_) => (),
+                            }
+                            Ok(server::elaboration_result(
+                                diagnostics(),
+                                String::from_utf8_lossy(&output).into_owned(),
+                            ))
+                        }
+                        None => Err("expected a `name`".to_string()),
+                    },
+                    "query" => match (request.param_str("kind"), request.param_str("name")) {
+                        (Some(kind), Some(name)) if QUERY_KINDS.contains(&kind) => {
+                            let design =
+                                asts.iter().chain(manifest_libs.iter().flat_map(|(_, x)| x));
+                            let vhdl_units: Vec<_> = design
+                                .clone()
+                                .filter_map(|ast| match ast {
+                                    score::Ast::Vhdl(x) => Some(x),
+                                    _ => None,
+                                })
+                                .flatten()
+                                .cloned()
+                                .collect();
+                            let svlog_files = design.filter_map(|ast| match ast {
+                                score::Ast::Svlog(x) => Some(x),
+                                _ => None,
+                            });
+                            run_query(kind, name, &vhdl_units, svlog_files).map(|results| {
+                                Json::Array(
+                                    results
+                                        .into_iter()
+                                        .map(|(unit, name, span)| {
+                                            let begin = span.begin();
+                                            Json::Object(vec![
+                                                (
+                                                    "unit".to_string(),
+                                                    Json::String(unit.as_str().to_string()),
+                                                ),
+                                                (
+                                                    "name".to_string(),
+                                                    name.map(|n| {
+                                                        Json::String(n.as_str().to_string())
+                                                    })
+                                                    .unwrap_or(Json::Null),
+                                                ),
+                                                (
+                                                    "file".to_string(),
+                                                    Json::String(
+                                                        span.source.get_path().to_string(),
+                                                    ),
+                                                ),
+                                                (
+                                                    "line".to_string(),
+                                                    Json::Number(begin.human_line() as f64),
+                                                ),
+                                                (
+                                                    "column".to_string(),
+                                                    Json::Number(begin.human_column() as f64),
+                                                ),
+                                            ])
+                                        })
+                                        .collect(),
+                                )
+                            })
+                        }
+                        _ => Err(format!(
+                            "expected a `name` and a `kind` out of {}",
+                            QUERY_KINDS.join(", ")
+                        )),
+                    },
+                    "uses" => match (
+                        request.param_str("file"),
+                        request.param_usize("line"),
+                        request.param_usize("column"),
+                    ) {
+                        (Some(file), Some(line), Some(column)) => {
+                            match source::get_source_manager()
+                                .find(file)
+                                .and_then(|source| server::name_at(source, line, column))
+                            {
+                                Some(decl) => {
+                                    Ok(server::uses_result(&find_uses(&ctx, &libs, decl)))
+                                }
+                                None => Err(format!("no name at {}:{}:{}", file, line, column)),
+                            }
+                        }
+                        _ => Err("expected a `file`, a `line`, and a `column`".to_string()),
+                    },
+                    "shutdown" => {
+                        conn.reply(&server::response(&request.id, Json::Null));
+                        // The errors have been reported to the clients, so the
+                        // server itself succeeded.
+                        std::process::exit(0);
+                    }
+                    _ => {
+                        let msg = format!("unknown method `{}`", request.method);
+                        conn.reply(&server::error_response(
+                            &request.id,
+                            server::METHOD_NOT_FOUND,
+                            &msg,
+                        ));
+                        continue;
+                    }
+                };
+                conn.reply(&match result {
+                    Ok(result) => server::response(&request.id, result),
+                    Err(msg) => server::error_response(&request.id, server::INVALID_PARAMS, &msg),
+                });
+
+                // Like the compiler, stop once `--max-errors` has been reached,
+                // since any further diagnostics would be dropped.
+                if sess.error_limit_reached() {
+                    exit(sess, 1);
+                }
+            }
+        }
+    })
+}
+
+/// Find the uses of the declaration whose name is at `decl`, across all
+/// libraries of the design.
+///
+/// A declaration in a SystemVerilog file is looked for in the SystemVerilog
+/// files, any other one in the VHDL libraries.
+fn find_uses<'ast>(
+    ctx: &ScoreContext<'_, '_, 'ast, '_>,
+    libs: &[(score::LibRef, &'ast [score::Ast<'ast>])],
+    decl: source::Span,
+) -> Vec<source::Span> {
+    let svlog_files: Vec<_> = libs
+        .iter()
+        .flat_map(|&(_, asts)| asts)
+        .filter_map(|ast| match ast {
+            score::Ast::Svlog(x) => Some(x),
+            _ => None,
+        })
+        .collect();
+    let in_svlog = svlog_files
+        .iter()
+        .flat_map(|file| &file.items)
+        .any(|item| item.span.source == decl.source);
+    let mut uses: Vec<_> = if in_svlog {
+        svlog_files
+            .into_iter()
+            .flat_map(|file| svlog::find_decl_uses(ctx.svlog, file, decl))
+            .collect()
+    } else {
+        libs.iter()
+            .flat_map(|&(lib, _)| {
+                let lib = vhdl::score::LibRef::new(lib.into());
+                vhdl::rename::find_decl_uses(&ctx.vhdl(), lib, decl)
+            })
+            .collect()
+    };
+    uses.sort();
+    uses.dedup();
+    uses.retain(|&span| span != decl);
+    uses.insert(0, decl);
+    uses
+}
+
+/// Determine where to look for modules that are instantiated but not defined.
+fn library_search(matches: &ArgMatches, libexts: Vec<String>) -> libdirs::LibrarySearch {
+    libdirs::LibrarySearch {
+        dirs: matches
+            .values_of("libdir")
+            .into_iter()
+            .flatten()
+            .map(PathBuf::from)
+            .collect(),
+        exts: libexts,
+    }
+}
+
+/// Find the files in the library directories that define the modules which are
+/// instantiated but not defined in a design.
+///
+/// Modules in `tried` are skipped, and the modules looked up are added to it,
+/// such that repeated calls make progress.
+fn library_files<'a, 'b: 'a>(
+    search: &libdirs::LibrarySearch,
+    map: &inputs::LanguageMap,
+    design: impl IntoIterator<Item = &'a score::Ast<'b>>,
+    tried: &mut HashSet<Name>,
+) -> Vec<(String, Language)> {
+    libdirs::missing_modules(design)
+        .into_iter()
+        .filter(|name| tried.insert(*name))
+        .filter_map(|name| search.find(&name.as_str()))
+        .map(|path| {
+            let language = map.lookup(&path).unwrap_or(Language::Verilog);
+            (path.to_string_lossy().into_owned(), language)
+        })
+        .collect()
+}
+
+/// Parse the files of a library for the compile server.
+///
+/// The VHDL units are taken from the cache, which only parses the files that
+/// changed. The SystemVerilog files are preprocessed with the given include
+/// paths and macro definitions. Returns the SystemVerilog files followed by
+/// all VHDL units.
+fn parse_cached<'a>(
+    sess: &Session,
+    cache: &mut moore::server::VhdlCache,
+    files: &[(String, Language)],
+    include_paths: &[&Path],
+    defines: &[(&str, Option<&str>)],
+    arena: &'a svlog::ast::Arena<'a>,
+) -> Vec<score::Ast<'a>> {
+    let mut vhdl_units = vec![];
+    let mut asts = vec![];
+    for (filename, language) in files {
+        match language {
+            Language::Vhdl => {
+                if let Ok(units) = cache.parse(filename, sess) {
+                    vhdl_units.extend(units.iter().cloned());
+                }
+            }
+            Language::SystemVerilog | Language::Verilog => {
+                let source = match source::get_source_manager().reopen(filename) {
+                    Some(s) => s,
+                    None => {
+                        sess.emit(DiagBuilder2::error(format!("cannot open `{}`", filename)));
+                        continue;
+                    }
+                };
+                let preproc = svlog::preproc::Preprocessor::new(source, include_paths, defines);
+                let lexer = svlog::lexer::Lexer::new(preproc);
+                if let Ok(x) = svlog::parser::parse(lexer, arena) {
+                    asts.push(score::Ast::Svlog(x));
+                }
+            }
+        }
+    }
+    asts.push(score::Ast::Vhdl(vhdl_units));
+    asts
+}
+
+fn score(sess: &Session, matches: &ArgMatches, libexts: Vec<String>) {
+    use crate::name::get_name_table;
+    let svlog_arenas = svlog::GlobalArenas::default();
+
+    let include_paths = include_paths(matches);
+    let defines = defines(matches);
+
+    // Establish into which library the entities will be compiled. Later on this
+    // should be made configurable per entity.
+    let lib = get_name_table().intern(matches.value_of("lib").unwrap_or("work"), true);
+
+    // Parse the parameter overrides.
+    let mut failed = false;
+    let manifest = read_manifest(sess, matches);
+    let params = match param_overrides(sess, matches, &manifest, lib) {
+        Ok(x) => x,
+        Err(()) => {
+            failed = true;
+            vec![]
+        }
+    };
+
+    // Parse the input files.
+    let mut asts = match parse_files(
+        sess,
+        matches,
+        input_files(sess, matches),
+        &include_paths,
+        &defines,
+        &svlog_arenas.ast,
+    ) {
+        Ok(x) => x,
+        Err(()) => {
+            failed = true;
+            vec![]
+        }
+    };
+
+    // Parse the libraries listed in the manifest. Their include paths are
+    // searched before, and their defines override, those given on the command
+    // line. Files of the library compiled into are added to the inputs.
+    let mut manifest_libs = Vec::new();
+    for library in manifest {
+        let (lib_include_paths, lib_defines) = library_options(&library, &include_paths, &defines);
+        let files = expand_files(sess, matches, library.files.iter().map(String::as_str));
+        let lib_asts = match parse_files(
+            sess,
+            matches,
+            files,
+            &lib_include_paths,
+            &lib_defines,
+            &svlog_arenas.ast,
+        ) {
+            Ok(x) => x,
+            Err(()) => {
+                failed = true;
+                continue;
+            }
+        };
+        let name = get_name_table().intern(&library.name, true);
+        if name == lib {
+            asts.extend(lib_asts);
+        } else {
+            manifest_libs.push((name, lib_asts));
+        }
+    }
+
+    // Analyze the files in the library directories that define modules which
+    // are instantiated but not defined, until no more such files are found.
+    if !failed {
+        let search = library_search(matches, libexts);
+        let map = language_map(sess, matches);
+        let mut tried = HashSet::new();
+        loop {
+            let design = asts.iter().chain(manifest_libs.iter().flat_map(|(_, x)| x));
+            let files = library_files(&search, &map, design, &mut tried);
+            if files.is_empty() {
+                break;
+            }
+            match parse_files(
+                sess,
+                matches,
+                files,
+                &include_paths,
+                &defines,
+                &svlog_arenas.ast,
+            ) {
+                Ok(x) => asts.extend(x),
+                Err(()) => {
+                    failed = true;
+                    break;
+                }
+            }
+        }
+    }
+
+    // Parse the standard libraries, if their sources were provided.
+    let mut std_libs = Vec::new();
+    for &(arg, name) in &[("std-path", "std"), ("ieee-path", "ieee")] {
+        if let Some(root) = matches.value_of(arg) {
+            let name = get_name_table().intern(name, false);
+            match parse_std_library(sess, name, Path::new(root)) {
+                Ok(x) => std_libs.push((name, x)),
+                Err(()) => failed = true,
+            }
+        }
+    }
+    if failed || sess.failed() {
+        exit(sess, 1);
+    }
+    if matches.is_present("preproc") {
+        return;
+    }
+
+    // Narrow the design down to the requested unit.
+    let all_asts = asts;
+    let selected_asts;
+    let asts = match matches.value_of("unit") {
+        Some(unit) => match select_unit(sess, &all_asts, lib, unit) {
+            Ok(x) => {
+                selected_asts = x;
+                &selected_asts
+            }
+            Err(()) => exit(sess, 1),
+        },
+        None => &all_asts,
+    };
+
+    // Dump the AST if so requested.
+    if matches.is_present("dump-ast") {
+        println!("{:#99?}", asts);
+    }
+
+    if matches.is_present("emit_pkgs") {
+        vhdl::debug::emit_pkgs(
+            sess,
+            asts.iter()
+                .flat_map(|ast| match *ast {
+                    score::Ast::Vhdl(ref x) => x.iter(),
+                    _ => [].iter(),
+                })
+                .collect(),
+        );
+    }
+
+    // Stop processing if requested.
+    if matches.is_present("check-syntax") {
+        exit(sess, 0);
+    }
+
+    // Create the scoreboard and add the initial map of libraries.
+    let arenas = score::Arenas::new();
+    let sb = ScoreBoard::new(&arenas);
+    let vhdl_sb = vhdl::score::ScoreBoard::new(&arenas.vhdl);
+    let mut svlog_sb = svlog::GlobalContext::new(&sess, &svlog_arenas);
+
+    // Elaborate the requested entities or modules.
+    {
+        let mut vhdl_phases = vhdl::lazy::LazyPhaseTable::new(&vhdl_sb);
+        if matches.is_present("trace-passes") || matches.is_present("trace-chrome") {
+            let chrome = matches.value_of("trace-chrome").map(Path::new);
+            match vhdl::trace::PassTrace::new(matches.value_of("trace-passes"), chrome) {
+                Ok(trace) => vhdl_phases.trace = trace,
+                Err(e) => {
+                    sess.emit(DiagBuilder2::error(format!(
+                        "cannot create trace file `{}`: {}",
+                        chrome.unwrap().display(),
+                        e
+                    )));
+                    exit(sess, 1);
+                }
+            }
+        }
+        let cancel = match matches.value_of("timeout") {
+            Some(secs) => match secs.parse::<f64>() {
+                Ok(secs) if secs >= 0.0 => CancelToken::with_timeout(Duration::from_secs_f64(secs)),
+                _ => {
+                    sess.emit(DiagBuilder2::error(format!(
+                        "invalid timeout `{}`; expected a number of seconds",
+                        secs
+                    )));
+                    exit(sess, 1);
+                }
+            },
+            None => CancelToken::new(),
+        };
+        svlog_sb.cancel = cancel.clone();
+        let ctx = ScoreContext {
+            sess: sess,
+            sb: &sb,
+            vhdl: &vhdl_sb,
+            vhdl_phases: &vhdl_phases,
+            svlog: &svlog_sb,
+            cancel: &cancel,
         };
+        for (name, asts) in std_libs.iter().chain(&manifest_libs) {
+            ctx.add_library(*name, asts);
+        }
+        let lib_id = ctx.add_library(lib, asts);
+        if let Some(names) = matches.values_of("elaborate") {
+            debug!("lib_id = {:?}", lib_id);
+            debug!("{:?}", sb);
+            for name in names {
+                match elaborate_name(&ctx, lib_id, name, &params, &mut std::io::stdout()) {
+                    Ok(_) => (),
+                    Err(_) if cancel.is_cancelled() => {
+                        sess.emit(DiagBuilder2::error(format!(
+                            "elaboration of `{}` timed out",
+                            name
+                        )));
+                        failed = true;
+                        break;
+                    }
+                    Err(Error::Internal(msg)) => {
+                        sess.emit(DiagBuilder2::bug(format!(
+                            "elaboration of `{}` failed: {}",
+                            name, msg
+                        )));
+                        failed = true;
+                    }
+                    Err(_) => failed = true,
+                };
+                if sess.error_limit_reached() {
+                    break;
+                }
+            }
+            if sess.failed() {
+                failed = true;
+            }
+        }
+        if matches.is_present("audit-lazy") {
+            vhdl_phases.audit(sess, &[vhdl::trace::Pass::Typeck]);
+        }
+    }
+    // With `--keep-going`, emit the VHDL units that succeeded even if others
+    // failed.
+    if (failed || sess.failed()) && !sess.opts.keep_going {
+        exit(sess, 1);
+    }
+
+    // Emit the module populated by the elaborated VHDL units. The SystemVerilog
+    // units have already been emitted as they were elaborated.
+    if write_vhdl_module(sess, &vhdl_sb, &mut std::io::stdout()).is_err() || failed {
+        exit(sess, 1);
     }
-    session.opts.opt_level = matches.value_of("opt-level").unwrap().parse().unwrap();
 
-    // Invoke the compiler.
-    score(&session, &matches);
+    if sess.failed() {
+        exit(sess, 1);
+    }
 }
 
-fn score(sess: &Session, matches: &ArgMatches) {
+/// Gather the parameter overrides of the elaborated units.
+///
+/// These are the overrides listed in the manifest for the library compiled
+/// into, and the ones given with `-P`. The latter take precedence.
+fn param_overrides(
+    sess: &Session,
+    matches: &ArgMatches,
+    manifest: &[manifest::Library],
+    lib: Name,
+) -> Result<Vec<(Name, Vec<i64>)>, ()> {
     use crate::name::get_name_table;
-    let svlog_arenas = svlog::GlobalArenas::default();
+    let mut params: Vec<_> = manifest
+        .iter()
+        .filter(|library| get_name_table().intern(&library.name, true) == lib)
+        .flat_map(|library| library.params.iter())
+        .map(|(name, values)| (get_name_table().intern(name, true), values.clone()))
+        .collect();
+    let mut failed = false;
+    for arg in matches.values_of("param").into_iter().flat_map(|v| v) {
+        match parse_param_sweep(arg) {
+            Ok((name, values)) => {
+                params.retain(|&(n, _)| n != name);
+                params.push((name, values));
+            }
+            Err(()) => {
+                sess.emit(
+                    DiagBuilder2::error(format!("invalid parameter override `{}`", arg))
+                        .add_note("Overrides must be of the form `NAME=VALUE[,VALUE...]`"),
+                );
+                failed = true;
+            }
+        }
+    }
+    if failed {
+        Err(())
+    } else {
+        Ok(params)
+    }
+}
 
-    // Prepare a list of include paths.
-    let include_paths: Vec<_> = match matches.values_of("inc") {
-        Some(args) => args.map(|x| std::path::Path::new(x)).collect(),
-        None => Vec::new(),
-    };
+/// Parse a parameter override of the form `NAME=VALUE[,VALUE...]`.
+fn parse_param_sweep(arg: &str) -> Result<(Name, Vec<i64>), ()> {
+    use self::name::get_name_table;
+    let mut iter = arg.splitn(2, '=');
+    let name = iter.next().unwrap().trim();
+    let values = iter.next().ok_or(())?;
+    if name.is_empty() {
+        return Err(());
+    }
+    let values = values
+        .split(',')
+        .map(|v| v.trim().parse().map_err(|_| ()))
+        .collect::<Result<Vec<_>, ()>>()?;
+    Ok((get_name_table().intern(name, true), values))
+}
 
-    let defines: Vec<_> = match matches.values_of("def") {
-        Some(args) => args
-            .map(|x| {
-                let mut iter = x.split("=");
-                (iter.next().unwrap(), iter.next())
+/// Expand parameter overrides into the cartesian product of their values.
+///
+/// Each entry of the result is one configuration to elaborate.
+fn param_configs(params: &[(Name, Vec<i64>)]) -> Vec<Vec<(Name, i64)>> {
+    let mut configs = vec![vec![]];
+    for &(name, ref values) in params {
+        configs = configs
+            .into_iter()
+            .flat_map(|config| {
+                values.iter().map(move |&v| {
+                    let mut config = config.clone();
+                    config.push((name, v));
+                    config
+                })
             })
-            .collect(),
-        None => Vec::new(),
-    };
+            .collect();
+    }
+    configs
+}
 
-    // Establish into which library the entities will be compiled. Later on this
-    // should be made configurable per entity.
-    let lib = get_name_table().intern(matches.value_of("lib").unwrap_or("work"), true);
+/// The include paths given with `-I`.
+fn include_paths<'a>(matches: &'a ArgMatches) -> Vec<&'a Path> {
+    matches
+        .values_of("inc")
+        .into_iter()
+        .flatten()
+        .map(Path::new)
+        .collect()
+}
 
-    // Parse the input files.
+/// The macro definitions given with `-D`, as `NAME` or `NAME=VALUE`.
+fn defines<'a>(matches: &'a ArgMatches) -> Vec<(&'a str, Option<&'a str>)> {
+    matches
+        .values_of("def")
+        .into_iter()
+        .flatten()
+        .map(|x| {
+            let mut iter = x.split("=");
+            (iter.next().unwrap(), iter.next())
+        })
+        .collect()
+}
+
+/// The include paths and macro definitions to parse the files of a manifest
+/// library with. The library's include paths are searched before, and its
+/// defines override, those given on the command line.
+fn library_options<'a>(
+    library: &'a manifest::Library,
+    include_paths: &[&'a Path],
+    defines: &[(&'a str, Option<&'a str>)],
+) -> (Vec<&'a Path>, Vec<(&'a str, Option<&'a str>)>) {
+    let lib_include_paths = library
+        .include_paths
+        .iter()
+        .map(|p| p.as_path())
+        .chain(include_paths.iter().cloned())
+        .collect();
+    let lib_defines = defines
+        .iter()
+        .cloned()
+        .chain(
+            library
+                .defines
+                .iter()
+                .map(|(name, value)| (name.as_str(), value.as_ref().map(String::as_str))),
+        )
+        .collect();
+    (lib_include_paths, lib_defines)
+}
+
+/// Parse input files, preprocessing SystemVerilog files with the given
+/// include paths and macro definitions.
+///
+/// With `-E`, the preprocessed SystemVerilog files are printed instead of
+/// being parsed. Returns an error if any of the files is malformed, after all
+/// of them have been parsed.
+fn parse_files<'a>(
+    sess: &Session,
+    matches: &ArgMatches,
+    files: Vec<(String, Language)>,
+    include_paths: &[&Path],
+    defines: &[(&str, Option<&str>)],
+    arena: &'a svlog::ast::Arena<'a>,
+) -> Result<Vec<score::Ast<'a>>, ()> {
     let mut failed = false;
     let mut asts = Vec::new();
-    for filename in matches.values_of("INPUT").unwrap() {
-        // Detect the file type.
-        let language = match Path::new(&filename).extension().and_then(|s| s.to_str()) {
-            Some("sv") | Some("svh") => Language::SystemVerilog,
-            Some("v") | Some("vh") => Language::Verilog,
-            Some("vhd") | Some("vhdl") => Language::Vhdl,
-            Some(ext) => {
-                sess.emit(
-                    DiagBuilder2::warning(format!("ignoring `{}`", filename)).add_note(format!(
-                        "Cannot determine language from extension `.{}`",
-                        ext
-                    )),
-                );
-                continue;
-            }
-            None => {
-                sess.emit(
-                    DiagBuilder2::warning(format!("ignoring `{}`", filename)).add_note(format!(
-                        "No file extension that can be used to guess language"
-                    )),
-                );
-                continue;
-            }
-        };
-
+    for (filename, language) in files {
         // Add the file to the source manager.
         let sm = source::get_source_manager();
         let source = match sm.open(&filename) {
             Some(s) => s,
-            None => panic!("Unable to open input file '{}'", filename),
+            None => {
+                sess.emit(DiagBuilder2::error(format!("cannot open `{}`", filename)));
+                failed = true;
+                continue;
+            }
         };
 
         // Parse the file.
         match language {
             Language::SystemVerilog | Language::Verilog => {
-                let preproc = svlog::preproc::Preprocessor::new(source, &include_paths, &defines);
+                let preproc = svlog::preproc::Preprocessor::new(source, include_paths, defines);
                 if matches.is_present("preproc") {
                     for token in preproc {
                         print!(
@@ -247,7 +2422,7 @@ fn score(sess: &Session, matches: &ArgMatches) {
                 }
 
                 let lexer = svlog::lexer::Lexer::new(preproc);
-                match svlog::parser::parse(lexer, &svlog_arenas.ast) {
+                match svlog::parser::parse(lexer, arena) {
                     Ok(x) => asts.push(score::Ast::Svlog(x)),
                     Err(()) => failed = true,
                 }
@@ -258,86 +2433,207 @@ fn score(sess: &Session, matches: &ArgMatches) {
             },
         }
     }
-    if failed || sess.failed() {
-        std::process::exit(1);
-    }
-    if matches.is_present("preproc") {
-        return;
+    if failed {
+        Err(())
+    } else {
+        Ok(asts)
     }
+}
 
-    // Dump the AST if so requested.
-    if matches.is_present("dump-ast") {
-        println!("{:#99?}", asts);
+/// Read the manifest given with `--manifest`, if any.
+fn read_manifest(sess: &Session, matches: &ArgMatches) -> Vec<manifest::Library> {
+    let path = match matches.value_of("manifest") {
+        Some(path) => Path::new(path),
+        None => return vec![],
+    };
+    let text = match std::fs::read_to_string(path) {
+        Ok(x) => x,
+        Err(e) => {
+            sess.emit(DiagBuilder2::fatal(format!(
+                "cannot read `{}`: {}",
+                path.display(),
+                e
+            )));
+            exit(sess, 1);
+        }
+    };
+    let dir = path.parent().unwrap_or_else(|| Path::new("."));
+    match manifest::parse_manifest(&text, dir) {
+        Ok(x) => x,
+        Err(msg) => {
+            sess.emit(DiagBuilder2::fatal(format!(
+                "invalid manifest `{}`: {}",
+                path.display(),
+                msg
+            )));
+            exit(sess, 1);
+        }
     }
+}
 
-    if matches.is_present("emit_pkgs") {
-        vhdl::debug::emit_pkgs(
-            sess,
-            asts.iter()
-                .flat_map(|ast| match *ast {
-                    score::Ast::Vhdl(ref x) => x.iter(),
-                    _ => [].iter(),
-                })
-                .collect(),
-        );
+/// Expand the input files, directories, and glob patterns on the command line
+/// and determine the language of each file.
+fn input_files(sess: &Session, matches: &ArgMatches) -> Vec<(String, Language)> {
+    expand_files(
+        sess,
+        matches,
+        matches.values_of("INPUT").into_iter().flatten(),
+    )
+}
+
+/// Expand input files, directories, and glob patterns into the files to
+/// compile, warning about files whose language cannot be determined.
+fn expand_files<'a>(
+    sess: &Session,
+    matches: &ArgMatches,
+    inputs: impl IntoIterator<Item = &'a str>,
+) -> Vec<(String, Language)> {
+    let map = language_map(sess, matches);
+    let mut files = vec![];
+    for input in inputs::expand_inputs(inputs, &map, sess) {
+        let filename = input.path.to_string_lossy().into_owned();
+        match (input.language, input.path.extension()) {
+            (Some(language), _) => files.push((filename, language)),
+            (None, Some(ext)) => sess.emit(
+                DiagBuilder2::warning(format!("ignoring `{}`", filename)).add_note(format!(
+                    "Cannot determine language from extension `.{}`",
+                    ext.to_string_lossy()
+                )),
+            ),
+            (None, None) => sess.emit(
+                DiagBuilder2::warning(format!("ignoring `{}`", filename)).add_note(format!(
+                    "No file extension that can be used to guess language"
+                )),
+            ),
+        }
     }
+    files
+}
 
-    // Stop processing if requested.
-    if matches.is_present("check-syntax") {
-        std::process::exit(0);
+/// Assemble the mapping from file extensions to languages, taking the
+/// `--lang-map` options into account.
+fn language_map(sess: &Session, matches: &ArgMatches) -> inputs::LanguageMap {
+    let mut map = inputs::LanguageMap::default();
+    for mapping in matches.values_of("lang-map").into_iter().flat_map(|v| v) {
+        if map.add(mapping).is_err() {
+            sess.emit(
+                DiagBuilder2::error(format!("invalid language mapping `{}`", mapping))
+                    .add_note("Mappings must be of the form `EXT=LANG`"),
+            );
+        }
     }
+    map
+}
 
-    // Create the scoreboard and add the initial map of libraries.
-    let arenas = score::Arenas::new();
-    let sb = ScoreBoard::new(&arenas);
-    let vhdl_sb = vhdl::score::ScoreBoard::new(&arenas.vhdl);
-    let svlog_sb = svlog::GlobalContext::new(&sess, &svlog_arenas);
+/// Parse the sources of a standard library such as `ieee` or `std`.
+///
+/// Selects the variant of the library that matches the VHDL standard revision
+/// requested on the command line.
+fn parse_std_library<'a>(
+    sess: &Session,
+    name: Name,
+    root: &Path,
+) -> Result<Vec<score::Ast<'a>>, ()> {
+    let std = sess.opts.vhdl_std;
+    if !root.is_dir() {
+        sess.emit(DiagBuilder2::error(format!(
+            "library path `{}` is not a directory",
+            root.display()
+        )));
+        return Err(());
+    }
+    let dir = match stdlib::select_variant(root, std) {
+        Some(dir) => dir,
+        None => {
+            sess.emit(
+                DiagBuilder2::error(format!(
+                    "no variant of library `{}` for VHDL-{} in `{}`",
+                    name,
+                    std.year(),
+                    root.display()
+                ))
+                .add_note(format!(
+                    "Found variants for: {}",
+                    stdlib::find_variants(root)
+                        .iter()
+                        .map(|v| format!("VHDL-{}", v.std.year()))
+                        .collect::<Vec<_>>()
+                        .join(", ")
+                )),
+            );
+            return Err(());
+        }
+    };
+    debug!("using `{}` sources from {}", name, dir.display());
 
-    // Elaborate the requested entities or modules.
-    {
-        let vhdl_phases = vhdl::lazy::LazyPhaseTable::new(&vhdl_sb);
-        let ctx = ScoreContext {
-            sess: sess,
-            sb: &sb,
-            vhdl: &vhdl_sb,
-            vhdl_phases: &vhdl_phases,
-            svlog: &svlog_sb,
-        };
-        let lib_id = ctx.add_library(lib, &asts);
-        if let Some(names) = matches.values_of("elaborate") {
-            debug!("lib_id = {:?}", lib_id);
-            debug!("{:?}", sb);
-            for name in names {
-                match elaborate_name(&ctx, lib_id, name) {
-                    Ok(_) => (),
-                    Err(_) => failed = true,
-                };
-            }
-            if sess.failed() {
-                failed = true;
+    let dir = dir.to_string_lossy().into_owned();
+    let map = inputs::LanguageMap::default();
+    let mut asts = Vec::new();
+    let mut failed = false;
+    for input in inputs::expand_inputs(Some(dir.as_str()), &map, sess) {
+        if input.language != Some(Language::Vhdl) {
+            continue;
+        }
+        let source = match source::get_source_manager().open(&input.path.to_string_lossy()) {
+            Some(s) => s,
+            None => {
+                sess.emit(DiagBuilder2::fatal(format!(
+                    "cannot open library file `{}`",
+                    input.path.display()
+                )));
+                return Err(());
             }
+        };
+        match vhdl::syntax::parse(source) {
+            Ok(x) => asts.push(score::Ast::Vhdl(x)),
+            Err(()) => failed = true,
         }
     }
-    if failed || sess.failed() {
-        std::process::exit(1);
+    if failed {
+        Err(())
+    } else {
+        Ok(asts)
     }
+}
 
-    // Extract the populated LLHD modules from the scoreboards and link them
-    // together.
-    let _vhdl_module = vhdl_sb.llmod.into_inner();
-
-    // Emit the module.
-    // TODO: Re-enable this once the VHDL crate has been moved over to llhd v0.8.
-    // llhd::assembly::write_module(&mut std::io::stdout().lock(), &vhdl_module);
-
-    if sess.failed() {
-        std::process::exit(1);
+/// Select a design unit and its dependencies among the parsed inputs.
+fn select_unit<'a>(
+    sess: &Session,
+    asts: &'a [score::Ast<'a>],
+    lib: Name,
+    unit: &str,
+) -> Result<Vec<score::Ast<'a>>, ()> {
+    let (unit_lib, name, _) = parse_elaborate_name(unit)?;
+    if let Some(unit_lib) = unit_lib {
+        if unit_lib != lib {
+            sess.emit(
+                DiagBuilder2::error(format!("unit `{}` is not in library `{}`", unit, lib))
+                    .add_note("Use `--lib` to compile into a different library"),
+            );
+            return Err(());
+        }
+    }
+    match select::select_unit_deps(asts, name) {
+        Some(asts) => Ok(asts),
+        None => {
+            sess.emit(DiagBuilder2::error(format!(
+                "unit `{}` not found in the inputs",
+                unit
+            )));
+            Err(())
+        }
     }
 }
 
 /// Resolve an entity/module specificaiton of the form `[lib.]entity[.arch]` for
-/// elaboration.
-fn elaborate_name(ctx: &ScoreContext, lib_id: score::LibRef, input_name: &str) -> Result<(), ()> {
+/// elaboration, and write the generated code to `out`.
+fn elaborate_name(
+    ctx: &ScoreContext,
+    lib_id: score::LibRef,
+    input_name: &str,
+    params: &[(Name, Vec<i64>)],
+    out: &mut dyn std::io::Write,
+) -> Result<(), Error> {
     let (lib, name, arch) = parse_elaborate_name(input_name)?;
     debug!(
         "parsed `{}` into (lib: {:?}, name: {:?}, arch: {:?})",
@@ -360,7 +2656,7 @@ fn elaborate_name(ctx: &ScoreContext, lib_id: score::LibRef, input_name: &str) -
                         d = d.add_note(format!("- {}", name));
                     }
                     ctx.sess.emit(d);
-                    return Err(());
+                    return Err(Error::Reported);
                 }
             }
         } else {
@@ -376,7 +2672,7 @@ fn elaborate_name(ctx: &ScoreContext, lib_id: score::LibRef, input_name: &str) -
     enum Elaborate {
         VhdlEntity(vhdl::score::EntityRef, vhdl::score::ArchRef),
         VhdlPkg(vhdl::score::PkgDeclRef),
-        Svlog(NodeId), // TODO: handle svlog case
+        Svlog(Vec<NodeId>),
     };
     let defs = ctx.defs(lib.into())?;
     let elab = match defs.get(&name) {
@@ -395,7 +2691,7 @@ fn elaborate_name(ctx: &ScoreContext, lib_id: score::LibRef, input_name: &str) -
                             "`{}` is not an architecture of entity `{}`",
                             arch, name
                         )));
-                        return Err(());
+                        return Err(Error::Reported);
                     }
                 }
             } else {
@@ -406,14 +2702,18 @@ fn elaborate_name(ctx: &ScoreContext, lib_id: score::LibRef, input_name: &str) -
                             "Entity `{}` has no architecture defined",
                             name
                         )));
-                        return Err(());
+                        return Err(Error::Reported);
                     }
                 }
             };
             Elaborate::VhdlEntity(entity, arch_ref)
         }
         Some(&score::Def::Vhdl(vhdl::score::Def::Pkg(p))) => Elaborate::VhdlPkg(p),
-        Some(&score::Def::Svlog(e)) => Elaborate::Svlog(e),
+        Some(&score::Def::Svlog(e)) => Elaborate::Svlog(vec![e]),
+        _ if ctx.svlog.find_config(name).is_some() => {
+            let config = ctx.svlog.find_config(name).unwrap();
+            Elaborate::Svlog(ctx.svlog.set_config(config)?)
+        }
         _ => {
             let mut d = DiagBuilder2::error(format!("Item `{}` does not exist", name))
                 .add_note("The following items are defined:");
@@ -423,18 +2723,68 @@ fn elaborate_name(ctx: &ScoreContext, lib_id: score::LibRef, input_name: &str) -
                 d = d.add_note(format!("- {}", name));
             }
             ctx.sess.emit(d);
-            return Err(());
+            return Err(Error::Reported);
         }
     };
     debug!("elaborating {:?}", elab);
 
     // Generate the LLHD definition for whatever we're elaborating.
     match elab {
+        Elaborate::VhdlPkg(..) if !params.is_empty() => {
+            ctx.sess.emit(DiagBuilder2::error(format!(
+                "`{}` is a package, which has no generics to override",
+                input_name
+            )));
+            return Err(Error::Reported);
+        }
         Elaborate::VhdlEntity(_entity, arch) => {
-            // let decl = ctx.vhdl.lldecl(arch);
-            // println!("Architecture declared as {:?}", decl);
-            let def = ctx.vhdl().lldef(arch)?;
-            eprintln!("Architecture declared as {:?}", def);
+            // Specialize the architecture once per generic configuration, which
+            // gives each of them a distinct entity. With `--keep-going`, carry
+            // on with the remaining configurations if one of them fails.
+            let keep_going = ctx.sess.opts.keep_going;
+            let mut failed_units = vec![];
+            for config in param_configs(params) {
+                let result = if config.is_empty() {
+                    ctx.vhdl().lldef(arch).map(|_| ())
+                } else {
+                    ctx.vhdl()
+                        .override_generics(arch, &config)
+                        .and_then(|arch| ctx.vhdl().lldef(arch))
+                        .map(|_| ())
+                };
+                match result {
+                    Err(e)
+                        if keep_going
+                            && e != Error::Cancelled
+                            && !ctx.sess.error_limit_reached() =>
+                    {
+                        let config: Vec<_> = config
+                            .iter()
+                            .map(|(name, value)| format!("{}={}", name, value))
+                            .collect();
+                        failed_units.push(if config.is_empty() {
+                            input_name.to_string()
+                        } else {
+                            format!("{} with {}", input_name, config.join(", "))
+                        });
+                    }
+                    result => result?,
+                }
+            }
+
+            // List the units that failed to generate. Their processes are
+            // omitted from the output, since nothing instantiates them.
+            if !failed_units.is_empty() {
+                let mut d = DiagBuilder2::error(format!(
+                    "{} unit(s) failed to generate and are omitted",
+                    failed_units.len()
+                ));
+                for unit in &failed_units {
+                    d = d.add_note(format!("- {}", unit));
+                }
+                ctx.sess.emit(d);
+                return Err(Error::Reported);
+            }
         }
         Elaborate::VhdlPkg(pkg) => {
             use moore::vhdl::typeck::{Typeck, TypeckContext};
@@ -444,37 +2794,305 @@ fn elaborate_name(ctx: &ScoreContext, lib_id: score::LibRef, input_name: &str) -
             // use moore::vhdl::codegen::Codegen;
             // ctx.vhdl().codegen(pkg, &mut ())?;
         }
-        Elaborate::Svlog(m) => {
+        Elaborate::Svlog(tops) => {
             // Emit the detailed type analysis if requested.
             if ctx.sess.has_verbosity(Verbosity::TYPES) {
                 use svlog::BaseContext;
-                TypeVerbosityVisitor(ctx.svlog, ctx.svlog.default_param_env())
-                    .visit_node_with_id(m, false);
+                for &m in &tops {
+                    TypeVerbosityVisitor(ctx.svlog, ctx.svlog.default_param_env())
+                        .visit_node_with_id(m, false);
+                }
             }
 
             // Emit the instantiation details if requested.
             if ctx.sess.has_verbosity(Verbosity::INSTS) {
-                svlog::InstVerbosityVisitor::new(ctx.svlog).visit_node_with_id(m, false);
+                for &m in &tops {
+                    svlog::InstVerbosityVisitor::new(ctx.svlog).visit_node_with_id(m, false);
+                }
             }
 
+            // Emit one entity per parameter configuration. Entities elaborated
+            // with overridden parameters receive distinct names.
+            // With `--keep-going`, carry on with the remaining tops if one
+            // of them fails.
             let mut cg = svlog::CodeGenerator::new(ctx.svlog);
-            cg.emit_module(m)?;
-            let mut module = cg.finalize();
-            let pass_ctx = PassContext;
-            if ctx.sess.opts.opt_level > 0 {
-                llhd::pass::ConstFolding::run_on_module(&pass_ctx, &mut module);
-                // llhd::pass::VarToPhiPromotion::run_on_module(&pass_ctx, &mut module); // broken in llhd 0.13
-                llhd::pass::DeadCodeElim::run_on_module(&pass_ctx, &mut module);
-                llhd::pass::GlobalCommonSubexprElim::run_on_module(&pass_ctx, &mut module);
-                llhd::pass::InstSimplification::run_on_module(&pass_ctx, &mut module);
-                llhd::pass::DeadCodeElim::run_on_module(&pass_ctx, &mut module);
+            let keep_going = ctx.sess.opts.keep_going;
+            let mut failed = false;
+            for &m in &tops {
+                let result = if params.is_empty() {
+                    cg.emit_module(m).map(|_| ())
+                } else {
+                    param_configs(params).iter().try_for_each(|config| {
+                        let env = svlog::param_env_from_overrides(ctx.svlog, m, config)?;
+                        cg.emit_module_with_env(m, env).map(|_| ())
+                    })
+                };
+                match result {
+                    Err(e)
+                        if keep_going
+                            && e != Error::Cancelled
+                            && !ctx.sess.error_limit_reached() =>
+                    {
+                        failed = true
+                    }
+                    result => result?,
+                }
+            }
+            let directives = cg.directives().to_vec();
+            let sources = cg.sources().to_vec();
+            let attributes = cg.attributes().to_vec();
+            let failed_units = cg.failed_units().to_vec();
+            let module = cg.finalize();
+
+            // List the units that failed to generate, such that they can be
+            // brought up one by one.
+            if !failed_units.is_empty() {
+                let mut d = DiagBuilder2::error(format!(
+                    "{} unit(s) failed to generate and are only declared",
+                    failed_units.len()
+                ));
+                for unit in &failed_units {
+                    d = d.add_note(format!("- {}", unit));
+                }
+                ctx.sess.emit(d);
+                failed = true;
+            }
+
+            // Pass the verification directives, synthesis attributes, and
+            // source locations on to downstream tools as comments in the
+            // assembly.
+            let mut notes: Vec<_> = directives
+                .iter()
+                .map(|d| (d.entity.clone(), d.to_string()))
+                .collect();
+            notes.extend(attributes.iter().map(|a| (a.unit.clone(), a.to_string())));
+            if ctx.sess.opts.debug_info {
+                notes.extend(sources.iter().map(|s| (s.unit.clone(), s.to_string())));
+            }
+            emit_design(ctx.sess, module, &directives, notes, out)?;
+            if failed {
+                return Err(Error::Reported);
             }
-            llhd::assembly::write_module(&mut std::io::stdout().lock(), &module);
         }
     }
     Ok(())
 }
 
+/// Optimize an elaborated design and write it to `out` in the requested
+/// format.
+///
+/// The notes are emitted as comments before the units they refer to, and are
+/// kept in snapshots written with `--save-snapshot`.
+fn emit_design(
+    sess: &Session,
+    mut module: llhd::ir::Module,
+    directives: &[svlog::Directive],
+    mut notes: Vec<(llhd::ir::UnitName, String)>,
+    out: &mut dyn std::io::Write,
+) -> Result<(), Error> {
+    // VarToPhiPromotion is not offered since it is broken in llhd 0.13.
+    let pass_ctx = PassContext;
+    for pass in sess.opts.llhd_passes() {
+        debug!("Running LLHD pass `{}`", pass);
+        match pass {
+            LlhdPass::ConstFolding => {
+                llhd::pass::ConstFolding::run_on_module(&pass_ctx, &mut module)
+            }
+            LlhdPass::DeadCodeElim => {
+                llhd::pass::DeadCodeElim::run_on_module(&pass_ctx, &mut module)
+            }
+            LlhdPass::CommonSubexprElim => {
+                llhd::pass::GlobalCommonSubexprElim::run_on_module(&pass_ctx, &mut module)
+            }
+            LlhdPass::InstSimplification => {
+                llhd::pass::InstSimplification::run_on_module(&pass_ctx, &mut module)
+            }
+            LlhdPass::ControlFlowSimplification => {
+                llhd::pass::ControlFlowSimplification::run_on_module(&pass_ctx, &mut module)
+            }
+            LlhdPass::EarlyCodeMotion => {
+                llhd::pass::EarlyCodeMotion::run_on_module(&pass_ctx, &mut module)
+            }
+            LlhdPass::TemporalCodeMotion => {
+                llhd::pass::TemporalCodeMotion::run_on_module(&pass_ctx, &mut module)
+            }
+            LlhdPass::Desequentialization => {
+                llhd::pass::Desequentialization::run_on_module(&pass_ctx, &mut module)
+            }
+            LlhdPass::ProcessLowering => {
+                llhd::pass::ProcessLowering::run_on_module(&pass_ctx, &mut module)
+            }
+        };
+    }
+
+    // Save the design for later runs if requested.
+    if let Some(ref path) = sess.opts.save_snapshot {
+        let text = moore::annotate::write_module(&module, &notes);
+        if let Err(e) = std::fs::write(path, text) {
+            sess.emit(DiagBuilder2::error(format!(
+                "cannot write snapshot `{}`: {}",
+                path, e
+            )));
+            return Err(Error::Reported);
+        }
+    }
+
+    if sess.opts.size_report {
+        let report = moore::size_report::SizeReport::new(&module);
+        eprint!("{}", report);
+        if report.any_flagged() {
+            sess.emit(
+                DiagBuilder2::warning("some units are unusually large")
+                    .add_note("Generate loops or arrays may have been unrolled by accident"),
+            );
+        }
+    }
+
+    // Emit the combinational cone of a signal instead of the assembly if
+    // requested.
+    if sess.opts.emit == EmitFormat::Smt {
+        let cone = sess.opts.cone.as_ref().unwrap();
+        if directives
+            .iter()
+            .any(|d| d.label.map_or(false, |l| l.to_string() == *cone))
+        {
+            sess.emit(
+                DiagBuilder2::error(format!("cannot emit the cone of `{}`", cone))
+                    .add_note("The cones of assertions are not supported yet")
+                    .add_note("Assign the asserted expression to a signal instead"),
+            );
+            return Err(Error::Reported);
+        }
+        match moore::smt::emit(&module, cone) {
+            Ok(text) => write_output(sess, out, &text)?,
+            Err(msg) => {
+                sess.emit(DiagBuilder2::error(format!(
+                    "cannot emit the cone of `{}`: {}",
+                    cone, msg
+                )));
+                return Err(Error::Reported);
+            }
+        }
+        return Ok(());
+    }
+
+    // Emit the bit-blasted design instead of the assembly if requested.
+    if sess.opts.emit == EmitFormat::Aiger {
+        match moore::aiger::export(&module) {
+            Ok(text) => write_output(sess, out, &text)?,
+            Err(issues) => {
+                let mut d = DiagBuilder2::error("cannot export the design to AIGER")
+                    .add_note("The following constructs are outside the synthesizable subset:");
+                for issue in issues {
+                    d = d.add_note(format!("- {}", issue));
+                }
+                sess.emit(d);
+                return Err(Error::Reported);
+            }
+        }
+        return Ok(());
+    }
+
+    // Emit bindings for the ports of the top-level entity instead of the
+    // assembly if requested.
+    if sess.opts.emit == EmitFormat::CHeader || sess.opts.emit == EmitFormat::Rust {
+        let lang = if sess.opts.emit == EmitFormat::CHeader {
+            moore::bindings::Lang::C
+        } else {
+            moore::bindings::Lang::Rust
+        };
+        match moore::bindings::export(&module, lang) {
+            Ok(text) => write_output(sess, out, &text)?,
+            Err(issues) => {
+                let mut d = DiagBuilder2::error("cannot generate bindings for the top-level ports");
+                for issue in issues {
+                    d = d.add_note(issue.to_string());
+                }
+                sess.emit(d);
+                return Err(Error::Reported);
+            }
+        }
+        return Ok(());
+    }
+
+    // Pass the inferred memories on to downstream tools as comments in the
+    // assembly.
+    if sess.opts.infer_memories {
+        let memories = moore::mem_infer::infer(&module);
+        notes.extend(moore::mem_infer::notes(&memories));
+    }
+    if notes.is_empty() {
+        write_output(sess, out, &llhd::assembly::write_module_string(&module))
+    } else {
+        write_output(sess, out, &moore::annotate::write_module(&module, &notes))
+    }
+}
+
+/// Write the LLHD module populated by the elaborated VHDL units to `out`, with
+/// the verification directives of every entity listed before it.
+fn write_vhdl_module(
+    sess: &Session,
+    vhdl: &vhdl::score::ScoreBoard,
+    out: &mut dyn std::io::Write,
+) -> Result<(), Error> {
+    let module = vhdl.llmod.borrow();
+    if module.values().len() == 0 {
+        return Ok(());
+    }
+    let directives = vhdl.directives.borrow();
+    write_output(
+        sess,
+        out,
+        &vhdl::codegen::write_annotated(&module, &directives),
+    )
+}
+
+/// Write generated code to the output of the compiler.
+fn write_output(sess: &Session, out: &mut dyn std::io::Write, text: &str) -> Result<(), Error> {
+    match out.write_all(text.as_bytes()).and_then(|_| out.flush()) {
+        Ok(()) => Ok(()),
+        Err(e) => {
+            sess.emit(DiagBuilder2::fatal(format!("cannot write output: {}", e)));
+            Err(Error::Reported)
+        }
+    }
+}
+
+/// Load a design saved with `--save-snapshot` and emit it without parsing and
+/// elaborating the sources again.
+fn load_snapshot(sess: &Session, path: &str) {
+    let (module, notes) = read_snapshot(sess, path);
+    if emit_design(sess, module, &[], notes, &mut std::io::stdout()).is_err() {
+        exit(sess, 1);
+    }
+}
+
+/// Read a design saved with `--save-snapshot`, exiting on failure.
+fn read_snapshot(
+    sess: &Session,
+    path: &str,
+) -> (llhd::ir::Module, Vec<(llhd::ir::UnitName, String)>) {
+    let text = match std::fs::read_to_string(path) {
+        Ok(text) => text,
+        Err(e) => {
+            sess.emit(DiagBuilder2::error(format!(
+                "cannot read snapshot `{}`: {}",
+                path, e
+            )));
+            exit(sess, 1);
+        }
+    };
+    match moore::annotate::read_module(&text) {
+        Ok(x) => x,
+        Err(msg) => {
+            sess.emit(
+                DiagBuilder2::error(format!("cannot load snapshot `{}`", path)).add_note(msg),
+            );
+            exit(sess, 1);
+        }
+    }
+}
+
 /// Parse an entity name of the form `(first\.)?second((arch))?` for
 /// elaboration.
 fn parse_elaborate_name<S: AsRef<str>>(name: S) -> Result<(Option<Name>, Name, Option<Name>), ()> {
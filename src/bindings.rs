@@ -0,0 +1,344 @@
+// Copyright (c) 2016-2020 Fabian Schuiki
+
+//! C and Rust bindings for the ports of the top-level entity.
+//!
+//! The bindings declare one struct holding the values of all input ports and
+//! one holding the values of all output ports, in the order of the ports, as
+//! well as a constant with the bit width of each port. Software driving the
+//! design through a cosimulation or an FPGA shell can use them instead of a
+//! hand-written copy of the port list.
+//!
+//! Ports of up to 64 bits are stored in the smallest of the unsigned 8, 16, 32,
+//! and 64 bit integer types that fits. Wider ports are stored as an array of
+//! 32 bit words, the least significant word first. Ports of array or struct
+//! type are flattened into a bit vector, the first element occupying the least
+//! significant bits. The C structs and the `#[repr(C)]` Rust structs have the
+//! same layout.
+
+use crate::aiger::{find_top, Issue};
+use llhd::ir::{Module, Value};
+use llhd::{Type, TypeKind};
+use std::fmt::Write;
+
+/// The language of the bindings.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Lang {
+    /// A C header.
+    C,
+    /// A Rust module.
+    Rust,
+}
+
+/// A port of the top-level entity.
+struct TopPort {
+    name: String,
+    width: usize,
+}
+
+/// Generate bindings for the ports of the top-level entity of a module.
+///
+/// Returns the ports without a fixed bit width, such as ports of time type, if
+/// there are any.
+pub fn export(module: &Module, lang: Lang) -> Result<String, Vec<Issue>> {
+    let top = find_top(module)?;
+    let mut issues = vec![];
+    let mut ports = |args: Vec<Value>| -> Vec<TopPort> {
+        let mut ports = vec![];
+        for (i, arg) in args.into_iter().enumerate() {
+            let name = match top.get_name(arg) {
+                Some(name) => name.to_string(),
+                None => format!("port{}", i),
+            };
+            let ty = top.value_type(arg);
+            let ty = if ty.is_signal() {
+                ty.unwrap_signal()
+            } else {
+                &ty
+            };
+            match bit_width(ty) {
+                Some(width) => ports.push(TopPort { name, width }),
+                None => issues.push(Issue {
+                    unit: top.name().clone(),
+                    message: format!("port `{}` of type {} has no fixed bit width", name, ty),
+                }),
+            }
+        }
+        ports
+    };
+    let inputs = ports(top.input_args().collect());
+    let outputs = ports(top.output_args().collect());
+    if !issues.is_empty() {
+        return Err(issues);
+    }
+
+    let name = identifier(top.name().to_string().trim_start_matches('@'));
+    Ok(match lang {
+        Lang::C => render_c(&name, &inputs, &outputs),
+        Lang::Rust => render_rust(&name, &inputs, &outputs),
+    })
+}
+
+/// The number of bits needed to store a value of a type.
+fn bit_width(ty: &Type) -> Option<usize> {
+    match **ty {
+        TypeKind::IntType(width) => Some(width),
+        TypeKind::EnumType(states) => {
+            let mut width = 0;
+            while 1 << width < states {
+                width += 1;
+            }
+            Some(width)
+        }
+        TypeKind::ArrayType(len, ref elem) => bit_width(elem).map(|w| len * w),
+        TypeKind::StructType(ref fields) => fields.iter().map(bit_width).sum(),
+        _ => None,
+    }
+}
+
+/// The storage of a port in the bindings.
+enum Storage {
+    /// An unsigned integer with the given number of bits.
+    Scalar(usize),
+    /// An array with the given number of 32 bit words.
+    Words(usize),
+}
+
+impl Storage {
+    fn new(width: usize) -> Self {
+        match width {
+            0..=8 => Storage::Scalar(8),
+            9..=16 => Storage::Scalar(16),
+            17..=32 => Storage::Scalar(32),
+            33..=64 => Storage::Scalar(64),
+            _ => Storage::Words((width - 1) / 32 + 1),
+        }
+    }
+}
+
+/// A number of bits in words.
+fn bits(width: usize) -> String {
+    if width == 1 {
+        "1 bit".to_string()
+    } else {
+        format!("{} bits", width)
+    }
+}
+
+/// Turn a unit or port name into an identifier valid in C and Rust.
+fn identifier(name: &str) -> String {
+    let mut id: String = name
+        .chars()
+        .map(|c| if c.is_ascii_alphanumeric() { c } else { '_' })
+        .collect();
+    if id.is_empty() || id.starts_with(|c: char| c.is_ascii_digit()) || is_keyword(&id) {
+        id.insert(0, '_');
+    }
+    id
+}
+
+/// Check if a name is reserved in C or Rust.
+fn is_keyword(name: &str) -> bool {
+    const KEYWORDS: &[&str] = &[
+        "as", "auto", "break", "case", "char", "const", "continue", "crate", "default", "do",
+        "double", "else", "enum", "extern", "false", "float", "fn", "for", "goto", "if", "impl",
+        "in", "int", "let", "long", "loop", "match", "mod", "move", "mut", "pub", "ref",
+        "register", "return", "self", "short", "signed", "sizeof", "static", "struct", "super",
+        "switch", "trait", "true", "type", "typedef", "union", "unsafe", "unsigned", "use", "void",
+        "volatile", "where", "while",
+    ];
+    KEYWORDS.contains(&name)
+}
+
+/// Convert an identifier to `CamelCase`.
+fn camel_case(name: &str) -> String {
+    let mut out = String::new();
+    for part in name.split('_').filter(|p| !p.is_empty()) {
+        let mut chars = part.chars();
+        out.extend(chars.next().map(|c| c.to_ascii_uppercase()));
+        out.extend(chars);
+    }
+    if out.is_empty() || out.starts_with(|c: char| c.is_ascii_digit()) {
+        out.insert(0, '_');
+    }
+    out
+}
+
+fn render_c(name: &str, inputs: &[TopPort], outputs: &[TopPort]) -> String {
+    let mut out = String::new();
+    let guard = format!("{}_PORTS_H", name.to_uppercase());
+    writeln!(out, "/* Generated by moore. */").unwrap();
+    writeln!(out, "#ifndef {}", guard).unwrap();
+    writeln!(out, "#define {}", guard).unwrap();
+    writeln!(out).unwrap();
+    writeln!(out, "#include <stdint.h>").unwrap();
+    writeln!(out).unwrap();
+    for port in inputs.iter().chain(outputs) {
+        writeln!(
+            out,
+            "#define {}_{}_WIDTH {}",
+            name.to_uppercase(),
+            identifier(&port.name).to_uppercase(),
+            port.width
+        )
+        .unwrap();
+    }
+    for (suffix, ports) in &[("inputs", inputs), ("outputs", outputs)] {
+        writeln!(out).unwrap();
+        writeln!(out, "typedef struct {{").unwrap();
+        for port in ports.iter() {
+            let field = identifier(&port.name);
+            match Storage::new(port.width) {
+                Storage::Scalar(bits) => {
+                    write!(out, "    uint{}_t {};", bits, field).unwrap();
+                }
+                Storage::Words(words) => {
+                    write!(out, "    uint32_t {}[{}];", field, words).unwrap();
+                }
+            }
+            writeln!(out, " /* {} */", bits(port.width)).unwrap();
+        }
+        if ports.is_empty() {
+            writeln!(out, "    uint8_t _unused;").unwrap();
+        }
+        writeln!(out, "}} {}_{}_t;", name, suffix).unwrap();
+    }
+    writeln!(out).unwrap();
+    writeln!(out, "#endif /* {} */", guard).unwrap();
+    out
+}
+
+fn render_rust(name: &str, inputs: &[TopPort], outputs: &[TopPort]) -> String {
+    let mut out = String::new();
+    writeln!(out, "// Generated by moore.").unwrap();
+    writeln!(out, "//! Ports of the `{}` entity.", name).unwrap();
+    writeln!(out).unwrap();
+    for port in inputs.iter().chain(outputs) {
+        writeln!(
+            out,
+            "pub const {}_WIDTH: usize = {};",
+            identifier(&port.name).to_uppercase(),
+            port.width
+        )
+        .unwrap();
+    }
+    for (suffix, ports) in &[("Inputs", inputs), ("Outputs", outputs)] {
+        writeln!(out).unwrap();
+        writeln!(out, "#[repr(C)]").unwrap();
+        writeln!(out, "#[derive(Debug, Clone, Copy, PartialEq, Eq)]").unwrap();
+        writeln!(out, "pub struct {}{} {{", camel_case(name), suffix).unwrap();
+        for port in ports.iter() {
+            let field = identifier(&port.name.to_lowercase());
+            writeln!(out, "    /// {}", bits(port.width)).unwrap();
+            match Storage::new(port.width) {
+                Storage::Scalar(bits) => writeln!(out, "    pub {}: u{},", field, bits).unwrap(),
+                Storage::Words(words) => {
+                    writeln!(out, "    pub {}: [u32; {}],", field, words).unwrap()
+                }
+            }
+        }
+        if ports.is_empty() {
+            writeln!(out, "    pub _unused: u8,").unwrap();
+        }
+        writeln!(out, "}}").unwrap();
+    }
+    out
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const TOP: &str =
+        "entity @top (i1$ %clk, i12$ %data, [2 x i4]$ %pair) -> (i70$ %sum, {i3, i5}$ %type) {
+        }";
+
+    fn export_text(text: &str, lang: Lang) -> Result<String, Vec<String>> {
+        let module = llhd::assembly::parse_module(text).unwrap();
+        export(&module, lang).map_err(|issues| issues.iter().map(|i| i.to_string()).collect())
+    }
+
+    #[test]
+    fn names() {
+        assert_eq!(identifier("a.b"), "a_b");
+        assert_eq!(identifier("1x"), "_1x");
+        assert_eq!(identifier("type"), "_type");
+        assert_eq!(camel_case("my_top_2"), "MyTop2");
+        assert_eq!(camel_case("_type"), "Type");
+    }
+
+    #[test]
+    fn c_header() {
+        assert_eq!(
+            export_text(TOP, Lang::C).unwrap(),
+            "/* Generated by moore. */
+#ifndef TOP_PORTS_H
+#define TOP_PORTS_H
+
+#include <stdint.h>
+
+#define TOP_CLK_WIDTH 1
+#define TOP_DATA_WIDTH 12
+#define TOP_PAIR_WIDTH 8
+#define TOP_SUM_WIDTH 70
+#define TOP__TYPE_WIDTH 8
+
+typedef struct {
+    uint8_t clk; /* 1 bit */
+    uint16_t data; /* 12 bits */
+    uint8_t pair; /* 8 bits */
+} top_inputs_t;
+
+typedef struct {
+    uint32_t sum[3]; /* 70 bits */
+    uint8_t _type; /* 8 bits */
+} top_outputs_t;
+
+#endif /* TOP_PORTS_H */
+"
+        );
+    }
+
+    #[test]
+    fn rust_module() {
+        assert_eq!(
+            export_text(TOP, Lang::Rust).unwrap(),
+            "// Generated by moore.
+//! Ports of the `top` entity.
+
+pub const CLK_WIDTH: usize = 1;
+pub const DATA_WIDTH: usize = 12;
+pub const PAIR_WIDTH: usize = 8;
+pub const SUM_WIDTH: usize = 70;
+pub const _TYPE_WIDTH: usize = 8;
+
+#[repr(C)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct TopInputs {
+    /// 1 bit
+    pub clk: u8,
+    /// 12 bits
+    pub data: u16,
+    /// 8 bits
+    pub pair: u8,
+}
+
+#[repr(C)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct TopOutputs {
+    /// 70 bits
+    pub sum: [u32; 3],
+    /// 8 bits
+    pub _type: u8,
+}
+"
+        );
+    }
+
+    #[test]
+    fn ports_without_width() {
+        assert_eq!(
+            export_text("entity @top (time$ %t) -> () {\n}", Lang::C).unwrap_err(),
+            vec!["@top: port `t` of type time has no fixed bit width"]
+        );
+    }
+}
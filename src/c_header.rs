@@ -0,0 +1,522 @@
+// Copyright (c) 2016-2020 Fabian Schuiki
+
+//! Generation of C headers for foreign subprograms.
+//!
+//! SystemVerilog functions and tasks imported from or exported to C via
+//! `"DPI-C"`, and VHDL subprograms with a `VHPIDIRECT` foreign attribute, are
+//! implemented or called on the C side. This module derives the C prototypes
+//! of these subprograms. SystemVerilog types are mapped as prescribed by
+//! Annex H of IEEE 1800, using the types of `svdpi.h`. VHDL types are mapped
+//! following the VHPIDIRECT conventions: integers as `int32_t`, enumerations
+//! such as `boolean` and `std_logic` as `uint8_t` holding the position of the
+//! value, and constrained arrays as pointers to their elements. Subprograms
+//! with a type that has no defined mapping, such as a user-defined type, are
+//! emitted as a comment naming the offending type.
+
+use crate::common::name::Name;
+use crate::svlog::ast as svlog_ast;
+use crate::vhdl::syntax::ast as vhdl_ast;
+use std::collections::HashMap;
+use std::fmt::Write;
+
+/// The C prototype of a foreign subprogram.
+#[derive(Debug, Clone)]
+pub struct Prototype {
+    /// The name of the C function.
+    pub name: String,
+    /// The C return type.
+    pub ret: CType,
+    /// The names and C types of the arguments.
+    pub args: Vec<(String, CType)>,
+    /// A description of the declaration the prototype was derived from.
+    pub origin: String,
+}
+
+/// A C type, or the source text of a type which cannot be mapped to C.
+pub type CType = Result<String, String>;
+
+impl Prototype {
+    /// The source text of the first type that cannot be mapped to C.
+    pub fn unmapped(&self) -> Option<&str> {
+        std::iter::once(&self.ret)
+            .chain(self.args.iter().map(|(_, ty)| ty))
+            .filter_map(|ty| ty.as_ref().err())
+            .map(|s| s.as_str())
+            .next()
+    }
+}
+
+/// Collect the DPI imports and exports in a set of SystemVerilog files.
+pub fn svlog_prototypes(files: &[svlog_ast::SourceFile]) -> Vec<Prototype> {
+    let mut protos = vec![];
+    for file in files {
+        svlog_scope(&file.items, &mut protos);
+    }
+    protos
+}
+
+fn svlog_scope(items: &[svlog_ast::Item], protos: &mut Vec<Prototype>) {
+    // Exports refer to a function or task declared in the same scope.
+    let mut subroutines = HashMap::new();
+    for item in items {
+        if let svlog_ast::ItemData::SubroutineDecl(ref decl) = item.data {
+            subroutines.insert(decl.prototype.name.value, &decl.prototype);
+        }
+    }
+    for item in items {
+        match item.data {
+            svlog_ast::ItemData::ModuleDecl(ref module) => svlog_scope(&module.items, protos),
+            svlog_ast::ItemData::InterfaceDecl(ref intf) => svlog_scope(&intf.items, protos),
+            svlog_ast::ItemData::PackageDecl(ref pkg) => svlog_scope(&pkg.items, protos),
+            svlog_ast::ItemData::DpiDecl(ref decl) => match decl.data {
+                svlog_ast::DpiDeclData::Import {
+                    ref cident,
+                    ref prototype,
+                    ..
+                } => protos.push(svlog_prototype(
+                    prototype,
+                    cident.as_ref().map(|n| n.value),
+                    collapse(&decl.span.extract()),
+                )),
+                svlog_ast::DpiDeclData::Export {
+                    ref cident, name, ..
+                } => {
+                    if let Some(prototype) = subroutines.get(&name.value) {
+                        protos.push(svlog_prototype(
+                            prototype,
+                            cident.as_ref().map(|n| n.value),
+                            collapse(&decl.span.extract()),
+                        ))
+                    }
+                }
+            },
+            _ => (),
+        }
+    }
+}
+
+fn svlog_prototype(
+    proto: &svlog_ast::SubroutinePrototype,
+    cident: Option<Name>,
+    origin: String,
+) -> Prototype {
+    let ret = match proto.kind {
+        // Tasks return whether they were disabled.
+        svlog_ast::SubroutineKind::Task => Ok("int".to_string()),
+        svlog_ast::SubroutineKind::Func => match proto.retty {
+            Some(ref ty) => svlog_return_type(ty),
+            None => Ok("svLogic".to_string()),
+        },
+    };
+
+    // Arguments without a direction inherit the one of the previous argument.
+    let mut dir = svlog_ast::SubroutinePortDir::Input;
+    let mut args = vec![];
+    for (i, arg) in proto.args.iter().enumerate() {
+        dir = arg.dir.unwrap_or(dir);
+        let (name, unpacked) = match arg.name {
+            Some(ref name) => (name.name.value.to_string(), name.dims.as_slice()),
+            None => (format!("arg{}", i), &[][..]),
+        };
+        args.push((name, svlog_arg_type(&arg.ty, unpacked, dir)));
+    }
+
+    Prototype {
+        name: cident.unwrap_or(proto.name.value).to_string(),
+        ret,
+        args,
+        origin,
+    }
+}
+
+/// The C type of a SystemVerilog type without packed dimensions, and whether
+/// it is passed as a vector of `svBitVecVal` or `svLogicVecVal`.
+fn svlog_scalar_type(ty: &svlog_ast::Type) -> Option<(&'static str, bool)> {
+    let unsigned = ty.sign == svlog_ast::TypeSign::Unsigned;
+    Some(match ty.kind.data {
+        svlog_ast::ByteType if unsigned => ("unsigned char", false),
+        svlog_ast::ByteType => ("char", false),
+        svlog_ast::ShortIntType if unsigned => ("unsigned short", false),
+        svlog_ast::ShortIntType => ("short", false),
+        svlog_ast::IntType if unsigned => ("unsigned int", false),
+        svlog_ast::IntType => ("int", false),
+        svlog_ast::LongIntType if unsigned => ("unsigned long long", false),
+        svlog_ast::LongIntType => ("long long", false),
+        svlog_ast::RealType => ("double", false),
+        svlog_ast::ShortRealType => ("float", false),
+        svlog_ast::ChandleType => ("void*", false),
+        svlog_ast::StringType => ("const char*", false),
+        svlog_ast::BitType => ("svBit", false),
+        svlog_ast::LogicType
+        | svlog_ast::RegType
+        | svlog_ast::ImplicitType
+        | svlog_ast::ImplicitSignedType
+        | svlog_ast::ImplicitUnsignedType => ("svLogic", false),
+        svlog_ast::IntegerType | svlog_ast::TimeType => ("svLogicVecVal", true),
+        _ => return None,
+    })
+}
+
+fn svlog_return_type(ty: &svlog_ast::Type) -> CType {
+    if let svlog_ast::VoidType = ty.kind.data {
+        return Ok("void".to_string());
+    }
+    match svlog_scalar_type(ty) {
+        Some((cty, false)) if ty.dims.is_empty() => Ok(cty.to_string()),
+        _ => Err(ty.span.extract()),
+    }
+}
+
+fn svlog_arg_type<'a>(
+    ty: &svlog_ast::Type<'a>,
+    unpacked: &[svlog_ast::TypeDim<'a>],
+    dir: svlog_ast::SubroutinePortDir,
+) -> CType {
+    let input =
+        dir == svlog_ast::SubroutinePortDir::Input || dir == svlog_ast::SubroutinePortDir::ConstRef;
+    let (scalar, vector) = match svlog_scalar_type(ty) {
+        Some(x) => x,
+        None => return Err(ty.span.extract()),
+    };
+    let mut open = false;
+    for dim in unpacked.iter().chain(ty.dims.iter()) {
+        match *dim {
+            svlog_ast::TypeDim::Unsized => open = true,
+            svlog_ast::TypeDim::Expr(..) | svlog_ast::TypeDim::Range(..) => (),
+            _ => return Err(ty.span.extract()),
+        }
+    }
+
+    // Open arrays are accessed through a handle.
+    if open {
+        return Ok(if input {
+            "const svOpenArrayHandle".to_string()
+        } else {
+            "svOpenArrayHandle".to_string()
+        });
+    }
+
+    // Packed vectors are passed as arrays of 32 bit chunks, unpacked arrays
+    // as pointers to their first element.
+    let elem = if vector || !ty.dims.is_empty() {
+        if scalar == "svBit" {
+            "svBitVecVal"
+        } else {
+            "svLogicVecVal"
+        }
+    } else if unpacked.is_empty() {
+        return Ok(if input {
+            scalar.to_string()
+        } else if scalar == "const char*" {
+            "const char**".to_string()
+        } else {
+            format!("{}*", scalar)
+        });
+    } else {
+        scalar
+    };
+    Ok(if input && !elem.starts_with("const ") {
+        format!("const {}*", elem)
+    } else {
+        format!("{}*", elem)
+    })
+}
+
+/// Collect the subprograms with a `VHPIDIRECT` foreign attribute in a set of
+/// VHDL design units.
+pub fn vhdl_prototypes(units: &[vhdl_ast::DesignUnit]) -> Vec<Prototype> {
+    let mut protos = vec![];
+    for unit in units {
+        match unit.data {
+            vhdl_ast::DesignUnitData::EntityDecl(ref decl) => vhdl_scope(&decl.decls, &mut protos),
+            vhdl_ast::DesignUnitData::ArchBody(ref body) => vhdl_scope(&body.decls, &mut protos),
+            vhdl_ast::DesignUnitData::PkgDecl(ref decl) => vhdl_scope(&decl.decls, &mut protos),
+            vhdl_ast::DesignUnitData::PkgBody(ref body) => vhdl_scope(&body.decls, &mut protos),
+            _ => (),
+        }
+    }
+    protos
+}
+
+fn vhdl_scope(decls: &[vhdl_ast::DeclItem], protos: &mut Vec<Prototype>) {
+    for decl in decls {
+        let (target, cls, expr) = match *decl {
+            vhdl_ast::DeclItem::AttrDecl(vhdl_ast::AttrDecl {
+                name,
+                data:
+                    vhdl_ast::AttrData::Spec {
+                        target: vhdl_ast::AttrTarget::List(ref target),
+                        cls,
+                        ref expr,
+                    },
+                ..
+            }) if name.value.to_string().eq_ignore_ascii_case("foreign") => (target, cls, expr),
+            _ => continue,
+        };
+        if cls != vhdl_ast::EntityClass::Func && cls != vhdl_ast::EntityClass::Proc {
+            continue;
+        }
+
+        // The attribute value is `VHPIDIRECT [library] [function]`.
+        let value = expr.span.extract();
+        let mut words = value.trim_matches('"').split_whitespace();
+        if words.next() != Some("VHPIDIRECT") {
+            continue;
+        }
+        let cname = words.last();
+
+        for (target, _) in target {
+            let name = match target.primary.kind {
+                vhdl_ast::PrimaryNameKind::Ident(name) if target.parts.is_empty() => name,
+                _ => continue,
+            };
+            let spec = decls.iter().find_map(|decl| match *decl {
+                vhdl_ast::DeclItem::SubprogDecl(ref subprog)
+                    if subprog.spec.name.kind == vhdl_ast::PrimaryNameKind::Ident(name) =>
+                {
+                    Some(&subprog.spec)
+                }
+                _ => None,
+            });
+            if let Some(spec) = spec {
+                protos.push(vhdl_prototype(
+                    spec,
+                    cname.map(String::from).unwrap_or_else(|| name.to_string()),
+                ));
+            }
+        }
+    }
+}
+
+fn vhdl_prototype(spec: &vhdl_ast::SubprogSpec, name: String) -> Prototype {
+    let ret = match spec.retty {
+        Some(ref ty) => match vhdl_type(&ty.span.extract()) {
+            (Some(cty), false) => Ok(cty.to_string()),
+            _ => Err(ty.span.extract()),
+        },
+        None => Ok("void".to_string()),
+    };
+    let mut args = vec![];
+    for decl in spec.params.iter().flatten() {
+        let obj = match *decl {
+            vhdl_ast::IntfDecl::ObjDecl(ref obj) => obj,
+            _ => continue,
+        };
+        let input = obj.mode.is_none() || obj.mode == Some(vhdl_ast::IntfMode::In);
+        let text = obj.ty.span.extract();
+        let ty = match vhdl_type(&text) {
+            (Some(cty), false) if input => Ok(cty.to_string()),
+            (Some(cty), false) => Ok(format!("{}*", cty)),
+            (Some(cty), true) if input => Ok(format!("const {}*", cty)),
+            (Some(cty), true) => Ok(format!("{}*", cty)),
+            (None, _) => Err(text),
+        };
+        for name in &obj.names {
+            args.push((name.name.to_string(), ty.clone()));
+        }
+    }
+    Prototype {
+        name,
+        ret,
+        args,
+        origin: collapse(&spec.span.extract()),
+    }
+}
+
+/// The C type of a VHDL subtype indication, and whether it is a constrained
+/// array of elements of that type.
+fn vhdl_type(text: &str) -> (Option<&'static str>, bool) {
+    let lower = text.to_lowercase();
+    let (base, constrained) = match lower.find('(') {
+        Some(open) => (lower[..open].trim(), true),
+        None => (lower.as_str(), false),
+    };
+    let base = base.rsplit('.').next().unwrap();
+    let scalar = match base {
+        "integer" | "natural" | "positive" => Some("int32_t"),
+        "real" => Some("double"),
+        "time" => Some("int64_t"),
+        "boolean" | "bit" | "std_logic" | "std_ulogic" => Some("uint8_t"),
+        "character" => Some("char"),
+        _ => None,
+    };
+    if scalar.is_some() {
+        return (scalar, false);
+    }
+    // Unconstrained arrays are passed with their bounds, in a layout which is
+    // specific to the simulator.
+    let elem = match base {
+        "std_logic_vector" | "std_ulogic_vector" | "bit_vector" | "signed" | "unsigned" => {
+            Some("uint8_t")
+        }
+        "string" => Some("char"),
+        _ => None,
+    };
+    match elem {
+        Some(elem) if constrained => (Some(elem), true),
+        _ => (None, false),
+    }
+}
+
+/// Collapse all whitespace in a piece of source text into single spaces.
+fn collapse(text: &str) -> String {
+    text.split_whitespace().collect::<Vec<_>>().join(" ")
+}
+
+/// Render a C header declaring a list of prototypes.
+///
+/// The header is wrapped in an include guard named `guard` and includes
+/// `svdpi.h` if `dpi` is set.
+pub fn render(protos: &[Prototype], guard: &str, dpi: bool) -> String {
+    let mut out = String::new();
+    writeln!(out, "/* Generated by moore. */").unwrap();
+    writeln!(out, "#ifndef {}", guard).unwrap();
+    writeln!(out, "#define {}", guard).unwrap();
+    writeln!(out).unwrap();
+    writeln!(out, "#include <stdint.h>").unwrap();
+    if dpi {
+        writeln!(out, "#include \"svdpi.h\"").unwrap();
+    }
+    writeln!(out).unwrap();
+    writeln!(out, "#ifdef __cplusplus").unwrap();
+    writeln!(out, "extern \"C\" {{").unwrap();
+    writeln!(out, "#endif").unwrap();
+    for proto in protos {
+        writeln!(out).unwrap();
+        writeln!(out, "/* {} */", proto.origin.replace("*/", "* /")).unwrap();
+        if let Some(ty) = proto.unmapped() {
+            writeln!(
+                out,
+                "/* {}: no C mapping for type `{}` */",
+                proto.name,
+                ty.replace("*/", "* /")
+            )
+            .unwrap();
+            continue;
+        }
+        let args: Vec<_> = proto
+            .args
+            .iter()
+            .map(|(name, ty)| format!("{} {}", ty.as_ref().unwrap(), name))
+            .collect();
+        writeln!(
+            out,
+            "{} {}({});",
+            proto.ret.as_ref().unwrap(),
+            proto.name,
+            if args.is_empty() {
+                "void".to_string()
+            } else {
+                args.join(", ")
+            }
+        )
+        .unwrap();
+    }
+    writeln!(out).unwrap();
+    writeln!(out, "#ifdef __cplusplus").unwrap();
+    writeln!(out, "}}").unwrap();
+    writeln!(out, "#endif").unwrap();
+    writeln!(out).unwrap();
+    writeln!(out, "#endif /* {} */", guard).unwrap();
+    out
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::common::source::get_source_manager;
+    use crate::svlog::{lexer::Lexer, parser, preproc::Preprocessor};
+
+    fn svlog_header(text: &str) -> String {
+        let source = get_source_manager().add_anonymous(text);
+        let arena = svlog_ast::Arena::default();
+        let file = parser::parse(Lexer::new(Preprocessor::new(source, &[], &[])), &arena).unwrap();
+        render(&svlog_prototypes(&[file]), "DPI_H", true)
+    }
+
+    fn vhdl_header(text: &str) -> String {
+        let source = get_source_manager().add_anonymous(text);
+        let units = crate::vhdl::syntax::parse(source).unwrap();
+        render(&vhdl_prototypes(&units), "VHPI_H", false)
+    }
+
+    #[test]
+    fn dpi_prototypes() {
+        assert_eq!(
+            svlog_header(
+                "module top;
+                    import \"DPI-C\" function int add(input int a, input bit [7:0] b);
+                    import \"DPI-C\" c_log = task log(input string msg, output logic x);
+                    export \"DPI-C\" function done;
+                    function void done(); endfunction
+                    import \"DPI-C\" function void f(input my_t x);
+                endmodule",
+            ),
+            "/* Generated by moore. */
+#ifndef DPI_H
+#define DPI_H
+
+#include <stdint.h>
+#include \"svdpi.h\"
+
+#ifdef __cplusplus
+extern \"C\" {
+#endif
+
+/* import \"DPI-C\" function int add(input int a, input bit [7:0] b); */
+int add(int a, const svBitVecVal* b);
+
+/* import \"DPI-C\" c_log = task log(input string msg, output logic x); */
+int c_log(const char* msg, svLogic* x);
+
+/* export \"DPI-C\" function done; */
+void done(void);
+
+/* import \"DPI-C\" function void f(input my_t x); */
+/* f: no C mapping for type `my_t` */
+
+#ifdef __cplusplus
+}
+#endif
+
+#endif /* DPI_H */
+"
+        );
+    }
+
+    #[test]
+    fn vhpidirect_prototypes() {
+        assert_eq!(
+            vhdl_header(
+                "package p is
+                    function add(a : integer; b : std_logic) return integer;
+                    attribute foreign of add : function is \"VHPIDIRECT c_add\";
+                    procedure fill(v : out std_logic_vector(7 downto 0));
+                    attribute foreign of fill : procedure is \"VHPIDIRECT\";
+                end package;",
+            ),
+            "/* Generated by moore. */
+#ifndef VHPI_H
+#define VHPI_H
+
+#include <stdint.h>
+
+#ifdef __cplusplus
+extern \"C\" {
+#endif
+
+/* function add(a : integer; b : std_logic) return integer */
+int32_t c_add(int32_t a, uint8_t b);
+
+/* procedure fill(v : out std_logic_vector(7 downto 0)) */
+void fill(uint8_t* v);
+
+#ifdef __cplusplus
+}
+#endif
+
+#endif /* VHPI_H */
+"
+        );
+    }
+}
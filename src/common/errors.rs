@@ -3,7 +3,11 @@
 //! Utilities to implement diagnostics and error reporting facilities.
 
 use crate::source::Span;
+use std::collections::HashMap;
 use std::fmt;
+use std::io::{self, Write};
+use std::sync::Mutex;
+use std::thread::{self, ThreadId};
 
 /// Print debug information. Omitted in release builds.
 #[macro_export]
@@ -69,6 +73,81 @@ impl<T, E: EmitError> EmitError for Result<T, E> {
     }
 }
 
+/// A buffer of diagnostics reported by several threads, which are written in
+/// a deterministic order.
+///
+/// Each thread renders its diagnostics into a buffer of its own, since the
+/// source files a diagnostic points into are only known to the thread that
+/// loaded them. Nothing is written until the sink is flushed, such that the
+/// output of threads reporting concurrently never interleaves. Flushing writes
+/// the diagnostics of all threads ordered by the path of the file they point
+/// into and their offset in that file, followed by those without a location.
+#[derive(Default)]
+pub struct DiagSink {
+    buffers: Mutex<HashMap<ThreadId, Vec<RenderedDiag>>>,
+}
+
+/// A diagnostic rendered for output, ordered by its location.
+#[derive(PartialEq, Eq, PartialOrd, Ord)]
+struct RenderedDiag {
+    unlocated: bool,
+    path: String,
+    offset: usize,
+    text: String,
+}
+
+impl DiagSink {
+    /// Render a diagnostic into the buffer of the current thread.
+    pub fn push(&self, diag: &DiagBuilder2) {
+        let span = diag.segments.iter().find_map(|segment| match *segment {
+            DiagSegment::Span(span) => Some(span),
+            DiagSegment::Note(_) => None,
+        });
+        let rendered = RenderedDiag {
+            unlocated: span.is_none(),
+            path: span
+                .map(|span| span.source.get_path().to_string())
+                .unwrap_or_default(),
+            offset: span.map(|span| span.begin).unwrap_or(0),
+            text: diag.to_string(),
+        };
+        self.buffers
+            .lock()
+            .unwrap()
+            .entry(thread::current().id())
+            .or_default()
+            .push(rendered);
+    }
+
+    /// Check whether no diagnostics are buffered.
+    pub fn is_empty(&self) -> bool {
+        self.buffers.lock().unwrap().values().all(Vec::is_empty)
+    }
+
+    /// Take the diagnostics buffered by all threads, rendered and in the order
+    /// of their location, and clear the buffers.
+    pub fn take(&self) -> Vec<String> {
+        let mut diags: Vec<_> = self
+            .buffers
+            .lock()
+            .unwrap()
+            .drain()
+            .flat_map(|(_, diags)| diags)
+            .collect();
+        diags.sort();
+        diags.into_iter().map(|diag| diag.text).collect()
+    }
+
+    /// Write the diagnostics buffered by all threads in the order of their
+    /// location, and clear the buffers.
+    pub fn flush(&self, out: &mut dyn Write) -> io::Result<()> {
+        for diag in self.take() {
+            writeln!(out, "{}", diag)?;
+        }
+        out.flush()
+    }
+}
+
 #[must_use]
 #[derive(Clone, Debug)]
 pub struct DiagBuilder2 {
@@ -191,67 +270,20 @@ impl fmt::Display for DiagBuilder2 {
         for segment in &self.segments {
             match *segment {
                 DiagSegment::Span(sp) => {
-                    let c = sp.source.get_content();
-
-                    // Look for the start of the line.
-                    let (line, col, line_offset) = sp.begin().human();
-
-                    // Print the line in question.
-                    let text: String = c
-                        .iter_from(line_offset)
-                        .map(|x| x.1)
-                        .take_while(|c| *c != '\n' && *c != '\r')
-                        .collect();
-                    write!(
-                        f,
-                        "  --> {}:{}:{}-{}:\n",
-                        sp.source.get_path(),
-                        line,
-                        col,
-                        col + sp.extract().len()
-                    )?;
-                    write!(f, "   | \n")?;
-                    write!(f, "   | ")?;
-                    for (mut i, c) in text.char_indices() {
-                        i += line_offset;
-                        if sp.begin != sp.end {
-                            if i == sp.begin {
-                                write!(f, "{}", colorcode)?;
-                            }
-                            if i == sp.end {
-                                write!(f, "\x1B[m")?;
-                            }
-                        }
-                        match c {
-                            '\t' => write!(f, "    ")?,
-                            c => write!(f, "{}", c)?,
-                        }
-                    }
-                    write!(f, "\x1B[m\n")?;
-                    write!(f, "   | ")?;
-
-                    // Print the caret markers for the line in question.
-                    let mut pd = ' ';
-                    for (mut i, c) in text.char_indices() {
-                        i += line_offset;
-                        let d = if (i >= sp.begin && i < sp.end)
-                            || (i == sp.begin && sp.begin == sp.end)
-                        {
-                            '^'
-                        } else {
-                            ' '
-                        };
-                        if d != pd {
-                            write!(f, "{}", if d == ' ' { "\x1B[m" } else { colorcode })?;
-                        }
-                        pd = d;
-                        match c {
-                            '\t' => write!(f, "{}{}{}{}", d, d, d, d)?,
-                            _ => write!(f, "{}", d)?,
-                        }
-                    }
-                    write!(f, "\x1B[m\n")?;
+                    write_span(f, sp, colorcode)?;
                     colorcode = "\x1B[1m";
+
+                    // Show where the span was expanded if it lies in a macro.
+                    let mut source = sp.source;
+                    while let Some(expansion) = source.get_expansion() {
+                        write!(
+                            f,
+                            "   = \x1B[1mnote:\x1B[m in expansion of macro `{}`\n",
+                            expansion.name
+                        )?;
+                        write_span(f, expansion.site, colorcode)?;
+                        source = expansion.site.source;
+                    }
                 }
                 DiagSegment::Note(ref message) => {
                     write!(f, "   = \x1B[1mnote:\x1B[m {}\n", message)?
@@ -272,3 +304,66 @@ impl fmt::Display for DiagBuilder2 {
         Ok(())
     }
 }
+
+/// Print the line of source code covered by a span, with the span underlined.
+fn write_span(f: &mut fmt::Formatter, sp: Span, colorcode: &str) -> fmt::Result {
+    let c = sp.source.get_content();
+
+    // Look for the start of the line.
+    let (line, col, line_offset) = sp.begin().human();
+
+    // Print the line in question. The characters keep their byte offsets in
+    // the source, which may differ from those in a UTF-8 copy of the line.
+    let text: Vec<(usize, char)> = c
+        .iter_from(line_offset)
+        .take_while(|&(_, c)| c != '\n' && c != '\r')
+        .collect();
+    write!(
+        f,
+        "  --> {}:{}:{}-{}:\n",
+        sp.source.get_path(),
+        line,
+        col,
+        col + sp.extract().chars().count()
+    )?;
+    write!(f, "   | \n")?;
+    write!(f, "   | ")?;
+    for &(mut i, c) in &text {
+        i += line_offset;
+        if sp.begin != sp.end {
+            if i == sp.begin {
+                write!(f, "{}", colorcode)?;
+            }
+            if i == sp.end {
+                write!(f, "\x1B[m")?;
+            }
+        }
+        match c {
+            '\t' => write!(f, "    ")?,
+            c => write!(f, "{}", c)?,
+        }
+    }
+    write!(f, "\x1B[m\n")?;
+    write!(f, "   | ")?;
+
+    // Print the caret markers for the line in question.
+    let mut pd = ' ';
+    for &(mut i, c) in &text {
+        i += line_offset;
+        let d = if (i >= sp.begin && i < sp.end) || (i == sp.begin && sp.begin == sp.end) {
+            '^'
+        } else {
+            ' '
+        };
+        if d != pd {
+            write!(f, "{}", if d == ' ' { "\x1B[m" } else { colorcode })?;
+        }
+        pd = d;
+        match c {
+            '\t' => write!(f, "{}{}{}{}", d, d, d, d)?,
+            _ => write!(f, "{}", d)?,
+        }
+    }
+    write!(f, "\x1B[m\n")?;
+    Ok(())
+}
@@ -18,13 +18,32 @@ pub mod source;
 pub mod util;
 
 pub use self::id::NodeId;
-use crate::errors::{DiagBuilder2, DiagEmitter, Severity};
-use std::cell::Cell;
+use crate::errors::{DiagBuilder2, DiagEmitter, DiagSink, Severity};
+use std::sync::atomic::{AtomicBool, AtomicUsize, Ordering};
 
+/// The state of a compilation.
+///
+/// A session may be shared among threads, which can emit diagnostics
+/// concurrently. See `Session::buffered` for how to keep their output in a
+/// deterministic order.
 pub struct Session {
     pub opts: SessionOptions,
     /// Whether any error diagnostics were produced.
-    pub failed: Cell<bool>,
+    pub failed: AtomicBool,
+    /// The number of error diagnostics produced.
+    pub num_errors: AtomicUsize,
+    /// The number of warning diagnostics produced.
+    pub num_warnings: AtomicUsize,
+    /// The number of messages raised by the design at or above the severity
+    /// given by `--stop-on`.
+    pub num_stop_messages: AtomicUsize,
+    /// Whether the number of errors given by `--max-errors` has been reached.
+    /// The diagnostics emitted afterwards are dropped.
+    pub error_limit_reached: AtomicBool,
+    /// The diagnostics held back while buffering.
+    pub diags: DiagSink,
+    /// The number of `Session::buffered` calls in progress.
+    buffering: AtomicUsize,
 }
 
 impl Session {
@@ -32,21 +51,132 @@ impl Session {
     pub fn new() -> Session {
         Session {
             opts: Default::default(),
-            failed: Cell::new(false),
+            failed: AtomicBool::new(false),
+            num_errors: AtomicUsize::new(0),
+            num_warnings: AtomicUsize::new(0),
+            num_stop_messages: AtomicUsize::new(0),
+            error_limit_reached: AtomicBool::new(false),
+            diags: Default::default(),
+            buffering: AtomicUsize::new(0),
         }
     }
 
     pub fn failed(&self) -> bool {
-        self.failed.get()
+        self.failed.load(Ordering::SeqCst)
+    }
+
+    /// Get the number of error diagnostics produced.
+    pub fn num_errors(&self) -> usize {
+        self.num_errors.load(Ordering::SeqCst)
+    }
+
+    /// Get the number of warning diagnostics produced.
+    pub fn num_warnings(&self) -> usize {
+        self.num_warnings.load(Ordering::SeqCst)
+    }
+
+    /// Check whether the number of errors given by `--max-errors` has been
+    /// reached, in which case the driver should abort.
+    pub fn error_limit_reached(&self) -> bool {
+        self.error_limit_reached.load(Ordering::SeqCst)
+    }
+
+    /// Forget the diagnostics produced so far, such that the session can be
+    /// used for another compilation of the same design.
+    pub fn reset(&self) {
+        self.failed.store(false, Ordering::SeqCst);
+        self.num_errors.store(0, Ordering::SeqCst);
+        self.num_warnings.store(0, Ordering::SeqCst);
+        self.num_stop_messages.store(0, Ordering::SeqCst);
+        self.error_limit_reached.store(false, Ordering::SeqCst);
+    }
+
+    /// Count a message raised by the design, after its severity has been
+    /// mapped with `SessionOptions::map_severity`.
+    ///
+    /// Returns `true` if the limit given by `--stop-on` has been reached and
+    /// compilation should stop.
+    pub fn count_message(&self, severity: MessageSeverity) -> bool {
+        let (stop_severity, limit) = self.opts.stop_on();
+        if severity < stop_severity {
+            return false;
+        }
+        self.num_stop_messages.fetch_add(1, Ordering::SeqCst) + 1 >= limit
+    }
+
+    /// Hold back the diagnostics emitted by any thread while a closure runs,
+    /// and emit them ordered by file and offset once it returns.
+    ///
+    /// Passes which run on several threads use this to report concurrently
+    /// without the order of the output depending on which thread is faster.
+    /// Nested calls emit the diagnostics when the outermost call returns.
+    pub fn buffered<R>(&self, f: impl FnOnce() -> R) -> R {
+        // Emit the diagnostics even if the closure panics.
+        struct Guard<'a>(&'a Session);
+        impl Drop for Guard<'_> {
+            fn drop(&mut self) {
+                if self.0.buffering.fetch_sub(1, Ordering::SeqCst) == 1 {
+                    self.0.flush();
+                }
+            }
+        }
+        self.buffering.fetch_add(1, Ordering::SeqCst);
+        let _guard = Guard(self);
+        f()
+    }
+
+    /// Emit the diagnostics held back so far.
+    pub fn flush(&self) {
+        let _ = self.diags.flush(&mut std::io::stderr().lock());
+    }
+
+    /// Get a summary of the number of errors and warnings produced.
+    ///
+    /// Returns `None` if no errors or warnings were produced.
+    pub fn summary(&self) -> Option<String> {
+        let plural = |n: usize, what: &str| match n {
+            1 => format!("1 {}", what),
+            n => format!("{} {}s", n, what),
+        };
+        match (self.num_errors(), self.num_warnings()) {
+            (0, 0) => None,
+            (e, 0) => Some(plural(e, "error")),
+            (0, w) => Some(plural(w, "warning")),
+            (e, w) => Some(format!("{}, {}", plural(e, "error"), plural(w, "warning"))),
+        }
     }
 }
 
 impl DiagEmitter for Session {
-    fn emit(&self, diag: DiagBuilder2) {
-        if diag.severity >= Severity::Error {
-            self.failed.set(true);
+    fn emit(&self, mut diag: DiagBuilder2) {
+        if self.error_limit_reached() {
+            return;
+        }
+        if diag.severity == Severity::Warning && self.opts.warnings_as_errors {
+            diag.severity = Severity::Error;
+        }
+        let num_errors = if diag.severity >= Severity::Error {
+            self.failed.store(true, Ordering::SeqCst);
+            self.num_errors.fetch_add(1, Ordering::SeqCst) + 1
+        } else {
+            if diag.severity == Severity::Warning {
+                self.num_warnings.fetch_add(1, Ordering::SeqCst);
+            }
+            self.num_errors()
+        };
+        if self.buffering.load(Ordering::SeqCst) > 0 {
+            self.diags.push(&diag);
+        } else {
+            eprintln!("{}", diag);
+        }
+
+        // Stop emitting once we have hit the maximum number of errors. The
+        // driver checks for this and aborts.
+        if let Some(max) = self.opts.max_errors {
+            if num_errors >= max {
+                self.error_limit_reached.store(true, Ordering::SeqCst);
+            }
         }
-        eprintln!("{}", diag);
     }
 }
 
@@ -75,6 +205,82 @@ pub struct SessionOptions {
     pub verbosity: Verbosity,
     /// The optimization level.
     pub opt_level: usize,
+    /// The LLHD passes to run on the generated code, in order. Overrides the
+    /// passes implied by the optimization level if set.
+    pub llhd_passes: Option<Vec<LlhdPass>>,
+    /// Print a report of the size of the generated code.
+    pub size_report: bool,
+    /// Annotate the generated code with the memories inferred from arrays.
+    pub infer_memories: bool,
+    /// Annotate the generated code with the source locations of its units.
+    pub debug_info: bool,
+    /// The format in which to emit the generated code.
+    pub emit: EmitFormat,
+    /// The signal whose combinational cone to emit as SMT-LIB2.
+    pub cone: Option<String>,
+    /// Save the elaborated design to this file before emitting it.
+    pub save_snapshot: Option<String>,
+    /// Treat warnings as errors.
+    pub warnings_as_errors: bool,
+    /// Abort after this many errors have been emitted.
+    pub max_errors: Option<usize>,
+    /// The revision of the VHDL standard to compile against.
+    pub vhdl_std: VhdlStandard,
+    /// Lower multi-valued logic to two-state bits, mapping the values other
+    /// than `0` and `1` as given. Four-valued logic is kept if this is `None`.
+    pub two_state: Option<XPolicy>,
+    /// Remap the severity of messages raised by the design.
+    pub severity_map: Vec<(MessageSeverity, MessageSeverity)>,
+    /// Stop after this many messages of at least this severity. Defaults to
+    /// the first failure if this is `None`.
+    pub stop_on: Option<(MessageSeverity, usize)>,
+    /// The seed of the random number generators of a simulation run. It is
+    /// passed to the runtime by every call of a random number function, such
+    /// that a run can be reproduced exactly.
+    pub seed: u32,
+    /// The order in which signal updates of the different languages take
+    /// effect.
+    pub scheduling: Scheduling,
+    /// Truncate or extend the signals connected to instance ports of a
+    /// different width, instead of rejecting them.
+    pub permissive_connections: bool,
+    /// Emit the code generated for all units that succeeded, declaring the
+    /// ones that failed, instead of aborting at the first failing unit.
+    pub keep_going: bool,
+}
+
+impl SessionOptions {
+    /// The LLHD passes to run on the generated code, in order.
+    pub fn llhd_passes(&self) -> Vec<LlhdPass> {
+        match self.llhd_passes {
+            Some(ref passes) => passes.clone(),
+            None if self.opt_level > 0 => LlhdPass::DEFAULT.to_vec(),
+            None => vec![],
+        }
+    }
+
+    /// Apply the `--severity` remapping to the severity of a message.
+    pub fn map_severity(&self, severity: MessageSeverity) -> MessageSeverity {
+        self.severity_map
+            .iter()
+            .rev()
+            .find(|&&(from, _)| from == severity)
+            .map(|&(_, to)| to)
+            .unwrap_or(severity)
+    }
+
+    /// The severity and number of messages after which to stop.
+    pub fn stop_on(&self) -> (MessageSeverity, usize) {
+        self.stop_on.unwrap_or((MessageSeverity::Failure, 1))
+    }
+
+    /// Check whether a message raised at runtime stops the simulation.
+    ///
+    /// Generated code does not count messages, so the first message at or
+    /// above the `--stop-on` severity stops regardless of the given count.
+    pub fn stops_at(&self, severity: MessageSeverity) -> bool {
+        self.map_severity(severity) >= self.stop_on().0
+    }
 }
 
 bitflags! {
@@ -94,3 +300,344 @@ bitflags! {
         const INSTS         = 1 << 8;
     }
 }
+
+/// An output format for the generated code.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum EmitFormat {
+    /// LLHD assembly.
+    Llhd,
+    /// SMT-LIB2 definitions of a combinational cone.
+    Smt,
+    /// A bit-blasted and-inverter graph in ASCII AIGER format.
+    Aiger,
+    /// A C header describing the ports of the top-level entity.
+    CHeader,
+    /// A Rust module describing the ports of the top-level entity.
+    Rust,
+}
+
+impl Default for EmitFormat {
+    fn default() -> EmitFormat {
+        EmitFormat::Llhd
+    }
+}
+
+/// The severity of a message raised by the design itself, such as a VHDL
+/// assertion or a SystemVerilog `$error` task.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub enum MessageSeverity {
+    /// VHDL `note` and SystemVerilog `$info`.
+    Note,
+    /// VHDL `warning` and SystemVerilog `$warning`.
+    Warning,
+    /// VHDL `error` and SystemVerilog `$error`.
+    Error,
+    /// VHDL `failure` and SystemVerilog `$fatal`.
+    Failure,
+}
+
+impl MessageSeverity {
+    /// All severities, in the order of the VHDL `severity_level` type.
+    pub const ALL: [MessageSeverity; 4] = [
+        MessageSeverity::Note,
+        MessageSeverity::Warning,
+        MessageSeverity::Error,
+        MessageSeverity::Failure,
+    ];
+
+    /// Parse a severity by its VHDL or SystemVerilog name, e.g. `failure` or
+    /// `fatal`.
+    pub fn from_name(name: &str) -> Option<MessageSeverity> {
+        match name {
+            "note" | "info" => Some(MessageSeverity::Note),
+            "warning" => Some(MessageSeverity::Warning),
+            "error" => Some(MessageSeverity::Error),
+            "failure" | "fatal" => Some(MessageSeverity::Failure),
+            _ => None,
+        }
+    }
+
+    /// The severity of diagnostics reporting the message at compile time.
+    pub fn diag_severity(self) -> Severity {
+        match self {
+            MessageSeverity::Note => Severity::Note,
+            MessageSeverity::Warning => Severity::Warning,
+            MessageSeverity::Error => Severity::Error,
+            MessageSeverity::Failure => Severity::Fatal,
+        }
+    }
+}
+
+impl std::fmt::Display for MessageSeverity {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        match *self {
+            MessageSeverity::Note => write!(f, "note"),
+            MessageSeverity::Warning => write!(f, "warning"),
+            MessageSeverity::Error => write!(f, "error"),
+            MessageSeverity::Failure => write!(f, "failure"),
+        }
+    }
+}
+
+/// How values other than `0` and `1` are mapped in two-state lowering.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum XPolicy {
+    /// Map unknown and high-impedance values to `0`.
+    Zero,
+    /// Map unknown and high-impedance values to `1`.
+    One,
+    /// Reject unknown and high-impedance values with an error.
+    Error,
+}
+
+impl XPolicy {
+    /// Parse a policy as given on the command line.
+    pub fn from_name(name: &str) -> Option<XPolicy> {
+        match name {
+            "zero" => Some(XPolicy::Zero),
+            "one" => Some(XPolicy::One),
+            "error" => Some(XPolicy::Error),
+            _ => None,
+        }
+    }
+}
+
+/// How signal updates are scheduled in the generated code.
+///
+/// LLHD orders the updates within a point in simulated time by delta cycles,
+/// and the updates within a delta cycle by epsilon steps. The modes map the
+/// scheduling rules of VHDL and SystemVerilog onto these steps, such that the
+/// languages of a mixed design agree on when an update becomes visible.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Scheduling {
+    /// Follow the rules of each language. SystemVerilog continuous and
+    /// blocking assignments to signals take effect within the current delta
+    /// cycle, while VHDL signal assignments take effect in the next one.
+    Native,
+    /// Defer all signal updates to the next delta cycle, as prescribed by the
+    /// VHDL standard.
+    Delta,
+    /// Order signal updates like the SystemVerilog event regions. Continuous
+    /// assignments of both languages take effect within the current delta
+    /// cycle, like updates in the active region, and all other updates in the
+    /// next one, like updates in the NBA region.
+    Stratified,
+}
+
+impl Default for Scheduling {
+    fn default() -> Scheduling {
+        Scheduling::Native
+    }
+}
+
+impl Scheduling {
+    /// Parse a scheduling mode as given on the command line.
+    pub fn from_name(name: &str) -> Option<Scheduling> {
+        match name {
+            "native" => Some(Scheduling::Native),
+            "delta" => Some(Scheduling::Delta),
+            "stratified" => Some(Scheduling::Stratified),
+            _ => None,
+        }
+    }
+}
+
+/// An LLHD pass that can be run on the generated code.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum LlhdPass {
+    /// Fold instructions with constant operands.
+    ConstFolding,
+    /// Remove instructions whose results are unused, and unreachable blocks.
+    DeadCodeElim,
+    /// Replace instructions with an identical earlier one.
+    CommonSubexprElim,
+    /// Simplify instructions, such as `and` with an all-ones operand.
+    InstSimplification,
+    /// Merge and remove basic blocks.
+    ControlFlowSimplification,
+    /// Move instructions to the earliest block they can be computed in.
+    EarlyCodeMotion,
+    /// Move instructions across waits in processes.
+    TemporalCodeMotion,
+    /// Lower processes which describe flip-flops and latches to entities.
+    Desequentialization,
+    /// Lower processes which describe combinational logic to entities.
+    ProcessLowering,
+}
+
+impl LlhdPass {
+    /// All passes, by the names used on the command line.
+    pub const ALL: [(&'static str, LlhdPass); 9] = [
+        ("cf", LlhdPass::ConstFolding),
+        ("dce", LlhdPass::DeadCodeElim),
+        ("cse", LlhdPass::CommonSubexprElim),
+        ("insim", LlhdPass::InstSimplification),
+        ("cfs", LlhdPass::ControlFlowSimplification),
+        ("ecm", LlhdPass::EarlyCodeMotion),
+        ("tcm", LlhdPass::TemporalCodeMotion),
+        ("deseq", LlhdPass::Desequentialization),
+        ("proclower", LlhdPass::ProcessLowering),
+    ];
+
+    /// The passes run at optimization levels above zero.
+    pub const DEFAULT: [LlhdPass; 5] = [
+        LlhdPass::ConstFolding,
+        LlhdPass::DeadCodeElim,
+        LlhdPass::CommonSubexprElim,
+        LlhdPass::InstSimplification,
+        LlhdPass::DeadCodeElim,
+    ];
+
+    /// Parse a pass name as given on the command line.
+    pub fn from_name(name: &str) -> Option<LlhdPass> {
+        LlhdPass::ALL
+            .iter()
+            .find(|&&(n, _)| n == name)
+            .map(|&(_, pass)| pass)
+    }
+
+    /// Parse a comma-separated pipeline such as `cf,dce,cse`, returning the
+    /// first unknown pass name on failure. An empty pipeline runs no passes.
+    pub fn parse_pipeline(s: &str) -> Result<Vec<LlhdPass>, &str> {
+        s.split(',')
+            .map(str::trim)
+            .filter(|name| !name.is_empty())
+            .map(|name| LlhdPass::from_name(name).ok_or(name))
+            .collect()
+    }
+}
+
+impl std::fmt::Display for LlhdPass {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        let name = LlhdPass::ALL.iter().find(|&&(_, p)| p == *self).unwrap().0;
+        write!(f, "{}", name)
+    }
+}
+
+/// A revision of the VHDL standard.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub enum VhdlStandard {
+    V1987,
+    V1993,
+    V2000,
+    V2002,
+    V2008,
+}
+
+impl Default for VhdlStandard {
+    fn default() -> VhdlStandard {
+        VhdlStandard::V2008
+    }
+}
+
+impl VhdlStandard {
+    /// All revisions of the standard, oldest first.
+    pub const ALL: [VhdlStandard; 5] = [
+        VhdlStandard::V1987,
+        VhdlStandard::V1993,
+        VhdlStandard::V2000,
+        VhdlStandard::V2002,
+        VhdlStandard::V2008,
+    ];
+
+    /// Parse a standard revision such as `93`, `1993`, or `08`.
+    pub fn from_year(s: &str) -> Option<VhdlStandard> {
+        VhdlStandard::ALL.iter().cloned().find(|std| {
+            let year = std.year().to_string();
+            s == year || s == &year[2..]
+        })
+    }
+
+    /// The year the revision was published.
+    pub fn year(self) -> usize {
+        match self {
+            VhdlStandard::V1987 => 1987,
+            VhdlStandard::V1993 => 1993,
+            VhdlStandard::V2000 => 2000,
+            VhdlStandard::V2002 => 2002,
+            VhdlStandard::V2008 => 2008,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn error_limit() {
+        let mut sess = Session::new();
+        sess.opts.max_errors = Some(2);
+        sess.emit(DiagBuilder2::error("first"));
+        assert!(!sess.error_limit_reached());
+        sess.emit(DiagBuilder2::error("second"));
+        assert!(sess.error_limit_reached());
+        sess.emit(DiagBuilder2::error("third"));
+        sess.emit(DiagBuilder2::warning("fourth"));
+        assert_eq!(sess.num_errors(), 2);
+        assert_eq!(sess.num_warnings(), 0);
+    }
+
+    #[test]
+    fn reset() {
+        let mut sess = Session::new();
+        sess.opts.max_errors = Some(1);
+        sess.emit(DiagBuilder2::error("first"));
+        assert!(sess.failed() && sess.error_limit_reached());
+        sess.reset();
+        assert!(!sess.failed() && !sess.error_limit_reached());
+        sess.emit(DiagBuilder2::warning("second"));
+        assert_eq!(sess.num_errors(), 0);
+        assert_eq!(sess.num_warnings(), 1);
+    }
+
+    #[test]
+    fn buffered_order() {
+        use crate::source::{get_source_manager, Span};
+        use std::sync::Arc;
+
+        // Each thread reports out of order into a file of its own, and the
+        // threads are started in reverse order of their files.
+        let sess = Arc::new(Session::new());
+        let diags = sess.buffered(|| {
+            let threads: Vec<_> = vec![("b.sv", vec![5]), ("a.sv", vec![20, 10])]
+                .into_iter()
+                .map(|(path, offsets)| {
+                    let sess = sess.clone();
+                    std::thread::spawn(move || {
+                        let source = get_source_manager().add(path, &" ".repeat(32));
+                        sess.emit(DiagBuilder2::error(format!("{} unlocated", path)));
+                        for offset in offsets {
+                            sess.emit(
+                                DiagBuilder2::error(format!("{}@{}", path, offset))
+                                    .span(Span::new(source, offset, offset + 1)),
+                            );
+                        }
+                    })
+                })
+                .collect();
+            for thread in threads {
+                thread.join().unwrap();
+            }
+            sess.diags.take()
+        });
+        let expected = [
+            "a.sv@10",
+            "a.sv@20",
+            "b.sv@5",
+            "a.sv unlocated",
+            "b.sv unlocated",
+        ];
+        assert_eq!(diags.len(), expected.len());
+        for (diag, message) in diags.iter().zip(&expected) {
+            assert!(
+                diag.contains(message),
+                "expected `{}` in {:?}",
+                message,
+                diag
+            );
+        }
+        assert!(sess.diags.is_empty());
+        assert_eq!(sess.num_errors(), 5);
+    }
+}
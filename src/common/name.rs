@@ -195,6 +195,25 @@ impl NameTable {
     }
 }
 
+/// Escape a name for use in LLHD assembly.
+///
+/// LLHD names consist of letters, digits, `_`, and `.`. Every other character,
+/// such as the delimiters of a VHDL extended identifier or the brackets of a
+/// SystemVerilog escaped identifier in a netlist, is replaced by a backslash
+/// followed by its code point in hexadecimal. This is the escape LLHD applies
+/// to the names of values, such that `\data[3]\` becomes `\5cdata\5b3\5d\5c`.
+pub fn llhd_name(name: &str) -> String {
+    let mut escaped = String::with_capacity(name.len());
+    for c in name.chars() {
+        if c.is_ascii_alphanumeric() || c == '_' || c == '.' {
+            escaped.push(c);
+        } else {
+            escaped.push_str(&format!("\\{:x}", c as u32));
+        }
+    }
+    escaped
+}
+
 /// Get this thread's current name table.
 pub fn get_name_table() -> Rc<NameTable> {
     thread_local!(static TBL: Rc<NameTable> = {
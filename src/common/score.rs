@@ -9,6 +9,9 @@ use std;
 use std::collections::{BTreeMap, HashMap};
 use std::fmt::Debug;
 use std::hash::Hash;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Arc;
+use std::time::{Duration, Instant};
 
 use crate::id::NodeId;
 
@@ -182,7 +185,7 @@ where
 pub trait NodeMaker<I, N> {
     /// Creates the node with the given ID.
     ///
-    /// Returns `Err(())` upon failure. Note that the generated node has
+    /// Returns an `Error` upon failure. Note that the generated node has
     /// lifetime `'tn` that outlives the `NodeMaker`. This is required to allow
     /// for the `NodeMaker` to generate multiple nodes at the same time. The
     /// generated nodes should be owned by an arena or the owner of the
@@ -193,7 +196,105 @@ pub trait NodeMaker<I, N> {
 /// The result of making a node. Errors that occur while making a node should be
 /// reported via a separate channel, e.g. diagnostics, which provide more
 /// information to the user.
-pub type Result<T> = std::result::Result<T, ()>;
+pub type Result<T> = std::result::Result<T, Error>;
+
+/// The reason why making a node failed.
+///
+/// Most failures are problems in the user's input, which have already been
+/// reported as a diagnostic. The caller should simply propagate these. The
+/// remaining variants allow callers to tell bugs in the compiler and aborted
+/// queries apart from such errors, for example to retry a cancelled query
+/// rather than treating its node as erroneous.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Error {
+    /// The error has been reported to the user via a diagnostic.
+    Reported,
+    /// The compiler reached an inconsistent state. The message describes what
+    /// went wrong and is intended for compiler developers.
+    Internal(&'static str),
+    /// The query was cancelled before it completed, e.g. because its inputs
+    /// changed. The node itself is not necessarily erroneous.
+    Cancelled,
+}
+
+impl Error {
+    /// Check whether the error may go away if the query is run again.
+    pub fn is_recoverable(&self) -> bool {
+        match *self {
+            Error::Cancelled => true,
+            _ => false,
+        }
+    }
+}
+
+impl std::fmt::Display for Error {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        match *self {
+            Error::Reported => write!(f, "error reported as diagnostic"),
+            Error::Internal(msg) => write!(f, "internal error: {}", msg),
+            Error::Cancelled => write!(f, "query cancelled"),
+        }
+    }
+}
+
+impl std::error::Error for Error {}
+
+/// A token that allows running queries to be cancelled.
+///
+/// Queries check the token before doing any work and fail with
+/// `Error::Cancelled` once it has been triggered, either explicitly via
+/// `cancel` or because its deadline has passed. Clones of a token share their
+/// state, such that a token may be cancelled from a different thread than the
+/// one running the queries. Cancellation cannot be undone; use a fresh token
+/// for the next round of queries.
+#[derive(Debug, Clone, Default)]
+pub struct CancelToken {
+    cancelled: Arc<AtomicBool>,
+    deadline: Option<Instant>,
+}
+
+impl CancelToken {
+    /// Create a token that is only cancelled explicitly.
+    pub fn new() -> CancelToken {
+        Default::default()
+    }
+
+    /// Create a token that is cancelled automatically after a timeout.
+    pub fn with_timeout(timeout: Duration) -> CancelToken {
+        CancelToken {
+            cancelled: Default::default(),
+            deadline: Some(Instant::now() + timeout),
+        }
+    }
+
+    /// Cancel all queries using this token or a clone of it.
+    pub fn cancel(&self) {
+        self.cancelled.store(true, Ordering::Relaxed);
+    }
+
+    /// Check whether the token has been cancelled or its deadline has passed.
+    pub fn is_cancelled(&self) -> bool {
+        self.cancelled.load(Ordering::Relaxed)
+            || self.deadline.map(|d| Instant::now() >= d).unwrap_or(false)
+    }
+
+    /// Fail with `Error::Cancelled` if the token has been cancelled.
+    pub fn check(&self) -> Result<()> {
+        if self.is_cancelled() {
+            Err(Error::Cancelled)
+        } else {
+            Ok(())
+        }
+    }
+}
+
+/// Errors reported by the parsers and other parts of the compiler that do not
+/// distinguish failures.
+impl From<()> for Error {
+    fn from(_: ()) -> Error {
+        Error::Reported
+    }
+}
 
 /// A reference to a node.
 ///
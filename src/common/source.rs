@@ -8,7 +8,7 @@ use crate::name::RcStr;
 use memmap::Mmap;
 use std;
 use std::borrow::Borrow;
-use std::cell::RefCell;
+use std::cell::{Cell, RefCell};
 use std::collections::HashMap;
 use std::fmt;
 use std::hash::{Hash, Hasher};
@@ -46,6 +46,11 @@ impl Source {
     pub fn extract(self, begin: usize, end: usize) -> String {
         get_source_manager().with(self, |x| x.extract(begin, end))
     }
+
+    /// Return the macro expansion this source was created for, if any.
+    pub fn get_expansion(self) -> Option<Expansion> {
+        get_source_manager().with(self, |x| x.get_expansion())
+    }
 }
 
 impl fmt::Debug for Source {
@@ -105,6 +110,11 @@ pub trait SourceFile {
     fn extract(&self, begin: usize, end: usize) -> String {
         self.get_content().extract(begin, end)
     }
+
+    /// Return the macro expansion this source was created for, if any.
+    fn get_expansion(&self) -> Option<Expansion> {
+        None
+    }
 }
 
 pub trait SourceContent {
@@ -132,10 +142,43 @@ pub trait SourceContent {
     fn bytes(&self) -> &[u8];
 }
 
+/// The character encoding of source files on disk.
+///
+/// Whatever the encoding, the positions of the characters in a file remain
+/// byte offsets into the file as it is stored on disk.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Encoding {
+    /// Treat files as UTF-8 if they are valid UTF-8, and as Latin-1 otherwise.
+    Auto,
+    /// Treat files as UTF-8, replacing invalid bytes with U+FFFD.
+    Utf8,
+    /// Treat files as ISO 8859-1, where each byte is one character.
+    Latin1,
+}
+
+impl Default for Encoding {
+    fn default() -> Encoding {
+        Encoding::Auto
+    }
+}
+
+impl Encoding {
+    /// Parse an encoding as given on the command line.
+    pub fn from_name(name: &str) -> Option<Encoding> {
+        match name {
+            "auto" => Some(Encoding::Auto),
+            "utf-8" => Some(Encoding::Utf8),
+            "latin-1" => Some(Encoding::Latin1),
+            _ => None,
+        }
+    }
+}
+
 /// A manager for source files and their assigned IDs.
 pub struct SourceManager {
     map: RefCell<HashMap<RcStr, Source>>,
     vect: RefCell<Vec<Box<dyn SourceFile>>>,
+    encoding: Cell<Encoding>,
 }
 
 impl SourceManager {
@@ -143,9 +186,15 @@ impl SourceManager {
         SourceManager {
             map: RefCell::new(HashMap::new()),
             vect: RefCell::new(Vec::new()),
+            encoding: Cell::new(Encoding::default()),
         }
     }
 
+    /// Set the encoding of the files subsequently opened from disk.
+    pub fn set_encoding(&self, encoding: Encoding) {
+        self.encoding.set(encoding);
+    }
+
     /// Obtain the source file for a given source ID.
     pub fn with<F, R>(&self, id: Source, f: F) -> R
     where
@@ -186,6 +235,7 @@ impl SourceManager {
             vect.push(Box::new(DiskSourceFile {
                 id: new_id,
                 filename: v,
+                encoding: self.encoding.get(),
                 content: RefCell::new(None),
             }));
             Some(new_id)
@@ -194,6 +244,15 @@ impl SourceManager {
         }
     }
 
+    /// Open a file from disk anew, discarding any content read previously.
+    ///
+    /// This picks up modifications of a file that has already been opened. The
+    /// file is assigned a new source.
+    pub fn reopen(&self, filename: &str) -> Option<Source> {
+        self.map.borrow_mut().remove(filename);
+        self.open(filename)
+    }
+
     /// Create a virtual file from the contents of a string and add it to the
     /// source manager. Future calls to `open()` with the given filename will
     /// yield the provided contents.
@@ -233,6 +292,25 @@ impl SourceManager {
         }));
         new_id
     }
+
+    /// Create a source for one expansion of a macro defined in `definition`.
+    ///
+    /// The new source shares the path and content of `definition`, such that
+    /// the tokens of the macro body keep their offsets and text. Moving them
+    /// into the new source records where they were expanded, which allows
+    /// diagnostics to show the chain of expansions.
+    pub fn add_expansion(&self, definition: Source, expansion: Expansion) -> Source {
+        let (filename, content) = self.with(definition, |x| (x.get_path(), x.get_content()));
+        let mut vect = self.vect.borrow_mut();
+        let new_id = Source(vect.len() as u32 + 1);
+        vect.push(Box::new(ExpansionSourceFile {
+            id: new_id,
+            filename,
+            content,
+            expansion,
+        }));
+        new_id
+    }
 }
 
 /// Get the global source manager.
@@ -293,11 +371,25 @@ impl SourceContent for VirtualSourceContent {
 struct DiskSourceFile {
     id: Source,
     filename: RcStr,
+    encoding: Encoding,
     content: RefCell<Option<Rc<DiskSourceContent>>>,
 }
 
-#[derive(Debug)]
-struct DiskSourceContent(pub Mmap);
+struct DiskSourceContent {
+    map: Mmap,
+    chars: DiskChars,
+}
+
+/// How the bytes of a file on disk map to characters.
+enum DiskChars {
+    /// The file is valid UTF-8.
+    Utf8,
+    /// Each byte is one character.
+    Latin1,
+    /// The characters decoded from a file that is not valid UTF-8, with
+    /// invalid bytes replaced by U+FFFD, together with their byte offsets.
+    Lossy(Vec<(usize, char)>),
+}
 
 impl SourceFile for DiskSourceFile {
     fn get_id(&self) -> Source {
@@ -312,9 +404,18 @@ impl SourceFile for DiskSourceFile {
         use memmap::Protection;
         let is_none = self.content.borrow().is_none();
         if is_none {
-            let c = Rc::new(DiskSourceContent(
-                Mmap::open_path(Path::new(&*self.filename), Protection::Read).unwrap(),
-            ));
+            let map = Mmap::open_path(Path::new(&*self.filename), Protection::Read).unwrap();
+            let chars = {
+                let bytes = unsafe { map.as_slice() };
+                let valid = std::str::from_utf8(bytes).is_ok();
+                match self.encoding {
+                    Encoding::Latin1 => DiskChars::Latin1,
+                    _ if valid => DiskChars::Utf8,
+                    Encoding::Auto => DiskChars::Latin1,
+                    Encoding::Utf8 => DiskChars::Lossy(decode_utf8_lossy(bytes)),
+                }
+            };
+            let c = Rc::new(DiskSourceContent { map, chars });
             *self.content.borrow_mut() = Some(c.clone());
             c
         } else {
@@ -323,43 +424,118 @@ impl SourceFile for DiskSourceFile {
     }
 }
 
+/// Decode bytes as UTF-8, replacing invalid sequences with U+FFFD.
+///
+/// Returns the characters together with their byte offsets.
+fn decode_utf8_lossy(bytes: &[u8]) -> Vec<(usize, char)> {
+    let mut chars = Vec::with_capacity(bytes.len());
+    let mut offset = 0;
+    while offset < bytes.len() {
+        let (valid, skip) = match std::str::from_utf8(&bytes[offset..]) {
+            Ok(s) => (s.len(), 0),
+            Err(e) => (
+                e.valid_up_to(),
+                e.error_len()
+                    .unwrap_or(bytes.len() - offset - e.valid_up_to()),
+            ),
+        };
+        let s = std::str::from_utf8(&bytes[offset..offset + valid]).unwrap();
+        chars.extend(s.char_indices().map(|(i, c)| (offset + i, c)));
+        if skip > 0 {
+            chars.push((offset + valid, '\u{FFFD}'));
+        }
+        offset += valid + skip;
+    }
+    chars
+}
+
+impl DiskSourceContent {
+    fn bytes_range(&self, begin: usize, end: usize) -> &[u8] {
+        unsafe { &self.map.as_slice()[begin..end] }
+    }
+}
+
 impl SourceContent for DiskSourceContent {
     fn iter(&self) -> Box<CharIter> {
-        use std::str;
-        Box::new(
-            str::from_utf8(unsafe { self.0.as_slice() })
-                .unwrap()
-                .char_indices(),
-        )
+        self.extract_iter(0, self.bytes().len())
     }
 
     fn iter_from(&self, offset: usize) -> Box<CharIter> {
-        use std::str;
-        Box::new(
-            str::from_utf8(unsafe { &self.0.as_slice()[offset..] })
-                .unwrap()
-                .char_indices(),
-        )
+        self.extract_iter(offset, self.bytes().len())
     }
 
     fn extract(&self, begin: usize, end: usize) -> String {
-        use std::str;
-        str::from_utf8(unsafe { &self.0.as_slice()[begin..end] })
-            .unwrap()
-            .to_string()
+        match self.chars {
+            DiskChars::Utf8 => std::str::from_utf8(self.bytes_range(begin, end))
+                .unwrap()
+                .to_string(),
+            _ => self.extract_iter(begin, end).map(|(_, c)| c).collect(),
+        }
     }
 
     fn extract_iter(&self, begin: usize, end: usize) -> Box<CharIter> {
-        use std::str;
-        Box::new(
-            str::from_utf8(unsafe { &self.0.as_slice()[begin..end] })
-                .unwrap()
-                .char_indices(),
-        )
+        match self.chars {
+            DiskChars::Utf8 => Box::new(
+                std::str::from_utf8(self.bytes_range(begin, end))
+                    .unwrap()
+                    .char_indices(),
+            ),
+            DiskChars::Latin1 => Box::new(
+                self.bytes_range(begin, end)
+                    .iter()
+                    .enumerate()
+                    .map(|(i, &b)| (i, b as char)),
+            ),
+            DiskChars::Lossy(ref chars) => {
+                let index = |offset| match chars.binary_search_by_key(&offset, |&(i, _)| i) {
+                    Ok(i) | Err(i) => i,
+                };
+                Box::new(
+                    chars[index(begin)..index(end)]
+                        .iter()
+                        .map(move |&(i, c)| (i - begin, c)),
+                )
+            }
+        }
     }
 
     fn bytes(&self) -> &[u8] {
-        unsafe { self.0.as_slice() }
+        unsafe { self.map.as_slice() }
+    }
+}
+
+/// A macro expansion.
+#[derive(Clone, Debug)]
+pub struct Expansion {
+    /// The name of the expanded macro.
+    pub name: RcStr,
+    /// The macro invocation that was expanded.
+    pub site: Span,
+}
+
+/// The tokens of one macro expansion. See `SourceManager::add_expansion`.
+struct ExpansionSourceFile {
+    id: Source,
+    filename: RcStr,
+    content: Rc<dyn SourceContent>,
+    expansion: Expansion,
+}
+
+impl SourceFile for ExpansionSourceFile {
+    fn get_id(&self) -> Source {
+        self.id
+    }
+
+    fn get_path(&self) -> RcStr {
+        self.filename.clone()
+    }
+
+    fn get_content(&self) -> Rc<dyn SourceContent> {
+        self.content.clone()
+    }
+
+    fn get_expansion(&self) -> Option<Expansion> {
+        Some(self.expansion.clone())
     }
 }
 
@@ -472,11 +648,13 @@ impl Span {
 
     /// Create a new span that covers two spans, i.e. represents the smallest
     /// possible span that fully contains both input spans `a` and `b`.
+    ///
+    /// If the spans lie in different sources, spans within macro expansions
+    /// are replaced by the macro invocation until both lie in the same source.
+    /// If that fails, returns `a`.
     pub fn union<S: Into<Span>>(a: S, b: S) -> Span {
         use std::cmp::{max, min};
-        let sa = a.into();
-        let sb = b.into();
-        // assert_eq!(sa.source, sb.source);
+        let (sa, sb) = Span::in_common_source(a.into(), b.into());
         if sa.source != sb.source {
             return sa;
         }
@@ -487,19 +665,43 @@ impl Span {
         }
     }
 
-    /// Modify this range to also cover the entirety of the `other` range. The
-    /// `other` range must lie in the same source as `self`.
+    /// Modify this range to also cover the entirety of the `other` range.
+    ///
+    /// The spans are brought into the same source as in `union`. If that
+    /// fails, `self` is left unchanged.
     pub fn expand<S: Into<Span>>(&mut self, other: S) -> &mut Self {
-        use std::cmp::{max, min};
-        let o = other.into();
-        // assert_eq!(self.source, o.source);
-        if self.source == o.source {
-            self.begin = min(self.begin, o.begin);
-            self.end = max(self.end, o.end);
+        let (s, o) = Span::in_common_source(*self, other.into());
+        if s.source == o.source {
+            *self = Span::union(s, o);
         }
         self
     }
 
+    /// Map two spans in different sources to the same source, by replacing
+    /// spans within macro expansions with the invocation of the macro.
+    ///
+    /// Returns the spans unchanged if they already lie in the same source, or
+    /// if their expansions do not lead to a common source.
+    fn in_common_source(a: Span, b: Span) -> (Span, Span) {
+        if a.source == b.source {
+            return (a, b);
+        }
+        let sites: Vec<Span> = std::iter::successors(Some(b), |sp| {
+            sp.source.get_expansion().map(|expansion| expansion.site)
+        })
+        .collect();
+        let mut site = a;
+        loop {
+            if let Some(&other) = sites.iter().find(|sp| sp.source == site.source) {
+                return (site, other);
+            }
+            site = match site.source.get_expansion() {
+                Some(expansion) => expansion.site,
+                None => return (a, b),
+            };
+        }
+    }
+
     /// Return the location just before the first character in this span.
     pub fn begin(&self) -> Location {
         Location::new(self.source, self.begin)
@@ -629,6 +831,49 @@ mod tests {
         assert_eq!(source, id);
     }
 
+    #[test]
+    fn expansion_spans() {
+        let sm = get_source_manager();
+        let main = sm.add_anonymous("`define WIDTH 8\n`define MSB (`WIDTH-1)\nx[`MSB:0]\n");
+        let msb = sm.add_expansion(
+            main,
+            Expansion {
+                name: RcStr::new("MSB"),
+                site: Span::new(main, 41, 45),
+            },
+        );
+        let width = sm.add_expansion(
+            main,
+            Expansion {
+                name: RcStr::new("WIDTH"),
+                site: Span::new(msb, 29, 35),
+            },
+        );
+
+        // Tokens of the same expansion are unioned as usual.
+        assert_eq!(
+            Span::union(Span::new(msb, 28, 29), Span::new(msb, 37, 38)),
+            Span::new(msb, 28, 38)
+        );
+
+        // Tokens of an expansion map to the invocation of the macro, through
+        // as many levels of expansion as needed.
+        let index = Span::union(Span::new(width, 14, 15), Span::new(main, 47, 48));
+        assert_eq!(index, Span::new(main, 41, 48));
+        assert_eq!(index.extract(), "`MSB:0]");
+        let mut expr = Span::new(main, 39, 40);
+        expr.expand(Span::new(width, 14, 15))
+            .expand(Span::new(main, 47, 48));
+        assert_eq!(expr.extract(), "x[`MSB:0]");
+
+        // Spans without a common source are left alone.
+        let other = sm.add_anonymous("y");
+        assert_eq!(
+            Span::union(Span::new(width, 14, 15), Span::new(other, 0, 1)),
+            Span::new(width, 14, 15)
+        );
+    }
+
     #[test]
     fn inexistent_file() {
         let sm = get_source_manager();
@@ -665,4 +910,75 @@ mod tests {
 
         assert_eq!(expected, actual);
     }
+
+    #[test]
+    fn reopen_file() {
+        use std::fs::write;
+
+        let path = "/tmp/moore-test-reopen";
+        write(path, "old\n").unwrap();
+        let sm = get_source_manager();
+        let old = sm.open(path).expect("file should exist");
+        assert_eq!(old.get_content().extract(0, 3), "old");
+
+        write(path, "new\n").unwrap();
+        assert_eq!(sm.open(path), Some(old));
+        let new = sm.reopen(path).expect("file should exist");
+        assert_ne!(new, old);
+        assert_eq!(sm.open(path), Some(new));
+        assert_eq!(new.get_content().extract(0, 3), "new");
+    }
+
+    /// Write a file unique to this test run, and return its path.
+    fn temp_file(name: &str, data: &[u8]) -> std::path::PathBuf {
+        let path = std::env::temp_dir().join(format!("moore-test-{}-{}", name, std::process::id()));
+        std::fs::write(&path, data).unwrap();
+        path
+    }
+
+    #[test]
+    fn latin1_file() {
+        let path = temp_file("latin1", b"-- caf\xe9 \xb5s\n");
+        let sm = get_source_manager();
+        let source = sm.open(path.to_str().unwrap()).expect("file should exist");
+        let content = source.get_content();
+        std::fs::remove_file(&path).unwrap();
+        let actual: Vec<_> = content.iter().collect();
+        assert_eq!(actual[6], (6, '\u{e9}'));
+        assert_eq!(actual[8], (8, '\u{b5}'));
+        assert_eq!(actual.len(), 11);
+        assert_eq!(content.extract(3, 7), "caf\u{e9}");
+        assert_eq!(content.bytes().len(), 11);
+    }
+
+    #[test]
+    fn lossy_utf8_file() {
+        let path = temp_file("lossy", b"a\xffb\xc3\xa4\n");
+        let sm = get_source_manager();
+        sm.set_encoding(Encoding::Utf8);
+        let source = sm.open(path.to_str().unwrap());
+        sm.set_encoding(Encoding::default());
+        let content = source.expect("file should exist").get_content();
+        std::fs::remove_file(&path).unwrap();
+        let actual: Vec<_> = content.iter().collect();
+        assert_eq!(
+            actual,
+            vec![
+                (0, 'a'),
+                (1, '\u{fffd}'),
+                (2, 'b'),
+                (3, '\u{e4}'),
+                (5, '\n')
+            ]
+        );
+        assert_eq!(content.extract(2, 5), "b\u{e4}");
+    }
+
+    #[test]
+    fn encoding_names() {
+        assert_eq!(Encoding::from_name("auto"), Some(Encoding::Auto));
+        assert_eq!(Encoding::from_name("utf-8"), Some(Encoding::Utf8));
+        assert_eq!(Encoding::from_name("latin-1"), Some(Encoding::Latin1));
+        assert_eq!(Encoding::from_name("ebcdic"), None);
+    }
 }
@@ -0,0 +1,178 @@
+// Copyright (c) 2016-2020 Fabian Schuiki
+
+//! Running of external conformance test suites.
+//!
+//! A suite is described by a manifest, which lists one test per line:
+//!
+//! ```text
+//! # Comments start with a hash.
+//! tests/and_gate.vhd  pass  -e and_gate
+//! tests/bad_port.vhd  fail
+//! ```
+//!
+//! Each line contains the path of the test file relative to the manifest, the
+//! expected outcome, and optionally additional arguments for the compiler. A
+//! test expected to `pass` must compile without errors; a test expected to
+//! `fail` must be rejected with an error. A test whose outcome matches the
+//! expectation is said to conform.
+//!
+//! The set of conforming tests can be recorded in a baseline file, against
+//! which later runs are compared. This makes progress in language coverage
+//! measurable, and points out regressions.
+
+use std::collections::BTreeMap;
+use std::fmt;
+use std::path::{Path, PathBuf};
+
+/// The outcome of compiling a test.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+pub enum Outcome {
+    /// The compiler accepted the input.
+    Pass,
+    /// The compiler rejected the input with an error.
+    Fail,
+    /// The compiler crashed.
+    Crash,
+}
+
+impl Outcome {
+    /// Parse an outcome as written in a manifest or baseline.
+    pub fn from_name(name: &str) -> Option<Outcome> {
+        match name {
+            "pass" => Some(Outcome::Pass),
+            "fail" => Some(Outcome::Fail),
+            "crash" => Some(Outcome::Crash),
+            _ => None,
+        }
+    }
+
+    /// Determine the outcome from the exit code of the compiler.
+    pub fn from_exit_code(code: Option<i32>) -> Outcome {
+        match code {
+            Some(0) => Outcome::Pass,
+            Some(1) => Outcome::Fail,
+            _ => Outcome::Crash,
+        }
+    }
+}
+
+impl fmt::Display for Outcome {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match *self {
+            Outcome::Pass => write!(f, "pass"),
+            Outcome::Fail => write!(f, "fail"),
+            Outcome::Crash => write!(f, "crash"),
+        }
+    }
+}
+
+/// A test listed in a manifest.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Test {
+    /// The path of the test as written in the manifest. This identifies the
+    /// test in the baseline.
+    pub name: String,
+    /// The path of the test file.
+    pub path: PathBuf,
+    /// The expected outcome.
+    pub expected: Outcome,
+    /// Additional arguments to pass to the compiler.
+    pub args: Vec<String>,
+}
+
+/// Parse a manifest.
+///
+/// Relative test paths are resolved against `dir`, usually the directory
+/// containing the manifest. Returns an error message mentioning the offending
+/// line if the manifest is malformed.
+pub fn parse_manifest(text: &str, dir: &Path) -> Result<Vec<Test>, String> {
+    let mut tests = vec![];
+    for (index, line) in text.lines().enumerate() {
+        let line = line.trim();
+        if line.is_empty() || line.starts_with('#') {
+            continue;
+        }
+        let mut fields = line.split_whitespace();
+        let name = fields.next().unwrap();
+        let expected = match fields.next().map(Outcome::from_name) {
+            Some(Some(Outcome::Crash)) | Some(None) | None => {
+                return Err(format!(
+                    "line {}: expected `pass` or `fail` after `{}`",
+                    index + 1,
+                    name
+                ))
+            }
+            Some(Some(x)) => x,
+        };
+        tests.push(Test {
+            name: name.to_string(),
+            path: dir.join(name),
+            expected,
+            args: fields.map(String::from).collect(),
+        });
+    }
+    Ok(tests)
+}
+
+/// The outcomes of a run of a suite, by test name.
+pub type Results = BTreeMap<String, Outcome>;
+
+/// Parse a baseline previously written by `write_baseline`.
+pub fn parse_baseline(text: &str) -> Result<Results, String> {
+    let mut results = Results::new();
+    for (index, line) in text.lines().enumerate() {
+        let line = line.trim();
+        if line.is_empty() || line.starts_with('#') {
+            continue;
+        }
+        let mut fields = line.split_whitespace();
+        let name = fields.next().unwrap();
+        match fields.next().and_then(Outcome::from_name) {
+            Some(outcome) => {
+                results.insert(name.to_string(), outcome);
+            }
+            None => return Err(format!("line {}: expected an outcome", index + 1)),
+        }
+    }
+    Ok(results)
+}
+
+/// Render the results of a run as a baseline.
+pub fn write_baseline(results: &Results) -> String {
+    let mut text = String::new();
+    for (name, outcome) in results {
+        text.push_str(&format!("{} {}\n", name, outcome));
+    }
+    text
+}
+
+/// The difference in conformance between a baseline and a new run.
+#[derive(Debug, Default, Clone, PartialEq, Eq)]
+pub struct Delta {
+    /// Tests that conform now, but did not in the baseline.
+    pub fixed: Vec<String>,
+    /// Tests that conformed in the baseline, but no longer do.
+    pub regressed: Vec<String>,
+    /// Tests that are not in the baseline.
+    pub added: Vec<String>,
+}
+
+/// Compare the results of a run against a baseline.
+pub fn compare(tests: &[Test], baseline: &Results, results: &Results) -> Delta {
+    let mut delta = Delta::default();
+    for test in tests {
+        let now = results.get(&test.name) == Some(&test.expected);
+        match baseline.get(&test.name) {
+            Some(&before) => {
+                let before = before == test.expected;
+                if now && !before {
+                    delta.fixed.push(test.name.clone());
+                } else if !now && before {
+                    delta.regressed.push(test.name.clone());
+                }
+            }
+            None => delta.added.push(test.name.clone()),
+        }
+    }
+    delta
+}
@@ -14,14 +14,18 @@ pub(crate) fn accept_visitor(input: TokenStream, with_foreach: bool) -> TokenStr
 
     // Generate the match that visits the relevant fields of the input.
     let mut visits = vec![];
+    let mut visits_mut = vec![];
     let mut eachs = vec![];
     let dont_visit = has_dont_visit(&input.attrs);
     match &input.data {
         syn::Data::Struct(input) => {
-            let (visit, each) = visit_fields(&input.fields, dont_visit);
+            let (visit, visit_mut, each) = visit_fields(&input.fields, dont_visit);
             visits.push(quote! {
                 #name #visit
             });
+            visits_mut.push(quote! {
+                #name #visit_mut
+            });
             eachs.push(quote! {
                 #name #each
             });
@@ -30,10 +34,13 @@ pub(crate) fn accept_visitor(input: TokenStream, with_foreach: bool) -> TokenStr
             for variant in &input.variants {
                 let dont_visit = dont_visit || has_dont_visit(&variant.attrs);
                 let variant_name = &variant.ident;
-                let (visit, each) = visit_fields(&variant.fields, dont_visit);
+                let (visit, visit_mut, each) = visit_fields(&variant.fields, dont_visit);
                 visits.push(quote! {
                     #name::#variant_name #visit
                 });
+                visits_mut.push(quote! {
+                    #name::#variant_name #visit_mut
+                });
                 eachs.push(quote! {
                     #name::#variant_name #each
                 });
@@ -71,12 +78,67 @@ pub(crate) fn accept_visitor(input: TokenStream, with_foreach: bool) -> TokenStr
                     self.for_each_child(each);
                 }
             }
+
+            impl #impl_generics AcceptVisitorMut<#lt> for #name #generics {
+                fn accept_mut(&mut self, visitor: &mut dyn VisitorMut<#lt>) {
+                    match self {
+                        #(#visits_mut,)*
+                    }
+                }
+            }
         });
     }
 
     output.into()
 }
 
+pub(crate) fn accept_rewriter(input: TokenStream) -> TokenStream {
+    // Parse the input.
+    let input = syn::parse_macro_input!(input as DeriveInput);
+
+    let name = &input.ident;
+    let generics = &input.generics;
+
+    // Generate the match that rebuilds the input from its rewritten fields.
+    let mut rewrites = vec![];
+    let dont_visit = has_dont_visit(&input.attrs);
+    match &input.data {
+        syn::Data::Struct(input) => {
+            let (pat, rebuild) = rewrite_fields(&input.fields, dont_visit);
+            rewrites.push(quote! {
+                #name #pat => #name #rebuild
+            });
+        }
+        syn::Data::Enum(input) => {
+            for variant in &input.variants {
+                let dont_visit = dont_visit || has_dont_visit(&variant.attrs);
+                let variant_name = &variant.ident;
+                let (pat, rebuild) = rewrite_fields(&variant.fields, dont_visit);
+                rewrites.push(quote! {
+                    #name::#variant_name #pat => #name::#variant_name #rebuild
+                });
+            }
+        }
+        _ => panic!("unsupported item for AcceptRewriter"),
+    };
+
+    // Determine the impl generics, which may add another lifetime.
+    let mut impl_generics = generics.clone();
+    let lt = crate::first_lifetime(&mut impl_generics);
+
+    // Generate the implementation of the `AcceptRewriter` trait.
+    let output = quote! {
+        impl #impl_generics AcceptRewriter<#lt> for #name #generics {
+            fn accept_rewrite(self, rewriter: &mut dyn Rewriter<#lt>) -> Self {
+                match self {
+                    #(#rewrites,)*
+                }
+            }
+        }
+    };
+    output.into()
+}
+
 /// Check if a field has the `#[dont_visit]` attribute.
 fn has_dont_visit(attrs: &[syn::Attribute]) -> bool {
     attrs
@@ -88,7 +150,11 @@ fn has_dont_visit(attrs: &[syn::Attribute]) -> bool {
 fn visit_fields(
     fields: &syn::Fields,
     dont_visit: bool,
-) -> (proc_macro2::TokenStream, proc_macro2::TokenStream) {
+) -> (
+    proc_macro2::TokenStream,
+    proc_macro2::TokenStream,
+    proc_macro2::TokenStream,
+) {
     // Generate a destructuring pattern that assigns predictable names to all
     // fields.
     let mut names = vec![];
@@ -130,6 +196,11 @@ fn visit_fields(
                 #(#names.walk(visitor);)*
             }
         },
+        quote! {
+            #pat => {
+                #(#names.walk_mut(visitor);)*
+            }
+        },
         quote! {
             #pat => {
                 #(#names.for_each_node(each);)*
@@ -137,3 +208,49 @@ fn visit_fields(
         },
     )
 }
+
+/// Generate the destructuring pattern and the rebuilding expression to rewrite
+/// the fields in a struct-like item.
+fn rewrite_fields(
+    fields: &syn::Fields,
+    dont_visit: bool,
+) -> (proc_macro2::TokenStream, proc_macro2::TokenStream) {
+    // Rewrite all fields that we do not skip or ignore, and move the others
+    // over unchanged.
+    let rewrite = |name: &syn::Ident, attrs: &[syn::Attribute]| {
+        if !dont_visit && !has_dont_visit(attrs) {
+            quote! { #name.walk_rewrite(rewriter) }
+        } else {
+            quote! { #name }
+        }
+    };
+    match fields {
+        syn::Fields::Named(ref fields) => {
+            let mut mapping = vec![];
+            let mut rebuild = vec![];
+            for (i, field) in fields.named.iter().enumerate() {
+                let field_name = &field.ident;
+                let name = format_ident!("arg{}", i);
+                let value = rewrite(&name, &field.attrs);
+                mapping.push(quote! {
+                    #field_name: #name
+                });
+                rebuild.push(quote! {
+                    #field_name: #value
+                });
+            }
+            (quote! { {#(#mapping),*} }, quote! { {#(#rebuild),*} })
+        }
+        syn::Fields::Unnamed(ref fields) => {
+            let mut mapping = vec![];
+            let mut rebuild = vec![];
+            for (i, field) in fields.unnamed.iter().enumerate() {
+                let name = format_ident!("arg{}", i);
+                rebuild.push(rewrite(&name, &field.attrs));
+                mapping.push(name);
+            }
+            (quote! { (#(#mapping),*) }, quote! { (#(#rebuild),*) })
+        }
+        syn::Fields::Unit => (quote! {}, quote! {}),
+    }
+}
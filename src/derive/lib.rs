@@ -44,6 +44,12 @@ pub fn accept_visitor_and_foreach(input: TokenStream) -> TokenStream {
     accept_visitor::accept_visitor(input, true)
 }
 
+/// Generate an `AcceptRewriter` implementation.
+#[proc_macro_derive(AcceptRewriter, attributes(dont_visit))]
+pub fn accept_rewriter(input: TokenStream) -> TokenStream {
+    accept_visitor::accept_rewriter(input)
+}
+
 /// Wrap a struct or enum in a `Node`.
 #[proc_macro_attribute]
 pub fn node(args: TokenStream, input: TokenStream) -> TokenStream {
@@ -62,13 +68,28 @@ pub fn walk_visitor(args: TokenStream, input: TokenStream) -> TokenStream {
     walk_visitor::walk_visitor(args, input)
 }
 
-/// Convenience macro to derive `AcceptVisitorAndForeach` and `walk_visitor`.
+/// Generate corresponding `*_visit_*_mut` functions in a mutable visitor.
+#[proc_macro_attribute]
+pub fn walk_visitor_mut(_args: TokenStream, input: TokenStream) -> TokenStream {
+    walk_visitor::walk_visitor_mut(input)
+}
+
+/// Generate the corresponding `*_rewrite_*` functions in a rewriter.
+#[proc_macro_attribute]
+pub fn walk_rewriter(_args: TokenStream, input: TokenStream) -> TokenStream {
+    walk_visitor::walk_rewriter(input)
+}
+
+/// Convenience macro to derive `AcceptVisitorAndForeach`, `AcceptRewriter`,
+/// `walk_visitor`, `walk_visitor_mut`, and `walk_rewriter`.
 #[proc_macro_attribute]
 pub fn visit(_args: TokenStream, input: TokenStream) -> TokenStream {
     let input = proc_macro2::TokenStream::from(input);
     TokenStream::from(quote! {
         #[moore_derive::walk_visitor]
-        #[derive(moore_derive::AcceptVisitorAndForeach)]
+        #[moore_derive::walk_visitor_mut]
+        #[moore_derive::walk_rewriter]
+        #[derive(moore_derive::AcceptVisitorAndForeach, moore_derive::AcceptRewriter)]
         #input
     })
 }
@@ -84,6 +105,17 @@ pub fn visit_without_foreach(_args: TokenStream, input: TokenStream) -> TokenStr
     })
 }
 
+/// Convenience macro to derive `AcceptRewriter` and `walk_rewriter`.
+#[proc_macro_attribute]
+pub fn rewrite(_args: TokenStream, input: TokenStream) -> TokenStream {
+    let input = proc_macro2::TokenStream::from(input);
+    TokenStream::from(quote! {
+        #[moore_derive::walk_rewriter]
+        #[derive(moore_derive::AcceptRewriter)]
+        #input
+    })
+}
+
 /// Generate a `AllNode` enum.
 #[proc_macro]
 pub fn derive_all_node(input: TokenStream) -> TokenStream {
@@ -102,6 +134,18 @@ pub fn derive_visitor(input: TokenStream) -> TokenStream {
     visitor::visitor(input)
 }
 
+/// Generate a `VisitorMut` trait.
+#[proc_macro]
+pub fn derive_visitor_mut(input: TokenStream) -> TokenStream {
+    visitor::visitor_mut(input)
+}
+
+/// Generate a `Rewriter` trait.
+#[proc_macro]
+pub fn derive_rewriter(input: TokenStream) -> TokenStream {
+    visitor::rewriter(input)
+}
+
 /// Mark a function as a compiler query.
 #[proc_macro_attribute]
 pub fn query(args: TokenStream, input: TokenStream) -> TokenStream {
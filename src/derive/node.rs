@@ -31,6 +31,8 @@ pub(crate) fn node(args: TokenStream, input: TokenStream) -> TokenStream {
     output.extend(quote! {
         #[moore_derive::arena]
         #[moore_derive::walk_visitor(node)]
+        #[moore_derive::walk_visitor_mut]
+        #[moore_derive::walk_rewriter]
         #vis type #node_name #impl_generics = Node<#lt, #data_name #generics>;
     });
 
@@ -38,6 +40,7 @@ pub(crate) fn node(args: TokenStream, input: TokenStream) -> TokenStream {
     output.extend(quote! {
         #[derive(
             moore_derive::AcceptVisitorAndForeach,
+            moore_derive::AcceptRewriter,
             moore_derive::AnyNodeData
         )]
         #input
@@ -12,6 +12,8 @@ use syn::Generics;
 // minute. Better have a robust CI.
 thread_local! {
     static CALLS: RefCell<Vec<Call>> = Default::default();
+    static CALLS_MUT: RefCell<Vec<Call>> = Default::default();
+    static CALLS_REWRITE: RefCell<Vec<Call>> = Default::default();
 }
 
 struct Call {
@@ -29,6 +31,22 @@ pub(crate) fn add_call(name: &Ident, generics: &Generics) {
     CALLS.with(|c| c.borrow_mut().push(call));
 }
 
+pub(crate) fn add_call_mut(name: &Ident, generics: &Generics) {
+    let call = Call {
+        name: name.to_string(),
+        generics: generics.to_token_stream().to_string(),
+    };
+    CALLS_MUT.with(|c| c.borrow_mut().push(call));
+}
+
+pub(crate) fn add_call_rewrite(name: &Ident, generics: &Generics) {
+    let call = Call {
+        name: name.to_string(),
+        generics: generics.to_token_stream().to_string(),
+    };
+    CALLS_REWRITE.with(|c| c.borrow_mut().push(call));
+}
+
 pub(crate) fn visitor(input: TokenStream) -> TokenStream {
     let input = proc_macro2::TokenStream::from(input);
 
@@ -106,3 +124,154 @@ pub(crate) fn visitor(input: TokenStream) -> TokenStream {
     };
     output.into()
 }
+
+pub(crate) fn visitor_mut(input: TokenStream) -> TokenStream {
+    let input = proc_macro2::TokenStream::from(input);
+
+    // Flush the accumulated calls.
+    let calls = CALLS_MUT.with(|c| std::mem::replace(&mut *c.borrow_mut(), Default::default()));
+
+    // Determine a lifetime for the visitor.
+    let lt: syn::Lifetime = syn::parse_str("'a").unwrap();
+
+    // Generate some documentation.
+    let mut doc = format!(
+        "A mutable visitor.\n\nUse the `accept_mut()` function to start visiting nodes. \
+         Assign to the node passed to a `pre_visit_*_mut` or `post_visit_*_mut` function to \
+         replace it. Nodes shared by reference, such as the ones allocated in an arena, are \
+         not visited. For example:\n\n```ignore\nstruct MagicVisitor;\n\nimpl VisitorMut \
+         for MagicVisitor {{\n}}\n\nnode.accept_mut(&mut MagicVisitor);\n```\n\n"
+    );
+    doc.push_str("Implements the visitor pattern over the following nodes:\n\n");
+    for call in &calls {
+        doc.push_str(&format!("- `{}`\n", call.name));
+    }
+
+    // Generate the `visit_*_mut` calls.
+    let mut emitted = HashSet::new();
+    let mut pre_calls = vec![];
+    let mut post_calls = vec![];
+    for call in calls {
+        // Avoid duplicates.
+        if !emitted.insert(call.name.clone()) {
+            continue;
+        }
+
+        // Convert the names back to identifiers.
+        let pre_visit_fn = format_ident!("pre_visit_{}_mut", call.name.to_snake_case());
+        let post_visit_fn = format_ident!("post_visit_{}_mut", call.name.to_snake_case());
+        let name = format_ident!("{}", call.name);
+        let generics: syn::Generics = syn::parse_str(&call.generics).unwrap();
+
+        // Generate some documentation.
+        let pre_doc = format!(
+            "Called for every `{}` node before visiting its children.\n\nReturn `false` from this \
+             function to not visit the node's children.",
+            name
+        );
+        let post_doc = format!(
+            "Called for every `{}` node after visiting its children.",
+            name
+        );
+
+        // Render the corresponding call.
+        pre_calls.push(quote! {
+            #[doc = #pre_doc]
+            fn #pre_visit_fn (&mut self, node: &mut #name #generics) -> bool {
+                true
+            }
+        });
+        post_calls.push(quote! {
+            #[doc = #post_doc]
+            fn #post_visit_fn (&mut self, node: &mut #name #generics) {
+            }
+        });
+    }
+
+    let output = quote! {
+        #[doc = #doc]
+        pub trait VisitorMut<#lt> {
+            #input
+            #(#pre_calls)*
+            #(#post_calls)*
+        }
+    };
+    output.into()
+}
+
+pub(crate) fn rewriter(input: TokenStream) -> TokenStream {
+    let input = proc_macro2::TokenStream::from(input);
+
+    // Flush the accumulated calls.
+    let calls = CALLS_REWRITE.with(|c| std::mem::replace(&mut *c.borrow_mut(), Default::default()));
+
+    // Determine a lifetime for the rewriter.
+    let lt: syn::Lifetime = syn::parse_str("'a").unwrap();
+
+    // Generate some documentation.
+    let mut doc = format!(
+        "A rewriter.\n\nUse the `walk_rewrite()` function to start rewriting nodes. Each node \
+         is consumed and rebuilt from its rewritten children, after which the corresponding \
+         `post_rewrite_*` function may replace it. Nodes shared by reference, such as the ones \
+         allocated in an arena, are passed through unchanged. For example:\n\n```ignore\n\
+         struct MagicRewriter;\n\nimpl Rewriter for MagicRewriter {{\n}}\n\n\
+         let node = node.walk_rewrite(&mut MagicRewriter);\n```\n\n"
+    );
+    doc.push_str("Implements the rewriter pattern over the following nodes:\n\n");
+    for call in &calls {
+        doc.push_str(&format!("- `{}`\n", call.name));
+    }
+
+    // Generate the `*_rewrite_*` calls.
+    let mut emitted = HashSet::new();
+    let mut pre_calls = vec![];
+    let mut post_calls = vec![];
+    for call in calls {
+        // Avoid duplicates.
+        if !emitted.insert(call.name.clone()) {
+            continue;
+        }
+
+        // Convert the names back to identifiers.
+        let pre_rewrite_fn = format_ident!("pre_rewrite_{}", call.name.to_snake_case());
+        let post_rewrite_fn = format_ident!("post_rewrite_{}", call.name.to_snake_case());
+        let name = format_ident!("{}", call.name);
+        let generics: syn::Generics = syn::parse_str(&call.generics).unwrap();
+
+        // Generate some documentation.
+        let pre_doc = format!(
+            "Called for every `{}` node before rewriting its children.\n\nReturn `false` from \
+             this function to leave the node's children untouched.",
+            name
+        );
+        let post_doc = format!(
+            "Called for every `{}` node after rewriting its children.\n\nReturns the node that \
+             replaces `node` in the tree.",
+            name
+        );
+
+        // Render the corresponding call.
+        pre_calls.push(quote! {
+            #[doc = #pre_doc]
+            fn #pre_rewrite_fn (&mut self, node: &#name #generics) -> bool {
+                true
+            }
+        });
+        post_calls.push(quote! {
+            #[doc = #post_doc]
+            fn #post_rewrite_fn (&mut self, node: #name #generics) -> #name #generics {
+                node
+            }
+        });
+    }
+
+    let output = quote! {
+        #[doc = #doc]
+        pub trait Rewriter<#lt> {
+            #input
+            #(#pre_calls)*
+            #(#post_calls)*
+        }
+    };
+    output.into()
+}
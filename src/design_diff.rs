@@ -0,0 +1,201 @@
+// Copyright (c) 2016-2020 Fabian Schuiki
+
+//! Structural comparison of two elaborated designs.
+//!
+//! The comparison operates on the LLHD modules of two elaborations, usually
+//! snapshots saved with `--save-snapshot`, and reports which units were added
+//! or removed, which ports of a unit changed, and how the number of instances
+//! in a unit changed. Since every parameter configuration of a module is
+//! emitted as a separate entity, changed parameters show up as added and
+//! removed units.
+
+use llhd::ir::{Module, Opcode, Unit, UnitKind};
+use std::collections::{BTreeMap, BTreeSet};
+use std::fmt;
+
+/// A structural change between two designs.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Change {
+    /// A unit only present in the new design.
+    UnitAdded(UnitKind, String),
+    /// A unit only present in the old design.
+    UnitRemoved(UnitKind, String),
+    /// A port only present in the new version of a unit.
+    PortAdded {
+        unit: String,
+        port: String,
+        ty: String,
+    },
+    /// A port only present in the old version of a unit.
+    PortRemoved {
+        unit: String,
+        port: String,
+        ty: String,
+    },
+    /// A port whose type changed.
+    PortChanged {
+        unit: String,
+        port: String,
+        old: String,
+        new: String,
+    },
+    /// A change in the number of instances of `target` in `unit`.
+    Instances {
+        unit: String,
+        target: String,
+        old: usize,
+        new: usize,
+    },
+    /// A change in the number of processes in the design.
+    Processes { old: usize, new: usize },
+}
+
+impl fmt::Display for Change {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Change::UnitAdded(kind, name) => write!(f, "+ {} {}", kind, name),
+            Change::UnitRemoved(kind, name) => write!(f, "- {} {}", kind, name),
+            Change::PortAdded { unit, port, ty } => write!(f, "+ {}: port {} {}", unit, ty, port),
+            Change::PortRemoved { unit, port, ty } => {
+                write!(f, "- {}: port {} {}", unit, ty, port)
+            }
+            Change::PortChanged {
+                unit,
+                port,
+                old,
+                new,
+            } => write!(f, "~ {}: port {} {} -> {}", unit, port, old, new),
+            Change::Instances {
+                unit,
+                target,
+                old,
+                new,
+            } => write!(f, "~ {}: instances of {} {} -> {}", unit, target, old, new),
+            Change::Processes { old, new } => write!(f, "~ processes {} -> {}", old, new),
+        }
+    }
+}
+
+/// The structure of a single unit relevant for the comparison.
+struct UnitSummary {
+    kind: UnitKind,
+    /// The ports of the unit, by name, with their type.
+    ports: Vec<(String, String)>,
+    /// The number of instances of each unit.
+    instances: BTreeMap<String, usize>,
+}
+
+impl UnitSummary {
+    fn new(unit: Unit) -> Self {
+        let mut ports = vec![];
+        for (i, arg) in unit.input_args().chain(unit.output_args()).enumerate() {
+            let name = match unit.get_name(arg) {
+                Some(name) => format!("%{}", name),
+                None => format!("#{}", i),
+            };
+            ports.push((name, unit.value_type(arg).to_string()));
+        }
+        let mut instances = BTreeMap::new();
+        for inst in unit.all_insts() {
+            if unit[inst].opcode() == Opcode::Inst {
+                let target = unit.extern_name(unit[inst].get_ext_unit().unwrap());
+                *instances.entry(target.to_string()).or_insert(0) += 1;
+            }
+        }
+        UnitSummary {
+            kind: unit.kind(),
+            ports,
+            instances,
+        }
+    }
+}
+
+/// Compare two designs and list the structural changes from `old` to `new`.
+pub fn diff(old: &Module, new: &Module) -> Vec<Change> {
+    let summarize = |module: &Module| -> BTreeMap<String, UnitSummary> {
+        module
+            .units()
+            .map(|unit| (unit.name().to_string(), UnitSummary::new(unit)))
+            .collect()
+    };
+    let old_units = summarize(old);
+    let new_units = summarize(new);
+    let mut changes = vec![];
+
+    for (name, unit) in &old_units {
+        if !new_units.contains_key(name) {
+            changes.push(Change::UnitRemoved(unit.kind, name.clone()));
+        }
+    }
+    for (name, unit) in &new_units {
+        let old_unit = match old_units.get(name) {
+            Some(x) => x,
+            None => {
+                changes.push(Change::UnitAdded(unit.kind, name.clone()));
+                continue;
+            }
+        };
+
+        // Compare the ports.
+        for (port, ty) in &old_unit.ports {
+            if !unit.ports.iter().any(|(p, _)| p == port) {
+                changes.push(Change::PortRemoved {
+                    unit: name.clone(),
+                    port: port.clone(),
+                    ty: ty.clone(),
+                });
+            }
+        }
+        for (port, ty) in &unit.ports {
+            match old_unit.ports.iter().find(|(p, _)| p == port) {
+                None => changes.push(Change::PortAdded {
+                    unit: name.clone(),
+                    port: port.clone(),
+                    ty: ty.clone(),
+                }),
+                Some((_, old_ty)) if old_ty != ty => changes.push(Change::PortChanged {
+                    unit: name.clone(),
+                    port: port.clone(),
+                    old: old_ty.clone(),
+                    new: ty.clone(),
+                }),
+                _ => (),
+            }
+        }
+
+        // Compare the instances.
+        let targets: BTreeSet<_> = old_unit
+            .instances
+            .keys()
+            .chain(unit.instances.keys())
+            .collect();
+        for target in targets {
+            let old_count = old_unit.instances.get(target).cloned().unwrap_or(0);
+            let new_count = unit.instances.get(target).cloned().unwrap_or(0);
+            if old_count != new_count {
+                changes.push(Change::Instances {
+                    unit: name.clone(),
+                    target: target.clone(),
+                    old: old_count,
+                    new: new_count,
+                });
+            }
+        }
+    }
+
+    // Compare the number of processes.
+    let count_procs = |units: &BTreeMap<String, UnitSummary>| {
+        units
+            .values()
+            .filter(|u| u.kind == UnitKind::Process)
+            .count()
+    };
+    let (old_procs, new_procs) = (count_procs(&old_units), count_procs(&new_units));
+    if old_procs != new_procs {
+        changes.push(Change::Processes {
+            old: old_procs,
+            new: new_procs,
+        });
+    }
+    changes
+}
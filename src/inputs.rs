@@ -0,0 +1,312 @@
+// Copyright (c) 2016-2020 Fabian Schuiki
+
+//! Expansion of input files, directories, and glob patterns.
+//!
+//! Inputs given on the command line may be individual files, directories which
+//! are searched recursively, or glob patterns such as `rtl/**/*.sv`. The
+//! language of each file is derived from its extension. The default mapping
+//! can be extended on the command line, and on a per-directory basis by placing
+//! a `.moore-lang` file into a directory. Each line of such a file has the form
+//! `EXT=LANG` and applies to the directory and all its subdirectories; lines
+//! starting with `#` are ignored.
+
+use crate::errors::*;
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+
+/// The name of the per-directory extension override file.
+pub const LANG_MAP_FILE: &str = ".moore-lang";
+
+/// The languages an input file may be written in.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Language {
+    Verilog,
+    SystemVerilog,
+    Vhdl,
+}
+
+impl Language {
+    /// Parse a language name as used on the command line.
+    pub fn from_name(name: &str) -> Option<Language> {
+        match name.to_lowercase().as_str() {
+            "v" | "verilog" => Some(Language::Verilog),
+            "sv" | "svlog" | "systemverilog" => Some(Language::SystemVerilog),
+            "vhd" | "vhdl" => Some(Language::Vhdl),
+            _ => None,
+        }
+    }
+}
+
+/// A mapping from file extensions to languages.
+#[derive(Debug, Clone)]
+pub struct LanguageMap {
+    map: HashMap<String, Language>,
+}
+
+impl Default for LanguageMap {
+    fn default() -> Self {
+        let mut map = HashMap::new();
+        map.insert("sv".to_string(), Language::SystemVerilog);
+        map.insert("svh".to_string(), Language::SystemVerilog);
+        map.insert("v".to_string(), Language::Verilog);
+        map.insert("vh".to_string(), Language::Verilog);
+        map.insert("vhd".to_string(), Language::Vhdl);
+        map.insert("vhdl".to_string(), Language::Vhdl);
+        LanguageMap { map }
+    }
+}
+
+impl LanguageMap {
+    /// Add a mapping of the form `EXT=LANG`.
+    pub fn add(&mut self, mapping: &str) -> Result<(), ()> {
+        let mut iter = mapping.splitn(2, '=');
+        let ext = iter.next().unwrap().trim().trim_start_matches('.');
+        let lang = iter.next().and_then(|l| Language::from_name(l.trim()));
+        match lang {
+            Some(lang) if !ext.is_empty() => {
+                self.map.insert(ext.to_string(), lang);
+                Ok(())
+            }
+            _ => Err(()),
+        }
+    }
+
+    /// Determine the language of a file.
+    pub fn lookup(&self, path: &Path) -> Option<Language> {
+        path.extension()
+            .and_then(|s| s.to_str())
+            .and_then(|s| self.map.get(s).cloned())
+    }
+
+    /// Extend the mapping with the overrides in a directory, if any.
+    fn with_dir_overrides(&self, dir: &Path, sess: &dyn DiagEmitter) -> LanguageMap {
+        let path = dir.join(LANG_MAP_FILE);
+        let content = match std::fs::read_to_string(&path) {
+            Ok(c) => c,
+            Err(_) => return self.clone(),
+        };
+        let mut map = self.clone();
+        for line in content.lines().map(str::trim) {
+            if line.is_empty() || line.starts_with('#') {
+                continue;
+            }
+            if map.add(line).is_err() {
+                sess.emit(
+                    DiagBuilder2::warning(format!(
+                        "ignoring invalid mapping `{}` in `{}`",
+                        line,
+                        path.display()
+                    ))
+                    .add_note("Mappings must be of the form `EXT=LANG`"),
+                );
+            }
+        }
+        map
+    }
+}
+
+/// An input file together with its language.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct InputFile {
+    pub path: PathBuf,
+    pub language: Option<Language>,
+}
+
+/// Expand a list of command line inputs into individual files.
+///
+/// Files are passed through as they are, even if their language cannot be
+/// determined, such that the caller may report them. Directories and glob
+/// patterns only yield files whose language is known, in sorted order.
+pub fn expand_inputs<'a>(
+    inputs: impl IntoIterator<Item = &'a str>,
+    map: &LanguageMap,
+    sess: &dyn DiagEmitter,
+) -> Vec<InputFile> {
+    let mut files = vec![];
+    for input in inputs {
+        let path = Path::new(input);
+        if is_glob(input) {
+            let before = files.len();
+            expand_glob(input, map, sess, &mut files);
+            if files.len() == before {
+                sess.emit(DiagBuilder2::warning(format!(
+                    "pattern `{}` does not match any files",
+                    input
+                )));
+            }
+        } else if path.is_dir() {
+            walk_dir(path, &map.with_dir_overrides(path, sess), sess, &mut files);
+        } else {
+            let map = match path.parent() {
+                Some(dir) if !dir.as_os_str().is_empty() => map.with_dir_overrides(dir, sess),
+                _ => map.clone(),
+            };
+            files.push(InputFile {
+                path: path.to_path_buf(),
+                language: map.lookup(path),
+            });
+        }
+    }
+    files
+}
+
+/// Check whether an input contains glob metacharacters.
+fn is_glob(input: &str) -> bool {
+    input.contains(|c| c == '*' || c == '?' || c == '[')
+}
+
+/// Collect all files with a known language in a directory, recursively.
+fn walk_dir(dir: &Path, map: &LanguageMap, sess: &dyn DiagEmitter, files: &mut Vec<InputFile>) {
+    for path in read_dir_sorted(dir, sess) {
+        if path.is_dir() {
+            walk_dir(&path, &map.with_dir_overrides(&path, sess), sess, files);
+        } else if let Some(language) = map.lookup(&path) {
+            files.push(InputFile {
+                path,
+                language: Some(language),
+            });
+        }
+    }
+}
+
+/// List the entries of a directory in sorted order.
+fn read_dir_sorted(dir: &Path, sess: &dyn DiagEmitter) -> Vec<PathBuf> {
+    let mut entries: Vec<_> = match std::fs::read_dir(dir) {
+        Ok(rd) => rd.filter_map(|e| e.ok()).map(|e| e.path()).collect(),
+        Err(e) => {
+            sess.emit(DiagBuilder2::error(format!(
+                "cannot read directory `{}`: {}",
+                dir.display(),
+                e
+            )));
+            return vec![];
+        }
+    };
+    entries.sort();
+    entries
+}
+
+/// Expand a glob pattern.
+///
+/// Supports `*` and `?` within a path component, character classes `[abc]`,
+/// and `**` as a component matching any number of directories.
+fn expand_glob(
+    pattern: &str,
+    map: &LanguageMap,
+    sess: &dyn DiagEmitter,
+    files: &mut Vec<InputFile>,
+) {
+    // Split the pattern into a literal base directory and the components that
+    // need matching.
+    let components: Vec<&str> = pattern.split('/').collect();
+    let literal = components.iter().take_while(|c| !is_glob(c)).count();
+    let mut base = PathBuf::new();
+    if pattern.starts_with('/') {
+        base.push("/");
+    }
+    for c in &components[..literal] {
+        base.push(c);
+    }
+    if base.as_os_str().is_empty() {
+        base.push(".");
+    }
+    let strip_dot = !pattern.starts_with("./");
+    let mut matches = vec![];
+    glob_walk(&base, &components[literal..], sess, &mut matches);
+    matches.sort();
+    matches.dedup();
+    for path in matches {
+        let path = match path.strip_prefix("./") {
+            Ok(p) if strip_dot => p.to_path_buf(),
+            _ => path,
+        };
+        let map = match path.parent() {
+            Some(dir) if !dir.as_os_str().is_empty() => map.with_dir_overrides(dir, sess),
+            _ => map.clone(),
+        };
+        if let Some(language) = map.lookup(&path) {
+            files.push(InputFile {
+                path,
+                language: Some(language),
+            });
+        }
+    }
+}
+
+fn glob_walk(dir: &Path, pattern: &[&str], sess: &dyn DiagEmitter, matches: &mut Vec<PathBuf>) {
+    let (first, rest) = match pattern.split_first() {
+        Some(x) => x,
+        None => {
+            if dir.is_file() {
+                matches.push(dir.to_path_buf());
+            }
+            return;
+        }
+    };
+    if first.is_empty() {
+        return glob_walk(dir, rest, sess, matches);
+    }
+    if !dir.is_dir() {
+        return;
+    }
+    if *first == "**" {
+        // Match zero directories, then descend into each subdirectory.
+        glob_walk(dir, rest, sess, matches);
+        for path in read_dir_sorted(dir, sess) {
+            if path.is_dir() {
+                glob_walk(&path, pattern, sess, matches);
+            }
+        }
+        return;
+    }
+    if !is_glob(first) {
+        return glob_walk(&dir.join(first), rest, sess, matches);
+    }
+    for path in read_dir_sorted(dir, sess) {
+        let name = match path.file_name().and_then(|n| n.to_str()) {
+            Some(n) => n,
+            None => continue,
+        };
+        if !name.starts_with('.') && glob_match(first.as_bytes(), name.as_bytes()) {
+            glob_walk(&path, rest, sess, matches);
+        }
+    }
+}
+
+/// Match a single path component against a pattern.
+fn glob_match(pattern: &[u8], name: &[u8]) -> bool {
+    match pattern.split_first() {
+        None => name.is_empty(),
+        Some((b'*', rest)) => (0..=name.len()).any(|i| glob_match(rest, &name[i..])),
+        Some((b'?', rest)) => !name.is_empty() && glob_match(rest, &name[1..]),
+        Some((b'[', rest)) => {
+            let end = match rest.iter().position(|&c| c == b']') {
+                Some(end) => end,
+                None => return name.first() == Some(&b'[') && glob_match(rest, &name[1..]),
+            };
+            let (class, rest) = (&rest[..end], &rest[end + 1..]);
+            let (negate, class) = match class.split_first() {
+                Some((b'!', class)) | Some((b'^', class)) => (true, class),
+                _ => (false, class),
+            };
+            match name.split_first() {
+                Some((c, name)) => {
+                    let mut found = false;
+                    let mut i = 0;
+                    while i < class.len() {
+                        if i + 2 < class.len() && class[i + 1] == b'-' {
+                            found |= class[i] <= *c && *c <= class[i + 2];
+                            i += 3;
+                        } else {
+                            found |= class[i] == *c;
+                            i += 1;
+                        }
+                    }
+                    found != negate && glob_match(rest, name)
+                }
+                None => false,
+            }
+        }
+        Some((c, rest)) => name.first() == Some(c) && glob_match(rest, &name[1..]),
+    }
+}
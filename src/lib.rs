@@ -15,4 +15,24 @@ pub use moore_common::*;
 pub use moore_svlog as svlog;
 pub use moore_vhdl as vhdl;
 
+pub mod aiger;
+pub mod annotate;
+pub mod batch;
+pub mod bindings;
+pub mod c_header;
+pub mod conformance;
+pub mod design_diff;
+pub mod inputs;
+pub mod libdirs;
+pub mod manifest;
+pub mod mem_infer;
+pub mod reduce;
 pub mod score;
+pub mod select;
+pub mod server;
+pub mod signal_kind;
+pub mod size_report;
+pub mod smt;
+pub mod stdlib;
+pub mod watch;
+pub mod wrapper;
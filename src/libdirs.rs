@@ -0,0 +1,131 @@
+// Copyright (c) 2016-2020 Fabian Schuiki
+
+//! Verilog library directories.
+//!
+//! Following the convention of other Verilog tools, modules that are
+//! instantiated but not defined in any of the input files are looked up in the
+//! library directories given with `-y DIR`. A module `foo` is found in a file
+//! named after the module with one of the library extensions, which are given
+//! as `+libext+.v+.sv` and default to `.sv` and `.v`. Only the files of modules
+//! that are actually needed are analyzed, and the modules they instantiate are
+//! looked up in turn.
+
+use crate::common::name::Name;
+use crate::score::Ast;
+use crate::svlog::ast as svlog_ast;
+use crate::vhdl::syntax::query::unit_name;
+use std::collections::HashSet;
+use std::path::{Path, PathBuf};
+
+/// The extensions searched if none are given with `+libext+`.
+pub const DEFAULT_EXTENSIONS: &[&str] = &[".sv", ".v"];
+
+/// The library directories and extensions to search for missing modules.
+#[derive(Debug, Clone, Default)]
+pub struct LibrarySearch {
+    /// The directories to search, in order.
+    pub dirs: Vec<PathBuf>,
+    /// The file extensions to try, in order, including the leading dot.
+    pub exts: Vec<String>,
+}
+
+impl LibrarySearch {
+    /// Find the file defining the module `name`.
+    ///
+    /// Returns the first file named after the module in any of the library
+    /// directories, trying all extensions in one directory before moving on to
+    /// the next.
+    pub fn find(&self, name: &str) -> Option<PathBuf> {
+        let default_exts: Vec<String>;
+        let exts = if self.exts.is_empty() {
+            default_exts = DEFAULT_EXTENSIONS.iter().map(|s| s.to_string()).collect();
+            &default_exts
+        } else {
+            &self.exts
+        };
+        self.dirs
+            .iter()
+            .find_map(|dir| find_in_dir(dir, name, exts))
+    }
+}
+
+fn find_in_dir(dir: &Path, name: &str, exts: &[String]) -> Option<PathBuf> {
+    exts.iter()
+        .map(|ext| dir.join(format!("{}{}", name, ext)))
+        .find(|path| path.is_file())
+}
+
+/// Parse a `+libext+` argument into the extensions it lists.
+///
+/// Returns `None` if the argument is not a `+libext+` argument. For example
+/// `+libext+.v+.sv` lists `.v` and `.sv`.
+pub fn parse_libext(arg: &str) -> Option<Vec<String>> {
+    let prefix = "+libext+";
+    if !arg.starts_with(prefix) {
+        return None;
+    }
+    let exts = &arg[prefix.len()..];
+    Some(
+        exts.split('+')
+            .filter(|ext| !ext.is_empty())
+            .map(String::from)
+            .collect(),
+    )
+}
+
+/// Find the modules that are instantiated but not defined in a design.
+///
+/// Modules, interfaces, and packages, as well as VHDL design units, count as
+/// defined. The names are returned in the order they are first instantiated.
+pub fn missing_modules<'a, 'b: 'a>(asts: impl IntoIterator<Item = &'a Ast<'b>>) -> Vec<Name> {
+    let mut defined = HashSet::new();
+    let mut insts = vec![];
+    for ast in asts {
+        match *ast {
+            Ast::Vhdl(ref x) => defined.extend(x.iter().map(|unit| unit_name(unit).value)),
+            Ast::Svlog(ref x) => collect_items(&x.items, &mut defined, &mut insts),
+        }
+    }
+    let mut seen = HashSet::new();
+    insts
+        .into_iter()
+        .filter(|name| !defined.contains(name) && seen.insert(*name))
+        .collect()
+}
+
+/// Collect the units defined and the modules instantiated in a list of items.
+///
+/// This walks the items by hand rather than with a visitor, since the latter
+/// would keep the syntax trees borrowed for as long as the arena lives, and
+/// more trees are added to the design once their modules are found.
+fn collect_items(items: &[svlog_ast::Item], defined: &mut HashSet<Name>, insts: &mut Vec<Name>) {
+    for item in items {
+        match item.data {
+            svlog_ast::ItemData::ModuleDecl(ref x) => {
+                defined.insert(x.name.value);
+                collect_items(&x.items, defined, insts);
+            }
+            svlog_ast::ItemData::InterfaceDecl(ref x) => {
+                defined.insert(x.name.value);
+                collect_items(&x.items, defined, insts);
+            }
+            svlog_ast::ItemData::PackageDecl(ref x) => {
+                defined.insert(x.name.value);
+            }
+            svlog_ast::ItemData::GenerateRegion(_, ref items) => {
+                collect_items(items, defined, insts);
+            }
+            svlog_ast::ItemData::GenerateFor(ref x) => {
+                collect_items(&x.block.items, defined, insts);
+            }
+            svlog_ast::ItemData::GenerateIf(ref x) => {
+                collect_items(&x.main_block.items, defined, insts);
+                if let Some(ref block) = x.else_block {
+                    collect_items(&block.items, defined, insts);
+                }
+            }
+            svlog_ast::ItemData::Inst(ref x) => insts.push(x.target.value),
+            _ => (),
+        }
+    }
+}
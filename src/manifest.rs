@@ -0,0 +1,153 @@
+// Copyright (c) 2016-2020 Fabian Schuiki
+
+//! Project manifests grouping the sources of a design into libraries.
+//!
+//! Each library has its own SystemVerilog include search paths and macro
+//! definitions, such that third-party IP with conflicting macro names or
+//! private include trees can be compiled alongside the rest of the design:
+//!
+//! ```text
+//! # Comments start with a hash.
+//! library common_cells
+//!     include vendor/common_cells/include
+//!     define  COMMON_CELLS_ASSERTS_OFF
+//!     files   vendor/common_cells/src
+//!
+//! library work
+//!     include rtl/include
+//!     define  WIDTH=8 TRACE
+//!     files   rtl/**/*.sv rtl/top.vhd
+//!     param   DEPTH=16,32 LANES=1,2,4
+//! ```
+//!
+//! A `library` line starts a library, and the `include`, `define`, `files`, and
+//! `param` lines that follow apply to it. Each of them may list several values.
+//! Paths are relative to the manifest, and files may be given as directories or
+//! glob patterns as on the command line. Listing the same library again adds to
+//! it.
+//!
+//! The `param` lines override the parameters or generics of the units
+//! elaborated from the library that is compiled into, as `-P` does on the
+//! command line. Several comma-separated values sweep over all combinations.
+
+use std::path::{Path, PathBuf};
+
+/// A library listed in a manifest.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Library {
+    /// The name of the library.
+    pub name: String,
+    /// The search paths for includes, in the order they are searched.
+    pub include_paths: Vec<PathBuf>,
+    /// The macro definitions, as name and optional value.
+    pub defines: Vec<(String, Option<String>)>,
+    /// The source files, directories, and glob patterns.
+    pub files: Vec<String>,
+    /// The parameter overrides, as name and the values to sweep over.
+    pub params: Vec<(String, Vec<i64>)>,
+}
+
+/// Parse a manifest.
+///
+/// Relative paths are resolved against `dir`, usually the directory containing
+/// the manifest. Returns an error message mentioning the offending line if the
+/// manifest is malformed.
+pub fn parse_manifest(text: &str, dir: &Path) -> Result<Vec<Library>, String> {
+    let mut libs: Vec<Library> = vec![];
+    let mut current = None;
+    for (index, line) in text.lines().enumerate() {
+        let line = line.trim();
+        if line.is_empty() || line.starts_with('#') {
+            continue;
+        }
+        let mut fields = line.split_whitespace();
+        let keyword = fields.next().unwrap();
+        let values: Vec<_> = fields.collect();
+        if keyword == "library" {
+            let name = match values.as_slice() {
+                &[name] => name,
+                _ => return Err(format!("line {}: expected one library name", index + 1)),
+            };
+            current = Some(match libs.iter().position(|l| l.name == name) {
+                Some(i) => i,
+                None => {
+                    libs.push(Library {
+                        name: name.to_string(),
+                        include_paths: vec![],
+                        defines: vec![],
+                        files: vec![],
+                        params: vec![],
+                    });
+                    libs.len() - 1
+                }
+            });
+            continue;
+        }
+        let lib = match current {
+            Some(i) => &mut libs[i],
+            None => {
+                return Err(format!(
+                    "line {}: `{}` before the first `library`",
+                    index + 1,
+                    keyword
+                ))
+            }
+        };
+        match keyword {
+            "include" => lib
+                .include_paths
+                .extend(values.iter().map(|path| dir.join(path))),
+            "define" => lib.defines.extend(values.iter().map(|def| {
+                let mut iter = def.splitn(2, '=');
+                (
+                    iter.next().unwrap().to_string(),
+                    iter.next().map(String::from),
+                )
+            })),
+            "files" => lib.files.extend(
+                values
+                    .iter()
+                    .map(|path| dir.join(path).to_string_lossy().into_owned()),
+            ),
+            "param" => {
+                for param in &values {
+                    match parse_param(param) {
+                        Some(x) => lib.params.push(x),
+                        None => {
+                            return Err(format!(
+                                "line {}: invalid parameter override `{}`; expected \
+                                 `NAME=VALUE[,VALUE...]`",
+                                index + 1,
+                                param
+                            ))
+                        }
+                    }
+                }
+            }
+            _ => {
+                return Err(format!(
+                    "line {}: unknown directive `{}`; expected `library`, `include`, `define`, \
+                     `files`, or `param`",
+                    index + 1,
+                    keyword
+                ))
+            }
+        }
+    }
+    Ok(libs)
+}
+
+/// Parse a parameter override of the form `NAME=VALUE[,VALUE...]`.
+fn parse_param(param: &str) -> Option<(String, Vec<i64>)> {
+    let mut iter = param.splitn(2, '=');
+    let name = iter.next().unwrap();
+    let values = iter.next()?;
+    if name.is_empty() {
+        return None;
+    }
+    let values = values
+        .split(',')
+        .map(|v| v.parse().ok())
+        .collect::<Option<Vec<_>>>()?;
+    Some((name.to_string(), values))
+}
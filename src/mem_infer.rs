@@ -0,0 +1,264 @@
+// Copyright (c) 2016-2020 Fabian Schuiki
+
+//! Inference of memories in generated code.
+//!
+//! Arrays that are written at a computed address in a clocked process are
+//! candidates for being mapped to block RAM by downstream synthesis. This
+//! module finds such arrays among the signals of every entity, together with
+//! the processes that read and write them, and annotates the LLHD assembly with
+//! a comment describing each memory.
+//!
+//! A process is considered clocked if it only waits on a subset of the signals
+//! it reads, as is the case for processes sensitive to a clock edge. Reads in a
+//! clocked process are synchronous, all other reads are asynchronous.
+
+use llhd::ir::{Module, Opcode, Unit, UnitName, Value};
+use llhd::TypeKind;
+use std::collections::HashSet;
+use std::fmt;
+
+/// A memory inferred from an array signal.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Memory {
+    /// The entity which declares the memory.
+    pub entity: UnitName,
+    /// The name of the memory signal, as it appears in the assembly.
+    pub name: String,
+    /// The number of words.
+    pub depth: usize,
+    /// The number of bits per word.
+    pub width: usize,
+    /// The accesses to the memory.
+    pub ports: Vec<Port>,
+}
+
+/// An access to a memory at a computed address.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Port {
+    /// The unit performing the access.
+    pub unit: UnitName,
+    /// The kind of access.
+    pub kind: PortKind,
+}
+
+/// The different kinds of memory accesses.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+pub enum PortKind {
+    /// A write in a clocked process.
+    Write,
+    /// A read in a clocked process.
+    SyncRead,
+    /// A read outside of a clocked process.
+    AsyncRead,
+}
+
+impl fmt::Display for Memory {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(
+            f,
+            "memory {}: {} x {} bits",
+            self.name, self.depth, self.width
+        )?;
+        for port in &self.ports {
+            let kind = match port.kind {
+                PortKind::Write => "write",
+                PortKind::SyncRead => "sync read",
+                PortKind::AsyncRead => "async read",
+            };
+            write!(f, "; {} in {}", kind, port.unit)?;
+        }
+        Ok(())
+    }
+}
+
+/// Find the memories in a module.
+pub fn infer(module: &Module) -> Vec<Memory> {
+    let mut memories = vec![];
+    for entity in module.units().filter(|u| u.is_entity()) {
+        for inst in entity.all_insts() {
+            if entity[inst].opcode() != Opcode::Sig {
+                continue;
+            }
+            let value = entity.inst_result(inst);
+            let (depth, width) = match word_shape(entity, value) {
+                Some(x) => x,
+                None => continue,
+            };
+
+            // Collect the accesses in the entity itself, and in the processes
+            // the signal is connected to.
+            let mut ports: Vec<_> = accesses(entity, value)
+                .into_iter()
+                .map(|kind| Port {
+                    unit: entity.name().clone(),
+                    kind,
+                })
+                .collect();
+            for &user in entity.uses(value) {
+                let data = &entity[user];
+                if data.opcode() != Opcode::Inst {
+                    continue;
+                }
+                let name = entity.extern_name(data.get_ext_unit().unwrap());
+                let callee = match module.units().find(|u| u.name() == name) {
+                    Some(x) => x,
+                    None => continue,
+                };
+                let args = data
+                    .input_args()
+                    .iter()
+                    .zip(callee.input_args())
+                    .chain(data.output_args().iter().zip(callee.output_args()));
+                for (&outer, inner) in args {
+                    if outer != value {
+                        continue;
+                    }
+                    ports.extend(accesses(callee, inner).into_iter().map(|kind| Port {
+                        unit: callee.name().clone(),
+                        kind,
+                    }));
+                }
+            }
+
+            ports.sort_by_key(|p| (p.unit.to_string(), p.kind));
+            if ports.iter().any(|p| p.kind == PortKind::Write) {
+                memories.push(Memory {
+                    entity: entity.name().clone(),
+                    name: value.dump(&entity).to_string(),
+                    depth,
+                    width,
+                    ports,
+                });
+            }
+        }
+    }
+    memories
+}
+
+/// Determine the number and width of the words of an array signal.
+fn word_shape(unit: Unit, value: Value) -> Option<(usize, usize)> {
+    let ty = unit.value_type(value);
+    match *ty {
+        TypeKind::SignalType(ref ty) => match **ty {
+            TypeKind::ArrayType(depth, ref elem) => match **elem {
+                TypeKind::IntType(width) => Some((depth, width)),
+                _ => None,
+            },
+            _ => None,
+        },
+        _ => None,
+    }
+}
+
+/// Find the accesses at a computed address to an array signal in a unit.
+///
+/// Writes outside of clocked processes, such as the initialization in an
+/// initial block, are not memory ports and are skipped.
+fn accesses(unit: Unit, signal: Value) -> Vec<PortKind> {
+    let clocked = is_clocked(unit);
+    let read = if clocked {
+        PortKind::SyncRead
+    } else {
+        PortKind::AsyncRead
+    };
+    let mut ports = vec![];
+    for &user in unit.uses(signal) {
+        match unit[user].opcode() {
+            // Writes and reads of a shifted signal, i.e. `drv` or `prb` of
+            // `extf (shr %mem, %hidden, %addr), 0`.
+            Opcode::Shr if unit[user].args()[0] == signal => {
+                if !is_computed(unit, unit[user].args()[2]) {
+                    continue;
+                }
+                for word in elements(unit, unit.inst_result(user)) {
+                    for &access in unit.uses(word) {
+                        match unit[access].opcode() {
+                            Opcode::Drv if unit[access].args()[0] == word && clocked => {
+                                ports.push(PortKind::Write)
+                            }
+                            Opcode::Prb => ports.push(read),
+                            _ => (),
+                        }
+                    }
+                }
+            }
+
+            // Reads of a probed value, i.e. `extf (shr (prb %mem), ...), 0`.
+            Opcode::Prb => {
+                let probed = unit.inst_result(user);
+                for &shift in unit.uses(probed) {
+                    let data = &unit[shift];
+                    if data.opcode() == Opcode::Shr
+                        && data.args()[0] == probed
+                        && is_computed(unit, data.args()[2])
+                        && !elements(unit, unit.inst_result(shift)).is_empty()
+                    {
+                        ports.push(read);
+                    }
+                }
+            }
+            _ => (),
+        }
+    }
+    ports
+}
+
+/// Find the elements extracted from a shifted array.
+fn elements(unit: Unit, shifted: Value) -> Vec<Value> {
+    unit.uses(shifted)
+        .iter()
+        .filter(|&&inst| match unit[inst].opcode() {
+            Opcode::ExtField | Opcode::ExtSlice => true,
+            _ => false,
+        })
+        .map(|&inst| unit.inst_result(inst))
+        .collect()
+}
+
+/// Check whether an address is computed rather than constant.
+fn is_computed(unit: Unit, addr: Value) -> bool {
+    unit.get_const_int(addr).is_none()
+}
+
+/// Check whether a unit is a clocked process.
+///
+/// This is the case if every `wait` in the process is sensitive to some, but
+/// not all of the signals probed by the process.
+fn is_clocked(unit: Unit) -> bool {
+    if !unit.is_process() {
+        return false;
+    }
+    let probed: HashSet<Value> = unit
+        .all_insts()
+        .filter(|&inst| unit[inst].opcode() == Opcode::Prb)
+        .map(|inst| unit[inst].args()[0])
+        .collect();
+    let mut waits = unit
+        .all_insts()
+        .filter(|&inst| match unit[inst].opcode() {
+            Opcode::Wait | Opcode::WaitTime => true,
+            _ => false,
+        })
+        .peekable();
+    if waits.peek().is_none() {
+        return false;
+    }
+    waits.all(|inst| {
+        let sensitive: HashSet<Value> = unit[inst]
+            .args()
+            .iter()
+            .cloned()
+            .filter(|v| unit.value_type(*v).is_signal())
+            .collect();
+        !sensitive.is_empty() && sensitive.len() < probed.len() && sensitive.is_subset(&probed)
+    })
+}
+
+/// Describe each memory in a note on the entity declaring it, to be rendered
+/// by [`annotate::write_module`](crate::annotate::write_module).
+pub fn notes(memories: &[Memory]) -> Vec<(UnitName, String)> {
+    memories
+        .iter()
+        .map(|memory| (memory.entity.clone(), memory.to_string()))
+        .collect()
+}
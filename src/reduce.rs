@@ -0,0 +1,304 @@
+// Copyright (c) 2016-2020 Fabian Schuiki
+
+//! Minimization of inputs that trigger a compiler bug.
+//!
+//! Given a set of input files and a test that checks whether the files still
+//! reproduce a problem, the reducer removes as much of the input as possible
+//! while keeping the problem reproducible. It uses delta debugging, first on
+//! entire files, then on design units, declarations, and finally statements.
+//! The syntax tree of each file is used to determine which parts of the text
+//! can be removed, but the removal itself operates on the text, such that the
+//! formatting and comments of the remaining input are preserved.
+
+use crate::common::source::{get_source_manager, Span};
+use crate::common::util::HasSpan;
+use crate::inputs::Language;
+use crate::svlog::{self, ast as svlog_ast, ast::AcceptVisitor};
+use crate::vhdl::syntax::ast as vhdl_ast;
+use crate::vhdl::syntax::visit::{self as vhdl_visit, WalkVisitor as _};
+use std::path::PathBuf;
+
+/// An input file being reduced.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ReduceFile {
+    /// The path of the original file.
+    pub path: PathBuf,
+    /// The language of the file.
+    pub language: Language,
+    /// The current contents of the file.
+    pub text: String,
+}
+
+/// The granularity at which the input is reduced.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Level {
+    /// VHDL design units and SystemVerilog modules, interfaces, and packages.
+    Units,
+    /// Declarations and items within units.
+    Decls,
+    /// Statements.
+    Stmts,
+}
+
+/// Minimize a set of input files.
+///
+/// The `test` function is called with a candidate set of files and returns
+/// whether the candidate still reproduces the problem. The files passed in are
+/// expected to reproduce the problem. Returns the reduced set of files.
+pub fn reduce(
+    files: Vec<ReduceFile>,
+    mut test: impl FnMut(&[ReduceFile]) -> bool,
+) -> Vec<ReduceFile> {
+    // Remove entire files first.
+    let keep = ddmin(files.len(), |keep| {
+        !keep.is_empty() && test(&keep.iter().map(|&i| files[i].clone()).collect::<Vec<_>>())
+    });
+    let mut files: Vec<_> = keep.into_iter().map(|i| files[i].clone()).collect();
+    debug!("reduced to {} files", files.len());
+
+    // Then remove parts of the files, from coarse to fine. Removing smaller
+    // parts may enable the removal of larger ones, so repeat until the input
+    // no longer shrinks.
+    loop {
+        let size: usize = files.iter().map(|f| f.text.len()).sum();
+        for &level in &[Level::Units, Level::Decls, Level::Stmts] {
+            files = reduce_level(files, level, &mut test);
+        }
+        let new_size: usize = files.iter().map(|f| f.text.len()).sum();
+        debug!("reduced from {} to {} bytes", size, new_size);
+        if new_size >= size {
+            break;
+        }
+    }
+    files
+}
+
+/// Remove parts of the files at one level of granularity.
+fn reduce_level(
+    files: Vec<ReduceFile>,
+    level: Level,
+    test: &mut impl FnMut(&[ReduceFile]) -> bool,
+) -> Vec<ReduceFile> {
+    let chunks: Vec<(usize, (usize, usize))> = files
+        .iter()
+        .enumerate()
+        .flat_map(|(i, f)| removable_ranges(f, level).into_iter().map(move |r| (i, r)))
+        .collect();
+    debug!("trying {} {:?} chunks", chunks.len(), level);
+    if chunks.is_empty() {
+        return files;
+    }
+    let apply = |keep: &[usize]| -> Vec<ReduceFile> {
+        files
+            .iter()
+            .enumerate()
+            .map(|(i, f)| {
+                let removed: Vec<_> = chunks
+                    .iter()
+                    .enumerate()
+                    .filter(|&(ci, &(fi, _))| fi == i && !keep.contains(&ci))
+                    .map(|(_, &(_, r))| r)
+                    .collect();
+                ReduceFile {
+                    text: remove_ranges(&f.text, removed),
+                    ..f.clone()
+                }
+            })
+            .collect()
+    };
+    let keep = ddmin(chunks.len(), |keep| test(&apply(keep)));
+    apply(&keep)
+}
+
+/// Find a minimal subset of `n` items for which `test` holds.
+///
+/// This is the classic delta debugging algorithm. The test is called with the
+/// indices of the items to keep. Assumes that the test holds for all items.
+pub fn ddmin(n: usize, mut test: impl FnMut(&[usize]) -> bool) -> Vec<usize> {
+    let mut items: Vec<usize> = (0..n).collect();
+    if !items.is_empty() && test(&[]) {
+        return vec![];
+    }
+    let mut granularity = 2;
+    while items.len() >= 2 {
+        let chunk = (items.len() + granularity - 1) / granularity;
+        let mut reduced = false;
+        for start in (0..items.len()).step_by(chunk) {
+            let end = (start + chunk).min(items.len());
+            let complement: Vec<_> = items[..start]
+                .iter()
+                .chain(items[end..].iter())
+                .cloned()
+                .collect();
+            if test(&complement) {
+                items = complement;
+                granularity = (granularity - 1).max(2);
+                reduced = true;
+                break;
+            }
+        }
+        if !reduced {
+            if granularity >= items.len() {
+                break;
+            }
+            granularity = (granularity * 2).min(items.len());
+        }
+    }
+    items
+}
+
+/// Remove a set of possibly overlapping byte ranges from a text.
+fn remove_ranges(text: &str, mut ranges: Vec<(usize, usize)>) -> String {
+    ranges.sort();
+    let mut result = String::with_capacity(text.len());
+    let mut pos = 0;
+    for (begin, end) in ranges {
+        if begin > pos {
+            result.push_str(&text[pos..begin]);
+        }
+        pos = pos.max(end);
+    }
+    if pos < text.len() {
+        result.push_str(&text[pos..]);
+    }
+    result
+}
+
+/// Determine the byte ranges in a file that may be removed at a level.
+///
+/// Returns no ranges if the file does not parse.
+fn removable_ranges(file: &ReduceFile, level: Level) -> Vec<(usize, usize)> {
+    let source = get_source_manager().add_anonymous(file.text.clone());
+    let spans = match file.language {
+        Language::Vhdl => match crate::vhdl::syntax::parse(source) {
+            Ok(units) => vhdl_spans(&units, level),
+            Err(()) => vec![],
+        },
+        Language::SystemVerilog | Language::Verilog => {
+            let arena = svlog_ast::Arena::default();
+            let preproc = svlog::preproc::Preprocessor::new(source, &[], &[]);
+            let lexer = svlog::lexer::Lexer::new(preproc);
+            match svlog::parser::parse(lexer, &arena) {
+                Ok(ast) => svlog_spans(&ast, level),
+                Err(()) => vec![],
+            }
+        }
+    };
+    spans
+        .into_iter()
+        .filter(|s| s.source == source && s.begin < s.end && s.end <= file.text.len())
+        .map(|s| (s.begin, s.end))
+        .collect()
+}
+
+/// Collect the spans of a level in VHDL design units.
+fn vhdl_spans(units: &[vhdl_ast::DesignUnit], level: Level) -> Vec<Span> {
+    match level {
+        Level::Units => units.iter().map(vhdl_unit_span).collect(),
+        _ => {
+            let mut collector = VhdlSpans {
+                level,
+                spans: vec![],
+            };
+            for unit in units {
+                unit.walk(&mut collector);
+            }
+            collector.spans
+        }
+    }
+}
+
+/// Get the span of a VHDL design unit, including its context clause.
+fn vhdl_unit_span(unit: &vhdl_ast::DesignUnit) -> Span {
+    let mut span = match unit.data {
+        vhdl_ast::DesignUnitData::EntityDecl(ref x) => x.span,
+        vhdl_ast::DesignUnitData::CfgDecl(ref x) => x.span,
+        vhdl_ast::DesignUnitData::PkgDecl(ref x) => x.span,
+        vhdl_ast::DesignUnitData::PkgInst(ref x) => x.span,
+        vhdl_ast::DesignUnitData::CtxDecl(ref x) => x.span,
+        vhdl_ast::DesignUnitData::ArchBody(ref x) => x.span,
+        vhdl_ast::DesignUnitData::PkgBody(ref x) => x.span,
+    };
+    for item in &unit.ctx {
+        match *item {
+            vhdl_ast::CtxItem::LibClause(ref x) => span.expand(x.span),
+            vhdl_ast::CtxItem::UseClause(ref x) => span.expand(x.span),
+            vhdl_ast::CtxItem::CtxRef(ref x) => span.expand(x.span),
+        };
+    }
+    span
+}
+
+struct VhdlSpans {
+    level: Level,
+    spans: Vec<Span>,
+}
+
+impl<'a> vhdl_visit::Visitor<'a> for VhdlSpans {
+    fn pre_visit_decl_item(&mut self, node: &'a vhdl_ast::DeclItem) -> bool {
+        if self.level == Level::Decls {
+            self.spans.push(node.span());
+        }
+        true
+    }
+
+    fn pre_visit_stmt(&mut self, node: &'a vhdl_ast::Stmt) -> bool {
+        if self.level == Level::Stmts {
+            self.spans.push(node.span);
+        }
+        true
+    }
+}
+
+/// Collect the spans of a level in a SystemVerilog source file.
+fn svlog_spans<'a>(file: &'a svlog_ast::SourceFile<'a>, level: Level) -> Vec<Span> {
+    let units: Vec<_> = file
+        .items
+        .iter()
+        .filter(|item| match item.data {
+            svlog_ast::ItemData::ModuleDecl(..)
+            | svlog_ast::ItemData::InterfaceDecl(..)
+            | svlog_ast::ItemData::PackageDecl(..) => true,
+            _ => false,
+        })
+        .map(|item| item.span)
+        .collect();
+    match level {
+        Level::Units => units,
+        _ => {
+            let mut collector = SvlogSpans {
+                level,
+                spans: vec![],
+            };
+            for item in &file.items {
+                item.accept(&mut collector);
+            }
+            collector
+                .spans
+                .into_iter()
+                .filter(|s| !units.contains(s))
+                .collect()
+        }
+    }
+}
+
+struct SvlogSpans {
+    level: Level,
+    spans: Vec<Span>,
+}
+
+impl<'a> svlog_ast::Visitor<'a> for SvlogSpans {
+    fn pre_visit_item(&mut self, node: &'a svlog_ast::Item<'a>) -> bool {
+        if self.level == Level::Decls {
+            self.spans.push(node.span);
+        }
+        true
+    }
+
+    fn pre_visit_stmt(&mut self, node: &'a svlog_ast::Stmt<'a>) -> bool {
+        if self.level == Level::Stmts {
+            self.spans.push(node.span);
+        }
+        true
+    }
+}
@@ -8,7 +8,7 @@
 
 use crate::common::errors::*;
 use crate::common::name::Name;
-use crate::common::score::{GenericContext, NodeMaker, NodeRef, Result};
+use crate::common::score::{CancelToken, Error, GenericContext, NodeMaker, NodeRef, Result};
 use crate::common::source::Spanned;
 use crate::common::NodeId;
 use crate::common::Session;
@@ -35,6 +35,8 @@ pub struct ScoreContext<'lazy, 'sb: 'lazy, 'ast: 'sb, 'ctx: 'sb> {
     pub vhdl_phases: &'lazy vhdl::lazy::LazyPhaseTable<'sb, 'ast, 'ctx>,
     /// The SystemVerilog scoreboard.
     pub svlog: &'sb svlog::GlobalContext<'ast>,
+    /// The token through which running queries are cancelled.
+    pub cancel: &'lazy CancelToken,
 }
 
 /// The global scoreboard that drives the compilation of pretty much everything.
@@ -89,6 +91,7 @@ impl<'lazy, 'sb, 'ast, 'ctx> ScoreContext<'lazy, 'sb, 'ast, 'ctx> {
             global: self,
             sb: self.vhdl,
             lazy: self.vhdl_phases,
+            cancel: self.cancel,
         }
     }
 
@@ -113,7 +116,7 @@ impl<'lazy, 'sb, 'ast, 'ctx> ScoreContext<'lazy, 'sb, 'ast, 'ctx> {
             Ast::Svlog(ref a) => Some(a),
             _ => None,
         });
-        self.svlog.add_files(svlog_ast);
+        self.svlog.add_files(name, svlog_ast);
 
         id
     }
@@ -152,7 +155,7 @@ impl<'lazy, 'sb, 'ast, 'ctx> NodeMaker<ScopeRef, &'ctx Defs>
                             "Library `{}` defined multiple times",
                             name
                         )));
-                        return Err(());
+                        return Err(Error::Reported);
                     }
                 }
                 Ok(self.sb.arenas.defs.alloc(defs))
@@ -217,7 +220,7 @@ impl<'lazy, 'sb, 'ast, 'ctx> NodeMaker<ScopeRef, &'ctx Defs>
                     defs.insert(name, both_defs[0].value);
                 }
                 if had_dups {
-                    return Err(());
+                    return Err(Error::Reported);
                 }
 
                 // Return the defs of definitions.
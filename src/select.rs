@@ -0,0 +1,254 @@
+// Copyright (c) 2016-2020 Fabian Schuiki
+
+//! Selection of a design unit and its dependencies.
+//!
+//! When debugging a single unit of a large design it is wasteful to analyze
+//! and lower every unit that was passed on the command line. This module
+//! narrows the parsed design down to one unit and everything it transitively
+//! depends on, before any further processing takes place.
+//!
+//! Dependencies are determined on the syntax tree alone, by collecting every
+//! simple name a unit mentions. This over-approximates the actual set of
+//! dependencies, but never misses one. Watch mode uses the same analysis to
+//! find the files a unit depends on, and only elaborates the unit again if one
+//! of them changed.
+
+use crate::common::name::Name;
+use crate::common::source::Source;
+use crate::score::Ast;
+use crate::svlog::ast::{self as svlog_ast, AcceptVisitor};
+use crate::vhdl::syntax::ast as vhdl_ast;
+use crate::vhdl::syntax::query::unit_name;
+use crate::vhdl::syntax::visit::{self as vhdl_visit, WalkVisitor as _};
+use std::collections::HashSet;
+
+/// A design unit as seen by the dependency analysis.
+struct UnitInfo {
+    /// The name under which other units refer to this unit.
+    name: Name,
+    /// For secondary units such as architectures and package bodies, the name
+    /// of the primary unit they belong to.
+    owner: Option<Name>,
+    /// The names mentioned in the unit.
+    mentions: HashSet<Name>,
+    /// The source file the unit is defined in.
+    source: Source,
+}
+
+/// Select the unit `target` and its dependencies from a parsed design.
+///
+/// Architectures and package bodies are selected together with the entity or
+/// package they belong to. SystemVerilog items outside of modules, interfaces,
+/// and packages are always selected. Returns the selected units, or `None` if
+/// no unit named `target` exists.
+pub fn select_unit_deps<'a>(asts: &'a [Ast<'a>], target: Name) -> Option<Vec<Ast<'a>>> {
+    let units = unit_infos(asts);
+    let selected = select(&units, target)?;
+
+    // Assemble a copy of the design with only the selected units. The syntax
+    // trees are not linked yet at this point, so copying them is safe.
+    let mut selected = selected.into_iter();
+    let result = asts
+        .iter()
+        .map(|ast| match *ast {
+            Ast::Vhdl(ref x) => Ast::Vhdl(
+                x.iter()
+                    .filter(|_| selected.next().unwrap())
+                    .cloned()
+                    .collect(),
+            ),
+            Ast::Svlog(ref x) => Ast::Svlog(svlog_ast::SourceFile::new(
+                x.span,
+                svlog_ast::SourceFileData {
+                    timeunits: x.timeunits.clone(),
+                    items: x
+                        .items
+                        .iter()
+                        .filter(|item| svlog_unit_name(item).is_none() || selected.next().unwrap())
+                        .cloned()
+                        .collect(),
+                    attrs: x.attrs.clone(),
+                },
+            )),
+        })
+        .collect();
+    Some(result)
+}
+
+/// Determine the source files of the unit `target` and its dependencies.
+///
+/// The files of SystemVerilog items outside of modules, interfaces, and
+/// packages are always included. Returns the paths of the files, or `None` if
+/// no unit named `target` exists.
+pub fn unit_sources<'a>(
+    asts: impl IntoIterator<Item = &'a Ast<'a>> + Clone,
+    target: Name,
+) -> Option<Vec<String>> {
+    let units = unit_infos(asts.clone());
+    let selected = select(&units, target)?;
+    let mut paths: Vec<String> = units
+        .iter()
+        .zip(selected)
+        .filter(|&(_, selected)| selected)
+        .map(|(unit, _)| unit.source.get_path().to_string())
+        .collect();
+    for ast in asts {
+        if let Ast::Svlog(ref x) = *ast {
+            paths.extend(
+                x.items
+                    .iter()
+                    .filter(|item| svlog_unit_name(item).is_none())
+                    .map(|item| item.span.source.get_path().to_string()),
+            );
+        }
+    }
+    paths.sort();
+    paths.dedup();
+    Some(paths)
+}
+
+/// Gather the units of a design and the names they mention.
+fn unit_infos<'a>(asts: impl IntoIterator<Item = &'a Ast<'a>>) -> Vec<UnitInfo> {
+    let mut units = vec![];
+    for ast in asts {
+        match *ast {
+            Ast::Vhdl(ref x) => units.extend(x.iter().map(vhdl_unit_info)),
+            Ast::Svlog(ref x) => units.extend(x.items.iter().filter_map(svlog_unit_info)),
+        }
+    }
+    units
+}
+
+/// Determine which units are needed by the unit `target`.
+///
+/// Returns whether each unit is selected, or `None` if no unit named `target`
+/// exists.
+fn select(units: &[UnitInfo], target: Name) -> Option<Vec<bool>> {
+    if !units.iter().any(|u| u.owner.is_none() && u.name == target) {
+        return None;
+    }
+
+    // Mark units as selected until no new names are discovered.
+    let mut names = HashSet::new();
+    names.insert(target);
+    let mut selected = vec![false; units.len()];
+    let mut changed = true;
+    while changed {
+        changed = false;
+        for (unit, selected) in units.iter().zip(selected.iter_mut()) {
+            if *selected || !names.contains(&unit.owner.unwrap_or(unit.name)) {
+                continue;
+            }
+            *selected = true;
+            changed = true;
+            names.extend(unit.mentions.iter().cloned());
+        }
+    }
+    Some(selected)
+}
+
+/// Analyze a VHDL design unit.
+fn vhdl_unit_info(unit: &vhdl_ast::DesignUnit) -> UnitInfo {
+    let owner = match unit.data {
+        vhdl_ast::DesignUnitData::ArchBody(ref x) => last_name(&x.target),
+        vhdl_ast::DesignUnitData::PkgBody(ref x) => Some(x.name.value),
+        _ => None,
+    };
+    let mut collector = VhdlMentions::default();
+    unit.walk(&mut collector);
+    let name = unit_name(unit);
+    UnitInfo {
+        name: name.value,
+        owner,
+        mentions: collector.0,
+        source: name.span.source,
+    }
+}
+
+/// Get the last simple name in a VHDL compound name, e.g. `foo` in `work.foo`.
+fn last_name(name: &vhdl_ast::CompoundName) -> Option<Name> {
+    let mut last = match name.primary.kind {
+        vhdl_ast::PrimaryNameKind::Ident(n) => Some(n),
+        _ => None,
+    };
+    for part in &name.parts {
+        if let vhdl_ast::NamePart::Select(vhdl_ast::PrimaryName {
+            kind: vhdl_ast::PrimaryNameKind::Ident(n),
+            ..
+        }) = *part
+        {
+            last = Some(n);
+        }
+    }
+    last
+}
+
+#[derive(Default)]
+struct VhdlMentions(HashSet<Name>);
+
+impl<'a> vhdl_visit::Visitor<'a> for VhdlMentions {
+    fn pre_visit_primary_name(&mut self, node: &'a vhdl_ast::PrimaryName) -> bool {
+        if let vhdl_ast::PrimaryNameKind::Ident(n) = node.kind {
+            self.0.insert(n);
+        }
+        true
+    }
+}
+
+/// Get the name of a SystemVerilog item that forms a unit of its own.
+fn svlog_unit_name(item: &svlog_ast::Item) -> Option<Name> {
+    match item.data {
+        svlog_ast::ItemData::ModuleDecl(ref x) => Some(x.name.value),
+        svlog_ast::ItemData::InterfaceDecl(ref x) => Some(x.name.value),
+        svlog_ast::ItemData::PackageDecl(ref x) => Some(x.name.value),
+        _ => None,
+    }
+}
+
+/// Analyze a SystemVerilog item, if it forms a unit of its own.
+fn svlog_unit_info<'a>(item: &'a svlog_ast::Item<'a>) -> Option<UnitInfo> {
+    let name = svlog_unit_name(item)?;
+    let mut collector = SvlogMentions::default();
+    item.accept(&mut collector);
+    Some(UnitInfo {
+        name,
+        owner: None,
+        mentions: collector.0,
+        source: item.span.source,
+    })
+}
+
+#[derive(Default)]
+struct SvlogMentions(HashSet<Name>);
+
+impl<'a> svlog_ast::Visitor<'a> for SvlogMentions {
+    fn pre_visit_inst(&mut self, node: &'a svlog_ast::Inst<'a>) -> bool {
+        self.0.insert(node.target.value);
+        true
+    }
+
+    fn pre_visit_import_item(&mut self, node: &'a svlog_ast::ImportItem<'a>) -> bool {
+        self.0.insert(node.pkg.value);
+        true
+    }
+
+    fn pre_visit_expr(&mut self, node: &'a svlog_ast::Expr<'a>) -> bool {
+        if let svlog_ast::IdentExpr(name) = node.data {
+            self.0.insert(name.value);
+        }
+        true
+    }
+
+    fn pre_visit_type(&mut self, node: &'a svlog_ast::Type<'a>) -> bool {
+        match node.kind.data {
+            svlog_ast::NamedType(name) => {
+                self.0.insert(name.value);
+            }
+            svlog_ast::VirtIntfType(name) => {
+                self.0.insert(name);
+            }
+            _ => (),
+        }
+        true
+    }
+}
@@ -0,0 +1,692 @@
+// Copyright (c) 2016-2020 Fabian Schuiki
+
+//! A compile server that keeps the design in memory across requests.
+//!
+//! Build scripts that invoke the compiler repeatedly pay for parsing and
+//! analyzing the same sources on every run. `moore serve` instead listens on a
+//! TCP socket and answers requests against a design it keeps in memory. Each
+//! request and response is a JSON-RPC 2.0 object on a line of its own:
+//!
+//! ```text
+//! --> {"jsonrpc": "2.0", "id": 1, "method": "elaborate", "params": {"name": "top"}}
+//! <-- {"jsonrpc": "2.0", "id": 1, "result": {"errors": 0, "warnings": 0, "diagnostics": [], "output": "entity @top () -> () {\n}\n"}}
+//! ```
+//!
+//! The following methods are supported:
+//!
+//! - `compile` analyzes the design and reports its diagnostics. The optional
+//!   `files` and `lib` parameters replace the input files and the library they
+//!   are compiled into.
+//! - `elaborate` elaborates the entity or module given as `name`, and reports
+//!   its diagnostics. The generated code is returned as `output`, in the
+//!   format given by `--emit`.
+//! - `query` runs a where-used query of the given `kind` for `name`, as
+//!   `moore query` does, and returns the matches.
+//! - `uses` finds the uses of the declaration whose name is at the given
+//!   `line` and `column` of `file`, and returns their locations and lengths,
+//!   starting with the declaration itself. Renaming all of them renames the
+//!   declaration. Uses are found through name resolution, so declarations of
+//!   the same name in other scopes are left alone.
+//! - `shutdown` stops the server.
+//!
+//! The diagnostics are reported with the number of errors and warnings among
+//! them, rendered as the compiler would print them but without colors. A
+//! `compile` or `elaborate` request reports all diagnostics emitted since the
+//! previous one, including those of parsing the files that changed. The
+//! server is compiled with the include paths, macro definitions, and manifest
+//! given on its command line. Clients are served one at a time, each for as
+//! long as it keeps its connection open.
+
+use crate::errors::{DiagBuilder2, DiagEmitter};
+use crate::source::{get_source_manager, Source, Span};
+use crate::vhdl::syntax::{self as vhdl_syntax, ast::DesignUnit};
+use std::collections::HashMap;
+use std::fmt;
+use std::io::{self, BufRead, BufReader, Write};
+use std::net::{SocketAddr, TcpListener, TcpStream};
+use std::time::SystemTime;
+
+/// The request is not valid JSON.
+pub const PARSE_ERROR: i64 = -32700;
+/// The request is not a valid request object.
+pub const INVALID_REQUEST: i64 = -32600;
+/// The requested method does not exist.
+pub const METHOD_NOT_FOUND: i64 = -32601;
+/// The parameters of the request are invalid.
+pub const INVALID_PARAMS: i64 = -32602;
+
+/// The maximum depth of nested arrays and objects in a request, which keeps a
+/// malformed request from overflowing the stack of the parser.
+const MAX_NESTING: usize = 128;
+
+/// A JSON value.
+#[derive(Debug, Clone, PartialEq)]
+pub enum Json {
+    Null,
+    Bool(bool),
+    Number(f64),
+    String(String),
+    Array(Vec<Json>),
+    /// An object, with its members in the order they were given.
+    Object(Vec<(String, Json)>),
+}
+
+impl Json {
+    /// Parse a JSON value.
+    ///
+    /// Returns an error message mentioning the offset of the problem if the
+    /// text is malformed.
+    pub fn parse(text: &str) -> Result<Json, String> {
+        let mut parser = JsonParser {
+            text,
+            pos: 0,
+            depth: 0,
+        };
+        let value = parser.value()?;
+        parser.skip_whitespace();
+        if parser.pos < text.len() {
+            return Err(parser.error("expected end of input"));
+        }
+        Ok(value)
+    }
+
+    /// Look up a member of an object.
+    pub fn get(&self, key: &str) -> Option<&Json> {
+        match *self {
+            Json::Object(ref members) => members.iter().find(|(k, _)| k == key).map(|(_, v)| v),
+            _ => None,
+        }
+    }
+
+    /// Get the contents of a string.
+    pub fn as_str(&self) -> Option<&str> {
+        match *self {
+            Json::String(ref s) => Some(s),
+            _ => None,
+        }
+    }
+
+    /// Get the elements of an array.
+    pub fn as_array(&self) -> Option<&[Json]> {
+        match *self {
+            Json::Array(ref elems) => Some(elems),
+            _ => None,
+        }
+    }
+}
+
+impl fmt::Display for Json {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match *self {
+            Json::Null => write!(f, "null"),
+            Json::Bool(b) => write!(f, "{}", b),
+            Json::Number(n) if n.is_finite() => write!(f, "{}", n),
+            Json::Number(_) => write!(f, "null"),
+            Json::String(ref s) => {
+                write!(f, "\"")?;
+                for c in s.chars() {
+                    match c {
+                        '"' => write!(f, "\\\"")?,
+                        '\\' => write!(f, "\\\\")?,
+                        '\n' => write!(f, "\\n")?,
+                        c if (c as u32) < 0x20 => write!(f, "\\u{:04x}", c as u32)?,
+                        c => write!(f, "{}", c)?,
+                    }
+                }
+                write!(f, "\"")
+            }
+            Json::Array(ref elems) => {
+                write!(f, "[")?;
+                for (i, elem) in elems.iter().enumerate() {
+                    if i > 0 {
+                        write!(f, ", ")?;
+                    }
+                    write!(f, "{}", elem)?;
+                }
+                write!(f, "]")
+            }
+            Json::Object(ref members) => {
+                write!(f, "{{")?;
+                for (i, (key, value)) in members.iter().enumerate() {
+                    if i > 0 {
+                        write!(f, ", ")?;
+                    }
+                    write!(f, "{}: {}", Json::String(key.clone()), value)?;
+                }
+                write!(f, "}}")
+            }
+        }
+    }
+}
+
+struct JsonParser<'a> {
+    text: &'a str,
+    pos: usize,
+    depth: usize,
+}
+
+impl<'a> JsonParser<'a> {
+    fn error(&self, msg: &str) -> String {
+        format!("{} at offset {}", msg, self.pos)
+    }
+
+    fn peek(&self) -> Option<char> {
+        self.text[self.pos..].chars().next()
+    }
+
+    fn bump(&mut self) -> Option<char> {
+        let c = self.peek()?;
+        self.pos += c.len_utf8();
+        Some(c)
+    }
+
+    fn skip_whitespace(&mut self) {
+        while self.peek().map_or(false, |c| c.is_ascii_whitespace()) {
+            self.pos += 1;
+        }
+    }
+
+    fn value(&mut self) -> Result<Json, String> {
+        self.skip_whitespace();
+        match self.peek() {
+            Some('{') | Some('[') => {
+                if self.depth == MAX_NESTING {
+                    return Err(self.error("arrays and objects nested too deeply"));
+                }
+                self.depth += 1;
+                let value = if self.peek() == Some('{') {
+                    self.object()
+                } else {
+                    self.array()
+                };
+                self.depth -= 1;
+                value
+            }
+            Some('"') => Ok(Json::String(self.string()?)),
+            Some(c) if c == '-' || c.is_ascii_digit() => {
+                let start = self.pos;
+                while self
+                    .peek()
+                    .map_or(false, |c| c.is_ascii_digit() || "+-.eE".contains(c))
+                {
+                    self.pos += 1;
+                }
+                match self.text[start..self.pos].parse() {
+                    Ok(n) => Ok(Json::Number(n)),
+                    Err(_) => Err(self.error("invalid number")),
+                }
+            }
+            _ => {
+                for &(word, ref value) in &[
+                    ("null", Json::Null),
+                    ("true", Json::Bool(true)),
+                    ("false", Json::Bool(false)),
+                ] {
+                    if self.text[self.pos..].starts_with(word) {
+                        self.pos += word.len();
+                        return Ok(value.clone());
+                    }
+                }
+                Err(self.error("expected a value"))
+            }
+        }
+    }
+
+    /// Parse an object, starting at its opening brace.
+    fn object(&mut self) -> Result<Json, String> {
+        self.bump();
+        let mut members = vec![];
+        self.skip_whitespace();
+        if self.peek() == Some('}') {
+            self.bump();
+            return Ok(Json::Object(members));
+        }
+        loop {
+            self.skip_whitespace();
+            if self.peek() != Some('"') {
+                return Err(self.error("expected a member name"));
+            }
+            let key = self.string()?;
+            self.skip_whitespace();
+            if self.bump() != Some(':') {
+                return Err(self.error("expected `:`"));
+            }
+            members.push((key, self.value()?));
+            self.skip_whitespace();
+            match self.bump() {
+                Some(',') => continue,
+                Some('}') => return Ok(Json::Object(members)),
+                _ => return Err(self.error("expected `,` or `}`")),
+            }
+        }
+    }
+
+    /// Parse an array, starting at its opening bracket.
+    fn array(&mut self) -> Result<Json, String> {
+        self.bump();
+        let mut elems = vec![];
+        self.skip_whitespace();
+        if self.peek() == Some(']') {
+            self.bump();
+            return Ok(Json::Array(elems));
+        }
+        loop {
+            elems.push(self.value()?);
+            self.skip_whitespace();
+            match self.bump() {
+                Some(',') => continue,
+                Some(']') => return Ok(Json::Array(elems)),
+                _ => return Err(self.error("expected `,` or `]`")),
+            }
+        }
+    }
+
+    /// Parse the four hexadecimal digits of a `\u` escape.
+    fn hex_escape(&mut self) -> Result<u32, String> {
+        let code = self
+            .text
+            .get(self.pos..self.pos + 4)
+            .filter(|hex| hex.chars().all(|c| c.is_ascii_hexdigit()))
+            .and_then(|hex| u32::from_str_radix(hex, 16).ok());
+        match code {
+            Some(code) => {
+                self.pos += 4;
+                Ok(code)
+            }
+            None => Err(self.error("invalid unicode escape")),
+        }
+    }
+
+    /// Parse a string, starting at its opening quote.
+    fn string(&mut self) -> Result<String, String> {
+        self.bump();
+        let mut s = String::new();
+        loop {
+            match self.bump() {
+                Some('"') => return Ok(s),
+                Some('\\') => match self.bump() {
+                    Some('"') => s.push('"'),
+                    Some('\\') => s.push('\\'),
+                    Some('/') => s.push('/'),
+                    Some('b') => s.push('\u{8}'),
+                    Some('f') => s.push('\u{c}'),
+                    Some('n') => s.push('\n'),
+                    Some('r') => s.push('\r'),
+                    Some('t') => s.push('\t'),
+                    Some('u') => {
+                        // Characters outside the basic multilingual plane are
+                        // escaped as a pair of UTF-16 surrogates.
+                        let code = match self.hex_escape()? {
+                            high @ 0xd800..=0xdbff if self.text[self.pos..].starts_with("\\u") => {
+                                self.pos += 2;
+                                match self.hex_escape()? {
+                                    low @ 0xdc00..=0xdfff => {
+                                        0x10000 + ((high - 0xd800) << 10) + (low - 0xdc00)
+                                    }
+                                    _ => return Err(self.error("unpaired surrogate")),
+                                }
+                            }
+                            code => code,
+                        };
+                        match std::char::from_u32(code) {
+                            Some(c) => s.push(c),
+                            None => return Err(self.error("unpaired surrogate")),
+                        }
+                    }
+                    _ => return Err(self.error("invalid escape sequence")),
+                },
+                Some(c) => s.push(c),
+                None => return Err(self.error("unterminated string")),
+            }
+        }
+    }
+}
+
+/// A request received from a client.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Request {
+    /// The identifier to answer with.
+    pub id: Json,
+    /// The name of the method to call.
+    pub method: String,
+    /// The parameters of the call, usually an object.
+    pub params: Json,
+}
+
+impl Request {
+    /// Parse a request from a line of input.
+    ///
+    /// On failure, returns the error response to send back.
+    pub fn parse(line: &str) -> Result<Request, String> {
+        let value =
+            Json::parse(line).map_err(|msg| error_response(&Json::Null, PARSE_ERROR, &msg))?;
+        let id = value.get("id").cloned().unwrap_or(Json::Null);
+        let method = match value.get("method").and_then(Json::as_str) {
+            Some(method) => method.to_string(),
+            None => return Err(error_response(&id, INVALID_REQUEST, "expected a `method`")),
+        };
+        let params = value.get("params").cloned().unwrap_or(Json::Null);
+        Ok(Request { id, method, params })
+    }
+
+    /// Get a string parameter.
+    pub fn param_str(&self, name: &str) -> Option<&str> {
+        self.params.get(name).and_then(Json::as_str)
+    }
+
+    /// Get a parameter that is a non-negative integer.
+    pub fn param_usize(&self, name: &str) -> Option<usize> {
+        match self.params.get(name) {
+            Some(&Json::Number(x)) if x >= 0.0 && x.fract() == 0.0 => Some(x as usize),
+            _ => None,
+        }
+    }
+}
+
+/// Format the response to a successful request.
+pub fn response(id: &Json, result: Json) -> String {
+    Json::Object(vec![
+        ("jsonrpc".to_string(), Json::String("2.0".to_string())),
+        ("id".to_string(), id.clone()),
+        ("result".to_string(), result),
+    ])
+    .to_string()
+}
+
+/// Format the response to a failed request.
+pub fn error_response(id: &Json, code: i64, message: &str) -> String {
+    let error = Json::Object(vec![
+        ("code".to_string(), Json::Number(code as f64)),
+        ("message".to_string(), Json::String(message.to_string())),
+    ]);
+    Json::Object(vec![
+        ("jsonrpc".to_string(), Json::String("2.0".to_string())),
+        ("id".to_string(), id.clone()),
+        ("error".to_string(), error),
+    ])
+    .to_string()
+}
+
+/// Format the result of a `compile` or `elaborate` request, from the number
+/// of errors and warnings and the rendered diagnostics.
+pub fn diagnostics_result(errors: usize, warnings: usize, diags: Vec<String>) -> Json {
+    let diags = diags
+        .iter()
+        .map(|diag| Json::String(strip_colors(diag).trim_end().to_string()))
+        .collect();
+    Json::Object(vec![
+        ("errors".to_string(), Json::Number(errors as f64)),
+        ("warnings".to_string(), Json::Number(warnings as f64)),
+        ("diagnostics".to_string(), Json::Array(diags)),
+    ])
+}
+
+/// Format the result of an `elaborate` request, from the result formatted by
+/// `diagnostics_result` and the generated code.
+pub fn elaboration_result(mut result: Json, output: String) -> Json {
+    if let Json::Object(ref mut fields) = result {
+        fields.push(("output".to_string(), Json::String(output)));
+    }
+    result
+}
+
+/// Format the result of a `uses` request, from the spans of the uses.
+pub fn uses_result(uses: &[Span]) -> Json {
+    let uses = uses
+        .iter()
+        .map(|span| {
+            let begin = span.begin();
+            Json::Object(vec![
+                (
+                    "file".to_string(),
+                    Json::String(span.source.get_path().to_string()),
+                ),
+                ("line".to_string(), Json::Number(begin.human_line() as f64)),
+                (
+                    "column".to_string(),
+                    Json::Number(begin.human_column() as f64),
+                ),
+                (
+                    "length".to_string(),
+                    Json::Number(span.extract().chars().count() as f64),
+                ),
+            ])
+        })
+        .collect();
+    Json::Array(uses)
+}
+
+/// Find the name at a line and column of a source file.
+///
+/// Lines and columns count from 1, like in diagnostics. Returns the span of
+/// the identifier that covers the position, if any.
+pub fn name_at(source: Source, line: usize, column: usize) -> Option<Span> {
+    let content = source.get_content();
+    let chars: Vec<(usize, char)> = content.iter().collect();
+    let is_ident = |c: char| c.is_alphanumeric() || c == '_' || c == '$';
+
+    // Locate the character at the position. Carriage returns do not count as
+    // a column, as in `Location::human`.
+    let (mut at_line, mut at_column) = (1, 1);
+    let mut at = None;
+    for (i, &(_, c)) in chars.iter().enumerate() {
+        if at_line == line && at_column == column && c != '\r' {
+            at = Some(i);
+            break;
+        }
+        match c {
+            '\n' => {
+                at_line += 1;
+                at_column = 1;
+            }
+            '\r' => (),
+            _ => at_column += 1,
+        }
+    }
+    let at = at.filter(|&i| is_ident(chars[i].1))?;
+
+    // Extend the position to the entire identifier.
+    let first = (0..at)
+        .rev()
+        .take_while(|&i| is_ident(chars[i].1))
+        .last()
+        .unwrap_or(at);
+    let end = (at..chars.len())
+        .find(|&i| !is_ident(chars[i].1))
+        .map(|i| chars[i].0)
+        .unwrap_or_else(|| content.bytes().len());
+    Some(Span::new(source, chars[first].0, end))
+}
+
+/// Remove the terminal escape sequences which color a rendered diagnostic.
+fn strip_colors(text: &str) -> String {
+    let mut stripped = String::with_capacity(text.len());
+    let mut chars = text.chars();
+    while let Some(c) = chars.next() {
+        if c == '\x1B' {
+            // Skip up to the final letter of the sequence, e.g. `[31;1m`.
+            chars.find(char::is_ascii_alphabetic);
+        } else {
+            stripped.push(c);
+        }
+    }
+    stripped
+}
+
+/// The socket of a server and the client currently being served.
+pub struct Connection {
+    listener: TcpListener,
+    client: Option<(BufReader<TcpStream>, TcpStream)>,
+}
+
+impl Connection {
+    /// Listen for clients on an address such as `127.0.0.1:7878`.
+    pub fn bind(addr: &str) -> io::Result<Connection> {
+        Ok(Connection {
+            listener: TcpListener::bind(addr)?,
+            client: None,
+        })
+    }
+
+    /// The address the server listens on.
+    pub fn local_addr(&self) -> io::Result<SocketAddr> {
+        self.listener.local_addr()
+    }
+
+    /// Wait for the next request, from the current client or a new one.
+    ///
+    /// Malformed requests are answered right away and skipped.
+    pub fn next_request(&mut self) -> io::Result<Request> {
+        loop {
+            if self.client.is_none() {
+                let (stream, _) = self.listener.accept()?;
+                self.client = Some((BufReader::new(stream.try_clone()?), stream));
+            }
+            let mut line = String::new();
+            let (ref mut reader, _) = *self.client.as_mut().unwrap();
+            match reader.read_line(&mut line) {
+                Ok(0) | Err(_) => {
+                    self.client = None;
+                    continue;
+                }
+                Ok(_) => (),
+            }
+            if line.trim().is_empty() {
+                continue;
+            }
+            match Request::parse(&line) {
+                Ok(request) => return Ok(request),
+                Err(response) => self.reply(&response),
+            }
+        }
+    }
+
+    /// Send a response to the current client.
+    ///
+    /// A client that cannot be written to is dropped.
+    pub fn reply(&mut self, response: &str) {
+        let failed = match self.client {
+            Some((_, ref mut stream)) => writeln!(stream, "{}", response)
+                .and_then(|_| stream.flush())
+                .is_err(),
+            None => false,
+        };
+        if failed {
+            self.client = None;
+        }
+    }
+}
+
+/// The parsed VHDL files of a design, kept across requests.
+#[derive(Default)]
+pub struct VhdlCache {
+    files: HashMap<String, (Option<SystemTime>, Vec<DesignUnit>)>,
+}
+
+impl VhdlCache {
+    /// Get the design units of a file.
+    ///
+    /// The file is only parsed if it is new or has been modified since it was
+    /// last parsed.
+    pub fn parse(
+        &mut self,
+        filename: &str,
+        emitter: &dyn DiagEmitter,
+    ) -> Result<&[DesignUnit], ()> {
+        let mtime = std::fs::metadata(filename).and_then(|m| m.modified()).ok();
+        let cached = self.files.get(filename).map(|&(cached, _)| cached);
+        if mtime.is_none() || cached != Some(mtime) {
+            self.files.remove(filename);
+            let source = match get_source_manager().reopen(filename) {
+                Some(source) => source,
+                None => {
+                    emitter.emit(DiagBuilder2::error(format!("cannot open `{}`", filename)));
+                    return Err(());
+                }
+            };
+            let units = vhdl_syntax::parse(source)?;
+            self.files.insert(filename.to_string(), (mtime, units));
+        }
+        Ok(&self.files[filename].1)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn string(s: &str) -> Json {
+        Json::String(s.to_string())
+    }
+
+    #[test]
+    fn escapes() {
+        let text = "quote \" backslash \\ newline \n tab \t bell \u{7} umlaut \u{e4}";
+        assert_eq!(
+            string(text).to_string(),
+            "\"quote \\\" backslash \\\\ newline \\n tab \\u0009 bell \\u0007 umlaut \u{e4}\""
+        );
+        assert_eq!(Json::parse(&string(text).to_string()), Ok(string(text)));
+        assert_eq!(
+            Json::parse(r#""\"\\\/\b\f\n\r\t\u00e4\u00E4""#),
+            Ok(string("\"\\/\u{8}\u{c}\n\r\t\u{e4}\u{e4}"))
+        );
+        for text in &[
+            r#""\x""#,
+            r#""\u00g0""#,
+            r#""\u+0e4""#,
+            r#""\u00e""#,
+            "\"abc",
+        ] {
+            assert!(Json::parse(text).is_err(), "{} should not parse", text);
+        }
+    }
+
+    #[test]
+    fn surrogate_pairs() {
+        assert_eq!(
+            Json::parse(r#""\ud83d\ude00 \uD83D\uDE00""#),
+            Ok(string("\u{1f600} \u{1f600}"))
+        );
+        assert_eq!(
+            Json::parse(&string("\u{1f600}").to_string()),
+            Ok(string("\u{1f600}"))
+        );
+        for text in &[
+            r#""\ud83d""#,
+            r#""\ude00""#,
+            r#""\ud83d x""#,
+            r#""\ud83dA""#,
+            r#""\ude00\ud83d""#,
+        ] {
+            assert!(Json::parse(text).is_err(), "{} should not parse", text);
+        }
+    }
+
+    #[test]
+    fn nesting() {
+        let text = r#" { "a" : [1, {"b": [true, null, -2.5e1]}, []], "c": {}, "d": "x" } "#;
+        let value = Json::parse(text).unwrap();
+        assert_eq!(
+            value.to_string(),
+            r#"{"a": [1, {"b": [true, null, -25]}, []], "c": {}, "d": "x"}"#
+        );
+        assert_eq!(
+            value.get("a").and_then(Json::as_array).map(|a| a.len()),
+            Some(3)
+        );
+        assert_eq!(value.get("c"), Some(&Json::Object(vec![])));
+        assert_eq!(value.get("d").and_then(Json::as_str), Some("x"));
+        assert_eq!(Json::parse(&value.to_string()), Ok(value));
+
+        // Nesting is limited, rather than overflowing the stack.
+        let nested = |depth| "[".repeat(depth) + &"]".repeat(depth);
+        assert!(Json::parse(&nested(MAX_NESTING)).is_ok());
+        assert!(Json::parse(&nested(MAX_NESTING + 1)).is_err());
+        assert!(Json::parse(&"[{\"a\": ".repeat(100_000)).is_err());
+
+        for text in &["[1, 2", "[1 2]", "{\"a\" 1}", "{\"a\": 1,}", "{} x"] {
+            assert!(Json::parse(text).is_err(), "{} should not parse", text);
+        }
+    }
+}
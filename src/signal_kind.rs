@@ -0,0 +1,565 @@
+// Copyright (c) 2016-2020 Fabian Schuiki
+
+//! Inference of the role of the ports of an entity or module.
+//!
+//! Wrapper and constraint generators need to know which inputs are clocks and
+//! resets. This module guesses that from the way the ports are used in the
+//! architectures of an entity or the procedures of a module, without
+//! elaborating the design:
+//!
+//! - A port is a clock if it appears in `rising_edge`, `falling_edge`, or a
+//!   `'event` attribute, or in a `posedge` or `negedge` event.
+//! - A port is an asynchronous reset if it is tested in an `if` branch that
+//!   precedes the clock edge, or if it is an edge in the event control of a
+//!   procedure and tested by its first `if` statement.
+//! - A port is a synchronous reset if it is tested by the first `if`
+//!   statement inside a clock edge and the branch only assigns constants,
+//!   literals, and signals other than ports.
+//!
+//! All other ports, including all outputs, are data.
+
+use crate::common::name::Name;
+use crate::common::source::Spanned;
+use crate::inputs::Language;
+use crate::svlog::ast as svlog_ast;
+use crate::svlog::token::Op;
+use crate::vhdl::syntax::ast as vhdl_ast;
+use crate::vhdl::syntax::visit::{Visitor, WalkVisitor};
+use crate::wrapper::{Interface, PortDir};
+use std::collections::HashSet;
+use std::fmt;
+
+/// The role of a port.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SignalKind {
+    /// A clock, used for its edges.
+    Clock,
+    /// A synchronous or asynchronous reset.
+    Reset,
+    /// Any other port.
+    Data,
+}
+
+impl fmt::Display for SignalKind {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match *self {
+            SignalKind::Clock => write!(f, "clock"),
+            SignalKind::Reset => write!(f, "reset"),
+            SignalKind::Data => write!(f, "data"),
+        }
+    }
+}
+
+/// Infer the role of each port of `intf`, in the order of its ports.
+pub fn infer(
+    vhdl_units: &[vhdl_ast::DesignUnit],
+    svlog_files: &[svlog_ast::SourceFile],
+    intf: &Interface,
+) -> Vec<SignalKind> {
+    let mut usage = Usage {
+        inputs: intf
+            .ports
+            .iter()
+            .filter(|p| p.dir == PortDir::In)
+            .map(|p| p.name.clone())
+            .collect(),
+        clocks: HashSet::new(),
+        resets: HashSet::new(),
+    };
+    match intf.lang {
+        Language::Vhdl => {
+            for unit in vhdl_units {
+                if let vhdl_ast::DesignUnitData::ArchBody(ref arch) = unit.data {
+                    if primary_name(&arch.target).map(|n| n.to_string()) == Some(intf.name.clone())
+                    {
+                        usage.vhdl_arch(arch);
+                    }
+                }
+            }
+        }
+        Language::SystemVerilog | Language::Verilog => {
+            for file in svlog_files {
+                for item in &file.items {
+                    if let svlog_ast::ItemData::ModuleDecl(ref module) = item.data {
+                        if module.name.value.to_string() == intf.name {
+                            usage.svlog_items(&module.items);
+                        }
+                    }
+                }
+            }
+        }
+    }
+    intf.ports
+        .iter()
+        .map(|p| {
+            if !usage.inputs.contains(&p.name) {
+                SignalKind::Data
+            } else if usage.clocks.contains(&p.name) {
+                SignalKind::Clock
+            } else if usage.resets.contains(&p.name) {
+                SignalKind::Reset
+            } else {
+                SignalKind::Data
+            }
+        })
+        .collect()
+}
+
+/// The clocks and resets found so far, by name.
+struct Usage {
+    inputs: HashSet<String>,
+    clocks: HashSet<String>,
+    resets: HashSet<String>,
+}
+
+impl Usage {
+    fn vhdl_arch(&mut self, arch: &vhdl_ast::ArchBody) {
+        let mut finder = EdgeFinder(vec![]);
+        arch.walk(&mut finder);
+        self.clocks
+            .extend(finder.0.into_iter().map(|n| n.to_string()));
+        self.vhdl_stmts(&arch.stmts);
+    }
+
+    fn vhdl_stmts(&mut self, stmts: &[vhdl_ast::Stmt]) {
+        // The first `if` after a `wait until rising_edge(clk)` behaves like
+        // the first `if` inside a clock edge.
+        let mut after_edge = false;
+        for stmt in stmts {
+            match stmt.data {
+                vhdl_ast::StmtData::WaitStmt {
+                    until: Some(ref cond),
+                    ..
+                } => {
+                    after_edge = vhdl_has_edge(cond);
+                    continue;
+                }
+                vhdl_ast::StmtData::IfStmt { ref conds, ref alt } => {
+                    if after_edge {
+                        self.vhdl_sync_reset(stmt);
+                    }
+                    if let Some(edge) = conds.iter().position(|(c, _)| vhdl_has_edge(c)) {
+                        for (cond, _) in &conds[..edge] {
+                            if let Some(name) = self.vhdl_tested_port(cond) {
+                                self.resets.insert(name);
+                            }
+                        }
+                        if let Some(first) = conds[edge].1.stmts.first() {
+                            self.vhdl_sync_reset(first);
+                        }
+                    }
+                    for (_, body) in conds {
+                        self.vhdl_stmts(&body.stmts);
+                    }
+                    if let Some(ref alt) = *alt {
+                        self.vhdl_stmts(&alt.stmts);
+                    }
+                }
+                vhdl_ast::StmtData::CaseStmt { ref cases, .. } => {
+                    for (_, body) in cases {
+                        self.vhdl_stmts(&body.stmts);
+                    }
+                }
+                vhdl_ast::StmtData::LoopStmt { ref body, .. } => self.vhdl_stmts(&body.stmts),
+                vhdl_ast::StmtData::IfGenStmt { ref conds, ref alt } => {
+                    for (_, body) in conds {
+                        self.vhdl_stmts(&body.stmts);
+                    }
+                    if let Some(ref alt) = *alt {
+                        self.vhdl_stmts(&alt.stmts);
+                    }
+                }
+                vhdl_ast::StmtData::CaseGenStmt { ref cases, .. } => {
+                    for (_, body) in cases {
+                        self.vhdl_stmts(&body.stmts);
+                    }
+                }
+                vhdl_ast::StmtData::ForGenStmt { ref body, .. } => self.vhdl_stmts(&body.stmts),
+                vhdl_ast::StmtData::BlockStmt { ref stmts, .. }
+                | vhdl_ast::StmtData::ProcStmt { ref stmts, .. } => self.vhdl_stmts(stmts),
+                _ => (),
+            }
+            after_edge = false;
+        }
+    }
+
+    /// Record the port tested by `stmt` as a reset, if it is an `if`
+    /// statement whose first branch only assigns constants.
+    fn vhdl_sync_reset(&mut self, stmt: &vhdl_ast::Stmt) {
+        if let vhdl_ast::StmtData::IfStmt { ref conds, .. } = stmt.data {
+            let (ref cond, ref body) = conds[0];
+            if let Some(name) = self.vhdl_tested_port(cond) {
+                if body.stmts.iter().all(|s| self.vhdl_assigns_constant(s)) {
+                    self.resets.insert(name);
+                }
+            }
+        }
+    }
+
+    /// Find the input port tested by a condition such as `rst = '1'`.
+    fn vhdl_tested_port(&self, cond: &vhdl_ast::Expr) -> Option<String> {
+        match cond.data {
+            vhdl_ast::NameExpr(ref name) if name.parts.is_empty() => primary_name(name)
+                .map(|n| n.to_string())
+                .filter(|n| self.inputs.contains(n)),
+            vhdl_ast::UnaryExpr(
+                Spanned {
+                    value: vhdl_ast::UnaryOp::Not,
+                    ..
+                },
+                ref arg,
+            ) => self.vhdl_tested_port(arg),
+            vhdl_ast::BinaryExpr(
+                Spanned {
+                    value: vhdl_ast::BinaryOp::Rel(op),
+                    ..
+                },
+                ref lhs,
+                ref rhs,
+            ) if op == vhdl_ast::RelationalOp::Eq || op == vhdl_ast::RelationalOp::Neq => {
+                if vhdl_is_literal(rhs) {
+                    self.vhdl_tested_port(lhs)
+                } else if vhdl_is_literal(lhs) {
+                    self.vhdl_tested_port(rhs)
+                } else {
+                    None
+                }
+            }
+            vhdl_ast::ParenExpr(ref elems) if elems.value.len() == 1 => {
+                self.vhdl_tested_port(&elems.value[0].expr)
+            }
+            _ => None,
+        }
+    }
+
+    /// Check whether `stmt` assigns only constants.
+    fn vhdl_assigns_constant(&self, stmt: &vhdl_ast::Stmt) -> bool {
+        let waves = match stmt.data {
+            vhdl_ast::StmtData::AssignStmt {
+                mode:
+                    Spanned {
+                        value: vhdl_ast::AssignMode::Normal(_, ref waves),
+                        ..
+                    },
+                ..
+            } => waves,
+            vhdl_ast::StmtData::NullStmt => return true,
+            _ => return false,
+        };
+        waves.iter().all(|vhdl_ast::CondWave(wave, cond)| {
+            cond.is_none()
+                && wave
+                    .elems
+                    .iter()
+                    .flatten()
+                    .all(|(value, after)| after.is_none() && self.vhdl_is_constant(value))
+        })
+    }
+
+    fn vhdl_is_constant(&self, expr: &vhdl_ast::Expr) -> bool {
+        match expr.data {
+            vhdl_ast::LitExpr(..) | vhdl_ast::OthersExpr => true,
+            vhdl_ast::NameExpr(ref name) => {
+                name.parts.is_empty()
+                    && primary_name(name).map_or(true, |n| !self.inputs.contains(&n.to_string()))
+            }
+            vhdl_ast::ParenExpr(ref elems) => {
+                elems.value.iter().all(|e| self.vhdl_is_constant(&e.expr))
+            }
+            _ => false,
+        }
+    }
+
+    fn svlog_items(&mut self, items: &[svlog_ast::Item]) {
+        for item in items {
+            if let svlog_ast::ItemData::Procedure(ref procedure) = item.data {
+                self.svlog_procedure(procedure);
+            }
+        }
+    }
+
+    fn svlog_procedure(&mut self, procedure: &svlog_ast::Procedure) {
+        let (event, body) = match procedure.stmt.kind {
+            svlog_ast::TimedStmt(svlog_ast::TimingControl::Event(ref ctrl), ref body) => {
+                match ctrl.data {
+                    svlog_ast::EventControlData::Expr(ref event) => (event, body),
+                    _ => return,
+                }
+            }
+            _ => return,
+        };
+        let mut edges = vec![];
+        svlog_edges(event, &mut edges);
+        if edges.is_empty() {
+            return;
+        }
+
+        // An edge tested by the first `if` is an asynchronous reset. With a
+        // single edge, a test that only assigns constants is a synchronous
+        // reset.
+        let mut reset = None;
+        if let svlog_ast::IfStmt {
+            ref cond,
+            ref main_stmt,
+            ..
+        } = svlog_first_stmt(body).kind
+        {
+            if let Some(name) = self.svlog_tested_port(cond) {
+                if edges.contains(&name)
+                    || (edges.len() == 1 && self.svlog_assigns_constant(main_stmt))
+                {
+                    reset = Some(name);
+                }
+            }
+        }
+        for edge in edges {
+            if Some(&edge) != reset.as_ref() {
+                self.clocks.insert(edge);
+            }
+        }
+        self.resets.extend(reset);
+    }
+
+    /// Find the input port tested by a condition such as `!rst_n`.
+    fn svlog_tested_port(&self, cond: &svlog_ast::Expr) -> Option<String> {
+        match cond.data {
+            svlog_ast::IdentExpr(name) => {
+                Some(name.value.to_string()).filter(|n| self.inputs.contains(n))
+            }
+            svlog_ast::UnaryExpr {
+                op: Op::LogicNot | Op::BitNot,
+                ref expr,
+                ..
+            } => self.svlog_tested_port(expr),
+            svlog_ast::BinaryExpr {
+                op: Op::LogicEq | Op::LogicNeq | Op::CaseEq | Op::CaseNeq,
+                ref lhs,
+                ref rhs,
+            } => match (&lhs.data, &rhs.data) {
+                (_, svlog_ast::LiteralExpr(..)) => self.svlog_tested_port(lhs),
+                (svlog_ast::LiteralExpr(..), _) => self.svlog_tested_port(rhs),
+                _ => None,
+            },
+            _ => None,
+        }
+    }
+
+    /// Check whether `stmt` assigns only constants.
+    fn svlog_assigns_constant(&self, stmt: &svlog_ast::Stmt) -> bool {
+        match stmt.kind {
+            svlog_ast::NullStmt => true,
+            svlog_ast::SequentialBlock(ref stmts) => {
+                stmts.iter().all(|s| self.svlog_assigns_constant(s))
+            }
+            svlog_ast::BlockingAssignStmt { ref rhs, .. }
+            | svlog_ast::NonblockingAssignStmt { ref rhs, .. } => self.svlog_is_constant(rhs),
+            _ => false,
+        }
+    }
+
+    fn svlog_is_constant(&self, expr: &svlog_ast::Expr) -> bool {
+        match expr.data {
+            svlog_ast::LiteralExpr(..) | svlog_ast::PatternExpr(..) => true,
+            svlog_ast::IdentExpr(name) => !self.inputs.contains(&name.value.to_string()),
+            svlog_ast::ConcatExpr { ref exprs, .. } => {
+                exprs.iter().all(|e| self.svlog_is_constant(e))
+            }
+            _ => false,
+        }
+    }
+}
+
+/// The name of a simple name such as `clk`.
+fn primary_name(name: &vhdl_ast::CompoundName) -> Option<Name> {
+    match name.primary.kind {
+        vhdl_ast::PrimaryNameKind::Ident(n) => Some(n),
+        _ => None,
+    }
+}
+
+/// Check whether `expr` is a literal such as `'1'` or `"0000"`.
+fn vhdl_is_literal(expr: &vhdl_ast::Expr) -> bool {
+    match expr.data {
+        vhdl_ast::LitExpr(..) => true,
+        vhdl_ast::NameExpr(ref name) => name.parts.is_empty() && primary_name(name).is_none(),
+        _ => false,
+    }
+}
+
+/// Find the signal whose edge `name` detects, as in `rising_edge(clk)`,
+/// `falling_edge(clk)`, or `clk'event`.
+fn vhdl_edge(name: &vhdl_ast::CompoundName) -> Option<Name> {
+    let func = primary_name(name)?;
+    match name.parts.as_slice() {
+        [vhdl_ast::NamePart::Attribute(attr)] => {
+            if attr.name.as_str().eq_ignore_ascii_case("event") {
+                Some(func)
+            } else {
+                None
+            }
+        }
+        [vhdl_ast::NamePart::Call(args)] if args.value.len() == 1 => {
+            let func = func.as_str();
+            if !func.eq_ignore_ascii_case("rising_edge")
+                && !func.eq_ignore_ascii_case("falling_edge")
+            {
+                return None;
+            }
+            match args.value[0].expr.data {
+                vhdl_ast::NameExpr(ref arg) if arg.parts.is_empty() => primary_name(arg),
+                _ => None,
+            }
+        }
+        _ => None,
+    }
+}
+
+fn vhdl_has_edge(expr: &vhdl_ast::Expr) -> bool {
+    let mut finder = EdgeFinder(vec![]);
+    expr.walk(&mut finder);
+    !finder.0.is_empty()
+}
+
+/// Collects the signals whose edges are detected.
+struct EdgeFinder(Vec<Name>);
+
+impl<'a> Visitor<'a> for EdgeFinder {
+    fn pre_visit_compound_name(&mut self, node: &'a vhdl_ast::CompoundName) -> bool {
+        self.0.extend(vhdl_edge(node));
+        true
+    }
+}
+
+/// Collect the signals in the `posedge` and `negedge` events of `event`.
+fn svlog_edges(event: &svlog_ast::EventExpr, into: &mut Vec<String>) {
+    match *event {
+        svlog_ast::EventExpr::Edge {
+            edge: svlog_ast::EdgeIdent::Posedge | svlog_ast::EdgeIdent::Negedge,
+            ref value,
+            ..
+        } => {
+            if let svlog_ast::IdentExpr(name) = value.data {
+                into.push(name.value.to_string());
+            }
+        }
+        svlog_ast::EventExpr::Edge { .. } => (),
+        svlog_ast::EventExpr::Iff { ref expr, .. } => svlog_edges(expr, into),
+        svlog_ast::EventExpr::Or {
+            ref lhs, ref rhs, ..
+        } => {
+            svlog_edges(lhs, into);
+            svlog_edges(rhs, into);
+        }
+    }
+}
+
+/// Skip into `begin`/`end` blocks to the first statement they contain.
+fn svlog_first_stmt<'a, 'b>(stmt: &'b svlog_ast::Stmt<'a>) -> &'b svlog_ast::Stmt<'a> {
+    match stmt.kind {
+        svlog_ast::SequentialBlock(ref stmts) if !stmts.is_empty() => svlog_first_stmt(&stmts[0]),
+        _ => stmt,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::SignalKind::*;
+    use super::*;
+    use crate::common::source::get_source_manager;
+    use crate::svlog::{lexer::Lexer, parser, preproc::Preprocessor};
+    use crate::wrapper::find_interface;
+
+    fn vhdl_kinds(text: &str, name: &str) -> Vec<SignalKind> {
+        let source = get_source_manager().add_anonymous(text);
+        let units = crate::vhdl::syntax::parse(source).unwrap();
+        let intf = find_interface(&units, &[], name).unwrap();
+        infer(&units, &[], &intf)
+    }
+
+    fn svlog_kinds(text: &str, name: &str) -> Vec<SignalKind> {
+        let source = get_source_manager().add_anonymous(text);
+        let arena = svlog_ast::Arena::default();
+        let files =
+            vec![parser::parse(Lexer::new(Preprocessor::new(source, &[], &[])), &arena).unwrap()];
+        let intf = find_interface(&[], &files, name).unwrap();
+        infer(&[], &files, &intf)
+    }
+
+    #[test]
+    fn vhdl_async_reset() {
+        let kinds = vhdl_kinds(
+            "entity ff is
+                port (clk, rst, d : in bit; q : out bit);
+            end;
+            architecture rtl of ff is
+            begin
+                process (clk, rst) begin
+                    if rst = '1' then
+                        q <= '0';
+                    elsif rising_edge(clk) then
+                        q <= d;
+                    end if;
+                end process;
+            end;",
+            "ff",
+        );
+        assert_eq!(kinds, vec![Clock, Reset, Data, Data]);
+    }
+
+    #[test]
+    fn vhdl_sync_reset() {
+        let kinds = vhdl_kinds(
+            "entity ff is
+                port (clk, srst, en, d : in bit; q : out bit);
+            end;
+            architecture rtl of ff is
+            begin
+                process (clk) begin
+                    if clk'event and clk = '1' then
+                        if srst = '1' then
+                            q <= '0';
+                        elsif en = '1' then
+                            q <= d;
+                        end if;
+                    end if;
+                end process;
+            end;",
+            "ff",
+        );
+        assert_eq!(kinds, vec![Clock, Reset, Data, Data, Data]);
+    }
+
+    #[test]
+    fn svlog_resets() {
+        let kinds = svlog_kinds(
+            "module ff (input logic clk_i, rst_ni, d, output logic q, r);
+                always_ff @(posedge clk_i or negedge rst_ni)
+                    if (!rst_ni) q <= 0;
+                    else q <= d;
+                always_ff @(posedge clk_i)
+                    if (d) r <= q & d;
+                    else r <= 1;
+            endmodule",
+            "ff",
+        );
+        assert_eq!(kinds, vec![Clock, Reset, Data, Data, Data]);
+    }
+
+    #[test]
+    fn svlog_sync_reset() {
+        // A condition whose branch assigns an input port is not a reset.
+        let kinds = svlog_kinds(
+            "module ff (input logic clk, srst, load, d, output logic q);
+                always_ff @(posedge clk) begin
+                    if (srst) q <= 0;
+                    else q <= d;
+                end
+                always_ff @(posedge clk) begin
+                    if (load) q <= d;
+                end
+            endmodule",
+            "ff",
+        );
+        assert_eq!(kinds, vec![Clock, Reset, Data, Data, Data]);
+    }
+}
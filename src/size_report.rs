@@ -0,0 +1,225 @@
+// Copyright (c) 2016-2020 Fabian Schuiki
+
+//! A report of the size of generated code.
+//!
+//! After code generation, the report lists the number of LLHD instructions,
+//! processes, instances, and signal bits of every unit in a module. Units that
+//! are much larger than their peers are flagged, since this usually hints at a
+//! generate loop or array that was unrolled by accident.
+
+use llhd::ir::{Module, Opcode, UnitKind};
+use llhd::{Type, TypeKind};
+use std::fmt;
+
+/// Units with fewer instructions than this are never flagged.
+const FLAG_MIN_INSTS: usize = 1000;
+
+/// Units with more than this many times the median instruction count are
+/// flagged.
+const FLAG_MEDIAN_FACTOR: usize = 10;
+
+/// The size of a single unit.
+#[derive(Debug, Clone)]
+pub struct UnitSize {
+    /// The name of the unit.
+    pub name: String,
+    /// The kind of unit.
+    pub kind: UnitKind,
+    /// The number of instructions in the unit.
+    pub insts: usize,
+    /// The number of processes and entities instantiated by the unit.
+    pub instances: usize,
+    /// The number of signal bits declared in the unit.
+    pub signal_bits: usize,
+    /// Whether the unit is suspiciously large.
+    pub flagged: bool,
+}
+
+/// A size report for an entire module.
+#[derive(Debug, Clone)]
+pub struct SizeReport {
+    /// The individual units, in the order they appear in the module.
+    pub units: Vec<UnitSize>,
+}
+
+impl SizeReport {
+    /// Compute the size report for a module.
+    pub fn new(module: &Module) -> Self {
+        let mut units: Vec<_> = module
+            .units()
+            .map(|unit| {
+                let mut size = UnitSize {
+                    name: unit.name().to_string(),
+                    kind: unit.kind(),
+                    insts: 0,
+                    instances: 0,
+                    signal_bits: 0,
+                    flagged: false,
+                };
+                for inst in unit.all_insts() {
+                    size.insts += 1;
+                    match unit[inst].opcode() {
+                        Opcode::Inst => size.instances += 1,
+                        Opcode::Sig => {
+                            size.signal_bits += bit_size(unit.inst_type(inst).unwrap_signal())
+                        }
+                        _ => (),
+                    }
+                }
+                size
+            })
+            .collect();
+
+        // Flag units that are much larger than the median unit.
+        let mut counts: Vec<_> = units.iter().map(|u| u.insts).collect();
+        counts.sort();
+        let median = counts.get(counts.len() / 2).cloned().unwrap_or(0);
+        for unit in &mut units {
+            unit.flagged =
+                unit.insts >= FLAG_MIN_INSTS && unit.insts > median.max(1) * FLAG_MEDIAN_FACTOR;
+        }
+
+        SizeReport { units }
+    }
+
+    /// Check whether any unit has been flagged as suspiciously large.
+    pub fn any_flagged(&self) -> bool {
+        self.units.iter().any(|u| u.flagged)
+    }
+}
+
+impl fmt::Display for SizeReport {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let width = self.units.iter().map(|u| u.name.len()).max().unwrap_or(0);
+        writeln!(
+            f,
+            "{:<w$}  {:<8}  {:>8}  {:>9}  {:>11}",
+            "unit",
+            "kind",
+            "insts",
+            "instances",
+            "signal bits",
+            w = width
+        )?;
+        for unit in &self.units {
+            writeln!(
+                f,
+                "{:<w$}  {:<8}  {:>8}  {:>9}  {:>11}{}",
+                unit.name,
+                unit.kind.to_string(),
+                unit.insts,
+                unit.instances,
+                unit.signal_bits,
+                if unit.flagged {
+                    "  <- unusually large; check for unrolled generate loops"
+                } else {
+                    ""
+                },
+                w = width
+            )?;
+        }
+        Ok(())
+    }
+}
+
+/// Estimate the number of bits needed to represent a value of a type.
+fn bit_size(ty: &Type) -> usize {
+    match **ty {
+        TypeKind::IntType(w) => w,
+        TypeKind::EnumType(n) => (n as f64).log2().ceil() as usize,
+        TypeKind::ArrayType(n, ref ty) => n * bit_size(ty),
+        TypeKind::StructType(ref fields) => fields.iter().map(bit_size).sum(),
+        TypeKind::SignalType(ref ty) | TypeKind::PointerType(ref ty) => bit_size(ty),
+        _ => 0,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// An entity with a given number of instructions, counting the implicit
+    /// terminator of its body.
+    fn entity(name: &str, insts: usize) -> String {
+        let mut text = format!("entity @{} () -> () {{\n", name);
+        for i in 1..insts {
+            text.push_str(&format!("    %c{} = const i1 0\n", i));
+        }
+        text.push_str("}\n");
+        text
+    }
+
+    fn report(text: &str) -> SizeReport {
+        SizeReport::new(&llhd::assembly::parse_module(text).unwrap())
+    }
+
+    fn flagged(report: &SizeReport) -> Vec<bool> {
+        report.units.iter().map(|u| u.flagged).collect()
+    }
+
+    #[test]
+    fn unit_sizes() {
+        let report = report(
+            "entity @leaf () -> () {
+            }
+            entity @top () -> () {
+                %zero = const i8 0
+                %a = sig i8 %zero
+                %arr = [4 x i8 %zero]
+                %b = sig [4 x i8] %arr
+                inst @leaf () -> ()
+                inst @leaf () -> ()
+            }",
+        );
+        assert_eq!(
+            report.to_string(),
+            "\
+unit   kind         insts  instances  signal bits
+@leaf  entity           1          0            0
+@top   entity           7          2           40
+"
+        );
+        assert!(!report.any_flagged());
+    }
+
+    #[test]
+    fn flag_min_insts() {
+        // Only units with at least `FLAG_MIN_INSTS` instructions are flagged,
+        // however small their peers.
+        let text = [
+            entity("a", 1),
+            entity("b", 1),
+            entity("c", 1),
+            entity("d", FLAG_MIN_INSTS - 1),
+            entity("e", FLAG_MIN_INSTS),
+        ]
+        .concat();
+        let report = report(&text);
+        assert_eq!(flagged(&report), vec![false, false, false, false, true]);
+        assert!(report
+            .to_string()
+            .lines()
+            .last()
+            .unwrap()
+            .ends_with("  <- unusually large; check for unrolled generate loops"));
+    }
+
+    #[test]
+    fn flag_median_factor() {
+        // Units are flagged once they exceed `FLAG_MEDIAN_FACTOR` times the
+        // median instruction count.
+        let limit = FLAG_MIN_INSTS * FLAG_MEDIAN_FACTOR;
+        let text = [
+            entity("a", FLAG_MIN_INSTS),
+            entity("b", FLAG_MIN_INSTS),
+            entity("c", FLAG_MIN_INSTS),
+            entity("d", limit),
+            entity("e", limit + 1),
+        ]
+        .concat();
+        assert_eq!(
+            flagged(&report(&text)),
+            vec![false, false, false, false, true]
+        );
+    }
+}
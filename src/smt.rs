@@ -0,0 +1,504 @@
+// Copyright (c) 2016-2020 Fabian Schuiki
+
+//! Export of combinational logic cones as SMT-LIB2.
+//!
+//! Starting from a signal of an entity, usually an output, this module walks
+//! back through the value driven onto the signal and collects the
+//! combinational logic that computes it. The result is emitted as a set of
+//! SMT-LIB2 definitions over bit vectors, which can be handed to any solver
+//! supporting the `QF_BV` logic to check properties of the output or the
+//! equivalence of two implementations.
+//!
+//! Input ports become free variables. So do signals driven by the processes
+//! and entities instantiated in the entity, since their behaviour is not part
+//! of the combinational cone; they act as cut points. Internal signals driven
+//! within the entity are followed to the value driven onto them.
+
+use llhd::ir::{Module, Opcode, Unit, Value};
+use llhd::TypeKind;
+use std::collections::{HashMap, HashSet};
+
+/// Emit the combinational cone of a signal as SMT-LIB2.
+///
+/// The signal is named either as `SIGNAL` or as `ENTITY.SIGNAL`; the former
+/// is searched for in all entities of the module. The cone is defined as an
+/// SMT-LIB2 function of the same name. Returns an error message if the signal
+/// cannot be found or its cone contains logic that has no bit vector
+/// equivalent.
+pub fn emit(module: &Module, name: &str) -> Result<String, String> {
+    let (entity, signal) = match name.rfind('.') {
+        Some(dot) => (Some(&name[..dot]), &name[dot + 1..]),
+        None => (None, name),
+    };
+    let mut candidates = vec![];
+    for unit in module.units().filter(|u| u.is_entity()) {
+        if entity.is_some() && unit.name().get_name() != entity {
+            continue;
+        }
+        for value in signals(unit) {
+            if unit.get_name(value) == Some(signal) {
+                candidates.push((unit, value));
+            }
+        }
+    }
+    let (unit, value) = match candidates.len() {
+        0 => return Err(format!("no signal named `{}`", name)),
+        1 => candidates[0],
+        _ => {
+            return Err(format!(
+                "signal name `{}` is ambiguous; use `ENTITY.{}` to choose one of: {}",
+                signal,
+                signal,
+                candidates
+                    .iter()
+                    .map(|(unit, _)| unit.name().to_string())
+                    .collect::<Vec<_>>()
+                    .join(", ")
+            ))
+        }
+    };
+
+    let mut cone = Cone {
+        unit,
+        names: HashMap::new(),
+        used: Some(signal.to_string()).into_iter().collect(),
+        active: HashSet::new(),
+        decls: vec![],
+        defs: vec![],
+    };
+    let result = cone.signal(value)?;
+    let mut text = format!(
+        "; cone of `{}` in {}\n(set-logic QF_BV)\n",
+        signal,
+        unit.name()
+    );
+    for line in cone.decls.iter().chain(cone.defs.iter()) {
+        text.push_str(line);
+        text.push('\n');
+    }
+    text.push_str(&format!(
+        "(define-fun |{}| () {} {})\n",
+        signal,
+        sort(unit, value)?,
+        result
+    ));
+    Ok(text)
+}
+
+/// The signals of an entity, i.e. its ports and `sig` instructions.
+fn signals<'a>(unit: Unit<'a>) -> impl Iterator<Item = Value> + 'a {
+    unit.args().chain(
+        unit.all_insts()
+            .filter(move |&inst| unit[inst].opcode() == Opcode::Sig)
+            .map(move |inst| unit.inst_result(inst)),
+    )
+}
+
+/// The SMT-LIB2 sort of a value or the signal it is carried on.
+fn sort(unit: Unit, value: Value) -> Result<String, String> {
+    let ty = unit.value_type(value);
+    let ty = match *ty {
+        TypeKind::SignalType(ref ty) => ty.clone(),
+        _ => ty.clone(),
+    };
+    match *ty {
+        TypeKind::IntType(width) if width > 0 => Ok(format!("(_ BitVec {})", width)),
+        _ => Err(format!(
+            "`{}` is of type {}, which has no bit vector equivalent",
+            value.dump(&unit),
+            ty
+        )),
+    }
+}
+
+/// The bit width of an integer value.
+fn width(unit: Unit, value: Value) -> usize {
+    match *unit.value_type(value) {
+        TypeKind::IntType(width) => width,
+        _ => 0,
+    }
+}
+
+/// The state of a cone extraction.
+struct Cone<'a> {
+    unit: Unit<'a>,
+    /// The SMT-LIB2 term for each value and signal already visited.
+    names: HashMap<Value, String>,
+    /// The SMT-LIB2 symbols already in use.
+    used: HashSet<String>,
+    /// The signals currently being visited, to detect combinational loops.
+    active: HashSet<Value>,
+    /// The free variables of the cone.
+    decls: Vec<String>,
+    /// The definitions of the intermediate values of the cone.
+    defs: Vec<String>,
+}
+
+impl<'a> Cone<'a> {
+    /// Allocate a unique symbol based on a name.
+    fn symbol(&mut self, name: &str) -> String {
+        let mut symbol = name.to_string();
+        let mut index = 0;
+        while !self.used.insert(symbol.clone()) {
+            index += 1;
+            symbol = format!("{}.{}", name, index);
+        }
+        format!("|{}|", symbol)
+    }
+
+    /// Determine the term for the value carried on a signal.
+    fn signal(&mut self, signal: Value) -> Result<String, String> {
+        if let Some(name) = self.names.get(&signal) {
+            return Ok(name.clone());
+        }
+        let unit = self.unit;
+        let name = match unit.get_name(signal) {
+            Some(name) => name.to_string(),
+            None => signal.dump(&unit).to_string(),
+        };
+        if !self.active.insert(signal) {
+            return Err(format!("combinational loop through `{}`", name));
+        }
+
+        // Find the instructions in the entity that assign the signal.
+        let mut drivers = vec![];
+        let mut cut = unit.input_args().any(|arg| arg == signal);
+        for &inst in unit.uses(signal) {
+            let data = &unit[inst];
+            match data.opcode() {
+                Opcode::Drv if data.args()[0] == signal => drivers.push(data.args()[1]),
+                Opcode::DrvCond if data.args()[0] == signal => {
+                    return Err(format!("`{}` is driven conditionally", name))
+                }
+                Opcode::Inst if data.output_args().contains(&signal) => cut = true,
+                _ => (),
+            }
+        }
+
+        let term = match (cut, drivers.len()) {
+            (true, 0) => {
+                let term = self.symbol(&name);
+                self.decls
+                    .push(format!("(declare-const {} {})", term, sort(unit, signal)?));
+                term
+            }
+            (false, 1) => self.value(drivers[0])?,
+            (false, 0) => match unit.get_value_inst(signal) {
+                Some(inst) => self.value(unit[inst].args()[0])?,
+                None => return Err(format!("`{}` is never driven", name)),
+            },
+            _ => return Err(format!("`{}` has multiple drivers", name)),
+        };
+        self.active.remove(&signal);
+        self.names.insert(signal, term.clone());
+        Ok(term)
+    }
+
+    /// Determine the term for a value, defining it and its operands as needed.
+    fn value(&mut self, value: Value) -> Result<String, String> {
+        if let Some(name) = self.names.get(&value) {
+            return Ok(name.clone());
+        }
+        let unit = self.unit;
+        let inst = match unit.get_value_inst(value) {
+            Some(inst) => inst,
+            None => {
+                return Err(format!(
+                    "`{}` is not computed in the entity",
+                    value.dump(&unit)
+                ))
+            }
+        };
+        let data = &unit[inst];
+        let args = data.args();
+        let sort = sort(unit, value)?;
+        let unary = |op: &str, this: &mut Self| -> Result<String, String> {
+            Ok(format!("({} {})", op, this.value(args[0])?))
+        };
+        let binary = |op: &str, this: &mut Self| -> Result<String, String> {
+            Ok(format!(
+                "({} {} {})",
+                op,
+                this.value(args[0])?,
+                this.value(args[1])?
+            ))
+        };
+        let compare = |op: &str, this: &mut Self| -> Result<String, String> {
+            Ok(format!("(ite {} #b1 #b0)", binary(op, this)?))
+        };
+
+        let term = match data.opcode() {
+            Opcode::ConstInt => {
+                let int = data.get_const_int().unwrap();
+                format!("(_ bv{} {})", int.value, int.width)
+            }
+            Opcode::Alias => self.value(args[0])?,
+            Opcode::Prb => self.signal(args[0])?,
+            Opcode::Not => unary("bvnot", self)?,
+            Opcode::Neg => unary("bvneg", self)?,
+            Opcode::Add => binary("bvadd", self)?,
+            Opcode::Sub => binary("bvsub", self)?,
+            Opcode::And => binary("bvand", self)?,
+            Opcode::Or => binary("bvor", self)?,
+            Opcode::Xor => binary("bvxor", self)?,
+            Opcode::Smul | Opcode::Umul => binary("bvmul", self)?,
+            Opcode::Sdiv => binary("bvsdiv", self)?,
+            Opcode::Smod => binary("bvsmod", self)?,
+            Opcode::Srem => binary("bvsrem", self)?,
+            Opcode::Udiv => binary("bvudiv", self)?,
+            Opcode::Umod | Opcode::Urem => binary("bvurem", self)?,
+            Opcode::Eq => compare("=", self)?,
+            Opcode::Neq => compare("distinct", self)?,
+            Opcode::Slt => compare("bvslt", self)?,
+            Opcode::Sgt => compare("bvsgt", self)?,
+            Opcode::Sle => compare("bvsle", self)?,
+            Opcode::Sge => compare("bvsge", self)?,
+            Opcode::Ult => compare("bvult", self)?,
+            Opcode::Ugt => compare("bvugt", self)?,
+            Opcode::Ule => compare("bvule", self)?,
+            Opcode::Uge => compare("bvuge", self)?,
+            Opcode::ExtSlice => format!(
+                "((_ extract {} {}) {})",
+                data.imms()[0] + data.imms()[1] - 1,
+                data.imms()[0],
+                self.value(args[0])?
+            ),
+            Opcode::InsSlice => self.insert(args[0], args[1], data.imms()[0], data.imms()[1])?,
+            Opcode::Shl | Opcode::Shr => {
+                self.shift(data.opcode() == Opcode::Shl, args[0], args[1], args[2])?
+            }
+            Opcode::Mux => self.mux(args[0], args[1])?,
+            op => {
+                return Err(format!(
+                    "`{}` instruction computing `{}` has no bit vector equivalent",
+                    op,
+                    value.dump(&unit)
+                ))
+            }
+        };
+
+        let name = self.symbol(&value.dump(&unit).to_string());
+        self.defs
+            .push(format!("(define-fun {} () {} {})", name, sort, term));
+        self.names.insert(value, name.clone());
+        Ok(name)
+    }
+
+    /// Build the term for an `inss` instruction.
+    fn insert(
+        &mut self,
+        target: Value,
+        value: Value,
+        offset: usize,
+        length: usize,
+    ) -> Result<String, String> {
+        let total = width(self.unit, target);
+        let target = self.value(target)?;
+        let mut parts = vec![];
+        if offset + length < total {
+            parts.push(format!(
+                "((_ extract {} {}) {})",
+                total - 1,
+                offset + length,
+                target
+            ));
+        }
+        parts.push(self.value(value)?);
+        if offset > 0 {
+            parts.push(format!("((_ extract {} 0) {})", offset - 1, target));
+        }
+        Ok(match parts.len() {
+            1 => parts.pop().unwrap(),
+            _ => format!("(concat {})", parts.join(" ")),
+        })
+    }
+
+    /// Build the term for a `shl` or `shr` instruction.
+    ///
+    /// The bits shifted in are taken from the hidden value. This is modelled
+    /// by shifting the concatenation of the base and hidden value and
+    /// extracting the bits of the base.
+    fn shift(
+        &mut self,
+        left: bool,
+        base: Value,
+        hidden: Value,
+        amount: Value,
+    ) -> Result<String, String> {
+        let unit = self.unit;
+        let base_width = width(unit, base);
+        let total = base_width + width(unit, hidden);
+        let amount_width = width(unit, amount);
+        let (base, hidden, amount) = (self.value(base)?, self.value(hidden)?, self.value(amount)?);
+        let amount = if amount_width < total {
+            format!("((_ zero_extend {}) {})", total - amount_width, amount)
+        } else if amount_width > total {
+            // Amounts beyond the combined width shift out all bits, which
+            // the saturating comparison preserves.
+            format!(
+                "(ite (bvuge {} (_ bv{} {})) (_ bv{} {}) ((_ extract {} 0) {}))",
+                amount,
+                total,
+                amount_width,
+                total,
+                total,
+                total - 1,
+                amount
+            )
+        } else {
+            amount
+        };
+        Ok(if left {
+            format!(
+                "((_ extract {} {}) (bvshl (concat {} {}) {}))",
+                total - 1,
+                total - base_width,
+                base,
+                hidden,
+                amount
+            )
+        } else {
+            format!(
+                "((_ extract {} 0) (bvlshr (concat {} {}) {}))",
+                base_width - 1,
+                hidden,
+                base,
+                amount
+            )
+        })
+    }
+
+    /// Build the term for a `mux` instruction.
+    ///
+    /// The choices must be given as an array literal. Selectors beyond the
+    /// last choice yield the last choice.
+    fn mux(&mut self, choices: Value, select: Value) -> Result<String, String> {
+        let unit = self.unit;
+        let choices = match unit.get_value_inst(choices) {
+            Some(inst) if unit[inst].opcode() == Opcode::Array => unit[inst].args().to_vec(),
+            _ => {
+                return Err(format!(
+                    "choices `{}` of a `mux` are not an array literal",
+                    choices.dump(&unit)
+                ))
+            }
+        };
+        let select_width = width(unit, select);
+        let select = self.value(select)?;
+        let mut term = self.value(*choices.last().unwrap())?;
+        for (index, &choice) in choices.iter().enumerate().rev().skip(1) {
+            if select_width < 64 && index >> select_width != 0 {
+                continue;
+            }
+            term = format!(
+                "(ite (= {} (_ bv{} {})) {} {})",
+                select,
+                index,
+                select_width,
+                self.value(choice)?,
+                term
+            );
+        }
+        Ok(term)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn emit_text(text: &str, name: &str) -> Result<String, String> {
+        emit(&llhd::assembly::parse_module(text).unwrap(), name)
+    }
+
+    #[test]
+    fn cone_of_output() {
+        let smt = emit_text(
+            "entity @top (i4$ %a, i4$ %b) -> (i4$ %z) {
+                %ap = prb i4$ %a
+                %bp = prb i4$ %b
+                %y = add i4 %ap, %bp
+                %x = exts i2, i4 %y, 1, 2
+                %c = const i2 3
+                %w = and i2 %x, %c
+                %t = const time 0s
+                %v = inss i4 %y, i2 %w, 0, 2
+                drv i4$ %z, %v, %t
+            }",
+            "z",
+        );
+        assert_eq!(
+            smt.unwrap(),
+            "; cone of `z` in @top\n\
+             (set-logic QF_BV)\n\
+             (declare-const |a| (_ BitVec 4))\n\
+             (declare-const |b| (_ BitVec 4))\n\
+             (define-fun |%ap| () (_ BitVec 4) |a|)\n\
+             (define-fun |%bp| () (_ BitVec 4) |b|)\n\
+             (define-fun |%y| () (_ BitVec 4) (bvadd |%ap| |%bp|))\n\
+             (define-fun |%x| () (_ BitVec 2) ((_ extract 2 1) |%y|))\n\
+             (define-fun |%c| () (_ BitVec 2) (_ bv3 2))\n\
+             (define-fun |%w| () (_ BitVec 2) (bvand |%x| |%c|))\n\
+             (define-fun |%v| () (_ BitVec 4) (concat ((_ extract 3 2) |%y|) |%w|))\n\
+             (define-fun |z| () (_ BitVec 4) |%v|)\n"
+        );
+    }
+
+    #[test]
+    fn instances_are_cut_points() {
+        let smt = emit_text(
+            "entity @inv (i1$ %a) -> (i1$ %z) {
+            }
+            entity @top (i1$ %a) -> (i1$ %z) {
+                %init = const i1 0
+                %m = sig i1 %init
+                inst @inv (i1$ %a) -> (i1$ %m)
+                %mp = prb i1$ %m
+                %ap = prb i1$ %a
+                %y = eq i1 %mp, %ap
+                %t = const time 0s
+                drv i1$ %z, %y, %t
+            }",
+            "top.z",
+        );
+        assert_eq!(
+            smt.unwrap(),
+            "; cone of `z` in @top\n\
+             (set-logic QF_BV)\n\
+             (declare-const |m| (_ BitVec 1))\n\
+             (declare-const |a| (_ BitVec 1))\n\
+             (define-fun |%mp| () (_ BitVec 1) |m|)\n\
+             (define-fun |%ap| () (_ BitVec 1) |a|)\n\
+             (define-fun |%y| () (_ BitVec 1) (ite (= |%mp| |%ap|) #b1 #b0))\n\
+             (define-fun |z| () (_ BitVec 1) |%y|)\n"
+        );
+    }
+
+    #[test]
+    fn signal_lookup() {
+        let text = "entity @a () -> (i1$ %z) {
+            }
+            entity @b () -> (i1$ %z) {
+            }";
+        assert_eq!(emit_text(text, "y").unwrap_err(), "no signal named `y`");
+        assert_eq!(
+            emit_text(text, "z").unwrap_err(),
+            "signal name `z` is ambiguous; use `ENTITY.z` to choose one of: @a, @b"
+        );
+        assert_eq!(emit_text(text, "b.z").unwrap_err(), "`z` is never driven");
+    }
+
+    #[test]
+    fn multiple_drivers() {
+        let smt = emit_text(
+            "entity @top (i1$ %a) -> (i1$ %z) {
+                %ap = prb i1$ %a
+                %t = const time 0s
+                drv i1$ %z, %ap, %t
+                drv i1$ %z, %ap, %t
+            }",
+            "z",
+        );
+        assert_eq!(smt.unwrap_err(), "`z` has multiple drivers");
+    }
+}
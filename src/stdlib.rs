@@ -0,0 +1,70 @@
+// Copyright (c) 2016-2020 Fabian Schuiki
+
+//! Selection of standard library sources.
+//!
+//! The `--ieee-path` and `--std-path` options point at source trees of the
+//! `ieee` and `std` libraries. Since the contents of these libraries differ
+//! between revisions of the VHDL standard, a source tree may contain one
+//! subdirectory per revision, named after the year of the revision, e.g.
+//! `1993/` and `2008/`. The short forms `93/`, `v93/`, and `vhdl93/` are
+//! accepted as well. A source tree without such subdirectories is used as is.
+
+use crate::VhdlStandard;
+use std::path::{Path, PathBuf};
+
+/// A variant of a library source tree for a specific revision of the standard.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct LibraryVariant {
+    /// The revision the variant was written for.
+    pub std: VhdlStandard,
+    /// The directory containing the sources.
+    pub path: PathBuf,
+}
+
+/// Find the variants of a library source tree.
+///
+/// Returns the variants in order of ascending revision.
+pub fn find_variants(root: &Path) -> Vec<LibraryVariant> {
+    VhdlStandard::ALL
+        .iter()
+        .filter_map(|&std| {
+            variant_dir_names(std)
+                .into_iter()
+                .map(|name| root.join(name))
+                .find(|path| path.is_dir())
+                .map(|path| LibraryVariant { std, path })
+        })
+        .collect()
+}
+
+/// Select the directory of a library source tree to use for a revision of the
+/// standard.
+///
+/// If the tree contains variants, the variant for `std` is used. If there is
+/// none, the most recent variant preceding `std` is used, since later revisions
+/// are largely backwards compatible. Returns `None` if the tree only contains
+/// variants for later revisions.
+pub fn select_variant(root: &Path, std: VhdlStandard) -> Option<PathBuf> {
+    let variants = find_variants(root);
+    if variants.is_empty() {
+        return Some(root.to_path_buf());
+    }
+    variants
+        .into_iter()
+        .rev()
+        .find(|v| v.std <= std)
+        .map(|v| v.path)
+}
+
+/// The directory names that may hold the variant for a revision.
+fn variant_dir_names(std: VhdlStandard) -> Vec<String> {
+    let year = std.year().to_string();
+    let short = &year[2..];
+    vec![
+        year.clone(),
+        short.to_string(),
+        format!("v{}", short),
+        format!("vhdl{}", short),
+        format!("vhdl-{}", year),
+    ]
+}
@@ -103,6 +103,10 @@ pub enum AstNode<'ast> {
     SubroutineDecl(&'ast ast::SubroutineDecl<'ast>),
     /// An interface.
     Interface(&'ast ast::Interface<'ast>),
+    /// A concurrent assertion.
+    Assertion(&'ast ast::Assertion<'ast>),
+    /// An elaboration system task.
+    ElabTask(&'ast ast::ElabTask<'ast>),
 }
 
 impl<'a> AstNode<'a> {
@@ -135,6 +139,7 @@ impl<'a> AstNode<'a> {
             AstNode::Import(x) => Some(x),
             AstNode::SubroutineDecl(x) => Some(x),
             AstNode::Interface(x) => Some(x),
+            AstNode::ElabTask(x) => Some(x),
             _ => None,
         }
     }
@@ -192,6 +197,7 @@ impl<'a> AstNode<'a> {
             AllNode::ImportItem(x) => Box::new(Some(AstNode::Import(x)).into_iter()),
             AllNode::SubroutineDecl(x) => Box::new(Some(AstNode::SubroutineDecl(x)).into_iter()),
             AllNode::Interface(x) => Box::new(Some(AstNode::Interface(x)).into_iter()),
+            AllNode::ElabTask(x) => Box::new(Some(AstNode::ElabTask(x)).into_iter()),
             _ => Box::new(None.into_iter()),
         }
     }
@@ -226,6 +232,8 @@ impl<'ast> HasSpan for AstNode<'ast> {
             AstNode::Import(x) => x.span(),
             AstNode::SubroutineDecl(x) => x.span(),
             AstNode::Interface(x) => x.span(),
+            AstNode::Assertion(x) => x.span,
+            AstNode::ElabTask(x) => x.span(),
         }
     }
 
@@ -257,6 +265,8 @@ impl<'ast> HasSpan for AstNode<'ast> {
             AstNode::Import(x) => x.human_span(),
             AstNode::SubroutineDecl(x) => x.human_span(),
             AstNode::Interface(x) => x.human_span(),
+            AstNode::Assertion(x) => x.label.map(|(_, span)| span).unwrap_or(x.span),
+            AstNode::ElabTask(x) => x.human_span(),
         }
     }
 }
@@ -291,6 +301,8 @@ impl<'ast> HasDesc for AstNode<'ast> {
             AstNode::Import(x) => "import",
             AstNode::SubroutineDecl(x) => "subroutine declaration",
             AstNode::Interface(x) => "interface",
+            AstNode::Assertion(x) => "assertion",
+            AstNode::ElabTask(x) => "elaboration system task",
         }
     }
 
@@ -322,6 +334,8 @@ impl<'ast> HasDesc for AstNode<'ast> {
             AstNode::Import(x) => x.to_definite_string(),
             AstNode::SubroutineDecl(x) => x.to_definite_string(),
             AstNode::Interface(x) => x.to_definite_string(),
+            AstNode::Assertion(_) => "assertion".to_string(),
+            AstNode::ElabTask(x) => x.to_definite_string(),
         }
     }
 }
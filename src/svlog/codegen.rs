@@ -4,14 +4,18 @@
 
 use crate::{
     crate_prelude::*,
+    format,
     hir::{AccessedNode, HirNode},
     port_list::PortList,
+    readmem,
     resolver::InstTarget,
     ty::UnpackedType,
     value::{Value, ValueKind},
     ParamEnv,
 };
-use num::{BigInt, One, ToPrimitive, Zero};
+use bit_vec::BitVec;
+use moore_common::{MessageSeverity, Scheduling, XPolicy};
+use num::{BigInt, BigRational, One, ToPrimitive, Zero};
 use std::{
     collections::{HashMap, HashSet},
     iter::{once, repeat},
@@ -29,6 +33,14 @@ pub struct CodeGenerator<'gcx, C> {
     into: llhd::ir::Module,
     /// Tables holding mappings and interned values.
     tables: Tables<'gcx>,
+    /// The concurrent assertions encountered in the emitted entities.
+    directives: Vec<Directive>,
+    /// The source locations of the emitted units.
+    sources: Vec<UnitSource>,
+    /// The synthesis attributes of the emitted units.
+    attributes: Vec<SynthAttr>,
+    /// The units that failed to generate with `--keep-going`.
+    failed: Vec<FailedUnit>,
 }
 
 impl<'gcx, C> CodeGenerator<'gcx, C> {
@@ -38,22 +50,177 @@ impl<'gcx, C> CodeGenerator<'gcx, C> {
             cx,
             into: llhd::ir::Module::new(),
             tables: Default::default(),
+            directives: vec![],
+            sources: vec![],
+            attributes: vec![],
+            failed: vec![],
         }
     }
 
+    /// Get the verification directives of the emitted entities.
+    ///
+    /// LLHD has no notion of assertions yet, so these are not part of the
+    /// generated module. Formal backends can pick them up from here, or from
+    /// the comments added to the assembly by the driver.
+    pub fn directives(&self) -> &[Directive] {
+        &self.directives
+    }
+
+    /// Get the source locations of the emitted units.
+    ///
+    /// Simulators can use these to place breakpoints on the processes of a
+    /// source line, or to show the source of the process being stepped.
+    pub fn sources(&self) -> &[UnitSource] {
+        &self.sources
+    }
+
+    /// Get the synthesis attributes of the emitted units.
+    ///
+    /// LLHD has no metadata attached to its units and instructions, so these
+    /// are passed on to downstream tools as comments in the assembly.
+    pub fn attributes(&self) -> &[SynthAttr] {
+        &self.attributes
+    }
+
+    /// Get the units that failed to generate.
+    ///
+    /// With `--keep-going`, a module whose code cannot be generated is only
+    /// declared in the LLHD module, such that the modules instantiating it
+    /// can still be emitted. Without it, the first failure aborts code
+    /// generation and this list remains empty.
+    pub fn failed_units(&self) -> &[FailedUnit] {
+        &self.failed
+    }
+
     /// Finalize code generation and return the generated LLHD module.
     pub fn finalize(self) -> llhd::ir::Module {
         self.into
     }
 }
 
+/// A concurrent assertion in an emitted entity.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Directive {
+    /// The entity containing the assertion.
+    pub entity: llhd::ir::UnitName,
+    /// The flavor of the assertion. Assumptions and restrictions constrain
+    /// the inputs, all other flavors are checked against the design.
+    pub kind: hir::AssertionKind,
+    /// The label of the assertion, if any.
+    pub label: Option<Name>,
+    /// The location of the assertion in the source.
+    pub span: Span,
+}
+
+impl std::fmt::Display for Directive {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        write!(f, "{} property", self.kind.keyword())?;
+        if let Some(label) = self.label {
+            write!(f, " `{}`", label)?;
+        }
+        let (line, column, _) = self.span.begin().human();
+        write!(f, " at {}:{}:{}", self.span.source.get_path(), line, column)
+    }
+}
+
+/// A unit whose code could not be generated.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct FailedUnit {
+    /// The name of the unit, declared in the generated module.
+    pub unit: llhd::ir::UnitName,
+    /// The location of the module in the source.
+    pub span: Span,
+}
+
+impl std::fmt::Display for FailedUnit {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        let (line, column, _) = self.span.begin().human();
+        write!(
+            f,
+            "{} at {}:{}:{}",
+            self.unit,
+            self.span.source.get_path(),
+            line,
+            column
+        )
+    }
+}
+
+/// The source location an emitted unit was generated from.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct UnitSource {
+    /// The emitted entity or process.
+    pub unit: llhd::ir::UnitName,
+    /// The location of the module or procedure in the source.
+    pub span: Span,
+}
+
+impl std::fmt::Display for UnitSource {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        let (line, column, _) = self.span.begin().human();
+        write!(
+            f,
+            "source at {}:{}:{}",
+            self.span.source.get_path(),
+            line,
+            column
+        )
+    }
+}
+
+/// The synthesis attributes passed on to downstream tools.
+const SYNTH_ATTRS: &[&str] = &["keep", "dont_touch", "ram_style", "async_reg", "mark_debug"];
+
+/// A synthesis attribute such as `(* keep *)` attached to an emitted entity,
+/// or to one of its signals or instances.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SynthAttr {
+    /// The emitted entity.
+    pub unit: llhd::ir::UnitName,
+    /// The signal or instance the attribute is attached to, or `None` if it is
+    /// attached to the entity itself.
+    pub target: Option<String>,
+    /// The name of the attribute, in lower case.
+    pub name: String,
+    /// The source text of the value of the attribute, if any.
+    pub value: Option<String>,
+}
+
+impl std::fmt::Display for SynthAttr {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        write!(f, "attribute {}", self.name)?;
+        if let Some(ref value) = self.value {
+            write!(f, " = {}", value)?;
+        }
+        if let Some(ref target) = self.target {
+            write!(f, " on {}", target)?;
+        }
+        Ok(())
+    }
+}
+
 #[derive(Default)]
 struct Tables<'gcx> {
     module_defs: HashMap<NodeEnvId, Result<Rc<EmittedModule<'gcx>>>>,
     module_signatures: HashMap<NodeEnvId, (llhd::ir::UnitName, llhd::ir::Signature)>,
+    module_insts: HashMap<ModuleInstKey<'gcx>, Rc<EmittedModule<'gcx>>>,
     interned_types: HashMap<&'gcx UnpackedType<'gcx>, Result<llhd::Type>>,
 }
 
+/// The parametrization of a module that determines its generated code.
+///
+/// Parameter environments refer to the expressions assigned in each
+/// instantiation, such that identical instances end up in distinct
+/// environments. Instances that agree in their parameter values and types,
+/// and in the types of their ports, share one emitted unit.
+#[derive(PartialEq, Eq, Hash)]
+struct ModuleInstKey<'gcx> {
+    module: NodeId,
+    values: Vec<Value<'gcx>>,
+    types: Vec<&'gcx UnpackedType<'gcx>>,
+    ports: Vec<&'gcx UnpackedType<'gcx>>,
+}
+
 impl<'gcx, C> Deref for CodeGenerator<'gcx, C> {
     type Target = C;
 
@@ -63,6 +230,21 @@ impl<'gcx, C> Deref for CodeGenerator<'gcx, C> {
 }
 
 impl<'a, 'gcx, C: Context<'gcx>> CodeGenerator<'gcx, &'a C> {
+    /// Record the synthesis attributes of the construct starting at `span`.
+    fn record_attributes(&mut self, unit: &str, target: Option<String>, span: Span) {
+        for attr in self.gcx().attributes_of(span) {
+            let name = attr.name.value.to_string().to_lowercase();
+            if SYNTH_ATTRS.contains(&name.as_str()) {
+                self.attributes.push(SynthAttr {
+                    unit: llhd::ir::UnitName::Global(unit.to_string()),
+                    target: target.clone(),
+                    name,
+                    value: attr.value.map(|v| v.extract()),
+                });
+            }
+        }
+    }
+
     /// Emit the code for a module and all its dependent modules.
     pub fn emit_module(&mut self, id: NodeId) -> Result<Rc<EmittedModule<'gcx>>> {
         self.emit_module_with_env(id, self.default_param_env())
@@ -77,6 +259,7 @@ impl<'a, 'gcx, C: Context<'gcx>> CodeGenerator<'gcx, &'a C> {
         if let Some(x) = self.tables.module_defs.get(&id.env(env)) {
             return x.clone();
         }
+        self.gcx().cancel.check()?;
         let hir = match self.hir_of(id)? {
             HirNode::Module(m) => m,
             _ => panic!("expected {:?} to be a module", id),
@@ -91,12 +274,29 @@ impl<'a, 'gcx, C: Context<'gcx>> CodeGenerator<'gcx, &'a C> {
         // Determine entity type and port names.
         let ports = self.determine_module_ports(&hir.ports_new.int, env)?;
 
+        // Reuse the code emitted for an identically parametrized instance.
+        let key = self.module_inst_key(id, hir, env, &ports);
+        if let Some(x) = self.tables.module_insts.get(&key) {
+            debug!(
+                "Reusing module `{}` emitted for identical parameters",
+                hir.name
+            );
+            let result = Ok(x.clone());
+            self.tables.module_defs.insert(id.env(env), result.clone());
+            return result;
+        }
+
         // Pick an entity name.
-        let mut entity_name: String = hir.name.value.into();
+        let mut entity_name = self.gcx().module_unit_name(hir.ast);
         if env != self.default_param_env() {
             entity_name.push_str(&format!(".param{}", env.0));
         }
         let name = llhd::ir::UnitName::Global(entity_name.clone());
+        self.sources.push(UnitSource {
+            unit: name.clone(),
+            span: hir.human_span(),
+        });
+        self.record_attributes(&entity_name, None, hir.ast.span);
 
         // Create entity.
         let mut ent =
@@ -104,7 +304,7 @@ impl<'a, 'gcx, C: Context<'gcx>> CodeGenerator<'gcx, &'a C> {
         let mut builder = llhd::ir::UnitBuilder::new_anonymous(&mut ent);
         self.tables
             .module_signatures
-            .insert(id.env(env), (name, ports.sig.clone()));
+            .insert(id.env(env), (name.clone(), ports.sig.clone()));
         let mut values = HashMap::new();
         let mut gen = UnitGenerator {
             gen: self,
@@ -114,6 +314,7 @@ impl<'a, 'gcx, C: Context<'gcx>> CodeGenerator<'gcx, &'a C> {
             interned_lvalues: Default::default(),
             interned_rvalues: Default::default(),
             shadows: Default::default(),
+            finish: None,
         };
 
         // Assign proper port names and collect ports into a lookup table.
@@ -138,8 +339,31 @@ impl<'a, 'gcx, C: Context<'gcx>> CodeGenerator<'gcx, &'a C> {
             );
         }
 
-        // Emit the actual contents of the entity.
-        gen.emit_module_block(id, env, &hir.block, &entity_name)?;
+        // Emit the actual contents of the entity. With `--keep-going`, only
+        // declare the entity if this fails, such that the modules
+        // instantiating it can still be emitted.
+        if let Err(()) = gen.emit_module_block(id, env, &hir.block, &entity_name) {
+            if !self.sess().opts.keep_going {
+                return Err(());
+            }
+            self.directives.retain(|d| d.entity != name);
+            self.attributes.retain(|a| a.unit != name);
+            self.sources.retain(|s| s.unit != name);
+            self.into.declare(name.clone(), ports.sig.clone());
+            self.failed.push(FailedUnit {
+                unit: name.clone(),
+                span: hir.human_span(),
+            });
+            let emitted = Rc::new(EmittedModule {
+                name,
+                sig: ports.sig.clone(),
+                ports,
+            });
+            self.tables.module_insts.insert(key, emitted.clone());
+            let result = Ok(emitted);
+            self.tables.module_defs.insert(id.env(env), result.clone());
+            return result;
+        }
 
         // Assign default values to undriven output ports.
         for port in ports.outputs.iter() {
@@ -171,12 +395,55 @@ impl<'a, 'gcx, C: Context<'gcx>> CodeGenerator<'gcx, &'a C> {
                 .drv(gen.values[&port.accnode], default_value, zero_time);
         }
 
-        let unit = self.into.add_unit(ent);
-        let result = Ok(Rc::new(EmittedModule { unit, ports }));
+        self.into.add_unit(ent);
+        let emitted = Rc::new(EmittedModule {
+            name,
+            sig: ports.sig.clone(),
+            ports,
+        });
+        self.tables.module_insts.insert(key, emitted.clone());
+        let result = Ok(emitted);
         self.tables.module_defs.insert(id.env(env), result.clone());
         result
     }
 
+    /// Determine the parameter values and types, and the port types, of a
+    /// module in a parameter environment.
+    fn module_inst_key(
+        &mut self,
+        id: NodeId,
+        hir: &hir::Module<'gcx>,
+        env: ParamEnv,
+        ports: &ModuleIntf<'gcx>,
+    ) -> ModuleInstKey<'gcx> {
+        let mut values = vec![];
+        let mut types = vec![];
+        for &param_id in hir.params.iter().chain(hir.block.params.iter()) {
+            match self.hir_of(param_id) {
+                Ok(HirNode::ValueParam(_)) => values.push(self.constant_value_of(param_id, env)),
+                Ok(HirNode::TypeParam(_)) => {
+                    let ast = self.ast_for_id(param_id);
+                    types.push(
+                        self.map_to_type(Ref(ast), env)
+                            .unwrap_or_else(UnpackedType::make_error),
+                    );
+                }
+                _ => (),
+            }
+        }
+        ModuleInstKey {
+            module: id,
+            values,
+            types,
+            ports: ports
+                .inputs
+                .iter()
+                .chain(ports.outputs.iter())
+                .map(|port| port.ty)
+                .collect(),
+        }
+    }
+
     fn determine_module_ports(
         &mut self,
         ports: &'gcx [port_list::IntPort<'gcx>],
@@ -318,6 +585,7 @@ impl<'a, 'gcx, C: Context<'gcx>> CodeGenerator<'gcx, &'a C> {
         env: ParamEnv,
         name_prefix: &str,
     ) -> Result<EmittedProcedure> {
+        self.gcx().cancel.check()?;
         let hir = match self.hir_of(id)? {
             HirNode::Proc(x) => x,
             _ => unreachable!(),
@@ -355,6 +623,15 @@ impl<'a, 'gcx, C: Context<'gcx>> CodeGenerator<'gcx, &'a C> {
             })?));
             outputs.push(id);
         }
+
+        // Procedures calling `$finish` drive the finish signal, and `final`
+        // procedures wait for it.
+        let waits_for_finish = hir.kind == ast::ProcedureKind::Final;
+        if acc.finishes {
+            sig.add_output(llhd::signal_ty(llhd::int_ty(1)));
+        } else if waits_for_finish {
+            sig.add_input(llhd::signal_ty(llhd::int_ty(1)));
+        }
         trace!("Process Inputs: {:?}", inputs);
         trace!("Process Outputs: {:?}", outputs);
         trace!("Process Signature: {}", sig);
@@ -375,11 +652,12 @@ impl<'a, 'gcx, C: Context<'gcx>> CodeGenerator<'gcx, &'a C> {
             id.as_usize(),
             env.0,
         );
-        let mut prok = llhd::ir::UnitData::new(
-            llhd::ir::UnitKind::Process,
-            llhd::ir::UnitName::Local(proc_name),
-            sig,
-        );
+        let proc_name = llhd::ir::UnitName::Local(proc_name);
+        self.sources.push(UnitSource {
+            unit: proc_name.clone(),
+            span: hir.span,
+        });
+        let mut prok = llhd::ir::UnitData::new(llhd::ir::UnitKind::Process, proc_name, sig);
         let mut builder = llhd::ir::UnitBuilder::new_anonymous(&mut prok);
 
         // Assign names to inputs and outputs.
@@ -428,6 +706,16 @@ impl<'a, 'gcx, C: Context<'gcx>> CodeGenerator<'gcx, &'a C> {
         {
             values.insert(id.into(), arg);
         }
+        let finish = if acc.finishes {
+            Some(builder.output_arg(outputs.len()))
+        } else if waits_for_finish {
+            Some(builder.input_arg(inputs.len()))
+        } else {
+            None
+        };
+        if let Some(finish) = finish {
+            builder.set_name(finish, "finish".to_string());
+        }
         let mut pg = UnitGenerator {
             gen: self,
             builder: &mut builder,
@@ -436,6 +724,7 @@ impl<'a, 'gcx, C: Context<'gcx>> CodeGenerator<'gcx, &'a C> {
             interned_lvalues: Default::default(),
             interned_rvalues: Default::default(),
             shadows: Default::default(),
+            finish,
         };
         let entry_blk = pg.add_nameless_block();
         pg.builder.append_to(entry_blk);
@@ -475,7 +764,7 @@ impl<'a, 'gcx, C: Context<'gcx>> CodeGenerator<'gcx, &'a C> {
                 check_blk
             }
             ast::ProcedureKind::Final => {
-                // TODO(fschuiki): Replace this with a cleverer way to implement a trigger-on-end.
+                // Run either when `$finish` is called, or at the end of time.
                 let body_blk = pg.add_named_block("body");
                 let endtimes = pg.builder.ins().const_time(llhd::value::TimeValue::new(
                     "9001".parse().unwrap(),
@@ -483,7 +772,8 @@ impl<'a, 'gcx, C: Context<'gcx>> CodeGenerator<'gcx, &'a C> {
                     0,
                 ));
                 pg.builder.set_name(endtimes, "endtimes".to_string());
-                pg.builder.ins().wait_time(body_blk, endtimes, vec![]);
+                let trigger_on = pg.finish.into_iter().collect();
+                pg.builder.ins().wait_time(body_blk, endtimes, trigger_on);
                 pg.builder.append_to(body_blk);
                 pg.emit_shadow_update();
                 entry_blk // This block is ignored for final blocks
@@ -514,6 +804,11 @@ impl<'a, 'gcx, C: Context<'gcx>> CodeGenerator<'gcx, &'a C> {
             unit: self.into.add_unit(prok),
             inputs,
             outputs,
+            finish: match (acc.finishes, waits_for_finish) {
+                (true, _) => Some(true),
+                (false, true) => Some(false),
+                (false, false) => None,
+            },
         })
     }
 
@@ -531,7 +826,7 @@ impl<'a, 'gcx, C: Context<'gcx>> CodeGenerator<'gcx, &'a C> {
     /// Map a type to an LLHD type.
     fn emit_type_uninterned(&mut self, ty: &'gcx UnpackedType<'gcx>) -> Result<llhd::Type> {
         if ty.is_error() {
-            return Err(());
+            return Err(Error::Reported);
         }
         let ty = ty.resolve_full();
 
@@ -540,6 +835,12 @@ impl<'a, 'gcx, C: Context<'gcx>> CodeGenerator<'gcx, &'a C> {
             return Ok(llhd::int_ty(ty.get_bit_size().unwrap()));
         }
 
+        // Handle reals, which are passed around as the bits of their IEEE 754
+        // representation.
+        if let Some(real) = ty.get_real() {
+            return Ok(llhd::int_ty(real.bit_size()));
+        }
+
         // Handle arrays.
         if let Some(dim) = ty.outermost_dim() {
             let size = match dim.get_size() {
@@ -622,10 +923,10 @@ impl<'a, 'gcx, C: Context<'gcx>> CodeGenerator<'gcx, &'a C> {
                 hir::ExprKind::Assign { .. } => {
                     let mir = self.mir_rvalue(id, env);
                     match mir.kind {
-                        mir::RvalueKind::Error => return Err(()),
+                        mir::RvalueKind::Error => return Err(Error::Reported),
                         mir::RvalueKind::Assignment { lvalue, rvalue, .. } => {
                             let target_id = match lvalue.kind {
-                                mir::LvalueKind::Error => return Err(()),
+                                mir::LvalueKind::Error => return Err(Error::Reported),
                                 mir::LvalueKind::Genvar(id) => id,
                                 _ => unreachable!(),
                             };
@@ -652,7 +953,7 @@ impl<'a, 'gcx, C: Context<'gcx>> CodeGenerator<'gcx, &'a C> {
                     ))
                     .span(hir.human_span()),
                 );
-                Err(())
+                Err(Error::Reported)
             }
         }
     }
@@ -675,6 +976,9 @@ struct UnitGenerator<'a, 'gcx, C> {
     /// The shadow variables introduced to handle signals which are both read
     /// and written in a process.
     shadows: HashMap<AccessedNode, llhd::ir::Value>,
+    /// The signal set by `$finish` to trigger the `final` procedures. Created
+    /// on demand in entities.
+    finish: Option<llhd::ir::Value>,
 }
 
 impl<'a, 'gcx, C> Deref for UnitGenerator<'a, 'gcx, C> {
@@ -733,6 +1037,16 @@ where
         hir: &hir::ModuleBlock,
         name_prefix: &str,
     ) -> Result<()> {
+        // Report the elaboration system tasks. Those in generate blocks are
+        // only reached if the block is selected by the parameters.
+        for &task_id in &hir.elab_tasks {
+            let hir = match self.hir_of(task_id)? {
+                HirNode::ElabTask(x) => x,
+                _ => unreachable!(),
+            };
+            self.emit_elab_task(hir, env, name_prefix)?;
+        }
+
         // Emit declarations.
         for &decl_id in &hir.decls {
             let hir = match self.hir_of(decl_id)? {
@@ -751,6 +1065,7 @@ where
             let value = self.builder.ins().sig(init);
             self.builder.set_name(value, hir.name.value.into());
             self.values.insert(decl_id.into(), value.into());
+            self.record_attributes(name_prefix, Some(format!("%{}", hir.name.value)), hir.span);
         }
 
         // Emit interface instances.
@@ -844,9 +1159,8 @@ where
             assert_type!(rhs.ty, lhs.ty, rhs.span, self.cx);
             let lhs = self.emit_mir_lvalue(lhs)?.0;
             let rhs = self.emit_mir_rvalue(rhs)?;
-            let one_epsilon = llhd::value::TimeValue::new(num::zero(), 0, 1);
-            let one_epsilon = self.builder.ins().const_time(one_epsilon);
-            self.builder.ins().drv(lhs, rhs, one_epsilon);
+            let delay = self.emit_update_delay();
+            self.builder.ins().drv(lhs, rhs, delay);
         }
 
         // Emit module instantiations.
@@ -874,10 +1188,9 @@ where
             )?;
 
             // Instantiate the module.
-            let ext_unit = self.builder.add_extern(
-                self.into.unit(target.unit).name().clone(),
-                self.into.unit(target.unit).sig().clone(),
-            );
+            let ext_unit = self
+                .builder
+                .add_extern(target.name.clone(), target.sig.clone());
             if !inst.hir.ast.dims.is_empty() {
                 bug_span!(
                     inst.hir.ast.span(),
@@ -887,6 +1200,13 @@ where
             }
             self.builder.ins().inst(ext_unit, inputs, outputs);
             // TODO: Annotate instance name once LLHD allows that.
+            if let Some(parent) = inst.hir.ast.get_parent() {
+                self.record_attributes(
+                    name_prefix,
+                    Some(format!("inst {}", inst.hir.name)),
+                    parent.span(),
+                );
+            }
         }
 
         // Emit generate blocks.
@@ -947,8 +1267,13 @@ where
                     panic!("no value emitted for {:?}", id);
                 }
             };
-            let inputs = prok.inputs.iter().map(lookup_value).collect();
-            let outputs = prok.outputs.iter().map(lookup_value).collect();
+            let mut inputs: Vec<_> = prok.inputs.iter().map(lookup_value).collect();
+            let mut outputs: Vec<_> = prok.outputs.iter().map(lookup_value).collect();
+            match prok.finish {
+                Some(true) => outputs.push(self.emit_finish_signal()),
+                Some(false) => inputs.push(self.emit_finish_signal()),
+                None => (),
+            }
             let ext_unit = self.builder.add_extern(
                 self.into.unit(prok.unit).name().clone(),
                 self.into.unit(prok.unit).sig().clone(),
@@ -956,9 +1281,107 @@ where
             self.builder.ins().inst(ext_unit, inputs, outputs);
         }
 
+        // Record the concurrent assertions. Their properties are not lowered
+        // yet, but their flavor is preserved for formal backends.
+        for &assert_id in &hir.asserts {
+            let hir = match self.hir_of(assert_id)? {
+                HirNode::Assertion(x) => x,
+                _ => unreachable!(),
+            };
+            let directive = Directive {
+                entity: llhd::ir::UnitName::Global(name_prefix.to_string()),
+                kind: hir.kind,
+                label: hir.label.map(|l| l.value),
+                span: hir.span,
+            };
+            self.directives.push(directive);
+        }
+
         Ok(())
     }
 
+    /// Evaluate and report an elaboration system task.
+    ///
+    /// The arguments must be constant. A `$fatal` aborts elaboration.
+    fn emit_elab_task(&mut self, task: &hir::ElabTask, env: ParamEnv, scope: &str) -> Result<()> {
+        let items = match format::parse(&task.format.value.as_str()) {
+            Ok(x) => x,
+            Err(msg) => {
+                self.emit(DiagBuilder2::error(msg).span(task.format.span));
+                return Err(Error::Reported);
+            }
+        };
+        let specs = format::arg_specs(&items);
+        if specs.len() != task.args.len() {
+            self.emit(
+                DiagBuilder2::error(format!(
+                    "format `{}` expects {} argument(s), but {} given",
+                    task.format.value,
+                    specs.len(),
+                    task.args.len()
+                ))
+                .span(task.span),
+            );
+            return Err(Error::Reported);
+        }
+        let mut args = vec![];
+        for &arg in &task.args {
+            let value = self.constant_value_of(arg, env);
+            match value.kind {
+                ValueKind::Int(ref v, ..) => args.push(v.clone()),
+                ValueKind::Error => return Err(Error::Reported),
+                _ => {
+                    self.emit(
+                        DiagBuilder2::error(format!(
+                            "`{}` is not a constant integer",
+                            self.span(arg).extract()
+                        ))
+                        .span(self.span(arg))
+                        .add_note(format!(
+                            "Arguments of `${}` are evaluated during elaboration",
+                            task.severity.task_name()
+                        )),
+                    );
+                    return Err(Error::Reported);
+                }
+            }
+        }
+        let mut msg = format::substitute(&items, &args, scope);
+        if msg.is_empty() {
+            msg = format!("`${}` during elaboration", task.severity.task_name());
+        }
+        match task.severity {
+            hir::ElabSeverity::Fatal => {
+                self.emit(DiagBuilder2::fatal(msg).span(task.span));
+                Err(Error::Reported)
+            }
+            hir::ElabSeverity::Error => {
+                self.emit(DiagBuilder2::error(msg).span(task.span));
+                Ok(())
+            }
+            hir::ElabSeverity::Warning => {
+                self.emit(DiagBuilder2::warning(msg).span(task.span));
+                Ok(())
+            }
+            hir::ElabSeverity::Info => {
+                self.emit(DiagBuilder2::note(msg).span(task.span));
+                Ok(())
+            }
+        }
+    }
+
+    /// Get the finish signal of the entity, creating it if necessary.
+    fn emit_finish_signal(&mut self) -> llhd::ir::Value {
+        if let Some(finish) = self.finish {
+            return finish;
+        }
+        let init = self.builder.ins().const_int((1, 0));
+        let finish = self.builder.ins().sig(init);
+        self.builder.set_name(finish, "finish".to_string());
+        self.finish = Some(finish);
+        finish
+    }
+
     /// Emit code for the connections made in a port list.
     fn emit_port_connections(
         &mut self,
@@ -1025,10 +1448,12 @@ where
                 if lvalue {
                     let mir = self.mir_lvalue(mapping.id(), mapping.env());
                     if mir.is_error() {
-                        return Err(());
+                        return Err(Error::Reported);
                     }
                     let mir = match port.kind {
-                        ModulePortKind::Port => mir,
+                        ModulePortKind::Port => {
+                            return self.emit_output_connection(inst, port, mir)
+                        }
                         ModulePortKind::IntfSignal { decl_id, env, .. } => {
                             self.arena().alloc_mir_lvalue(mir::Lvalue {
                                 id: NodeId::alloc(),
@@ -1044,7 +1469,7 @@ where
                 } else {
                     let mir = self.mir_rvalue(mapping.id(), mapping.env());
                     if mir.is_error() {
-                        return Err(());
+                        return Err(Error::Reported);
                     }
                     let mir = match port.kind {
                         ModulePortKind::Port => mir,
@@ -1108,6 +1533,36 @@ where
         }
     }
 
+    /// Map the `x` and `z` bits of a constant to `0` or `1` for two-state
+    /// lowering. The special bits are given most significant bit first.
+    fn map_special_bits(
+        &mut self,
+        value: &BigInt,
+        special_bits: &BitVec,
+        policy: XPolicy,
+        span: Span,
+    ) -> Result<BigInt> {
+        let mut mask = BigInt::zero();
+        for b in special_bits {
+            mask <<= 1;
+            if b {
+                mask |= BigInt::one();
+            }
+        }
+        match policy {
+            XPolicy::Zero => Ok(value & !mask),
+            XPolicy::One => Ok(value | mask),
+            XPolicy::Error => {
+                self.emit(
+                    DiagBuilder2::error("`x` and `z` bits have no two-state equivalent")
+                        .span(span)
+                        .add_note("Use `--x-policy zero` or `--x-policy one` to map them to bits"),
+                );
+                Err(Error::Reported)
+            }
+        }
+    }
+
     /// Map a value to an LLHD constant.
     fn emit_const_uninterned(
         &mut self,
@@ -1116,12 +1571,29 @@ where
         span: Span,
     ) -> Result<llhd::ir::Value> {
         if value.ty.is_error() {
-            return Err(());
+            return Err(Error::Reported);
         }
         match value.kind {
-            ValueKind::Int(ref k, ..) => {
+            ValueKind::Int(ref k, ref special_bits, _) => {
                 let size = value.ty.simple_bit_vector(self.cx, span).size;
-                Ok(self.builder.ins().const_int((size, k.clone())))
+                let k = match self.sess().opts.two_state {
+                    Some(policy) if special_bits.any() => {
+                        self.map_special_bits(k, special_bits, policy, span)?
+                    }
+                    _ => k.clone(),
+                };
+                Ok(self.builder.ins().const_int((size, k)))
+            }
+            ValueKind::Real(v) => {
+                // Reals are passed around as the bits of their IEEE 754
+                // representation.
+                let v = f64::from_bits(v);
+                let k = match value.ty.get_real() {
+                    Some(ty::RealType::ShortReal) => BigInt::from((v as f32).to_bits()),
+                    _ => BigInt::from(v.to_bits()),
+                };
+                let size = value.ty.get_bit_size().unwrap();
+                Ok(self.builder.ins().const_int((size, k)))
             }
             ValueKind::Time(ref k) => Ok(self
                 .builder
@@ -1147,7 +1619,7 @@ where
                     );
                 }
             }
-            ValueKind::Error => Err(()),
+            ValueKind::Error => Err(Error::Reported),
             _ => panic!(
                 "invalid combination of type `{}` and value {:#?}",
                 value.ty, value
@@ -1155,6 +1627,82 @@ where
         }
     }
 
+    /// Emit the signal connected to an output port of an instance.
+    ///
+    /// A signal of a different width than the port is an error, unless
+    /// `--permissive-connections` is given. In that case the port drives an
+    /// auxiliary signal of its own width, whose value is truncated or
+    /// zero-extended onto the connected signal.
+    fn emit_output_connection(
+        &mut self,
+        inst: &InstDetails<'gcx>,
+        port: &ModulePort<'gcx>,
+        conn: &'gcx mir::Lvalue<'gcx>,
+    ) -> Result<llhd::ir::Value> {
+        let target = self.emit_mir_lvalue(conn)?.0;
+        let (port_width, conn_width) = match (
+            port.ty.get_simple_bit_vector(),
+            conn.ty.get_simple_bit_vector(),
+        ) {
+            (Some(p), Some(c)) if p.size != c.size => (p.size, c.size),
+            _ => return Ok(target),
+        };
+        let msg = format!(
+            "port `{}` is {} bits wide, but connected to `{}` of {} bits",
+            port.name,
+            port_width,
+            conn.span.extract(),
+            conn_width
+        );
+        if !self.sess().opts.permissive_connections {
+            self.emit(
+                DiagBuilder2::error(msg)
+                    .span(conn.span)
+                    .add_note("Port declared here:")
+                    .span(port.port.span)
+                    .add_note("Use `--permissive-connections` to truncate or extend the value"),
+            );
+            return Err(Error::Reported);
+        }
+        let note = if port_width > conn_width {
+            "The port's value is truncated"
+        } else {
+            "The port's value is zero-extended"
+        };
+        self.emit(DiagBuilder2::warning(msg).span(conn.span).add_note(note));
+        let llty = self.emit_type(port.ty)?;
+        let init = self.emit_zero_for_type(&llty);
+        let aux = self.builder.ins().sig(init);
+        self.builder
+            .set_name(aux, format!("{}.{}.coerced", inst.hir.name, port.name));
+        let value = self.builder.ins().prb(aux);
+        let value = if port_width > conn_width {
+            self.builder.ins().ext_slice(value, 0, conn_width)
+        } else {
+            let conn_llty = self.emit_type(conn.ty)?;
+            let zeros = self.emit_zero_for_type(&conn_llty);
+            self.builder.ins().ins_slice(zeros, value, 0, port_width)
+        };
+        let delay = self.emit_update_delay();
+        self.builder.ins().drv(target, value, delay);
+        Ok(aux)
+    }
+
+    /// Emit the delay of a continuous or blocking assignment to a signal.
+    ///
+    /// Such assignments take effect within the current delta cycle, like
+    /// updates in the active region, unless `--scheduling delta` defers all
+    /// signal updates to the next delta cycle.
+    fn emit_update_delay(&mut self) -> llhd::ir::Value {
+        let delay = match self.sess().opts.scheduling {
+            Scheduling::Delta => llhd::value::TimeValue::new(num::zero(), 1, 0),
+            Scheduling::Native | Scheduling::Stratified => {
+                llhd::value::TimeValue::new(num::zero(), 0, 1)
+            }
+        };
+        self.builder.ins().const_time(delay)
+    }
+
     /// Emit the zero value for an LLHD type.
     ///
     /// This function is ultimately expected to be moved into LLHD.
@@ -1221,9 +1769,8 @@ where
                 let ty = self.llhd_type(value);
                 let init = self.emit_zero_for_type(&ty);
                 let sig = self.builder.ins().sig(init);
-                let delay = llhd::value::TimeValue::new(num::zero(), 0, 1);
-                let delay_const = self.builder.ins().const_time(delay);
-                self.builder.ins().drv(sig, value, delay_const);
+                let delay = self.emit_update_delay();
+                self.builder.ins().drv(sig, value, delay);
                 Ok(sig)
             }
             (Mode::Value, Mode::Signal) => unreachable!(),
@@ -1263,7 +1810,7 @@ where
                     mir
                 );
             }
-            Err(()) => (),
+            Err(_) => (),
         }
         result
     }
@@ -1290,7 +1837,7 @@ where
                 self.emit(
                     DiagBuilder2::error("interface cannot be used in an expression").span(mir.span),
                 );
-                Err(())
+                Err(Error::Reported)
             }
 
             // Interface signals require special care, because they are emitted
@@ -1320,6 +1867,24 @@ where
                 Ok(self.builder.ins().neq(value, zero))
             }
 
+            mir::RvalueKind::CastRealToInt(value) | mir::RvalueKind::CastIntToReal(value) => {
+                // LLHD has no floating point operations, so the runtime
+                // converts between reals and integers.
+                let name = match mir.kind {
+                    mir::RvalueKind::CastRealToInt(..) => "moore.rtoi",
+                    _ => "moore.itor",
+                };
+                let value = self.emit_mir_rvalue(value)?;
+                let mut sig = llhd::ir::Signature::new();
+                sig.add_input(self.llhd_type(value));
+                sig.set_return_type(self.emit_type(mir.ty)?);
+                let ext_unit = self
+                    .builder
+                    .add_extern(llhd::ir::UnitName::Global(name.to_string()), sig);
+                let call = self.builder.ins().call(ext_unit, vec![value]);
+                Ok(self.builder.unit().inst_result(call))
+            }
+
             mir::RvalueKind::Truncate(target_width, value) => {
                 let llvalue = self.emit_mir_rvalue(value)?;
                 Ok(self.builder.ins().ext_slice(llvalue, 0, target_width))
@@ -1497,7 +2062,7 @@ where
                             DiagBuilder2::error("`**` operator on non-constants not supported")
                                 .span(mir.span),
                         );
-                        return Err(());
+                        return Err(Error::Reported);
                     }
                 })
             }
@@ -1597,6 +2162,111 @@ where
                 Ok(value)
             }
 
+            mir::RvalueKind::Clog2(arg) => {
+                // Find the position of the highest set bit in `arg - 1`, and
+                // map zero to zero.
+                let width = arg.ty.simple_bit_vector(self.cx, arg.span).size;
+                let result_width = mir.ty.simple_bit_vector(self.cx, mir.span).size;
+                let arg = self.emit_mir_rvalue(arg)?;
+                let one = self.builder.ins().const_int((width, 1));
+                let arg_zero = self.builder.ins().const_int((width, 0));
+                let zero = self.builder.ins().const_int((result_width, 0));
+                let below = self.builder.ins().sub(arg, one);
+                let mut value = zero;
+                for i in 0..width {
+                    let bit = self.builder.ins().ext_slice(below, i, 1);
+                    let size = self.builder.ins().const_int((result_width, i + 1));
+                    let mux = self.builder.ins().array(vec![value, size]);
+                    value = self.builder.ins().mux(mux, bit);
+                }
+                let is_zero = self.builder.ins().eq(arg, arg_zero);
+                let mux = self.builder.ins().array(vec![value, zero]);
+                let value = self.builder.ins().mux(mux, is_zero);
+                self.builder.set_name(value, "clog2".to_string());
+                Ok(value)
+            }
+
+            mir::RvalueKind::MathCall(func, ref args) => {
+                let mut sig = llhd::ir::Signature::new();
+                let mut inputs = vec![];
+                for arg in args {
+                    let value = self.emit_mir_rvalue(arg)?;
+                    sig.add_input(self.llhd_type(value));
+                    inputs.push(value);
+                }
+                sig.set_return_type(self.emit_type(mir.ty)?);
+                let ext_unit = self.builder.add_extern(
+                    llhd::ir::UnitName::Global(format!("moore.{}", func.name())),
+                    sig,
+                );
+                let call = self.builder.ins().call(ext_unit, inputs);
+                Ok(self.builder.unit().inst_result(call))
+            }
+
+            mir::RvalueKind::RandomCall(func, ref args) => {
+                // The seed of the run is passed along with every call, such
+                // that the runtime can seed its generator on first use.
+                let mut sig = llhd::ir::Signature::new();
+                let seed = self.sess().opts.seed;
+                let mut inputs = vec![self.builder.ins().const_int((32, seed))];
+                sig.add_input(llhd::int_ty(32));
+                for arg in args {
+                    let value = self.emit_mir_rvalue(arg)?;
+                    sig.add_input(self.llhd_type(value));
+                    inputs.push(value);
+                }
+                sig.set_return_type(self.emit_type(mir.ty)?);
+                let ext_unit = self.builder.add_extern(
+                    llhd::ir::UnitName::Global(format!("moore.{}", func.name())),
+                    sig,
+                );
+                let call = self.builder.ins().call(ext_unit, inputs);
+                let value = self.builder.unit().inst_result(call);
+                self.builder.set_name(value, func.name().to_string());
+                Ok(value)
+            }
+
+            mir::RvalueKind::TestPlusargs(string) => {
+                let string = self.emit_cstring(&string.as_str());
+                let mut sig = llhd::ir::Signature::new();
+                sig.add_input(self.llhd_type(string));
+                sig.set_return_type(self.emit_type(mir.ty)?);
+                let ext_unit = self.builder.add_extern(
+                    llhd::ir::UnitName::Global("moore.test_plusargs".to_string()),
+                    sig,
+                );
+                let call = self.builder.ins().call(ext_unit, vec![string]);
+                let value = self.builder.unit().inst_result(call);
+                self.builder.set_name(value, "test_plusargs".to_string());
+                Ok(value)
+            }
+
+            mir::RvalueKind::ValuePlusargs(format, target) => {
+                // The current value of the target is passed along and returned
+                // unchanged if no plusarg matches, since the target must not
+                // be modified in that case.
+                let (format_value, arg_types) =
+                    self.emit_checked_format(format, &[target], true, mir.span, mir.env)?;
+                let current = self.emit_rvalue(target, mir.env)?;
+                let mut sig = llhd::ir::Signature::new();
+                sig.add_input(self.llhd_type(format_value));
+                sig.add_input(self.llhd_type(current));
+                sig.set_return_type(self.emit_scan_type(&arg_types)?);
+                let ext_unit = self.builder.add_extern(
+                    llhd::ir::UnitName::Global("moore.value_plusargs".to_string()),
+                    sig,
+                );
+                let call = self
+                    .builder
+                    .ins()
+                    .call(ext_unit, vec![format_value, current]);
+                let result = self.builder.unit().inst_result(call);
+                self.emit_scan_results(result, &[target], None, mir.env)?;
+                let value = self.builder.ins().ext_field(result, 0);
+                self.builder.set_name(value, "value_plusargs".to_string());
+                Ok(value)
+            }
+
             mir::RvalueKind::Assignment {
                 lvalue,
                 rvalue,
@@ -1606,7 +2276,7 @@ where
                 self.emit_mir_rvalue(result)
             }
 
-            mir::RvalueKind::Error => Err(()),
+            mir::RvalueKind::Error => Err(Error::Reported),
         }
     }
 
@@ -1768,7 +2438,7 @@ where
                     );
                 }
             }
-            Err(()) => (),
+            Err(_) => (),
         }
         result
     }
@@ -1817,7 +2487,7 @@ where
             }
 
             // Errors from MIR lowering have already been reported. Just abort.
-            mir::LvalueKind::Error => Err(()),
+            mir::LvalueKind::Error => Err(Error::Reported),
 
             _ => {
                 unimplemented!(
@@ -1948,8 +2618,39 @@ where
         bb
     }
 
+    /// Emit the code for a delay, such as the `#5ns` in `#5ns x = y`.
+    ///
+    /// Delays without a time unit, such as `#5`, must be constant and are
+    /// given in the default time unit of one nanosecond.
+    fn emit_delay(&mut self, expr_id: NodeId, env: ParamEnv) -> Result<llhd::ir::Value> {
+        let ty = self.type_of(expr_id, env)?;
+        if ty.get_packed().map(|ty| ty.is_time()).unwrap_or(false) {
+            return self.emit_rvalue(expr_id, env);
+        }
+        let value = self.constant_value_of(expr_id, env);
+        let time = match value.kind {
+            ValueKind::Int(ref k, ..) => BigRational::new(k.clone(), BigInt::from(1_000_000_000)),
+            ValueKind::Error => return Err(Error::Reported),
+            _ => {
+                self.emit(
+                    DiagBuilder2::error(format!(
+                        "delay `{}` is neither a time nor a number",
+                        self.span(expr_id).extract()
+                    ))
+                    .span(self.span(expr_id)),
+                );
+                return Err(Error::Reported);
+            }
+        };
+        Ok(self
+            .builder
+            .ins()
+            .const_time(llhd::value::TimeValue::new(time, 0, 0)))
+    }
+
     /// Emit the code for a statement.
     fn emit_stmt(&mut self, stmt_id: NodeId, env: ParamEnv) -> Result<()> {
+        self.gcx().cancel.check()?;
         self.flush_mir();
         match self.hir_of(stmt_id)? {
             HirNode::Stmt(x) => self.emit_stmt_regular(stmt_id, x, env),
@@ -1985,7 +2686,7 @@ where
                 let lhs_mir = self.mir_lvalue(lhs, env);
                 let rhs_mir = self.mir_rvalue(rhs, env);
                 if lhs_mir.is_error() || rhs_mir.is_error() {
-                    return Err(());
+                    return Err(Error::Reported);
                 }
                 assert_type!(rhs_mir.ty, lhs_mir.ty, rhs_mir.span, self.cx);
                 let lhs_lv = self.emit_mir_lvalue(lhs_mir)?;
@@ -2028,7 +2729,7 @@ where
                         self.builder.ins().drv(lhs_lv.0, rhs_rv, delay_const);
                     }
                     hir::AssignKind::NonblockDelay(delay) => {
-                        let delay = self.emit_rvalue(delay, env)?;
+                        let delay = self.emit_delay(delay, env)?;
                         self.builder.ins().drv(lhs_lv.0, rhs_rv, delay);
                     }
                     _ => {
@@ -2045,7 +2746,7 @@ where
                 stmt,
             } => {
                 let resume_blk = self.add_nameless_block();
-                let duration = self.emit_rvalue(expr_id, env)?.into();
+                let duration = self.emit_delay(expr_id, env)?;
                 self.builder.ins().wait_time(resume_blk, duration, vec![]);
                 self.builder.append_to(resume_blk);
                 self.emit_shadow_update();
@@ -2318,6 +3019,31 @@ where
                 self.builder.ins().br(final_blk);
                 self.builder.append_to(final_blk);
             }
+            hir::StmtKind::ReadMem { .. } => {
+                self.emit_readmem(hir, env)?;
+            }
+            hir::StmtKind::Format { .. } => {
+                self.emit_format(hir, env)?;
+            }
+            hir::StmtKind::File(..) => {
+                self.emit_file_task(hir, env)?;
+            }
+            hir::StmtKind::Finish => {
+                self.emit_finish(hir, "$finish")?;
+            }
+            hir::StmtKind::Message(severity) => {
+                // Messages are not printed, but those that stop the simulation
+                // as per `--stop-on` behave like `$finish`.
+                if self.sess().opts.stops_at(severity) {
+                    let task = match severity {
+                        MessageSeverity::Note => "$info",
+                        MessageSeverity::Warning => "$warning",
+                        MessageSeverity::Error => "$error",
+                        MessageSeverity::Failure => "$fatal",
+                    };
+                    self.emit_finish(hir, task)?;
+                }
+            }
 
             _ => {
                 error!("{:#?}", hir);
@@ -2327,6 +3053,421 @@ where
         Ok(())
     }
 
+    /// Emit the code for a `$finish` system task, or a task that behaves like
+    /// one.
+    ///
+    /// Raises the finish signal to trigger the `final` procedures, and stops
+    /// the process. Code after the task is emitted into a block that is never
+    /// reached.
+    fn emit_finish(&mut self, hir: &hir::Stmt, task: &str) -> Result<()> {
+        let finish = match self.finish {
+            Some(finish) => finish,
+            None => {
+                self.emit(
+                    DiagBuilder2::error(format!("`{}` outside of a procedure not supported", task))
+                        .span(hir.span()),
+                );
+                return Err(Error::Reported);
+            }
+        };
+        let one = self.builder.ins().const_int((1, 1));
+        let delay = llhd::value::TimeValue::new(num::zero(), 1, 0);
+        let delay = self.builder.ins().const_time(delay);
+        self.builder.ins().drv(finish, one, delay);
+        self.builder.ins().halt();
+        let after_blk = self.add_named_block("after_finish");
+        self.builder.append_to(after_blk);
+        Ok(())
+    }
+
+    /// Emit the code for a `$readmemh` or `$readmemb` system task.
+    ///
+    /// The memory file is read during code generation. Its words are inserted
+    /// into the current value of the memory as constants, and the result is
+    /// assigned back to the memory.
+    fn emit_readmem(&mut self, hir: &hir::Stmt, env: ParamEnv) -> Result<()> {
+        let (format, file, target, start, end) = match hir.kind {
+            hir::StmtKind::ReadMem {
+                format,
+                file,
+                target,
+                start,
+                end,
+            } => (format, file, target, start, end),
+            _ => unreachable!(),
+        };
+        let lhs_mir = self.mir_lvalue(target, env);
+        if lhs_mir.is_error() {
+            return Err(Error::Reported);
+        }
+
+        // Determine the address range and word size of the memory.
+        let range = match lhs_mir.ty.outermost_dim() {
+            Some(ty::Dim::Unpacked(ty::UnpackedDim::Array(size))) => Some((0, size)),
+            Some(ty::Dim::Unpacked(ty::UnpackedDim::Range(range))) if range.offset >= 0 => {
+                Some((range.offset as usize, range.size))
+            }
+            _ => None,
+        };
+        let width = lhs_mir
+            .ty
+            .pop_dim(self.cx)
+            .and_then(|ty| ty.get_simple_bit_vector())
+            .map(|sbv| sbv.size);
+        let (lo, hi, width) = match (range, width) {
+            (Some((lo, size)), Some(width)) if size > 0 => (lo, lo + size - 1, width),
+            _ => {
+                self.emit(
+                    DiagBuilder2::error(format!(
+                        "cannot load memory file into `{}` of type `{}`",
+                        lhs_mir.span.extract(),
+                        lhs_mir.ty
+                    ))
+                    .span(lhs_mir.span)
+                    .add_note("Target must be an unpacked array of bit vectors"),
+                );
+                return Err(Error::Reported);
+            }
+        };
+
+        // Determine the addresses to load, defaulting to the entire memory.
+        let address = |id: Option<NodeId>, default: usize| -> Result<usize> {
+            let id = match id {
+                Some(id) => id,
+                None => return Ok(default),
+            };
+            let value = self.constant_int_value_of(id, env)?;
+            match value.to_usize() {
+                Some(addr) if addr >= lo && addr <= hi => Ok(addr),
+                _ => {
+                    self.emit(
+                        DiagBuilder2::error(format!(
+                            "address {} is outside the memory range {} to {}",
+                            value, lo, hi
+                        ))
+                        .span(self.span(id)),
+                    );
+                    Err(Error::Reported)
+                }
+            }
+        };
+        let start_addr = address(start, lo)?;
+        let end_addr = address(end, hi)?;
+
+        // Read the file, relative to the source file if it exists there.
+        let name = file.value.as_str();
+        let source = file.span.source.get_path();
+        let path = match std::path::Path::new(&*source).parent() {
+            Some(dir) if dir.join(&*name).exists() => dir.join(&*name),
+            _ => std::path::PathBuf::from(&*name),
+        };
+        let words = std::fs::read_to_string(&path)
+            .map_err(|e| format!("cannot read `{}`: {}", path.display(), e))
+            .and_then(|text| readmem::parse(&text, format.radix()));
+        let words = match words {
+            Ok(x) => x,
+            Err(msg) => {
+                self.emit(DiagBuilder2::error(msg).span(file.span));
+                return Err(Error::Reported);
+            }
+        };
+        let words = match readmem::assign(&words, start_addr, end_addr) {
+            Ok(x) => x,
+            Err(msg) => {
+                self.emit(
+                    DiagBuilder2::error(format!("memory file `{}`: {}", path.display(), msg))
+                        .span(hir.span),
+                );
+                return Err(Error::Reported);
+            }
+        };
+
+        // Insert the words into the memory.
+        let mut value = self.emit_rvalue(target, env)?;
+        for (addr, word) in words {
+            if word.bits() > width {
+                self.emit(
+                    DiagBuilder2::error(format!(
+                        "memory file `{}`: word {:x} does not fit into {} bits",
+                        path.display(),
+                        word,
+                        width
+                    ))
+                    .span(hir.span),
+                );
+                return Err(Error::Reported);
+            }
+            let word = self.builder.ins().const_int((width, word.clone()));
+            value = self.builder.ins().ins_field(value, word, addr - lo);
+        }
+        let lhs_lv = self.emit_mir_lvalue(lhs_mir)?;
+        self.emit_blocking_assign_llhd(lhs_lv, value)
+    }
+
+    /// Emit the code for a `$sformat`, `$swrite`, or `$sscanf` system task.
+    ///
+    /// The formatted string is returned by the `moore.sformat` runtime
+    /// function. The `moore.sscanf` function returns a struct of the number of
+    /// converted arguments and their values.
+    fn emit_format(&mut self, hir: &hir::Stmt, env: ParamEnv) -> Result<()> {
+        let (scan, string, text, args) = match hir.kind {
+            hir::StmtKind::Format {
+                scan,
+                string,
+                format,
+                ref args,
+            } => (scan, string, format, args),
+            _ => unreachable!(),
+        };
+        let (format_value, arg_types) =
+            self.emit_checked_format(text, args, scan, hir.span, env)?;
+
+        // The string is held in a bit vector of 8 bits per character.
+        let string_ty = self.type_of(string, env)?;
+        let width = match string_ty.get_simple_bit_vector() {
+            Some(sbv) => sbv.size,
+            None => {
+                self.emit(
+                    DiagBuilder2::error(format!(
+                        "`{}` cannot hold a string; has type `{}`",
+                        self.span(string).extract(),
+                        string_ty
+                    ))
+                    .span(self.span(string))
+                    .add_note("Only bit vectors are supported as strings"),
+                );
+                return Err(Error::Reported);
+            }
+        };
+
+        if scan {
+            // Read the arguments from the string.
+            let input = self.emit_rvalue(string, env)?;
+            let mut sig = llhd::ir::Signature::new();
+            sig.add_input(self.llhd_type(format_value));
+            sig.add_input(llhd::int_ty(width));
+            sig.set_return_type(self.emit_scan_type(&arg_types)?);
+            let ext_unit = self
+                .builder
+                .add_extern(llhd::ir::UnitName::Global("moore.sscanf".to_string()), sig);
+            let call = self.builder.ins().call(ext_unit, vec![format_value, input]);
+            let result = self.builder.unit().inst_result(call);
+            self.emit_scan_results(result, args, None, env)
+        } else {
+            // Format the arguments into the string.
+            let mut sig = llhd::ir::Signature::new();
+            let mut inputs = vec![format_value];
+            for &arg in args {
+                inputs.push(self.emit_rvalue(arg, env)?);
+            }
+            for &input in &inputs {
+                sig.add_input(self.llhd_type(input));
+            }
+            sig.set_return_type(llhd::int_ty(width));
+            let ext_unit = self
+                .builder
+                .add_extern(llhd::ir::UnitName::Global("moore.sformat".to_string()), sig);
+            let call = self.builder.ins().call(ext_unit, inputs);
+            let value = self.builder.unit().inst_result(call);
+            self.emit_assign_to(string, value, env)
+        }
+    }
+
+    /// Emit the code for a file I/O system task.
+    ///
+    /// Each task calls the runtime function of the same name, e.g.
+    /// `moore.fopen` for `$fopen`, which implement the descriptor semantics
+    /// described in [`hir::FileTask`]. Formats are passed as for `$sformat`,
+    /// and `moore.fscanf` returns its results like `moore.sscanf`.
+    fn emit_file_task(&mut self, hir: &hir::Stmt, env: ParamEnv) -> Result<()> {
+        let task = match hir.kind {
+            hir::StmtKind::File(ref task) => task,
+            _ => unreachable!(),
+        };
+        let mut sig = llhd::ir::Signature::new();
+        let mut inputs = vec![];
+        let (name, scanned) = match *task {
+            hir::FileTask::Open { file, mode, .. } => {
+                inputs.push(self.emit_cstring(&file.value.as_str()));
+                let mode = mode.map(|mode| mode.value.as_str().to_string());
+                inputs.push(self.emit_cstring(mode.as_ref().map(|m| m.as_str()).unwrap_or("")));
+                sig.set_return_type(llhd::int_ty(32));
+                ("moore.fopen", None)
+            }
+            hir::FileTask::Write {
+                fd,
+                format,
+                ref args,
+            } => {
+                inputs.push(self.emit_descriptor(fd, env)?);
+                let (format_value, _) =
+                    self.emit_checked_format(format, args, false, hir.span, env)?;
+                inputs.push(format_value);
+                for &arg in args {
+                    inputs.push(self.emit_rvalue(arg, env)?);
+                }
+                ("moore.fwrite", None)
+            }
+            hir::FileTask::Scan {
+                fd,
+                format,
+                ref args,
+                count,
+            } => {
+                inputs.push(self.emit_descriptor(fd, env)?);
+                let (format_value, arg_types) =
+                    self.emit_checked_format(format, args, true, hir.span, env)?;
+                inputs.push(format_value);
+                sig.set_return_type(self.emit_scan_type(&arg_types)?);
+                ("moore.fscanf", Some((args, count)))
+            }
+            hir::FileTask::Close { fd } => {
+                inputs.push(self.emit_descriptor(fd, env)?);
+                ("moore.fclose", None)
+            }
+        };
+        for &input in &inputs {
+            sig.add_input(self.llhd_type(input));
+        }
+        let ext_unit = self
+            .builder
+            .add_extern(llhd::ir::UnitName::Global(name.to_string()), sig);
+        let call = self.builder.ins().call(ext_unit, inputs);
+        let result = self.builder.unit().get_inst_result(call);
+        match (task, scanned, result) {
+            (&hir::FileTask::Open { target, .. }, _, Some(result)) => {
+                self.emit_assign_to(target, result, env)
+            }
+            (_, Some((args, count)), Some(result)) => {
+                self.emit_scan_results(result, args, count, env)
+            }
+            _ => Ok(()),
+        }
+    }
+
+    /// Check a format against its arguments and emit it in canonical form.
+    ///
+    /// The format is passed to the runtime as a NUL-terminated byte array.
+    /// Returns the format together with the types of the arguments.
+    fn emit_checked_format(
+        &mut self,
+        text: Spanned<Name>,
+        args: &[NodeId],
+        scan: bool,
+        span: Span,
+        env: ParamEnv,
+    ) -> Result<(llhd::ir::Value, Vec<&'gcx UnpackedType<'gcx>>)> {
+        let items = match format::parse(&text.value.as_str()) {
+            Ok(x) => x,
+            Err(msg) => {
+                self.emit(DiagBuilder2::error(msg).span(text.span));
+                return Err(Error::Reported);
+            }
+        };
+        let specs = format::arg_specs(&items);
+        if specs.len() != args.len() {
+            self.emit(
+                DiagBuilder2::error(format!(
+                    "format `{}` expects {} argument(s), but {} given",
+                    text.value,
+                    specs.len(),
+                    args.len()
+                ))
+                .span(span),
+            );
+            return Err(Error::Reported);
+        }
+        let mut arg_types = vec![];
+        for (spec, &arg) in specs.iter().zip(args) {
+            let ty = self.type_of(arg, env)?;
+            if !spec.conv.accepts(format_arg_kind(ty), scan) {
+                self.emit(
+                    DiagBuilder2::error(format!(
+                        "`{}` expects {}, but `{}` has type `{}`",
+                        spec,
+                        spec.conv.expected(scan),
+                        self.span(arg).extract(),
+                        ty
+                    ))
+                    .span(self.span(arg)),
+                );
+                return Err(Error::Reported);
+            }
+            arg_types.push(ty);
+        }
+        Ok((self.emit_cstring(&format::render(&items)), arg_types))
+    }
+
+    /// Emit a string as a NUL-terminated byte array.
+    fn emit_cstring(&mut self, text: &str) -> llhd::ir::Value {
+        let mut bytes = vec![];
+        for byte in text.bytes().chain(Some(0)) {
+            bytes.push(self.builder.ins().const_int((8, byte as usize)));
+        }
+        self.builder.ins().array(bytes)
+    }
+
+    /// Emit the value of a file descriptor, which must be 32 bits wide.
+    fn emit_descriptor(&mut self, fd: NodeId, env: ParamEnv) -> Result<llhd::ir::Value> {
+        let ty = self.type_of(fd, env)?;
+        if format_arg_kind(ty) != format::ArgKind::Integral || ty.get_bit_size() != Some(32) {
+            self.emit(
+                DiagBuilder2::error(format!(
+                    "`{}` is not a file descriptor; has type `{}`",
+                    self.span(fd).extract(),
+                    ty
+                ))
+                .span(self.span(fd))
+                .add_note("File descriptors are 32 bit integers, e.g. `integer` or `int`"),
+            );
+            return Err(Error::Reported);
+        }
+        self.emit_rvalue(fd, env)
+    }
+
+    /// Map the result of a scanning runtime function to an LLHD type.
+    ///
+    /// The result is a struct of the number of converted arguments, followed
+    /// by the values of the arguments.
+    fn emit_scan_type(&mut self, arg_types: &[&'gcx UnpackedType<'gcx>]) -> Result<llhd::Type> {
+        let mut fields = vec![llhd::int_ty(32)];
+        for &ty in arg_types {
+            fields.push(self.emit_type(ty)?);
+        }
+        Ok(llhd::struct_ty(fields))
+    }
+
+    /// Assign the result of a scanning runtime function to the arguments, and
+    /// the number of converted arguments to `count`.
+    fn emit_scan_results(
+        &mut self,
+        result: llhd::ir::Value,
+        args: &[NodeId],
+        count: Option<NodeId>,
+        env: ParamEnv,
+    ) -> Result<()> {
+        for (index, &arg) in args.iter().enumerate() {
+            let value = self.builder.ins().ext_field(result, index + 1);
+            self.emit_assign_to(arg, value, env)?;
+        }
+        if let Some(count) = count {
+            let value = self.builder.ins().ext_field(result, 0);
+            self.emit_assign_to(count, value, env)?;
+        }
+        Ok(())
+    }
+
+    /// Assign an LLHD value to an lvalue expression with a blocking
+    /// assignment.
+    fn emit_assign_to(&mut self, lhs: NodeId, value: llhd::ir::Value, env: ParamEnv) -> Result<()> {
+        let lhs_mir = self.mir_lvalue(lhs, env);
+        if lhs_mir.is_error() {
+            return Err(Error::Reported);
+        }
+        let lhs_lv = self.emit_mir_lvalue(lhs_mir)?;
+        self.emit_blocking_assign_llhd(lhs_lv, value)
+    }
+
     /// Emit the code for a variable declaration statement, given its HIR.
     fn emit_stmt_var_decl(
         &mut self,
@@ -2427,9 +3568,8 @@ where
             let lty = self.llhd_type(lvalue);
             match *lty {
                 llhd::SignalType(..) => {
-                    let one_epsilon = llhd::value::TimeValue::new(num::zero(), 0, 1);
-                    let one_epsilon = self.builder.ins().const_time(one_epsilon);
-                    self.builder.ins().drv(lvalue, rvalue, one_epsilon);
+                    let delay = self.emit_update_delay();
+                    self.builder.ins().drv(lvalue, rvalue, delay);
                     // // Emit a wait statement to allow for the assignment to take
                     // // effect.
                     // let blk = self.add_nameless_block();
@@ -2541,8 +3681,10 @@ fn emit_port_details<'gcx>(cx: &impl Context<'gcx>, hir: &hir::Module<'gcx>, env
 
 /// Result of emitting a module.
 pub struct EmittedModule<'a> {
-    /// The emitted LLHD unit.
-    unit: llhd::ir::UnitId,
+    /// The name of the emitted LLHD unit.
+    name: llhd::ir::UnitName,
+    /// The signature of the emitted LLHD unit.
+    sig: llhd::ir::Signature,
     /// The module's ports.
     ports: ModuleIntf<'a>,
 }
@@ -2555,6 +3697,9 @@ pub struct EmittedProcedure {
     inputs: Vec<AccessedNode>,
     /// The nodes used as lvalues.
     outputs: Vec<AccessedNode>,
+    /// Whether the finish signal is connected as an output (`Some(true)`) or
+    /// an input (`Some(false)`).
+    finish: Option<bool>,
 }
 
 /// A module's port interface.
@@ -2612,3 +3757,17 @@ pub struct IntfSignal<'a> {
     /// The expression assigned as default to the signal.
     pub default: Option<NodeId>,
 }
+
+/// Classify a type for checking it against a format specifier.
+fn format_arg_kind(ty: &UnpackedType) -> format::ArgKind {
+    let ty = ty.resolve_full();
+    if !ty.dims.is_empty() {
+        return format::ArgKind::Other;
+    }
+    match ty.core {
+        ty::UnpackedCore::Packed(..) => format::ArgKind::Integral,
+        ty::UnpackedCore::Real(..) => format::ArgKind::Real,
+        ty::UnpackedCore::String => format::ArgKind::String,
+        _ => format::ArgKind::Other,
+    }
+}
@@ -0,0 +1,293 @@
+// Copyright (c) 2016-2020 Fabian Schuiki
+
+//! Binding of instances to modules as prescribed by a configuration.
+//!
+//! A configuration names the top-level cells of a design and lists rules that
+//! determine which library's version of a module an instance is bound to. The
+//! rules are applied while walking the design hierarchy from the top:
+//!
+//! - An `instance` rule applies to the instance with the given hierarchical
+//!   path, and takes precedence over all other rules.
+//! - A `cell` rule applies to all instances of a cell, optionally only those
+//!   which would otherwise be bound to the cell in a specific library.
+//! - A `default liblist` rule lists the libraries searched for all other
+//!   instances.
+//!
+//! A `use` clause binds an instance to a specific cell, while a `liblist`
+//! clause searches the listed libraries in order. The libraries listed for an
+//! instance are searched for all instances below it as well. Instances which
+//! are not covered by any rule are bound to the cell in the library of the
+//! module containing them.
+//!
+//! Bindings are recorded per instantiation in the syntax tree. A module that
+//! is instantiated at several places in the hierarchy must be bound the same
+//! way at all of them.
+
+use crate::crate_prelude::*;
+use std::collections::{HashMap, HashSet};
+
+/// The binding of a design described by a configuration.
+#[derive(Debug)]
+pub struct ConfigBinding<'a> {
+    /// The top-level modules of the design.
+    pub tops: Vec<&'a ast::Module<'a>>,
+    /// The module each instantiation is bound to, by the instantiation's id.
+    pub insts: HashMap<NodeId, &'a ast::Module<'a>>,
+}
+
+/// Bind the instances in the design described by a configuration.
+pub(crate) fn bind_config<'a>(
+    cx: &GlobalContext<'a>,
+    config: &'a ast::Config<'a>,
+) -> Result<ConfigBinding<'a>> {
+    let own_lib = cx.library_of(config.id());
+    let mut binder = Binder {
+        cx,
+        config,
+        insts: HashMap::new(),
+        visited: HashSet::new(),
+        failed: false,
+    };
+
+    // Resolve the top-level cells.
+    let mut tops = vec![];
+    for cell in &config.design {
+        let lib = cell.lib.map(|l| l.value).or(own_lib);
+        match lib.and_then(|lib| cx.find_library_module(lib, cell.cell.value)) {
+            Some(module) => tops.push(module),
+            None => {
+                cx.emit(
+                    DiagBuilder2::error(format!(
+                        "`{}` is not a module in library `{}`",
+                        cell.cell.value,
+                        lib.map(|l| l.to_string()).unwrap_or_default()
+                    ))
+                    .span(cell.cell.span),
+                );
+                binder.failed = true;
+            }
+        }
+    }
+
+    // Walk the design hierarchy and bind the instances.
+    for &top in &tops {
+        binder.bind_module(top, &[top.name.value], None);
+    }
+    if binder.failed {
+        return Err(Error::Reported);
+    }
+    Ok(ConfigBinding {
+        tops,
+        insts: binder.insts,
+    })
+}
+
+struct Binder<'a, 'c> {
+    cx: &'c GlobalContext<'a>,
+    config: &'a ast::Config<'a>,
+    insts: HashMap<NodeId, &'a ast::Module<'a>>,
+    /// The modules already walked with a list of libraries, for subtrees not
+    /// affected by any `instance` rule.
+    visited: HashSet<(NodeId, Option<Vec<Name>>)>,
+    failed: bool,
+}
+
+impl<'a, 'c> Binder<'a, 'c> {
+    /// Bind the instances in a module at a position in the hierarchy.
+    fn bind_module(
+        &mut self,
+        module: &'a ast::Module<'a>,
+        path: &[Name],
+        liblist: Option<&[Name]>,
+    ) {
+        // The bindings below a module only depend on its position in the
+        // hierarchy if an `instance` rule applies below it.
+        let has_inst_rules = self.config.rules.iter().any(|rule| match rule.clause {
+            ast::ConfigClause::Instance(ref p) => {
+                p.len() > path.len() && p.iter().zip(path).all(|(a, &b)| a.value == b)
+            }
+            _ => false,
+        });
+        if !has_inst_rules
+            && !self
+                .visited
+                .insert((module.id(), liblist.map(|l| l.to_vec())))
+        {
+            return;
+        }
+
+        let parent_lib = self.cx.library_of(module.id());
+        let mut insts = vec![];
+        collect_insts(&module.items, &mut insts);
+        for inst in insts {
+            for name in &inst.names {
+                let mut inst_path = path.to_vec();
+                inst_path.push(name.name.value);
+                let (target, inherited) =
+                    match self.bind_inst(inst, &inst_path, parent_lib, liblist) {
+                        Some(x) => x,
+                        None => continue,
+                    };
+                match self.insts.get(&inst.id()) {
+                    Some(&existing) if existing.id() != target.id() => {
+                        self.cx.emit(
+                            DiagBuilder2::error(format!(
+                                "`{}` bound to different modules at different places in the \
+                                 design",
+                                inst.target.value
+                            ))
+                            .span(name.name.span)
+                            .add_note(format!(
+                                "Bound to `{}` from library `{}` and from library `{}`",
+                                inst.target.value,
+                                self.lib_name(existing),
+                                self.lib_name(target)
+                            )),
+                        );
+                        self.failed = true;
+                        continue;
+                    }
+                    _ => (),
+                }
+                self.insts.insert(inst.id(), target);
+                self.bind_module(target, &inst_path, inherited.as_ref().map(|l| l.as_slice()));
+            }
+        }
+    }
+
+    /// Determine the module an instance is bound to.
+    ///
+    /// Returns the module together with the libraries to search for the
+    /// instances below it, or `None` if the configuration does not determine
+    /// the binding.
+    fn bind_inst(
+        &mut self,
+        inst: &'a ast::Inst<'a>,
+        path: &[Name],
+        parent_lib: Option<Name>,
+        liblist: Option<&[Name]>,
+    ) -> Option<(&'a ast::Module<'a>, Option<Vec<Name>>)> {
+        let config = self.config;
+        let cell = inst.target.value;
+        let default_liblist =
+            config
+                .rules
+                .iter()
+                .find_map(|rule| match (&rule.clause, &rule.action) {
+                    (ast::ConfigClause::Default, ast::ConfigAction::Liblist(libs)) => Some(libs),
+                    _ => None,
+                });
+        let default_libs: Vec<Name> = match liblist {
+            Some(libs) => libs.to_vec(),
+            None => match default_liblist {
+                Some(libs) => libs.iter().map(|l| l.value).collect(),
+                None => parent_lib.into_iter().collect(),
+            },
+        };
+        let default_lib = default_libs
+            .iter()
+            .cloned()
+            .find(|&lib| self.cx.find_library_module(lib, cell).is_some());
+
+        // Find the rule that applies to the instance.
+        let rule = config
+            .rules
+            .iter()
+            .find(|rule| match rule.clause {
+                ast::ConfigClause::Instance(ref p) => {
+                    p.len() == path.len() && p.iter().zip(path).all(|(a, &b)| a.value == b)
+                }
+                _ => false,
+            })
+            .or_else(|| {
+                config.rules.iter().find(|rule| match rule.clause {
+                    ast::ConfigClause::Cell(ref c) => {
+                        c.cell.value == cell
+                            && c.lib.map(|l| Some(l.value) == default_lib).unwrap_or(true)
+                    }
+                    _ => false,
+                })
+            });
+
+        match rule.map(|r| (r, &r.action)) {
+            Some((rule, &ast::ConfigAction::Use(ref used, is_config))) => {
+                if is_config {
+                    self.cx.emit(
+                        DiagBuilder2::error("unsupported: hierarchical configurations")
+                            .span(rule.span),
+                    );
+                    self.failed = true;
+                    return None;
+                }
+                let lib = used.lib.map(|l| l.value).or(parent_lib);
+                match lib.and_then(|lib| self.cx.find_library_module(lib, used.cell.value)) {
+                    Some(module) => Some((module, liblist.map(|l| l.to_vec()))),
+                    None => {
+                        self.cx.emit(
+                            DiagBuilder2::error(format!(
+                                "`{}` is not a module in library `{}`",
+                                used.cell.value,
+                                lib.map(|l| l.to_string()).unwrap_or_default()
+                            ))
+                            .span(used.cell.span),
+                        );
+                        self.failed = true;
+                        None
+                    }
+                }
+            }
+            Some((rule, &ast::ConfigAction::Liblist(ref libs))) => {
+                let libs: Vec<Name> = libs.iter().map(|l| l.value).collect();
+                match libs
+                    .iter()
+                    .find_map(|&lib| self.cx.find_library_module(lib, cell))
+                {
+                    Some(module) => Some((module, Some(libs))),
+                    None => {
+                        self.cx.emit(
+                            DiagBuilder2::error(format!(
+                                "`{}` is not a module in any of the listed libraries",
+                                cell
+                            ))
+                            .span(inst.target.span)
+                            .add_note("The configuration rule applying to it is here:")
+                            .span(rule.span),
+                        );
+                        self.failed = true;
+                        None
+                    }
+                }
+            }
+            None => default_lib
+                .and_then(|lib| self.cx.find_library_module(lib, cell))
+                .map(|module| (module, liblist.map(|l| l.to_vec()))),
+        }
+    }
+
+    /// Get the name of the library a module was compiled into.
+    fn lib_name(&self, module: &ast::Module) -> String {
+        self.cx
+            .library_of(module.id())
+            .map(|l| l.to_string())
+            .unwrap_or_default()
+    }
+}
+
+/// Collect the instantiations in a list of items, including those in
+/// generate blocks.
+fn collect_insts<'a>(items: &'a [ast::Item<'a>], insts: &mut Vec<&'a ast::Inst<'a>>) {
+    for item in items {
+        match item.data {
+            ast::ItemData::Inst(ref x) => insts.push(x),
+            ast::ItemData::GenerateRegion(_, ref items) => collect_insts(items, insts),
+            ast::ItemData::GenerateFor(ref x) => collect_insts(&x.block.items, insts),
+            ast::ItemData::GenerateIf(ref x) => {
+                collect_insts(&x.main_block.items, insts);
+                if let Some(ref block) = x.else_block {
+                    collect_insts(&block.items, insts);
+                }
+            }
+            _ => (),
+        }
+    }
+}
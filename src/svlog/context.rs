@@ -25,7 +25,10 @@ use crate::salsa; // TODO(fschuiki): Remove this once salsa is regular dep again
 use crate::{
     ast::{self, Visitor},
     ast_map::{AstMap, AstNode},
-    common::{arenas::Alloc, arenas::TypedArena, Session},
+    common::{
+        arenas::Alloc, arenas::TypedArena, name::llhd_name, score::CancelToken, source::Source,
+        Session,
+    },
     hir::{self, HirNode},
     port_list::PortList,
     resolver::{Scope, StructDef},
@@ -46,6 +49,10 @@ pub struct GlobalContext<'gcx> {
     pub sess: &'gcx Session,
     /// The arena that owns all references.
     pub arena: &'gcx GlobalArenas<'gcx>,
+    /// The token through which a running elaboration is cancelled. Code
+    /// generation checks it before emitting each module, process, and
+    /// statement.
+    pub cancel: CancelToken,
     /// The underlying runtime for the query system.
     runtime: salsa::Runtime<GlobalContext<'gcx>>,
     /// The underlying storage for the new query system.
@@ -62,8 +69,20 @@ pub struct GlobalContext<'gcx> {
     interfaces: RefCell<HashMap<Name, NodeId>>,
     /// The global imports in the AST.
     imports: RefCell<Vec<NodeId>>,
+    /// The library each module, interface, and configuration was compiled
+    /// into.
+    libraries: RefCell<HashMap<NodeId, Name>>,
+    /// The modules in the AST, by library and name.
+    library_modules: RefCell<HashMap<(Name, Name), &'gcx ast::Module<'gcx>>>,
+    /// The configurations in the AST.
+    configs: RefCell<HashMap<Name, &'gcx ast::Config<'gcx>>>,
+    /// The modules instances are bound to by the active configuration.
+    config_bindings: RefCell<HashMap<NodeId, &'gcx ast::Module<'gcx>>>,
     /// A mapping from node ids to spans for diagnostics.
     node_id_to_span: RefCell<HashMap<NodeId, Span>>,
+    /// The attribute instances in the AST, by the first token of the node
+    /// they are attached to.
+    attributes: RefCell<HashMap<(Source, usize), Vec<&'gcx ast::AttrInst>>>,
     /// The tables.
     tables: GlobalTables<'gcx>,
 }
@@ -74,6 +93,7 @@ impl<'gcx> GlobalContext<'gcx> {
         GlobalContext {
             sess,
             arena,
+            cancel: CancelToken::new(),
             runtime: Default::default(),
             storage: Default::default(),
             ast_map: Default::default(),
@@ -82,16 +102,22 @@ impl<'gcx> GlobalContext<'gcx> {
             packages: Default::default(),
             interfaces: Default::default(),
             imports: Default::default(),
+            libraries: Default::default(),
+            library_modules: Default::default(),
+            configs: Default::default(),
+            config_bindings: Default::default(),
             node_id_to_span: Default::default(),
+            attributes: Default::default(),
             tables: Default::default(),
         }
     }
 
     /// Add an AST root to the context for processing.
     ///
-    /// Use the `find_global_item` function afterwards to look up the id of
-    /// modules that were added.
-    pub fn add_root(&self, root: &'gcx ast::Root<'gcx>) {
+    /// The modules, interfaces, and configurations in the root are compiled
+    /// into library `lib`. Use the `find_global_item` function afterwards to
+    /// look up the id of modules that were added.
+    pub fn add_root(&self, lib: Name, root: &'gcx ast::Root<'gcx>) {
         debug!("Linking nodes");
         let mut index = 0;
         root.link(None, &mut index);
@@ -109,6 +135,17 @@ impl<'gcx> GlobalContext<'gcx> {
         debug!("Checking names");
         self.nameck(root);
 
+        // Keep track of the attribute instances.
+        for file in &root.files {
+            let mut attributes = self.attributes.borrow_mut();
+            for inst in &file.attrs {
+                attributes
+                    .entry((inst.target.source, inst.target.begin))
+                    .or_insert_with(Vec::new)
+                    .push(inst);
+            }
+        }
+
         // Keep track of some names for now.
         for file in &root.files {
             for item in &file.items {
@@ -116,6 +153,10 @@ impl<'gcx> GlobalContext<'gcx> {
                     ast::ItemData::ModuleDecl(ref n) => {
                         let id = self.map_ast(AstNode::Module(n));
                         self.modules.borrow_mut().insert(n.name.value, id);
+                        self.libraries.borrow_mut().insert(id, lib);
+                        self.library_modules
+                            .borrow_mut()
+                            .insert((lib, n.name.value), n);
                     }
                     ast::ItemData::PackageDecl(ref n) => {
                         let id = self.map_ast(AstNode::Package(n));
@@ -124,6 +165,11 @@ impl<'gcx> GlobalContext<'gcx> {
                     ast::ItemData::InterfaceDecl(ref n) => {
                         let id = self.map_ast(AstNode::Interface(n));
                         self.interfaces.borrow_mut().insert(n.name.value, id);
+                        self.libraries.borrow_mut().insert(id, lib);
+                    }
+                    ast::ItemData::ConfigDecl(ref n) => {
+                        self.configs.borrow_mut().insert(n.name.value, n);
+                        self.libraries.borrow_mut().insert(n.id(), lib);
                     }
                     ast::ItemData::ImportDecl(ref n) => {
                         for item in &n.items {
@@ -140,8 +186,8 @@ impl<'gcx> GlobalContext<'gcx> {
     }
 
     /// Add an AST root with a series of source files to the context for
-    /// processing.
-    pub fn add_files(&self, files: impl Iterator<Item = &'gcx ast::SourceFile<'gcx>>) {
+    /// processing, compiling them into library `lib`.
+    pub fn add_files(&self, lib: Name, files: impl Iterator<Item = &'gcx ast::SourceFile<'gcx>>) {
         let root = ast::Root::new(
             moore_common::source::INVALID_SPAN,
             ast::RootData {
@@ -149,7 +195,7 @@ impl<'gcx> GlobalContext<'gcx> {
             },
         );
         let root = self.arena.alloc_ast_root(root);
-        self.add_root(root);
+        self.add_root(lib, root);
     }
 
     /// Find a module in the AST.
@@ -162,11 +208,75 @@ impl<'gcx> GlobalContext<'gcx> {
         self.modules.borrow().clone().into_iter()
     }
 
+    /// Find a module compiled into a specific library.
+    pub fn find_library_module(&self, lib: Name, name: Name) -> Option<&'gcx ast::Module<'gcx>> {
+        self.library_modules.borrow().get(&(lib, name)).cloned()
+    }
+
+    /// Get the library a module, interface, or configuration was compiled
+    /// into.
+    pub fn library_of(&self, id: NodeId) -> Option<Name> {
+        self.libraries.borrow().get(&id).cloned()
+    }
+
+    /// Get the name of the entity generated for a module.
+    ///
+    /// Modules defined in more than one library are qualified with their
+    /// library, such as `gates.adder`, to keep the versions apart. Escaped
+    /// identifiers such as `\\cell[0] ` are escaped for LLHD.
+    pub fn module_unit_name(&self, module: &ast::Module) -> String {
+        let name = module.name.value;
+        let count = self
+            .library_modules
+            .borrow()
+            .keys()
+            .filter(|&&(_, n)| n == name)
+            .count();
+        let unit = llhd_name(&name.as_str());
+        match self.library_of(module.id()) {
+            Some(lib) if count > 1 => format!("{}.{}", lib, unit),
+            _ => unit,
+        }
+    }
+
+    /// Find a configuration in the AST.
+    pub fn find_config(&self, name: Name) -> Option<&'gcx ast::Config<'gcx>> {
+        self.configs.borrow().get(&name).cloned()
+    }
+
+    /// Make a configuration the active one.
+    ///
+    /// Binds the instances in the design described by the configuration to
+    /// modules as its rules prescribe. Must be called before any of the
+    /// design is elaborated. Returns the top-level modules of the design.
+    pub fn set_config(&self, config: &'gcx ast::Config<'gcx>) -> Result<Vec<NodeId>> {
+        let binding = crate::config::bind_config(self, config)?;
+        *self.config_bindings.borrow_mut() = binding.insts;
+        Ok(binding.tops.iter().map(|m| m.id()).collect())
+    }
+
+    /// Get the module an instantiation is bound to by the active
+    /// configuration, if any.
+    pub fn config_binding(&self, inst: &ast::Inst) -> Option<&'gcx ast::Module<'gcx>> {
+        self.config_bindings.borrow().get(&inst.id()).cloned()
+    }
+
     /// Find a package in the AST.
     pub fn find_package(&self, name: Name) -> Option<NodeId> {
         self.packages.borrow().get(&name).cloned()
     }
 
+    /// Get the attributes attached to the node covering `span`.
+    ///
+    /// Returns the attributes of all instances preceding the node, such as
+    /// `(* keep *)` before a declaration, in source order.
+    pub fn attributes_of(&self, span: Span) -> Vec<&'gcx ast::AttrSpec> {
+        match self.attributes.borrow().get(&(span.source, span.begin)) {
+            Some(insts) => insts.iter().flat_map(|inst| inst.specs.iter()).collect(),
+            None => vec![],
+        }
+    }
+
     /// Get an iterator over all root imports in the AST.
     pub fn imports(&self) -> impl Iterator<Item = NodeId> {
         self.imports.borrow().clone().into_iter()
@@ -393,7 +503,7 @@ pub trait BaseContext<'gcx>:
             DiagBuilder2::bug(format!("{} not implemented", node.desc_full()))
                 .span(node.human_span()),
         );
-        Err(())
+        Err(Error::Reported)
     }
 
     /// Emit an internal compiler error and message that a node is not
@@ -408,7 +518,7 @@ pub trait BaseContext<'gcx>:
             ))
             .span(node.human_span()),
         );
-        Err(())
+        Err(Error::Reported)
     }
 
     /// Allocate a new node id.
@@ -640,7 +750,7 @@ pub trait BaseContext<'gcx>:
                 self.emit(
                     DiagBuilder2::error(format!("`{}` not found", name.value)).span(name.span),
                 );
-                Err(())
+                Err(Error::Reported)
             }
         }
     }
@@ -658,7 +768,7 @@ pub trait BaseContext<'gcx>:
                     ))
                     .span(name.span),
                 );
-                Err(())
+                Err(Error::Reported)
             }
         }
     }
@@ -680,14 +790,14 @@ pub trait BaseContext<'gcx>:
     fn constant_int_value_of(&self, node_id: NodeId, env: ParamEnv) -> Result<&'gcx num::BigInt> {
         match self.gcx().constant_value_of(node_id, env).kind {
             ValueKind::Int(ref x, ..) => Ok(x),
-            ValueKind::Error => Err(()),
+            ValueKind::Error => Err(Error::Reported),
             _ => {
                 let hir = self.gcx().hir_of(node_id)?;
                 self.emit(
                     DiagBuilder2::error(format!("{} is not a constant integer", hir.desc_full()))
                         .span(hir.human_span()),
                 );
-                Err(())
+                Err(Error::Reported)
             }
         }
     }
@@ -0,0 +1,405 @@
+// Copyright (c) 2016-2020 Fabian Schuiki
+
+//! Format strings as used by the `$sformat`, `$swrite`, and `$sscanf` system
+//! tasks.
+//!
+//! A format string consists of literal text and format specifiers of the form
+//! `%[-][0][width][.precision]c`, where `c` is one of the conversions listed
+//! in [`Conversion`]. A `%%` stands for a literal percent sign. Conversions
+//! are case-insensitive, such that `%H` is the same as `%h`.
+
+use num::BigInt;
+use std::fmt;
+use std::iter::Peekable;
+use std::str::CharIndices;
+
+/// An item of a format string.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Item {
+    /// Literal text, with `%%` already replaced by `%`.
+    Text(String),
+    /// A format specifier.
+    Spec(Spec),
+}
+
+/// A format specifier such as `%-8h`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Spec {
+    /// The conversion to perform.
+    pub conv: Conversion,
+    /// Whether the value is left-justified, as with `%-8d`.
+    pub left: bool,
+    /// Whether the value is padded with zeros, as with `%08d`.
+    pub zero: bool,
+    /// The minimum field width.
+    pub width: Option<usize>,
+    /// The number of fractional digits of a real value.
+    pub precision: Option<usize>,
+}
+
+/// The different conversions of a format specifier.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Conversion {
+    /// `%b`, a binary number.
+    Binary,
+    /// `%o`, an octal number.
+    Octal,
+    /// `%d`, a decimal number.
+    Decimal,
+    /// `%h` or `%x`, a hexadecimal number.
+    Hex,
+    /// `%c`, a single character.
+    Char,
+    /// `%s`, a string.
+    String,
+    /// `%e`, a real number in exponential notation.
+    Exp,
+    /// `%f`, a real number in decimal notation.
+    Float,
+    /// `%g`, the shorter of `%e` and `%f`.
+    General,
+    /// `%t`, a simulation time.
+    Time,
+    /// `%m`, the hierarchical name of the calling scope. Takes no argument.
+    Module,
+}
+
+/// The kind of an argument matched against a format specifier.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ArgKind {
+    /// A packed or integer value.
+    Integral,
+    /// A `real` or `shortreal` value.
+    Real,
+    /// A `string` value.
+    String,
+    /// Any other value, such as an unpacked array.
+    Other,
+}
+
+impl Conversion {
+    /// Look up the conversion for a specifier letter.
+    pub fn from_letter(letter: char) -> Option<Conversion> {
+        Some(match letter.to_ascii_lowercase() {
+            'b' => Conversion::Binary,
+            'o' => Conversion::Octal,
+            'd' => Conversion::Decimal,
+            'h' | 'x' => Conversion::Hex,
+            'c' => Conversion::Char,
+            's' => Conversion::String,
+            'e' => Conversion::Exp,
+            'f' => Conversion::Float,
+            'g' => Conversion::General,
+            't' => Conversion::Time,
+            'm' => Conversion::Module,
+            _ => return None,
+        })
+    }
+
+    /// The canonical specifier letter of the conversion.
+    pub fn letter(self) -> char {
+        match self {
+            Conversion::Binary => 'b',
+            Conversion::Octal => 'o',
+            Conversion::Decimal => 'd',
+            Conversion::Hex => 'h',
+            Conversion::Char => 'c',
+            Conversion::String => 's',
+            Conversion::Exp => 'e',
+            Conversion::Float => 'f',
+            Conversion::General => 'g',
+            Conversion::Time => 't',
+            Conversion::Module => 'm',
+        }
+    }
+
+    /// Check whether the conversion consumes an argument.
+    pub fn takes_arg(self) -> bool {
+        self != Conversion::Module
+    }
+
+    /// Check whether the conversion accepts an argument of a given kind.
+    ///
+    /// When formatting, integral and real values convert into each other as
+    /// in an assignment. When scanning, the argument is the target of the
+    /// conversion and must hold the value as read.
+    pub fn accepts(self, kind: ArgKind, scan: bool) -> bool {
+        match (self, kind) {
+            (Conversion::Module, _) => false,
+            (_, ArgKind::Other) => false,
+            (Conversion::String, _) => kind != ArgKind::Real,
+            (_, ArgKind::String) => false,
+            (Conversion::Exp, _) | (Conversion::Float, _) | (Conversion::General, _) => {
+                !scan || kind == ArgKind::Real
+            }
+            (Conversion::Char, _) => kind == ArgKind::Integral,
+            (Conversion::Time, _) => true,
+            _ => !scan || kind == ArgKind::Integral,
+        }
+    }
+
+    /// A description of the arguments the conversion accepts, for diagnostics.
+    pub fn expected(self, scan: bool) -> &'static str {
+        match self {
+            Conversion::Module => "no argument",
+            Conversion::String => "an integral or string argument",
+            Conversion::Exp | Conversion::Float | Conversion::General if scan => "a real argument",
+            Conversion::Char => "an integral argument",
+            _ if scan => "an integral argument",
+            _ => "an integral or real argument",
+        }
+    }
+}
+
+impl fmt::Display for Spec {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "%")?;
+        if self.left {
+            write!(f, "-")?;
+        }
+        if self.zero {
+            write!(f, "0")?;
+        }
+        if let Some(width) = self.width {
+            write!(f, "{}", width)?;
+        }
+        if let Some(precision) = self.precision {
+            write!(f, ".{}", precision)?;
+        }
+        write!(f, "{}", self.conv.letter())
+    }
+}
+
+/// Parse a format string.
+///
+/// Returns an error message mentioning the offending specifier if the format
+/// string is malformed.
+pub fn parse(text: &str) -> Result<Vec<Item>, String> {
+    let mut items = vec![];
+    let mut literal = String::new();
+    let mut chars = text.char_indices().peekable();
+    while let Some((start, c)) = chars.next() {
+        if c != '%' {
+            literal.push(c);
+            continue;
+        }
+        if let Some(&(_, '%')) = chars.peek() {
+            chars.next();
+            literal.push('%');
+            continue;
+        }
+
+        // Parse the flags, width, and precision.
+        let mut spec = Spec {
+            conv: Conversion::Decimal,
+            left: false,
+            zero: false,
+            width: None,
+            precision: None,
+        };
+        if let Some(&(_, '-')) = chars.peek() {
+            chars.next();
+            spec.left = true;
+        }
+        if let Some(&(_, '0')) = chars.peek() {
+            chars.next();
+            spec.zero = true;
+        }
+        spec.width = parse_number(&mut chars);
+        if let Some(&(_, '.')) = chars.peek() {
+            chars.next();
+            spec.precision = Some(parse_number(&mut chars).unwrap_or(0));
+        }
+
+        // Parse the conversion.
+        let (end, letter) = match chars.next() {
+            Some(x) => x,
+            None => return Err(format!("incomplete format specifier `{}`", &text[start..])),
+        };
+        spec.conv = match Conversion::from_letter(letter) {
+            Some(conv) => conv,
+            None => {
+                return Err(format!(
+                    "unknown format specifier `{}`",
+                    &text[start..end + letter.len_utf8()]
+                ))
+            }
+        };
+        if !literal.is_empty() {
+            items.push(Item::Text(std::mem::replace(&mut literal, String::new())));
+        }
+        items.push(Item::Spec(spec));
+    }
+    if !literal.is_empty() {
+        items.push(Item::Text(literal));
+    }
+    Ok(items)
+}
+
+/// Parse the decimal width or precision of a format specifier.
+fn parse_number(chars: &mut Peekable<CharIndices>) -> Option<usize> {
+    let mut value = None;
+    while let Some(digit) = chars.peek().and_then(|&(_, c)| c.to_digit(10)) {
+        value = Some(value.unwrap_or(0) * 10 + digit as usize);
+        chars.next();
+    }
+    value
+}
+
+/// Render parsed items back into a canonical format string.
+///
+/// This is the validated format passed to the runtime, with `%x` spelled as
+/// `%h` and all letters in lower case.
+pub fn render(items: &[Item]) -> String {
+    let mut text = String::new();
+    for item in items {
+        match *item {
+            Item::Text(ref literal) => text.push_str(&literal.replace('%', "%%")),
+            Item::Spec(ref spec) => text.push_str(&spec.to_string()),
+        }
+    }
+    text
+}
+
+/// Find the specifiers of parsed items which consume an argument.
+pub fn arg_specs(items: &[Item]) -> Vec<Spec> {
+    items
+        .iter()
+        .filter_map(|item| match *item {
+            Item::Spec(spec) if spec.conv.takes_arg() => Some(spec),
+            _ => None,
+        })
+        .collect()
+}
+
+/// Render parsed items with constant arguments substituted for specifiers.
+///
+/// This produces the messages of elaboration system tasks, whose arguments
+/// are known at compile time. The hierarchical name `scope` is substituted for
+/// `%m`. Real and time conversions print the integer value in decimal.
+pub fn substitute(items: &[Item], args: &[BigInt], scope: &str) -> String {
+    let mut text = String::new();
+    let mut args = args.iter();
+    for item in items {
+        let spec = match *item {
+            Item::Text(ref literal) => {
+                text.push_str(literal);
+                continue;
+            }
+            Item::Spec(spec) => spec,
+        };
+        if spec.conv == Conversion::Module {
+            text.push_str(scope);
+            continue;
+        }
+        let value = match (spec.conv, args.next()) {
+            (_, None) => spec.to_string(),
+            (Conversion::Binary, Some(v)) => v.to_str_radix(2),
+            (Conversion::Octal, Some(v)) => v.to_str_radix(8),
+            (Conversion::Hex, Some(v)) => v.to_str_radix(16),
+            (Conversion::Char, Some(v)) => (v.to_bytes_le().1[0] as char).to_string(),
+            (Conversion::String, Some(v)) => v
+                .to_bytes_be()
+                .1
+                .into_iter()
+                .filter(|&b| b != 0)
+                .map(|b| b as char)
+                .collect(),
+            (_, Some(v)) => v.to_str_radix(10),
+        };
+        let width = spec.width.unwrap_or(0);
+        if spec.left {
+            text.push_str(&format!("{:<1$}", value, width));
+        } else if spec.zero {
+            text.push_str(&format!("{:0>1$}", value, width));
+        } else {
+            text.push_str(&format!("{:>1$}", value, width));
+        }
+    }
+    text
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn spec(conv: Conversion) -> Spec {
+        Spec {
+            conv,
+            left: false,
+            zero: false,
+            width: None,
+            precision: None,
+        }
+    }
+
+    #[test]
+    fn parse_specs() {
+        assert_eq!(
+            parse("x=%-8H, 100%% %08.3f%m").unwrap(),
+            vec![
+                Item::Text("x=".to_string()),
+                Item::Spec(Spec {
+                    left: true,
+                    width: Some(8),
+                    ..spec(Conversion::Hex)
+                }),
+                Item::Text(", 100% ".to_string()),
+                Item::Spec(Spec {
+                    zero: true,
+                    width: Some(8),
+                    precision: Some(3),
+                    ..spec(Conversion::Float)
+                }),
+                Item::Spec(spec(Conversion::Module)),
+            ]
+        );
+        assert_eq!(parse("%5").unwrap_err(), "incomplete format specifier `%5`");
+        assert_eq!(
+            parse("a %q b").unwrap_err(),
+            "unknown format specifier `%q`"
+        );
+    }
+
+    #[test]
+    fn render_canonical() {
+        let items = parse("%X %% %-4b %.f").unwrap();
+        assert_eq!(render(&items), "%h %% %-4b %.0f");
+        let convs: Vec<_> = arg_specs(&parse("%m %d %s").unwrap())
+            .iter()
+            .map(|s| s.conv)
+            .collect();
+        assert_eq!(convs, vec![Conversion::Decimal, Conversion::String]);
+    }
+
+    #[test]
+    fn substitute_args() {
+        let items = parse("%m: %0d|%4h|%-4b|%04o|%c%s").unwrap();
+        let args: Vec<BigInt> = vec![
+            BigInt::from(42),
+            BigInt::from(255),
+            BigInt::from(5),
+            BigInt::from(8),
+            BigInt::from(0x41),
+            BigInt::from(0x6869),
+        ];
+        assert_eq!(
+            substitute(&items, &args, "top"),
+            "top: 42|  ff|101 |0010|Ahi"
+        );
+    }
+
+    #[test]
+    fn accepted_args() {
+        assert!(Conversion::Decimal.accepts(ArgKind::Real, false));
+        assert!(!Conversion::Decimal.accepts(ArgKind::Real, true));
+        assert!(Conversion::Float.accepts(ArgKind::Integral, false));
+        assert!(!Conversion::Float.accepts(ArgKind::Integral, true));
+        assert!(Conversion::String.accepts(ArgKind::Integral, true));
+        assert!(!Conversion::String.accepts(ArgKind::Real, false));
+        assert!(!Conversion::Hex.accepts(ArgKind::String, false));
+        assert!(!Conversion::Char.accepts(ArgKind::Real, false));
+        assert!(!Conversion::Module.accepts(ArgKind::Integral, false));
+        assert!(!Conversion::Time.accepts(ArgKind::Other, false));
+    }
+}
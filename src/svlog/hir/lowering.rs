@@ -5,6 +5,7 @@
 use crate::crate_prelude::*;
 use crate::{ast_map::AstNode, hir::HirNode};
 use bit_vec::BitVec;
+use moore_common::MessageSeverity;
 use num::BigInt;
 
 /// A hint about how a node should be lowered to HIR.
@@ -185,6 +186,28 @@ pub(crate) fn hir_of<'gcx>(cx: &impl Context<'gcx>, node_id: NodeId) -> Result<H
             };
             Ok(HirNode::Proc(cx.arena().alloc_hir(hir)))
         }
+        AstNode::Assertion(assert) => {
+            let kind = match assert.data {
+                ast::AssertionData::Concurrent(ref conc) => match *conc {
+                    ast::ConcurrentAssertion::AssertProperty(..) => hir::AssertionKind::Assert,
+                    ast::ConcurrentAssertion::AssumeProperty(..) => hir::AssertionKind::Assume,
+                    ast::ConcurrentAssertion::CoverProperty(..)
+                    | ast::ConcurrentAssertion::CoverSequence => hir::AssertionKind::Cover,
+                    ast::ConcurrentAssertion::ExpectProperty(..) => hir::AssertionKind::Expect,
+                    ast::ConcurrentAssertion::RestrictProperty(..) => hir::AssertionKind::Restrict,
+                },
+                _ => unreachable!("only concurrent assertions are mapped"),
+            };
+            let hir = hir::Assertion {
+                id: node_id,
+                span: assert.span,
+                label: assert.label.map(|(name, span)| Spanned::new(name, span)),
+                kind,
+                ast: assert,
+            };
+            Ok(HirNode::Assertion(cx.arena().alloc_hir(hir)))
+        }
+        AstNode::ElabTask(task) => lower_elab_task(cx, task, node_id),
         AstNode::Stmt(stmt) => {
             let kind = match stmt.kind {
                 ast::NullStmt => hir::StmtKind::Null,
@@ -201,6 +224,11 @@ pub(crate) fn hir_of<'gcx>(cx: &impl Context<'gcx>, node_id: NodeId) -> Result<H
                             .collect(),
                     )
                 }
+                ast::BlockingAssignStmt {
+                    ref lhs,
+                    ref rhs,
+                    op: ast::AssignOp::Identity,
+                } if is_file_task(rhs) => lower_file_task(cx, rhs, Some(lhs), node_id)?,
                 ast::BlockingAssignStmt {
                     ref lhs,
                     ref rhs,
@@ -250,6 +278,15 @@ pub(crate) fn hir_of<'gcx>(cx: &impl Context<'gcx>, node_id: NodeId) -> Result<H
                         .as_ref()
                         .map(|else_stmt| cx.map_ast_with_parent(AstNode::Stmt(else_stmt), node_id)),
                 },
+                ast::ExprStmt(ref expr) if is_readmem(expr) => lower_readmem(cx, expr, node_id)?,
+                ast::ExprStmt(ref expr) if is_format(expr) => lower_format(cx, expr, node_id)?,
+                ast::ExprStmt(ref expr) if is_file_task(expr) => {
+                    lower_file_task(cx, expr, None, node_id)?
+                }
+                ast::ExprStmt(ref expr) if is_finish(expr) => hir::StmtKind::Finish,
+                ast::ExprStmt(ref expr) if message_severity(expr).is_some() => {
+                    hir::StmtKind::Message(message_severity(expr).unwrap())
+                }
                 ast::ExprStmt(ref expr) => {
                     hir::StmtKind::Expr(cx.map_ast_with_parent(AstNode::Expr(expr), node_id))
                 }
@@ -595,6 +632,8 @@ fn lower_module_block<'gcx>(
     let mut gens = Vec::new();
     let mut params = Vec::new();
     let mut assigns = Vec::new();
+    let mut asserts = Vec::new();
+    let mut elab_tasks = Vec::new();
     for item in items {
         match item.data {
             ast::ItemData::Dummy => (),
@@ -617,6 +656,15 @@ fn lower_module_block<'gcx>(
                         .span(decl.span),
                 );
             }
+            ast::ItemData::ConfigDecl(ref decl) => {
+                cx.emit(
+                    DiagBuilder2::error(format!(
+                        "{} must be declared outside of modules and packages",
+                        decl
+                    ))
+                    .span(decl.human_span()),
+                );
+            }
             ast::ItemData::ProgramDecl(ref _decl) => {
                 // let id = cx.map_ast_with_parent(AstNode::Program(decl), next_rib);
                 // next_rib = id;
@@ -712,10 +760,42 @@ fn lower_module_block<'gcx>(
                 let id = cx.map_ast_with_parent(AstNode::SubroutineDecl(decl), next_rib);
                 next_rib = id;
             }
-            ast::ItemData::Assertion(ref assert) => {
+            ast::ItemData::Assertion(ref assert) => match assert.data {
+                ast::AssertionData::Concurrent(..) => {
+                    let id = cx.map_ast_with_parent(AstNode::Assertion(assert), next_rib);
+                    asserts.push(id);
+                }
+                _ => {
+                    cx.emit(
+                        DiagBuilder2::warning("unsupported: deferred assertion; ignored")
+                            .span(assert.span),
+                    );
+                }
+            },
+            ast::ItemData::ElabTask(ref task) => {
+                if ELAB_TASKS.contains(&&*task.name.value.as_str()) {
+                    let id = cx.map_ast_with_parent(AstNode::ElabTask(task), next_rib);
+                    elab_tasks.push(id);
+                } else {
+                    cx.emit(
+                        DiagBuilder2::warning(format!(
+                            "unsupported: elaboration system task `${}`; ignored",
+                            task.name.value
+                        ))
+                        .span(task.span),
+                    );
+                }
+            }
+            ast::ItemData::SequenceDecl(ref decl) => {
+                cx.emit(
+                    DiagBuilder2::warning("unsupported: sequence declaration; ignored")
+                        .span(decl.span),
+                );
+            }
+            ast::ItemData::PropertyDecl(ref decl) => {
                 cx.emit(
-                    DiagBuilder2::warning("unsupported: concurrent assertion; ignored")
-                        .span(assert.span),
+                    DiagBuilder2::warning("unsupported: property declaration; ignored")
+                        .span(decl.span),
                 );
             }
 
@@ -732,6 +812,8 @@ fn lower_module_block<'gcx>(
         gens,
         params,
         assigns,
+        asserts,
+        elab_tasks,
         last_rib: next_rib,
     })
 }
@@ -865,7 +947,7 @@ fn lower_type<'gcx>(
                     .span(ty.human_span())
                     .add_note("packed array dimensions can only be given as range, e.g. `[31:0]`"),
                 );
-                return Err(());
+                return Err(Error::Reported);
             }
         }
     }
@@ -909,9 +991,12 @@ fn lower_expr_inner<'gcx>(
                         .span(expr.span)
                         .add_note(format!("{}", e)),
                 );
-                return Err(());
+                return Err(Error::Reported);
             }
         },
+        ast::LiteralExpr(Lit::Number(int, Some(frac))) => {
+            hir::ExprKind::RealConst(parse_fixed_point_number(cx, expr.span, int, Some(frac))?)
+        }
         ast::LiteralExpr(Lit::UnbasedUnsized(c)) => hir::ExprKind::UnsizedConst(c),
 
         ast::LiteralExpr(Lit::BasedInteger(maybe_size, signed, base, value)) => {
@@ -938,7 +1023,7 @@ fn lower_expr_inner<'gcx>(
                                 .span(expr.span)
                                 .add_note("valid bases are `b`, `o`, `d`, and `h`"),
                         );
-                        return Err(());
+                        return Err(Error::Reported);
                     }
                 },
             );
@@ -949,11 +1034,12 @@ fn lower_expr_inner<'gcx>(
                         DiagBuilder2::error(format!("`{}` is not a valid integer literal", value))
                             .span(expr.span),
                     );
-                    return Err(());
+                    return Err(Error::Reported);
                 }
             };
 
-            // Parse the size and verify the number fits.
+            // Parse the size and verify the number fits. Unsized literals are
+            // at least 32 bits wide.
             let size_needed = parsed.bits();
             let size = match maybe_size {
                 Some(size) => match size.as_str().parse() {
@@ -964,10 +1050,10 @@ fn lower_expr_inner<'gcx>(
                                 .span(expr.span)
                                 .add_note(format!("{}", e)),
                         );
-                        return Err(());
+                        return Err(Error::Reported);
                     }
                 },
-                None => size_needed,
+                None => std::cmp::max(size_needed, 32),
             };
             if size_needed > size {
                 cx.emit(
@@ -982,29 +1068,20 @@ fn lower_expr_inner<'gcx>(
             }
 
             // Identify the special bits (x and z) in the input.
-            // TODO(fschuiki): Decimal literals are not handled properly.
-            let bit_iter = value_str.chars().flat_map(|c| {
-                std::iter::repeat(c).take(match base {
-                    'h' => 4,
-                    'o' => 3,
-                    'b' => 1,
-                    _ => 0,
-                })
-            });
-            let special_bits: BitVec = bit_iter
-                .clone()
-                .map(|c| match c {
-                    'x' | 'X' | 'z' | 'Z' | '?' => true,
-                    _ => false,
-                })
-                .collect();
-            let x_bits: BitVec = bit_iter
-                .clone()
-                .map(|c| match c {
-                    'x' | 'X' => true,
-                    _ => false,
-                })
-                .collect();
+            let (special_bits, x_bits) = match literal_special_bits(value_str, base, size) {
+                Some(bits) => bits,
+                None => {
+                    cx.emit(
+                        DiagBuilder2::error(format!("`{}` is not a valid decimal literal", value))
+                            .span(expr.span)
+                            .add_note(
+                                "`x` and `z` may only appear as the single digit of a decimal \
+                                 literal",
+                            ),
+                    );
+                    return Err(Error::Reported);
+                }
+            };
 
             // Assemble the HIR node.
             hir::ExprKind::IntConst {
@@ -1071,7 +1148,7 @@ fn lower_expr_inner<'gcx>(
                         ))
                         .span(expr.span()),
                     );
-                    return Err(());
+                    return Err(Error::Reported);
                 }
             },
             cx.map_ast_with_parent(AstNode::Expr(arg), node_id),
@@ -1119,7 +1196,7 @@ fn lower_expr_inner<'gcx>(
                             .span(expr.span()),
                     );
                     error!("Offending operator is {:?}", op);
-                    return Err(());
+                    return Err(Error::Reported);
                 }
             },
             cx.map_ast_with_parent(AstNode::Expr(lhs), node_id),
@@ -1161,7 +1238,7 @@ fn lower_expr_inner<'gcx>(
                                 DiagBuilder2::error(format!("`{}` takes one argument", ident))
                                     .span(expr.human_span()),
                             );
-                            return Err(());
+                            return Err(Error::Reported);
                         }
                     })
                 };
@@ -1169,6 +1246,93 @@ fn lower_expr_inner<'gcx>(
                     "clog2" => hir::BuiltinCall::Clog2(map_unary()?),
                     "signed" => hir::BuiltinCall::Signed(map_unary()?),
                     "unsigned" => hir::BuiltinCall::Unsigned(map_unary()?),
+                    name if hir::MathFn::from_name(name).is_some() => {
+                        let func = hir::MathFn::from_name(name).unwrap();
+                        let args: Vec<_> = args.iter().flat_map(|arg| arg.expr.as_ref()).collect();
+                        if args.len() != func.arity() {
+                            cx.emit(
+                                DiagBuilder2::error(format!(
+                                    "`{}` takes {} argument{}",
+                                    ident,
+                                    if func.arity() == 1 { "one" } else { "two" },
+                                    if func.arity() == 1 { "" } else { "s" },
+                                ))
+                                .span(expr.human_span()),
+                            );
+                            return Err(Error::Reported);
+                        }
+                        let mut args = args
+                            .into_iter()
+                            .map(|arg| cx.map_ast_with_parent(AstNode::Expr(arg), node_id));
+                        hir::BuiltinCall::Math(func, args.next().unwrap(), args.next())
+                    }
+                    name if hir::RandomFn::from_name(name).is_some() => {
+                        let func = hir::RandomFn::from_name(name).unwrap();
+                        let args: Vec<_> = args.iter().flat_map(|arg| arg.expr.as_ref()).collect();
+                        let (min, max) = func.arity();
+                        if func == hir::RandomFn::Random && !args.is_empty() {
+                            cx.emit(
+                                DiagBuilder2::error(format!(
+                                    "`{}` with a seed variable not supported",
+                                    ident
+                                ))
+                                .span(expr.human_span())
+                                .add_note("Use `--seed` to seed the simulation run instead"),
+                            );
+                            return Err(Error::Reported);
+                        }
+                        if args.len() < min || args.len() > max {
+                            cx.emit(
+                                DiagBuilder2::error(format!(
+                                    "`{}` takes {} to {} arguments",
+                                    ident, min, max
+                                ))
+                                .span(expr.human_span()),
+                            );
+                            return Err(Error::Reported);
+                        }
+                        let mut args = args
+                            .into_iter()
+                            .map(|arg| cx.map_ast_with_parent(AstNode::Expr(arg), node_id));
+                        hir::BuiltinCall::Random(func, args.next(), args.next())
+                    }
+                    "test$plusargs" => match args.as_slice() {
+                        [ast::CallArg {
+                            expr: Some(ref string),
+                            ..
+                        }] => hir::BuiltinCall::TestPlusargs(lower_string_literal(
+                            cx, ident, string, "argument",
+                        )?),
+                        _ => {
+                            cx.emit(
+                                DiagBuilder2::error(format!("`${}` takes a string", ident))
+                                    .span(expr.human_span()),
+                            );
+                            return Err(Error::Reported);
+                        }
+                    },
+                    "value$plusargs" => match args.as_slice() {
+                        [ast::CallArg {
+                            expr: Some(ref format),
+                            ..
+                        }, ast::CallArg {
+                            expr: Some(ref target),
+                            ..
+                        }] => hir::BuiltinCall::ValuePlusargs(
+                            lower_string_literal(cx, ident, format, "format")?,
+                            cx.map_ast_with_parent(AstNode::Expr(target), node_id),
+                        ),
+                        _ => {
+                            cx.emit(
+                                DiagBuilder2::error(format!(
+                                    "`${}` takes a format string and the variable to read into",
+                                    ident
+                                ))
+                                .span(expr.human_span()),
+                            );
+                            return Err(Error::Reported);
+                        }
+                    },
                     _ => {
                         cx.emit(
                             DiagBuilder2::warning(format!("`${}` not supported; ignored", ident))
@@ -1197,7 +1361,7 @@ fn lower_expr_inner<'gcx>(
                     ))
                     .span(expr.human_span()),
                 );
-                return Err(());
+                return Err(Error::Reported);
             }
         },
         ast::TernaryExpr {
@@ -1215,7 +1379,7 @@ fn lower_expr_inner<'gcx>(
         ),
         ast::PatternExpr(ref fields) if fields.is_empty() => {
             cx.emit(DiagBuilder2::error("pattern must have at least one field").span(expr.span()));
-            return Err(());
+            return Err(Error::Reported);
         }
         ast::PatternExpr(ref fields) => {
             let deciding_span = fields[0].span;
@@ -1406,6 +1570,61 @@ fn lower_expr_inner<'gcx>(
     })
 }
 
+/// Determine the `x` and `z` bits of a based integer literal.
+///
+/// Returns the masks of special bits and `x` bits, most significant bit first
+/// and `size` bits long. Following IEEE 1800-2017 5.7.1, the digits are
+/// extended to the left with `x` or `z` if the leftmost digit is one, and with
+/// zeros otherwise. A decimal literal may only contain `x` or `z` as its single
+/// digit, in which case all bits are special; `None` is returned otherwise.
+fn literal_special_bits(value: &str, base: char, size: usize) -> Option<(BitVec, BitVec)> {
+    let is_special = |c| match c {
+        'x' | 'X' | 'z' | 'Z' | '?' => true,
+        _ => false,
+    };
+    let is_x = |c| c == 'x' || c == 'X';
+    let digit_bits = match base {
+        'h' => 4,
+        'o' => 3,
+        'b' => 1,
+        _ => {
+            let mut chars = value.chars();
+            return match (chars.next(), chars.next()) {
+                (Some(c), None) if is_special(c) => Some((
+                    BitVec::from_elem(size, true),
+                    BitVec::from_elem(size, is_x(c)),
+                )),
+                _ if value.chars().any(is_special) => None,
+                _ => Some((
+                    BitVec::from_elem(size, false),
+                    BitVec::from_elem(size, false),
+                )),
+            };
+        }
+    };
+    let digits: Vec<char> = value
+        .chars()
+        .flat_map(|c| std::iter::repeat(c).take(digit_bits))
+        .collect();
+    let fill = match digits.first() {
+        Some(&c) if is_special(c) => c,
+        _ => '0',
+    };
+    let bits: Vec<char> = std::iter::repeat(fill)
+        .take(size.saturating_sub(digits.len()))
+        .chain(
+            digits
+                .iter()
+                .cloned()
+                .skip(digits.len().saturating_sub(size)),
+        )
+        .collect();
+    Some((
+        bits.iter().map(|&c| is_special(c)).collect(),
+        bits.iter().map(|&c| is_x(c)).collect(),
+    ))
+}
+
 /// Parse a fixed point number into a [`BigRational`].
 ///
 /// The fractional part of the number is optional, such that this function may
@@ -1435,7 +1654,7 @@ fn parse_fixed_point_number<'gcx>(
                     .span(span)
                     .add_note(format!("{}", e)),
             );
-            Err(())
+            Err(Error::Reported)
         }
     }
 }
@@ -1505,7 +1724,7 @@ fn alloc_genvar_init<'gcx>(
                     .span(stmt.human_span()),
             );
             error!("Offending AST is {:#02?}", stmt);
-            return Err(());
+            return Err(Error::Reported);
         }
     }
     Ok(ids)
@@ -1624,7 +1843,7 @@ fn lower_package<'gcx>(
                     DiagBuilder2::error(format!("{:#} cannot appear in a package", item))
                         .span(item.human_span()),
                 );
-                return Err(());
+                return Err(Error::Reported);
             }
         }
     }
@@ -1660,6 +1879,430 @@ pub(crate) fn lower_index_mode<'gcx>(
     }
 }
 
+/// Check whether an expression is a call to the `$readmemh` or `$readmemb`
+/// system task.
+fn is_readmem(expr: &ast::Expr) -> bool {
+    match expr.data {
+        ast::CallExpr(ref callee, _) => match callee.data {
+            ast::SysIdentExpr(ident) => match &*ident.value.as_str() {
+                "readmemh" | "readmemb" => true,
+                _ => false,
+            },
+            _ => false,
+        },
+        _ => false,
+    }
+}
+
+/// Check whether an expression is a call to the `$finish` system task, with or
+/// without arguments.
+fn is_finish(expr: &ast::Expr) -> bool {
+    let callee = match expr.data {
+        ast::CallExpr(ref callee, _) => callee,
+        _ => expr,
+    };
+    match callee.data {
+        ast::SysIdentExpr(ident) => &*ident.value.as_str() == "finish",
+        _ => false,
+    }
+}
+
+/// Determine the severity of a call to the `$info`, `$warning`, `$error`, or
+/// `$fatal` system task.
+fn message_severity(expr: &ast::Expr) -> Option<MessageSeverity> {
+    let callee = match expr.data {
+        ast::CallExpr(ref callee, _) => callee,
+        _ => expr,
+    };
+    match callee.data {
+        ast::SysIdentExpr(ident) => match &*ident.value.as_str() {
+            "info" => Some(MessageSeverity::Note),
+            "warning" => Some(MessageSeverity::Warning),
+            "error" => Some(MessageSeverity::Error),
+            "fatal" => Some(MessageSeverity::Failure),
+            _ => None,
+        },
+        _ => None,
+    }
+}
+
+/// Lower a call to the `$readmemh` or `$readmemb` system task.
+fn lower_readmem<'gcx>(
+    cx: &impl Context<'gcx>,
+    expr: &'gcx ast::Expr<'gcx>,
+    node_id: NodeId,
+) -> Result<hir::StmtKind> {
+    use crate::syntax::token::Lit;
+    let (ident, args) = match expr.data {
+        ast::CallExpr(ref callee, ref args) => match callee.data {
+            ast::SysIdentExpr(ident) => (ident, args),
+            _ => unreachable!(),
+        },
+        _ => unreachable!(),
+    };
+    let format = match &*ident.value.as_str() {
+        "readmemh" => hir::MemFormat::Hex,
+        _ => hir::MemFormat::Bin,
+    };
+    let args: Vec<_> = args.iter().map(|arg| arg.expr.as_ref()).collect();
+    let (file, target, start, end) = match *args.as_slice() {
+        [Some(file), Some(target)] => (file, target, None, None),
+        [Some(file), Some(target), Some(start)] => (file, target, Some(start), None),
+        [Some(file), Some(target), Some(start), Some(end)] => {
+            (file, target, Some(start), Some(end))
+        }
+        _ => {
+            cx.emit(
+                DiagBuilder2::error(format!(
+                    "`${}` takes a file name, a memory, and optionally a start and end address",
+                    ident
+                ))
+                .span(expr.human_span()),
+            );
+            return Err(Error::Reported);
+        }
+    };
+    let file = match file.data {
+        ast::LiteralExpr(Lit::Str(value)) => Spanned::new(value, file.span),
+        _ => {
+            cx.emit(
+                DiagBuilder2::error(format!("`${}` requires a string literal file name", ident))
+                    .span(file.span),
+            );
+            return Err(Error::Reported);
+        }
+    };
+    Ok(hir::StmtKind::ReadMem {
+        format,
+        file,
+        target: cx.map_ast_with_parent(AstNode::Expr(target), node_id),
+        start: start.map(|expr| cx.map_ast_with_parent(AstNode::Expr(expr), node_id)),
+        end: end.map(|expr| cx.map_ast_with_parent(AstNode::Expr(expr), node_id)),
+    })
+}
+
+/// Determine the name of the system task called by an expression, if it is
+/// one of the given tasks.
+fn system_task<'gcx>(
+    expr: &'gcx ast::Expr<'gcx>,
+    tasks: &[&str],
+) -> Option<(Spanned<Name>, &'gcx [ast::CallArg<'gcx>])> {
+    match expr.data {
+        ast::CallExpr(ref callee, ref args) => match callee.data {
+            ast::SysIdentExpr(ident) if tasks.contains(&&*ident.value.as_str()) => {
+                Some((ident, args.as_slice()))
+            }
+            _ => None,
+        },
+        _ => None,
+    }
+}
+
+/// The system tasks lowered to `hir::StmtKind::Format`.
+const FORMAT_TASKS: &[&str] = &["sformat", "swrite", "sscanf"];
+
+/// The system tasks lowered to `hir::StmtKind::File`.
+const FILE_TASKS: &[&str] = &["fopen", "fwrite", "fdisplay", "fscanf", "fclose"];
+
+/// The elaboration system tasks lowered to `hir::ElabTask`.
+const ELAB_TASKS: &[&str] = &["fatal", "error", "warning", "info"];
+
+/// Check whether an expression is a call to the `$sformat`, `$swrite`, or
+/// `$sscanf` system task.
+fn is_format(expr: &ast::Expr) -> bool {
+    system_task(expr, FORMAT_TASKS).is_some()
+}
+
+/// Check whether an expression is a call to one of the file I/O system tasks.
+fn is_file_task(expr: &ast::Expr) -> bool {
+    system_task(expr, FILE_TASKS).is_some()
+}
+
+/// Collect the arguments of a system task.
+///
+/// Returns the error of the given usage diagnostic if an argument is empty.
+fn task_args<'gcx>(
+    args: &'gcx [ast::CallArg<'gcx>],
+    usage: &dyn Fn() -> Error,
+) -> Result<Vec<&'gcx ast::Expr<'gcx>>> {
+    args.iter()
+        .map(|arg| arg.expr.as_ref().ok_or_else(|| usage()))
+        .collect()
+}
+
+/// Extract the string literal argument of a system task.
+fn lower_string_literal<'gcx>(
+    cx: &impl Context<'gcx>,
+    ident: Spanned<Name>,
+    expr: &'gcx ast::Expr<'gcx>,
+    what: &str,
+) -> Result<Spanned<Name>> {
+    use crate::syntax::token::Lit;
+    match expr.data {
+        ast::LiteralExpr(Lit::Str(value)) => Ok(Spanned::new(value, expr.span)),
+        _ => {
+            cx.emit(
+                DiagBuilder2::error(format!("`${}` requires a string literal {}", ident, what))
+                    .span(expr.span),
+            );
+            Err(Error::Reported)
+        }
+    }
+}
+
+/// Assemble a format from the arguments of a `$write`-like system task.
+///
+/// String literal arguments are taken as part of the format, and every other
+/// argument is formatted with `%d`.
+fn assemble_format<'gcx>(
+    cx: &impl Context<'gcx>,
+    args: &[&'gcx ast::Expr<'gcx>],
+    node_id: NodeId,
+) -> (String, Vec<NodeId>) {
+    use crate::syntax::token::Lit;
+    let mut text = String::new();
+    let mut format_args = vec![];
+    for &arg in args {
+        match arg.data {
+            ast::LiteralExpr(Lit::Str(value)) => text.push_str(&value.as_str()),
+            _ => {
+                text.push_str("%d");
+                format_args.push(cx.map_ast_with_parent(AstNode::Expr(arg), node_id));
+            }
+        }
+    }
+    (text, format_args)
+}
+
+/// Lower an elaboration system task such as `$error("...")`.
+///
+/// A leading string literal is the format of the message, as for `$sformat`.
+/// Otherwise the message is assembled from the arguments as for `$swrite`.
+fn lower_elab_task<'gcx>(
+    cx: &impl Context<'gcx>,
+    task: &'gcx ast::ElabTask<'gcx>,
+    node_id: NodeId,
+) -> Result<HirNode<'gcx>> {
+    use crate::syntax::token::Lit;
+    let name = task.name.value.as_str();
+    let severity = match &*name {
+        "fatal" => hir::ElabSeverity::Fatal,
+        "error" => hir::ElabSeverity::Error,
+        "warning" => hir::ElabSeverity::Warning,
+        "info" => hir::ElabSeverity::Info,
+        _ => unreachable!("only severity tasks are mapped"),
+    };
+    let usage = || {
+        cx.emit(
+            DiagBuilder2::error(format!(
+                "`${}` takes {}a format string followed by the arguments to format",
+                task.name.value,
+                if severity == hir::ElabSeverity::Fatal {
+                    "a finish number, "
+                } else {
+                    ""
+                }
+            ))
+            .span(task.span),
+        );
+        Error::Reported
+    };
+    let mut args = task_args(&task.args, &usage)?;
+    let is_literal = |expr: &ast::Expr| match expr.data {
+        ast::LiteralExpr(Lit::Str(..)) => true,
+        _ => false,
+    };
+
+    // The finish number of `$fatal` only controls the statistics printed by
+    // a simulator and has no meaning during elaboration.
+    if severity == hir::ElabSeverity::Fatal && !args.is_empty() && !is_literal(args[0]) {
+        args.remove(0);
+    }
+
+    let map = |expr: &'gcx ast::Expr<'gcx>| cx.map_ast_with_parent(AstNode::Expr(expr), node_id);
+    let (format, args) = match args.split_first() {
+        Some((&first, rest)) if is_literal(first) => {
+            let text = match first.data {
+                ast::LiteralExpr(Lit::Str(value)) => value,
+                _ => unreachable!(),
+            };
+            (
+                Spanned::new(text, first.span),
+                rest.iter().map(|&arg| map(arg)).collect(),
+            )
+        }
+        _ => {
+            let (text, args) = assemble_format(cx, &args, node_id);
+            (Spanned::new(Name::from(text.as_str()), task.span), args)
+        }
+    };
+    let hir = hir::ElabTask {
+        id: node_id,
+        span: task.span,
+        severity,
+        format,
+        args,
+    };
+    Ok(HirNode::ElabTask(cx.arena().alloc_hir(hir)))
+}
+
+/// Lower a call to the `$sformat`, `$swrite`, or `$sscanf` system task.
+fn lower_format<'gcx>(
+    cx: &impl Context<'gcx>,
+    expr: &'gcx ast::Expr<'gcx>,
+    node_id: NodeId,
+) -> Result<hir::StmtKind> {
+    let (ident, args) = system_task(expr, FORMAT_TASKS).unwrap();
+    let swrite = &*ident.value.as_str() == "swrite";
+    let usage = || {
+        cx.emit(
+            DiagBuilder2::error(format!(
+                "`${}` takes a string, {}followed by the arguments to format",
+                ident,
+                if swrite { "" } else { "a format string, " }
+            ))
+            .span(expr.human_span()),
+        );
+        Error::Reported
+    };
+    let args = task_args(args, &usage)?;
+    let (string, rest) = match args.split_first() {
+        Some((&string, rest)) => (cx.map_ast_with_parent(AstNode::Expr(string), node_id), rest),
+        None => return Err(usage()),
+    };
+
+    // Assemble the format of `$swrite` from its arguments.
+    if swrite {
+        let (text, args) = assemble_format(cx, rest, node_id);
+        return Ok(hir::StmtKind::Format {
+            scan: false,
+            string,
+            format: Spanned::new(Name::from(text.as_str()), expr.span),
+            args,
+        });
+    }
+
+    let (format, rest) = match rest.split_first() {
+        Some((&format, rest)) => (lower_string_literal(cx, ident, format, "format")?, rest),
+        None => return Err(usage()),
+    };
+    Ok(hir::StmtKind::Format {
+        scan: &*ident.value.as_str() == "sscanf",
+        string,
+        format,
+        args: rest
+            .iter()
+            .map(|&arg| cx.map_ast_with_parent(AstNode::Expr(arg), node_id))
+            .collect(),
+    })
+}
+
+/// Lower a call to one of the file I/O system tasks.
+///
+/// The `result` is the target the value returned by `$fopen` or `$fscanf` is
+/// assigned to, if any.
+fn lower_file_task<'gcx>(
+    cx: &impl Context<'gcx>,
+    expr: &'gcx ast::Expr<'gcx>,
+    result: Option<&'gcx ast::Expr<'gcx>>,
+    node_id: NodeId,
+) -> Result<hir::StmtKind> {
+    let (ident, args) = system_task(expr, FILE_TASKS).unwrap();
+    let name = ident.value.as_str();
+    let usage = || {
+        let args = match &*name {
+            "fopen" => "a file name and optionally a mode",
+            "fwrite" | "fdisplay" => "a descriptor followed by the arguments to write",
+            "fscanf" => "a descriptor, a format string, and the arguments to read",
+            _ => "a descriptor",
+        };
+        cx.emit(
+            DiagBuilder2::error(format!("`${}` takes {}", ident, args)).span(expr.human_span()),
+        );
+        Error::Reported
+    };
+    let args = task_args(args, &usage)?;
+    let map = |expr: &'gcx ast::Expr<'gcx>| cx.map_ast_with_parent(AstNode::Expr(expr), node_id);
+
+    // Only `$fopen` and `$fscanf` return a value.
+    match (&*name, result) {
+        ("fopen", None) => {
+            cx.emit(
+                DiagBuilder2::error("result of `$fopen` must be assigned to a descriptor")
+                    .span(expr.human_span()),
+            );
+            return Err(Error::Reported);
+        }
+        ("fopen", _) | ("fscanf", _) | (_, None) => (),
+        (_, Some(result)) => {
+            cx.emit(
+                DiagBuilder2::error(format!("`${}` does not return a value", ident))
+                    .span(result.span),
+            );
+            return Err(Error::Reported);
+        }
+    }
+
+    let task = match (&*name, args.as_slice()) {
+        ("fopen", &[file]) => hir::FileTask::Open {
+            target: map(result.unwrap()),
+            file: lower_string_literal(cx, ident, file, "file name")?,
+            mode: None,
+        },
+        ("fopen", &[file, mode]) => {
+            let mode = lower_string_literal(cx, ident, mode, "mode")?;
+            if !is_file_mode(&mode.value.as_str()) {
+                cx.emit(
+                    DiagBuilder2::error(format!("`{}` is not a valid file mode", mode.value))
+                        .span(mode.span)
+                        .add_note(
+                            "Valid modes are `r`, `w`, `a`, optionally followed by `+` and `b`",
+                        ),
+                );
+                return Err(Error::Reported);
+            }
+            hir::FileTask::Open {
+                target: map(result.unwrap()),
+                file: lower_string_literal(cx, ident, file, "file name")?,
+                mode: Some(mode),
+            }
+        }
+        ("fwrite", &[fd, ref rest @ ..]) | ("fdisplay", &[fd, ref rest @ ..]) => {
+            let (mut text, args) = assemble_format(cx, rest, node_id);
+            if &*name == "fdisplay" {
+                text.push('\n');
+            }
+            hir::FileTask::Write {
+                fd: map(fd),
+                format: Spanned::new(Name::from(text.as_str()), expr.span),
+                args,
+            }
+        }
+        ("fscanf", &[fd, format, ref rest @ ..]) => hir::FileTask::Scan {
+            fd: map(fd),
+            format: lower_string_literal(cx, ident, format, "format")?,
+            args: rest.iter().map(|&arg| map(arg)).collect(),
+            count: result.map(map),
+        },
+        ("fclose", &[fd]) => hir::FileTask::Close { fd: map(fd) },
+        _ => return Err(usage()),
+    };
+    Ok(hir::StmtKind::File(task))
+}
+
+/// Check whether a string is a valid mode for `$fopen`, such as `r` or `wb+`.
+fn is_file_mode(mode: &str) -> bool {
+    let mut chars = mode.chars();
+    match chars.next() {
+        Some('r') | Some('w') | Some('a') => (),
+        _ => return false,
+    }
+    match chars.as_str() {
+        "" | "+" | "b" | "+b" | "b+" => true,
+        _ => false,
+    }
+}
+
 /// Lower a function or method call argument to HIR.
 fn lower_call_arg<'gcx>(
     cx: &impl Context<'gcx>,
@@ -41,6 +41,8 @@ make_arenas!(
         packages: Package,
         enum_variants: EnumVariant,
         subroutines: Subroutine,
+        assertions: Assertion<'hir>,
+        elab_tasks: ElabTask,
     }
 );
 
@@ -58,6 +60,7 @@ pub(crate) fn accessed_nodes<'a>(
             node_id,
             read: Default::default(),
             written: Default::default(),
+            finishes: false,
         },
     };
     k.visit_node_with_id(node_id, false);
@@ -73,6 +76,8 @@ pub struct AccessTable {
     pub read: BTreeSet<AccessedNode>,
     /// All nodes being written.
     pub written: BTreeSet<AccessedNode>,
+    /// Whether a `$finish` system task is called.
+    pub finishes: bool,
 }
 
 /// An accessed node. The `AccessTable` carries this enum as entries.
@@ -119,6 +124,17 @@ where
         self.cx
     }
 
+    fn visit_stmt(&mut self, stmt: &'gcx Stmt) {
+        match stmt.kind {
+            StmtKind::Finish => self.table.finishes = true,
+            StmtKind::Message(severity) if self.cx.sess().opts.stops_at(severity) => {
+                self.table.finishes = true
+            }
+            _ => (),
+        }
+        walk_stmt(self, stmt);
+    }
+
     fn visit_expr(&mut self, expr: &'gcx Expr, lvalue: bool) {
         if lvalue {
             self.cx.mir_lvalue(expr.id, self.env).walk(self);
@@ -4,6 +4,7 @@
 
 use crate::crate_prelude::*;
 use bit_vec::BitVec;
+use moore_common::MessageSeverity;
 use num::{BigInt, BigRational};
 use std::ops::Deref;
 
@@ -34,6 +35,8 @@ pub enum HirNode<'a> {
     Package(&'a Package),
     EnumVariant(&'a EnumVariant),
     Subroutine(&'a Subroutine),
+    Assertion(&'a Assertion<'a>),
+    ElabTask(&'a ElabTask),
 }
 
 impl<'hir> HasSpan for HirNode<'hir> {
@@ -60,6 +63,8 @@ impl<'hir> HasSpan for HirNode<'hir> {
             HirNode::Package(x) => x.span(),
             HirNode::EnumVariant(x) => x.span(),
             HirNode::Subroutine(x) => x.span(),
+            HirNode::Assertion(x) => x.span(),
+            HirNode::ElabTask(x) => x.span(),
         }
     }
 
@@ -86,6 +91,8 @@ impl<'hir> HasSpan for HirNode<'hir> {
             HirNode::Package(x) => x.human_span(),
             HirNode::EnumVariant(x) => x.human_span(),
             HirNode::Subroutine(x) => x.human_span(),
+            HirNode::Assertion(x) => x.human_span(),
+            HirNode::ElabTask(x) => x.human_span(),
         }
     }
 }
@@ -114,6 +121,8 @@ impl<'hir> HasDesc for HirNode<'hir> {
             HirNode::Package(x) => x.desc(),
             HirNode::EnumVariant(x) => x.desc(),
             HirNode::Subroutine(x) => x.desc(),
+            HirNode::Assertion(x) => x.desc(),
+            HirNode::ElabTask(x) => x.desc(),
         }
     }
 
@@ -140,6 +149,8 @@ impl<'hir> HasDesc for HirNode<'hir> {
             HirNode::Package(x) => x.desc_full(),
             HirNode::EnumVariant(x) => x.desc_full(),
             HirNode::Subroutine(x) => x.desc_full(),
+            HirNode::Assertion(x) => x.desc_full(),
+            HirNode::ElabTask(x) => x.desc_full(),
         }
     }
 }
@@ -202,6 +213,10 @@ pub struct ModuleBlock {
     pub params: Vec<NodeId>,
     /// The continuous assignments in the module.
     pub assigns: Vec<NodeId>,
+    /// The concurrent assertions in the module.
+    pub asserts: Vec<NodeId>,
+    /// The elaboration system tasks in the module.
+    pub elab_tasks: Vec<NodeId>,
     /// The bottom of the name scope tree.
     pub last_rib: NodeId,
 }
@@ -602,6 +617,7 @@ impl HasDesc for Expr<'_> {
         match self.kind {
             ExprKind::IntConst { .. } => "integer constant",
             ExprKind::TimeConst(_) => "time constant",
+            ExprKind::RealConst(_) => "real constant",
             ExprKind::Ident(_) => "identifier",
             _ => "expression",
         }
@@ -612,6 +628,7 @@ impl HasDesc for Expr<'_> {
         match self.kind {
             ExprKind::IntConst { value: ref k, .. } => format!("{} `{}`", self.desc(), k),
             ExprKind::TimeConst(ref k) => format!("{} `{}`", self.desc(), k),
+            ExprKind::RealConst(_) => format!("{} `{}`", self.desc(), self.span().extract()),
             ExprKind::Ident(n) => format!("`{}`", n.value),
             ExprKind::PositionalPattern(..) => format!("positional pattern"),
             ExprKind::NamedPattern(..) => format!("named pattern"),
@@ -639,6 +656,8 @@ pub enum ExprKind<'a> {
     UnsizedConst(char),
     /// A time constant literal.
     TimeConst(BigRational),
+    /// A real constant literal such as `2.5`.
+    RealConst(BigRational),
     /// A string constant literal.
     StringConst(Spanned<Name>),
     /// An identifier.
@@ -843,6 +862,191 @@ pub enum BuiltinCall<'a> {
     Signed(NodeId),
     /// A call to the convert-to-unsigned function `$unsigned(x)`.
     Unsigned(NodeId),
+    /// A call to a real math function such as `$sqrt(x)` or `$pow(x, y)`. The
+    /// second argument is only present for functions of two arguments.
+    Math(MathFn, NodeId, Option<NodeId>),
+    /// A call to a random number function such as `$urandom(seed)` or
+    /// `$urandom_range(max, min)`, with up to two arguments.
+    Random(RandomFn, Option<NodeId>, Option<NodeId>),
+    /// A call to `$test$plusargs(<string>)`, checking whether a plusarg
+    /// starting with the string was passed to the simulation.
+    TestPlusargs(Spanned<Name>),
+    /// A call to `$value$plusargs(<format>, <target>)`, scanning the value
+    /// of a plusarg matching the format into the target.
+    ValuePlusargs(Spanned<Name>, NodeId),
+}
+
+/// The real math functions defined in IEEE 1800-2017 section 20.8.2.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+#[allow(missing_docs)]
+pub enum MathFn {
+    Ln,
+    Log10,
+    Exp,
+    Sqrt,
+    Pow,
+    Floor,
+    Ceil,
+    Sin,
+    Cos,
+    Tan,
+    Asin,
+    Acos,
+    Atan,
+    Atan2,
+    Hypot,
+    Sinh,
+    Cosh,
+    Tanh,
+    Asinh,
+    Acosh,
+    Atanh,
+}
+
+impl MathFn {
+    /// All math functions.
+    pub const ALL: [MathFn; 21] = [
+        MathFn::Ln,
+        MathFn::Log10,
+        MathFn::Exp,
+        MathFn::Sqrt,
+        MathFn::Pow,
+        MathFn::Floor,
+        MathFn::Ceil,
+        MathFn::Sin,
+        MathFn::Cos,
+        MathFn::Tan,
+        MathFn::Asin,
+        MathFn::Acos,
+        MathFn::Atan,
+        MathFn::Atan2,
+        MathFn::Hypot,
+        MathFn::Sinh,
+        MathFn::Cosh,
+        MathFn::Tanh,
+        MathFn::Asinh,
+        MathFn::Acosh,
+        MathFn::Atanh,
+    ];
+
+    /// Look up a math function by its name, without the leading `$`.
+    pub fn from_name(name: &str) -> Option<MathFn> {
+        MathFn::ALL.iter().cloned().find(|f| f.name() == name)
+    }
+
+    /// The name of the function, without the leading `$`.
+    pub fn name(self) -> &'static str {
+        match self {
+            MathFn::Ln => "ln",
+            MathFn::Log10 => "log10",
+            MathFn::Exp => "exp",
+            MathFn::Sqrt => "sqrt",
+            MathFn::Pow => "pow",
+            MathFn::Floor => "floor",
+            MathFn::Ceil => "ceil",
+            MathFn::Sin => "sin",
+            MathFn::Cos => "cos",
+            MathFn::Tan => "tan",
+            MathFn::Asin => "asin",
+            MathFn::Acos => "acos",
+            MathFn::Atan => "atan",
+            MathFn::Atan2 => "atan2",
+            MathFn::Hypot => "hypot",
+            MathFn::Sinh => "sinh",
+            MathFn::Cosh => "cosh",
+            MathFn::Tanh => "tanh",
+            MathFn::Asinh => "asinh",
+            MathFn::Acosh => "acosh",
+            MathFn::Atanh => "atanh",
+        }
+    }
+
+    /// The number of arguments the function takes.
+    pub fn arity(self) -> usize {
+        match self {
+            MathFn::Pow | MathFn::Atan2 | MathFn::Hypot => 2,
+            _ => 1,
+        }
+    }
+
+    /// Evaluate the function. The second argument is ignored by functions
+    /// which only take one.
+    pub fn apply(self, x: f64, y: f64) -> f64 {
+        match self {
+            MathFn::Ln => x.ln(),
+            MathFn::Log10 => x.log10(),
+            MathFn::Exp => x.exp(),
+            MathFn::Sqrt => x.sqrt(),
+            MathFn::Pow => x.powf(y),
+            MathFn::Floor => x.floor(),
+            MathFn::Ceil => x.ceil(),
+            MathFn::Sin => x.sin(),
+            MathFn::Cos => x.cos(),
+            MathFn::Tan => x.tan(),
+            MathFn::Asin => x.asin(),
+            MathFn::Acos => x.acos(),
+            MathFn::Atan => x.atan(),
+            MathFn::Atan2 => x.atan2(y),
+            MathFn::Hypot => x.hypot(y),
+            MathFn::Sinh => x.sinh(),
+            MathFn::Cosh => x.cosh(),
+            MathFn::Tanh => x.tanh(),
+            MathFn::Asinh => x.asinh(),
+            MathFn::Acosh => x.acosh(),
+            MathFn::Atanh => x.atanh(),
+        }
+    }
+}
+
+/// The random number functions defined in IEEE 1800-2017 sections 18.13 and
+/// 20.15.
+///
+/// The functions are evaluated by the runtime, which seeds its generator with
+/// the `--seed` option passed along with every call.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum RandomFn {
+    /// `$random`, a signed 32 bit number.
+    Random,
+    /// `$urandom[(seed)]`, an unsigned 32 bit number. The optional seed
+    /// reseeds the generator.
+    Urandom,
+    /// `$urandom_range(max[, min])`, an unsigned number between the bounds.
+    UrandomRange,
+}
+
+impl RandomFn {
+    /// Look up a random number function by its name, without the leading `$`.
+    pub fn from_name(name: &str) -> Option<RandomFn> {
+        match name {
+            "random" => Some(RandomFn::Random),
+            "urandom" => Some(RandomFn::Urandom),
+            "urandom_range" => Some(RandomFn::UrandomRange),
+            _ => None,
+        }
+    }
+
+    /// The name of the function, without the leading `$`.
+    pub fn name(self) -> &'static str {
+        match self {
+            RandomFn::Random => "random",
+            RandomFn::Urandom => "urandom",
+            RandomFn::UrandomRange => "urandom_range",
+        }
+    }
+
+    /// The minimum and maximum number of arguments the function takes.
+    pub fn arity(self) -> (usize, usize) {
+        match self {
+            RandomFn::Random => (0, 0),
+            RandomFn::Urandom => (0, 1),
+            RandomFn::UrandomRange => (1, 2),
+        }
+    }
+
+    /// Whether the function returns a signed number.
+    pub fn is_signed(self) -> bool {
+        self == RandomFn::Random
+    }
 }
 
 /// A variable or net declaration.
@@ -927,6 +1131,135 @@ impl HasDesc for Proc {
     }
 }
 
+/// A concurrent assertion.
+#[derive(Debug, PartialEq, Eq)]
+pub struct Assertion<'a> {
+    pub id: NodeId,
+    pub span: Span,
+    pub label: Option<Spanned<Name>>,
+    pub kind: AssertionKind,
+    /// The AST node.
+    pub ast: &'a ast::Assertion<'a>,
+}
+
+impl HasSpan for Assertion<'_> {
+    fn span(&self) -> Span {
+        self.span
+    }
+
+    fn human_span(&self) -> Span {
+        self.label.map(|l| l.span).unwrap_or(self.span)
+    }
+}
+
+impl HasDesc for Assertion<'_> {
+    fn desc(&self) -> &'static str {
+        match self.kind {
+            AssertionKind::Assert => "`assert property` directive",
+            AssertionKind::Assume => "`assume property` directive",
+            AssertionKind::Cover => "`cover property` directive",
+            AssertionKind::Restrict => "`restrict property` directive",
+            AssertionKind::Expect => "`expect` statement",
+        }
+    }
+
+    fn desc_full(&self) -> String {
+        match self.label {
+            Some(label) => format!("{} `{}`", self.desc(), label.value),
+            None => self.desc().to_string(),
+        }
+    }
+}
+
+/// The different flavors of concurrent assertions.
+///
+/// Assertions and expectations are obligations that the design must meet,
+/// while assumptions and restrictions constrain the environment the design is
+/// verified in. Covers ask for a witness trace instead of a proof.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum AssertionKind {
+    Assert,
+    Assume,
+    Cover,
+    Restrict,
+    Expect,
+}
+
+impl AssertionKind {
+    /// Check whether the assertion constrains the environment rather than
+    /// checking the design.
+    pub fn is_constraint(self) -> bool {
+        match self {
+            AssertionKind::Assume | AssertionKind::Restrict => true,
+            _ => false,
+        }
+    }
+
+    /// The keyword that introduces the assertion.
+    pub fn keyword(self) -> &'static str {
+        match self {
+            AssertionKind::Assert => "assert",
+            AssertionKind::Assume => "assume",
+            AssertionKind::Cover => "cover",
+            AssertionKind::Restrict => "restrict",
+            AssertionKind::Expect => "expect",
+        }
+    }
+}
+
+/// An elaboration system task, such as `$error("...")`.
+#[derive(Debug, PartialEq, Eq)]
+pub struct ElabTask {
+    pub id: NodeId,
+    pub span: Span,
+    pub severity: ElabSeverity,
+    /// The format of the message, assembled from the arguments.
+    pub format: Spanned<Name>,
+    /// The arguments to format.
+    pub args: Vec<NodeId>,
+}
+
+impl HasSpan for ElabTask {
+    fn span(&self) -> Span {
+        self.span
+    }
+}
+
+impl HasDesc for ElabTask {
+    fn desc(&self) -> &'static str {
+        "elaboration system task"
+    }
+
+    fn desc_full(&self) -> String {
+        format!("`${}` elaboration system task", self.severity.task_name())
+    }
+}
+
+/// The severity of an elaboration system task.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ElabSeverity {
+    /// `$fatal`, which aborts elaboration.
+    Fatal,
+    /// `$error`
+    Error,
+    /// `$warning`
+    Warning,
+    /// `$info`
+    Info,
+}
+
+impl ElabSeverity {
+    /// The name of the system task, without the leading `$`.
+    pub fn task_name(self) -> &'static str {
+        match self {
+            ElabSeverity::Fatal => "fatal",
+            ElabSeverity::Error => "error",
+            ElabSeverity::Warning => "warning",
+            ElabSeverity::Info => "info",
+        }
+    }
+}
+
 /// A variable declaration.
 #[derive(Debug, PartialEq, Eq)]
 pub struct Stmt {
@@ -1004,6 +1337,120 @@ pub enum StmtKind {
         default: Option<NodeId>,
         kind: ast::CaseKind,
     },
+    /// A `$readmemh` or `$readmemb` system task.
+    ///
+    /// ```text
+    /// $readmemh(<file>, <target> [, <start> [, <end>]])
+    /// ```
+    ReadMem {
+        format: MemFormat,
+        file: Spanned<Name>,
+        target: NodeId,
+        start: Option<NodeId>,
+        end: Option<NodeId>,
+    },
+    /// A `$sformat`, `$swrite`, or `$sscanf` system task.
+    ///
+    /// ```text
+    /// $sformat(<string>, <format>, <args>...)
+    /// $swrite(<string>, <args>...)
+    /// $sscanf(<string>, <format>, <args>...)
+    /// ```
+    ///
+    /// The format of `$swrite` is assembled from its string literal
+    /// arguments, with a `%d` for every other argument. The number of
+    /// arguments converted by `$sscanf` is discarded.
+    Format {
+        /// Whether this is a `$sscanf` reading from the string, rather than
+        /// writing to it.
+        scan: bool,
+        string: NodeId,
+        format: Spanned<Name>,
+        args: Vec<NodeId>,
+    },
+    /// A file I/O system task.
+    File(FileTask),
+    /// A `$finish` system task.
+    ///
+    /// Any argument controlling the diagnostic output of the task is ignored.
+    Finish,
+    /// A `$info`, `$warning`, `$error`, or `$fatal` system task.
+    ///
+    /// The message itself is not printed. A message which stops the
+    /// simulation as per `--stop-on` behaves like `$finish`.
+    Message(MessageSeverity),
+}
+
+/// The file I/O system tasks.
+///
+/// Files are accessed through 32 bit descriptors. A descriptor with the most
+/// significant bit set refers to a single file opened with a mode. Otherwise
+/// the descriptor is a multichannel descriptor, where each bit selects a
+/// channel opened by `$fopen` without a mode, and bit 0 is the standard output.
+/// Writing to a multichannel descriptor writes to all selected channels.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum FileTask {
+    /// A `$fopen` system task, assigning the descriptor to `target`.
+    ///
+    /// ```text
+    /// <target> = $fopen(<file> [, <mode>])
+    /// ```
+    Open {
+        target: NodeId,
+        file: Spanned<Name>,
+        /// The mode, or `None` to open a multichannel descriptor.
+        mode: Option<Spanned<Name>>,
+    },
+    /// A `$fwrite` or `$fdisplay` system task.
+    ///
+    /// ```text
+    /// $fwrite(<fd>, <args>...)
+    /// ```
+    ///
+    /// The format is assembled as for `$swrite`, with a trailing newline for
+    /// `$fdisplay`.
+    Write {
+        fd: NodeId,
+        format: Spanned<Name>,
+        args: Vec<NodeId>,
+    },
+    /// A `$fscanf` system task, optionally assigning the number of converted
+    /// arguments to `count`.
+    ///
+    /// ```text
+    /// [<count> =] $fscanf(<fd>, <format>, <args>...)
+    /// ```
+    Scan {
+        fd: NodeId,
+        format: Spanned<Name>,
+        args: Vec<NodeId>,
+        count: Option<NodeId>,
+    },
+    /// A `$fclose` system task.
+    ///
+    /// ```text
+    /// $fclose(<fd>)
+    /// ```
+    Close { fd: NodeId },
+}
+
+/// The format of a memory file loaded by `$readmemh` or `$readmemb`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum MemFormat {
+    /// Hexadecimal words, as read by `$readmemh`.
+    Hex,
+    /// Binary words, as read by `$readmemb`.
+    Bin,
+}
+
+impl MemFormat {
+    /// The radix of the words in the file.
+    pub fn radix(self) -> u32 {
+        match self {
+            MemFormat::Hex => 16,
+            MemFormat::Bin => 2,
+        }
+    }
 }
 
 /// The different forms an assignment can take.
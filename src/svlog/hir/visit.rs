@@ -23,7 +23,7 @@ pub trait Visitor<'a>: Sized {
     fn visit_node_with_id(&mut self, node_id: NodeId, lvalue: bool) {
         match self.context().hir_of(node_id) {
             Ok(x) => self.visit_node(x, lvalue),
-            Err(()) => (),
+            Err(_) => (),
         }
     }
 
@@ -213,6 +213,56 @@ pub fn walk_stmt<'a>(visitor: &mut impl Visitor<'a>, stmt: &'a Stmt) {
                 visitor.visit_node_with_id(default, false);
             }
         }
+        StmtKind::ReadMem {
+            target, start, end, ..
+        } => {
+            visitor.visit_node_with_id(target, true);
+            if let Some(start) = start {
+                visitor.visit_node_with_id(start, false);
+            }
+            if let Some(end) = end {
+                visitor.visit_node_with_id(end, false);
+            }
+        }
+        StmtKind::Format {
+            scan,
+            string,
+            ref args,
+            ..
+        } => {
+            visitor.visit_node_with_id(string, !scan);
+            for &arg in args {
+                visitor.visit_node_with_id(arg, scan);
+            }
+        }
+        StmtKind::File(FileTask::Open { target, .. }) => {
+            visitor.visit_node_with_id(target, true);
+        }
+        StmtKind::File(FileTask::Write { fd, ref args, .. }) => {
+            visitor.visit_node_with_id(fd, false);
+            for &arg in args {
+                visitor.visit_node_with_id(arg, false);
+            }
+        }
+        StmtKind::File(FileTask::Scan {
+            fd,
+            ref args,
+            count,
+            ..
+        }) => {
+            visitor.visit_node_with_id(fd, false);
+            for &arg in args {
+                visitor.visit_node_with_id(arg, true);
+            }
+            if let Some(count) = count {
+                visitor.visit_node_with_id(count, true);
+            }
+        }
+        StmtKind::File(FileTask::Close { fd }) => {
+            visitor.visit_node_with_id(fd, false);
+        }
+        StmtKind::Finish => (),
+        StmtKind::Message(_) => (),
     }
 }
 
@@ -220,9 +270,11 @@ pub fn walk_stmt<'a>(visitor: &mut impl Visitor<'a>, stmt: &'a Stmt) {
 pub fn walk_expr<'a>(visitor: &mut impl Visitor<'a>, expr: &'a Expr, lvalue: bool) {
     match expr.kind {
         ExprKind::Builtin(BuiltinCall::Unsupported)
+        | ExprKind::Builtin(BuiltinCall::TestPlusargs(_))
         | ExprKind::IntConst { .. }
         | ExprKind::UnsizedConst(_)
         | ExprKind::TimeConst(_)
+        | ExprKind::RealConst(_)
         | ExprKind::StringConst(_) => (),
         ExprKind::Ident(x) => {
             visitor.visit_ident(x);
@@ -257,6 +309,20 @@ pub fn walk_expr<'a>(visitor: &mut impl Visitor<'a>, expr: &'a Expr, lvalue: boo
         ExprKind::Builtin(BuiltinCall::Bits(arg)) => {
             visitor.visit_node_with_id(arg.id(), false);
         }
+        ExprKind::Builtin(BuiltinCall::Math(_, arg, second)) => {
+            visitor.visit_node_with_id(arg, false);
+            if let Some(second) = second {
+                visitor.visit_node_with_id(second, false);
+            }
+        }
+        ExprKind::Builtin(BuiltinCall::Random(_, first, second)) => {
+            for arg in first.into_iter().chain(second) {
+                visitor.visit_node_with_id(arg, false);
+            }
+        }
+        ExprKind::Builtin(BuiltinCall::ValuePlusargs(_, target)) => {
+            visitor.visit_node_with_id(target, true);
+        }
         ExprKind::Ternary(cond, true_expr, false_expr) => {
             visitor.visit_node_with_id(cond, false);
             visitor.visit_node_with_id(true_expr, lvalue);
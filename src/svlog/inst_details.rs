@@ -198,7 +198,7 @@ impl<'a, 'gcx> hir::Visitor<'gcx> for InstVerbosityVisitor<'a, 'gcx> {
     fn visit_inst(&mut self, hir: &'gcx hir::Inst<'gcx>) {
         let details = match self.cx.inst_details(Ref(hir), self.env) {
             Ok(x) => x,
-            Err(()) => return,
+            Err(_) => return,
         };
         self.cx.emit(
             DiagBuilder2::note("instantiation details")
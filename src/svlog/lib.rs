@@ -113,7 +113,9 @@ macro_rules! assert_type {
 
 mod ast_map;
 mod codegen;
+pub mod config;
 mod context;
+mod format;
 pub mod hir;
 mod inst_details;
 pub mod mir;
@@ -122,6 +124,7 @@ mod param_env;
 pub mod pattern_mapping;
 pub mod port_list;
 mod port_mapping;
+mod readmem;
 pub mod resolver;
 pub mod rst;
 #[warn(missing_docs)]
@@ -135,16 +138,18 @@ pub use moore_common::{
 };
 
 /// A general result returned by the queries.
-pub type Result<T> = std::result::Result<T, ()>;
+pub use moore_common::score::{Error, Result};
 
 pub use crate::{
-    codegen::CodeGenerator,
+    codegen::{CodeGenerator, Directive, FailedUnit, SynthAttr, UnitSource},
     context::*,
     inst_details::{InstDetails, InstTargetDetails, InstVerbosityVisitor},
     param_env::{
-        IntoNodeEnvId, NodeEnvId, ParamEnv, ParamEnvBinding, ParamEnvData, ParamEnvSource,
+        param_env_from_overrides, IntoNodeEnvId, NodeEnvId, ParamEnv, ParamEnvBinding,
+        ParamEnvData, ParamEnvSource,
     },
     port_mapping::{PortMapping, PortMappingSource},
+    resolver::find_decl_uses,
     // resolver::*,
     syntax::*,
 };
@@ -157,7 +162,7 @@ mod crate_prelude {
         common::{
             errors::*,
             name::Name,
-            score::Result,
+            score::{Error, Result},
             source::{Span, Spanned},
             util::{HasDesc, HasSpan},
             NodeId, SessionContext, Verbosity,
@@ -117,7 +117,7 @@ fn try_lower_expr<'gcx>(
                         ))
                         .span(span),
                     );
-                    Err(())
+                    Err(Error::Reported)
                 }
             };
         }
@@ -166,5 +166,5 @@ fn try_lower_expr<'gcx>(
     // assigned to.
     error!("{:#?}", hir);
     cx.emit(DiagBuilder2::error(format!("{} cannot be assigned to", hir.desc_full())).span(span));
-    Err(())
+    Err(Error::Reported)
 }
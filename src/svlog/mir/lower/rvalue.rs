@@ -120,7 +120,7 @@ fn lower_expr_inner<'gcx>(
     let span = cx.span(expr_id);
     let env = builder.env;
     if ty.is_error() {
-        return Err(());
+        return Err(Error::Reported);
     }
 
     // Determine the expression type and match on the various forms.
@@ -168,6 +168,10 @@ fn lower_expr_inner<'gcx>(
             Ok(builder.constant(value::make_int(ty, num::zero())))
         }
         hir::ExprKind::Builtin(hir::BuiltinCall::Clog2(arg)) => {
+            let arg_mir = cx.mir_rvalue(arg, env);
+            if !arg_mir.is_const() {
+                return Ok(builder.build(ty, RvalueKind::Clog2(arg_mir)));
+            }
             let arg_val = cx.constant_value_of(arg, env);
             let arg_int = match arg_val.kind {
                 ValueKind::Int(ref arg, ..) => arg,
@@ -181,6 +185,43 @@ fn lower_expr_inner<'gcx>(
             };
             Ok(builder.constant(value::make_int(ty, value)))
         }
+        hir::ExprKind::Builtin(hir::BuiltinCall::Math(func, arg, second)) => {
+            let x = real_value_of(cx, arg, env)?;
+            let y = match second {
+                Some(second) => real_value_of(cx, second, env)?,
+                None => Some(0.0),
+            };
+            let (x, y) = match (x, y) {
+                (Some(x), Some(y)) => (x, y),
+                _ => {
+                    let args = std::iter::once(arg)
+                        .chain(second)
+                        .map(|arg| cx.mir_rvalue(arg, env))
+                        .collect();
+                    return Ok(builder.build(ty, RvalueKind::MathCall(func, args)));
+                }
+            };
+            Ok(builder.constant(value::make_real(ty, func.apply(x, y))))
+        }
+        hir::ExprKind::RealConst(ref k) => Ok(builder.constant(value::make_real(
+            ty,
+            k.numer().to_f64().unwrap_or(std::f64::NAN)
+                / k.denom().to_f64().unwrap_or(std::f64::NAN),
+        ))),
+        hir::ExprKind::Builtin(hir::BuiltinCall::Random(func, first, second)) => {
+            let args = first
+                .into_iter()
+                .chain(second)
+                .map(|arg| cx.mir_rvalue(arg, env))
+                .collect();
+            Ok(builder.build(ty, RvalueKind::RandomCall(func, args)))
+        }
+        hir::ExprKind::Builtin(hir::BuiltinCall::TestPlusargs(string)) => {
+            Ok(builder.build(ty, RvalueKind::TestPlusargs(string.value)))
+        }
+        hir::ExprKind::Builtin(hir::BuiltinCall::ValuePlusargs(format, target)) => {
+            Ok(builder.build(ty, RvalueKind::ValuePlusargs(format, target)))
+        }
         hir::ExprKind::Builtin(hir::BuiltinCall::Bits(arg)) => {
             let arg_ty = match cx.disamb_type_or_expr(Ref(arg))? {
                 &ast::TypeOrExpr::Type(x) => cx.map_to_type_or_error(Ref(x), env),
@@ -226,7 +267,7 @@ fn lower_expr_inner<'gcx>(
                     );
                     error!("Offending HIR: {:?}", hir);
                     error!("Resolved to: {:?}", x);
-                    Err(())
+                    Err(Error::Reported)
                 }
             }
         }
@@ -267,7 +308,7 @@ fn lower_expr_inner<'gcx>(
             // Compute the result type of the concatenation.
             let final_ty = builder.cx.need_self_determined_type(hir.id, env);
             if final_ty.is_error() {
-                return Err(());
+                return Err(Error::Reported);
             }
             let domain = final_ty.domain();
             let concat_width = exprs.iter().map(|(w, _)| w).sum();
@@ -559,6 +600,14 @@ fn lower_cast<'gcx>(
             CastOp::PickModport => {
                 value = builder.build(to, value.kind.clone());
             }
+            CastOp::RealToInt => {
+                assert_span!(to.is_simple_bit_vector(), value.span, builder.cx);
+                value = builder.build(to, RvalueKind::CastRealToInt(value));
+            }
+            CastOp::IntToReal => {
+                assert_span!(value.ty.is_simple_bit_vector(), value.span, builder.cx);
+                value = builder.build(to, RvalueKind::CastIntToReal(value));
+            }
         }
         if !value.ty.is_identical(to) {
             error!(
@@ -1440,3 +1489,81 @@ fn lower_assign<'a>(
         },
     )
 }
+
+/// Evaluate the argument of a real math function at elaboration time.
+///
+/// Real constants, arithmetic on them, and nested math functions are evaluated
+/// as real numbers. Other expressions must have a constant value.
+/// Returns `None` if the argument is not constant and must be evaluated at
+/// runtime.
+fn real_value_of<'a>(cx: &impl Context<'a>, expr_id: NodeId, env: ParamEnv) -> Result<Option<f64>> {
+    let hir = match cx.hir_of(expr_id)? {
+        HirNode::Expr(x) => x,
+        _ => unreachable!("math function argument must be an expression"),
+    };
+    let both = |lhs, rhs| -> Result<Option<(f64, f64)>> {
+        Ok(
+            match (real_value_of(cx, lhs, env)?, real_value_of(cx, rhs, env)?) {
+                (Some(lhs), Some(rhs)) => Some((lhs, rhs)),
+                _ => None,
+            },
+        )
+    };
+    Ok(match hir.kind {
+        hir::ExprKind::RealConst(ref k) => Some(
+            k.numer().to_f64().unwrap_or(std::f64::NAN)
+                / k.denom().to_f64().unwrap_or(std::f64::NAN),
+        ),
+        hir::ExprKind::IntConst { value: ref k, .. } => k.to_f64(),
+        hir::ExprKind::Unary(hir::UnaryOp::Pos, arg) => real_value_of(cx, arg, env)?,
+        hir::ExprKind::Unary(hir::UnaryOp::Neg, arg) => real_value_of(cx, arg, env)?.map(|x| -x),
+        hir::ExprKind::Binary(op @ hir::BinaryOp::Add, lhs, rhs)
+        | hir::ExprKind::Binary(op @ hir::BinaryOp::Sub, lhs, rhs)
+        | hir::ExprKind::Binary(op @ hir::BinaryOp::Mul, lhs, rhs)
+        | hir::ExprKind::Binary(op @ hir::BinaryOp::Div, lhs, rhs)
+        | hir::ExprKind::Binary(op @ hir::BinaryOp::Pow, lhs, rhs) => {
+            both(lhs, rhs)?.map(|(lhs, rhs)| match op {
+                hir::BinaryOp::Add => lhs + rhs,
+                hir::BinaryOp::Sub => lhs - rhs,
+                hir::BinaryOp::Mul => lhs * rhs,
+                hir::BinaryOp::Div => lhs / rhs,
+                _ => lhs.powf(rhs),
+            })
+        }
+        hir::ExprKind::Builtin(hir::BuiltinCall::Math(func, arg, second)) => {
+            let second = match second {
+                Some(second) => second,
+                None => return Ok(real_value_of(cx, arg, env)?.map(|x| func.apply(x, 0.0))),
+            };
+            both(arg, second)?.map(|(x, y)| func.apply(x, y))
+        }
+        hir::ExprKind::Ident(..) | hir::ExprKind::Scope(..) => {
+            let binding = cx.resolve_node(expr_id, env)?;
+            match cx.hir_of(binding)? {
+                HirNode::EnumVariant(..) | HirNode::ValueParam(..) | HirNode::GenvarDecl(..) => {
+                    let value = cx.constant_value_of(binding, env);
+                    if value.is_error() {
+                        return Err(Error::Reported);
+                    }
+                    value
+                        .get_real()
+                        .or_else(|| value.get_int().and_then(|k| k.to_f64()))
+                }
+                _ => None,
+            }
+        }
+        _ => {
+            let mir = cx.mir_rvalue(expr_id, env);
+            if !mir.is_const() {
+                return Ok(None);
+            }
+            let value = cx.const_mir_rvalue(mir.into());
+            if value.is_error() {
+                return Err(Error::Reported);
+            }
+            value
+                .get_real()
+                .or_else(|| value.get_int().and_then(|k| k.to_f64()))
+        }
+    })
+}
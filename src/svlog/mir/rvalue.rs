@@ -87,6 +87,12 @@ pub enum RvalueKind<'a> {
     /// A cast from a simple bit type to a boolean.
     // TODO: Add SBVT
     CastToBool(&'a Rvalue<'a>),
+    /// A conversion from a real to an integer, rounding to the nearest integer.
+    /// E.g. `real` to `bit signed [63:0]`.
+    CastRealToInt(&'a Rvalue<'a>),
+    /// A conversion from an integer to a real. E.g. `bit signed [63:0]` to
+    /// `real`.
+    CastIntToReal(&'a Rvalue<'a>),
     /// Shrink the width of a vector type. E.g. `bit [31:0]` to `bit [7:0]`.
     // TODO: Add SBVT
     Truncate(usize, &'a Rvalue<'a>),
@@ -197,6 +203,18 @@ pub enum RvalueKind<'a> {
         // TODO: Add SBVT
         arg: &'a Rvalue<'a>,
     },
+    /// A call to `$clog2` with a non-constant argument.
+    Clog2(&'a Rvalue<'a>),
+    /// A call to a real math function with non-constant arguments, evaluated
+    /// by the runtime.
+    MathCall(hir::MathFn, Vec<&'a Rvalue<'a>>),
+    /// A call to a random number function, evaluated by the runtime.
+    RandomCall(hir::RandomFn, Vec<&'a Rvalue<'a>>),
+    /// A call to `$test$plusargs`, evaluated by the runtime.
+    TestPlusargs(Name),
+    /// A call to `$value$plusargs`, evaluated by the runtime. The value read
+    /// is assigned to the target node.
+    ValuePlusargs(Spanned<Name>, NodeId),
     /// An assignment operator.
     Assignment {
         lvalue: &'a Lvalue<'a>,
@@ -225,6 +243,8 @@ impl<'a> RvalueKind<'a> {
             | RvalueKind::Transmute(value)
             | RvalueKind::CastSign(_, value)
             | RvalueKind::CastToBool(value)
+            | RvalueKind::CastRealToInt(value)
+            | RvalueKind::CastIntToReal(value)
             | RvalueKind::Truncate(_, value)
             | RvalueKind::ZeroExtend(_, value)
             | RvalueKind::SignExtend(_, value)
@@ -252,6 +272,11 @@ impl<'a> RvalueKind<'a> {
                 false_value,
             } => cond.is_const() && true_value.is_const() && false_value.is_const(),
             RvalueKind::Shift { value, amount, .. } => value.is_const() && amount.is_const(),
+            RvalueKind::Clog2(..) => false,
+            RvalueKind::MathCall(..) => false,
+            RvalueKind::RandomCall(..) => false,
+            RvalueKind::TestPlusargs(..) => false,
+            RvalueKind::ValuePlusargs(..) => false,
             RvalueKind::Assignment { .. } => false,
             RvalueKind::Error => true,
         }
@@ -30,6 +30,8 @@ impl<'a> WalkVisitor<'a> for Span {}
 impl<'a> WalkVisitor<'a> for ty::UnpackedType<'a> {}
 impl<'a> WalkVisitor<'a> for ty::Sign {}
 impl<'a> WalkVisitor<'a> for ty::Domain {}
+impl<'a> WalkVisitor<'a> for crate::hir::MathFn {}
+impl<'a> WalkVisitor<'a> for crate::hir::RandomFn {}
 impl<'a> WalkVisitor<'a> for value::Value<'_> {}
 
 impl<'a, T: WalkVisitor<'a>> WalkVisitor<'a> for &'_ T {
@@ -5,7 +5,7 @@
 use crate::{
     ast_map::AstNode,
     crate_prelude::*,
-    hir::{NamedParam, PosParam},
+    hir::{HirNode, NamedParam, PosParam},
     ty::UnpackedType,
     value::Value,
 };
@@ -208,6 +208,71 @@ pub(crate) fn compute<'gcx>(
     }
 }
 
+/// Create a parameter environment that overrides value parameters of a module.
+///
+/// This is used to elaborate a top-level module with parameter values given
+/// from the outside, e.g. on the command line during a parameter sweep. Any
+/// parameter not mentioned in `overrides` retains its default value.
+pub fn param_env_from_overrides<'gcx>(
+    cx: &impl Context<'gcx>,
+    module: NodeId,
+    overrides: &[(Name, i64)],
+) -> Result<ParamEnv> {
+    let hir = match cx.hir_of(module)? {
+        HirNode::Module(m) => m,
+        _ => {
+            cx.emit(
+                DiagBuilder2::error("parameter overrides only apply to modules")
+                    .span(cx.span(module)),
+            );
+            return Err(());
+        }
+    };
+    let default_env = cx.default_param_env();
+    let mut values = vec![];
+    for &(name, value) in overrides {
+        let param_id = hir
+            .params
+            .iter()
+            .chain(hir.block.params.iter())
+            .cloned()
+            .find(|&id| match cx.ast_of(id) {
+                Ok(AstNode::ValueParam(_, p)) => p.name.value == name,
+                _ => false,
+            });
+        let param_id = match param_id {
+            Some(id) => id,
+            None => {
+                cx.emit(DiagBuilder2::error(format!(
+                    "no value parameter `{}` in module `{}`",
+                    name, hir.name
+                )));
+                return Err(Error::Reported);
+            }
+        };
+        let ty = cx.type_of(param_id, default_env)?;
+        if ty.get_bit_size().is_none() {
+            cx.emit(
+                DiagBuilder2::error(format!(
+                    "parameter `{}` of type `{}` cannot be overridden with an integer",
+                    name, ty
+                ))
+                .span(cx.span(param_id)),
+            );
+            return Err(Error::Reported);
+        }
+        let value = cx.intern_value(value::make_int(ty, value.into()));
+        values.push((param_id, ParamEnvBinding::Direct(value)));
+    }
+    let env = cx.intern_param_env(ParamEnvData {
+        module: Some(module),
+        values,
+        ..Default::default()
+    });
+    cx.add_param_env_context(env, module);
+    Ok(env)
+}
+
 fn param_env_from_instance<'a>(
     cx: &impl Context<'a>,
     node: &'a dyn ast::AnyNode<'a>,
@@ -228,7 +293,7 @@ fn param_env_from_instance<'a>(
                     DiagBuilder2::error(format!("{} only has {} parameter(s)", node, params.len()))
                         .span(span),
                 );
-                Err(())
+                Err(Error::Reported)
             }
         })
         .chain(named.iter().map(|&(_span, name, assign_id)| {
@@ -238,7 +303,7 @@ fn param_env_from_instance<'a>(
                     Ok(AstNode::TypeParam(_, p)) => Some((p.name.value, id)),
                     Ok(AstNode::ValueParam(_, p)) => Some((p.name.value, id)),
                     Ok(_) => unreachable!(),
-                    Err(()) => None,
+                    Err(_) => None,
                 })
                 .collect();
             match names
@@ -259,7 +324,7 @@ fn param_env_from_instance<'a>(
                                     .join(", ")
                             )),
                     );
-                    Err(())
+                    Err(Error::Reported)
                 }
             }
         }));
@@ -56,7 +56,7 @@ pub(crate) fn map_pattern<'a>(
     // First determine the type the pattern will have.
     let ty = cx.need_type_context(expr.id, env);
     if ty.is_error() {
-        return Err(());
+        return Err(Error::Reported);
     }
     let ty = ty.ty();
 
@@ -77,7 +77,7 @@ pub(crate) fn map_pattern<'a>(
                         ))
                         .span(cx.span(count)),
                     );
-                    return Err(());
+                    return Err(Error::Reported);
                 }
             };
             map_positional_pattern(cx, mapping, const_count, ty, expr.span)?
@@ -96,7 +96,7 @@ pub(crate) fn map_pattern<'a>(
                     .span(expr.span)
                     .add_note("Named patterns can only construct arrays or structs."),
                 );
-                return Err(());
+                return Err(Error::Reported);
             }
         }
         _ => bug_span!(expr.span, cx, "expression is not a pattern"),
@@ -172,13 +172,13 @@ fn map_named_array_pattern<'a>(
                     let index = cx.constant_value_of(member_id, env);
                     let index = match &index.kind {
                         ValueKind::Int(i, ..) => i - num::BigInt::from(offset),
-                        ValueKind::Error => return Err(()),
+                        ValueKind::Error => return Err(Error::Reported),
                         _ => {
                             cx.emit(
                                 DiagBuilder2::error("array index must be a constant integer")
                                     .span(cx.span(member_id)),
                             );
-                            return Err(());
+                            return Err(Error::Reported);
                         }
                     };
                     let index = match index.to_isize() {
@@ -188,7 +188,7 @@ fn map_named_array_pattern<'a>(
                                 DiagBuilder2::error(format!("index `{}` out of bounds", index))
                                     .span(cx.span(member_id)),
                             );
-                            return Err(());
+                            return Err(Error::Reported);
                         }
                     };
                     Ok(index)
@@ -245,7 +245,7 @@ fn map_named_array_pattern<'a>(
                     .span(span)
                     .add_note("Array patterns must assign a value to every index."),
             );
-            return Err(());
+            return Err(Error::Reported);
         };
         (0..length)
             .map(|i| {
@@ -260,7 +260,7 @@ fn map_named_array_pattern<'a>(
     };
 
     if failed {
-        Err(())
+        Err(Error::Reported)
     } else {
         Ok(values)
     }
@@ -358,7 +358,7 @@ fn map_named_struct_pattern<'a>(
                     failed = true;
                     continue;
                 }
-                Err(()) => {
+                Err(_) => {
                     failed = true;
                     continue;
                 }
@@ -436,7 +436,7 @@ fn map_named_struct_pattern<'a>(
     }
 
     if failed {
-        Err(())
+        Err(Error::Reported)
     } else {
         Ok((0..values.len()).map(|i| values[&i]).collect())
     }
@@ -487,7 +487,7 @@ fn map_positional_pattern<'a>(
                     ))
                     .span(span),
                 );
-                return Err(());
+                return Err(Error::Reported);
             }
         }
     } else if let Some(strukt) = ty.get_struct() {
@@ -515,7 +515,7 @@ fn map_positional_pattern<'a>(
             ))
             .span(span),
         );
-        return Err(());
+        return Err(Error::Reported);
     }
 
     Ok(result)
@@ -83,7 +83,7 @@ pub(crate) fn port_mapping<'a>(
                     ))
                     .span(span),
                 );
-                Err(())
+                Err(Error::Reported)
             }
         }
     });
@@ -103,7 +103,7 @@ pub(crate) fn port_mapping<'a>(
                         ))
                         .add_note(format!("Remove `.{}(...)`", name)),
                 );
-                return Err(());
+                return Err(Error::Reported);
             }
         };
         match names.get(&name.value) {
@@ -122,7 +122,7 @@ pub(crate) fn port_mapping<'a>(
                                 .format(", ")
                         )),
                 );
-                Err(())
+                Err(Error::Reported)
             }
         }
     });
@@ -133,7 +133,7 @@ pub(crate) fn port_mapping<'a>(
         .filter_map(|err| match err {
             Ok((port, Some(assign_id))) => Some(Ok((Ref(port), assign_id.env(outer_env)))),
             Ok(_) => None,
-            Err(()) => Some(Err(())),
+            Err(e) => Some(Err(e)),
         })
         .collect();
 
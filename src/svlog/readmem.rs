@@ -0,0 +1,139 @@
+// Copyright (c) 2016-2020 Fabian Schuiki
+
+//! Memory files as read by the `$readmemh` and `$readmemb` system tasks.
+//!
+//! A memory file consists of words separated by white space, with `//` and
+//! `/* */` comments. Words are given in hexadecimal for `$readmemh` and in
+//! binary for `$readmemb`, and may contain `_` separators as well as `x` and
+//! `z` digits. An address entry of the form `@hhhh` moves the load to the
+//! given hexadecimal address.
+
+use num::{BigInt, ToPrimitive};
+
+/// An entry of a memory file.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Entry {
+    /// An `@` address entry.
+    Address(usize),
+    /// A data word. Unknown and high-impedance digits are read as zero.
+    Word(BigInt),
+}
+
+/// Parse the contents of a memory file.
+///
+/// The `radix` is 16 for `$readmemh` and 2 for `$readmemb`. Returns an error
+/// message mentioning the offending line if the file is malformed.
+pub fn parse(text: &str, radix: u32) -> Result<Vec<Entry>, String> {
+    let mut entries = vec![];
+    let mut in_comment = false;
+    for (index, line) in text.lines().enumerate() {
+        let mut rest = line;
+        while !rest.is_empty() {
+            // Skip over block comments, which may span multiple lines.
+            if in_comment {
+                match rest.find("*/") {
+                    Some(end) => {
+                        in_comment = false;
+                        rest = &rest[end + 2..];
+                        continue;
+                    }
+                    None => break,
+                }
+            }
+            rest = rest.trim_start();
+            if rest.starts_with("//") {
+                break;
+            }
+            if rest.starts_with("/*") {
+                in_comment = true;
+                rest = &rest[2..];
+                continue;
+            }
+
+            // Extract the next token.
+            let len = rest
+                .find(|c: char| c.is_whitespace() || c == '/')
+                .unwrap_or(rest.len());
+            let token = &rest[..len];
+            rest = &rest[len..];
+            if token.is_empty() {
+                return Err(format!("line {}: unexpected `/`", index + 1));
+            }
+            let entry = if token.starts_with('@') {
+                parse_number(&token[1..], 16)
+                    .and_then(|addr| addr.to_usize())
+                    .map(Entry::Address)
+            } else {
+                parse_number(token, radix).map(Entry::Word)
+            };
+            match entry {
+                Some(entry) => entries.push(entry),
+                None => return Err(format!("line {}: invalid entry `{}`", index + 1, token)),
+            }
+        }
+    }
+    Ok(entries)
+}
+
+/// Parse a number in a memory file, reading `x` and `z` digits as zero.
+fn parse_number(text: &str, radix: u32) -> Option<BigInt> {
+    let digits: String = text
+        .chars()
+        .filter(|&c| c != '_')
+        .map(|c| match c {
+            'x' | 'X' | 'z' | 'Z' | '?' => '0',
+            c => c,
+        })
+        .collect();
+    if digits.is_empty() || !digits.chars().all(|c| c.is_digit(radix)) {
+        return None;
+    }
+    BigInt::parse_bytes(digits.as_bytes(), radix)
+}
+
+/// Assign the words of a memory file to addresses.
+///
+/// Loading starts at address `start` and proceeds towards address `end`,
+/// which may be lower than `start`. Address entries must lie within the range.
+/// Returns the words together with their address, or an error message if the
+/// file does not fit into the range.
+pub fn assign(
+    entries: &[Entry],
+    start: usize,
+    end: usize,
+) -> Result<Vec<(usize, &BigInt)>, String> {
+    let (lo, hi) = (start.min(end), start.max(end));
+    let mut words = vec![];
+    let mut next = Some(start);
+    for entry in entries {
+        match *entry {
+            Entry::Address(addr) if addr < lo || addr > hi => {
+                return Err(format!(
+                    "address {:x} is outside the range {:x} to {:x}",
+                    addr, start, end
+                ));
+            }
+            Entry::Address(addr) => next = Some(addr),
+            Entry::Word(ref word) => {
+                let addr = match next {
+                    Some(addr) => addr,
+                    None => {
+                        return Err(format!(
+                            "more words than fit into the range {:x} to {:x}",
+                            start, end
+                        ))
+                    }
+                };
+                words.push((addr, word));
+                next = if addr == end {
+                    None
+                } else if start <= end {
+                    Some(addr + 1)
+                } else {
+                    Some(addr - 1)
+                };
+            }
+        }
+    }
+    Ok(words)
+}
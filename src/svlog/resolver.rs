@@ -127,7 +127,7 @@ pub(crate) fn local_rib<'gcx>(cx: &impl Context<'gcx>, node_id: NodeId) -> Resul
                         DiagBuilder2::error(format!("`{}` not found", import.pkg.value))
                             .span(import.pkg.span),
                     );
-                    return Err(());
+                    return Err(Error::Reported);
                 }
             };
             if let Some(name) = import.name {
@@ -329,7 +329,7 @@ pub(crate) fn resolve_node<'gcx>(
     }
     error!("{:#?}", hir);
     cx.emit(DiagBuilder2::bug("cannot resolve node").span(hir.human_span()));
-    Err(())
+    Err(Error::Reported)
 }
 
 #[derive(Debug, Clone, PartialEq, Eq)]
@@ -358,7 +358,7 @@ pub(crate) fn struct_def<'gcx>(cx: &impl Context<'gcx>, node_id: NodeId) -> Resu
                 DiagBuilder2::error(format!("{} is not a struct", hir.desc_full()))
                     .span(hir.human_span()),
             );
-            return Err(());
+            return Err(Error::Reported);
         }
     };
     let fields = struct_fields
@@ -407,7 +407,7 @@ pub(crate) fn resolve_field_access<'a>(
             d = d.add_note(format!("`{}` is defined as `{}`", ty, ty.resolve_full()));
         }
         cx.emit(d);
-        return Err(());
+        return Err(Error::Reported);
     };
 
     let index = strukt
@@ -423,7 +423,7 @@ pub(crate) fn resolve_field_access<'a>(
                     .add_note(format!("`{}` was defined here:", ty))
                     .span(strukt.ast.span()),
             );
-            Err(())
+            Err(Error::Reported)
         }
     }
 }
@@ -1200,7 +1200,7 @@ pub(crate) fn resolve_local_or_error<'a>(
         }
         None => {
             cx.emit(DiagBuilder2::error(format!("`{}` not found", name.value)).span(name.span));
-            Err(())
+            Err(Error::Reported)
         }
     }
 }
@@ -1254,7 +1254,7 @@ pub(crate) fn resolve_namespace_or_error<'a>(
                     .add_note(format!("{} was defined here:", inside))
                     .span(inside.human_span()),
             );
-            Err(())
+            Err(Error::Reported)
         }
     }
 }
@@ -1308,7 +1308,7 @@ pub(crate) fn resolve_hierarchical_or_error<'a>(
                     .add_note(format!("{} was defined here:", inside))
                     .span(inside.human_span()),
             );
-            Err(())
+            Err(Error::Reported)
         }
     }
 }
@@ -1340,7 +1340,7 @@ pub(crate) fn resolve_imported_scope<'a>(
                 DiagBuilder2::error(format!("name `{}` does not refer to a package", node.pkg))
                     .span(node.pkg.span),
             );
-            Err(())
+            Err(Error::Reported)
         }
     }
 }
@@ -1441,6 +1441,204 @@ where
     }
 }
 
+/// Find all uses of the declaration whose name is at `decl`.
+///
+/// Resolves the names in `root` and returns the spans of those that refer to
+/// the declaration in source order, preceded by `decl` itself. Besides
+/// identifiers and named types, this covers the targets of instances, the
+/// port names in named port connections, and the last part of hierarchical
+/// names such as `pkg::a`, `inst.a`, or `gen_block.a`. Since the names are
+/// resolved in their scope, a declaration of the same name that shadows `decl`
+/// in an inner scope, or that lives in an unrelated scope, is not mistaken for
+/// it.
+pub fn find_decl_uses<'a>(
+    cx: &impl Context<'a>,
+    root: &'a dyn ast::AnyNode<'a>,
+    decl: Span,
+) -> Vec<Span> {
+    let mut uv = UseVisitor {
+        cx,
+        decl,
+        uses: vec![],
+    };
+    root.accept(&mut uv);
+    let mut uses = uv.uses;
+    uses.sort();
+    uses.dedup();
+    uses.insert(0, decl);
+    uses
+}
+
+/// A visitor that collects the names which resolve to a declaration.
+struct UseVisitor<'cx, C> {
+    cx: &'cx C,
+    decl: Span,
+    uses: Vec<Span>,
+}
+
+impl<'a, 'cx, C> UseVisitor<'cx, C>
+where
+    C: Context<'a>,
+    'a: 'cx,
+{
+    /// Record `name` as a use if it resolves to the declaration.
+    fn record(&mut self, name: Spanned<Name>, def: Option<&'a Def<'a>>) {
+        if let Some(def) = def {
+            if def.name.span == self.decl && name.span != self.decl {
+                self.uses.push(name.span);
+            }
+        }
+    }
+
+    /// Record `ident` as a use if it resolves to the declaration in the scope
+    /// of `node`.
+    fn check(&mut self, ident: Spanned<Name>, node: &'a dyn ast::AnyNode<'a>) {
+        let loc = self.cx.scope_location(node);
+        let def = self
+            .cx
+            .resolve_local(ident.value, loc, false)
+            .ok()
+            .and_then(|x| x);
+        self.record(ident, def);
+    }
+
+    /// Record `name` as a use if it resolves to the declaration within
+    /// `scope`, as the last part of a hierarchical name.
+    fn check_in(&mut self, name: Spanned<Name>, scope: &'a dyn ScopedNode<'a>) {
+        let def = self.cx.resolve_hierarchical(name.value, scope);
+        self.record(name, def);
+    }
+
+    /// Determine the scope that a hierarchical name selects from, if the head
+    /// of the name refers to a package, an instance, or a generate block.
+    fn scope_of(&self, head: &'a ast::Expr<'a>) -> Option<&'a dyn ScopedNode<'a>> {
+        let def = match head.data {
+            ast::IdentExpr(ident) => {
+                let loc = self.cx.scope_location(head);
+                self.cx.resolve_local(ident.value, loc, false).ok()??
+            }
+            ast::MemberExpr { ref expr, name } | ast::ScopeExpr(ref expr, name) => self
+                .cx
+                .resolve_hierarchical(name.value, self.scope_of(expr)?)?,
+            _ => return None,
+        };
+        self.def_scope(def)
+    }
+
+    /// Determine the scope that a definition opens to hierarchical names.
+    ///
+    /// Instances open the scope of the instantiated module or interface.
+    fn def_scope(&self, def: &'a Def<'a>) -> Option<&'a dyn ScopedNode<'a>> {
+        let node = match def.node {
+            DefNode::Ast(node) => node.as_all(),
+            DefNode::IntPort(_) => return None,
+        };
+        if let Some(inst) = node.get_inst_name() {
+            return self.inst_scope(inst);
+        }
+        if node.is_package() || node.is_generate_block() {
+            node.get_scoped_node()
+        } else {
+            None
+        }
+    }
+
+    /// Determine the scope of the module or interface an instance refers to.
+    fn inst_scope(&self, inst: &'a ast::InstName<'a>) -> Option<&'a dyn ScopedNode<'a>> {
+        let target = self.cx.resolve_inst_target(inst.inst()).ok()?;
+        target.as_any().as_all().get_scoped_node()
+    }
+}
+
+impl<'a, 'cx, C> ast::Visitor<'a> for UseVisitor<'cx, C>
+where
+    C: Context<'a>,
+    'a: 'cx,
+{
+    fn pre_visit_expr(&mut self, node: &'a ast::Expr<'a>) -> bool {
+        // The left-hand side of named pattern fields refers to a field name.
+        if let Some(patfield) = node
+            .get_parent()
+            .and_then(|p| p.as_all().get_pattern_field())
+        {
+            match patfield.data {
+                ast::PatternFieldData::Member(ref name_expr, ..) if name_expr.as_ref() == node => {
+                    return false;
+                }
+                _ => (),
+            }
+        }
+
+        match node.data {
+            ast::IdentExpr(ident) => {
+                self.check(ident, node);
+                false
+            }
+            // Visit the head of hierarchical names as well.
+            ast::MemberExpr { ref expr, name } | ast::ScopeExpr(ref expr, name) => {
+                if let Some(scope) = self.scope_of(expr) {
+                    self.check_in(name, scope);
+                }
+                true
+            }
+            _ => true,
+        }
+    }
+
+    fn pre_visit_type(&mut self, node: &'a ast::Type<'a>) -> bool {
+        match node.kind.data {
+            ast::NamedType(ident) => {
+                self.check(ident, node);
+                false
+            }
+            ast::ScopedType {
+                ref ty,
+                member: false,
+                name,
+            } => {
+                if let ast::NamedType(pkg) = ty.kind.data {
+                    let loc = self.cx.scope_location(node);
+                    let scope = self
+                        .cx
+                        .resolve_local(pkg.value, loc, false)
+                        .ok()
+                        .and_then(|x| x)
+                        .and_then(|def| self.def_scope(def));
+                    if let Some(scope) = scope {
+                        self.check_in(name, scope);
+                    }
+                }
+                true
+            }
+            _ => true,
+        }
+    }
+
+    fn pre_visit_inst(&mut self, node: &'a ast::Inst<'a>) -> bool {
+        self.check(node.target, node);
+        true
+    }
+
+    fn pre_visit_port_conn(&mut self, node: &'a ast::PortConn<'a>) -> bool {
+        if let ast::PortConnData::Named(name, ref mode) = node.data {
+            // The name refers to a port of the instantiated module.
+            let scope = node
+                .get_parent()
+                .and_then(|p| p.as_all().get_inst_name())
+                .and_then(|inst| self.inst_scope(inst));
+            if let Some(scope) = scope {
+                self.check_in(name, scope);
+            }
+
+            // The `.name` shorthand also refers to the local signal.
+            if let ast::PortConnMode::Auto = *mode {
+                self.check(name, node);
+            }
+        }
+        true
+    }
+}
+
 /// Any AST node that can be instantiated.
 #[derive(Debug, Clone, Copy)]
 pub enum InstTarget<'a> {
@@ -1466,6 +1664,12 @@ pub(crate) fn resolve_inst_target<'a>(
     cx: &impl Context<'a>,
     inst: &'a ast::Inst<'a>,
 ) -> Result<InstTarget<'a>> {
+    // Use the module the active configuration binds the instance to, if any.
+    if let Some(module) = cx.gcx().config_binding(inst) {
+        trace!("Instance `{}` bound by configuration", inst.target);
+        return Ok(InstTarget::Module(module));
+    }
+
     // Resolve the name of the instantiated module.
     let loc = cx.scope_location(inst);
     let def = cx.resolve_local_or_error(inst.target, loc, false)?;
@@ -1489,7 +1693,7 @@ pub(crate) fn resolve_inst_target<'a>(
                     .add_note(format!("{} was declared here:", def.node))
                     .span(def.node.span()),
             );
-            Err(())
+            Err(Error::Reported)
         }
     }
 }
@@ -72,7 +72,7 @@ pub(crate) fn disamb_type_or_expr<'a>(
                     let loc = cx.scope_location(target.as_ref());
                     let def = match cx.resolve_local_or_error(pkg_name, loc, false) {
                         Ok(def) => def,
-                        _ => return Err(()),
+                        _ => return Err(Error::Reported),
                     };
 
                     // See if the binding is a package.
@@ -89,14 +89,14 @@ pub(crate) fn disamb_type_or_expr<'a>(
                                     .add_note(format!("`{}` was declared here:", pkg_name))
                                     .span(def.node.span()),
                             );
-                            return Err(());
+                            return Err(Error::Reported);
                         }
                     };
 
                     // Resolve the type name within the package.
                     let def = match cx.resolve_hierarchical_or_error(name, pkg) {
                         Ok(def) => def,
-                        _ => return Err(()),
+                        _ => return Err(Error::Reported),
                     };
                     match cx.disamb_kind(Ref(&def.node)) {
                         Kind::Value => Ok(ast),
@@ -139,7 +139,7 @@ pub(crate) fn disamb_type_or_expr<'a>(
                         ))
                         .span(expr.span()),
                     );
-                    return Err(());
+                    return Err(Error::Reported);
                 }
             },
             _ => Ok(ast),
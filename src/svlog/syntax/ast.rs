@@ -416,6 +416,27 @@ where
     }
 }
 
+impl<'a, 'b: 'a, T> AcceptVisitorMut<'a> for Node<'b, T>
+where
+    T: AcceptVisitorMut<'a>,
+{
+    fn accept_mut(&mut self, visitor: &mut dyn VisitorMut<'a>) {
+        self.data.accept_mut(visitor)
+    }
+}
+
+impl<'a, 'b: 'a, T> AcceptRewriter<'a> for Node<'b, T>
+where
+    T: AcceptRewriter<'a>,
+{
+    fn accept_rewrite(self, rewriter: &mut dyn Rewriter<'a>) -> Self {
+        Node {
+            data: self.data.accept_rewrite(rewriter),
+            ..self
+        }
+    }
+}
+
 impl<'a, T> std::ops::Deref for Node<'a, T> {
     type Target = T;
 
@@ -559,6 +580,227 @@ impl<'a> WalkVisitor<'a> for usize {
     fn walk(&'a self, visitor: &mut dyn Visitor<'a>) {}
 }
 
+/// A node that accepts `VisitorMut`s.
+pub trait AcceptVisitorMut<'a> {
+    /// Walk a mutable visitor over the contents of `self`.
+    fn accept_mut(&mut self, visitor: &mut dyn VisitorMut<'a>);
+}
+
+/// A node that walks a `VisitorMut` over itself.
+pub trait WalkVisitorMut<'a> {
+    /// Walk a mutable visitor over `self`.
+    fn walk_mut(&mut self, visitor: &mut dyn VisitorMut<'a>);
+}
+
+/// Nodes behind a shared reference, such as the ones allocated in an arena,
+/// cannot be changed and are skipped.
+impl<'a, T> AcceptVisitorMut<'a> for &'_ T {
+    fn accept_mut(&mut self, visitor: &mut dyn VisitorMut<'a>) {}
+}
+
+impl<'a, T> AcceptVisitorMut<'a> for Vec<T>
+where
+    T: AcceptVisitorMut<'a>,
+{
+    fn accept_mut(&mut self, visitor: &mut dyn VisitorMut<'a>) {
+        for c in self {
+            c.accept_mut(visitor);
+        }
+    }
+}
+
+impl<'a, T> AcceptVisitorMut<'a> for Option<T>
+where
+    T: AcceptVisitorMut<'a>,
+{
+    fn accept_mut(&mut self, visitor: &mut dyn VisitorMut<'a>) {
+        if let Some(c) = self {
+            c.accept_mut(visitor);
+        }
+    }
+}
+
+impl<'a, T> AcceptVisitorMut<'a> for Spanned<T>
+where
+    T: AcceptVisitorMut<'a>,
+{
+    fn accept_mut(&mut self, visitor: &mut dyn VisitorMut<'a>) {
+        self.value.accept_mut(visitor);
+    }
+}
+
+/// Nodes behind a shared reference, such as the ones allocated in an arena,
+/// cannot be changed and are skipped.
+impl<'a, T> WalkVisitorMut<'a> for &'_ T {
+    fn walk_mut(&mut self, visitor: &mut dyn VisitorMut<'a>) {}
+}
+
+impl<'a, T> WalkVisitorMut<'a> for Vec<T>
+where
+    T: WalkVisitorMut<'a>,
+{
+    fn walk_mut(&mut self, visitor: &mut dyn VisitorMut<'a>) {
+        for c in self {
+            c.walk_mut(visitor);
+        }
+    }
+}
+
+impl<'a, T> WalkVisitorMut<'a> for Option<T>
+where
+    T: WalkVisitorMut<'a>,
+{
+    fn walk_mut(&mut self, visitor: &mut dyn VisitorMut<'a>) {
+        if let Some(c) = self {
+            c.walk_mut(visitor);
+        }
+    }
+}
+
+impl<'a, T> WalkVisitorMut<'a> for Spanned<T>
+where
+    T: WalkVisitorMut<'a>,
+{
+    fn walk_mut(&mut self, visitor: &mut dyn VisitorMut<'a>) {
+        self.value.walk_mut(visitor);
+    }
+}
+
+impl<'a, T> WalkVisitorMut<'a> for Box<T>
+where
+    T: WalkVisitorMut<'a>,
+{
+    fn walk_mut(&mut self, visitor: &mut dyn VisitorMut<'a>) {
+        self.as_mut().walk_mut(visitor);
+    }
+}
+
+impl<'a> WalkVisitorMut<'a> for Span {
+    fn walk_mut(&mut self, visitor: &mut dyn VisitorMut<'a>) {}
+}
+
+impl<'a> WalkVisitorMut<'a> for Name {
+    fn walk_mut(&mut self, visitor: &mut dyn VisitorMut<'a>) {}
+}
+
+impl<'a> WalkVisitorMut<'a> for Identifier {
+    fn walk_mut(&mut self, visitor: &mut dyn VisitorMut<'a>) {}
+}
+
+impl<'a> WalkVisitorMut<'a> for Lit {
+    fn walk_mut(&mut self, visitor: &mut dyn VisitorMut<'a>) {}
+}
+
+impl<'a> WalkVisitorMut<'a> for Op {
+    fn walk_mut(&mut self, visitor: &mut dyn VisitorMut<'a>) {}
+}
+
+impl<'a> WalkVisitorMut<'a> for bool {
+    fn walk_mut(&mut self, visitor: &mut dyn VisitorMut<'a>) {}
+}
+
+impl<'a> WalkVisitorMut<'a> for usize {
+    fn walk_mut(&mut self, visitor: &mut dyn VisitorMut<'a>) {}
+}
+
+/// A node that accepts `Rewriter`s.
+pub trait AcceptRewriter<'a> {
+    /// Rebuild `self` from its children rewritten by a rewriter.
+    fn accept_rewrite(self, rewriter: &mut dyn Rewriter<'a>) -> Self;
+}
+
+/// A node that walks a `Rewriter` over itself.
+pub trait WalkRewriter<'a> {
+    /// Walk a rewriter over `self`, returning the rewritten node.
+    fn walk_rewrite(self, rewriter: &mut dyn Rewriter<'a>) -> Self;
+}
+
+/// Nodes behind a shared reference, such as the ones allocated in an arena,
+/// cannot be rebuilt and are passed through unchanged.
+impl<'a, T> WalkRewriter<'a> for &'_ T {
+    fn walk_rewrite(self, rewriter: &mut dyn Rewriter<'a>) -> Self {
+        self
+    }
+}
+
+impl<'a, T> WalkRewriter<'a> for Vec<T>
+where
+    T: WalkRewriter<'a>,
+{
+    fn walk_rewrite(self, rewriter: &mut dyn Rewriter<'a>) -> Self {
+        self.into_iter().map(|c| c.walk_rewrite(rewriter)).collect()
+    }
+}
+
+impl<'a, T> WalkRewriter<'a> for Option<T>
+where
+    T: WalkRewriter<'a>,
+{
+    fn walk_rewrite(self, rewriter: &mut dyn Rewriter<'a>) -> Self {
+        self.map(|c| c.walk_rewrite(rewriter))
+    }
+}
+
+impl<'a, T> WalkRewriter<'a> for Spanned<T>
+where
+    T: WalkRewriter<'a>,
+{
+    fn walk_rewrite(self, rewriter: &mut dyn Rewriter<'a>) -> Self {
+        self.map(|c| c.walk_rewrite(rewriter))
+    }
+}
+
+impl<'a, T> WalkRewriter<'a> for Box<T>
+where
+    T: WalkRewriter<'a>,
+{
+    fn walk_rewrite(self, rewriter: &mut dyn Rewriter<'a>) -> Self {
+        Box::new((*self).walk_rewrite(rewriter))
+    }
+}
+
+impl<'a> WalkRewriter<'a> for Span {
+    fn walk_rewrite(self, rewriter: &mut dyn Rewriter<'a>) -> Self {
+        self
+    }
+}
+
+impl<'a> WalkRewriter<'a> for Name {
+    fn walk_rewrite(self, rewriter: &mut dyn Rewriter<'a>) -> Self {
+        self
+    }
+}
+
+impl<'a> WalkRewriter<'a> for Identifier {
+    fn walk_rewrite(self, rewriter: &mut dyn Rewriter<'a>) -> Self {
+        self
+    }
+}
+
+impl<'a> WalkRewriter<'a> for Lit {
+    fn walk_rewrite(self, rewriter: &mut dyn Rewriter<'a>) -> Self {
+        self
+    }
+}
+
+impl<'a> WalkRewriter<'a> for Op {
+    fn walk_rewrite(self, rewriter: &mut dyn Rewriter<'a>) -> Self {
+        self
+    }
+}
+
+impl<'a> WalkRewriter<'a> for bool {
+    fn walk_rewrite(self, rewriter: &mut dyn Rewriter<'a>) -> Self {
+        self
+    }
+}
+
+impl<'a> WalkRewriter<'a> for usize {
+    fn walk_rewrite(self, rewriter: &mut dyn Rewriter<'a>) -> Self {
+        self
+    }
+}
+
 macro_rules! tuple_impls {
     ($($idx:tt => $args:ident),*) => {
         impl<'a $(, $args: AcceptVisitor<'a>)*> AcceptVisitor<'a> for ($($args),*) {
@@ -578,6 +820,24 @@ macro_rules! tuple_impls {
                 $(self.$idx.for_each_node(each);)*
             }
         }
+
+        impl<'a $(, $args: AcceptVisitorMut<'a>)*> AcceptVisitorMut<'a> for ($($args),*) {
+            fn accept_mut(&mut self, visitor: &mut dyn VisitorMut<'a>) {
+                $(self.$idx.accept_mut(visitor);)*
+            }
+        }
+
+        impl<'a $(, $args: WalkVisitorMut<'a>)*> WalkVisitorMut<'a> for ($($args),*) {
+            fn walk_mut(&mut self, visitor: &mut dyn VisitorMut<'a>) {
+                $(self.$idx.walk_mut(visitor);)*
+            }
+        }
+
+        impl<'a $(, $args: WalkRewriter<'a>)*> WalkRewriter<'a> for ($($args),*) {
+            fn walk_rewrite(self, rewriter: &mut dyn Rewriter<'a>) -> Self {
+                ($(self.$idx.walk_rewrite(rewriter)),*)
+            }
+        }
     };
 }
 
@@ -615,6 +875,36 @@ pub struct Root<'a> {
 pub struct SourceFile<'a> {
     pub timeunits: Timeunit,
     pub items: Vec<Item<'a>>,
+    /// The attribute instances in the file.
+    pub attrs: Vec<AttrInst>,
+}
+
+/// An attribute instance, such as `(* keep, ram_style = "block" *)`.
+///
+/// The lexer skips attribute instances like whitespace, so they are collected
+/// per source file rather than stored in the nodes they are attached to. An
+/// instance is attached to the declaration, item, or statement beginning at
+/// its `target` token. Use `attr::attributes_of` to find the attributes of a
+/// node.
+#[moore_derive::visit]
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct AttrInst {
+    /// The span of the instance, from `(*` to `*)`.
+    pub span: Span,
+    /// The first token after the instance.
+    pub target: Span,
+    /// The attributes in the instance.
+    pub specs: Vec<AttrSpec>,
+}
+
+/// A single attribute of an attribute instance, such as `ram_style = "block"`.
+#[moore_derive::visit]
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct AttrSpec {
+    /// The name of the attribute.
+    pub name: Spanned<Name>,
+    /// The constant expression assigned to the attribute, if any.
+    pub value: Option<Span>,
 }
 
 /// An item that may appear in a hierarchical scope.
@@ -635,6 +925,7 @@ pub enum Item<'a> {
     ModuleDecl(#[forward] Module<'a>),
     InterfaceDecl(#[forward] Interface<'a>),
     PackageDecl(#[forward] Package<'a>),
+    ConfigDecl(#[forward] Config<'a>),
     ClassDecl(#[forward] ClassDecl<'a>),
     ProgramDecl(()),
     ImportDecl(#[forward] ImportDecl<'a>),
@@ -652,6 +943,9 @@ pub enum Item<'a> {
     GenerateIf(#[forward] GenerateIf<'a>),
     GenerateCase(#[forward] GenerateCase<'a>),
     Assertion(Assertion<'a>),
+    ElabTask(#[forward] ElabTask<'a>),
+    SequenceDecl(SequenceDecl<'a>),
+    PropertyDecl(PropertyDecl<'a>),
     NetDecl(NetDecl<'a>),
     VarDecl(#[forward] VarDecl<'a>),
     Inst(Inst<'a>),
@@ -699,6 +993,73 @@ pub struct Package<'a> {
     pub items: Vec<Item<'a>>,
 }
 
+/// A configuration.
+///
+/// ```text
+/// "config" ident ";"
+///   "design" {[lib "."] cell} ";"
+///   {config_rule}
+/// "endconfig" [":" ident]
+/// ```
+#[moore_derive::node]
+#[indefinite("configuration")]
+#[definite("configuration `{}`", name)]
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Config {
+    #[name]
+    pub name: Spanned<Name>,
+    /// The top-level cells of the design.
+    pub design: Vec<CellRef>,
+    /// The rules binding cells and instances, in source order.
+    pub rules: Vec<ConfigRule>,
+}
+
+/// A cell in a configuration, optionally qualified with a library.
+///
+/// For example `lib.cell` or `cell`.
+#[moore_derive::visit]
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct CellRef {
+    pub lib: Option<Spanned<Name>>,
+    pub cell: Spanned<Name>,
+}
+
+/// A rule in a configuration.
+///
+/// ```text
+/// ("default" | "instance" path | "cell" [lib "."] cell) (liblist | use) ";"
+/// ```
+#[moore_derive::visit]
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ConfigRule {
+    pub span: Span,
+    pub clause: ConfigClause,
+    pub action: ConfigAction,
+}
+
+/// The instances or cells a configuration rule applies to.
+#[moore_derive::visit]
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ConfigClause {
+    /// A `default` clause, applying to all instances.
+    Default,
+    /// An `instance top.a.b` clause, applying to one instance in the design.
+    Instance(Vec<Spanned<Name>>),
+    /// A `cell [lib.]cell` clause, applying to all instances of a cell.
+    Cell(CellRef),
+}
+
+/// How a configuration rule binds the instances it applies to.
+#[moore_derive::visit]
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ConfigAction {
+    /// A `liblist a b` clause, searching the libraries in order.
+    Liblist(Vec<Spanned<Name>>),
+    /// A `use [lib.]cell [: config]` clause, binding to a specific cell. The
+    /// flag indicates whether the cell is a configuration.
+    Use(CellRef, bool),
+}
+
 /// Lifetime specifier for variables, tasks, and functions. Defaults to static.
 #[moore_derive::visit]
 #[derive(Debug, PartialEq, Eq, Clone)]
@@ -1851,12 +2212,12 @@ pub enum BlockingAssertion<'a> {
 #[moore_derive::visit]
 #[derive(Debug, Clone, PartialEq, Eq)]
 pub enum ConcurrentAssertion<'a> {
-    AssertProperty(PropSpec, AssertionActionBlock<'a>),
-    AssumeProperty(PropSpec, AssertionActionBlock<'a>),
-    CoverProperty(PropSpec, Stmt<'a>),
+    AssertProperty(PropSpec<'a>, AssertionActionBlock<'a>),
+    AssumeProperty(PropSpec<'a>, AssertionActionBlock<'a>),
+    CoverProperty(PropSpec<'a>, Stmt<'a>),
     CoverSequence,
-    ExpectProperty(PropSpec, AssertionActionBlock<'a>),
-    RestrictProperty(PropSpec),
+    ExpectProperty(PropSpec<'a>, AssertionActionBlock<'a>),
+    RestrictProperty(PropSpec<'a>),
 }
 
 #[moore_derive::visit]
@@ -1881,16 +2242,40 @@ pub enum SeqExprData<'a> {
     BinOp(SeqBinOp, Box<SeqExpr<'a>>, Box<SeqExpr<'a>>),
     Throughout(Expr<'a>, Box<SeqExpr<'a>>),
     Clocked(EventExpr<'a>, Box<SeqExpr<'a>>),
+    /// A cycle delay, e.g. `a ##[1:3] b`, or `##1 b` if there is no sequence
+    /// before the delay.
+    Delay(Option<Box<SeqExpr<'a>>>, SeqRange<'a>, Box<SeqExpr<'a>>),
+    /// A parenthesized sequence with local variable assignments performed
+    /// when it matches, and an optional repetition, e.g. `(a, x = b)[*2]`.
+    Paren(
+        Box<SeqExpr<'a>>,
+        Vec<(Expr<'a>, Expr<'a>)>,
+        Option<SeqRep<'a>>,
+    ),
 }
 
 #[moore_derive::visit]
 #[derive(Debug, Clone, PartialEq, Eq)]
 pub enum SeqRep<'a> {
-    Consec(Expr<'a>),    // [* expr]
-    ConsecStar,          // [*]
-    ConsecPlus,          // [+]
-    Nonconsec(Expr<'a>), // [= expr]
-    Goto(Expr<'a>),      // [-> expr]
+    Consec(SeqRange<'a>),    // [* range], [*], [+]
+    Nonconsec(SeqRange<'a>), // [= range]
+    Goto(SeqRange<'a>),      // [-> range]
+}
+
+/// The number of cycles of a delay or repetition.
+#[moore_derive::visit]
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum SeqRange<'a> {
+    /// Exactly `n` cycles.
+    Exact(Expr<'a>),
+    /// Between `m` and `n` cycles, as in `[m:n]`.
+    Bounded(Expr<'a>, Expr<'a>),
+    /// At least `m` cycles, as in `[m:$]`.
+    Unbounded(Expr<'a>),
+    /// Any number of cycles, as in `[*]`.
+    Star,
+    /// At least one cycle, as in `[+]`.
+    Plus,
 }
 
 #[moore_derive::visit]
@@ -1902,9 +2287,45 @@ pub enum SeqBinOp {
     Within,
 }
 
+/// A property specification, as it appears in a concurrent assertion.
+///
+/// For example `@(posedge clk) disable iff (rst) a |-> b`.
 #[moore_derive::visit]
 #[derive(Debug, Clone, PartialEq, Eq)]
-pub struct PropSpec;
+pub struct PropSpec<'a> {
+    pub span: Span,
+    pub clock: Option<EventExpr<'a>>,
+    pub disable: Option<Expr<'a>>,
+    pub prop: PropExpr<'a>,
+}
+
+/// A sequence declaration.
+///
+/// For example `sequence s(a); int x; (a, x = b) ##1 c == x; endsequence`.
+#[moore_derive::visit]
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SequenceDecl<'a> {
+    pub span: Span,
+    pub name: Spanned<Name>,
+    pub ports: Vec<Spanned<Name>>,
+    /// The local variables of the sequence.
+    pub vars: Vec<VarDecl<'a>>,
+    pub seq: SeqExpr<'a>,
+}
+
+/// A property declaration.
+///
+/// For example `property p; int x; (a, x = b) |=> c == x; endproperty`.
+#[moore_derive::visit]
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct PropertyDecl<'a> {
+    pub span: Span,
+    pub name: Spanned<Name>,
+    pub ports: Vec<Spanned<Name>>,
+    /// The local variables of the property.
+    pub vars: Vec<VarDecl<'a>>,
+    pub prop: PropSpec<'a>,
+}
 
 #[moore_derive::visit]
 #[derive(Debug, Clone, PartialEq, Eq)]
@@ -2125,6 +2546,19 @@ pub struct ContAssign<'a> {
     pub assignments: Vec<(Expr<'a>, Expr<'a>)>,
 }
 
+/// An elaboration system task.
+///
+/// ```text
+/// ("$fatal" | "$error" | "$warning" | "$info") ["(" list_of_arguments ")"] ";"
+/// ```
+#[moore_derive::node]
+#[indefinite("elaboration system task")]
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ElabTask<'a> {
+    pub name: Spanned<Name>,
+    pub args: Vec<CallArg<'a>>,
+}
+
 /// A `for` generate statement.
 #[moore_derive::node]
 #[indefinite("for-generate statement")]
@@ -2470,5 +2904,124 @@ moore_derive::derive_visitor!(
     /// Called for every node after visiting its children.
     fn post_visit_node(&mut self, node: &'a dyn AnyNode<'a>) {}
 );
+moore_derive::derive_visitor_mut!();
+moore_derive::derive_rewriter!();
 moore_derive::derive_all_node!();
 moore_derive::derive_arena!();
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::lexer::Lexer;
+    use crate::preproc::Preprocessor;
+    use moore_common::{name::get_name_table, source::get_source_manager};
+
+    /// Collects the names of all identifier expressions.
+    #[derive(Default)]
+    struct Idents(Vec<String>);
+
+    impl<'a> Visitor<'a> for Idents {
+        fn pre_visit_expr(&mut self, node: &'a Expr<'a>) -> bool {
+            if let IdentExpr(name) = node.data {
+                self.0.push(name.value.to_string());
+            }
+            true
+        }
+    }
+
+    /// Renames identifier expressions in place.
+    struct Rename(Name, Name);
+
+    impl<'a> VisitorMut<'a> for Rename {
+        fn pre_visit_expr_mut(&mut self, node: &mut Expr<'a>) -> bool {
+            if let IdentExpr(ref mut name) = node.data {
+                if name.value == self.0 {
+                    name.value = self.1;
+                }
+            }
+            true
+        }
+    }
+
+    fn parse<'n>(text: &str, arena: &'n Arena<'n>) -> SourceFile<'n> {
+        let source = get_source_manager().add_anonymous(text);
+        crate::parser::parse(Lexer::new(Preprocessor::new(source, &[], &[])), arena).unwrap()
+    }
+
+    #[test]
+    fn walk() {
+        let arena = Arena::default();
+        let file = parse(
+            "module foo;\n  logic a, b, c;\n  assign a = b & c;\n  assign c = a;\nendmodule",
+            &arena,
+        );
+        let mut idents = Idents::default();
+        file.walk(&mut idents);
+        assert_eq!(idents.0, vec!["a", "b", "c", "c", "a"]);
+    }
+
+    #[test]
+    fn rewrite() {
+        let arena = Arena::default();
+        let mut file = parse(
+            "module foo;\n  logic a, b;\n  assign a = b;\n  assign b = 1;\nendmodule",
+            &arena,
+        );
+        let nt = get_name_table();
+        file.walk_mut(&mut Rename(nt.intern("b", true), nt.intern("d", true)));
+        let mut idents = Idents::default();
+        file.walk(&mut idents);
+        assert_eq!(idents.0, vec!["a", "d", "d"]);
+    }
+
+    /// Replaces identifier expressions with new nodes.
+    struct Replace(Name, Name);
+
+    impl<'a> Rewriter<'a> for Replace {
+        fn post_rewrite_expr(&mut self, node: Expr<'a>) -> Expr<'a> {
+            match node.data {
+                IdentExpr(name) if name.value == self.0 => {
+                    Expr::new(node.span, IdentExpr(Spanned::new(self.1, name.span)))
+                }
+                _ => node,
+            }
+        }
+    }
+
+    #[test]
+    fn rewrite_nodes() {
+        let arena = Arena::default();
+        let file = parse(
+            "module foo;\n  logic a, b;\n  assign a = b;\n  assign b = 1;\nendmodule",
+            &arena,
+        );
+        let nt = get_name_table();
+        let file = file.walk_rewrite(&mut Replace(nt.intern("b", true), nt.intern("d", true)));
+        let mut idents = Idents::default();
+        file.walk(&mut idents);
+        assert_eq!(idents.0, vec!["a", "d", "d"]);
+    }
+
+    /// Collects the labels of all assertions.
+    #[derive(Default)]
+    struct Labels(Vec<Option<String>>);
+
+    impl<'a> Visitor<'a> for Labels {
+        fn pre_visit_assertion(&mut self, node: &'a Assertion<'a>) -> bool {
+            self.0.push(node.label.map(|(name, _)| name.to_string()));
+            true
+        }
+    }
+
+    #[test]
+    fn assertion_labels() {
+        let arena = Arena::default();
+        let file = parse(
+            "module foo;\n  a0: assert property (x);\n  cover property (x);\nendmodule",
+            &arena,
+        );
+        let mut labels = Labels::default();
+        file.walk(&mut labels);
+        assert_eq!(labels.0, vec![Some("a0".to_string()), None]);
+    }
+}
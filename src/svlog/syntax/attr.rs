@@ -0,0 +1,127 @@
+// Copyright (c) 2016-2020 Fabian Schuiki
+
+//! Attribute instances such as `(* keep *)`.
+//!
+//! The lexer skips attribute instances like whitespace and the parser collects
+//! them in the source file, each with the first token of the construct it is
+//! attached to. This module parses the instances and answers which attributes
+//! are attached to a node.
+
+use crate::ast;
+use moore_common::name::get_name_table;
+use moore_common::source::{Span, Spanned};
+
+/// Parse the attribute instance covering `span`, attached to the construct
+/// beginning with the token `target`.
+pub(crate) fn parse_instance(span: Span, target: Span) -> ast::AttrInst {
+    let text = span.extract();
+    let inner = text.len().saturating_sub(2).max(2);
+    let mut specs = vec![];
+    let mut start = 2;
+    let mut depth = 0;
+    let mut in_string = false;
+    for (i, c) in text[..inner].char_indices().skip(2) {
+        match c {
+            '"' => in_string = !in_string,
+            '(' | '[' | '{' if !in_string => depth += 1,
+            ')' | ']' | '}' if !in_string => depth -= 1,
+            ',' if !in_string && depth == 0 => {
+                specs.extend(parse_spec(span, &text, start, i));
+                start = i + 1;
+            }
+            _ => (),
+        }
+    }
+    specs.extend(parse_spec(span, &text, start, inner));
+    ast::AttrInst {
+        span,
+        target,
+        specs,
+    }
+}
+
+/// Parse the attribute spanning `begin..end` of the instance text.
+fn parse_spec(span: Span, text: &str, begin: usize, end: usize) -> Option<ast::AttrSpec> {
+    let sub = |begin: usize, end: usize| {
+        let s = &text[begin..end];
+        let begin = begin + (s.len() - s.trim_start().len());
+        let end = end - (s.len() - s.trim_end().len());
+        (begin, end)
+    };
+    let (name, value) = match text[begin..end].find('=') {
+        Some(eq) => (sub(begin, begin + eq), Some(sub(begin + eq + 1, end))),
+        None => (sub(begin, end), None),
+    };
+    if name.0 >= name.1 {
+        return None;
+    }
+    let offset = |(b, e): (usize, usize)| Span::new(span.source, span.begin + b, span.begin + e);
+    Some(ast::AttrSpec {
+        name: Spanned::new(
+            get_name_table().intern(&text[name.0..name.1], true),
+            offset(name),
+        ),
+        value: value.filter(|v| v.0 < v.1).map(offset),
+    })
+}
+
+/// Find the attributes attached to the node covering `span`.
+///
+/// Returns the attributes of all instances preceding the node, in source
+/// order.
+pub fn attributes_of<'a>(
+    file: &'a ast::SourceFile,
+    span: Span,
+) -> impl Iterator<Item = &'a ast::AttrSpec> + 'a {
+    file.attrs
+        .iter()
+        .filter(move |inst| inst.target.source == span.source && inst.target.begin == span.begin)
+        .flat_map(|inst| inst.specs.iter())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::lexer::Lexer;
+    use crate::preproc::Preprocessor;
+    use moore_common::source::get_source_manager;
+
+    #[test]
+    fn instances() {
+        let source = get_source_manager().add_anonymous(
+            "module foo;\n  (* keep *) (* ram_style = \"block\", mark_debug *)\n  logic [7:0] mem;\n  logic x;\n  always @(*) (* full_case *) case (x) default: ; endcase\nendmodule",
+        );
+        let arena = ast::Arena::default();
+        let file =
+            crate::parser::parse(Lexer::new(Preprocessor::new(source, &[], &[])), &arena).unwrap();
+        let module = match file.items[0].data {
+            ast::ItemData::ModuleDecl(ref m) => m,
+            _ => unreachable!(),
+        };
+        let attrs = |index: usize| -> Vec<(String, Option<String>)> {
+            attributes_of(&file, module.items[index].span)
+                .map(|a| (a.name.value.to_string(), a.value.map(|v| v.extract())))
+                .collect()
+        };
+        assert_eq!(
+            attrs(0),
+            vec![
+                ("keep".to_string(), None),
+                ("ram_style".to_string(), Some("\"block\"".to_string())),
+                ("mark_debug".to_string(), None),
+            ]
+        );
+        assert_eq!(attrs(1), vec![]);
+        let stmt = match module.items[2].data {
+            ast::ItemData::Procedure(ref p) => match p.stmt.kind {
+                ast::TimedStmt(_, ref stmt) => stmt,
+                _ => unreachable!(),
+            },
+            _ => unreachable!(),
+        };
+        let attrs: Vec<_> = attributes_of(&file, stmt.span)
+            .map(|a| a.name.value.to_string())
+            .collect();
+        assert_eq!(attrs, vec!["full_case".to_string()]);
+    }
+}
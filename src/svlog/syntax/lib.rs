@@ -7,8 +7,10 @@
 extern crate log;
 
 pub mod ast;
+pub mod attr;
 pub mod cat;
 pub mod lexer;
 pub mod parser;
 pub mod preproc;
+pub mod query;
 pub mod token;
@@ -237,6 +237,8 @@ struct Parser<'a, 'n> {
     severity: Severity,
     consumed: usize,
     arena: &'n ast::Arena<'n>,
+    /// The attribute instances encountered so far.
+    attrs: Vec<ast::AttrInst>,
 }
 
 impl<'a, 'n> AbstractParser<'n> for Parser<'a, 'n> {
@@ -313,6 +315,7 @@ impl<'a, 'n> Parser<'a, 'n> {
             severity: Severity::Note,
             consumed: 0,
             arena,
+            attrs: Vec::new(),
         }
     }
 
@@ -323,7 +326,12 @@ impl<'a, 'n> Parser<'a, 'n> {
         while self.queue.len() <= min_tokens {
             match self.input.next_token() {
                 Ok((Eof, sp)) => self.queue.push_back((Eof, sp)),
-                Ok(tkn) => self.queue.push_back(tkn),
+                Ok(tkn) => {
+                    for span in self.input.take_attributes() {
+                        self.attrs.push(crate::attr::parse_instance(span, tkn.1));
+                    }
+                    self.queue.push_back(tkn)
+                }
                 Err(x) => self.add_diag(x),
             }
         }
@@ -624,7 +632,8 @@ impl<
 
 pub fn parse<'n>(input: Lexer, arena: &'n ast::Arena<'n>) -> Result<ast::SourceFile<'n>, ()> {
     let mut p = Parser::new(input, arena);
-    let root = parse_source_text(&mut p);
+    let mut root = parse_source_text(&mut p);
+    root.data.attrs = std::mem::replace(&mut p.attrs, Vec::new());
     if p.is_error() {
         Err(())
     } else {
@@ -640,6 +649,7 @@ fn parse_source_text<'n>(p: &mut dyn AbstractParser<'n>) -> ast::SourceFile<'n>
             prec: None,
         },
         items: Vec::new(),
+        attrs: Vec::new(),
     };
 
     // Parse the optional timeunits declaration.
@@ -999,6 +1009,145 @@ fn parse_package_decl<'n>(p: &mut dyn AbstractParser<'n>) -> ReportedResult<Pack
     result
 }
 
+fn parse_config_decl<'n>(p: &mut dyn AbstractParser<'n>) -> ReportedResult<Config<'n>> {
+    let mut span = p.peek(0).1;
+    p.require_reported(Keyword(Kw::Config))?;
+    let result = recovered(p, Keyword(Kw::Endconfig), |p| {
+        let (name, name_span) = p.eat_ident("configuration name")?;
+        p.require_reported(Semicolon)?;
+
+        // Parse the design statement.
+        if let Keyword(Kw::Localparam) = p.peek(0).0 {
+            let q = p.peek(0).1;
+            p.add_diag(DiagBuilder2::error("unsupported: parameters in configurations").span(q));
+            return Err(());
+        }
+        p.require_reported(Keyword(Kw::Design))?;
+        let mut design = vec![];
+        while !p.is_fatal() && p.peek(0).0 != Semicolon && p.peek(0).0 != Eof {
+            design.push(parse_cell_ref(p, "cell name")?);
+        }
+        p.require_reported(Semicolon)?;
+
+        // Parse the configuration rules.
+        let mut rules = vec![];
+        while !p.is_fatal() && p.peek(0).0 != Keyword(Kw::Endconfig) && p.peek(0).0 != Eof {
+            rules.push(parse_config_rule(p)?);
+        }
+
+        span.expand(p.last_span());
+        Ok(Config::new(
+            span,
+            ConfigData {
+                name: Spanned::new(name, name_span),
+                design,
+                rules,
+            },
+        ))
+    });
+    p.require_reported(Keyword(Kw::Endconfig))?;
+    if p.try_eat(Colon) {
+        p.eat_ident("configuration name")?;
+    }
+    result
+}
+
+fn parse_config_rule<'n>(p: &mut dyn AbstractParser<'n>) -> ReportedResult<ConfigRule> {
+    let mut span = p.peek(0).1;
+    let clause = match p.peek(0).0 {
+        Keyword(Kw::Default) => {
+            p.bump();
+            ConfigClause::Default
+        }
+        Keyword(Kw::Instance) => {
+            p.bump();
+            let mut path = vec![];
+            loop {
+                let (name, name_span) = p.eat_ident("instance name")?;
+                path.push(Spanned::new(name, name_span));
+                if !p.try_eat(Period) {
+                    break;
+                }
+            }
+            ConfigClause::Instance(path)
+        }
+        Keyword(Kw::Cell) => {
+            p.bump();
+            ConfigClause::Cell(parse_cell_ref(p, "cell name")?)
+        }
+        _ => {
+            let q = p.peek(0).1;
+            p.add_diag(
+                DiagBuilder2::error("expected `default`, `instance`, or `cell` rule").span(q),
+            );
+            return Err(());
+        }
+    };
+    let action = match p.peek(0).0 {
+        Keyword(Kw::Liblist) => {
+            p.bump();
+            let mut libs = vec![];
+            while p.is_ident() {
+                let (name, name_span) = p.eat_ident("library name")?;
+                libs.push(Spanned::new(name, name_span));
+            }
+            ConfigAction::Liblist(libs)
+        }
+        Keyword(Kw::Use) if clause != ConfigClause::Default => {
+            p.bump();
+            if p.peek(0).0 == Hashtag {
+                let q = p.peek(0).1;
+                p.add_diag(
+                    DiagBuilder2::error("unsupported: parameter overrides in configurations")
+                        .span(q),
+                );
+                return Err(());
+            }
+            let cell = parse_cell_ref(p, "cell name")?;
+            let is_config = if p.try_eat(Colon) {
+                p.require_reported(Keyword(Kw::Config))?;
+                true
+            } else {
+                false
+            };
+            ConfigAction::Use(cell, is_config)
+        }
+        _ => {
+            let q = p.peek(0).1;
+            p.add_diag(match clause {
+                ConfigClause::Default => DiagBuilder2::error("expected `liblist`").span(q),
+                _ => DiagBuilder2::error("expected `liblist` or `use`").span(q),
+            });
+            return Err(());
+        }
+    };
+    p.require_reported(Semicolon)?;
+    span.expand(p.last_span());
+    Ok(ConfigRule {
+        span,
+        clause,
+        action,
+    })
+}
+
+/// Parse a cell optionally qualified with a library, such as `lib.cell`.
+fn parse_cell_ref<'n>(p: &mut dyn AbstractParser<'n>, msg: &str) -> ReportedResult<CellRef> {
+    let (name, name_span) = p.eat_ident(msg)?;
+    let first = Spanned::new(name, name_span);
+    if p.try_eat(Period) {
+        let (name, name_span) = p.eat_ident(msg)?;
+        Ok(CellRef {
+            lib: Some(first),
+            cell: Spanned::new(name, name_span),
+        })
+    } else {
+        Ok(CellRef {
+            lib: None,
+            cell: first,
+        })
+    }
+}
+
 fn parse_program_decl<'n>(p: &mut dyn AbstractParser<'n>) -> ReportedResult<()> {
     p.require_reported(Keyword(Kw::Program))?;
     let result = recovered(p, Keyword(Kw::Endprogram), |p| {
@@ -1018,11 +1167,14 @@ fn parse_item<'n>(p: &mut dyn AbstractParser<'n>) -> ReportedResult<Item<'n>> {
 }
 
 fn parse_item_data<'n>(p: &mut dyn AbstractParser<'n>) -> ReportedResult<ItemData<'n>> {
-    // Consume optional leading label.
-    if p.is_ident() && p.peek(1).0 == Colon {
-        p.bump();
+    // Consume optional leading label. Only assertions keep it.
+    let label = if p.is_ident() && p.peek(1).0 == Colon {
+        let label = p.eat_ident("item label")?;
         p.bump();
-    }
+        Some(label)
+    } else {
+        None
+    };
 
     // First attempt the simple cases where a keyword reliably identifies the
     // following item.
@@ -1039,6 +1191,7 @@ fn parse_item_data<'n>(p: &mut dyn AbstractParser<'n>) -> ReportedResult<ItemDat
         Keyword(Kw::Class) => return parse_class_decl(p).map(ItemData::ClassDecl),
         Keyword(Kw::Interface) => return parse_interface_decl(p).map(ItemData::InterfaceDecl),
         Keyword(Kw::Package) => return parse_package_decl(p).map(ItemData::PackageDecl),
+        Keyword(Kw::Config) => return parse_config_decl(p).map(ItemData::ConfigDecl),
         Keyword(Kw::Program) => return parse_program_decl(p).map(ItemData::ProgramDecl),
 
         Keyword(Kw::Localparam) | Keyword(Kw::Parameter) => {
@@ -1111,7 +1264,11 @@ fn parse_item_data<'n>(p: &mut dyn AbstractParser<'n>) -> ReportedResult<ItemDat
         | Keyword(Kw::Assume)
         | Keyword(Kw::Cover)
         | Keyword(Kw::Expect)
-        | Keyword(Kw::Restrict) => return parse_assertion(p).map(|x| ItemData::Assertion(x)),
+        | Keyword(Kw::Restrict) => {
+            return parse_assertion(p).map(|x| ItemData::Assertion(Assertion { label, ..x }))
+        }
+        Keyword(Kw::Sequence) => return parse_sequence_decl(p).map(ItemData::SequenceDecl),
+        Keyword(Kw::Property) => return parse_property_decl(p).map(ItemData::PropertyDecl),
         Semicolon => {
             p.bump();
             return Ok(ItemData::Dummy);
@@ -1141,8 +1298,8 @@ fn parse_item_data<'n>(p: &mut dyn AbstractParser<'n>) -> ReportedResult<ItemDat
             return Err(());
         }
 
-        // Unsupported constructs as of now.
-        SysIdent(..) => return parse_elab_system_task(p).map(|_| ItemData::Dummy),
+        // Elaboration system tasks
+        SysIdent(..) => return parse_elab_system_task(p).map(|x| ItemData::ElabTask(x)),
 
         _ => (),
     }
@@ -1165,16 +1322,21 @@ fn parse_item_data<'n>(p: &mut dyn AbstractParser<'n>) -> ReportedResult<ItemDat
     res
 }
 
-fn parse_elab_system_task<'n>(p: &mut dyn AbstractParser<'n>) -> ReportedResult<()> {
+/// Parse an elaboration system task.
+/// ```text
+/// sys_ident ["(" list_of_arguments ")"] ";"
+/// ```
+fn parse_elab_system_task<'n>(p: &mut dyn AbstractParser<'n>) -> ReportedResult<ElabTask<'n>> {
     let mut span = p.peek(0).1;
-    let name = match p.peek(0).0 {
-        SysIdent(name) => name,
+    let name = match p.peek(0) {
+        (SysIdent(name), sp) => Spanned::new(name, sp),
         _ => unreachable!(),
     };
-    p.recover_balanced(&[Semicolon], true);
+    p.bump();
+    let args = try_flanked(p, Paren, parse_call_args)?.unwrap_or(Vec::new());
+    p.require_reported(Semicolon)?;
     span.expand(p.last_span());
-    p.add_diag(DiagBuilder2::warning("unsupported elaboration system task").span(span));
-    Ok(())
+    Ok(ast::ElabTask::new(span, ast::ElabTaskData { name, args }))
 }
 
 fn parse_localparam_decl<'n>(p: &mut dyn AbstractParser<'n>) -> ReportedResult<()> {
@@ -2094,7 +2256,7 @@ fn parse_expr_suffix<'n>(
     let (tkn, sp) = p.peek(0);
     match tkn {
         // Index: "[" range_expression "]"
-        OpenDelim(Brack) if precedence <= Precedence::Postfix => {
+        OpenDelim(Brack) if precedence <= Precedence::Postfix && !is_seqrep_start(p) => {
             p.bump();
             let expr = match parse_range_expr(p) {
                 Ok(x) => x,
@@ -5572,31 +5734,122 @@ fn parse_assertion_action_block<'n>(
     }
 }
 
-fn parse_property_spec<'n>(p: &mut dyn AbstractParser<'n>) -> ReportedResult<PropSpec> {
+fn parse_property_spec<'n>(p: &mut dyn AbstractParser<'n>) -> ReportedResult<PropSpec<'n>> {
     let mut span = p.peek(0).1;
 
-    // TODO: Actually parse this stuff, rather than just chicken out.
-    p.recover_balanced(&[CloseDelim(Paren)], false);
-    return Ok(PropSpec);
+    // Parse the optional event expression.
+    let clock = if p.try_eat(At) {
+        Some(parse_event_expr(p, EventPrecedence::Min)?)
+    } else {
+        None
+    };
 
-    // // Parse the optional event expression.
-    // let event = if p.try_eat(At) {
-    //     Some(parse_event_expr(p, EventPrecedence::Min)?)
-    // } else {
-    //     None
-    // };
+    // Parse the optional "disable iff" clause.
+    let disable = if p.try_eat(Keyword(Kw::Disable)) {
+        p.require_reported(Keyword(Kw::Iff))?;
+        Some(flanked(p, Paren, parse_expr)?)
+    } else {
+        None
+    };
 
-    // // Parse the optional "disable iff" clause.
-    // let disable = if p.try_eat(Keyword(Kw::Disable)) {
-    //     p.require_reported(Keyword(Kw::Iff))?;
-    //     Some(flanked(p, Paren, parse_expr)?)
-    // } else {
-    //     None
-    // };
+    // Parse the property expression.
+    let prop = parse_propexpr(p)?;
+    span.expand(p.last_span());
+    Ok(PropSpec {
+        span,
+        clock,
+        disable,
+        prop,
+    })
+}
 
-    // // Parse the property expression.
-    // let prop = parse_propexpr(p)?;
-    // Ok(PropSpec)
+fn parse_sequence_decl<'n>(p: &mut dyn AbstractParser<'n>) -> ReportedResult<SequenceDecl<'n>> {
+    let mut span = p.peek(0).1;
+    p.require_reported(Keyword(Kw::Sequence))?;
+    let name = parse_identifier_name(p, "sequence name")?;
+    let ports = parse_assertion_decl_ports(p)?;
+    p.require_reported(Semicolon)?;
+    let vars = parse_assertion_decl_vars(p)?;
+    let seq = parse_seqexpr(p)?;
+    p.try_eat(Semicolon);
+    p.require_reported(Keyword(Kw::Endsequence))?;
+    if p.try_eat(Colon) {
+        p.eat_ident("sequence name")?;
+    }
+    span.expand(p.last_span());
+    Ok(SequenceDecl {
+        span,
+        name,
+        ports,
+        vars,
+        seq,
+    })
+}
+
+fn parse_property_decl<'n>(p: &mut dyn AbstractParser<'n>) -> ReportedResult<PropertyDecl<'n>> {
+    let mut span = p.peek(0).1;
+    p.require_reported(Keyword(Kw::Property))?;
+    let name = parse_identifier_name(p, "property name")?;
+    let ports = parse_assertion_decl_ports(p)?;
+    p.require_reported(Semicolon)?;
+    let vars = parse_assertion_decl_vars(p)?;
+    let prop = parse_property_spec(p)?;
+    p.try_eat(Semicolon);
+    p.require_reported(Keyword(Kw::Endproperty))?;
+    if p.try_eat(Colon) {
+        p.eat_ident("property name")?;
+    }
+    span.expand(p.last_span());
+    Ok(PropertyDecl {
+        span,
+        name,
+        ports,
+        vars,
+        prop,
+    })
+}
+
+/// Parse the optional list of untyped formal arguments of a sequence or
+/// property declaration.
+fn parse_assertion_decl_ports<'n>(
+    p: &mut dyn AbstractParser<'n>,
+) -> ReportedResult<Vec<Spanned<Name>>> {
+    if p.peek(0).0 != OpenDelim(Paren) {
+        return Ok(vec![]);
+    }
+    flanked(p, Paren, |p| {
+        comma_list(p, CloseDelim(Paren), "formal argument", |p| {
+            parse_identifier_name(p, "formal argument name")
+        })
+    })
+}
+
+/// Parse the local variable declarations at the beginning of a sequence or
+/// property declaration.
+///
+/// Only variables with a built-in type or introduced by `var` are recognized,
+/// which avoids ambiguities with the sequence expression that follows.
+fn parse_assertion_decl_vars<'n>(
+    p: &mut dyn AbstractParser<'n>,
+) -> ReportedResult<Vec<VarDecl<'n>>> {
+    let mut vars = vec![];
+    loop {
+        match p.peek(0).0 {
+            Keyword(Kw::Var)
+            | Keyword(Kw::Bit)
+            | Keyword(Kw::Logic)
+            | Keyword(Kw::Reg)
+            | Keyword(Kw::Byte)
+            | Keyword(Kw::Shortint)
+            | Keyword(Kw::Int)
+            | Keyword(Kw::Longint)
+            | Keyword(Kw::Integer)
+            | Keyword(Kw::Time)
+            | Keyword(Kw::Real)
+            | Keyword(Kw::String) => vars.push(parse_var_decl(p)?),
+            _ => return Ok(vars),
+        }
+    }
 }
 
 #[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
@@ -5768,12 +6021,19 @@ fn parse_seqexpr_prec<'n>(
 ) -> ReportedResult<SeqExpr<'n>> {
     let mut span = p.peek(0).1;
 
-    // See parse_propexpr_prec for an explanation of why we need a parallel
-    // parser here.
-    let mut pp = ParallelParser::new();
-    pp.add_greedy("expression", move |p| parse_seqexpr_expr(p, precedence));
-    pp.add_greedy("sequence", move |p| parse_seqexpr_nonexpr(p, precedence));
-    let data = pp.finish(p, "sequence or primary property expression")?;
+    // Handle sequences that start with a cycle delay, e.g. `##1 a`.
+    let data = if p.peek(0).0 == DoubleHashtag {
+        let range = parse_cycle_delay_range(p)?;
+        let rhs = parse_seqexpr_prec(p, PropSeqPrecedence::CycleDelay)?;
+        SeqExprData::Delay(None, range, Box::new(rhs))
+    } else {
+        // See parse_propexpr_prec for an explanation of why we need a parallel
+        // parser here.
+        let mut pp = ParallelParser::new();
+        pp.add_greedy("expression", move |p| parse_seqexpr_expr(p, precedence));
+        pp.add_greedy("sequence", move |p| parse_seqexpr_nonexpr(p, precedence));
+        pp.finish(p, "sequence or primary property expression")?
+    };
 
     span.expand(p.last_span());
     let expr = SeqExpr {
@@ -5787,23 +6047,7 @@ fn parse_seqexpr_expr<'n>(
     p: &mut dyn AbstractParser<'n>,
     precedence: PropSeqPrecedence,
 ) -> ReportedResult<SeqExprData<'n>> {
-    // TODO: Handle all the non-trivial cases.
-    let q = p.peek(0).1;
-    p.add_diag(
-        DiagBuilder2::error(
-            "Don't know how to parse sequence expression that don't start with an expression",
-        )
-        .span(q),
-    );
-    Err(())
-}
-
-fn parse_seqexpr_nonexpr<'n>(
-    p: &mut dyn AbstractParser<'n>,
-    precedence: PropSeqPrecedence,
-) -> ReportedResult<SeqExprData<'n>> {
-    // If we arrive here, the only possibility left is that this sequence starts
-    // with and expression or distribution.
+    // This sequence starts with and expression or distribution.
     let expr = parse_expr(p)?;
 
     // Handle the case of the "throughout" operator that has an expression on
@@ -5819,51 +6063,176 @@ fn parse_seqexpr_nonexpr<'n>(
     Ok(SeqExprData::Expr(expr, rep))
 }
 
+fn parse_seqexpr_nonexpr<'n>(
+    p: &mut dyn AbstractParser<'n>,
+    precedence: PropSeqPrecedence,
+) -> ReportedResult<SeqExprData<'n>> {
+    match p.peek(0).0 {
+        // Parenthesized sequence, with optional local variable assignments
+        // and repetition.
+        OpenDelim(Paren) => {
+            p.bump();
+            let seq = match parse_seqexpr(p) {
+                Ok(x) => x,
+                Err(()) => {
+                    p.recover_balanced(&[CloseDelim(Paren)], true);
+                    return Err(());
+                }
+            };
+            let mut assigns = vec![];
+            while p.try_eat(Comma) {
+                assigns.push(parse_assignment(p)?);
+            }
+            p.require_reported(CloseDelim(Paren))?;
+            let rep = try_flanked(p, Brack, parse_seqrep)?;
+            Ok(SeqExprData::Paren(Box::new(seq), assigns, rep))
+        }
+
+        // Clocking event
+        At => {
+            p.bump();
+            let ev = parse_event_expr(p, EventPrecedence::Min)?;
+            let seq = parse_seqexpr_prec(p, precedence)?;
+            Ok(SeqExprData::Clocked(ev, Box::new(seq)))
+        }
+
+        _ => {
+            let q = p.peek(0).1;
+            p.add_diag(DiagBuilder2::error("expected sequence expression").span(q));
+            Err(())
+        }
+    }
+}
+
 fn parse_seqexpr_suffix<'n>(
     p: &mut dyn AbstractParser<'n>,
     prefix: SeqExpr<'n>,
     precedence: PropSeqPrecedence,
 ) -> ReportedResult<SeqExpr<'n>> {
-    // TODO: Handle all the binary operators.
+    // Handle cycle delays, e.g. `a ##[1:2] b`.
+    if precedence < PropSeqPrecedence::CycleDelay && p.peek(0).0 == DoubleHashtag {
+        let range = parse_cycle_delay_range(p)?;
+        let rhs = parse_seqexpr_prec(p, PropSeqPrecedence::CycleDelay)?;
+        let expr = SeqExpr {
+            span: Span::union(prefix.span, rhs.span),
+            data: SeqExprData::Delay(Some(Box::new(prefix)), range, Box::new(rhs)),
+        };
+        return parse_seqexpr_suffix(p, expr, precedence);
+    }
+
+    // Handle the binary operators, all of which are left-associative.
+    if let Some((op, prec)) = match p.peek(0).0 {
+        Keyword(Kw::Or) => Some((SeqBinOp::Or, PropSeqPrecedence::Or)),
+        Keyword(Kw::And) => Some((SeqBinOp::And, PropSeqPrecedence::And)),
+        Keyword(Kw::Intersect) => Some((SeqBinOp::Intersect, PropSeqPrecedence::Intersect)),
+        Keyword(Kw::Within) => Some((SeqBinOp::Within, PropSeqPrecedence::Within)),
+        _ => None,
+    } {
+        if precedence < prec {
+            p.bump();
+            let rhs = parse_seqexpr_prec(p, prec)?;
+            let expr = SeqExpr {
+                span: Span::union(prefix.span, rhs.span),
+                data: SeqExprData::BinOp(op, Box::new(prefix), Box::new(rhs)),
+            };
+            return parse_seqexpr_suffix(p, expr, precedence);
+        }
+    }
+
     Ok(prefix)
 }
 
+/// Parse the range of a cycle delay, e.g. `##2`, `##[1:3]`, or `##[+]`.
+fn parse_cycle_delay_range<'n>(p: &mut dyn AbstractParser<'n>) -> ReportedResult<SeqRange<'n>> {
+    p.require_reported(DoubleHashtag)?;
+    match p.peek(0).0 {
+        OpenDelim(Brack) => flanked(p, Brack, |p| match p.peek(0).0 {
+            Operator(Op::Mul) if p.peek(1).0 == CloseDelim(Brack) => {
+                p.bump();
+                Ok(SeqRange::Star)
+            }
+            Operator(Op::Add) if p.peek(1).0 == CloseDelim(Brack) => {
+                p.bump();
+                Ok(SeqRange::Plus)
+            }
+            _ => parse_seqrange(p),
+        }),
+        Literal(..) | Ident(..) | EscIdent(..) | OpenDelim(Paren) => {
+            Ok(SeqRange::Exact(parse_expr_prec(p, Precedence::Max)?))
+        }
+        _ => {
+            let q = p.peek(0).1;
+            p.add_diag(
+                DiagBuilder2::error(
+                    "expected cycle delay `##<n>`, `##[<m>:<n>]`, `##[*]`, or `##[+]`",
+                )
+                .span(q),
+            );
+            Err(())
+        }
+    }
+}
+
+/// Parse a range of cycles, e.g. `2`, `1:3`, or `1:$`.
+fn parse_seqrange<'n>(p: &mut dyn AbstractParser<'n>) -> ReportedResult<SeqRange<'n>> {
+    let min = parse_expr(p)?;
+    if !p.try_eat(Colon) {
+        return Ok(SeqRange::Exact(min));
+    }
+    if p.peek(0).0 == Dollar && p.peek(1).0 == CloseDelim(Brack) {
+        p.bump();
+        return Ok(SeqRange::Unbounded(min));
+    }
+    let max = parse_expr(p)?;
+    Ok(SeqRange::Bounded(min, max))
+}
+
+/// Check whether an opening bracket starts a sequence repetition rather than
+/// an index, e.g. `[*3]` or `[->1]`.
+fn is_seqrep_start<'n>(p: &mut dyn AbstractParser<'n>) -> bool {
+    match p.peek(1).0 {
+        Operator(Op::Mul) | Operator(Op::Assign) | Operator(Op::LogicImpl) => true,
+        Operator(Op::Add) => p.peek(2).0 == CloseDelim(Brack),
+        _ => false,
+    }
+}
+
 fn parse_seqrep<'n>(p: &mut dyn AbstractParser<'n>) -> ReportedResult<SeqRep<'n>> {
     match p.peek(0).0 {
         // [*]
-        // [* expr]
+        // [* range]
         Operator(Op::Mul) => {
             p.bump();
             if p.peek(0).0 == CloseDelim(Brack) {
-                Ok(SeqRep::ConsecStar)
+                Ok(SeqRep::Consec(SeqRange::Star))
             } else {
-                Ok(SeqRep::Consec(parse_expr(p)?))
+                Ok(SeqRep::Consec(parse_seqrange(p)?))
             }
         }
 
         // [+]
         Operator(Op::Add) => {
             p.bump();
-            Ok(SeqRep::ConsecPlus)
+            Ok(SeqRep::Consec(SeqRange::Plus))
         }
 
-        // [= expr]
+        // [= range]
         Operator(Op::Assign) => {
             p.bump();
-            Ok(SeqRep::Nonconsec(parse_expr(p)?))
+            Ok(SeqRep::Nonconsec(parse_seqrange(p)?))
         }
 
-        // [-> expr]
+        // [-> range]
         Operator(Op::LogicImpl) => {
             p.bump();
-            Ok(SeqRep::Goto(parse_expr(p)?))
+            Ok(SeqRep::Goto(parse_seqrange(p)?))
         }
 
         _ => {
             let q = p.peek(0).1;
             p.add_diag(
                 DiagBuilder2::error(
-                    "expected sequence repetition [+], [*], [* <expr>], [= <expr>], or [-> <expr>]",
+                    "expected sequence repetition [+], [*], [* <range>], [= <range>], or [-> <range>]",
                 )
                 .span(q),
             );
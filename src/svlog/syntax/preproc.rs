@@ -6,6 +6,7 @@
 
 use crate::cat::*;
 use moore_common::errors::{DiagBuilder2, DiagResult2};
+use moore_common::name::RcStr;
 use moore_common::source::*;
 use std::{collections::HashMap, fmt, path::Path, rc::Rc};
 
@@ -387,16 +388,36 @@ impl<'a> Preprocessor<'a> {
                         None => (),
                     }
 
+                    // Move the tokens of the macro body into a source of their
+                    // own, which records this expansion for diagnostics.
+                    let body: Vec<TokenAndSpan> = match makro.body.first() {
+                        Some(&(_, first)) => {
+                            let expansion = get_source_manager().add_expansion(
+                                first.source,
+                                Expansion {
+                                    name: RcStr::new(dir_name),
+                                    site: span,
+                                },
+                            );
+                            makro
+                                .body
+                                .iter()
+                                .map(|&(tkn, sp)| (tkn, Span::new(expansion, sp.begin, sp.end)))
+                                .collect()
+                        }
+                        None => vec![],
+                    };
+
                     // Push the tokens of the macro onto the stack, potentially
                     // substituting any macro parameters as necessary.
                     if args.is_empty() {
                         self.macro_stack
-                            .extend(makro.body.iter().rev().map(|&(tkn, sp)| (tkn, sp)));
+                            .extend(body.iter().rev().map(|&(tkn, sp)| (tkn, sp)));
                     } else {
                         let mut replacement = Vec::<TokenAndSpan>::new();
                         // TODO: Make this work for argument names that contain
                         // underscores.
-                        for tkn in &makro.body {
+                        for tkn in &body {
                             match *tkn {
                                 (Text, sp) => match args.get(&sp.extract()) {
                                     Some(substitute) => {
@@ -1395,4 +1416,21 @@ mod tests {
     fn macro_name_with_digits_and_underscores() {
         check_str("`define AXI_BUS21_SV 42\n`AXI_BUS21_SV", "42");
     }
+
+    /// Verify that tokens of nested macros record the chain of expansions.
+    #[test]
+    fn macro_expansion_chain() {
+        let pp = preproc("`define INNER oops\n`define OUTER (`INNER)\n`OUTER\n");
+        let span = pp
+            .map(|x| x.unwrap().1)
+            .find(|sp| sp.extract() == "oops")
+            .unwrap();
+        let inner = span.source.get_expansion().unwrap();
+        assert_eq!(&*inner.name, "INNER");
+        assert_eq!(inner.site.extract(), "`INNER");
+        let outer = inner.site.source.get_expansion().unwrap();
+        assert_eq!(&*outer.name, "OUTER");
+        assert_eq!(outer.site.extract(), "`OUTER");
+        assert!(outer.site.source.get_expansion().is_none());
+    }
 }
@@ -0,0 +1,603 @@
+// Copyright (c) 2016-2020 Fabian Schuiki
+
+//! Where-used queries over the AST.
+//!
+//! This module answers questions such as "where is module X instantiated"
+//! directly on the parsed source files, without requiring the design to
+//! elaborate.
+
+use crate::ast::{self, AcceptVisitor, Visitor};
+use moore_common::name::{get_name_table, Name};
+use moore_common::source::Span;
+use std::collections::HashMap;
+
+/// A single query result.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct QueryMatch {
+    /// The name of the module or interface the match was found in.
+    pub unit: Name,
+    /// The name of the matched item, e.g. an instance name.
+    pub name: Option<Name>,
+    /// The location of the match.
+    pub span: Span,
+}
+
+/// Find all instantiations of the module or interface `target`.
+///
+/// Every instance in a multi-instance statement such as `foo u0(), u1();` is
+/// reported separately, including those inside generate blocks. The match
+/// name is the instance name.
+///
+/// The query only looks at the syntax. Instances are matched by the name of
+/// the module they refer to, ignoring configurations, and an instance inside
+/// a generate block is reported once, regardless of how often the block is
+/// elaborated.
+pub fn instances_of<'a>(
+    files: impl IntoIterator<Item = &'a ast::SourceFile<'a>>,
+    target: Name,
+) -> Vec<QueryMatch> {
+    let mut finder = InstFinder {
+        target,
+        unit: None,
+        matches: vec![],
+    };
+    for file in files {
+        file.accept(&mut finder);
+    }
+    finder.matches
+}
+
+struct InstFinder {
+    target: Name,
+    unit: Option<Name>,
+    matches: Vec<QueryMatch>,
+}
+
+impl<'a> Visitor<'a> for InstFinder {
+    fn pre_visit_module(&mut self, node: &'a ast::Module<'a>) -> bool {
+        self.unit = Some(node.name.value);
+        true
+    }
+
+    fn pre_visit_interface(&mut self, node: &'a ast::Interface<'a>) -> bool {
+        self.unit = Some(node.name.value);
+        true
+    }
+
+    fn pre_visit_inst(&mut self, node: &'a ast::Inst<'a>) -> bool {
+        if node.target.value != self.target {
+            return true;
+        }
+        if let Some(unit) = self.unit {
+            for name in &node.names {
+                self.matches.push(QueryMatch {
+                    unit,
+                    name: Some(name.name.value),
+                    span: name.span,
+                });
+            }
+        }
+        true
+    }
+}
+
+/// Find all signals driven by the procedure named `process`.
+///
+/// A procedure is named by the label of the block that forms its body, as in
+/// `always_ff @(posedge clk) begin : process`. The match name is the driven
+/// signal, i.e. the identifier at the root of the left-hand side of a blocking
+/// or nonblocking assignment. Variables declared inside the procedure are not
+/// reported.
+pub fn drivers_of<'a>(
+    files: impl IntoIterator<Item = &'a ast::SourceFile<'a>>,
+    process: Name,
+) -> Vec<QueryMatch> {
+    let mut finder = DriverFinder {
+        process,
+        unit: None,
+        active: false,
+        locals: vec![],
+        matches: vec![],
+    };
+    for file in files {
+        file.accept(&mut finder);
+    }
+    finder.matches
+}
+
+/// Get the label of the block that forms the body of a procedure.
+fn procedure_name(stmt: &ast::Stmt) -> Option<Name> {
+    match stmt.kind {
+        _ if stmt.label.is_some() => stmt.label,
+        ast::TimedStmt(_, ref stmt) => procedure_name(stmt),
+        _ => None,
+    }
+}
+
+struct DriverFinder {
+    process: Name,
+    unit: Option<Name>,
+    active: bool,
+    locals: Vec<Name>,
+    matches: Vec<QueryMatch>,
+}
+
+impl DriverFinder {
+    fn add_target(&mut self, target: &ast::Expr) {
+        match target.data {
+            ast::IdentExpr(name) => {
+                let unit = match self.unit {
+                    Some(unit) => unit,
+                    None => return,
+                };
+                if self.locals.contains(&name.value)
+                    || self
+                        .matches
+                        .iter()
+                        .any(|m| m.unit == unit && m.name == Some(name.value))
+                {
+                    return;
+                }
+                self.matches.push(QueryMatch {
+                    unit,
+                    name: Some(name.value),
+                    span: name.span,
+                });
+            }
+            ast::IndexExpr { ref indexee, .. } => self.add_target(indexee),
+            ast::MemberExpr { ref expr, .. } => self.add_target(expr),
+            ast::ConcatExpr { ref exprs, .. } => {
+                for expr in exprs {
+                    self.add_target(expr);
+                }
+            }
+            _ => (),
+        }
+    }
+}
+
+impl<'a> Visitor<'a> for DriverFinder {
+    fn pre_visit_module(&mut self, node: &'a ast::Module<'a>) -> bool {
+        self.unit = Some(node.name.value);
+        true
+    }
+
+    fn pre_visit_interface(&mut self, node: &'a ast::Interface<'a>) -> bool {
+        self.unit = Some(node.name.value);
+        true
+    }
+
+    fn pre_visit_procedure(&mut self, node: &'a ast::Procedure<'a>) -> bool {
+        // Procedures do not nest, so there is no outer state to restore.
+        self.active = procedure_name(&node.stmt) == Some(self.process);
+        self.locals.clear();
+        self.active
+    }
+
+    fn post_visit_procedure(&mut self, _: &'a ast::Procedure<'a>) {
+        self.active = false;
+    }
+
+    fn pre_visit_stmt(&mut self, node: &'a ast::Stmt<'a>) -> bool {
+        if !self.active {
+            return true;
+        }
+        match node.kind {
+            ast::BlockingAssignStmt { ref lhs, .. }
+            | ast::NonblockingAssignStmt { ref lhs, .. } => {
+                self.add_target(lhs);
+                false
+            }
+            ast::VarDeclStmt(ref decl) => {
+                self.locals.extend(decl.names.iter().map(|n| n.name));
+                true
+            }
+            _ => true,
+        }
+    }
+}
+
+/// Find all modules, interfaces, and packages that import the package `pkg`.
+///
+/// Imports in the module header, among the items, and in procedural code are
+/// considered, whether they import a single name or the whole package. The
+/// match name is the imported package. Imports outside of any module,
+/// interface, or package are not attributed to a unit and thus not reported.
+pub fn users_of<'a>(
+    files: impl IntoIterator<Item = &'a ast::SourceFile<'a>>,
+    pkg: Name,
+) -> Vec<QueryMatch> {
+    let mut finder = UseFinder {
+        pkg,
+        unit: None,
+        matches: vec![],
+    };
+    for file in files {
+        file.accept(&mut finder);
+    }
+    finder.matches
+}
+
+struct UseFinder {
+    pkg: Name,
+    unit: Option<Name>,
+    matches: Vec<QueryMatch>,
+}
+
+impl<'a> Visitor<'a> for UseFinder {
+    fn pre_visit_module(&mut self, node: &'a ast::Module<'a>) -> bool {
+        self.unit = Some(node.name.value);
+        true
+    }
+
+    fn pre_visit_interface(&mut self, node: &'a ast::Interface<'a>) -> bool {
+        self.unit = Some(node.name.value);
+        true
+    }
+
+    fn pre_visit_package(&mut self, node: &'a ast::Package<'a>) -> bool {
+        self.unit = Some(node.name.value);
+        true
+    }
+
+    fn post_visit_module(&mut self, _: &'a ast::Module<'a>) {
+        self.unit = None;
+    }
+
+    fn post_visit_interface(&mut self, _: &'a ast::Interface<'a>) {
+        self.unit = None;
+    }
+
+    fn post_visit_package(&mut self, _: &'a ast::Package<'a>) {
+        self.unit = None;
+    }
+
+    fn pre_visit_import_item(&mut self, node: &'a ast::ImportItem) -> bool {
+        if let Some(unit) = self.unit {
+            if node.pkg.value == self.pkg && !self.matches.iter().any(|m| m.unit == unit) {
+                self.matches.push(QueryMatch {
+                    unit,
+                    name: Some(self.pkg),
+                    span: node.pkg.span,
+                });
+            }
+        }
+        true
+    }
+}
+
+/// Find all signals matching a hierarchical path such as `top.*.state`.
+///
+/// The first segment of the path names a module or interface, each following
+/// segment an instance in the previous one, and the last segment a port,
+/// variable, or net. A `*` in a segment matches any sequence of characters
+/// within that segment. The match name is the full path of the signal.
+///
+/// Like `instances_of`, this only looks at the syntax, and parameters are not
+/// evaluated. Paths into named generate blocks are rejected with an error,
+/// since their contents depend on elaboration.
+pub fn signals_at<'a>(
+    files: impl IntoIterator<Item = &'a ast::SourceFile<'a>>,
+    path: &str,
+) -> Result<Vec<QueryMatch>, String> {
+    let mut units = vec![];
+    for file in files {
+        for item in &file.items {
+            match item.data {
+                ast::ItemData::ModuleDecl(ref x) => {
+                    units.push(HierUnit::new(x.name.value, &x.ports, &x.items))
+                }
+                ast::ItemData::InterfaceDecl(ref x) => {
+                    units.push(HierUnit::new(x.name.value, &x.ports, &x.items))
+                }
+                _ => (),
+            }
+        }
+    }
+    let by_name: HashMap<Name, &HierUnit> = units.iter().map(|u| (u.name, u)).collect();
+    let segments: Vec<&str> = path.split('.').collect();
+    let mut matches = vec![];
+    for unit in &units {
+        let name = unit.name.as_str();
+        if segment_matches(segments[0], &name) {
+            unit.find_signals(&by_name, &name, &segments[1..], &mut matches)?;
+        }
+    }
+    Ok(matches)
+}
+
+/// The signals and instances of a module or interface.
+struct HierUnit {
+    name: Name,
+    signals: Vec<(Name, Span)>,
+    /// The instance names and the modules they instantiate.
+    insts: Vec<(Name, Name)>,
+    /// The names of the generate blocks.
+    blocks: Vec<Name>,
+}
+
+impl HierUnit {
+    fn new<'a>(name: Name, ports: &'a [ast::Port<'a>], items: &'a [ast::Item<'a>]) -> HierUnit {
+        let mut unit = HierUnit {
+            name,
+            signals: vec![],
+            insts: vec![],
+            blocks: vec![],
+        };
+        for port in ports {
+            match port.data {
+                ast::PortData::Named { name, .. } | ast::PortData::Explicit { name, .. } => {
+                    unit.add_signal(name.value, name.span)
+                }
+                _ => (),
+            }
+        }
+        unit.add_items(items);
+        unit
+    }
+
+    fn add_signal(&mut self, name: Name, span: Span) {
+        if !self.signals.iter().any(|&(n, _)| n == name) {
+            self.signals.push((name, span));
+        }
+    }
+
+    fn add_items<'a>(&mut self, items: &'a [ast::Item<'a>]) {
+        for item in items {
+            let names = match item.data {
+                ast::ItemData::PortDecl(ref x) => &x.names,
+                ast::ItemData::VarDecl(ref x) => &x.names,
+                ast::ItemData::NetDecl(ref x) => &x.names,
+                ast::ItemData::GenerateRegion(_, ref items) => {
+                    self.add_items(items);
+                    continue;
+                }
+                ast::ItemData::Inst(ref x) => {
+                    for name in &x.names {
+                        self.insts.push((name.name.value, x.target.value));
+                    }
+                    continue;
+                }
+                ast::ItemData::GenerateFor(ref x) => {
+                    self.add_block(&x.block);
+                    continue;
+                }
+                ast::ItemData::GenerateIf(ref x) => {
+                    self.add_block(&x.main_block);
+                    if let Some(ref block) = x.else_block {
+                        self.add_block(block);
+                    }
+                    continue;
+                }
+                _ => continue,
+            };
+            for name in names {
+                self.add_signal(name.name, name.name_span);
+            }
+        }
+    }
+
+    fn add_block(&mut self, block: &ast::GenerateBlock) {
+        if let Some(label) = block.label {
+            self.blocks.push(label.value);
+        }
+    }
+
+    fn find_signals(
+        &self,
+        units: &HashMap<Name, &HierUnit>,
+        prefix: &str,
+        segments: &[&str],
+        matches: &mut Vec<QueryMatch>,
+    ) -> Result<(), String> {
+        let (first, rest) = match segments.split_first() {
+            Some(x) => x,
+            None => return Ok(()),
+        };
+        if rest.is_empty() {
+            for &(name, span) in &self.signals {
+                if segment_matches(first, &name.as_str()) {
+                    let path = format!("{}.{}", prefix, name);
+                    matches.push(QueryMatch {
+                        unit: self.name,
+                        name: Some(get_name_table().intern(&path, true)),
+                        span,
+                    });
+                }
+            }
+            return Ok(());
+        }
+        for &name in &self.blocks {
+            if segment_matches(first, &name.as_str()) {
+                return Err(format!(
+                    "`{}.{}` is a generate block; paths into generate blocks are not supported",
+                    prefix, name
+                ));
+            }
+        }
+        for &(name, target) in &self.insts {
+            if let Some(unit) = units.get(&target) {
+                if segment_matches(first, &name.as_str()) {
+                    let prefix = format!("{}.{}", prefix, name);
+                    unit.find_signals(units, &prefix, rest, matches)?;
+                }
+            }
+        }
+        Ok(())
+    }
+}
+
+/// Match a path segment against a pattern with `*` wildcards.
+fn segment_matches(pattern: &str, name: &str) -> bool {
+    match pattern.find('*') {
+        None => pattern == name,
+        Some(i) => {
+            let (head, tail) = (&pattern[..i], &pattern[i + 1..]);
+            name.starts_with(head)
+                && (head.len()..=name.len())
+                    .any(|j| name.is_char_boundary(j) && segment_matches(tail, &name[j..]))
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::{lexer::Lexer, parser::parse, preproc::Preprocessor};
+    use moore_common::name::*;
+    use moore_common::source::*;
+
+    #[test]
+    fn instances() {
+        let src = get_source_manager().add_anonymous(
+            "module foo; endmodule
+            module top; foo u0(), u1(); bar u2(); endmodule",
+        );
+        let arena = ast::Arena::default();
+        let files = vec![parse(Lexer::new(Preprocessor::new(src, &[], &[])), &arena).unwrap()];
+        let names: Vec<_> = instances_of(&files, get_name_table().intern("foo", true))
+            .into_iter()
+            .map(|m| m.name)
+            .collect();
+        let nt = get_name_table();
+        assert_eq!(
+            names,
+            vec![Some(nt.intern("u0", true)), Some(nt.intern("u1", true))]
+        );
+    }
+
+    #[test]
+    fn nested_instances() {
+        let src = get_source_manager().add_anonymous(
+            "module foo; endmodule
+            module top;
+                foo u0();
+                for (genvar i = 0; i < 2; i++) begin : g0
+                    foo u1();
+                end
+                if (1) begin : g1
+                    foo u2();
+                end
+            endmodule",
+        );
+        let arena = ast::Arena::default();
+        let files = vec![parse(Lexer::new(Preprocessor::new(src, &[], &[])), &arena).unwrap()];
+        let names: Vec<_> = instances_of(&files, get_name_table().intern("foo", true))
+            .into_iter()
+            .map(|m| m.name.unwrap().as_str().to_string())
+            .collect();
+        assert_eq!(names, vec!["u0", "u1", "u2"]);
+    }
+
+    #[test]
+    fn drivers() {
+        let src = get_source_manager().add_anonymous(
+            "module top(input clk, output logic [1:0] q, output logic r, s);
+                logic [3:0] a;
+                typedef struct packed { logic x; logic y; } pair_t;
+                pair_t p;
+                always_ff @(posedge clk) begin : regs
+                    int i;
+                    i = 0;
+                    q <= 0;
+                    if (clk) a[i] <= 1;
+                    {r, p.x} <= 0;
+                    q[0] <= 1;
+                end
+                always_comb begin : other
+                    s = 0;
+                end
+                always_comb s = 1;
+            endmodule",
+        );
+        let arena = ast::Arena::default();
+        let files = vec![parse(Lexer::new(Preprocessor::new(src, &[], &[])), &arena).unwrap()];
+        let names = |process| -> Vec<_> {
+            drivers_of(&files, get_name_table().intern(process, true))
+                .into_iter()
+                .map(|m| m.name.unwrap().as_str().to_string())
+                .collect()
+        };
+        assert_eq!(names("regs"), vec!["q", "a", "r", "p"]);
+        assert_eq!(names("other"), vec!["s"]);
+        assert_eq!(names("top"), Vec::<String>::new());
+    }
+
+    #[test]
+    fn users() {
+        let src = get_source_manager().add_anonymous(
+            "package p; endpackage
+            package q; import p::x; endpackage
+            module a import p::*; (); import p::y; endmodule
+            module b; initial begin import p::*; end endmodule
+            module c; import q::*; endmodule
+            interface d; import p::*; endinterface",
+        );
+        let arena = ast::Arena::default();
+        let files = vec![parse(Lexer::new(Preprocessor::new(src, &[], &[])), &arena).unwrap()];
+        let units: Vec<_> = users_of(&files, get_name_table().intern("p", true))
+            .into_iter()
+            .map(|m| m.unit.as_str().to_string())
+            .collect();
+        assert_eq!(units, vec!["q", "a", "b", "d"]);
+    }
+
+    #[test]
+    fn signals() {
+        let src = get_source_manager().add_anonymous(
+            "module fsm(input clk); logic [1:0] state; wire busy; endmodule
+            module top; logic clk; fsm u0(clk), u1(clk); fsm_wrap w(); endmodule
+            module fsm_wrap; fsm inner(); endmodule",
+        );
+        let arena = ast::Arena::default();
+        let files = vec![parse(Lexer::new(Preprocessor::new(src, &[], &[])), &arena).unwrap()];
+        let paths = |path| -> Vec<_> {
+            signals_at(&files, path)
+                .unwrap()
+                .into_iter()
+                .map(|m| m.name.unwrap().as_str().to_string())
+                .collect()
+        };
+        assert_eq!(paths("top.*.state"), vec!["top.u0.state", "top.u1.state"]);
+        assert_eq!(
+            paths("top.u1.*"),
+            vec!["top.u1.clk", "top.u1.state", "top.u1.busy"]
+        );
+        assert_eq!(paths("top.w.inner.st*"), vec!["top.w.inner.state"]);
+        assert_eq!(paths("*.clk"), vec!["fsm.clk", "top.clk"]);
+    }
+
+    #[test]
+    fn nested_scopes() {
+        let src = get_source_manager().add_anonymous(
+            "module fsm; logic [1:0] state; endmodule
+            module top;
+                fsm u0();
+                for (genvar i = 0; i < 2; i++) begin : g0
+                    fsm u1();
+                end
+                if (1) begin : g1
+                    fsm u2();
+                end else begin : g2
+                    fsm u3();
+                end
+            endmodule",
+        );
+        let arena = ast::Arena::default();
+        let files = vec![parse(Lexer::new(Preprocessor::new(src, &[], &[])), &arena).unwrap()];
+
+        // Paths may end next to a generate block, but must not enter one.
+        assert_eq!(signals_at(&files, "top.u0.state").unwrap().len(), 1);
+        assert_eq!(signals_at(&files, "top.g0").unwrap(), vec![]);
+        for path in &[
+            "top.g0.u1.state",
+            "top.g1.u2.state",
+            "top.g2.u3.state",
+            "top.*.state",
+        ] {
+            assert!(signals_at(&files, path)
+                .unwrap_err()
+                .contains("is a generate block"));
+        }
+    }
+}
@@ -0,0 +1,168 @@
+// Copyright (c) 2016-2020 Fabian Schuiki
+
+//! Tests of finding the uses of a declaration for renaming.
+//!
+//! Each test marks names in the source with a `/*N*/` comment in front of
+//! them, and checks that the uses found for the declaration marked `/*0*/` are
+//! exactly the marked names.
+
+use moore_common::name::get_name_table;
+use moore_common::source::{get_source_manager, Span};
+use moore_common::Session;
+use moore_svlog::{lexer::Lexer, parser, preproc::Preprocessor};
+use moore_svlog::{GlobalArenas, GlobalContext};
+
+/// Find the uses of the declaration marked `/*0*/` and check that they are
+/// the marked names.
+fn check_uses(text: &str) {
+    let sess = Session::new();
+    let source = get_source_manager().add_anonymous(text);
+    let arenas = GlobalArenas::default();
+    let preproc = Preprocessor::new(source, &[], &[]);
+    let file = parser::parse(Lexer::new(preproc), &arenas.ast).expect("parser failed");
+    let gcx = GlobalContext::new(&sess, &arenas);
+    gcx.add_files(
+        get_name_table().intern("work", true),
+        std::iter::once(&file),
+    );
+
+    // Locate the marked names.
+    let marked: Vec<Span> = text
+        .match_indices("*/")
+        .map(|(i, _)| {
+            let begin = i + 2;
+            let len = text[begin..]
+                .find(|c: char| !c.is_alphanumeric() && c != '_' && c != '$')
+                .unwrap();
+            Span::new(source, begin, begin + len)
+        })
+        .collect();
+    let decl = marked[text
+        .find("/*0*/")
+        .map(|i| text[..i].matches("*/").count())
+        .unwrap()];
+
+    let uses = moore_svlog::find_decl_uses(&gcx, &file, decl);
+    assert!(!sess.failed(), "compilation failed");
+    let mut expected = marked.clone();
+    expected.retain(|&span| span != decl);
+    expected.insert(0, decl);
+    let extract = |spans: &[Span]| {
+        spans
+            .iter()
+            .map(|s| (s.begin, s.extract()))
+            .collect::<Vec<_>>()
+    };
+    assert_eq!(extract(&uses), extract(&expected));
+}
+
+#[test]
+fn decl_uses() {
+    check_uses(
+        "module foo;
+            logic /*0*/a, b;
+            assign b = /*1*/a;
+            always_comb b = ~/*2*/a;
+        endmodule",
+    );
+
+    // The variable declared in the block shadows the outer one, such that the
+    // uses inside the block are not uses of the outer variable.
+    check_uses(
+        "module foo;
+            logic /*0*/a, b, c;
+            assign b = /*1*/a;
+            always_comb begin : blk
+                logic a;
+                a = 1;
+                c = a;
+            end
+        endmodule",
+    );
+
+    // Only the uses inside the block refer to the inner variable.
+    check_uses(
+        "module foo;
+            logic a, b, c;
+            assign b = a;
+            always_comb begin : blk
+                logic /*0*/a;
+                /*1*/a = 1;
+                c = /*2*/a;
+            end
+        endmodule",
+    );
+
+    // A declaration of the same name in another module is unrelated.
+    check_uses(
+        "module foo;
+            logic /*0*/a, b;
+            assign b = /*1*/a;
+        endmodule
+        module bar;
+            logic a, b;
+            assign b = a;
+        endmodule",
+    );
+}
+
+#[test]
+fn port_and_instance_uses() {
+    // Named port connections refer to the port of the instantiated module,
+    // and the `.name` shorthand also to the local signal of that name.
+    check_uses(
+        "module foo (input logic /*0*/a, output logic z);
+            assign z = /*1*/a;
+        endmodule
+        module top;
+            logic a, x, y;
+            foo i0 (./*2*/a(x), .z(y));
+            foo i1 (./*3*/a, .z());
+        endmodule",
+    );
+
+    // Instances refer to the module they instantiate.
+    check_uses(
+        "module /*0*/foo;
+        endmodule
+        module top;
+            /*1*/foo i0 ();
+            /*2*/foo i1 ();
+        endmodule",
+    );
+}
+
+#[test]
+fn hierarchical_uses() {
+    // The last part of a hierarchical name refers to the declaration in the
+    // package, instance, or generate block it selects from.
+    check_uses(
+        "package pkg;
+            localparam int /*0*/W = 4;
+        endpackage
+        module foo;
+            localparam int W = 2;
+            logic [pkg::/*1*/W-1:0] x;
+            logic [W-1:0] y;
+        endmodule",
+    );
+    check_uses(
+        "module foo;
+            logic /*0*/a;
+        endmodule
+        module top;
+            logic a, b;
+            foo i0 ();
+            assign b = i0./*1*/a;
+        endmodule",
+    );
+    check_uses(
+        "module top;
+            logic a, b;
+            if (1) begin : g
+                logic /*0*/a;
+            end
+            assign b = g./*1*/a;
+        endmodule",
+    );
+}
@@ -1229,6 +1229,15 @@ impl<'a> UnpackedType<'a> {
         unsafe { std::mem::transmute(ty) }
     }
 
+    /// Create a `real` type.
+    pub fn make_real() -> &'a Self {
+        static TYPE: Lazy<UnpackedType> = Lazy::new(|| UnpackedType::new(RealType::Real));
+        let ty: &UnpackedType = &TYPE;
+        // SAFETY: This is safe since the cell which causes 'a to need to
+        // outlive 'static is actually never mutated after AST construction.
+        unsafe { std::mem::transmute(ty) }
+    }
+
     /// Internalize this type in a context and resolve it.
     pub fn intern(mut self, cx: &impl TypeContext<'a>) -> &'a Self {
         let inner = match self.core {
@@ -1533,6 +1542,15 @@ impl<'a> UnpackedType<'a> {
         }
     }
 
+    /// Get the underlying real type, or `None` if the type is no real.
+    pub fn get_real(&self) -> Option<RealType> {
+        if self.dims.is_empty() {
+            self.resolve_full().core.get_real()
+        } else {
+            None
+        }
+    }
+
     /// Helper function to format this type around a declaration name.
     fn format_around(
         &self,
@@ -1692,6 +1710,15 @@ impl<'a> UnpackedCore<'a> {
             _ => None,
         }
     }
+
+    /// Get the underlying real type, or `None` if the type is no real.
+    pub fn get_real(&self) -> Option<RealType> {
+        match *self {
+            UnpackedCore::Real(x) => Some(x),
+            UnpackedCore::Named { ty, .. } | UnpackedCore::Ref { ty, .. } => ty.get_real(),
+            _ => None,
+        }
+    }
 }
 
 impl<'a> From<&'a PackedType<'a>> for UnpackedCore<'a> {
@@ -275,7 +275,7 @@ fn type_of_varlike<'a>(
     if let Some(init) = &ast_name.init {
         let hir = match cx.hir_of(init.id()) {
             Ok(HirNode::Expr(e)) => e,
-            Err(()) => return UnpackedType::make_error(),
+            Err(_) => return UnpackedType::make_error(),
             _ => unreachable!(),
         };
         return cx.type_of_expr(Ref(hir), env);
@@ -311,7 +311,7 @@ pub(crate) fn type_of_value_param<'a>(
         Some(ParamEnvBinding::Indirect(assigned_id)) => {
             let hir = match cx.hir_of(assigned_id.id()) {
                 Ok(HirNode::Expr(e)) => e,
-                Err(()) => return UnpackedType::make_error(),
+                Err(_) => return UnpackedType::make_error(),
                 _ => unreachable!(),
             };
             return cx.type_of_expr(Ref(hir), assigned_id.env());
@@ -324,7 +324,7 @@ pub(crate) fn type_of_value_param<'a>(
     if let Some(ref expr) = ast.expr {
         let hir = match cx.hir_of(expr.id()) {
             Ok(HirNode::Expr(e)) => e,
-            Err(()) => return UnpackedType::make_error(),
+            Err(_) => return UnpackedType::make_error(),
             _ => unreachable!(),
         };
         return cx.type_of_expr(Ref(hir), env);
@@ -414,7 +414,7 @@ pub(crate) fn map_to_type<'a>(
             debug!("Disambiguating {:?}", ast);
             let rst = match cx.disamb_type_or_expr(Ref(ast)) {
                 Ok(rst) => rst,
-                Err(()) => return Some(UnpackedType::make_error()),
+                Err(_) => return Some(UnpackedType::make_error()),
             };
             match rst {
                 ast::TypeOrExpr::Type(ast) => Some(cx.packed_type_from_ast(Ref(ast), env, None)),
@@ -665,7 +665,7 @@ pub(crate) fn packed_type_from_ast<'a>(
             let loc = cx.scope_location(ast);
             let def = match cx.resolve_local_or_error(name, loc, false) {
                 Ok(def) => def,
-                Err(()) => return UnpackedType::make_error(),
+                Err(_) => return UnpackedType::make_error(),
             };
             packed_type_from_def(cx, def, name.span, env)
         }
@@ -779,7 +779,7 @@ pub(crate) fn packed_type_from_ast<'a>(
             // Disambiguate if this is a `type(<type>)` or `type(<expr>)`.
             let arg = match cx.disamb_type_or_expr(Ref(arg)) {
                 Ok(arg) => arg,
-                Err(()) => return UnpackedType::make_error(),
+                Err(_) => return UnpackedType::make_error(),
             };
             let ty = match arg {
                 ast::TypeOrExpr::Expr(expr) => cx.need_self_determined_type(expr.id(), env),
@@ -822,7 +822,7 @@ pub(crate) fn packed_type_from_ast<'a>(
                     ast::TypeDim::Range(lhs, rhs) => {
                         match range_from_bounds_exprs(cx, lhs.id(), rhs.id(), env, ast.span()) {
                             Ok(r) => dims.push(ty::PackedDim::Range(r)),
-                            Err(()) => {
+                            Err(_) => {
                                 failed = true;
                                 continue;
                             }
@@ -953,7 +953,7 @@ fn apply_unpacked_dims<'a>(
             ast::TypeDim::Unsized => dims.push(ty::UnpackedDim::Unsized),
             ast::TypeDim::Expr(size) => match size_from_bounds_expr(cx, size.id(), env, span) {
                 Ok(s) => dims.push(ty::UnpackedDim::Array(s)),
-                Err(()) => {
+                Err(_) => {
                     failed = true;
                     continue;
                 }
@@ -961,7 +961,7 @@ fn apply_unpacked_dims<'a>(
             ast::TypeDim::Range(lhs, rhs) => {
                 match range_from_bounds_exprs(cx, lhs.id(), rhs.id(), env, span) {
                     Ok(r) => dims.push(ty::UnpackedDim::Range(r)),
-                    Err(()) => {
+                    Err(_) => {
                         failed = true;
                         continue;
                     }
@@ -970,7 +970,7 @@ fn apply_unpacked_dims<'a>(
             ast::TypeDim::Queue(Some(init_size)) => {
                 match size_from_bounds_expr(cx, init_size.id(), env, span) {
                     Ok(s) => dims.push(ty::UnpackedDim::Queue(Some(s))),
-                    Err(()) => {
+                    Err(_) => {
                         failed = true;
                         continue;
                     }
@@ -1013,6 +1013,7 @@ pub(crate) fn type_of_expr<'a>(
         // These expressions are have a fully self-determined type.
         hir::ExprKind::IntConst { .. }
         | hir::ExprKind::TimeConst(..)
+        | hir::ExprKind::RealConst(..)
         | hir::ExprKind::StringConst(..)
         | hir::ExprKind::Ident(..)
         | hir::ExprKind::Scope(..)
@@ -1024,6 +1025,10 @@ pub(crate) fn type_of_expr<'a>(
         | hir::ExprKind::Builtin(hir::BuiltinCall::Unsupported)
         | hir::ExprKind::Builtin(hir::BuiltinCall::Clog2(_))
         | hir::ExprKind::Builtin(hir::BuiltinCall::Bits(_))
+        | hir::ExprKind::Builtin(hir::BuiltinCall::Math(..))
+        | hir::ExprKind::Builtin(hir::BuiltinCall::Random(..))
+        | hir::ExprKind::Builtin(hir::BuiltinCall::TestPlusargs(_))
+        | hir::ExprKind::Builtin(hir::BuiltinCall::ValuePlusargs(..))
         | hir::ExprKind::Field(..)
         | hir::ExprKind::Index(..)
         | hir::ExprKind::Assign { .. } => cx.need_self_determined_type(expr.id, env),
@@ -1118,7 +1123,7 @@ pub(crate) fn cast_type<'a>(
 ) -> Option<CastType<'a>> {
     let hir = match cx.hir_of(node_id) {
         Ok(x) => x,
-        Err(()) => return Some(ty::UnpackedType::make_error().into()),
+        Err(_) => return Some(ty::UnpackedType::make_error().into()),
     };
     match hir {
         HirNode::Expr(e) => Some(cx.cast_expr_type(Ref(e), env)),
@@ -1160,6 +1165,8 @@ pub(crate) fn cast_expr_type<'a>(
                     ty
                 ),
                 CastOp::PickModport => format!("implicitly picking modport `{}`", ty),
+                CastOp::RealToInt => format!("round real to integer `{}`", ty),
+                CastOp::IntToReal => format!("convert integer to `{}`", ty),
             };
             d = d.add_note(msg);
         }
@@ -1245,6 +1252,21 @@ fn cast_expr_type_inner<'gcx>(
         }
     }
 
+    // Convert a real to a 64 bit signed integer first, rounding to the nearest
+    // integer. See §6.12.2 "Real and integer conversion".
+    let real_context = match context {
+        TypeContext::Type(ty) => ty.get_real().is_some(),
+        TypeContext::Bool => false,
+    };
+    let inferred = if inferred.get_real().is_some() && !real_context {
+        trace!("  Converting real to integer");
+        let ty = SbvType::new(Domain::TwoValued, Sign::Signed, 64).to_unpacked(cx);
+        cast.add_cast(CastOp::RealToInt, ty);
+        ty
+    } else {
+        inferred
+    };
+
     // Cast the expression to a simple bit vector type.
     let inferred_sbvt = match inferred.get_simple_bit_vector() {
         Some(ty) => {
@@ -1277,6 +1299,15 @@ fn cast_expr_type_inner<'gcx>(
     }
     trace!("  Mapped `{}` to SBVT `{}`", inferred, inferred_sbvt);
 
+    // Convert an integer to a real through a 64 bit signed integer.
+    let (context, to_real) = match context {
+        TypeContext::Type(ty) if real_context => (
+            TypeContext::Type(SbvType::new(Domain::TwoValued, Sign::Signed, 64).to_unpacked(cx)),
+            Some(ty),
+        ),
+        _ => (context, None),
+    };
+
     // Cast the SBVT to a boolean.
     let context = match context {
         TypeContext::Bool => {
@@ -1386,6 +1417,13 @@ fn cast_expr_type_inner<'gcx>(
         return cast;
     }
 
+    // Convert the integer to a real.
+    if let Some(ty) = to_real {
+        trace!("  Converting integer to real");
+        cast.add_cast(CastOp::IntToReal, ty);
+        return cast;
+    }
+
     // If types match now, we're good.
     if context.is_strictly_identical(cast.ty) {
         trace!("  Cast complete");
@@ -1424,7 +1462,7 @@ pub(crate) fn self_determined_type<'a>(
 ) -> Option<&'a UnpackedType<'a>> {
     let hir = match cx.hir_of(node_id) {
         Ok(x) => x,
-        Err(()) => return Some(UnpackedType::make_error()),
+        Err(_) => return Some(UnpackedType::make_error()),
     };
     match hir {
         HirNode::Expr(e) => self_determined_expr_type(cx, e, env),
@@ -1499,6 +1537,9 @@ fn self_determined_expr_type<'gcx>(
         // Time constants are of time type.
         hir::ExprKind::TimeConst(_) => Some(UnpackedType::make_time()),
 
+        // Real constants are of real type.
+        hir::ExprKind::RealConst(_) => Some(UnpackedType::make_real()),
+
         // String literals are of string type.
         hir::ExprKind::StringConst(_) => Some(
             ty::PackedType::make_dims(
@@ -1564,7 +1605,7 @@ fn self_determined_expr_type<'gcx>(
             let repeat = match repeat {
                 Some(repeat) => match cx.constant_int_value_of(repeat, env) {
                     Ok(r) => r.to_usize().unwrap(),
-                    Err(()) => {
+                    Err(_) => {
                         failed = true;
                         0
                     }
@@ -1641,10 +1682,25 @@ fn self_determined_expr_type<'gcx>(
         // Most builtin functions evaluate to the integer type.
         hir::ExprKind::Builtin(hir::BuiltinCall::Unsupported)
         | hir::ExprKind::Builtin(hir::BuiltinCall::Clog2(_))
-        | hir::ExprKind::Builtin(hir::BuiltinCall::Bits(_)) => {
+        | hir::ExprKind::Builtin(hir::BuiltinCall::Bits(_))
+        | hir::ExprKind::Builtin(hir::BuiltinCall::TestPlusargs(_))
+        | hir::ExprKind::Builtin(hir::BuiltinCall::ValuePlusargs(..)) => {
             Some(PackedType::make(cx, ty::IntAtomType::Int).to_unpacked(cx))
         }
 
+        // The math functions evaluate to real. See §20.8.2.
+        hir::ExprKind::Builtin(hir::BuiltinCall::Math(..)) => Some(UnpackedType::make_real()),
+
+        // The random number functions evaluate to `int` or `int unsigned`.
+        hir::ExprKind::Builtin(hir::BuiltinCall::Random(func, ..)) => {
+            let sign = if func.is_signed() {
+                ty::Sign::Signed
+            } else {
+                ty::Sign::Unsigned
+            };
+            Some(PackedType::make_sign(cx, ty::IntAtomType::Int, sign, true).to_unpacked(cx))
+        }
+
         // Member field accesses resolve to the type of the member.
         hir::ExprKind::Field(target, name) => {
             let target_ty = cx.self_determined_type(target, env)?;
@@ -2139,7 +2195,7 @@ pub(crate) fn type_context<'a>(
     let hir_id = cx.parent_node_id(onto).unwrap();
     let hir = match cx.hir_of(hir_id) {
         Ok(x) => x,
-        Err(()) => return None,
+        Err(_) => return None,
     };
     match hir {
         HirNode::Expr(e) => type_context_imposed_by_expr(cx, onto, e, env),
@@ -2382,6 +2438,17 @@ fn type_context_imposed_by_expr<'gcx>(
             Some(cx.need_self_determined_type(expr.id, env).into())
         }
 
+        // The arguments of the math functions are converted to real.
+        hir::ExprKind::Builtin(hir::BuiltinCall::Math(..)) => {
+            Some(UnpackedType::make_real().into())
+        }
+
+        // The seed and bounds of the random number functions are cast to the
+        // `int unsigned` result of the function.
+        hir::ExprKind::Builtin(hir::BuiltinCall::Random(..)) => {
+            Some(cx.need_self_determined_type(expr.id, env).into())
+        }
+
         // Concatenations require their arguments (including repetition counts)
         // to map to a corresponding SBVT.
         hir::ExprKind::Concat(..) => {
@@ -2575,6 +2642,10 @@ pub enum CastOp {
     Domain(ty::Domain),
     /// Pick an interface's modport.
     PickModport,
+    /// Round a real to a 64 bit signed integer.
+    RealToInt,
+    /// Convert a 64 bit signed integer to a real.
+    IntToReal,
 }
 
 impl<'a> CastType<'a> {
@@ -2614,7 +2685,7 @@ impl std::fmt::Display for CastType<'_> {
 pub(crate) fn expr_is_lvalue<'gcx>(cx: &impl Context<'gcx>, onto: NodeId, _env: ParamEnv) -> bool {
     let hir = match cx.hir_of(cx.parent_node_id(onto).unwrap()) {
         Ok(x) => x,
-        Err(()) => return false,
+        Err(_) => return false,
     };
     match hir {
         HirNode::Expr(e) => match e.kind {
@@ -2644,7 +2715,7 @@ fn size_from_bounds_expr<'a>(
 ) -> Result<usize> {
     let size = match cx.constant_value_of(expr, env).kind {
         ValueKind::Int(ref int, ..) => int,
-        ValueKind::Error => return Err(()),
+        ValueKind::Error => return Err(Error::Reported),
         _ => {
             let span = cx.span(expr);
             cx.emit(
@@ -2654,7 +2725,7 @@ fn size_from_bounds_expr<'a>(
                 ))
                 .span(span),
             );
-            return Err(());
+            return Err(Error::Reported);
         }
     };
     let size = match size.to_usize() {
@@ -2665,7 +2736,7 @@ fn size_from_bounds_expr<'a>(
                     .span(span)
                     .add_note(format!("array would contain {} elements", size)),
             );
-            return Err(());
+            return Err(Error::Reported);
         }
     };
     Ok(size)
@@ -2681,7 +2752,7 @@ fn range_from_bounds_exprs<'a>(
     let map_bound = |bound: NodeId| -> Result<&num::BigInt> {
         match cx.constant_value_of(bound, env).kind {
             ValueKind::Int(ref int, ..) => Ok(int),
-            ValueKind::Error => Err(()),
+            ValueKind::Error => Err(Error::Reported),
             _ => {
                 let span = cx.span(bound);
                 cx.emit(
@@ -2691,7 +2762,7 @@ fn range_from_bounds_exprs<'a>(
                     ))
                     .span(span),
                 );
-                return Err(());
+                return Err(Error::Reported);
             }
         }
     };
@@ -2711,7 +2782,7 @@ fn range_from_bounds_exprs<'a>(
                     .span(span)
                     .add_note(format!("array would contain {} elements", size)),
             );
-            return Err(());
+            return Err(Error::Reported);
         }
     };
     let offset = lo.to_isize().unwrap();
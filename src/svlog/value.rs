@@ -14,12 +14,12 @@
 use crate::{
     crate_prelude::*,
     hir::HirNode,
-    ty::{SbvType, UnpackedType},
+    ty::{RealType, SbvType, UnpackedType},
     ParamEnv, ParamEnvBinding,
 };
 use bit_vec::BitVec;
 use itertools::Itertools;
-use num::{BigInt, BigRational, Integer, One, ToPrimitive, Zero};
+use num::{BigInt, BigRational, FromPrimitive, Integer, One, ToPrimitive, Zero};
 
 /// A verilog value.
 pub type Value<'t> = &'t ValueData<'t>;
@@ -50,6 +50,7 @@ impl<'t> ValueData<'t> {
             ValueKind::Void => true,
             ValueKind::Int(ref v, ..) => v.is_zero(),
             ValueKind::Time(ref v) => v.is_zero(),
+            ValueKind::Real(v) => f64::from_bits(v) == 0.0,
             ValueKind::StructOrArray(_) => false,
             ValueKind::Error => true,
         }
@@ -62,6 +63,14 @@ impl<'t> ValueData<'t> {
             _ => None,
         }
     }
+
+    /// Convert the value to a real.
+    pub fn get_real(&self) -> Option<f64> {
+        match self.kind {
+            ValueKind::Real(v) => Some(f64::from_bits(v)),
+            _ => None,
+        }
+    }
 }
 
 /// The different forms a value can assume.
@@ -76,6 +85,9 @@ pub enum ValueKind<'t> {
     Int(BigInt, BitVec, BitVec),
     /// An arbitrary precision time interval.
     Time(BigRational),
+    /// A real number, as the bits of its IEEE 754 double precision
+    /// representation such that it can be compared and hashed.
+    Real(u64),
     /// A struct.
     StructOrArray(Vec<Value<'t>>),
     /// An error occurred during value computation.
@@ -98,6 +110,7 @@ impl std::fmt::Display for ValueKind<'_> {
             ValueKind::Void => write!(f, "void"),
             ValueKind::Int(v, ..) => write!(f, "{}", v),
             ValueKind::Time(v) => write!(f, "{}", v),
+            ValueKind::Real(v) => write!(f, "{}", f64::from_bits(*v)),
             ValueKind::StructOrArray(v) => {
                 write!(f, "{{ {} }}", v.iter().map(|v| &v.kind).format(", "))
             }
@@ -146,6 +159,22 @@ pub fn make_int_special<'a>(
     }
 }
 
+/// Create a new real value.
+///
+/// Panics if `ty` is not a real type. Rounds the value to single precision for
+/// `shortreal`.
+pub fn make_real<'a>(ty: &'a UnpackedType<'a>, value: f64) -> ValueData<'a> {
+    let value = match ty.get_real() {
+        Some(RealType::ShortReal) => value as f32 as f64,
+        Some(_) => value,
+        None => panic!("make_real got type `{}` which is not a real", ty),
+    };
+    ValueData {
+        ty: ty,
+        kind: ValueKind::Real(value.to_bits()),
+    }
+}
+
 /// Create a new time value.
 pub fn make_time<'a>(value: BigRational) -> ValueData<'a> {
     ValueData {
@@ -277,7 +306,7 @@ pub(crate) fn const_mir_rvalue_int<'a>(
 ) -> Result<&'a num::BigInt> {
     match cx.const_mir_rvalue(mir).kind {
         ValueKind::Int(ref x, ..) => Ok(x),
-        ValueKind::Error => Err(()),
+        ValueKind::Error => Err(Error::Reported),
         _ => {
             cx.emit(
                 DiagBuilder2::error(format!(
@@ -286,7 +315,7 @@ pub(crate) fn const_mir_rvalue_int<'a>(
                 ))
                 .span(mir.span),
             );
-            Err(())
+            Err(Error::Reported)
         }
     }
 }
@@ -349,6 +378,35 @@ fn const_mir_rvalue_inner<'a>(cx: &impl Context<'a>, mir: &'a mir::Rvalue<'a>) -
             cx.intern_value(make_int(mir.ty, (value.is_true() as usize).into()))
         }
 
+        mir::RvalueKind::CastRealToInt(value) => {
+            let value = cx.const_mir_rvalue(value.into());
+            if value.is_error() {
+                return cx.intern_value(make_error(mir.ty));
+            }
+            match value.get_real().and_then(|x| BigInt::from_f64(x.round())) {
+                Some(x) => cx.intern_value(make_int(mir.ty, x)),
+                None => {
+                    cx.emit(
+                        DiagBuilder2::error(format!(
+                            "`{}` does not evaluate to a finite number",
+                            mir.span.extract()
+                        ))
+                        .span(mir.span),
+                    );
+                    cx.intern_value(make_error(mir.ty))
+                }
+            }
+        }
+
+        mir::RvalueKind::CastIntToReal(value) => {
+            let value = cx.const_mir_rvalue(value.into());
+            if value.is_error() {
+                return cx.intern_value(make_error(mir.ty));
+            }
+            let x = value.get_int().and_then(|k| k.to_f64());
+            cx.intern_value(make_real(mir.ty, x.unwrap_or(std::f64::NAN)))
+        }
+
         mir::RvalueKind::ConstructArray(ref values) => cx.intern_value(make_array(
             mir.ty,
             (0..values.len())
@@ -495,6 +553,11 @@ fn const_mir_rvalue_inner<'a>(cx: &impl Context<'a>, mir: &'a mir::Rvalue<'a>) -
         }
 
         mir::RvalueKind::Assignment { .. }
+        | mir::RvalueKind::Clog2(_)
+        | mir::RvalueKind::MathCall(..)
+        | mir::RvalueKind::RandomCall(..)
+        | mir::RvalueKind::TestPlusargs(..)
+        | mir::RvalueKind::ValuePlusargs(..)
         | mir::RvalueKind::Var(_)
         | mir::RvalueKind::Port(_)
         | mir::RvalueKind::IntfSignal(..)
@@ -10,7 +10,7 @@
 #![deny(missing_docs)]
 
 use crate::common::errors::*;
-use crate::common::score::{NodeRef, Result};
+use crate::common::score::{Error, NodeRef, Result};
 use crate::common::source::{Span, INVALID_SPAN};
 use crate::common::util::{HasDesc, HasSpan};
 use crate::make_ctx::MakeContext;
@@ -66,7 +66,7 @@ impl<'sbc, 'lazy, 'sb, 'ast, 'ctx> AddContext<'sbc, 'lazy, 'sb, 'ast, 'ctx> {
         self.emit(
             DiagBuilder2::bug(format!("{} not implemented", node.desc())).span(node.human_span()),
         );
-        Err(())
+        Err(Error::Reported)
     }
 
     /// Add an optional node.
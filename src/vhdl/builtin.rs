@@ -13,11 +13,12 @@ use crate::common::name::*;
 use crate::common::score::NodeRef;
 use crate::common::source::*;
 
+use crate::math_real::{MathRealFn, MATH_REAL_CONSTANTS};
 use crate::op::*;
 use crate::scope::Scope;
 use crate::score::{
-    BuiltinOpRef, BuiltinPkgRef, Def, EnumRef, LibRef, ResolvableName, ScopeRef, ScoreBoard,
-    TypeDeclRef, TypeMarkRef, UnitRef,
+    BuiltinConstRef, BuiltinFnRef, BuiltinOpRef, BuiltinPkgRef, Def, EnumRef, LibRef,
+    ResolvableName, ScopeRef, ScoreBoard, TypeDeclRef, TypeMarkRef, UnitRef,
 };
 use crate::ty::*;
 
@@ -33,6 +34,12 @@ lazy_static! {
     pub static ref TEXTIO_PKG_REF: BuiltinPkgRef = BuiltinPkgRef::alloc();
     /// A reference to the package `ENV`.
     pub static ref ENV_PKG_REF: BuiltinPkgRef = BuiltinPkgRef::alloc();
+    /// A reference to the library `IEEE`.
+    pub static ref IEEE_LIB_REF: LibRef = LibRef::alloc();
+    /// A reference to the package `MATH_REAL`.
+    pub static ref MATH_REAL_PKG_REF: BuiltinPkgRef = BuiltinPkgRef::alloc();
+    /// A reference to the package `MATH_COMPLEX`.
+    pub static ref MATH_COMPLEX_PKG_REF: BuiltinPkgRef = BuiltinPkgRef::alloc();
 
     /// The builtin `BOOLEAN` type.
     pub static ref BOOLEAN_TYPE: BuiltinType = BuiltinType::new_enum("BOOLEAN");
@@ -46,6 +53,12 @@ lazy_static! {
         i32::min_value().into(),
         i32::max_value().into()
     ));
+    /// The builtin `REAL` type.
+    pub static ref REAL_TYPE: BuiltinType = BuiltinType::new("REAL", RealTy::new(
+        Dir::To,
+        -std::f64::MAX,
+        std::f64::MAX
+    ));
     /// The builtin `TIME` type.
     pub static ref TIME_TYPE: BuiltinType = {
         let id = TypeDeclRef::alloc();
@@ -106,6 +119,28 @@ lazy_static! {
     pub static ref FILE_OPEN_KIND_TYPE: BuiltinType = BuiltinType::new_enum("FILE_OPEN_KIND");
     /// The builtin `FILE_OPEN_STATUS` type.
     pub static ref FILE_OPEN_STATUS_TYPE: BuiltinType = BuiltinType::new_enum("FILE_OPEN_STATUS");
+    /// The builtin `MATH_COMPLEX.COMPLEX` type.
+    pub static ref COMPLEX_TYPE: BuiltinType = BuiltinType::new("COMPLEX", RecordTy::new(vec![
+        (get_name_table().intern("RE", false), Box::new(REAL_TYPE.named_ty())),
+        (get_name_table().intern("IM", false), Box::new(REAL_TYPE.named_ty())),
+    ]));
+    /// The builtin `MATH_COMPLEX.COMPLEX_POLAR` type.
+    pub static ref COMPLEX_POLAR_TYPE: BuiltinType = BuiltinType::new("COMPLEX_POLAR", RecordTy::new(vec![
+        (get_name_table().intern("MAG", false), Box::new(REAL_TYPE.named_ty())),
+        (get_name_table().intern("ARG", false), Box::new(REAL_TYPE.named_ty())),
+    ]));
+
+    /// The functions of package `MATH_REAL`.
+    static ref MATH_REAL_FNS: Vec<(BuiltinFnRef, MathRealFn)> = MathRealFn::ALL
+        .iter()
+        .map(|&func| (BuiltinFnRef::alloc(), func))
+        .collect();
+
+    /// The constants of package `MATH_REAL`.
+    static ref MATH_REAL_CONSTS: Vec<(BuiltinConstRef, &'static str, f64)> = MATH_REAL_CONSTANTS
+        .iter()
+        .map(|&(name, value)| (BuiltinConstRef::alloc(), name, value))
+        .collect();
 
     // A list of builtin unary operators.
     static ref BUILTIN_UNARY_OPS: Vec<BuiltinUnaryOp> = vec![
@@ -165,6 +200,7 @@ lazy_static! {
         bi.push(wrapup_type_builtin(&BIT_TYPE));
         bi.push(wrapup_type_builtin(&SEVERITY_LEVEL_TYPE));
         bi.push(wrapup_type_builtin(&INTEGER_TYPE));
+        bi.push(wrapup_type_builtin(&REAL_TYPE));
         bi.push(wrapup_type_builtin(&TIME_TYPE));
         bi.push(wrapup_type_builtin(&DELAY_LENGTH_TYPE));
         bi.push(wrapup_type_builtin(&NATURAL_TYPE));
@@ -177,6 +213,32 @@ lazy_static! {
         bi.push(wrapup_type_builtin(&FILE_OPEN_STATUS_TYPE));
         bi
     };
+
+    /// The builtins of package `MATH_COMPLEX`.
+    static ref MATH_COMPLEX_BUILTINS: Vec<(Builtin, Vec<Builtin>)> = {
+        let mut bi = Vec::new();
+        bi.push(wrapup_type_builtin(&COMPLEX_TYPE));
+        bi.push(wrapup_type_builtin(&COMPLEX_POLAR_TYPE));
+        bi
+    };
+}
+
+/// Find the `MATH_REAL` function a builtin function refers to.
+pub fn math_real_fn(id: BuiltinFnRef) -> MathRealFn {
+    MATH_REAL_FNS
+        .iter()
+        .find(|&&(fid, _)| fid == id)
+        .map(|&(_, func)| func)
+        .expect("builtin function should exist")
+}
+
+/// Find the value of a builtin constant.
+pub fn builtin_const_value(id: BuiltinConstRef) -> f64 {
+    MATH_REAL_CONSTS
+        .iter()
+        .find(|&&(cid, _, _)| cid == id)
+        .map(|&(_, _, value)| value)
+        .expect("builtin constant should exist")
 }
 
 /// Add the definition for a builtin resolvable name to a scope.
@@ -234,6 +296,7 @@ fn wrapup_type_builtin(bt: &BuiltinType) -> (Builtin, Vec<Builtin>) {
     match bt.ty {
         Ty::Enum(_) => enum_type_builtins(&bt.named_ty(), &mut aux),
         Ty::Int(_) => integer_type_builtins(&bt.named_ty(), &mut aux),
+        Ty::Real(_) => real_type_builtins(&bt.named_ty(), &mut aux),
         Ty::Physical(_) => physical_type_builtins(&bt.named_ty(), &mut aux),
         Ty::Array(ref at) => array_type_builtins(&bt.named_ty(), at, &mut aux),
         _ => (),
@@ -386,6 +449,44 @@ lazy_static! {
         scope
     };
 
+    /// The scope of the library `IEEE`.
+    ///
+    /// Only the packages which can be evaluated during elaboration are
+    /// provided as builtins.
+    pub static ref IEEE_LIB_SCOPE: Scope = {
+        let mut scope = Scope::new(Some(*ROOT_SCOPE_REF));
+        define_builtin_ident(&mut scope, "MATH_REAL", Def::BuiltinPkg(*MATH_REAL_PKG_REF));
+        define_builtin_ident(&mut scope, "MATH_COMPLEX", Def::BuiltinPkg(*MATH_COMPLEX_PKG_REF));
+        scope
+    };
+
+    /// The scope of the package `MATH_REAL`.
+    pub static ref MATH_REAL_PKG_SCOPE: Scope = {
+        let mut scope = Scope::new(Some((*IEEE_LIB_REF).into()));
+        for &(id, func) in &*MATH_REAL_FNS {
+            define_builtin_ident(&mut scope, func.name(), Def::BuiltinFn(id));
+        }
+        for &(id, name, _) in &*MATH_REAL_CONSTS {
+            define_builtin_ident(&mut scope, name, Def::BuiltinConst(id));
+        }
+        scope
+    };
+
+    /// The scope of the package `MATH_COMPLEX`.
+    ///
+    /// Only the `COMPLEX` and `COMPLEX_POLAR` types are provided. None of the
+    /// complex functions can be evaluated during elaboration.
+    pub static ref MATH_COMPLEX_PKG_SCOPE: Scope = {
+        let mut scope = Scope::new(Some((*IEEE_LIB_REF).into()));
+        for &(ref bt, ref aux) in &*MATH_COMPLEX_BUILTINS {
+            define_builtin(&mut scope, bt.name, bt.def);
+            for a in aux {
+                define_builtin(&mut scope, a.name, a.def);
+            }
+        }
+        scope
+    };
+
     /// All builtin scopes.
     ///
     /// These are added to the scoreboard upon construction.
@@ -393,6 +494,9 @@ lazy_static! {
         (*ROOT_SCOPE_REF, &*ROOT_SCOPE),
         ((*STD_LIB_REF).into(), &*STD_LIB_SCOPE),
         ((*STANDARD_PKG_REF).into(), &*STANDARD_PKG_SCOPE),
+        ((*IEEE_LIB_REF).into(), &*IEEE_LIB_SCOPE),
+        ((*MATH_REAL_PKG_REF).into(), &*MATH_REAL_PKG_SCOPE),
+        ((*MATH_COMPLEX_PKG_REF).into(), &*MATH_COMPLEX_PKG_SCOPE),
     ];
 
     /// All builtin scope references.
@@ -417,6 +521,7 @@ pub fn register_builtins<'ast, 'ctx>(sb: &ScoreBoard<'ast, 'ctx>) {
     sb.typeval_table.borrow_mut().extend(
         (*STANDARD_BUILTINS)
             .iter()
+            .chain((*MATH_COMPLEX_BUILTINS).iter())
             .flat_map(|&(ref bi, ref aux)| once(bi).chain(aux.iter()))
             .filter_map(|bi| match bi.ty {
                 Some(ref ty) => Some((bi.def.into(), Ok(sb.intern_ty(ty.clone())))),
@@ -856,6 +961,33 @@ impl BuiltinType {
     }
 }
 
+/// Determine the number of literals of a builtin enumeration type.
+///
+/// Returns `None` if the type is not a builtin enumeration.
+pub fn builtin_enum_len(decl: TypeDeclRef) -> Option<usize> {
+    [
+        (&*BOOLEAN_TYPE, 2),
+        (&*BIT_TYPE, 2),
+        (&*SEVERITY_LEVEL_TYPE, 4),
+        (&*FILE_OPEN_KIND_TYPE, 3),
+        (&*FILE_OPEN_STATUS_TYPE, 4),
+    ]
+    .iter()
+    .find(|&&(bt, _)| bt.id == decl)
+    .map(|&(_, len)| len)
+}
+
+/// Determine the name of a builtin type.
+///
+/// Returns `None` if the type is not a builtin.
+pub fn builtin_type_name(decl: TypeDeclRef) -> Option<ResolvableName> {
+    (*STANDARD_BUILTINS)
+        .iter()
+        .chain((*MATH_COMPLEX_BUILTINS).iter())
+        .find(|&&(ref bi, _)| bi.def == Def::Type(decl))
+        .map(|&(ref bi, _)| bi.name)
+}
+
 /// A helper to build an enum.
 struct EnumBuilder {
     id: TypeDeclRef,
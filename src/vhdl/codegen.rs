@@ -2,14 +2,111 @@
 
 //! LLHD code generation for VHDL.
 
+use crate::builtin::builtin_enum_len;
 use crate::hir;
 use crate::konst::*;
+use crate::op::{BinaryOp, LogicalOp, RelationalOp, UnaryOp};
 use crate::score::*;
 use crate::ty::*;
 use llhd;
+use llhd::Visitor as _;
 use moore_common::errors::*;
-use moore_common::score::Result;
-use num::{Signed, ToPrimitive, Zero};
+use moore_common::name::{get_name_table, llhd_name, Name};
+use moore_common::score::{Error, Result};
+use moore_common::source::{Span, Spanned};
+use moore_common::{MessageSeverity, Scheduling, VhdlStandard, XPolicy};
+use num::{BigInt, BigRational, One, Signed, ToPrimitive, Zero};
+use std::collections::{HashMap, HashSet};
+use std::fmt;
+
+/// A verification directive in an emitted entity.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Directive {
+    /// The name of the entity containing the directive.
+    pub entity: String,
+    /// The kind of the directive.
+    pub kind: hir::DirectiveKind,
+    /// The label of the directive, if any.
+    pub label: Option<Name>,
+    /// The severity of a failing assertion, after remapping.
+    pub severity: Option<MessageSeverity>,
+    /// The text of the report message of an assertion, if it is a literal.
+    pub report: Option<Name>,
+    /// The name of the process checking an assertion, if any.
+    pub process: Option<String>,
+    /// The location of the directive in the source.
+    pub span: Span,
+}
+
+impl fmt::Display for Directive {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{}", self.kind.keyword())?;
+        if let Some(label) = self.label {
+            write!(f, " `{}`", label)?;
+        }
+        write!(f, " at line {}", self.span.begin().human_line())?;
+        if let Some(severity) = self.severity {
+            write!(f, " severity {}", severity)?;
+        }
+        if let Some(report) = self.report {
+            write!(f, " report \"{}\"", report)?;
+        }
+        if let Some(ref process) = self.process {
+            write!(f, " checked by @{}", process)?;
+        }
+        Ok(())
+    }
+}
+
+/// Render the LLHD assembly of a module, with the directives of every entity
+/// listed in a comment before the entity.
+///
+/// Processes are added to the module as they are generated, but their entity
+/// only once all of it has been generated. An entity that fails, e.g. with
+/// `--keep-going`, thus leaves behind processes that nothing instantiates.
+/// LLHD 0.5 cannot remove units from a module, so these are skipped here.
+pub fn write_annotated(module: &llhd::Module, directives: &[Directive]) -> String {
+    let instantiated: HashSet<_> = module
+        .values()
+        .flat_map(|value| match *value {
+            llhd::ValueRef::Entity(r) => module.entity(r).insts().collect(),
+            _ => vec![],
+        })
+        .filter_map(|inst| match *inst.kind() {
+            llhd::InstanceInst(_, llhd::ValueRef::Process(r), ..) => Some(r),
+            _ => None,
+        })
+        .collect();
+    let ctx = llhd::ModuleContext::new(module);
+    let units: Vec<_> = module
+        .values()
+        .filter(|value| match **value {
+            llhd::ValueRef::Process(r) => instantiated.contains(&r),
+            _ => true,
+        })
+        .map(|value| {
+            let mut asm = vec![];
+            llhd::assembly::Writer::new(&mut asm).visit_module_value(&ctx, value);
+            String::from_utf8(asm).expect("writer should emit proper utf8")
+        })
+        .collect();
+    let asm = units.join("\n");
+    let mut output = String::with_capacity(asm.len());
+    for line in asm.lines() {
+        let prefix = "entity @";
+        if line.starts_with(prefix) {
+            let entity = line[prefix.len()..].split(' ').next().unwrap_or("");
+            for directive in directives {
+                if directive.entity == entity {
+                    output.push_str(&format!("; {}\n", directive));
+                }
+            }
+        }
+        output.push_str(line);
+        output.push('\n');
+    }
+    output
+}
 
 /// Generates LLHD code.
 pub trait Codegen<I, C> {
@@ -38,7 +135,7 @@ macro_rules! unimp {
             "code generation for {:?} not implemented",
             $id
         )));
-        return Err(());
+        return Err(Error::Reported);
     }};
 }
 
@@ -50,80 +147,45 @@ impl<'lazy, 'sb, 'ast, 'ctx> ScoreContext<'lazy, 'sb, 'ast, 'ctx> {
             Ty::Named(..) => unreachable!(),
             Ty::Null => llhd::void_ty(),
             Ty::Int(ref ty) => {
-                let diff = match ty.dir {
-                    hir::Dir::To => &ty.right_bound - &ty.left_bound,
-                    hir::Dir::Downto => &ty.left_bound - &ty.right_bound,
-                };
-                if diff.is_negative() {
+                if ty.len().is_negative() {
                     llhd::void_ty()
                 } else {
-                    llhd::int_ty(diff.bits())
+                    llhd::int_ty(int_width(ty))
                 }
             }
-            Ty::Enum(ref ty) => {
-                let hir = self.lazy_hir(ty.decl)?;
-                match hir.data.as_ref().unwrap().value {
-                    hir::TypeData::Enum(ref lits) => llhd::enum_ty(lits.len()),
-                    _ => unreachable!(),
+            // Enums are mapped to the index of their literal, such that `BIT`
+            // and `BOOLEAN` become a single bit.
+            Ty::Enum(ref ty) => match builtin_enum_len(ty.decl) {
+                Some(len) => llhd::int_ty(enum_width(len)),
+                None => {
+                    let hir = self.lazy_hir(ty.decl)?;
+                    match hir.data.as_ref().unwrap().value {
+                        hir::TypeData::Enum(ref lits) if self.is_two_state_logic(lits) => {
+                            llhd::int_ty(1)
+                        }
+                        hir::TypeData::Enum(ref lits) => llhd::int_ty(enum_width(lits.len())),
+                        _ => unreachable!(),
+                    }
                 }
-            }
-            Ty::Physical(ref ty) => {
-                self.emit(DiagBuilder2::error(format!(
-                    "cannot generate code for physical type `{}`",
-                    ty
-                )));
-                return Err(());
-            }
+            },
+            // Times are mapped to the LLHD time type, all other physical types
+            // to integers counting their primary unit.
+            Ty::Physical(ref ty) if is_time_type(ty) => llhd::time_ty(),
+            Ty::Physical(ref ty) => self.map_type(&Ty::Int(ty.base.clone()))?,
             Ty::Access(ref ty) => llhd::pointer_ty(self.map_type(ty)?),
             Ty::Array(ref ty) => {
+                // Multidimensional arrays are mapped to nested arrays, with the
+                // first index outermost. Each index is normalized to the number
+                // of values in its range, such that `0 to 7` and `7 downto 0`
+                // both map to eight elements. An array with a null index range
+                // has no elements. See IEEE 1076-2008 section 5.3.2.
                 let mut llty = self.map_type(&ty.element)?;
                 for index in ty.indices.iter().rev() {
-                    match *index {
-                        ArrayIndex::Unbounded(_) => {
-                            self.emit(
-                                DiagBuilder2::error(format!("type `{}` is unbounded", ty)), // TODO: What span should we use here?
-                            );
-                            return Err(());
-                        }
-                        ArrayIndex::Constrained(ref ty) => {
-                            let num = match **ty {
-                                Ty::Int(ref ty) => {
-                                    let l = ty.len();
-                                    if l.is_negative() || l.is_zero() {
-                                        return Ok(llhd::void_ty());
-                                    }
-                                    match l.to_usize() {
-                                        Some(l) => l,
-                                        None => {
-                                            self.emit(
-                                                DiagBuilder2::error(format!(
-                                                    "array index `{}` is too large; {} elements",
-                                                    ty, l
-                                                )), // TODO: What span should we use here?
-                                            );
-                                            return Err(());
-                                        }
-                                    }
-                                }
-                                Ty::Enum(ref ty) => {
-                                    match self.lazy_hir(ty.decl)?.data.as_ref().unwrap().value {
-                                        hir::TypeData::Enum(ref lits) => lits.len(),
-                                        _ => unreachable!(),
-                                    }
-                                }
-                                _ => {
-                                    self.emit(
-                                        DiagBuilder2::error(format!(
-                                            "`{}` is an invalid array index type",
-                                            ty
-                                        )), // TODO: What span should we use here?
-                                    );
-                                    return Err(());
-                                }
-                            };
-                            llty = llhd::array_ty(num, llty);
-                        }
+                    let len = self.array_index_len(ty, index)?;
+                    if len == 0 {
+                        return Ok(llhd::void_ty());
                     }
+                    llty = llhd::array_ty(len, llty);
                 }
                 llty
             }
@@ -136,183 +198,3469 @@ impl<'lazy, 'sb, 'ast, 'ctx> ScoreContext<'lazy, 'sb, 'ast, 'ctx> {
                     .collect::<Result<_>>()?;
                 llhd::struct_ty(fields)
             }
-            Ty::Subprog(..) => unimplemented!(),
+            Ty::Subprog(ref ty) => {
+                let args = ty
+                    .args
+                    .iter()
+                    .map(|arg| self.map_type(&arg.ty))
+                    .collect::<Result<_>>()?;
+                let ret = match ty.ret {
+                    Some(ref ret) => self.map_type(ret)?,
+                    None => llhd::void_ty(),
+                };
+                llhd::func_ty(args, ret)
+            }
             // Unbounded integers cannot be mapped to LLHD. All cases where
             // such an int can leak through to codegen should actually be caught
             // beforehand in the type check.
             Ty::UnboundedInt | Ty::UniversalInt => unreachable!(),
+            // LLHD has no floating-point type. Reals are carried as integers
+            // holding their IEEE 754 double precision encoding.
+            Ty::Real(..) | Ty::UniversalReal => llhd::int_ty(64),
+        })
+    }
+
+    /// Determine the number of elements along an index of an array type.
+    ///
+    /// An index with a null range has no elements.
+    pub fn array_index_len(&self, ty: &ArrayTy, index: &ArrayIndex) -> Result<usize> {
+        let index_ty = match *index {
+            ArrayIndex::Unbounded(_) => {
+                self.emit(DiagBuilder2::error(format!(
+                    "cannot generate code for unconstrained array type `{}`",
+                    ty
+                )));
+                return Err(Error::Reported);
+            }
+            ArrayIndex::Constrained(ref index_ty) => self.deref_named_type(index_ty)?,
+        };
+        Ok(match *index_ty {
+            Ty::Null => 0,
+            Ty::Int(ref index_ty) => {
+                let len = index_ty.len();
+                if len.is_negative() {
+                    return Ok(0);
+                }
+                match len.to_usize() {
+                    Some(len) => len,
+                    None => {
+                        self.emit(DiagBuilder2::error(format!(
+                            "array index `{}` of type `{}` is too large; {} elements",
+                            index_ty, ty, len
+                        )));
+                        return Err(Error::Reported);
+                    }
+                }
+            }
+            Ty::Enum(ref index_ty) => match builtin_enum_len(index_ty.decl) {
+                Some(len) => len,
+                None => match self.lazy_hir(index_ty.decl)?.data.as_ref().unwrap().value {
+                    hir::TypeData::Enum(ref lits) => lits.len(),
+                    _ => unreachable!(),
+                },
+            },
+            _ => {
+                self.emit(DiagBuilder2::error(format!(
+                    "`{}` is an invalid index type of array type `{}`",
+                    index_ty, ty
+                )));
+                return Err(Error::Reported);
+            }
+        })
+    }
+
+    /// Determine the severity of an assertion, after applying the mapping
+    /// given with `--severity`. Assertions without a severity are errors.
+    fn assert_severity(&self, severity: Option<ExprRef>) -> Result<MessageSeverity> {
+        let severity = match severity {
+            Some(expr) => match self.enum_literal(expr, crate::builtin::SEVERITY_LEVEL_TYPE.id)? {
+                Some(index) => MessageSeverity::ALL[index],
+                None => {
+                    self.emit(
+                        DiagBuilder2::error("severity of assertion must be a constant")
+                            .span(self.lazy_hir(expr)?.span),
+                    );
+                    return Err(Error::Reported);
+                }
+            },
+            None => MessageSeverity::Error,
+        };
+        Ok(self.sess.opts.map_severity(severity))
+    }
+
+    /// Determine the position of an expression which is a literal of the given
+    /// enumeration type.
+    fn enum_literal(&self, expr: ExprRef, ty: TypeDeclRef) -> Result<Option<usize>> {
+        let hir = self.lazy_hir(expr)?;
+        Ok(match hir.data {
+            hir::ExprData::Name(Def::Enum(EnumRef(decl, index)), _) if decl == ty => Some(index),
+            hir::ExprData::EnumName(ref lits) => {
+                let mut lits = lits.iter().filter(|lit| lit.value.0 == ty);
+                match (lits.next(), lits.next()) {
+                    (Some(lit), None) => Some(lit.value.1),
+                    _ => None,
+                }
+            }
+            _ => None,
         })
     }
 
+    /// Check whether an enumeration type is nine-valued logic like
+    /// `std_ulogic`, which is lowered to a single bit with `--two-state`.
+    fn is_two_state_logic(&self, lits: &[hir::EnumLit]) -> bool {
+        self.sess.opts.two_state.is_some()
+            && lits.len() == 9
+            && lits
+                .iter()
+                .zip("UX01ZWLH-".chars())
+                .all(|(lit, c)| match *lit {
+                    hir::EnumLit::Char(ref lit) => lit.value == c,
+                    _ => false,
+                })
+    }
+
+    /// Map a literal of nine-valued logic to a bit, according to the policy
+    /// for unknown and high-impedance values. Uninitialized values, which
+    /// signals without an explicit initial value start out with, map to `0`.
+    fn map_logic_literal(&self, lit: &hir::EnumLit) -> Result<usize> {
+        let lit = match *lit {
+            hir::EnumLit::Char(lit) => lit,
+            _ => unreachable!(),
+        };
+        match (lit.value, self.sess.opts.two_state.unwrap()) {
+            ('0', _) | ('L', _) => Ok(0),
+            ('1', _) | ('H', _) => Ok(1),
+            ('U', _) | (_, XPolicy::Zero) => Ok(0),
+            (_, XPolicy::One) => Ok(1),
+            (_, XPolicy::Error) => {
+                self.emit(
+                    DiagBuilder2::error(format!("`'{}'` has no two-state equivalent", lit.value))
+                        .span(lit.span)
+                        .add_note("Use `--x-policy zero` or `--x-policy one` to map it to a bit"),
+                );
+                Err(Error::Reported)
+            }
+        }
+    }
+
     /// Map a constant value to the LLHD counterpart.
     pub fn map_const(&self, konst: &Const) -> Result<llhd::ValueRef> {
         Ok(match *konst {
             // TODO: Map this to llhd::const_void once available.
             Const::Null => llhd::const_int(0, 0.into()),
-            Const::Int(ref k) => llhd::const_int(999, k.value.clone()),
-            Const::Enum(ref k) => {
-                let size = match self.lazy_hir(k.decl)?.data.as_ref().unwrap().value {
-                    hir::TypeData::Enum(ref lits) => lits.len(),
-                    _ => unreachable!(),
-                };
-                llhd::const_int(size, k.index.into())
+            // Integers of a known type have the width of that type.
+            Const::Int(ConstInt {
+                ty: Some(ref ty),
+                ref value,
+            }) => self.map_int_const(ty, value)?,
+            Const::Int(ref k) => {
+                self.emit(DiagBuilder2::bug(format!(
+                    "integer constant `{}` has no type to determine its width",
+                    k
+                )));
+                return Err(Error::Reported);
+            }
+            Const::Enum(ref k) => match builtin_enum_len(k.decl) {
+                Some(len) => llhd::const_int(enum_width(len), k.index.into()),
+                None => {
+                    let lits = match self.lazy_hir(k.decl)?.data.as_ref().unwrap().value {
+                        hir::TypeData::Enum(ref lits) => lits,
+                        _ => unreachable!(),
+                    };
+                    if self.is_two_state_logic(lits) {
+                        llhd::const_int(1, self.map_logic_literal(&lits[k.index])?.into())
+                    } else {
+                        llhd::const_int(enum_width(lits.len()), k.index.into())
+                    }
+                }
+            },
+            Const::Physical(ref k) if is_time_type(&k.ty) => {
+                llhd::const_time(fs_to_seconds(&k.value), 0, 0).into()
             }
-            Const::Float(ref _k) => panic!("cannot map float constant"),
+            Const::Physical(ref k) => self.map_int_const(&k.ty.base, &k.value)?,
+            Const::Float(ref k) => llhd::const_int(64, k.value.to_bits().into()),
             Const::IntRange(_) | Const::FloatRange(_) => panic!("cannot map range constant"),
+            Const::Record(ref k) => {
+                let fields = k
+                    .fields
+                    .iter()
+                    .map(|field| self.map_const(field))
+                    .collect::<Result<_>>()?;
+                return Ok(llhd::const_struct(fields));
+            }
+            Const::Array(ref k) => {
+                let elements = k
+                    .elements
+                    .iter()
+                    .map(|element| self.map_const(element))
+                    .collect::<Result<_>>()?;
+                return Ok(const_array(elements));
+            }
         }
         .into())
     }
-}
 
-impl_codegen!(self, id: DeclInBlockRef, ctx: &mut llhd::Entity => {
-    match id {
-        DeclInBlockRef::Subprog(id)     => self.codegen(id, &mut ()),
-        DeclInBlockRef::SubprogBody(id) => self.codegen(id, &mut ()),
-        DeclInBlockRef::SubprogInst(id) => self.codegen(id, &mut ()),
-        DeclInBlockRef::Pkg(id)         => self.codegen(id, &mut ()),
-        DeclInBlockRef::PkgBody(id)     => self.codegen(id, &mut ()),
-        DeclInBlockRef::PkgInst(id)     => self.codegen(id, &mut ()),
-        DeclInBlockRef::Type(_id)       => Ok(()),
-        DeclInBlockRef::Subtype(_id)    => Ok(()),
-        DeclInBlockRef::Const(id)       => self.codegen(id, ctx),
-        DeclInBlockRef::Signal(id)      => self.codegen(id, ctx),
-        DeclInBlockRef::Var(id)         => self.codegen(id, ctx),
-        DeclInBlockRef::File(id)        => self.codegen(id, ctx),
-        DeclInBlockRef::Alias(_id)      => Ok(()),
-        DeclInBlockRef::Comp(id)        => self.codegen(id, &mut ()),
-        DeclInBlockRef::Attr(_id)       => Ok(()),
-        DeclInBlockRef::AttrSpec(_id)   => Ok(()),
-        DeclInBlockRef::CfgSpec(_id)    => Ok(()),
-        DeclInBlockRef::Discon(_id)     => Ok(()),
-        DeclInBlockRef::GroupTemp(_id)  => Ok(()),
-        DeclInBlockRef::Group(_id)      => Ok(()),
+    /// Map a constant value to the LLHD counterpart of a type.
+    ///
+    /// Integers and physical values take on the width of `ty` rather than of
+    /// the type they were computed in, such that literals and values of a
+    /// base type fit the subtype they are assigned to.
+    pub fn map_const_of_type(&self, konst: &Const, ty: &Ty) -> Result<llhd::ValueRef> {
+        Ok(match (konst, self.deref_named_type(ty)?) {
+            (&Const::Int(ref k), &Ty::Int(ref ty)) => self.map_int_const(ty, &k.value)?.into(),
+            (&Const::Physical(ref k), &Ty::Physical(ref ty)) if !is_time_type(ty) => {
+                self.map_int_const(&ty.base, &k.value)?.into()
+            }
+            (&Const::Record(ref k), &Ty::Record(ref ty)) => {
+                let fields = k
+                    .fields
+                    .iter()
+                    .zip(ty.fields.iter())
+                    .map(|(field, &(_, ref ty))| self.map_const_of_type(field, ty))
+                    .collect::<Result<_>>()?;
+                llhd::const_struct(fields)
+            }
+            (&Const::Array(ref k), &Ty::Array(ref ty)) => {
+                let element = ty.first_element();
+                let elements = k
+                    .elements
+                    .iter()
+                    .map(|value| self.map_const_of_type(value, &element))
+                    .collect::<Result<_>>()?;
+                const_array(elements)
+            }
+            _ => self.map_const(konst)?,
+        })
     }
-});
 
-impl_codegen!(self, id: ConstDeclRef, _ctx: &mut llhd::Entity => {
-    unimp!(self, id);
-});
+    /// Map an integer value to a constant of the width of its type.
+    ///
+    /// Types with a negative bound hold values in two's complement, all others
+    /// hold them unsigned. A value outside the range of its type is an error,
+    /// since it would be truncated to the width of the type.
+    fn map_int_const(&self, ty: &IntTy, value: &BigInt) -> Result<llhd::Const> {
+        let (lo, hi) = int_bounds(ty);
+        if value < lo || value > hi {
+            self.emit(DiagBuilder2::error(format!(
+                "value {} is out of the range {} of its type",
+                value, ty
+            )));
+            return Err(Error::Reported);
+        }
+        let width = int_width(ty);
+        Ok(llhd::const_int(width, twos_complement(width, value)))
+    }
 
-impl_codegen!(self, id: VarDeclRef, _ctx: &mut llhd::Entity => {
-    unimp!(self, id);
-});
+    /// Determine the name of a signal or port.
+    fn signal_name(&self, sig: SignalRef) -> Result<Name> {
+        Ok(match sig {
+            SignalRef::Intf(id) => self.hir(id)?.name.value,
+            SignalRef::Decl(id) => self.lazy_hir(id)?.name.value,
+            SignalRef::Guard(_) => get_name_table().intern("GUARD", false),
+        })
+    }
 
-impl_codegen!(self, id: SignalDeclRef, ctx: &mut llhd::Entity => {
-    // Determine the type of the signal.
-    let hir = self.lazy_hir(id)?;
-    let ty = self.lazy_typeval(id)?;
+    /// Determine the name of something declared in a scope.
+    ///
+    /// Within a generated block the name is prefixed with the name of the
+    /// block, which is the label of the generate statement followed by the
+    /// value of the generate parameter, such that the names are unique in the
+    /// flattened entity. Extended identifiers are escaped for LLHD.
+    fn local_name(&self, mut scope: ScopeRef, name: impl fmt::Display) -> Result<String> {
+        let mut name = name.to_string();
+        while let ScopeRef::GenBlock(id) = scope {
+            let block = self.existing_hir(id)?;
+            let mut prefix = match block.label {
+                Some(label) => label.value.to_string(),
+                None => "gen".to_string(),
+            };
+            if let Some(ref param) = block.param {
+                prefix = format!("{}_{}", prefix, param.value);
+            }
+            name = format!("{}_{}", prefix, name);
+            scope = block.parent;
+        }
+        Ok(llhd_name(&name))
+    }
 
-    // Calculate the initial value for the signal, either from the provided
-    // expression or implicitly.
-    let init = if let Some(init_id) = hir.decl.init {
-        self.const_value(init_id)?
-    } else {
-        self.default_value_for_type(&ty)?
-    };
+    /// Collect the signals read by an expression, in the order they appear.
+    ///
+    /// Every signal named in the expression counts as read, including the
+    /// prefixes of selections and attributes, the elements of aggregates, and
+    /// the actuals of calls. See IEEE 1076-2008 section 11.3 for the same rule
+    /// applied to the `all` sensitivity list.
+    fn signals_read(&self, expr: ExprRef, into: &mut Vec<SignalRef>) -> Result<()> {
+        match self.lazy_hir(expr)?.data {
+            hir::ExprData::SignalName(sig) => {
+                if !into.contains(&sig) {
+                    into.push(sig);
+                }
+            }
+            hir::ExprData::Select(prefix, _) | hir::ExprData::Attr(prefix, _) => {
+                self.signals_read(prefix, into)?
+            }
+            hir::ExprData::Unary(_, _, arg) => self.signals_read(arg, into)?,
+            hir::ExprData::Binary(_, _, lhs, rhs) | hir::ExprData::Range(_, lhs, rhs) => {
+                self.signals_read(lhs, into)?;
+                self.signals_read(rhs, into)?;
+            }
+            hir::ExprData::Aggregate(id) => {
+                let hir = self.lazy_hir(id)?;
+                let named: Vec<ExprRef> = match hir.named {
+                    hir::AggregateKind::Both => vec![],
+                    hir::AggregateKind::Record(ref fields) => {
+                        fields.iter().map(|f| f.value.1.value).collect()
+                    }
+                    hir::AggregateKind::Array(ref fields) => {
+                        fields.iter().map(|f| f.value.1.value).collect()
+                    }
+                };
+                let elems = hir
+                    .positional
+                    .iter()
+                    .map(|e| e.value)
+                    .chain(named)
+                    .chain(hir.others.map(|e| e.value));
+                for elem in elems {
+                    self.signals_read(elem, into)?;
+                }
+            }
+            hir::ExprData::Qualified(_, arg)
+            | hir::ExprData::Cast(_, arg)
+            | hir::ExprData::TypeAttrCall(_, _, arg)
+            | hir::ExprData::Allocator(_, Some(arg)) => self.signals_read(arg, into)?,
+            hir::ExprData::Call(prefix, ref args) => {
+                self.signals_read(prefix, into)?;
+                for index in self.index_exprs(args)? {
+                    self.signals_read(index, into)?;
+                }
+            }
+            hir::ExprData::BuiltinCall(_, ref args) | hir::ExprData::SubprogCall(_, ref args) => {
+                for arg in &args.value {
+                    match arg.actual.value {
+                        hir::AssocActual::Expr(actual) | hir::AssocActual::InertialExpr(actual) => {
+                            self.signals_read(actual, into)?
+                        }
+                        hir::AssocActual::Subtype(_) | hir::AssocActual::Open => (),
+                    }
+                }
+            }
+            _ => (),
+        }
+        Ok(())
+    }
 
-    debugln!("signal {:?}, type {:?}, init {:?}", id, ty, init);
-    // Create the signal instance.
-    let inst = llhd::Inst::new(
-        Some(hir.name.value.into()),
-        llhd::SignalInst(self.map_type(ty)?, Some(self.map_const(init)?))
-    );
-    ctx.add_inst(inst, llhd::InstPosition::End);
-    Ok(())
-});
+    /// Make sure an expression reads no interface signals of mode `out`.
+    ///
+    /// Such signals may only be read as of VHDL-2008. See IEEE 1076-2008
+    /// section 6.5.2.
+    fn check_reads(&self, expr: ExprRef) -> Result<()> {
+        let std = self.sess.opts.vhdl_std;
+        if std >= VhdlStandard::V2008 {
+            return Ok(());
+        }
+        let mut reads = vec![];
+        self.signals_read(expr, &mut reads)?;
+        let mut had_fails = false;
+        for sig in reads {
+            let intf = match sig {
+                SignalRef::Intf(id) => self.hir(id)?,
+                _ => continue,
+            };
+            if let hir::IntfSignalMode::Out = intf.mode {
+                self.emit(
+                    DiagBuilder2::error(format!(
+                        "`{}` is of mode `out` and cannot be read",
+                        intf.name.value
+                    ))
+                    .span(self.lazy_hir(expr)?.span)
+                    .add_note(format!("`{}` is declared here:", intf.name.value))
+                    .span(intf.name.span)
+                    .add_note(format!(
+                        "Reading signals of mode `out` requires VHDL-2008, but VHDL-{} is \
+                         selected. Use `--vhdl-std 2008`, or declare `{}` as `buffer` or \
+                         `inout` instead.",
+                        std.year(),
+                        intf.name.value
+                    )),
+                );
+                had_fails = true;
+            }
+        }
+        if had_fails {
+            Err(Error::Reported)
+        } else {
+            Ok(())
+        }
+    }
 
-impl_codegen!(self, id: FileDeclRef, _ctx: &mut llhd::Entity => {
-    unimp!(self, id);
-});
+    /// Emit the code computing the value of an expression in a process, given
+    /// the probed values of the signals it reads.
+    fn codegen_expr(
+        &self,
+        expr: ExprRef,
+        env: &HashMap<SignalRef, llhd::ValueRef>,
+        builder: &mut InstBuilder,
+    ) -> Result<llhd::ValueRef> {
+        let hir = self.lazy_hir(expr)?;
+        let ty = self.lazy_typeval(expr)?;
+        let unimp = || {
+            self.emit(
+                DiagBuilder2::bug(format!(
+                    "code generation for expression `{}` not implemented",
+                    hir.span.extract()
+                ))
+                .span(hir.span),
+            );
+            Err(Error::Reported)
+        };
+        let not = |builder: &mut InstBuilder, value: llhd::ValueRef| -> Result<llhd::ValueRef> {
+            Ok(builder
+                .add_inst(llhd::Inst::new(
+                    None,
+                    llhd::UnaryInst(llhd::UnaryOp::Not, self.map_type(ty)?, value),
+                ))
+                .into())
+        };
+        Ok(match hir.data {
+            hir::ExprData::SignalName(sig) => env[&sig].clone(),
+            hir::ExprData::VarName(var) => {
+                let ptr = match self.sb.vars.borrow().get(&var) {
+                    Some(ptr) => ptr.clone(),
+                    None => return unimp(),
+                };
+                builder
+                    .add_inst(llhd::Inst::new(
+                        None,
+                        llhd::LoadInst(self.map_type(ty)?, ptr),
+                    ))
+                    .into()
+            }
+            // Parameters of the emitted function, or generics of the entity.
+            hir::ExprData::IntfConstName(param) => match self.sb.params.borrow().get(&param) {
+                Some(value) => value.clone(),
+                None => self.map_const_of_type(self.const_value(expr)?, ty)?,
+            },
+            hir::ExprData::EnumName(ref lits) => {
+                let decl = match *self.deref_named_type(ty)? {
+                    Ty::Enum(ref ty) => ty.decl,
+                    _ => unreachable!(),
+                };
+                let lit = lits.iter().find(|lit| lit.value.0 == decl).unwrap();
+                self.map_const(self.intern_const(ConstEnum::new(decl, lit.value.1)))?
+            }
+            hir::ExprData::Name(Def::Enum(..), _)
+            | hir::ExprData::ConstName(..)
+            | hir::ExprData::GenParamName(..)
+            | hir::ExprData::IntegerLiteral(..)
+            | hir::ExprData::FloatLiteral(..)
+            | hir::ExprData::PhysicalLiteral(..) => {
+                self.map_const_of_type(self.const_value(expr)?, ty)?
+            }
+            hir::ExprData::Qualified(_, arg) => self.codegen_expr(arg, env, builder)?,
+            hir::ExprData::Aggregate(agg) => {
+                let (exprs, element_tys) = match *self.deref_named_type(ty)? {
+                    Ty::Record(ref ty) => (
+                        self.record_aggregate_fields(agg, ty)?,
+                        ty.fields
+                            .iter()
+                            .map(|&(_, ref ty)| self.map_type(ty))
+                            .collect::<Result<Vec<_>>>()?,
+                    ),
+                    Ty::Array(ref ty) => {
+                        let exprs = self.array_aggregate_elements(agg, ty)?;
+                        let element_ty = self.map_type(&ty.first_element())?;
+                        let element_tys = vec![element_ty; exprs.len()];
+                        (exprs, element_tys)
+                    }
+                    _ => return unimp(),
+                };
+                let mut elements = vec![];
+                for (expr, element_ty) in exprs.into_iter().zip(element_tys.into_iter()) {
+                    elements.push((
+                        self.codegen_operand(expr, &element_ty, env, builder)?,
+                        element_ty,
+                    ));
+                }
 
-impl_codegen!(self, id: ConcStmtRef, ctx: &mut llhd::Entity => {
-    match id {
-        ConcStmtRef::Block(id)         => self.codegen(id, ctx),
-        ConcStmtRef::Process(id)       => self.codegen(id, ctx),
-        ConcStmtRef::ConcProcCall(id)  => self.codegen(id, ctx),
-        ConcStmtRef::ConcAssert(id)    => self.codegen(id, ctx),
-        ConcStmtRef::ConcSigAssign(id) => self.codegen(id, ctx),
-        ConcStmtRef::CompInst(id)      => self.codegen(id, ctx),
-        ConcStmtRef::ForGen(id)        => self.codegen(id, ctx),
-        ConcStmtRef::IfGen(id)         => self.codegen(id, ctx),
-        ConcStmtRef::CaseGen(id)       => self.codegen(id, ctx),
+                // The constant elements form a struct or array constant, into
+                // which the others are inserted one by one.
+                let is_const = |value: &llhd::ValueRef| match *value {
+                    llhd::ValueRef::Const(_) | llhd::ValueRef::Aggregate(_) => true,
+                    _ => false,
+                };
+                let consts = elements
+                    .iter()
+                    .map(|&(ref value, ref ty)| {
+                        if is_const(value) {
+                            value.clone()
+                        } else {
+                            llhd::const_zero(ty)
+                        }
+                    })
+                    .collect();
+                let mut value = match *self.deref_named_type(ty)? {
+                    Ty::Record(_) => llhd::const_struct(consts),
+                    _ => const_array(consts),
+                };
+                let llty = self.map_type(ty)?;
+                for (index, (element, _)) in elements.into_iter().enumerate() {
+                    if !is_const(&element) {
+                        value = builder
+                            .add_inst(llhd::Inst::new(
+                                None,
+                                llhd::InsertInst(
+                                    llty.clone(),
+                                    value,
+                                    llhd::SliceMode::Element(index),
+                                    element,
+                                ),
+                            ))
+                            .into();
+                    }
+                }
+                value
+            }
+            hir::ExprData::Select(prefix, _) => {
+                let value = self.codegen_expr(prefix, env, builder)?;
+                self.codegen_field(expr, value, &|ty| ty, builder)?
+            }
+            hir::ExprData::Call(prefix, _) => {
+                let value = self.codegen_expr(prefix, env, builder)?;
+                self.codegen_index(expr, value, &|ty| ty, env, builder)?
+            }
+            hir::ExprData::Unary(op, _, arg) => {
+                let arg = self.codegen_expr(arg, env, builder)?;
+                match op.value {
+                    UnaryOp::Not => not(builder, arg)?,
+                    _ => return unimp(),
+                }
+            }
+            hir::ExprData::Binary(op, _, lhs, rhs) => {
+                // Universal integers take on the type of the other operand.
+                let lhs_ty = self.lazy_typeval(lhs)?;
+                let ty = match *self.deref_named_type(lhs_ty)? {
+                    Ty::UniversalInt => self.map_type(self.lazy_typeval(rhs)?)?,
+                    _ => self.map_type(lhs_ty)?,
+                };
+                let lhs = self.codegen_operand(lhs, &ty, env, builder)?;
+                let rhs = self.codegen_operand(rhs, &ty, env, builder)?;
+                let (kind, invert) = match op.value {
+                    BinaryOp::Logical(op) => {
+                        let (op, invert) = match op {
+                            LogicalOp::And => (llhd::BinaryOp::And, false),
+                            LogicalOp::Or => (llhd::BinaryOp::Or, false),
+                            LogicalOp::Xor => (llhd::BinaryOp::Xor, false),
+                            LogicalOp::Nand => (llhd::BinaryOp::And, true),
+                            LogicalOp::Nor => (llhd::BinaryOp::Or, true),
+                            LogicalOp::Xnor => (llhd::BinaryOp::Xor, true),
+                        };
+                        (llhd::BinaryInst(op, ty, lhs, rhs), invert)
+                    }
+                    BinaryOp::Rel(RelationalOp::Eq) => {
+                        (llhd::CompareInst(llhd::CompareOp::Eq, ty, lhs, rhs), false)
+                    }
+                    BinaryOp::Rel(RelationalOp::Neq) => {
+                        (llhd::CompareInst(llhd::CompareOp::Neq, ty, lhs, rhs), false)
+                    }
+                    _ => return unimp(),
+                };
+                let value = builder.add_inst(llhd::Inst::new(None, kind)).into();
+                if invert {
+                    not(builder, value)?
+                } else {
+                    value
+                }
+            }
+            hir::ExprData::SubprogCall(subprog, ref args) => {
+                let subprog_ty = self.ty(subprog.value)?;
+                let func = self.function(subprog.value, hir.span)?;
+                let params = match *subprog_ty {
+                    Ty::Subprog(ref ty) => &ty.args,
+                    _ => unreachable!(),
+                };
+                let mut values = vec![];
+                for (arg, param) in args.value.iter().zip(params.iter()) {
+                    let value = match arg.actual.value {
+                        hir::AssocActual::Expr(expr) => {
+                            self.codegen_operand(expr, &self.map_type(&param.ty)?, env, builder)?
+                        }
+                        _ => return unimp(),
+                    };
+                    values.push(value);
+                }
+                builder
+                    .add_inst(llhd::Inst::new(
+                        None,
+                        llhd::CallInst(self.map_type(subprog_ty)?, func, values),
+                    ))
+                    .into()
+            }
+            hir::ExprData::TypeAttrCall(tm, func, arg) => {
+                if self.is_static(expr)? {
+                    self.map_const_of_type(self.const_value(expr)?, ty)?
+                } else {
+                    self.codegen_type_attr(expr, tm, func.value, arg, env, builder)?
+                }
+            }
+            _ => return unimp(),
+        })
     }
-});
 
-impl_codegen!(self, id: BlockStmtRef, _ctx: &mut llhd::Entity => {
-    unimp!(self, id);
-});
+    /// Emit the code selecting a record field, as denoted by a selected name
+    /// such as `a.b`.
+    ///
+    /// The record may be a value, or a signal or variable, in which case
+    /// `wrap` maps the record type to the signal or pointer type thereof.
+    fn codegen_field(
+        &self,
+        expr: ExprRef,
+        record: llhd::ValueRef,
+        wrap: &dyn Fn(llhd::Type) -> llhd::Type,
+        builder: &mut InstBuilder,
+    ) -> Result<llhd::ValueRef> {
+        // The type check only lets selections of record fields through.
+        let (prefix, name) = match self.lazy_hir(expr)?.data {
+            hir::ExprData::Select(prefix, name) => (prefix, name),
+            _ => unreachable!(),
+        };
+        let prefix_ty = self.lazy_typeval(prefix)?;
+        let index = match (self.deref_named_type(prefix_ty)?, name.value) {
+            (&Ty::Record(ref ty), ResolvableName::Ident(name)) => ty.lookup[&name],
+            _ => unreachable!(),
+        };
+        Ok(builder
+            .add_inst(llhd::Inst::new(
+                None,
+                llhd::ExtractInst(
+                    wrap(self.map_type(prefix_ty)?),
+                    record,
+                    llhd::SliceMode::Element(index),
+                ),
+            ))
+            .into())
+    }
 
-impl_codegen!(self, id: ProcessStmtRef, ctx: &mut llhd::Entity => {
-    let hir = self.hir(id)?;
-    let name = match hir.label {
-        Some(n) => format!("{}_{}", ctx.name(), n.value),
-        None => format!("{}_proc", ctx.name()),
-    };
-    debugln!("generating process `{}`", name);
-    // TODO: Check which signals are actually read and written.
-    let ty = llhd::entity_ty(vec![], vec![]);
-    let mut prok = llhd::Process::new(name, ty.clone());
-    // TODO: define the process as a local name
-    // TOOD: codegen declarations
-    // TOOD: codegen statements
-    {
-        let body = prok.body_mut();
-        let entry_blk = body.add_block(llhd::Block::new(Some("entry".into())), llhd::BlockPosition::End);
-        let mut builder = InstBuilder::new(body, entry_blk);
-        for &stmt in &hir.stmts {
-            self.codegen(stmt, &mut builder)?;
+    /// Emit the code selecting an element or a slice of an array, as denoted
+    /// by an indexed or slice name such as `a(3)` or `a(7 downto 4)`.
+    ///
+    /// The array may be a value, or a signal or variable, in which case `wrap`
+    /// maps the array type to the signal or pointer type thereof. Dynamic
+    /// indices shift the indexed element to a fixed position of the array.
+    fn codegen_index(
+        &self,
+        expr: ExprRef,
+        array: llhd::ValueRef,
+        wrap: &dyn Fn(llhd::Type) -> llhd::Type,
+        env: &HashMap<SignalRef, llhd::ValueRef>,
+        builder: &mut InstBuilder,
+    ) -> Result<llhd::ValueRef> {
+        let hir = self.lazy_hir(expr)?;
+        let (prefix, args) = match hir.data {
+            hir::ExprData::Call(prefix, ref args) => (prefix, args),
+            _ => unreachable!(),
+        };
+        let mut array_ty = match *self.deref_named_type(self.lazy_typeval(prefix)?)? {
+            Ty::Array(ref ty) => ty.clone(),
+            _ => unreachable!(),
+        };
+        let indices = self.index_exprs(args)?;
+
+        // The type check only lets static slices through.
+        if let hir::ExprData::Range(_, lb, rb) = self.lazy_hir(indices[0])?.data {
+            let lb = self.array_index_position(&array_ty, 0, &self.index_value(lb)?)?;
+            let rb = self.array_index_position(&array_ty, 0, &self.index_value(rb)?)?;
+            let (offset, len) = match (lb, rb) {
+                (Some(lb), Some(rb)) if lb <= rb => (lb, rb - lb + 1),
+                _ => {
+                    self.emit(
+                        DiagBuilder2::bug(format!(
+                            "code generation for null slice `{}` not implemented",
+                            hir.span.extract()
+                        ))
+                        .span(hir.span),
+                    );
+                    return Err(Error::Reported);
+                }
+            };
+            return Ok(builder
+                .add_inst(llhd::Inst::new(
+                    None,
+                    llhd::ExtractInst(
+                        wrap(self.map_type(&Ty::Array(array_ty))?),
+                        array,
+                        llhd::SliceMode::Slice(offset, len),
+                    ),
+                ))
+                .into());
+        }
+
+        let mut value = array;
+        for index in indices {
+            let ty = wrap(self.map_type(&Ty::Array(array_ty.clone()))?);
+            let position = if self.is_static(index)? {
+                match self.array_index_position(&array_ty, 0, &self.index_value(index)?)? {
+                    Some(position) => position,
+                    None => {
+                        let span = self.lazy_hir(index)?.span;
+                        self.emit(
+                            DiagBuilder2::error(format!(
+                                "index `{}` is out of the range of array type `{}`",
+                                span.extract(),
+                                array_ty
+                            ))
+                            .span(span),
+                        );
+                        return Err(Error::Reported);
+                    }
+                }
+            } else {
+                // The index counts upwards from the lowest bound, which is
+                // the first element of an ascending and the last element of
+                // a descending array. Shift the element towards that end by
+                // its distance from the lowest bound, which always fits into
+                // the type of the index.
+                let (left, dir) = self.array_index_left(&array_ty.indices[0])?;
+                let len = self.array_index_len(&array_ty, &array_ty.indices[0])?;
+                let (low, shift, position) = match dir {
+                    hir::Dir::To => (left, llhd::ShiftDir::Right, 0),
+                    hir::Dir::Downto => {
+                        (left - BigInt::from(len - 1), llhd::ShiftDir::Left, len - 1)
+                    }
+                };
+                let index_ty = self.map_type(self.lazy_typeval(index)?)?;
+                let mut offset = self.codegen_expr(index, env, builder)?;
+                if !low.is_zero() {
+                    let width = index_ty.unwrap_int();
+                    let low = llhd::const_int(width, twos_complement(width, &low));
+                    offset = builder
+                        .add_inst(llhd::Inst::new(
+                            None,
+                            llhd::BinaryInst(llhd::BinaryOp::Sub, index_ty, offset, low.into()),
+                        ))
+                        .into();
+                }
+                value = builder
+                    .add_inst(llhd::Inst::new(
+                        None,
+                        llhd::ShiftInst(shift, ty.clone(), value.clone(), value, offset),
+                    ))
+                    .into();
+                position
+            };
+            value = builder
+                .add_inst(llhd::Inst::new(
+                    None,
+                    llhd::ExtractInst(ty, value, llhd::SliceMode::Element(position)),
+                ))
+                .into();
+            if let Ty::Array(ty) = array_ty.first_element() {
+                array_ty = ty;
+            }
         }
+        Ok(value)
     }
-    // TODO: codegen wait statements implied by sensitivity list
-    let prok_ref = self.sb.llmod.borrow_mut().add_process(prok);
-    // TODO: wire instantiation with signals in the process' port.
-    ctx.add_inst(
-        llhd::Inst::new(hir.label.map(|l| l.value.into()), llhd::InstKind::InstanceInst(
-            ty, prok_ref.into(), vec![], vec![]
-        )),
-        llhd::InstPosition::End
-    );
-    Ok(())
-});
 
-impl_codegen!(self, id: ConcCallStmtRef, _ctx: &mut llhd::Entity => {
-    unimp!(self, id);
-});
+    /// Check whether an index is static, i.e. can be evaluated during
+    /// elaboration. Indices that read signals, variables, or subprogram
+    /// parameters are evaluated at runtime.
+    fn is_static(&self, expr: ExprRef) -> Result<bool> {
+        Ok(match self.lazy_hir(expr)?.data {
+            hir::ExprData::ConstName(_)
+            | hir::ExprData::GenParamName(_)
+            | hir::ExprData::EnumName(_)
+            | hir::ExprData::IntegerLiteral(_) => true,
+            hir::ExprData::IntfConstName(id) => match self.ast(id).0 {
+                ScopeRef::Entity(_) => true,
+                _ => false,
+            },
+            hir::ExprData::Unary(_, _, arg)
+            | hir::ExprData::Qualified(_, arg)
+            | hir::ExprData::Cast(_, arg)
+            | hir::ExprData::TypeAttrCall(_, _, arg) => self.is_static(arg)?,
+            hir::ExprData::Binary(_, _, lhs, rhs) => self.is_static(lhs)? && self.is_static(rhs)?,
+            _ => false,
+        })
+    }
 
-impl_codegen!(self, id: ConcAssertStmtRef, _ctx: &mut llhd::Entity => {
-    unimp!(self, id);
-});
+    /// Emit the code selecting the part of a signal or variable that an
+    /// assignment target such as `a(3)` or `a.b` denotes.
+    ///
+    /// `object` is the signal or the pointer to the variable named by the
+    /// target, and `wrap` maps a type to the signal or pointer type thereof.
+    fn codegen_target(
+        &self,
+        target: ExprRef,
+        object: &llhd::ValueRef,
+        wrap: &dyn Fn(llhd::Type) -> llhd::Type,
+        env: &HashMap<SignalRef, llhd::ValueRef>,
+        builder: &mut InstBuilder,
+    ) -> Result<llhd::ValueRef> {
+        match self.lazy_hir(target)?.data {
+            hir::ExprData::Select(prefix, _) => {
+                let record = self.codegen_target(prefix, object, wrap, env, builder)?;
+                self.codegen_field(target, record, wrap, builder)
+            }
+            hir::ExprData::Call(prefix, _) => {
+                let array = self.codegen_target(prefix, object, wrap, env, builder)?;
+                self.codegen_index(target, array, wrap, env, builder)
+            }
+            _ => Ok(object.clone()),
+        }
+    }
 
-impl_codegen!(self, id: ConcSigAssignStmtRef, _ctx: &mut llhd::Entity => {
+    /// Collect the indices of an assignment target such as `a(i)`. These are
+    /// read, unlike the object that is assigned.
+    fn target_indices(&self, target: ExprRef, into: &mut Vec<ExprRef>) -> Result<()> {
+        match self.lazy_hir(target)?.data {
+            hir::ExprData::Select(prefix, _) => self.target_indices(prefix, into),
+            hir::ExprData::Call(prefix, ref args) => {
+                into.extend(self.index_exprs(args)?);
+                self.target_indices(prefix, into)
+            }
+            _ => Ok(()),
+        }
+    }
+
+    /// Emit the code computing the value of an operand of the given type.
+    ///
+    /// Universal integers, such as integer literals, are emitted with the
+    /// width of the type. So are static integer expressions, such as the
+    /// arithmetic on generics, which are folded to their value.
+    fn codegen_operand(
+        &self,
+        expr: ExprRef,
+        ty: &llhd::Type,
+        env: &HashMap<SignalRef, llhd::ValueRef>,
+        builder: &mut InstBuilder,
+    ) -> Result<llhd::ValueRef> {
+        let fold = match *self.deref_named_type(self.lazy_typeval(expr)?)? {
+            Ty::UniversalInt => true,
+            Ty::Int(_) => self.is_static(expr)?,
+            _ => false,
+        };
+        if !fold || !ty.is_int() {
+            return self.codegen_expr(expr, env, builder);
+        }
+        match *self.const_value(expr)? {
+            Const::Int(ref k) => {
+                let width = ty.unwrap_int();
+                Ok(llhd::const_int(width, twos_complement(width, &k.value)).into())
+            }
+            _ => unreachable!(),
+        }
+    }
+
+    /// Emit the code computing a predefined attribute function of a discrete
+    /// type whose argument is only known at runtime, such as `T'succ(x)`.
+    ///
+    /// Enum literals are represented by their position and integers by their
+    /// value, such that the functions reduce to stepping or resizing the
+    /// argument. An argument for which the function has no result is reported
+    /// through the runtime as a failure, and halts the process. See IEEE
+    /// 1076-2008 section 16.2.2.
+    fn codegen_type_attr(
+        &self,
+        expr: ExprRef,
+        tm: Spanned<TypeMarkRef>,
+        func: hir::TypeAttrFn,
+        arg: ExprRef,
+        env: &HashMap<SignalRef, llhd::ValueRef>,
+        builder: &mut InstBuilder,
+    ) -> Result<llhd::ValueRef> {
+        let span = self.lazy_hir(expr)?.span;
+        let ty = self.intern_ty(Ty::Named(tm.span.into(), tm.value));
+        let llty = self.map_type(ty)?;
+        let width = llty.unwrap_int();
+        let (left, right, dir, signed) = match *self.deref_named_type(ty)? {
+            Ty::Int(ref ty) => (
+                ty.left_bound.clone(),
+                ty.right_bound.clone(),
+                ty.dir,
+                int_bounds(ty).0.is_negative(),
+            ),
+            Ty::Enum(ref ty) => {
+                // Two-state logic does not keep the position of its literals.
+                let len = self.enum_len(ty.decl)?;
+                if width < enum_width(len) {
+                    self.emit(
+                        DiagBuilder2::bug(format!(
+                            "code generation for `{}` on two-state logic not implemented",
+                            span.extract()
+                        ))
+                        .span(span),
+                    );
+                    return Err(Error::Reported);
+                }
+                (BigInt::zero(), BigInt::from(len - 1), hir::Dir::To, false)
+            }
+            _ => unreachable!(),
+        };
+        let (lo, hi) = match dir {
+            hir::Dir::To => (left, right),
+            hir::Dir::Downto => (right, left),
+        };
+        let int_ty_of = |expr: ExprRef| -> Result<IntTy> {
+            match *self.deref_named_type(self.lazy_typeval(expr)?)? {
+                Ty::Int(ref ty) => Ok(ty.clone()),
+                _ => {
+                    let span = self.lazy_hir(expr)?.span;
+                    self.emit(
+                        DiagBuilder2::error(format!(
+                            "integer type of `{}` cannot be inferred from context",
+                            span.extract()
+                        ))
+                        .span(span),
+                    );
+                    Err(Error::Reported)
+                }
+            }
+        };
+        let compare = |builder: &mut InstBuilder, op, width, value: &llhd::ValueRef, bound| {
+            let bound = llhd::const_int(width, twos_complement(width, bound));
+            builder
+                .add_inst(llhd::Inst::new(
+                    None,
+                    llhd::CompareInst(op, llhd::int_ty(width), value.clone(), bound.into()),
+                ))
+                .into()
+        };
+        let out_of_range = format!("argument of {} out of range", span.extract());
+        let value = self.codegen_expr(arg, env, builder)?;
+        match func {
+            hir::TypeAttrFn::Pos => {
+                let result_width = int_width(&int_ty_of(expr)?);
+                Ok(self.codegen_resize(value, width, result_width, signed, builder))
+            }
+            hir::TypeAttrFn::Val => {
+                // Only the bounds which the argument can exceed are checked.
+                let arg_ty = int_ty_of(arg)?;
+                let arg_width = int_width(&arg_ty);
+                let (arg_lo, arg_hi) = int_bounds(&arg_ty);
+                if lo > *arg_hi || hi < *arg_lo {
+                    self.emit(
+                        DiagBuilder2::error(format!("`{}` is always out of range", span.extract()))
+                            .span(span)
+                            .add_note(format!(
+                                "The positions of `{}` range from {} to {}",
+                                tm.span.extract(),
+                                lo,
+                                hi
+                            )),
+                    );
+                    return Err(Error::Reported);
+                }
+                let arg_signed = arg_lo.is_negative();
+                let (ge, le) = if arg_signed {
+                    (llhd::CompareOp::Sge, llhd::CompareOp::Sle)
+                } else {
+                    (llhd::CompareOp::Uge, llhd::CompareOp::Ule)
+                };
+                let mut conds = vec![];
+                if lo > *arg_lo {
+                    conds.push(compare(builder, ge, arg_width, &value, &lo));
+                }
+                if hi < *arg_hi {
+                    conds.push(compare(builder, le, arg_width, &value, &hi));
+                }
+                self.codegen_range_check(conds, &out_of_range, builder);
+                Ok(self.codegen_resize(value, arg_width, width, arg_signed, builder))
+            }
+            _ => {
+                // The argument steps towards the higher or lower positions,
+                // and must not be the last one in that direction.
+                let up = match func {
+                    hir::TypeAttrFn::Succ => true,
+                    hir::TypeAttrFn::Leftof => dir == hir::Dir::Downto,
+                    hir::TypeAttrFn::Rightof => dir == hir::Dir::To,
+                    _ => false,
+                };
+                let (bound, op) = if up {
+                    (&hi, llhd::BinaryOp::Add)
+                } else {
+                    (&lo, llhd::BinaryOp::Sub)
+                };
+                let cond = compare(builder, llhd::CompareOp::Neq, width, &value, bound);
+                self.codegen_range_check(vec![cond], &out_of_range, builder);
+                Ok(builder
+                    .add_inst(llhd::Inst::new(
+                        None,
+                        llhd::BinaryInst(op, llty, value, llhd::const_int(width, 1.into()).into()),
+                    ))
+                    .into())
+            }
+        }
+    }
+
+    /// Emit the code converting an integer from one width to another, which
+    /// extends its sign bit if it is signed.
+    fn codegen_resize(
+        &self,
+        value: llhd::ValueRef,
+        from: usize,
+        to: usize,
+        signed: bool,
+        builder: &mut InstBuilder,
+    ) -> llhd::ValueRef {
+        if to <= from {
+            if to == from {
+                return value;
+            }
+            return builder
+                .add_inst(llhd::Inst::new(
+                    None,
+                    llhd::ExtractInst(llhd::int_ty(from), value, llhd::SliceMode::Slice(0, to)),
+                ))
+                .into();
+        }
+        let ty = llhd::int_ty(to);
+        let zero: llhd::ValueRef = llhd::const_int(to, BigInt::zero()).into();
+        if from == 0 {
+            return zero;
+        }
+        let extended = builder
+            .add_inst(llhd::Inst::new(
+                None,
+                llhd::InsertInst(
+                    ty.clone(),
+                    zero.clone(),
+                    llhd::SliceMode::Slice(0, from),
+                    value.clone(),
+                ),
+            ))
+            .into();
+        if !signed {
+            return extended;
+        }
+
+        // A set sign bit weighs -2^from rather than 0 in the wider integer.
+        let sign = builder
+            .add_inst(llhd::Inst::new(
+                None,
+                llhd::ExtractInst(
+                    llhd::int_ty(from),
+                    value,
+                    llhd::SliceMode::Element(from - 1),
+                ),
+            ))
+            .into();
+        let sign = builder
+            .add_inst(llhd::Inst::new(
+                None,
+                llhd::InsertInst(ty.clone(), zero, llhd::SliceMode::Slice(0, 1), sign),
+            ))
+            .into();
+        let weight = builder
+            .add_inst(llhd::Inst::new(
+                None,
+                llhd::BinaryInst(
+                    llhd::BinaryOp::Mul,
+                    ty.clone(),
+                    sign,
+                    llhd::const_int(to, BigInt::one() << from).into(),
+                ),
+            ))
+            .into();
+        builder
+            .add_inst(llhd::Inst::new(
+                None,
+                llhd::BinaryInst(llhd::BinaryOp::Sub, ty, extended, weight),
+            ))
+            .into()
+    }
+
+    /// Emit a check that all of the given conditions hold, which reports the
+    /// message as a failure and halts the process otherwise. Code emitted
+    /// afterwards runs only if they hold.
+    fn codegen_range_check(
+        &self,
+        conds: Vec<llhd::ValueRef>,
+        message: &str,
+        builder: &mut InstBuilder,
+    ) {
+        let mut conds = conds.into_iter();
+        let first = match conds.next() {
+            Some(cond) => cond,
+            None => return,
+        };
+        let cond = conds.fold(first, |acc, cond| {
+            builder
+                .add_inst(llhd::Inst::new(
+                    None,
+                    llhd::BinaryInst(llhd::BinaryOp::And, llhd::int_ty(1), acc, cond),
+                ))
+                .into()
+        });
+        let in_range_blk = builder.add_block(llhd::Block::new(Some("in_range".into())));
+        let out_of_range_blk = builder.add_block(llhd::Block::new(Some("out_of_range".into())));
+        builder.add_inst(llhd::Inst::new(
+            None,
+            llhd::BranchInst(llhd::BranchKind::Cond(cond, in_range_blk, out_of_range_blk)),
+        ));
+        builder.set_block(out_of_range_blk);
+        self.codegen_report(MessageSeverity::Failure, message, builder);
+        builder.add_inst(llhd::Inst::new(None, llhd::HaltInst));
+        builder.set_block(in_range_blk);
+    }
+
+    /// Determine the message of an assertion or report statement.
+    ///
+    /// Only messages which are string literals are passed on. Other messages
+    /// cannot be evaluated yet, and are replaced by the default message of an
+    /// assertion. See IEEE 1076-2008 section 10.3.
+    fn report_message(&self, report: Option<ExprRef>, report_text: Option<Name>) -> Result<String> {
+        Ok(match (report, report_text) {
+            (_, Some(text)) => text.to_string(),
+            (Some(expr), None) => {
+                self.emit(
+                    DiagBuilder2::warning(
+                        "unsupported: report message which is not a string literal; reporting \
+                         the default message instead",
+                    )
+                    .span(self.lazy_hir(expr)?.span),
+                );
+                "Assertion violation.".to_string()
+            }
+            (None, None) => "Assertion violation.".to_string(),
+        })
+    }
+
+    /// Emit a call to the `moore.report` runtime function, which reports a
+    /// message with a severity. See `docs/runtime.md`.
+    fn codegen_report(&self, severity: MessageSeverity, text: &str, builder: &mut InstBuilder) {
+        let message: Vec<llhd::ValueRef> = text
+            .chars()
+            .chain(Some('\0'))
+            .map(|c| llhd::const_int(8, (c as u32).into()).into())
+            .collect();
+        let ty = llhd::func_ty(
+            vec![
+                llhd::int_ty(32),
+                llhd::array_ty(message.len(), llhd::int_ty(8)),
+            ],
+            llhd::void_ty(),
+        );
+        let func = self.runtime_func(
+            &format!("moore.report.{}", message.len()),
+            &ty,
+            &["severity", "message"],
+        );
+        let severity = MessageSeverity::ALL
+            .iter()
+            .position(|&s| s == severity)
+            .unwrap();
+        builder.add_inst(llhd::Inst::new(
+            None,
+            llhd::CallInst(
+                ty,
+                func,
+                vec![
+                    llhd::const_int(32, severity.into()).into(),
+                    const_array(message),
+                ],
+            ),
+        ));
+    }
+
+    /// Get the function standing in for a runtime function, and add it to the
+    /// module when it is first called. This version of LLHD has no
+    /// declarations, so the function has no body, and the simulator is
+    /// expected to provide it.
+    fn runtime_func(&self, name: &str, ty: &llhd::Type, args: &[&str]) -> llhd::ValueRef {
+        if let Some(func) = self.sb.runtime_funcs.borrow().get(name) {
+            return func.clone();
+        }
+        let mut func = llhd::Function::new(name.to_string(), ty.clone());
+        for (arg, &name) in func.args_mut().iter_mut().zip(args) {
+            arg.set_name(name);
+        }
+        let func_ref: llhd::ValueRef = func.as_ref().into();
+        self.sb.llmod.borrow_mut().add_function(func);
+        self.sb
+            .runtime_funcs
+            .borrow_mut()
+            .insert(name.to_string(), func_ref.clone());
+        func_ref
+    }
+
+    /// Emit a drive of a waveform onto a signal in a process.
+    ///
+    /// Each element of the waveform is driven with the delay of its `after`
+    /// clause. Elements without delay take effect in the next delta cycle,
+    /// unless `--scheduling stratified` orders them like a SystemVerilog
+    /// continuous assignment within the current one. The `unaffected`
+    /// waveform drives nothing.
+    ///
+    /// Both delay mechanisms drive the elements at their times. Pulses shorter
+    /// than the rejection limit of an `inertial` assignment are not removed.
+    /// See IEEE 1076-2008 section 10.5.2.
+    fn codegen_drive(
+        &self,
+        delay: &hir::DelayMechanism,
+        wave: &hir::Waveform,
+        signal: &llhd::ValueRef,
+        span: Span,
+        env: &HashMap<SignalRef, llhd::ValueRef>,
+        builder: &mut InstBuilder,
+    ) -> Result<()> {
+        if wave.is_empty() {
+            return Ok(());
+        }
+
+        // Fold the delays of the elements, which must be ascending.
+        let mut times = vec![];
+        for elem in wave {
+            let time = match elem.after {
+                Some(after) => {
+                    let time = self.const_time(after)?;
+                    let after_span = self.lazy_hir(after)?.span;
+                    if time.is_negative() {
+                        self.emit(
+                            DiagBuilder2::error(format!(
+                                "delay `{}` is negative",
+                                after_span.extract()
+                            ))
+                            .span(after_span),
+                        );
+                        return Err(Error::Reported);
+                    }
+                    if times.last().map(|last| time <= *last).unwrap_or(false) {
+                        self.emit(
+                            DiagBuilder2::error(format!(
+                                "delay `{}` is not greater than the one before",
+                                after_span.extract()
+                            ))
+                            .span(after_span)
+                            .add_note(
+                                "The delays of a waveform must be in ascending order. See IEEE \
+                                 1076-2008 section 10.5.2.1.",
+                            ),
+                        );
+                        return Err(Error::Reported);
+                    }
+                    time
+                }
+                None => BigRational::zero(),
+            };
+            times.push(time);
+        }
+        if let hir::DelayMechanism::RejectInertial(reject) = *delay {
+            let limit = self.const_time(reject)?;
+            let reject_span = self.lazy_hir(reject)?.span;
+            if limit.is_negative() || limit > times[0] {
+                self.emit(
+                    DiagBuilder2::error(format!(
+                        "pulse rejection limit `{}` must lie between zero and the first delay",
+                        reject_span.extract()
+                    ))
+                    .span(reject_span),
+                );
+                return Err(Error::Reported);
+            }
+            self.emit(
+                DiagBuilder2::warning(format!(
+                    "unsupported: pulse rejection limit `{}` is ignored",
+                    reject_span.extract()
+                ))
+                .span(reject_span)
+                .add_note("Pulses shorter than the limit are not removed from the waveform."),
+            );
+        }
+
+        for (elem, time) in wave.iter().zip(times) {
+            let value = match elem.value {
+                Some(value) => value,
+                None => {
+                    self.emit(
+                        DiagBuilder2::bug("code generation for null waveforms not implemented")
+                            .span(span),
+                    );
+                    return Err(Error::Reported);
+                }
+            };
+            // The elements have the type of the driven signal as context,
+            // which integer literals take on.
+            let value = match self.type_context_resolved(value)? {
+                Some(ty) => self.codegen_operand(value, &self.map_type(ty)?, env, builder)?,
+                None => self.codegen_expr(value, env, builder)?,
+            };
+            let delay = if time.is_zero() {
+                self.drive_delay()
+            } else {
+                Some(llhd::const_time(time, 0, 0).into())
+            };
+            builder.add_inst(llhd::Inst::new(
+                None,
+                llhd::DriveInst(signal.clone(), value, delay),
+            ));
+        }
+        Ok(())
+    }
+
+    /// Determine the delay with which a process drives a signal.
+    fn drive_delay(&self) -> Option<llhd::ValueRef> {
+        match self.sess.opts.scheduling {
+            Scheduling::Stratified => Some(llhd::const_time(BigRational::zero(), 0, 1).into()),
+            Scheduling::Native | Scheduling::Delta => None,
+        }
+    }
+
+    /// Fold a time expression into a constant number of seconds.
+    fn const_time(&self, expr: ExprRef) -> Result<BigRational> {
+        match *self.const_value(expr)? {
+            Const::Physical(ref k) if is_time_type(&k.ty) => Ok(fs_to_seconds(&k.value)),
+            ref k => {
+                let span = self.lazy_hir(expr)?.span;
+                self.emit(
+                    DiagBuilder2::error(format!(
+                        "`{}` is not a time, but {} `{}`",
+                        span.extract(),
+                        k.kind_desc(),
+                        k
+                    ))
+                    .span(span),
+                );
+                Err(Error::Reported)
+            }
+        }
+    }
+
+    /// Emit the implicit `GUARD` signal of a block statement, and a process
+    /// which drives it with the value of the guard condition.
+    ///
+    /// See IEEE 1076-2008 section 11.2.
+    fn codegen_guard(
+        &self,
+        block: GenBlockRef,
+        cond: ExprRef,
+        span: Span,
+        ctx: &mut llhd::Entity,
+    ) -> Result<()> {
+        let sig = SignalRef::Guard(block);
+        let bool_ty = self.builtin_boolean_type();
+        let out_ty = self.map_type(bool_ty)?;
+        let init = self.map_const(self.default_value_for_type(bool_ty)?)?;
+        let inst = llhd::Inst::new(
+            Some(self.local_name(block.into(), "GUARD")?),
+            llhd::SignalInst(out_ty.clone(), Some(init)),
+        );
+        let inst = ctx.add_inst(inst, llhd::InstPosition::End);
+        self.sb.signals.borrow_mut().insert(sig, inst.into());
+
+        // Create the process driving the signal.
+        let name = format!("{}_{}", ctx.name(), self.local_name(block.into(), "guard")?);
+        debugln!("generating process `{}`", name);
+        self.check_reads(cond)?;
+        let mut reads = vec![];
+        self.signals_read(cond, &mut reads)?;
+        let in_tys = reads
+            .iter()
+            .map(|&sig| self.map_type(self.ty(sig)?))
+            .collect::<Result<Vec<_>>>()?;
+        let ty = llhd::entity_ty(in_tys.clone(), vec![out_ty]);
+        let mut prok = llhd::Process::new(name, ty.clone());
+        for (arg, &sig) in prok.inputs_mut().iter_mut().zip(reads.iter()) {
+            arg.set_name(llhd_name(&self.signal_name(sig)?.as_str()));
+        }
+        prok.outputs_mut()[0].set_name("GUARD");
+        let inputs: Vec<llhd::ValueRef> = prok
+            .inputs()
+            .iter()
+            .map(|arg| arg.as_ref().into())
+            .collect();
+        let output: llhd::ValueRef = prok.outputs()[0].as_ref().into();
+        {
+            let body = prok.body_mut();
+            let entry_blk = body.add_block(
+                llhd::Block::new(Some("entry".into())),
+                llhd::BlockPosition::End,
+            );
+            let mut builder = InstBuilder::new(body, entry_blk);
+            let mut env = HashMap::new();
+            for ((&sig, ty), input) in reads.iter().zip(in_tys).zip(inputs.iter()) {
+                let value =
+                    builder.add_inst(llhd::Inst::new(None, llhd::ProbeInst(ty, input.clone())));
+                env.insert(sig, value.into());
+            }
+            let value = self.codegen_expr(cond, &env, &mut builder)?;
+            builder.add_inst(llhd::Inst::new(
+                None,
+                llhd::DriveInst(output, value, self.drive_delay()),
+            ));
+            builder.add_inst(llhd::Inst::new(
+                None,
+                llhd::WaitInst(entry_blk, None, inputs.clone()),
+            ));
+        }
+        let prok_ref = self.sb.llmod.borrow_mut().add_process(prok);
+        let inputs = reads
+            .iter()
+            .map(|&sig| self.signal_value(sig, span))
+            .collect::<Result<Vec<_>>>()?;
+        let outputs = vec![self.signal_driver(sig, span)?];
+        ctx.add_inst(
+            llhd::Inst::new(
+                None,
+                llhd::InstKind::InstanceInst(ty, prok_ref.into(), inputs, outputs),
+            ),
+            llhd::InstPosition::End,
+        );
+        Ok(())
+    }
+
+    /// Emit the process checking the condition of an assertion, and
+    /// instantiate it in an entity.
+    ///
+    /// A violation is reported through the runtime. A violation of an
+    /// assertion whose severity stops the simulation as per `--stop-on` halts
+    /// the process afterwards.
+    fn codegen_assert_process(
+        &self,
+        name: &str,
+        hir: &hir::Stmt<hir::ConcAssertStmt>,
+        cond: ExprRef,
+        severity: MessageSeverity,
+        ctx: &mut llhd::Entity,
+    ) -> Result<()> {
+        let (label, scope, span) = (hir.label, hir.parent, hir.span);
+        debugln!("generating process `{}`", name);
+        self.check_reads(cond)?;
+        let mut reads = vec![];
+        self.signals_read(cond, &mut reads)?;
+        let in_tys = reads
+            .iter()
+            .map(|&sig| self.map_type(self.ty(sig)?))
+            .collect::<Result<Vec<_>>>()?;
+        let ty = llhd::entity_ty(in_tys.clone(), vec![]);
+        let mut prok = llhd::Process::new(name.to_string(), ty.clone());
+        for (arg, &sig) in prok.inputs_mut().iter_mut().zip(reads.iter()) {
+            arg.set_name(llhd_name(&self.signal_name(sig)?.as_str()));
+        }
+        let inputs: Vec<llhd::ValueRef> = prok
+            .inputs()
+            .iter()
+            .map(|arg| arg.as_ref().into())
+            .collect();
+        {
+            let body = prok.body_mut();
+            let entry_blk = body.add_block(
+                llhd::Block::new(Some("entry".into())),
+                llhd::BlockPosition::End,
+            );
+            let mut builder = InstBuilder::new(body, entry_blk);
+            let mut env = HashMap::new();
+            for ((&sig, ty), input) in reads.iter().zip(in_tys).zip(inputs.iter()) {
+                let value =
+                    builder.add_inst(llhd::Inst::new(None, llhd::ProbeInst(ty, input.clone())));
+                env.insert(sig, value.into());
+            }
+            let cond = self.codegen_expr(cond, &env, &mut builder)?;
+            let held_blk = builder.body.add_block(
+                llhd::Block::new(Some("held".into())),
+                llhd::BlockPosition::End,
+            );
+            let violated_blk = builder.body.add_block(
+                llhd::Block::new(Some("violated".into())),
+                llhd::BlockPosition::End,
+            );
+            builder.add_inst(llhd::Inst::new(
+                None,
+                llhd::BranchInst(llhd::BranchKind::Cond(cond, held_blk, violated_blk)),
+            ));
+            builder.set_block(held_blk);
+            builder.add_inst(llhd::Inst::new(
+                None,
+                llhd::WaitInst(entry_blk, None, inputs.clone()),
+            ));
+            builder.set_block(violated_blk);
+            let message = self.report_message(hir.stmt.report, hir.stmt.report_text)?;
+            self.codegen_report(severity, &message, &mut builder);
+            if severity >= self.sess.opts.stop_on().0 {
+                builder.add_inst(llhd::Inst::new(None, llhd::HaltInst));
+            } else {
+                builder.add_inst(llhd::Inst::new(
+                    None,
+                    llhd::WaitInst(entry_blk, None, inputs.clone()),
+                ));
+            }
+        }
+        let prok_ref = self.sb.llmod.borrow_mut().add_process(prok);
+        let inputs = reads
+            .iter()
+            .map(|&sig| self.signal_value(sig, span))
+            .collect::<Result<Vec<_>>>()?;
+        let label = label.map(|l| self.local_name(scope, l.value)).transpose()?;
+        ctx.add_inst(
+            llhd::Inst::new(
+                label,
+                llhd::InstKind::InstanceInst(ty, prok_ref.into(), inputs, vec![]),
+            ),
+            llhd::InstPosition::End,
+        );
+        Ok(())
+    }
+
+    /// Determine the LLHD value of a signal of the architecture being
+    /// emitted.
+    fn signal_value(&self, sig: SignalRef, span: Span) -> Result<llhd::ValueRef> {
+        match self.sb.signals.borrow().get(&sig) {
+            Some(value) => Ok(value.clone()),
+            None => {
+                self.emit(
+                    DiagBuilder2::bug(format!(
+                        "signal `{}` not available to the statement",
+                        self.signal_name(sig)?
+                    ))
+                    .span(span),
+                );
+                Err(Error::Reported)
+            }
+        }
+    }
+
+    /// Determine the LLHD value through which a signal of the architecture
+    /// being emitted is driven.
+    ///
+    /// This is the value the signal is read through, except for `inout` ports,
+    /// which are read from an entity input but driven through an output.
+    fn signal_driver(&self, sig: SignalRef, span: Span) -> Result<llhd::ValueRef> {
+        match self.sb.drivers.borrow().get(&sig) {
+            Some(value) => Ok(value.clone()),
+            None => self.signal_value(sig, span),
+        }
+    }
+
+    /// Emit the comparison of a discriminant against the choices of a case
+    /// statement or selected assignment.
+    ///
+    /// Returns `None` for the `others` choice, which matches any value.
+    fn codegen_choices(
+        &self,
+        disc: &llhd::ValueRef,
+        disc_ty: &llhd::Type,
+        choices: &hir::Choices,
+        env: &HashMap<SignalRef, llhd::ValueRef>,
+        builder: &mut InstBuilder,
+    ) -> Result<Option<llhd::ValueRef>> {
+        let mut cond = None;
+        for choice in choices {
+            let value = match choice.value {
+                hir::Choice::Expr(expr) => self.codegen_operand(expr, disc_ty, env, builder)?,
+                hir::Choice::Others => return Ok(None),
+                _ => {
+                    self.emit(
+                        DiagBuilder2::bug(format!(
+                            "code generation for choice `{}` not implemented",
+                            choice.span.extract()
+                        ))
+                        .span(choice.span),
+                    );
+                    return Err(Error::Reported);
+                }
+            };
+            let eq = builder.add_inst(llhd::Inst::new(
+                None,
+                llhd::CompareInst(llhd::CompareOp::Eq, disc_ty.clone(), disc.clone(), value),
+            ));
+            cond = Some(match cond {
+                Some(prev) => builder
+                    .add_inst(llhd::Inst::new(
+                        None,
+                        llhd::BinaryInst(llhd::BinaryOp::Or, llhd::int_ty(1), prev, eq.into()),
+                    ))
+                    .into(),
+                None => eq.into(),
+            });
+        }
+        Ok(cond)
+    }
+
+    /// Collect the signals read and driven by the statements of a process,
+    /// and the first wait statement among them.
+    fn process_accesses(&self, stmts: &[SeqStmtRef], acc: &mut ProcessAccesses) -> Result<()> {
+        for &stmt in stmts {
+            let mut exprs = vec![];
+            match stmt {
+                SeqStmtRef::Wait(id) => {
+                    let hir = self.lazy_hir(id)?;
+                    acc.wait = acc.wait.or(Some(hir.span));
+                    if let Some(ref sens) = hir.stmt.sens {
+                        for sig in &sens.value {
+                            if !acc.reads.contains(&sig.value) {
+                                acc.reads.push(sig.value);
+                            }
+                        }
+                    }
+                    exprs.extend(hir.stmt.cond);
+                }
+                SeqStmtRef::Assert(id) => exprs.push(self.lazy_hir(id)?.stmt.cond),
+                SeqStmtRef::SigAssign(id) => {
+                    let hir = self.lazy_hir(id)?;
+                    match hir.stmt.target {
+                        hir::SigAssignTarget::Name(sig) | hir::SigAssignTarget::Part(sig, _) => {
+                            if !acc.drives.contains(&sig) {
+                                acc.drives.push(sig);
+                            }
+                        }
+                        hir::SigAssignTarget::Aggregate => (),
+                    }
+                    if let hir::SigAssignTarget::Part(_, target) = hir.stmt.target {
+                        self.target_indices(target, &mut exprs)?;
+                    }
+                    let mut waves = vec![];
+                    match hir.stmt.kind {
+                        hir::SigAssignKind::SimpleWave(_, ref wave) => waves.push(wave),
+                        hir::SigAssignKind::CondWave(_, ref cond) => {
+                            for &(ref wave, cond) in &cond.when {
+                                exprs.push(cond);
+                                waves.push(wave);
+                            }
+                            waves.extend(cond.other.as_ref());
+                        }
+                        hir::SigAssignKind::SelWave(_, ref sel) => {
+                            exprs.push(sel.disc);
+                            waves.extend(sel.when.iter().map(|&(ref wave, _)| wave));
+                        }
+                        _ => (),
+                    }
+                    exprs.extend(
+                        waves
+                            .iter()
+                            .flat_map(|wave| wave.iter())
+                            .flat_map(|elem| elem.value),
+                    );
+                }
+                SeqStmtRef::VarAssign(id) => {
+                    let hir = self.lazy_hir(id)?;
+                    if let hir::Target::Name(target) = hir.stmt.target.value {
+                        self.target_indices(target, &mut exprs)?;
+                    }
+                    match hir.stmt.kind {
+                        hir::VarAssignKind::Simple(expr) => exprs.push(expr),
+                        hir::VarAssignKind::Cond(ref cond) => {
+                            for &(value, cond) in &cond.when {
+                                exprs.push(cond);
+                                exprs.push(value);
+                            }
+                            exprs.extend(cond.other);
+                        }
+                        hir::VarAssignKind::Sel(ref sel) => {
+                            exprs.push(sel.disc);
+                            exprs.extend(sel.when.iter().map(|&(value, _)| value));
+                        }
+                    }
+                }
+                SeqStmtRef::If(id) => {
+                    let hir = self.lazy_hir(id)?;
+                    for &(cond, ref stmts) in &hir.stmt.branches {
+                        exprs.push(cond);
+                        self.process_accesses(stmts, acc)?;
+                    }
+                    if let Some(ref stmts) = hir.stmt.otherwise {
+                        self.process_accesses(stmts, acc)?;
+                    }
+                }
+                SeqStmtRef::Case(id) => {
+                    let hir = self.lazy_hir(id)?;
+                    exprs.push(hir.stmt.switch);
+                    for &(_, ref stmts) in &hir.stmt.cases {
+                        self.process_accesses(stmts, acc)?;
+                    }
+                }
+                SeqStmtRef::Loop(id) => {
+                    let hir = self.lazy_hir(id)?;
+                    if let hir::LoopScheme::While(cond) = hir.stmt.scheme {
+                        exprs.push(cond);
+                    }
+                    self.process_accesses(&hir.stmt.stmts, acc)?;
+                }
+                SeqStmtRef::Nexit(id) => exprs.extend(self.lazy_hir(id)?.stmt.cond),
+                SeqStmtRef::Return(id) => exprs.extend(self.lazy_hir(id)?.stmt.expr),
+                SeqStmtRef::ProcCall(id) => {
+                    let hir = self.lazy_hir(id)?;
+                    exprs.extend(self.call_accesses(
+                        hir.stmt.subprog.value,
+                        &hir.stmt.params.value,
+                        acc,
+                    )?);
+                }
+                SeqStmtRef::Report(_) | SeqStmtRef::Null(_) => (),
+            }
+            for expr in exprs {
+                self.check_reads(expr)?;
+                self.signals_read(expr, &mut acc.reads)?;
+            }
+        }
+        Ok(())
+    }
+
+    /// Probe the signals read by expressions of a process.
+    fn codegen_probes(
+        &self,
+        exprs: &[ExprRef],
+        ctx: &mut ProcessContext,
+    ) -> Result<HashMap<SignalRef, llhd::ValueRef>> {
+        let mut reads = vec![];
+        for &expr in exprs {
+            self.signals_read(expr, &mut reads)?;
+        }
+        let mut env = HashMap::new();
+        for sig in reads {
+            let ty = self.map_type(self.ty(sig)?)?;
+            let value = ctx.add_inst(llhd::Inst::new(
+                None,
+                llhd::ProbeInst(ty, ctx.inputs[&sig].clone()),
+            ));
+            env.insert(sig, value.into());
+        }
+        Ok(env)
+    }
+
+    /// Emit a sequence of statements of a process.
+    ///
+    /// Statements following a `next`, `exit`, or halting statement are never
+    /// executed and generate no code.
+    fn codegen_seq_stmts(&self, stmts: &[SeqStmtRef], ctx: &mut ProcessContext) -> Result<()> {
+        for &stmt in stmts {
+            if ctx.terminated {
+                break;
+            }
+            self.codegen(stmt, ctx)?;
+        }
+        Ok(())
+    }
+
+    /// Emit a branch on a condition of a conditional or selected assignment.
+    ///
+    /// Continues in the block taken if the condition holds, and returns the
+    /// block taken otherwise.
+    fn codegen_branch(
+        &self,
+        index: usize,
+        cond: llhd::ValueRef,
+        builder: &mut InstBuilder,
+    ) -> llhd::BlockRef {
+        let taken_blk = builder.body.add_block(
+            llhd::Block::new(Some(format!("when{}", index))),
+            llhd::BlockPosition::End,
+        );
+        let next_blk = builder.body.add_block(
+            llhd::Block::new(Some(format!("else{}", index))),
+            llhd::BlockPosition::End,
+        );
+        builder.add_inst(llhd::Inst::new(
+            None,
+            llhd::BranchInst(llhd::BranchKind::Cond(cond, taken_blk, next_blk)),
+        ));
+        builder.set_block(taken_blk);
+        next_blk
+    }
+
+    /// Allocate and initialize a variable.
+    ///
+    /// The initial value of a variable declared in a process is a constant.
+    /// That of a variable declared in a subprogram is evaluated on every call,
+    /// and may depend on the parameters. See IEEE 1076-2008 section 14.6.
+    fn codegen_var_decl(
+        &self,
+        var: VarDeclRef,
+        in_subprog: bool,
+        builder: &mut InstBuilder,
+    ) -> Result<()> {
+        let hir = self.lazy_hir(var)?;
+        let vhdl_ty = self.lazy_typeval(var)?;
+        let ty = self.map_type(vhdl_ty)?;
+        let init = match hir.decl.init {
+            Some(init) if in_subprog => {
+                self.codegen_operand(init, &ty, &HashMap::new(), builder)?
+            }
+            Some(init) => self.map_const_of_type(self.const_value(init)?, vhdl_ty)?,
+            None => self.map_const(self.default_value_for_type(vhdl_ty)?)?,
+        };
+        let ptr: llhd::ValueRef = builder
+            .add_inst(llhd::Inst::new(
+                Some(llhd_name(&hir.name.value.as_str())),
+                llhd::VariableInst(ty.clone()),
+            ))
+            .into();
+        builder.add_inst(llhd::Inst::new(
+            None,
+            llhd::StoreInst(ty, ptr.clone(), init),
+        ));
+        self.sb.vars.borrow_mut().insert(var, ptr);
+        Ok(())
+    }
+
+    /// Find the body of a subprogram declaration.
+    ///
+    /// The body has the same name and profile as the declaration, and is
+    /// declared in the same declarative region, or in the package body of the
+    /// package the declaration is in. See IEEE 1076-2008 section 4.3.
+    fn subprog_body(&self, id: SubprogDeclRef, span: Span) -> Result<SubprogBodyRef> {
+        let hir = self.hir(id)?;
+        let ty = match *self.ty(SubprogRef::from(id))? {
+            Ty::Subprog(ref ty) => ty,
+            _ => unreachable!(),
+        };
+        let scopes: Vec<ScopeRef> = match hir.parent {
+            ScopeRef::Pkg(pkg) => match self.hir(pkg)?.parent {
+                ScopeRef::CtxItems(ctx) => match self.ast(ctx).0 {
+                    ScopeRef::Lib(lib) => {
+                        let mut scopes = vec![];
+                        for &body in &self.hir(lib)?.pkg_bodies {
+                            if self.hir(body)?.pkg.value == pkg {
+                                scopes.push(body.into());
+                            }
+                        }
+                        scopes
+                    }
+                    _ => vec![],
+                },
+                _ => vec![],
+            },
+            scope => vec![scope],
+        };
+        for scope in scopes {
+            let defs = self.defs(scope)?.get(&hir.spec.name.value);
+            for def in defs.into_iter().flatten() {
+                if let Def::SubprogBody(body) = def.value {
+                    match *self.ty(SubprogRef::from(body))? {
+                        Ty::Subprog(ref body_ty) if body_ty.same_profile(ty) => return Ok(body),
+                        _ => (),
+                    }
+                }
+            }
+        }
+        self.emit(
+            DiagBuilder2::error(format!("subprogram `{}` has no body", hir.spec.name.value))
+                .span(span)
+                .add_note("Declared here:")
+                .span(hir.spec.name.span),
+        );
+        Err(Error::Reported)
+    }
+
+    /// Determine the name of a subprogram body emitted as a function.
+    ///
+    /// The name is prefixed with the entity and architecture, or the package,
+    /// the subprogram is declared in, and made unique among the functions
+    /// emitted so far.
+    fn function_name(&self, id: SubprogBodyRef) -> Result<String> {
+        let hir = self.hir(id)?;
+        let mut scope = hir.parent;
+        let prefix = loop {
+            scope = match scope {
+                ScopeRef::Arch(id) => {
+                    let arch = self.hir(id)?;
+                    let entity = self.hir(arch.entity)?;
+                    break Some(format!("{}_{}", entity.name.value, arch.name.value));
+                }
+                ScopeRef::Pkg(id) => break Some(self.hir(id)?.name.value.to_string()),
+                ScopeRef::PkgBody(id) => break Some(self.hir(id)?.name.value.to_string()),
+                ScopeRef::Process(id) => self.hir(id)?.parent,
+                ScopeRef::SubprogBody(id) => self.hir(id)?.parent,
+                ScopeRef::Subprog(id) => self.hir(id)?.parent,
+                ScopeRef::GenBlock(id) => self.existing_hir(id)?.parent,
+                _ => break None,
+            };
+        };
+        let name = self.local_name(hir.parent, hir.spec.name.value)?;
+        let name = match prefix {
+            Some(prefix) => format!("{}_{}", llhd_name(&prefix), name),
+            None => name,
+        };
+        let funcs = self.sb.funcs.borrow();
+        let mut unique = name.clone();
+        let mut index = 0;
+        while funcs.values().any(|(n, _)| *n == unique) {
+            index += 1;
+            unique = format!("{}{}", name, index);
+        }
+        Ok(unique)
+    }
+
+    /// Emit a subprogram as an LLHD function, and return the function.
+    ///
+    /// Each body is emitted once, the first time it is called. Calls to a
+    /// declaration call the function of its body. Constant parameters are
+    /// passed by value. Signal parameters of procedures are passed by
+    /// reference, as an argument through which the signal is read and one
+    /// through which it is driven, depending on the mode of the parameter.
+    fn function(&self, subprog: SubprogRef, span: Span) -> Result<llhd::ValueRef> {
+        let id = match subprog {
+            SubprogRef::Decl(id) => self.subprog_body(id, span)?,
+            SubprogRef::Body(id) => id,
+            SubprogRef::Inst(id) => unimp!(self, id),
+        };
+        if let Some((_, func)) = self.sb.funcs.borrow().get(&id) {
+            return Ok(func.clone());
+        }
+        let hir = self.hir(id)?;
+        let body_span = self.ast(id).1.span;
+        let is_proc = hir.spec.kind == hir::SubprogKind::Proc;
+
+        // Subprograms are emitted to execute within a single simulation
+        // cycle, with access to no signals other than their parameters.
+        let mut acc = ProcessAccesses::default();
+        self.process_accesses(&hir.stmts, &mut acc)?;
+        if let Some(wait) = acc.wait {
+            if is_proc {
+                self.emit(
+                    DiagBuilder2::bug(
+                        "code generation for procedures containing wait statements not \
+                         implemented",
+                    )
+                    .span(wait),
+                );
+            } else {
+                self.emit(
+                    DiagBuilder2::error("function cannot contain a wait statement")
+                        .span(wait)
+                        .add_note("See IEEE 1076-2008 section 10.2."),
+                );
+            }
+            return Err(Error::Reported);
+        }
+        let has_signal_params = hir.spec.params.iter().any(|param| match *param {
+            IntfObjRef::Signal(_) => true,
+            _ => false,
+        });
+        if !is_proc && (has_signal_params || !acc.reads.is_empty() || !acc.drives.is_empty()) {
+            self.emit(
+                DiagBuilder2::bug(
+                    "code generation for functions accessing signals not implemented",
+                )
+                .span(body_span),
+            );
+            return Err(Error::Reported);
+        }
+
+        // Determine the arguments of the function.
+        let mut arg_tys = vec![];
+        let mut arg_names = vec![];
+        let mut arg_params = vec![];
+        for &param in &hir.spec.params {
+            match param {
+                IntfObjRef::Const(param) => {
+                    arg_tys.push(self.map_type(self.ty(param)?)?);
+                    arg_names.push(self.ast(param).3.name);
+                    arg_params.push((param.into(), false));
+                }
+                IntfObjRef::Signal(param) => {
+                    let ty = llhd::signal_ty(self.map_type(self.ty(param)?)?);
+                    let (read, drive) = signal_param_dirs(self.hir(param)?.mode);
+                    if read {
+                        arg_tys.push(ty.clone());
+                        arg_names.push(self.ast(param).3.name);
+                        arg_params.push((param.into(), false));
+                    }
+                    if drive {
+                        arg_tys.push(ty);
+                        arg_names.push(self.ast(param).3.name);
+                        arg_params.push((param.into(), true));
+                    }
+                }
+                _ => unimp!(self, param),
+            }
+        }
+
+        // Create the function. It is registered before its body is emitted,
+        // such that recursive calls find it.
+        let ret = match *self.ty(SubprogRef::from(id))? {
+            Ty::Subprog(SubprogTy { ref ret, .. }) => ret.as_ref().map(|ret| &**ret),
+            _ => unreachable!(),
+        };
+        let ret_ty = match ret {
+            Some(ret) => self.map_type(ret)?,
+            None => llhd::void_ty(),
+        };
+        let name = self.function_name(id)?;
+        debugln!("generating function `{}`", name);
+        let mut func = llhd::Function::new(name.clone(), llhd::func_ty(arg_tys, ret_ty.clone()));
+        let func_ref: llhd::ValueRef = func.as_ref().into();
+        self.sb
+            .funcs
+            .borrow_mut()
+            .insert(id, (name, func_ref.clone()));
+        let mut inputs = HashMap::new();
+        let mut outputs = HashMap::new();
+        for ((arg, name), &(param, output)) in func
+            .args_mut()
+            .iter_mut()
+            .zip(arg_names)
+            .zip(arg_params.iter())
+        {
+            arg.set_name(llhd_name(&name.as_str()));
+            let value: llhd::ValueRef = arg.as_ref().into();
+            match param {
+                IntfObjRef::Const(param) => {
+                    self.sb.params.borrow_mut().insert(param, value);
+                }
+                IntfObjRef::Signal(param) if output => {
+                    outputs.insert(SignalRef::Intf(param), value);
+                }
+                IntfObjRef::Signal(param) => {
+                    inputs.insert(SignalRef::Intf(param), value);
+                }
+                _ => unreachable!(),
+            }
+        }
+
+        // Signal parameters of mode `in` cannot be driven. See IEEE 1076-2008
+        // section 6.5.2.
+        for &sig in &acc.drives {
+            if !outputs.contains_key(&sig) && inputs.contains_key(&sig) {
+                self.emit(
+                    DiagBuilder2::error(format!(
+                        "signal parameter `{}` of mode `in` cannot be driven",
+                        self.signal_name(sig)?
+                    ))
+                    .span(body_span)
+                    .add_note("See IEEE 1076-2008 section 6.5.2."),
+                );
+                return Err(Error::Reported);
+            }
+        }
+        let inaccessible = acc
+            .reads
+            .iter()
+            .filter(|sig| !inputs.contains_key(sig))
+            .chain(acc.drives.iter().filter(|sig| !outputs.contains_key(sig)))
+            .next();
+        if let Some(&sig) = inaccessible {
+            self.emit(
+                DiagBuilder2::bug(format!(
+                    "code generation for procedures accessing `{}` not implemented",
+                    self.signal_name(sig)?
+                ))
+                .span(body_span)
+                .add_note(
+                    "Procedures can only read their signal parameters of mode `in` and `inout`, \
+                     and drive those of mode `out` and `inout`.",
+                ),
+            );
+            return Err(Error::Reported);
+        }
+        {
+            let body = func.body_mut();
+            let entry_blk = body.add_block(
+                llhd::Block::new(Some("entry".into())),
+                llhd::BlockPosition::End,
+            );
+            let mut builder = InstBuilder::new(body, entry_blk);
+            for &decl in &hir.decls {
+                match decl {
+                    DeclInSubprogRef::Var(var) => self.codegen_var_decl(var, true, &mut builder)?,
+                    DeclInSubprogRef::Subprog(_)
+                    | DeclInSubprogRef::SubprogBody(_)
+                    | DeclInSubprogRef::Type(_)
+                    | DeclInSubprogRef::Subtype(_)
+                    | DeclInSubprogRef::Const(_)
+                    | DeclInSubprogRef::Alias(_)
+                    | DeclInSubprogRef::Attr(_)
+                    | DeclInSubprogRef::AttrSpec(_)
+                    | DeclInSubprogRef::GroupTemp(_)
+                    | DeclInSubprogRef::Group(_) => (),
+                    _ => unimp!(self, decl),
+                }
+            }
+            let mut pctx = ProcessContext {
+                builder,
+                inputs,
+                outputs,
+                loops: vec![],
+                ret: Some(ret_ty.clone()),
+                terminated: false,
+            };
+            self.codegen_seq_stmts(&hir.stmts, &mut pctx)?;
+
+            // Procedures return once their last statement completes. It is an
+            // error for a function to do so without a return statement. LLHD
+            // has no way to express this, so return the default value of the
+            // type instead. See IEEE 1076-2008 section 4.2.1.
+            let kind = match ret {
+                Some(ret) => {
+                    let value = self.map_const(self.default_value_for_type(ret)?)?;
+                    llhd::ReturnKind::Value(ret_ty, value)
+                }
+                None => llhd::ReturnKind::Void,
+            };
+            pctx.terminate(llhd::Inst::new(None, llhd::ReturnInst(kind)));
+        }
+        self.sb.llmod.borrow_mut().add_function(func);
+        Ok(func_ref)
+    }
+
+    /// Collect the signals read and driven by a procedure call.
+    ///
+    /// Returns the actuals of the constant parameters, whose signals are read
+    /// as well.
+    fn call_accesses(
+        &self,
+        subprog: SubprogRef,
+        params: &hir::AssocList,
+        acc: &mut ProcessAccesses,
+    ) -> Result<Vec<ExprRef>> {
+        let mut exprs = vec![];
+        for (&formal, arg) in self.subprog_params(subprog)?.iter().zip(params.iter()) {
+            let actual = match arg.actual.value {
+                hir::AssocActual::Expr(actual) => actual,
+                _ => continue,
+            };
+            match formal {
+                IntfObjRef::Signal(formal) => {
+                    let sig = self.signal_actual(actual)?;
+                    let (read, drive) = signal_param_dirs(self.hir(formal)?.mode);
+                    if read {
+                        self.check_reads(actual)?;
+                    }
+                    if read && !acc.reads.contains(&sig) {
+                        acc.reads.push(sig);
+                    }
+                    if drive && !acc.drives.contains(&sig) {
+                        acc.drives.push(sig);
+                    }
+                }
+                _ => exprs.push(actual),
+            }
+        }
+        Ok(exprs)
+    }
+
+    /// Determine the signal associated with a signal parameter.
+    fn signal_actual(&self, actual: ExprRef) -> Result<SignalRef> {
+        match self.lazy_hir(actual)?.data {
+            hir::ExprData::SignalName(sig) => Ok(sig),
+            _ => unreachable!(),
+        }
+    }
+
+    /// Emit a procedure call in a process or subprogram.
+    fn codegen_call(
+        &self,
+        subprog: Spanned<SubprogRef>,
+        params: &hir::AssocList,
+        ctx: &mut ProcessContext,
+    ) -> Result<()> {
+        let func = self.function(subprog.value, subprog.span)?;
+        let formals = self.subprog_params(subprog.value)?;
+        let exprs = self.call_accesses(subprog.value, params, &mut ProcessAccesses::default())?;
+        let env = self.codegen_probes(&exprs, ctx)?;
+        let mut tys = vec![];
+        let mut values = vec![];
+        for (&formal, arg) in formals.iter().zip(params.iter()) {
+            let actual = match arg.actual.value {
+                hir::AssocActual::Expr(actual) => actual,
+                _ => unreachable!(),
+            };
+            match formal {
+                IntfObjRef::Signal(formal) => {
+                    let sig = self.signal_actual(actual)?;
+                    let ty = self.map_type(self.ty(formal)?)?;
+                    if self.map_type(self.ty(sig)?)? != ty {
+                        self.emit(
+                            DiagBuilder2::bug(format!(
+                                "passing `{}` to a signal parameter of a different subtype not \
+                                 implemented",
+                                arg.actual.span.extract()
+                            ))
+                            .span(arg.actual.span),
+                        );
+                        return Err(Error::Reported);
+                    }
+                    let (read, drive) = signal_param_dirs(self.hir(formal)?.mode);
+                    if read {
+                        tys.push(llhd::signal_ty(ty.clone()));
+                        values.push(ctx.inputs[&sig].clone());
+                    }
+                    if drive {
+                        tys.push(llhd::signal_ty(ty));
+                        values.push(ctx.outputs[&sig].clone());
+                    }
+                }
+                _ => {
+                    let ty = self.map_type(self.ty(formal)?)?;
+                    values.push(self.codegen_operand(actual, &ty, &env, &mut ctx.builder)?);
+                    tys.push(ty);
+                }
+            }
+        }
+        ctx.add_inst(llhd::Inst::new(
+            None,
+            llhd::CallInst(llhd::func_ty(tys, llhd::void_ty()), func, values),
+        ));
+        Ok(())
+    }
+}
+
+/// Determine whether a subprogram reads and drives a signal parameter of the
+/// given mode, and thus takes an argument for either or both.
+fn signal_param_dirs(mode: hir::IntfSignalMode) -> (bool, bool) {
+    match mode {
+        hir::IntfSignalMode::In | hir::IntfSignalMode::Linkage => (true, false),
+        hir::IntfSignalMode::Out | hir::IntfSignalMode::Buffer => (false, true),
+        hir::IntfSignalMode::Inout => (true, true),
+    }
+}
+
+/// Check whether a physical type is `TIME` or one of its subtypes, whose
+/// primary unit is the femtosecond.
+fn is_time_type(ty: &PhysicalTy) -> bool {
+    ty.decl == TIME_TYPE.id || ty.decl == DELAY_LENGTH_TYPE.id
+}
+
+/// Determine the lowest and highest value of an integer type.
+fn int_bounds(ty: &IntTy) -> (&BigInt, &BigInt) {
+    match ty.dir {
+        hir::Dir::To => (&ty.left_bound, &ty.right_bound),
+        hir::Dir::Downto => (&ty.right_bound, &ty.left_bound),
+    }
+}
+
+/// Determine the number of bits needed to hold every value of an integer type.
+///
+/// Types with a negative bound need an additional sign bit.
+fn int_width(ty: &IntTy) -> usize {
+    let (lo, hi) = int_bounds(ty);
+    let hi_bits = if hi.is_positive() { hi.bits() } else { 0 };
+    if lo.is_negative() {
+        let lo_bits = (-lo - BigInt::one()).bits();
+        std::cmp::max(lo_bits, hi_bits) + 1
+    } else {
+        hi_bits
+    }
+}
+
+/// Encode an integer as the bits of a two's complement number of `width` bits.
+fn twos_complement(width: usize, value: &BigInt) -> BigInt {
+    if value.is_negative() {
+        (BigInt::one() << width) + value
+    } else {
+        value.clone()
+    }
+}
+
+/// Determine the number of bits needed to hold the index of any literal of an
+/// enum with `len` literals.
+fn enum_width(len: usize) -> usize {
+    (std::mem::size_of::<usize>() * 8) - len.saturating_sub(1).leading_zeros() as usize
+}
+
+/// Convert a number of femtoseconds into seconds.
+fn fs_to_seconds(value: &BigInt) -> BigRational {
+    BigRational::new(value.clone(), BigInt::from(1_000_000_000_000_000u64))
+}
+
+/// Build an array constant from the values of its elements.
+///
+/// Arrays whose elements all have the same value, such as the implicit initial
+/// value of an array signal, are built as uniform arrays.
+fn const_array(elements: Vec<llhd::ValueRef>) -> llhd::ValueRef {
+    let first = match elements.first() {
+        Some(first) => first.clone(),
+        // TODO: Map this to llhd::const_void once available.
+        None => return llhd::const_int(0, 0.into()).into(),
+    };
+    if elements.iter().all(|element| *element == first) {
+        return llhd::const_uniform_array(elements.len(), first);
+    }
+    let ty = match first {
+        llhd::ValueRef::Const(ref k) => llhd::Value::ty(&**k),
+        llhd::ValueRef::Aggregate(ref a) => llhd::Value::ty(&**a),
+        _ => unreachable!(),
+    };
+    llhd::const_array(ty, elements)
+}
+
+impl_codegen!(self, id: DeclInBlockRef, ctx: &mut llhd::Entity => {
+    match id {
+        DeclInBlockRef::Subprog(id)     => self.codegen(id, &mut ()),
+        DeclInBlockRef::SubprogBody(id) => self.codegen(id, &mut ()),
+        DeclInBlockRef::SubprogInst(id) => self.codegen(id, &mut ()),
+        DeclInBlockRef::Pkg(id)         => self.codegen(id, &mut ()),
+        DeclInBlockRef::PkgBody(id)     => self.codegen(id, &mut ()),
+        DeclInBlockRef::PkgInst(id)     => self.codegen(id, &mut ()),
+        DeclInBlockRef::Type(_id)       => Ok(()),
+        DeclInBlockRef::Subtype(_id)    => Ok(()),
+        DeclInBlockRef::Const(id)       => self.codegen(id, ctx),
+        DeclInBlockRef::Signal(id)      => self.codegen(id, ctx),
+        DeclInBlockRef::Var(id)         => self.codegen(id, ctx),
+        DeclInBlockRef::File(id)        => self.codegen(id, ctx),
+        DeclInBlockRef::Alias(_id)      => Ok(()),
+        DeclInBlockRef::Comp(id)        => self.codegen(id, &mut ()),
+        DeclInBlockRef::Attr(_id)       => Ok(()),
+        DeclInBlockRef::AttrSpec(_id)   => Ok(()),
+        DeclInBlockRef::CfgSpec(_id)    => Ok(()),
+        DeclInBlockRef::Discon(_id)     => Ok(()),
+        DeclInBlockRef::GroupTemp(_id)  => Ok(()),
+        DeclInBlockRef::Group(_id)      => Ok(()),
+    }
+});
+
+impl_codegen!(self, id: ConstDeclRef, _ctx: &mut llhd::Entity => {
+    // Constants are folded at elaboration time and their value substituted
+    // wherever they are referenced, so they need nothing in the entity. Fold
+    // the value anyway to report errors in constants that are never used.
+    let hir = self.lazy_hir(id)?;
+    match hir.decl.init {
+        Some(init) => {
+            self.const_value(init)?;
+            Ok(())
+        }
+        None => {
+            self.emit(
+                DiagBuilder2::error(format!("constant `{}` does not have a value", hir.name.value))
+                    .span(hir.span)
+                    .add_note("Deferred constants may only be declared in packages"),
+            );
+            Err(Error::Reported)
+        }
+    }
+});
+
+impl_codegen!(self, id: VarDeclRef, ctx: &mut llhd::Entity => {
+    // Only shared variables are declared in architectures and blocks. LLHD
+    // has no memory shared between processes, so a shared variable is held in
+    // a signal of the entity, which the processes accessing it read and drive.
+    let hir = self.lazy_hir(id)?;
+    let ty = self.lazy_typeval(id)?;
+    let init = if let Some(init_id) = hir.decl.init {
+        self.const_value(init_id)?
+    } else {
+        self.default_value_for_type(&ty)?
+    };
+    let inst = llhd::Inst::new(
+        Some(self.local_name(hir.parent, hir.name.value)?),
+        llhd::SignalInst(self.map_type(ty)?, Some(self.map_const_of_type(init, ty)?))
+    );
+    let inst = ctx.add_inst(inst, llhd::InstPosition::End);
+    self.sb.shared_vars.borrow_mut().insert(id, inst.into());
+    Ok(())
+});
+
+impl_codegen!(self, id: SignalDeclRef, ctx: &mut llhd::Entity => {
+    // Determine the type of the signal.
+    let hir = self.lazy_hir(id)?;
+    let ty = self.lazy_typeval(id)?;
+
+    // Calculate the initial value for the signal, either from the provided
+    // expression or implicitly.
+    let init = if let Some(init_id) = hir.decl.init {
+        self.const_value(init_id)?
+    } else {
+        self.default_value_for_type(&ty)?
+    };
+
+    debugln!("signal {:?}, type {:?}, init {:?}", id, ty, init);
+    // Create the signal instance.
+    let inst = llhd::Inst::new(
+        Some(self.local_name(hir.parent, hir.name.value)?),
+        llhd::SignalInst(self.map_type(ty)?, Some(self.map_const_of_type(init, ty)?))
+    );
+    let inst = ctx.add_inst(inst, llhd::InstPosition::End);
+    self.sb.signals.borrow_mut().insert(id.into(), inst.into());
+    Ok(())
+});
+
+impl_codegen!(self, id: FileDeclRef, _ctx: &mut llhd::Entity => {
     unimp!(self, id);
 });
 
-impl_codegen!(self, id: CompInstStmtRef, _ctx: &mut llhd::Entity => {
-    unimp!(self, id);
+impl_codegen!(self, id: ConcStmtRef, ctx: &mut llhd::Entity => {
+    match id {
+        ConcStmtRef::Block(id)         => self.codegen(id, ctx),
+        ConcStmtRef::Process(id)       => self.codegen(id, ctx),
+        ConcStmtRef::ConcProcCall(id)  => self.codegen(id, ctx),
+        ConcStmtRef::ConcAssert(id)    => self.codegen(id, ctx),
+        ConcStmtRef::ConcSigAssign(id) => self.codegen(id, ctx),
+        ConcStmtRef::CompInst(id)      => self.codegen(id, ctx),
+        ConcStmtRef::ForGen(id)        => self.codegen(id, ctx),
+        ConcStmtRef::IfGen(id)         => self.codegen(id, ctx),
+        ConcStmtRef::CaseGen(id)       => self.codegen(id, ctx),
+    }
+});
+
+impl_codegen!(self, id: BlockStmtRef, ctx: &mut llhd::Entity => {
+    // The block is flattened into the entity, with its declarations prefixed
+    // by the block label.
+    self.codegen(self.lazy_hir(id)?.stmt.block, ctx)
+});
+
+impl_codegen!(self, id: ProcessStmtRef, ctx: &mut llhd::Entity => {
+    let hir = self.hir(id)?;
+    let span = self.ast(id).1.span;
+    let name = match hir.label {
+        Some(n) => self.local_name(hir.parent, n.value)?,
+        None => self.local_name(hir.parent, format!("proc_{}", span.begin().human_line()))?,
+    };
+    let name = format!("{}_{}", ctx.name(), name);
+    debugln!("generating process `{}`", name);
+
+    // Determine the signals the process reads and drives. A process with a
+    // sensitivity list is equivalent to one with a trailing `wait on` of the
+    // listed signals, and must not wait anywhere else. See IEEE 1076-2008
+    // section 11.3.
+    let mut acc = ProcessAccesses::default();
+    self.process_accesses(&hir.stmts, &mut acc)?;
+    let sens = match hir.sensitivity {
+        hir::ProcessSensitivity::None => None,
+        hir::ProcessSensitivity::All => Some(acc.reads.clone()),
+        hir::ProcessSensitivity::List(ref sens) => {
+            let mut sigs = vec![];
+            for sig in &sens.value {
+                if !sigs.contains(&sig.value) {
+                    sigs.push(sig.value);
+                }
+            }
+            Some(sigs)
+        }
+    };
+    match (&sens, acc.wait) {
+        (Some(_), Some(wait)) => {
+            self.emit(
+                DiagBuilder2::error("process with a sensitivity list cannot contain a wait statement")
+                    .span(wait)
+                    .add_note("See IEEE 1076-2008 section 11.3."),
+            );
+            return Err(Error::Reported);
+        }
+        (None, None) => {
+            self.emit(
+                DiagBuilder2::warning("process never suspends")
+                    .span(span)
+                    .add_note("Add a sensitivity list or a wait statement to the process."),
+            );
+        }
+        _ => (),
+    }
+    let reads = {
+        let mut reads = acc.reads;
+        for &sig in sens.iter().flatten() {
+            if !reads.contains(&sig) {
+                reads.push(sig);
+            }
+        }
+        reads
+    };
+    let drives = acc.drives;
+
+    // Create the process.
+    let in_tys = reads
+        .iter()
+        .map(|&sig| self.map_type(self.ty(sig)?))
+        .collect::<Result<Vec<_>>>()?;
+    let out_tys = drives
+        .iter()
+        .map(|&sig| self.map_type(self.ty(sig)?))
+        .collect::<Result<Vec<_>>>()?;
+    let ty = llhd::entity_ty(in_tys, out_tys);
+    let mut prok = llhd::Process::new(name, ty.clone());
+    for (arg, &sig) in prok.inputs_mut().iter_mut().zip(reads.iter()) {
+        arg.set_name(llhd_name(&self.signal_name(sig)?.as_str()));
+    }
+    for (arg, &sig) in prok.outputs_mut().iter_mut().zip(drives.iter()) {
+        arg.set_name(llhd_name(&self.signal_name(sig)?.as_str()));
+    }
+    let inputs: HashMap<SignalRef, llhd::ValueRef> = reads
+        .iter()
+        .cloned()
+        .zip(prok.inputs().iter().map(|arg| arg.as_ref().into()))
+        .collect();
+    let outputs: HashMap<SignalRef, llhd::ValueRef> = drives
+        .iter()
+        .cloned()
+        .zip(prok.outputs().iter().map(|arg| arg.as_ref().into()))
+        .collect();
+    {
+        let body = prok.body_mut();
+        let entry_blk = body.add_block(llhd::Block::new(Some("entry".into())), llhd::BlockPosition::End);
+        let mut builder = InstBuilder::new(body, entry_blk);
+
+        // Allocate and initialize the variables. Constants are folded
+        // wherever they are used.
+        let mut has_vars = false;
+        for &decl in &hir.decls {
+            match decl {
+                DeclInProcRef::Var(var) => {
+                    self.codegen_var_decl(var, false, &mut builder)?;
+                    has_vars = true;
+                }
+                DeclInProcRef::Subprog(_)
+                | DeclInProcRef::SubprogBody(_)
+                | DeclInProcRef::Type(_)
+                | DeclInProcRef::Subtype(_)
+                | DeclInProcRef::Const(_)
+                | DeclInProcRef::Alias(_)
+                | DeclInProcRef::Attr(_)
+                | DeclInProcRef::AttrSpec(_)
+                | DeclInProcRef::GroupTemp(_)
+                | DeclInProcRef::Group(_) => (),
+                _ => unimp!(self, decl),
+            }
+        }
+
+        // Emit the statements, which are executed over and over again.
+        let body_blk = if has_vars {
+            let body_blk = builder.add_block(llhd::Block::new(Some("body".into())));
+            builder.add_inst(llhd::Inst::new(
+                None,
+                llhd::BranchInst(llhd::BranchKind::Uncond(body_blk)),
+            ));
+            builder.set_block(body_blk);
+            body_blk
+        } else {
+            entry_blk
+        };
+        let mut pctx = ProcessContext {
+            builder,
+            inputs,
+            outputs,
+            loops: vec![],
+            ret: None,
+            terminated: false,
+        };
+        self.codegen_seq_stmts(&hir.stmts, &mut pctx)?;
+        let end = match sens {
+            Some(ref sens) => llhd::WaitInst(
+                body_blk,
+                None,
+                sens.iter().map(|sig| pctx.inputs[sig].clone()).collect(),
+            ),
+            None => llhd::BranchInst(llhd::BranchKind::Uncond(body_blk)),
+        };
+        pctx.terminate(llhd::Inst::new(None, end));
+    }
+    let prok_ref = self.sb.llmod.borrow_mut().add_process(prok);
+
+    // Instantiate the process, connected to the signals it reads and drives.
+    let inputs = reads
+        .iter()
+        .map(|&sig| self.signal_value(sig, span))
+        .collect::<Result<Vec<_>>>()?;
+    let outputs = drives
+        .iter()
+        .map(|&sig| self.signal_driver(sig, span))
+        .collect::<Result<Vec<_>>>()?;
+    let label = hir.label.map(|l| self.local_name(hir.parent, l.value)).transpose()?;
+    ctx.add_inst(
+        llhd::Inst::new(label, llhd::InstKind::InstanceInst(
+            ty, prok_ref.into(), inputs, outputs
+        )),
+        llhd::InstPosition::End
+    );
+    Ok(())
+});
+
+impl_codegen!(self, id: ConcCallStmtRef, ctx: &mut llhd::Entity => {
+    // The call is equivalent to a process which calls the procedure and waits
+    // on the signals passed to parameters of mode `in` or `inout`, and those
+    // read by the actuals of constant parameters. See IEEE 1076-2008 section
+    // 11.4.
+    let hir = self.lazy_hir(id)?;
+    let mut acc = ProcessAccesses::default();
+    let exprs = self.call_accesses(hir.stmt.subprog.value, &hir.stmt.params.value, &mut acc)?;
+    let mut reads = acc.reads;
+    for &expr in &exprs {
+        self.check_reads(expr)?;
+        self.signals_read(expr, &mut reads)?;
+    }
+    let drives = acc.drives;
+
+    // Create the process.
+    let name = match hir.label {
+        Some(n) => self.local_name(hir.parent, n.value)?,
+        None => self.local_name(
+            hir.parent,
+            format!("call_{}", hir.span.begin().human_line()),
+        )?,
+    };
+    let name = format!("{}_{}", ctx.name(), name);
+    debugln!("generating process `{}`", name);
+    let in_tys = reads
+        .iter()
+        .map(|&sig| self.map_type(self.ty(sig)?))
+        .collect::<Result<Vec<_>>>()?;
+    let out_tys = drives
+        .iter()
+        .map(|&sig| self.map_type(self.ty(sig)?))
+        .collect::<Result<Vec<_>>>()?;
+    let ty = llhd::entity_ty(in_tys, out_tys);
+    let mut prok = llhd::Process::new(name, ty.clone());
+    for (arg, &sig) in prok.inputs_mut().iter_mut().zip(reads.iter()) {
+        arg.set_name(llhd_name(&self.signal_name(sig)?.as_str()));
+    }
+    for (arg, &sig) in prok.outputs_mut().iter_mut().zip(drives.iter()) {
+        arg.set_name(llhd_name(&self.signal_name(sig)?.as_str()));
+    }
+    let inputs: HashMap<SignalRef, llhd::ValueRef> = reads
+        .iter()
+        .cloned()
+        .zip(prok.inputs().iter().map(|arg| arg.as_ref().into()))
+        .collect();
+    let outputs: HashMap<SignalRef, llhd::ValueRef> = drives
+        .iter()
+        .cloned()
+        .zip(prok.outputs().iter().map(|arg| arg.as_ref().into()))
+        .collect();
+    {
+        let body = prok.body_mut();
+        let entry_blk = body.add_block(llhd::Block::new(Some("entry".into())), llhd::BlockPosition::End);
+        let builder = InstBuilder::new(body, entry_blk);
+        let mut pctx = ProcessContext {
+            builder,
+            inputs,
+            outputs,
+            loops: vec![],
+            ret: None,
+            terminated: false,
+        };
+        self.codegen_call(hir.stmt.subprog, &hir.stmt.params.value, &mut pctx)?;
+
+        // A call which reads no signals is executed once.
+        let end = if reads.is_empty() {
+            llhd::HaltInst
+        } else {
+            llhd::WaitInst(
+                entry_blk,
+                None,
+                reads.iter().map(|sig| pctx.inputs[sig].clone()).collect(),
+            )
+        };
+        pctx.terminate(llhd::Inst::new(None, end));
+    }
+    let prok_ref = self.sb.llmod.borrow_mut().add_process(prok);
+
+    // Instantiate the process, connected to the signals it reads and drives.
+    let inputs = reads
+        .iter()
+        .map(|&sig| self.signal_value(sig, hir.span))
+        .collect::<Result<Vec<_>>>()?;
+    let outputs = drives
+        .iter()
+        .map(|&sig| self.signal_driver(sig, hir.span))
+        .collect::<Result<Vec<_>>>()?;
+    let label = hir.label.map(|l| self.local_name(hir.parent, l.value)).transpose()?;
+    ctx.add_inst(
+        llhd::Inst::new(label, llhd::InstKind::InstanceInst(
+            ty, prok_ref.into(), inputs, outputs
+        )),
+        llhd::InstPosition::End
+    );
+    Ok(())
 });
 
-impl_codegen!(self, id: ForGenStmtRef, _ctx: &mut llhd::Entity => {
-    unimp!(self, id);
+impl_codegen!(self, id: ConcAssertStmtRef, ctx: &mut llhd::Entity => {
+    // The booleans of PSL directives are not lowered yet, but the kind of the
+    // directive is preserved for formal backends.
+    let hir = self.lazy_hir(id)?;
+    let severity = match hir.stmt.kind {
+        hir::DirectiveKind::Assert => Some(self.assert_severity(hir.stmt.severity)?),
+        _ => None,
+    };
+
+    // Assertions whose condition is not a literal are checked by a process
+    // which evaluates the condition whenever a signal it reads changes. A
+    // violation branches to a block of its own, which reports the message
+    // through the runtime, and halts the process if it stops the simulation.
+    let process = match (severity, hir.stmt.literal, hir.stmt.seq.as_slice()) {
+        (Some(severity), None, &[cond]) => {
+            let name = match hir.label {
+                Some(n) => self.local_name(hir.parent, n.value)?,
+                None => self.local_name(
+                    hir.parent,
+                    format!("assert_{}", hir.span.begin().human_line()),
+                )?,
+            };
+            let name = format!("{}_{}", ctx.name(), name);
+            self.codegen_assert_process(&name, hir, cond, severity, ctx)?;
+            Some(name)
+        }
+        _ => None,
+    };
+    self.sb.directives.borrow_mut().push(Directive {
+        entity: ctx.name().to_string(),
+        kind: hir.stmt.kind,
+        label: hir.label.map(|l| l.value),
+        severity,
+        report: hir.stmt.report_text,
+        process,
+        span: hir.span,
+    });
+
+    // Assertions of a constant `false` are reported right away.
+    match (severity, hir.stmt.literal) {
+        (Some(severity), Some(false)) => {
+            let message = match hir.stmt.report_text {
+                Some(text) => text.to_string(),
+                None => "Assertion violation.".to_string(),
+            };
+            self.emit(DiagBuilder2::new(severity.diag_severity(), message).span(hir.span));
+            if self.sess.count_message(severity) {
+                let (stop_severity, limit) = self.sess.opts.stop_on();
+                self.emit(
+                    DiagBuilder2::fatal(format!(
+                        "stopping after {} assertion(s) of severity {} or worse",
+                        limit, stop_severity
+                    ))
+                    .add_note("Use `--stop-on` to change this limit"),
+                );
+                return Err(Error::Reported);
+            }
+        }
+        _ => (),
+    }
+    Ok(())
 });
 
-impl_codegen!(self, id: IfGenStmtRef, _ctx: &mut llhd::Entity => {
-    unimp!(self, id);
+impl_codegen!(self, id: ConcSigAssignStmtRef, ctx: &mut llhd::Entity => {
+    // The assignment is equivalent to a process which drives the target and
+    // is sensitive to all signals read. See IEEE 1076-2008 section 11.6.
+    let hir = self.lazy_hir(id)?;
+    let (target, part) = match hir.stmt.target {
+        hir::SigAssignTarget::Name(sig) => (sig, None),
+        hir::SigAssignTarget::Part(sig, part) => (sig, Some(part)),
+        hir::SigAssignTarget::Aggregate => unreachable!(),
+    };
+    let mut exprs = vec![];
+    if let Some(part) = part {
+        self.target_indices(part, &mut exprs)?;
+    }
+    let mut waves = vec![];
+    match hir.stmt.kind {
+        hir::SigAssignKind::SimpleWave(_, ref wave) => waves.push(wave),
+        hir::SigAssignKind::CondWave(_, ref cond) => {
+            for &(ref wave, cond) in &cond.when {
+                exprs.push(cond);
+                waves.push(wave);
+            }
+            waves.extend(cond.other.as_ref());
+        }
+        hir::SigAssignKind::SelWave(_, ref sel) => {
+            if sel.matching {
+                self.emit(
+                    DiagBuilder2::bug("matching selected signal assignment not implemented")
+                        .span(hir.span),
+                );
+                return Err(Error::Reported);
+            }
+            exprs.push(sel.disc);
+            waves.extend(sel.when.iter().map(|&(ref wave, _)| wave));
+        }
+        _ => unreachable!(),
+    }
+    exprs.extend(waves.iter().flat_map(|wave| wave.iter()).flat_map(|elem| elem.value));
+    let mut reads = vec![];
+    if let Some(guard) = hir.stmt.guard {
+        // Guarded signals would have to be disconnected when the guard is
+        // false, which is not supported yet.
+        if let SignalRef::Decl(decl) = target {
+            if self.lazy_hir(decl)?.decl.kind != hir::SignalKind::Normal {
+                self.emit(
+                    DiagBuilder2::bug("disconnection of guarded signals not implemented")
+                        .span(hir.stmt.target_span),
+                );
+                return Err(Error::Reported);
+            }
+        }
+        reads.push(guard);
+    }
+    for &expr in &exprs {
+        self.check_reads(expr)?;
+        self.signals_read(expr, &mut reads)?;
+    }
+
+    // Create the process.
+    let name = match hir.label {
+        Some(n) => format!("{}_{}", ctx.name(), self.local_name(hir.parent, n.value)?),
+        None => format!(
+            "{}_{}",
+            ctx.name(),
+            self.local_name(hir.parent, self.signal_name(target)?)?
+        ),
+    };
+    debugln!("generating process `{}`", name);
+    let in_tys = reads
+        .iter()
+        .map(|&sig| self.map_type(self.ty(sig)?))
+        .collect::<Result<Vec<_>>>()?;
+    let out_ty = self.map_type(self.ty(target)?)?;
+    let ty = llhd::entity_ty(in_tys.clone(), vec![out_ty]);
+    let mut prok = llhd::Process::new(name, ty.clone());
+    for (arg, &sig) in prok.inputs_mut().iter_mut().zip(reads.iter()) {
+        arg.set_name(llhd_name(&self.signal_name(sig)?.as_str()));
+    }
+    prok.outputs_mut()[0].set_name(llhd_name(&self.signal_name(target)?.as_str()));
+    let inputs: Vec<llhd::ValueRef> = prok.inputs().iter().map(|arg| arg.as_ref().into()).collect();
+    let output: llhd::ValueRef = prok.outputs()[0].as_ref().into();
+    {
+        let body = prok.body_mut();
+        let entry_blk = body.add_block(llhd::Block::new(Some("entry".into())), llhd::BlockPosition::End);
+        let mut builder = InstBuilder::new(body, entry_blk);
+
+        // Probe the signals read, and compute and drive the new value.
+        let mut env: HashMap<SignalRef, llhd::ValueRef> = HashMap::new();
+        for ((&sig, ty), input) in reads.iter().zip(in_tys).zip(inputs.iter()) {
+            let value = builder.add_inst(llhd::Inst::new(None, llhd::ProbeInst(ty, input.clone())));
+            env.insert(sig, value.into());
+        }
+        let wait = || llhd::Inst::new(None, llhd::WaitInst(entry_blk, None, inputs.clone()));
+
+        // A guarded assignment only drives the target while its guard holds.
+        // See IEEE 1076-2008 section 11.6.
+        if let Some(guard) = hir.stmt.guard {
+            let guard = env[&guard].clone();
+            let next_blk = builder.body.add_block(
+                llhd::Block::new(Some("guarded".into())),
+                llhd::BlockPosition::End,
+            );
+            let skip_blk = builder.body.add_block(
+                llhd::Block::new(Some("unguarded".into())),
+                llhd::BlockPosition::End,
+            );
+            builder.add_inst(llhd::Inst::new(
+                None,
+                llhd::BranchInst(llhd::BranchKind::Cond(guard, next_blk, skip_blk)),
+            ));
+            builder.set_block(skip_blk);
+            builder.add_inst(wait());
+            builder.set_block(next_blk);
+        }
+        let output = match part {
+            Some(part) => self.codegen_target(part, &output, &llhd::signal_ty, &env, &mut builder)?,
+            None => output,
+        };
+        match hir.stmt.kind {
+            hir::SigAssignKind::SimpleWave(ref delay, ref wave) => {
+                self.codegen_drive(delay, wave, &output, hir.span, &env, &mut builder)?;
+            }
+            hir::SigAssignKind::CondWave(ref delay, ref cond) => {
+                for (index, &(ref wave, cond)) in cond.when.iter().enumerate() {
+                    let cond = self.codegen_expr(cond, &env, &mut builder)?;
+                    let next_blk = self.codegen_branch(index, cond, &mut builder);
+                    self.codegen_drive(delay, wave, &output, hir.span, &env, &mut builder)?;
+                    builder.add_inst(wait());
+                    builder.set_block(next_blk);
+                }
+                if let Some(ref wave) = cond.other {
+                    self.codegen_drive(delay, wave, &output, hir.span, &env, &mut builder)?;
+                }
+            }
+            hir::SigAssignKind::SelWave(ref delay, ref sel) => {
+                let disc = self.codegen_expr(sel.disc, &env, &mut builder)?;
+                let disc_ty = self.map_type(self.lazy_typeval(sel.disc)?)?;
+                for (index, &(ref wave, ref choices)) in sel.when.iter().enumerate() {
+                    match self.codegen_choices(&disc, &disc_ty, &choices.value, &env, &mut builder)? {
+                        Some(cond) => {
+                            let next_blk = self.codegen_branch(index, cond, &mut builder);
+                            self.codegen_drive(delay, wave, &output, hir.span, &env, &mut builder)?;
+                            builder.add_inst(wait());
+                            builder.set_block(next_blk);
+                        }
+                        None => {
+                            self.codegen_drive(delay, wave, &output, hir.span, &env, &mut builder)?;
+                            break;
+                        }
+                    }
+                }
+            }
+            _ => unreachable!(),
+        }
+        builder.add_inst(wait());
+    }
+    let prok_ref = self.sb.llmod.borrow_mut().add_process(prok);
+
+    // Instantiate the process, connected to the signals it reads and drives.
+    let inputs = reads
+        .iter()
+        .map(|&sig| self.signal_value(sig, hir.span))
+        .collect::<Result<Vec<_>>>()?;
+    let outputs = vec![self.signal_driver(target, hir.span)?];
+    let label = hir.label.map(|l| self.local_name(hir.parent, l.value)).transpose()?;
+    ctx.add_inst(
+        llhd::Inst::new(label, llhd::InstKind::InstanceInst(
+            ty, prok_ref.into(), inputs, outputs
+        )),
+        llhd::InstPosition::End
+    );
+    Ok(())
 });
 
-impl_codegen!(self, id: CaseGenStmtRef, _ctx: &mut llhd::Entity => {
-    unimp!(self, id);
+impl_codegen!(self, id: CompInstStmtRef, ctx: &mut llhd::Entity => {
+    let hir = self.lazy_hir(id)?;
+    let unit = self.lldef(hir.stmt.arch)?;
+    let ty = match unit {
+        llhd::ValueRef::Entity(id) => llhd::Value::ty(self.sb.llmod.borrow().entity(id)),
+        _ => unreachable!(),
+    };
+
+    // Connect the ports in the order of the entity's inputs and outputs. Ports
+    // left open are connected to a signal of their own, which holds the
+    // default value of the port.
+    let entity = self.hir(hir.stmt.entity)?;
+    let mut inputs = vec![];
+    let mut outputs = vec![];
+    for (&port, actual) in entity.ports.iter().zip(hir.stmt.ports.iter()) {
+        let port = self.hir(port)?;
+        let value = match *actual {
+            Some(sig) => match self.sb.signals.borrow().get(&sig.value) {
+                Some(value) => value.clone(),
+                None => {
+                    self.emit(
+                        DiagBuilder2::bug(format!(
+                            "signal `{}` not available to the instance",
+                            sig.span.extract()
+                        ))
+                        .span(sig.span),
+                    );
+                    return Err(Error::Reported);
+                }
+            },
+            None => {
+                let ty = self.ty(port.ty)?;
+                let init = match port.init {
+                    Some(init) => self.const_value(init)?,
+                    None => self.default_value_for_type(ty)?,
+                };
+                let inst = llhd::Inst::new(
+                    None,
+                    llhd::SignalInst(self.map_type(ty)?, Some(self.map_const_of_type(init, ty)?))
+                );
+                ctx.add_inst(inst, llhd::InstPosition::End).into()
+            }
+        };
+        // An `inout` port of the enclosing entity is driven through an output
+        // of its own.
+        let driver = actual
+            .and_then(|sig| self.sb.drivers.borrow().get(&sig.value).cloned())
+            .unwrap_or_else(|| value.clone());
+        match port.mode {
+            hir::IntfSignalMode::In
+            | hir::IntfSignalMode::Inout
+            | hir::IntfSignalMode::Linkage => inputs.push(value.clone()),
+            _ => (),
+        }
+        match port.mode {
+            hir::IntfSignalMode::Out
+            | hir::IntfSignalMode::Inout
+            | hir::IntfSignalMode::Buffer => outputs.push(driver),
+            _ => (),
+        }
+    }
+    let label = hir.label.map(|l| self.local_name(hir.parent, l.value)).transpose()?;
+    ctx.add_inst(
+        llhd::Inst::new(label, llhd::InstKind::InstanceInst(
+            ty, unit, inputs, outputs
+        )),
+        llhd::InstPosition::End
+    );
+    Ok(())
 });
 
-impl_codegen!(self, id: SeqStmtRef, _ctx: &'a mut InstBuilder<'a> => {
-    unimp!(self, id);
+impl_codegen!(self, id: ForGenStmtRef, ctx: &mut llhd::Entity => {
+    // The generated blocks are flattened into the entity, with the names of
+    // their contents prefixed with the name of the block.
+    let hir = self.lazy_hir(id)?;
+    for &block in &hir.stmt.blocks {
+        self.codegen(block, ctx)?;
+    }
+    Ok(())
 });
 
-impl_codegen!(self, id: SubprogDeclRef, _ctx: &mut () => {
-    unimp!(self, id);
+impl_codegen!(self, id: GenBlockRef, ctx: &mut llhd::Entity => {
+    let hir = self.existing_hir(id)?;
+    if let Some(guard) = hir.guard {
+        self.codegen_guard(id, guard, hir.span, ctx)?;
+    }
+    for &decl in &hir.decls {
+        self.codegen(decl, ctx)?;
+    }
+    for &stmt in &hir.stmts {
+        self.codegen(stmt, ctx)?;
+    }
+    Ok(())
 });
 
-impl_codegen!(self, id: SubprogBodyRef, _ctx: &mut () => {
-    unimp!(self, id);
+impl_codegen!(self, id: IfGenStmtRef, ctx: &mut llhd::Entity => {
+    // Only the selected alternative, if any, has been elaborated.
+    match self.lazy_hir(id)?.stmt.block {
+        Some(block) => self.codegen(block, ctx),
+        None => Ok(()),
+    }
+});
+
+impl_codegen!(self, id: CaseGenStmtRef, ctx: &mut llhd::Entity => {
+    match self.lazy_hir(id)?.stmt.block {
+        Some(block) => self.codegen(block, ctx),
+        None => Ok(()),
+    }
+});
+
+impl_codegen!(self, id: SeqStmtRef, ctx: &'a mut ProcessContext<'a> => {
+    match id {
+        SeqStmtRef::Wait(id)      => self.codegen(id, ctx),
+        SeqStmtRef::Assert(id)    => self.codegen(id, ctx),
+        SeqStmtRef::Report(id)    => self.codegen(id, ctx),
+        SeqStmtRef::SigAssign(id) => self.codegen(id, ctx),
+        SeqStmtRef::VarAssign(id) => self.codegen(id, ctx),
+        SeqStmtRef::ProcCall(id)  => self.codegen(id, ctx),
+        SeqStmtRef::If(id)        => self.codegen(id, ctx),
+        SeqStmtRef::Case(id)      => self.codegen(id, ctx),
+        SeqStmtRef::Loop(id)      => self.codegen(id, ctx),
+        SeqStmtRef::Nexit(id)     => self.codegen(id, ctx),
+        SeqStmtRef::Return(id)    => self.codegen(id, ctx),
+        SeqStmtRef::Null(_)       => Ok(()),
+    }
+});
+
+impl_codegen!(self, id: WaitStmtRef, ctx: &'a mut ProcessContext<'a> => {
+    // Without a sensitivity clause the statement waits for a change of the
+    // signals read by the condition. See IEEE 1076-2008 section 10.2.
+    let hir = self.lazy_hir(id)?;
+    let timeout = match hir.stmt.timeout {
+        Some(timeout) => {
+            // A condition is checked whenever the process wakes up, which
+            // would restart the timeout of the LLHD wait each time.
+            if hir.stmt.cond.is_some() {
+                self.emit(
+                    DiagBuilder2::error(
+                        "cannot generate code for `wait` with both condition and timeout",
+                    )
+                    .span(hir.span),
+                );
+                return Err(Error::Reported);
+            }
+            let time = self.const_time(timeout)?;
+            if time.is_negative() {
+                let span = self.lazy_hir(timeout)?.span;
+                self.emit(
+                    DiagBuilder2::error(format!("timeout `{}` is negative", span.extract()))
+                        .span(span),
+                );
+                return Err(Error::Reported);
+            }
+            Some(llhd::const_time(time, 0, 0).into())
+        }
+        None => None,
+    };
+    let mut sens = vec![];
+    match hir.stmt.sens {
+        Some(ref list) => sens.extend(list.value.iter().map(|sig| sig.value)),
+        None => {
+            if let Some(cond) = hir.stmt.cond {
+                self.signals_read(cond, &mut sens)?;
+            }
+        }
+    }
+
+    // A process waiting for no signal and no time is suspended forever.
+    if sens.is_empty() && timeout.is_none() {
+        ctx.terminate(llhd::Inst::new(None, llhd::HaltInst));
+        return Ok(());
+    }
+    let sens: Vec<_> = sens.iter().map(|sig| ctx.inputs[sig].clone()).collect();
+    let resume_blk = ctx.builder.body.add_block(
+        llhd::Block::new(Some("resume".into())),
+        llhd::BlockPosition::End,
+    );
+    match hir.stmt.cond {
+        Some(cond) => {
+            let wait_blk = ctx.builder.body.add_block(
+                llhd::Block::new(Some("wait".into())),
+                llhd::BlockPosition::End,
+            );
+            let check_blk = ctx.builder.body.add_block(
+                llhd::Block::new(Some("check".into())),
+                llhd::BlockPosition::End,
+            );
+            ctx.branch(wait_blk);
+            ctx.set_block(wait_blk);
+            ctx.terminate(llhd::Inst::new(None, llhd::WaitInst(check_blk, None, sens)));
+            ctx.set_block(check_blk);
+            let env = self.codegen_probes(&[cond], ctx)?;
+            let cond = self.codegen_expr(cond, &env, &mut ctx.builder)?;
+            ctx.terminate(llhd::Inst::new(
+                None,
+                llhd::BranchInst(llhd::BranchKind::Cond(cond, resume_blk, wait_blk)),
+            ));
+        }
+        None => {
+            ctx.terminate(llhd::Inst::new(None, llhd::WaitInst(resume_blk, timeout, sens)));
+        }
+    }
+    ctx.set_block(resume_blk);
+    Ok(())
+});
+
+impl_codegen!(self, id: AssertStmtRef, ctx: &'a mut ProcessContext<'a> => {
+    // A violation branches to a block of its own, which reports the message
+    // through the runtime, and halts the process if the severity stops the
+    // simulation.
+    let hir = self.lazy_hir(id)?;
+    let severity = self.assert_severity(hir.stmt.severity)?;
+    let env = self.codegen_probes(&[hir.stmt.cond], ctx)?;
+    let cond = self.codegen_expr(hir.stmt.cond, &env, &mut ctx.builder)?;
+    let held_blk = ctx.builder.body.add_block(
+        llhd::Block::new(Some("held".into())),
+        llhd::BlockPosition::End,
+    );
+    let violated_blk = ctx.builder.body.add_block(
+        llhd::Block::new(Some("violated".into())),
+        llhd::BlockPosition::End,
+    );
+    ctx.terminate(llhd::Inst::new(
+        None,
+        llhd::BranchInst(llhd::BranchKind::Cond(cond, held_blk, violated_blk)),
+    ));
+    ctx.set_block(violated_blk);
+    let message = self.report_message(hir.stmt.report, hir.stmt.report_text)?;
+    self.codegen_report(severity, &message, &mut ctx.builder);
+    if severity >= self.sess.opts.stop_on().0 {
+        ctx.terminate(llhd::Inst::new(None, llhd::HaltInst));
+    } else {
+        ctx.branch(held_blk);
+    }
+    ctx.set_block(held_blk);
+    Ok(())
+});
+
+impl_codegen!(self, id: ReportStmtRef, ctx: &'a mut ProcessContext<'a> => {
+    // Reports without a severity are notes. See IEEE 1076-2008 section 10.4.
+    let hir = self.lazy_hir(id)?;
+    let severity = match hir.stmt.severity {
+        Some(_) => self.assert_severity(hir.stmt.severity)?,
+        None => self.sess.opts.map_severity(MessageSeverity::Note),
+    };
+    let message = self.report_message(Some(hir.stmt.report), hir.stmt.report_text)?;
+    self.codegen_report(severity, &message, &mut ctx.builder);
+    if severity >= self.sess.opts.stop_on().0 {
+        ctx.terminate(llhd::Inst::new(None, llhd::HaltInst));
+    }
+    Ok(())
+});
+
+impl_codegen!(self, id: SigAssignStmtRef, ctx: &'a mut ProcessContext<'a> => {
+    let hir = self.lazy_hir(id)?;
+    let (sig, target) = match hir.stmt.target {
+        hir::SigAssignTarget::Name(sig) => (sig, None),
+        hir::SigAssignTarget::Part(sig, target) => (sig, Some(target)),
+        hir::SigAssignTarget::Aggregate => {
+            self.emit(
+                DiagBuilder2::bug("code generation for assignment to aggregate not implemented")
+                    .span(hir.stmt.target_span),
+            );
+            return Err(Error::Reported);
+        }
+    };
+    // The indices of the target are evaluated along with the waveforms.
+    let mut indices = vec![];
+    if let Some(target) = target {
+        self.target_indices(target, &mut indices)?;
+    }
+    let signal = ctx.outputs[&sig].clone();
+    let codegen_output = |env: &HashMap<SignalRef, llhd::ValueRef>, builder: &mut InstBuilder| {
+        match target {
+            Some(target) => self.codegen_target(target, &signal, &llhd::signal_ty, env, builder),
+            None => Ok(signal.clone()),
+        }
+    };
+    match hir.stmt.kind {
+        hir::SigAssignKind::SimpleWave(ref delay, ref wave) => {
+            let mut exprs: Vec<_> = wave.iter().flat_map(|elem| elem.value).collect();
+            exprs.extend(indices);
+            let env = self.codegen_probes(&exprs, ctx)?;
+            let output = codegen_output(&env, &mut ctx.builder)?;
+            self.codegen_drive(delay, wave, &output, hir.span, &env, &mut ctx.builder)?;
+        }
+        hir::SigAssignKind::CondWave(ref delay, ref cond) => {
+            let mut exprs = vec![];
+            for &(ref wave, cond) in &cond.when {
+                exprs.push(cond);
+                exprs.extend(wave.iter().flat_map(|elem| elem.value));
+            }
+            exprs.extend(cond.other.iter().flat_map(|wave| wave.iter()).flat_map(|elem| elem.value));
+            exprs.extend(indices);
+            let env = self.codegen_probes(&exprs, ctx)?;
+            let output = codegen_output(&env, &mut ctx.builder)?;
+            let end_blk = ctx.builder.body.add_block(
+                llhd::Block::new(Some("assigned".into())),
+                llhd::BlockPosition::End,
+            );
+            for (index, &(ref wave, cond)) in cond.when.iter().enumerate() {
+                let cond = self.codegen_expr(cond, &env, &mut ctx.builder)?;
+                let next_blk = self.codegen_branch(index, cond, &mut ctx.builder);
+                self.codegen_drive(delay, wave, &output, hir.span, &env, &mut ctx.builder)?;
+                ctx.branch(end_blk);
+                ctx.set_block(next_blk);
+            }
+            if let Some(ref wave) = cond.other {
+                self.codegen_drive(delay, wave, &output, hir.span, &env, &mut ctx.builder)?;
+            }
+            ctx.branch(end_blk);
+            ctx.set_block(end_blk);
+        }
+        hir::SigAssignKind::SelWave(..) => {
+            self.emit(
+                DiagBuilder2::bug("code generation for selected signal assignment in a process not implemented")
+                    .span(hir.span),
+            );
+            return Err(Error::Reported);
+        }
+        _ => unreachable!(),
+    }
+    Ok(())
+});
+
+impl_codegen!(self, id: VarAssignStmtRef, ctx: &'a mut ProcessContext<'a> => {
+    let hir = self.lazy_hir(id)?;
+    let target = match hir.stmt.target.value {
+        hir::Target::Name(expr) => Some(expr),
+        hir::Target::Aggregate(_) => None,
+    };
+    // Find the variable of which the target is an element or a field.
+    let mut var = None;
+    let mut root = target;
+    while let Some(expr) = root {
+        root = match self.lazy_hir(expr)?.data {
+            hir::ExprData::VarName(id) => {
+                var = Some(id);
+                None
+            }
+            hir::ExprData::Call(prefix, _) | hir::ExprData::Select(prefix, _) => Some(prefix),
+            _ => None,
+        };
+    }
+    let var_ptr = match var.and_then(|var| self.sb.vars.borrow().get(&var).cloned()) {
+        Some(ptr) => ptr,
+        None => {
+            self.emit(
+                DiagBuilder2::bug(format!(
+                    "code generation for assignment to `{}` not implemented",
+                    hir.stmt.target.span.extract()
+                ))
+                .span(hir.stmt.target.span),
+            );
+            return Err(Error::Reported);
+        }
+    };
+    let target = target.unwrap();
+    let ty = self.map_type(self.lazy_typeval(target)?)?;
+    let mut indices = vec![];
+    self.target_indices(target, &mut indices)?;
+    match hir.stmt.kind {
+        hir::VarAssignKind::Simple(value) => {
+            indices.push(value);
+            let env = self.codegen_probes(&indices, ctx)?;
+            let ptr = self.codegen_target(target, &var_ptr, &llhd::pointer_ty, &env, &mut ctx.builder)?;
+            let value = self.codegen_operand(value, &ty, &env, &mut ctx.builder)?;
+            ctx.add_inst(llhd::Inst::new(None, llhd::StoreInst(ty, ptr, value)));
+        }
+        hir::VarAssignKind::Cond(ref cond) => {
+            let mut exprs = vec![];
+            for &(value, cond) in &cond.when {
+                exprs.push(cond);
+                exprs.push(value);
+            }
+            exprs.extend(cond.other);
+            exprs.extend(indices);
+            let env = self.codegen_probes(&exprs, ctx)?;
+            let ptr = self.codegen_target(target, &var_ptr, &llhd::pointer_ty, &env, &mut ctx.builder)?;
+            let end_blk = ctx.builder.body.add_block(
+                llhd::Block::new(Some("assigned".into())),
+                llhd::BlockPosition::End,
+            );
+            for (index, &(value, cond)) in cond.when.iter().enumerate() {
+                let cond = self.codegen_expr(cond, &env, &mut ctx.builder)?;
+                let next_blk = self.codegen_branch(index, cond, &mut ctx.builder);
+                let value = self.codegen_operand(value, &ty, &env, &mut ctx.builder)?;
+                ctx.add_inst(llhd::Inst::new(None, llhd::StoreInst(ty.clone(), ptr.clone(), value)));
+                ctx.branch(end_blk);
+                ctx.set_block(next_blk);
+            }
+            if let Some(value) = cond.other {
+                let value = self.codegen_operand(value, &ty, &env, &mut ctx.builder)?;
+                ctx.add_inst(llhd::Inst::new(None, llhd::StoreInst(ty, ptr, value)));
+            }
+            ctx.branch(end_blk);
+            ctx.set_block(end_blk);
+        }
+        hir::VarAssignKind::Sel(..) => {
+            self.emit(
+                DiagBuilder2::bug("code generation for selected variable assignment not implemented")
+                    .span(hir.span),
+            );
+            return Err(Error::Reported);
+        }
+    }
+    Ok(())
+});
+
+impl_codegen!(self, id: CallStmtRef, ctx: &'a mut ProcessContext<'a> => {
+    let hir = self.lazy_hir(id)?;
+    self.codegen_call(hir.stmt.subprog, &hir.stmt.params.value, ctx)
+});
+
+impl_codegen!(self, id: IfStmtRef, ctx: &'a mut ProcessContext<'a> => {
+    let hir = self.lazy_hir(id)?;
+    let end_blk = ctx.builder.body.add_block(
+        llhd::Block::new(Some("endif".into())),
+        llhd::BlockPosition::End,
+    );
+    for (index, &(cond, ref stmts)) in hir.stmt.branches.iter().enumerate() {
+        let env = self.codegen_probes(&[cond], ctx)?;
+        let cond = self.codegen_expr(cond, &env, &mut ctx.builder)?;
+        let next_blk = self.codegen_branch(index, cond, &mut ctx.builder);
+        self.codegen_seq_stmts(stmts, ctx)?;
+        ctx.branch(end_blk);
+        ctx.set_block(next_blk);
+    }
+    if let Some(ref stmts) = hir.stmt.otherwise {
+        self.codegen_seq_stmts(stmts, ctx)?;
+    }
+    ctx.branch(end_blk);
+    ctx.set_block(end_blk);
+    Ok(())
+});
+
+impl_codegen!(self, id: CaseStmtRef, ctx: &'a mut ProcessContext<'a> => {
+    let hir = self.lazy_hir(id)?;
+    if hir.stmt.matching {
+        self.emit(
+            DiagBuilder2::bug("code generation for matching case statement not implemented")
+                .span(hir.span),
+        );
+        return Err(Error::Reported);
+    }
+    let env = self.codegen_probes(&[hir.stmt.switch], ctx)?;
+    let disc = self.codegen_expr(hir.stmt.switch, &env, &mut ctx.builder)?;
+    let disc_ty = self.map_type(self.lazy_typeval(hir.stmt.switch)?)?;
+    let end_blk = ctx.builder.body.add_block(
+        llhd::Block::new(Some("endcase".into())),
+        llhd::BlockPosition::End,
+    );
+    for (index, &(ref choices, ref stmts)) in hir.stmt.cases.iter().enumerate() {
+        match self.codegen_choices(&disc, &disc_ty, &choices.value, &env, &mut ctx.builder)? {
+            Some(cond) => {
+                let next_blk = self.codegen_branch(index, cond, &mut ctx.builder);
+                self.codegen_seq_stmts(stmts, ctx)?;
+                ctx.branch(end_blk);
+                ctx.set_block(next_blk);
+            }
+            None => {
+                self.codegen_seq_stmts(stmts, ctx)?;
+                break;
+            }
+        }
+    }
+    ctx.branch(end_blk);
+    ctx.set_block(end_blk);
+    Ok(())
+});
+
+impl_codegen!(self, id: LoopStmtRef, ctx: &'a mut ProcessContext<'a> => {
+    // A `next` statement continues with the loop condition, and an `exit`
+    // statement after the loop.
+    let hir = self.lazy_hir(id)?;
+    if let hir::LoopScheme::For(..) = hir.stmt.scheme {
+        self.emit(
+            DiagBuilder2::bug("code generation for for loops not implemented")
+                .span(hir.span),
+        );
+        return Err(Error::Reported);
+    }
+    let loop_blk = ctx.builder.body.add_block(
+        llhd::Block::new(Some("loop".into())),
+        llhd::BlockPosition::End,
+    );
+    let exit_blk = ctx.builder.body.add_block(
+        llhd::Block::new(Some("endloop".into())),
+        llhd::BlockPosition::End,
+    );
+    ctx.branch(loop_blk);
+    ctx.set_block(loop_blk);
+    if let hir::LoopScheme::While(cond) = hir.stmt.scheme {
+        let env = self.codegen_probes(&[cond], ctx)?;
+        let cond = self.codegen_expr(cond, &env, &mut ctx.builder)?;
+        let body_blk = ctx.builder.add_block(llhd::Block::new(Some("iter".into())));
+        ctx.terminate(llhd::Inst::new(
+            None,
+            llhd::BranchInst(llhd::BranchKind::Cond(cond, body_blk, exit_blk)),
+        ));
+        ctx.set_block(body_blk);
+    }
+    ctx.loops.push((id, loop_blk, exit_blk));
+    self.codegen_seq_stmts(&hir.stmt.stmts, ctx)?;
+    ctx.loops.pop();
+    ctx.branch(loop_blk);
+    ctx.set_block(exit_blk);
+    Ok(())
+});
+
+impl_codegen!(self, id: NexitStmtRef, ctx: &'a mut ProcessContext<'a> => {
+    let hir = self.lazy_hir(id)?;
+    let target = match hir.stmt.target {
+        Some(target) => ctx.loops.iter().rev().find(|&&(id, ..)| id == target.value),
+        None => ctx.loops.last(),
+    };
+    let target = match (target, hir.stmt.mode) {
+        (Some(&(_, next_blk, _)), hir::NexitMode::Next) => next_blk,
+        (Some(&(_, _, exit_blk)), hir::NexitMode::Exit) => exit_blk,
+        (None, _) => {
+            self.emit(
+                DiagBuilder2::error(format!(
+                    "`{}` outside of a loop",
+                    hir.span.extract()
+                ))
+                .span(hir.span),
+            );
+            return Err(Error::Reported);
+        }
+    };
+    match hir.stmt.cond {
+        Some(cond) => {
+            let env = self.codegen_probes(&[cond], ctx)?;
+            let cond = self.codegen_expr(cond, &env, &mut ctx.builder)?;
+            let next_blk = ctx.builder.add_block(llhd::Block::new(Some("continue".into())));
+            ctx.terminate(llhd::Inst::new(
+                None,
+                llhd::BranchInst(llhd::BranchKind::Cond(cond, target, next_blk)),
+            ));
+            ctx.set_block(next_blk);
+        }
+        None => ctx.branch(target),
+    }
+    Ok(())
+});
+
+impl_codegen!(self, id: ReturnStmtRef, ctx: &'a mut ProcessContext<'a> => {
+    let hir = self.lazy_hir(id)?;
+    if let Some(ret) = ctx.ret.clone() {
+        let kind = match hir.stmt.expr {
+            Some(expr) => {
+                let env = self.codegen_probes(&[expr], ctx)?;
+                let value = self.codegen_operand(expr, &ret, &env, &mut ctx.builder)?;
+                llhd::ReturnKind::Value(ret, value)
+            }
+            None => llhd::ReturnKind::Void,
+        };
+        ctx.terminate(llhd::Inst::new(None, llhd::ReturnInst(kind)));
+        return Ok(());
+    }
+    self.emit(
+        DiagBuilder2::error("return statement outside of a subprogram")
+            .span(hir.span)
+            .add_note("See IEEE 1076-2008 section 10.13."),
+    );
+    Err(Error::Reported)
+});
+
+impl_codegen!(self, _id: SubprogDeclRef, _ctx: &mut () => {
+    // Subprograms are emitted as functions when they are first called.
+    Ok(())
+});
+
+impl_codegen!(self, _id: SubprogBodyRef, _ctx: &mut () => {
+    // Subprograms are emitted as functions when they are first called.
+    Ok(())
 });
 
 impl_codegen!(self, id: SubprogInstRef, _ctx: &mut () => {
@@ -331,10 +3679,76 @@ impl_codegen!(self, id: PkgInstRef, _ctx: &mut () => {
     unimp!(self, id);
 });
 
-impl_codegen!(self, id: CompDeclRef, _ctx: &mut () => {
-    unimp!(self, id);
+impl_codegen!(self, _id: CompDeclRef, _ctx: &mut () => {
+    // Components are bound to entities where they are instantiated, and
+    // generate no code of their own.
+    Ok(())
 });
 
+/// The signals accessed by the statements of a process.
+#[derive(Default)]
+struct ProcessAccesses {
+    /// The signals read, in the order they appear.
+    reads: Vec<SignalRef>,
+    /// The signals driven, in the order they appear.
+    drives: Vec<SignalRef>,
+    /// The location of the first wait statement, if any.
+    wait: Option<Span>,
+}
+
+/// The state of the code generation for the statements of a process.
+pub struct ProcessContext<'ctx> {
+    /// The builder of the process body.
+    pub builder: InstBuilder<'ctx>,
+    /// The arguments through which the process reads signals.
+    pub inputs: HashMap<SignalRef, llhd::ValueRef>,
+    /// The arguments through which the process drives signals.
+    pub outputs: HashMap<SignalRef, llhd::ValueRef>,
+    /// The enclosing loops, innermost last, with the blocks that `next` and
+    /// `exit` branch to.
+    pub loops: Vec<(LoopStmtRef, llhd::BlockRef, llhd::BlockRef)>,
+    /// The return type of the function being emitted, if any.
+    pub ret: Option<llhd::Type>,
+    /// Whether the current block has been terminated.
+    pub terminated: bool,
+}
+
+impl<'ctx> ProcessContext<'ctx> {
+    /// Add a new instruction to the current block.
+    pub fn add_inst(&mut self, inst: llhd::Inst) -> llhd::InstRef {
+        self.builder.add_inst(inst)
+    }
+
+    /// Terminate the current block with an instruction, unless it already
+    /// has been.
+    pub fn terminate(&mut self, inst: llhd::Inst) {
+        if !self.terminated {
+            self.builder.add_inst(inst);
+            self.terminated = true;
+        }
+    }
+
+    /// Terminate the current block with a branch to another block.
+    pub fn branch(&mut self, block: llhd::BlockRef) {
+        self.terminate(llhd::Inst::new(
+            None,
+            llhd::BranchInst(llhd::BranchKind::Uncond(block)),
+        ));
+    }
+
+    /// Continue adding instructions to another block.
+    ///
+    /// The block is moved to the end of the body, such that the blocks appear
+    /// in the order they are filled in.
+    pub fn set_block(&mut self, block: llhd::BlockRef) {
+        self.builder
+            .body
+            .move_block(block, llhd::BlockPosition::End);
+        self.builder.set_block(block);
+        self.terminated = false;
+    }
+}
+
 /// An helper to build sequences of instructions.
 pub struct InstBuilder<'ctx> {
     pub body: &'ctx mut llhd::SeqBody,
@@ -6,7 +6,7 @@ use crate::hir;
 use crate::score::*;
 use crate::syntax::ast;
 use moore_common::errors::*;
-use moore_common::score::Result;
+use moore_common::score::{Error, Result};
 use moore_common::source::*;
 #[deny(missing_docs)]
 use std::collections::HashMap;
@@ -43,7 +43,7 @@ impl<'sbc, 'lazy, 'sb, 'ast, 'ctx> DefsContext<'sbc, 'lazy, 'sb, 'ast, 'ctx> {
     /// definitions.
     pub fn finish(self) -> Result<Defs> {
         if self.failed {
-            Err(())
+            Err(Error::Reported)
         } else {
             Ok(self.defs)
         }
@@ -64,7 +64,7 @@ impl<'sbc, 'lazy, 'sb, 'ast, 'ctx> DefsContext<'sbc, 'lazy, 'sb, 'ast, 'ctx> {
         }
         match def {
             // Handle overloadable cases.
-            Def::Enum(_) => {
+            Def::Enum(_) | Def::Subprog(_) | Def::SubprogBody(_) => {
                 self.defs
                     .entry(name.value)
                     .or_insert_with(|| Vec::new())
@@ -94,7 +94,7 @@ impl<'sbc, 'lazy, 'sb, 'ast, 'ctx> DefsContext<'sbc, 'lazy, 'sb, 'ast, 'ctx> {
     pub fn declare_primary_name(&mut self, name: &ast::PrimaryName, def: Def) {
         match self.ctx.resolvable_from_primary_name(name) {
             Ok(n) => self.declare(n, def),
-            Err(()) => self.failed = true,
+            Err(_) => self.failed = true,
         }
     }
 
@@ -125,7 +125,7 @@ impl<'sbc, 'lazy, 'sb, 'ast, 'ctx> DefsContext<'sbc, 'lazy, 'sb, 'ast, 'ctx> {
                         ast::NameExpr(ref name) => self.declare(
                             match self.ctx.resolvable_from_primary_name(&name.primary) {
                                 Ok(n) => n,
-                                Err(()) => continue,
+                                Err(_) => continue,
                             },
                             Def::Enum(EnumRef(id, i)),
                         ),
@@ -158,7 +158,7 @@ impl<'sbc, 'lazy, 'sb, 'ast, 'ctx> DefsContext<'sbc, 'lazy, 'sb, 'ast, 'ctx> {
         match id {
             DeclInBlockRef::Subprog(id) => self.declare_subprog(id),
             DeclInBlockRef::SubprogInst(id) => self.declare_subprog_inst(id),
-            DeclInBlockRef::SubprogBody(_id) => (),
+            DeclInBlockRef::SubprogBody(id) => self.declare_subprog_body(id),
             DeclInBlockRef::Pkg(id) => self.declare_pkg(id),
             DeclInBlockRef::PkgInst(id) => self.declare_pkg_inst(id),
             DeclInBlockRef::PkgBody(_id) => (),
@@ -206,7 +206,7 @@ impl<'sbc, 'lazy, 'sb, 'ast, 'ctx> DefsContext<'sbc, 'lazy, 'sb, 'ast, 'ctx> {
     pub fn declare_any_in_pkg_body(&mut self, id: DeclInPkgBodyRef) {
         match id {
             DeclInPkgBodyRef::Subprog(id) => self.declare_subprog(id),
-            DeclInPkgBodyRef::SubprogBody(_id) => (),
+            DeclInPkgBodyRef::SubprogBody(id) => self.declare_subprog_body(id),
             DeclInPkgBodyRef::SubprogInst(id) => self.declare_subprog_inst(id),
             DeclInPkgBodyRef::Pkg(id) => self.declare_pkg(id),
             DeclInPkgBodyRef::PkgBody(_id) => (),
@@ -228,7 +228,7 @@ impl<'sbc, 'lazy, 'sb, 'ast, 'ctx> DefsContext<'sbc, 'lazy, 'sb, 'ast, 'ctx> {
     pub fn declare_any_in_subprog(&mut self, id: DeclInSubprogRef) {
         match id {
             DeclInSubprogRef::Subprog(id) => self.declare_subprog(id),
-            DeclInSubprogRef::SubprogBody(_id) => (),
+            DeclInSubprogRef::SubprogBody(id) => self.declare_subprog_body(id),
             DeclInSubprogRef::SubprogInst(id) => self.declare_subprog_inst(id),
             DeclInSubprogRef::Pkg(id) => self.declare_pkg(id),
             DeclInSubprogRef::PkgBody(_id) => (),
@@ -251,7 +251,7 @@ impl<'sbc, 'lazy, 'sb, 'ast, 'ctx> DefsContext<'sbc, 'lazy, 'sb, 'ast, 'ctx> {
         match id {
             DeclInProcRef::Subprog(id) => self.declare_subprog(id),
             DeclInProcRef::SubprogInst(id) => self.declare_subprog_inst(id),
-            DeclInProcRef::SubprogBody(_id) => (),
+            DeclInProcRef::SubprogBody(id) => self.declare_subprog_body(id),
             DeclInProcRef::Pkg(id) => self.declare_pkg(id),
             DeclInProcRef::PkgInst(id) => self.declare_pkg_inst(id),
             DeclInProcRef::PkgBody(_id) => (),
@@ -272,7 +272,7 @@ impl<'sbc, 'lazy, 'sb, 'ast, 'ctx> DefsContext<'sbc, 'lazy, 'sb, 'ast, 'ctx> {
     pub fn declare_const(&mut self, id: ConstDeclRef) {
         let hir = match self.ctx.lazy_hir(id) {
             Ok(h) => h,
-            Err(()) => {
+            Err(_) => {
                 self.failed = true;
                 return;
             }
@@ -284,7 +284,7 @@ impl<'sbc, 'lazy, 'sb, 'ast, 'ctx> DefsContext<'sbc, 'lazy, 'sb, 'ast, 'ctx> {
     pub fn declare_signal(&mut self, id: SignalDeclRef) {
         let hir = match self.ctx.lazy_hir(id) {
             Ok(h) => h,
-            Err(()) => {
+            Err(_) => {
                 self.failed = true;
                 return;
             }
@@ -296,7 +296,7 @@ impl<'sbc, 'lazy, 'sb, 'ast, 'ctx> DefsContext<'sbc, 'lazy, 'sb, 'ast, 'ctx> {
     pub fn declare_var(&mut self, id: VarDeclRef) {
         let hir = match self.ctx.lazy_hir(id) {
             Ok(h) => h,
-            Err(()) => {
+            Err(_) => {
                 self.failed = true;
                 return;
             }
@@ -308,7 +308,7 @@ impl<'sbc, 'lazy, 'sb, 'ast, 'ctx> DefsContext<'sbc, 'lazy, 'sb, 'ast, 'ctx> {
     pub fn declare_file(&mut self, id: FileDeclRef) {
         let hir = match self.ctx.lazy_hir(id) {
             Ok(h) => h,
-            Err(()) => {
+            Err(_) => {
                 self.failed = true;
                 return;
             }
@@ -346,6 +346,11 @@ impl<'sbc, 'lazy, 'sb, 'ast, 'ctx> DefsContext<'sbc, 'lazy, 'sb, 'ast, 'ctx> {
         self.declare_primary_name(&self.ctx.ast(id).1.spec.name, Def::Subprog(id))
     }
 
+    /// Handle subprogram bodies.
+    pub fn declare_subprog_body(&mut self, id: SubprogBodyRef) {
+        self.declare_primary_name(&self.ctx.ast(id).1.spec.name, Def::SubprogBody(id))
+    }
+
     /// Handle subprogram instantiations.
     pub fn declare_subprog_inst(&mut self, id: SubprogInstRef) {
         self.declare_primary_name(&self.ctx.ast(id).1.spec.name, Def::SubprogInst(id))
@@ -364,15 +369,72 @@ impl<'sbc, 'lazy, 'sb, 'ast, 'ctx> DefsContext<'sbc, 'lazy, 'sb, 'ast, 'ctx> {
     ///
     /// These are mainly subprogram parameters and entity ports.
     pub fn declare_intf_objs(&mut self, ids: &[IntfObjRef]) {
-        if !ids.is_empty() {
-            unimplemented!();
+        for &id in ids {
+            match id {
+                IntfObjRef::Const(id) => {
+                    let name = self.ctx.ast(id).3;
+                    self.declare(
+                        Spanned::new(name.name.into(), name.span),
+                        Def::IntfConst(id),
+                    )
+                }
+                IntfObjRef::Signal(id) => {
+                    let name = self.ctx.ast(id).3;
+                    self.declare(
+                        Spanned::new(name.name.into(), name.span),
+                        Def::Signal(id.into()),
+                    )
+                }
+                // Lowering to HIR rejects these parameters with a diagnostic,
+                // so they should never be declared.
+                IntfObjRef::Var(_) | IntfObjRef::File(_) => self.emit(DiagBuilder2::bug(format!(
+                    "declaration of {:?} not implemented",
+                    id
+                ))),
+            }
         }
     }
 
+    /// Handle the ports of an entity.
+    pub fn declare_ports(&mut self, ids: &[IntfSignalRef]) {
+        for &id in ids {
+            let hir = match self.ctx.hir(id) {
+                Ok(h) => h,
+                Err(_) => {
+                    self.failed = true;
+                    continue;
+                }
+            };
+            self.declare(hir.name.map_into(), Def::Signal(id.into()))
+        }
+    }
+
+    /// Report a generic other than a constant as not implemented.
+    fn unimp_generic(&mut self, span: Span) {
+        self.emit(
+            DiagBuilder2::bug("generic types, subprograms, and packages not implemented")
+                .span(span),
+        )
+    }
+
     /// Handle generics.
+    ///
+    /// Only generic constants are supported for now. Generic types,
+    /// subprograms, and packages are reported as not implemented.
     pub fn declare_generics(&mut self, ids: &[GenericRef]) {
-        if !ids.is_empty() {
-            unimplemented!();
+        for &id in ids {
+            match id {
+                GenericRef::Const(id) => {
+                    let name = self.ctx.ast(id).3;
+                    self.declare(
+                        Spanned::new(name.name.into(), name.span),
+                        Def::IntfConst(id),
+                    )
+                }
+                GenericRef::Type(id) => self.unimp_generic(self.ctx.ast(id).1.span),
+                GenericRef::Subprog(id) => self.unimp_generic(self.ctx.ast(id).1.span),
+                GenericRef::Pkg(id) => self.unimp_generic(self.ctx.ast(id).1.span),
+            }
         }
     }
 }
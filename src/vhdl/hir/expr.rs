@@ -29,16 +29,16 @@ pub trait Expr2<'t>: Node<'t> {
 pub trait ExprContext<'t>:
     SessionContext
     + AllocInto<'t, IntegerConst<'t>>
-    + AllocOwnedInto<'t, Const2<'t>>
-    + AllocOwnedInto<'t, Type>
+    + AllocOwnedInto<'t, dyn Const2<'t> + 't>
+    + AllocOwnedInto<'t, dyn Type + 't>
 {
 }
 
 impl<'t, T> ExprContext<'t> for T where
     T: SessionContext
         + AllocInto<'t, IntegerConst<'t>>
-        + AllocOwnedInto<'t, Const2<'t>>
-        + AllocOwnedInto<'t, Type>
+        + AllocOwnedInto<'t, dyn Const2<'t> + 't>
+        + AllocOwnedInto<'t, dyn Type + 't>
 {
 }
 
@@ -202,7 +202,7 @@ impl<'t> Range2<'t> {
                         .add_note(format!(" left bound type: {}", lt))
                         .add_note(format!("right bound type: {}", rt)),
                     );
-                    Err(())
+                    Err(Error::Reported)
                 }
             }
         }
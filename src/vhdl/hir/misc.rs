@@ -23,7 +23,7 @@ fn apply_use_clause(clause: &ast::CompoundName, context: AllocContext) -> Result
     let mut lookup_name = pn;
     if lookup.is_empty() {
         context.emit(DiagBuilder2::error(format!("`{}` is unknown", pn.value)).span(pn.span));
-        return Err(());
+        return Err(Error::Reported);
     }
     // debugln!("`{}` resolved to {:?}", pn.value, lookup);
 
@@ -38,7 +38,7 @@ fn apply_use_clause(clause: &ast::CompoundName, context: AllocContext) -> Result
                 d = d.span(l.span);
             }
             context.emit(d);
-            return Err(());
+            return Err(Error::Reported);
         }
         let def = lookup.into_iter().next().unwrap();
         let scope = match def.value {
@@ -49,7 +49,7 @@ fn apply_use_clause(clause: &ast::CompoundName, context: AllocContext) -> Result
                     DiagBuilder2::error(format!("cannot select into {}", def.value.desc_kind()))
                         .span(def.span),
                 );
-                return Err(());
+                return Err(Error::Reported);
             }
         };
 
@@ -64,7 +64,7 @@ fn apply_use_clause(clause: &ast::CompoundName, context: AllocContext) -> Result
                         DiagBuilder2::error(format!("`{}` is unknown", lookup_name.value))
                             .span(lookup_name.span),
                     );
-                    return Err(());
+                    return Err(Error::Reported);
                 }
             }
             ast::NamePart::SelectAll(..) => {
@@ -76,7 +76,7 @@ fn apply_use_clause(clause: &ast::CompoundName, context: AllocContext) -> Result
                     DiagBuilder2::error(format!("`{}` cannot be used", clause.span.extract()))
                         .span(clause.span),
                 );
-                return Err(());
+                return Err(Error::Reported);
             }
         }
     }
@@ -60,7 +60,6 @@ make_arenas!(
         file_decl: Decl<FileDecl>,
         type_decl2: Decl<Option<TypeData>>,
         process_stmt: ProcessStmt,
-        sig_assign_stmt: SigAssignStmt,
         array_type_index: Spanned<ArrayTypeIndex>,
         subprog: Subprog,
         subprog_body: SubprogBody,
@@ -69,7 +68,7 @@ make_arenas!(
         wait_stmt: Stmt<WaitStmt>,
         assert_stmt: Stmt<AssertStmt>,
         report_stmt: Stmt<ReportStmt>,
-        // sig_assign_stmt:     Stmt<SigAssignStmt>,
+        sig_assign_stmt: Stmt<SigAssignStmt>,
         var_assign_stmt: Stmt<VarAssignStmt>,
         call_stmt: Stmt<CallStmt>,
         if_stmt: Stmt<IfStmt>,
@@ -78,6 +77,14 @@ make_arenas!(
         nexit_stmt: Stmt<NexitStmt>,
         return_stmt: Stmt<ReturnStmt>,
         null_stmt: Stmt<NullStmt>,
+        block_stmt: Stmt<BlockStmt>,
+        conc_assert_stmt: Stmt<ConcAssertStmt>,
+        conc_sig_assign_stmt: Stmt<ConcSigAssignStmt>,
+        comp_inst_stmt: Stmt<CompInstStmt>,
+        for_gen_stmt: Stmt<ForGenStmt>,
+        if_gen_stmt: Stmt<IfGenStmt>,
+        case_gen_stmt: Stmt<CaseGenStmt>,
+        gen_block: GenBlock,
     }
 );
 
@@ -165,6 +172,8 @@ pub struct SubtypeInd {
     pub type_mark: Spanned<TypeMarkRef>,
     /// The optional constraint.
     pub constraint: Option<Spanned<Constraint>>,
+    /// The optional resolution function.
+    pub resolution: Option<Spanned<SubprogRef>>,
 }
 
 /// A constraint.
@@ -336,7 +345,7 @@ pub struct PackageBody {
     /// The package name.
     pub name: Spanned<Name>,
     /// The package which this body targets.
-    pub pkg: Spanned<LatentPkgRef>,
+    pub pkg: Spanned<PkgDeclRef>,
     /// The declarations.
     pub decls: Vec<DeclInPkgBodyRef>,
 }
@@ -448,12 +457,16 @@ pub enum ExprData {
     Name(Def, Span),
     /// A resolved constant name.
     ConstName(ConstDeclRef),
+    /// A resolved generate parameter name.
+    GenParamName(GenBlockRef),
     /// A resolved signal name.
     SignalName(SignalRef),
     /// A resolved variable name.
     VarName(VarDeclRef),
     /// A resolved file name.
     FileName(FileDeclRef),
+    /// A resolved subprogram parameter name.
+    IntfConstName(IntfConstRef),
     /// An overloaded enum name.
     EnumName(Vec<Spanned<EnumRef>>),
     /// An overloaded resolved name.
@@ -469,6 +482,8 @@ pub enum ExprData {
     IntegerLiteral(ConstInt),
     /// A float literal.
     FloatLiteral(ConstFloat),
+    /// A physical literal, e.g. `10 ns`.
+    PhysicalLiteral(BigInt, Spanned<UnitRef>),
     /// A unary operator expression.
     Unary(Spanned<UnaryOp>, Vec<Spanned<Def>>, ExprRef),
     /// A binary operator expression.
@@ -485,6 +500,63 @@ pub enum ExprData {
     Cast(Spanned<TypeMarkRef>, ExprRef),
     /// A function call expression.
     Call(ExprRef, Spanned<AssocList>),
+    /// A call to a builtin function, e.g. `MATH_REAL.LOG2`.
+    BuiltinCall(Spanned<BuiltinFnRef>, Spanned<AssocList>),
+    /// A call to a subprogram declared in the design.
+    SubprogCall(Spanned<SubprogRef>, Spanned<AssocList>),
+    /// A call to a predefined attribute function of a type, e.g. `T'pos(X)`.
+    TypeAttrCall(Spanned<TypeMarkRef>, Spanned<TypeAttrFn>, ExprRef),
+}
+
+/// A predefined attribute function of a discrete type.
+///
+/// See IEEE 1076-2008 section 16.2.2.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum TypeAttrFn {
+    /// `T'pos(X)`, the position of a value.
+    Pos,
+    /// `T'val(X)`, the value at a position.
+    Val,
+    /// `T'succ(X)`, the value at the next higher position.
+    Succ,
+    /// `T'pred(X)`, the value at the next lower position.
+    Pred,
+    /// `T'leftof(X)`, the value to the left in the range of the type.
+    Leftof,
+    /// `T'rightof(X)`, the value to the right in the range of the type.
+    Rightof,
+}
+
+impl TypeAttrFn {
+    /// All predefined attribute functions.
+    pub const ALL: [TypeAttrFn; 6] = [
+        TypeAttrFn::Pos,
+        TypeAttrFn::Val,
+        TypeAttrFn::Succ,
+        TypeAttrFn::Pred,
+        TypeAttrFn::Leftof,
+        TypeAttrFn::Rightof,
+    ];
+
+    /// The name of the attribute.
+    pub fn name(self) -> &'static str {
+        match self {
+            TypeAttrFn::Pos => "pos",
+            TypeAttrFn::Val => "val",
+            TypeAttrFn::Succ => "succ",
+            TypeAttrFn::Pred => "pred",
+            TypeAttrFn::Leftof => "leftof",
+            TypeAttrFn::Rightof => "rightof",
+        }
+    }
+
+    /// Find the attribute function with a name, ignoring case.
+    pub fn from_name(name: &str) -> Option<TypeAttrFn> {
+        TypeAttrFn::ALL
+            .iter()
+            .cloned()
+            .find(|func| func.name().eq_ignore_ascii_case(name))
+    }
 }
 
 /// An object declaration.
@@ -589,34 +661,186 @@ pub enum ProcessSensitivity {
     /// The `all` sensitivity list.
     All,
     /// Explicitly enumerated signals.
-    List(Vec<Def>),
+    List(Spanned<SensitivityList>),
 }
 
-/// A sequential signal assignment.
+/// A concurrent assertion statement or PSL verification directive.
 ///
-/// See IEEE 1076-2008 section 10.5.
+/// See IEEE 1076-2008 sections 11.5 and 11.1.
 #[derive(Debug)]
-pub struct SigAssignStmt {
-    /// The scope within which the statement has been made.
+pub struct ConcAssertStmt {
+    /// The kind of directive.
+    pub kind: DirectiveKind,
+    /// The booleans that must hold in consecutive cycles.
+    pub seq: Vec<ExprRef>,
+    /// The value of an assertion whose condition is the literal `true` or
+    /// `false`.
+    pub literal: Option<bool>,
+    /// The report message.
+    pub report: Option<ExprRef>,
+    /// The text of a report message which is a string literal.
+    pub report_text: Option<Name>,
+    /// The severity level.
+    pub severity: Option<ExprRef>,
+}
+
+/// The different kinds of verification directives.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum DirectiveKind {
+    /// A concurrent assertion statement, or PSL `assert`.
+    Assert,
+    /// A PSL `assume`.
+    Assume,
+    /// A PSL `cover`.
+    Cover,
+    /// A PSL `restrict`.
+    Restrict,
+}
+
+impl DirectiveKind {
+    /// Check whether the directive constrains the environment rather than
+    /// checking the design.
+    pub fn is_constraint(self) -> bool {
+        match self {
+            DirectiveKind::Assume | DirectiveKind::Restrict => true,
+            _ => false,
+        }
+    }
+
+    /// The keyword that introduces the directive.
+    pub fn keyword(self) -> &'static str {
+        match self {
+            DirectiveKind::Assert => "assert",
+            DirectiveKind::Assume => "assume",
+            DirectiveKind::Cover => "cover",
+            DirectiveKind::Restrict => "restrict",
+        }
+    }
+}
+
+/// A concurrent signal assignment.
+///
+/// See IEEE 1076-2008 section 11.6. The assignment behaves like a process
+/// which is sensitive to all signals read on the right hand side.
+#[derive(Debug)]
+pub struct ConcSigAssignStmt {
+    /// The target of the assignment.
+    pub target: SigAssignTarget,
+    /// The location of the target in the source file.
+    pub target_span: Span,
+    /// The kind of the assignment.
+    pub kind: SigAssignKind,
+    /// The `GUARD` signal controlling a guarded assignment, if any.
+    pub guard: Option<SignalRef>,
+}
+
+/// A component instantiation.
+///
+/// See IEEE 1076-2008 section 11.7. Instances of a component are bound to the
+/// entity of the same name, as in the default binding of section 7.3.3.
+#[derive(Debug)]
+pub struct CompInstStmt {
+    /// The entity the instance is bound to.
+    pub entity: EntityRef,
+    /// The architecture of the entity the instance is bound to.
+    pub arch: ArchRef,
+    /// The actual associated with each port of the entity, in the order the
+    /// ports are declared. `None` if the port is left open.
+    pub ports: Vec<Option<Spanned<SignalRef>>>,
+}
+
+/// A block statement.
+///
+/// See IEEE 1076-2008 section 11.2. The declarations and statements of the
+/// block are elaborated in place, like those of a generated block.
+#[derive(Debug)]
+pub struct BlockStmt {
+    /// The block holding the declarations and statements.
+    pub block: GenBlockRef,
+}
+
+/// A for-generate statement.
+///
+/// See IEEE 1076-2008 section 11.8. The statement is elaborated statically,
+/// such that there is one block for each value of the generate parameter.
+#[derive(Debug)]
+pub struct ForGenStmt {
+    /// The generate parameter.
+    pub param: Spanned<Name>,
+    /// The generated blocks, in the order of the values of the parameter.
+    pub blocks: Vec<GenBlockRef>,
+}
+
+/// An if-generate statement.
+///
+/// See IEEE 1076-2008 section 11.8. The conditions are evaluated statically,
+/// such that only the first alternative whose condition holds is elaborated.
+#[derive(Debug)]
+pub struct IfGenStmt {
+    /// The block generated for the selected alternative, if any.
+    pub block: Option<GenBlockRef>,
+}
+
+/// A case-generate statement.
+///
+/// See IEEE 1076-2008 section 11.8. The expression is evaluated statically,
+/// such that only the alternative whose choices cover its value is elaborated.
+#[derive(Debug)]
+pub struct CaseGenStmt {
+    /// The block generated for the selected alternative, if any.
+    pub block: Option<GenBlockRef>,
+}
+
+/// A block generated by a generate statement, or the body of a block
+/// statement.
+#[derive(Debug)]
+pub struct GenBlock {
+    /// The scope within which the statement appears.
     pub parent: ScopeRef,
-    /// The location of the entire statement in the source file.
+    /// The span of the statement's body.
     pub span: Span,
-    /// The optional statement label.
+    /// The label of the statement.
     pub label: Option<Spanned<Name>>,
+    /// The generate parameter and its value for this block, if any.
+    pub param: Option<GenParam>,
+    /// The guard condition of a block statement, if any. It defines the
+    /// implicit `GUARD` signal within the block.
+    pub guard: Option<ExprRef>,
+    /// The declarations in the block.
+    pub decls: Vec<DeclInBlockRef>,
+    /// The statements in the block.
+    pub stmts: Vec<ConcStmtRef>,
+}
+
+/// The value of a generate parameter in a generated block.
+#[derive(Debug)]
+pub struct GenParam {
+    /// The name of the parameter.
+    pub name: Spanned<Name>,
+    /// The value of the parameter.
+    pub value: ConstInt,
+}
+
+/// A sequential signal assignment.
+///
+/// See IEEE 1076-2008 section 10.5.
+#[derive(Debug)]
+pub struct SigAssignStmt {
     /// The target of the assignment.
     pub target: SigAssignTarget,
-    /// The location of the right hand side in the source file.
+    /// The location of the target in the source file.
     pub target_span: Span,
     /// The kind of the assignment.
     pub kind: SigAssignKind,
-    /// The location of the right hand side in the source file.
-    pub kind_span: Span,
 }
 
 /// A signal assignment target.
 #[derive(Debug)]
 pub enum SigAssignTarget {
     Name(SignalRef),
+    /// A part of a signal, denoted by an indexed, slice, or selected name such
+    /// as `a(3)`, `a(7 downto 4)`, or `a.b`.
+    Part(SignalRef, ExprRef),
     Aggregate,
 }
 
@@ -816,6 +1040,8 @@ pub struct AssertStmt {
     pub cond: ExprRef,
     /// The report message.
     pub report: Option<ExprRef>,
+    /// The text of a report message which is a string literal.
+    pub report_text: Option<Name>,
     /// The severity level.
     pub severity: Option<ExprRef>,
 }
@@ -827,6 +1053,8 @@ pub struct AssertStmt {
 pub struct ReportStmt {
     /// The report message.
     pub report: ExprRef,
+    /// The text of the report message if it is a string literal.
+    pub report_text: Option<Name>,
     /// The severity level.
     pub severity: Option<ExprRef>,
 }
@@ -861,9 +1089,9 @@ pub enum VarAssignKind {
 #[derive(Debug)]
 pub struct CallStmt {
     /// The target subprogram.
-    pub subprog: SubprogRef,
-    /// The optional call parameters.
-    pub params: (),
+    pub subprog: Spanned<SubprogRef>,
+    /// The call parameters.
+    pub params: Spanned<AssocList>,
 }
 
 /// An if statement.
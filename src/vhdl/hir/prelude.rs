@@ -2,7 +2,7 @@
 
 pub use crate::common::errors::*;
 pub use crate::common::name::Name;
-pub use crate::common::score::Result;
+pub use crate::common::score::{Error, Result};
 pub use crate::common::source::{Span, Spanned};
 pub use crate::common::NodeId;
 
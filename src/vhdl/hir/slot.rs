@@ -3,7 +3,7 @@
 use std::cell::RefCell;
 use std::fmt;
 
-use crate::common::score::Result;
+use crate::common::score::{Error, Result};
 
 use crate::arenas::Alloc;
 use crate::hir::visit::Visitor;
@@ -39,7 +39,7 @@ where
     pub fn poll(&self) -> Result<&'t T> {
         match *self.0.borrow() {
             SlotState::ReadyOk(x) => return Ok(x),
-            SlotState::ReadyErr => return Err(()),
+            SlotState::ReadyErr => return Err(Error::Reported),
             SlotState::Transient => panic!("slot recursion"),
             _ => (),
         }
@@ -50,7 +50,7 @@ where
         let node = T::from_ast(ast, context.clone()).map(|x| context.alloc(x) as &T);
         self.0.replace(match node {
             Ok(x) => SlotState::ReadyOk(x),
-            Err(()) => SlotState::ReadyErr,
+            Err(_) => SlotState::ReadyErr,
         });
         node
     }
@@ -70,14 +70,14 @@ where
     fn accept(&self, visitor: &mut Visitor<'t>) {
         match self.poll() {
             Ok(n) => n.accept(visitor),
-            Err(()) => (),
+            Err(_) => (),
         }
     }
 
     fn walk(&self, visitor: &mut Visitor<'t>) {
         match self.poll() {
             Ok(n) => n.walk(visitor),
-            Err(()) => (),
+            Err(_) => (),
         }
     }
 }
@@ -55,7 +55,7 @@ impl<'t> TypeDecl2<'t> {
                             self.name.value
                         )),
                 );
-                Err(())
+                Err(Error::Reported)
             }
             TypeData::Enum(ref literals) => {
                 let ty = EnumBasetype::new(literals.value.iter().map(|x| match *x {
@@ -98,7 +98,7 @@ impl<'t> TypeDecl2<'t> {
                             .span(range.span)
                             .add_note(format!("bounds are of type {}", ty)),
                         );
-                        Err(())
+                        Err(Error::Reported)
                     }
                 }
             }
@@ -126,7 +126,7 @@ impl<'t> TypeDecl2<'t> {
                             .span(range.span)
                             .add_note(format!("bounds are of type {}", ty)),
                         );
-                        Err(())
+                        Err(Error::Reported)
                     }
                 }
             }
@@ -251,7 +251,7 @@ fn unpack_type_data<'t>(
                             .span(elem.span)
                             .add_note("expected an identifier or character literal"),
                         );
-                        Err(())
+                        Err(Error::Reported)
                     }
                 })
                 .collect::<Vec<Result<_>>>()
@@ -309,7 +309,7 @@ fn unpack_units<'t>(
                      1076-2008 section 5.2.4.",
                 ),
             );
-            return Err(());
+            return Err(Error::Reported);
         }
     };
     let mut had_fails = false;
@@ -328,7 +328,7 @@ fn unpack_units<'t>(
         had_fails = true;
     }
     if had_fails {
-        return Err(());
+        return Err(Error::Reported);
     }
 
     // Determine the units and how they are defined with respect
@@ -350,7 +350,7 @@ fn unpack_units<'t>(
                             .span(term.span),
                         );
                         debugln!("It is a {:#?}", term.value);
-                        return Err(());
+                        return Err(Error::Reported);
                     }
                 };
                 // TODO: Find a way to enable this again!
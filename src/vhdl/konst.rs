@@ -14,9 +14,12 @@ pub enum Const {
     Null,
     Int(ConstInt),
     Float(ConstFloat),
+    Physical(ConstPhysical),
     Enum(ConstEnum),
     IntRange(ConstIntRange),
     FloatRange(ConstFloatRange),
+    Record(ConstRecord),
+    Array(ConstArray),
 }
 
 impl Const {
@@ -25,9 +28,12 @@ impl Const {
             Const::Null => panic!("cannot negate null"),
             Const::Int(c) => Const::Int(c.negate()),
             Const::Float(c) => Const::Float(c.negate()),
+            Const::Physical(c) => Const::Physical(c.negate()),
             Const::Enum(_) => panic!("cannot negate enumeration literal"),
             Const::IntRange(_) => panic!("cannot negate integer range"),
             Const::FloatRange(_) => panic!("cannot negate float range"),
+            Const::Record(_) => panic!("cannot negate record"),
+            Const::Array(_) => panic!("cannot negate array"),
         }
     }
 
@@ -37,9 +43,12 @@ impl Const {
             Const::Null => "null",
             Const::Int(_) => "integer",
             Const::Float(_) => "float",
+            Const::Physical(_) => "physical value",
             Const::Enum(_) => "enumeration literal",
             Const::IntRange(_) => "integer range",
             Const::FloatRange(_) => "float range",
+            Const::Record(_) => "record",
+            Const::Array(_) => "array",
         }
     }
 }
@@ -56,6 +65,12 @@ impl From<ConstFloat> for Const {
     }
 }
 
+impl From<ConstPhysical> for Const {
+    fn from(k: ConstPhysical) -> Const {
+        Const::Physical(k)
+    }
+}
+
 impl From<ConstEnum> for Const {
     fn from(k: ConstEnum) -> Const {
         Const::Enum(k)
@@ -74,6 +89,18 @@ impl From<ConstFloatRange> for Const {
     }
 }
 
+impl From<ConstRecord> for Const {
+    fn from(k: ConstRecord) -> Const {
+        Const::Record(k)
+    }
+}
+
+impl From<ConstArray> for Const {
+    fn from(k: ConstArray) -> Const {
+        Const::Array(k)
+    }
+}
+
 /// A constant integer value.
 #[derive(Debug, Clone, PartialEq, Eq)]
 pub struct ConstInt {
@@ -99,12 +126,45 @@ impl ConstInt {
 }
 
 /// A constant float value.
-#[derive(Debug, Clone, PartialEq, Eq)]
-pub struct ConstFloat {}
+#[derive(Debug, Clone, PartialEq)]
+pub struct ConstFloat {
+    /// The value of the constant.
+    pub value: f64,
+}
 
 impl ConstFloat {
+    /// Create a new constant float.
+    pub fn new(value: f64) -> ConstFloat {
+        ConstFloat { value: value }
+    }
+
     pub fn negate(self) -> ConstFloat {
-        ConstFloat {}
+        ConstFloat::new(-self.value)
+    }
+}
+
+impl Eq for ConstFloat {}
+
+/// A constant physical value, such as a time.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ConstPhysical {
+    /// The type of the constant.
+    pub ty: PhysicalTy,
+    /// The value of the constant, as a multiple of the primary unit.
+    pub value: BigInt,
+}
+
+impl ConstPhysical {
+    /// Create a new constant physical value.
+    pub fn new(ty: PhysicalTy, value: BigInt) -> ConstPhysical {
+        ConstPhysical {
+            ty: ty,
+            value: value,
+        }
+    }
+
+    pub fn negate(self) -> ConstPhysical {
+        ConstPhysical::new(self.ty, -self.value)
     }
 }
 
@@ -152,6 +212,36 @@ where
 pub type ConstIntRange = ConstRange<ConstInt>;
 pub type ConstFloatRange = ConstRange<ConstFloat>;
 
+/// A constant record value.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ConstRecord {
+    /// The values of the fields, in the order they are declared in the record
+    /// type.
+    pub fields: Vec<Const>,
+}
+
+impl ConstRecord {
+    /// Create a new constant record.
+    pub fn new(fields: Vec<Const>) -> ConstRecord {
+        ConstRecord { fields: fields }
+    }
+}
+
+/// A constant array value.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ConstArray {
+    /// The values of the elements, from left to right. The elements of a
+    /// multidimensional array are arrays themselves.
+    pub elements: Vec<Const>,
+}
+
+impl ConstArray {
+    /// Create a new constant array.
+    pub fn new(elements: Vec<Const>) -> ConstArray {
+        ConstArray { elements: elements }
+    }
+}
+
 // ----- FORMATTING ------------------------------------------------------------
 
 impl fmt::Display for Const {
@@ -160,9 +250,12 @@ impl fmt::Display for Const {
             Const::Null => write!(f, "null"),
             Const::Int(ref k) => k.fmt(f),
             Const::Float(ref k) => k.fmt(f),
+            Const::Physical(ref k) => k.fmt(f),
             Const::Enum(ref k) => k.fmt(f),
             Const::IntRange(ref k) => k.fmt(f),
             Const::FloatRange(ref k) => k.fmt(f),
+            Const::Record(ref k) => k.fmt(f),
+            Const::Array(ref k) => k.fmt(f),
         }
     }
 }
@@ -173,6 +266,12 @@ impl fmt::Display for ConstInt {
     }
 }
 
+impl fmt::Display for ConstPhysical {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{} {}", self.value, self.ty.units[self.ty.primary].name)
+    }
+}
+
 impl fmt::Display for ConstEnum {
     fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
         write!(f, "<enum>")
@@ -181,7 +280,7 @@ impl fmt::Display for ConstEnum {
 
 impl fmt::Display for ConstFloat {
     fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
-        write!(f, "<float>")
+        write!(f, "{:?}", self.value)
     }
 }
 
@@ -193,3 +292,29 @@ where
         write!(f, "{} {} {}", self.left_bound, self.dir, self.right_bound)
     }
 }
+
+impl fmt::Display for ConstRecord {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "(")?;
+        for (i, field) in self.fields.iter().enumerate() {
+            if i > 0 {
+                write!(f, ", ")?;
+            }
+            field.fmt(f)?;
+        }
+        write!(f, ")")
+    }
+}
+
+impl fmt::Display for ConstArray {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "(")?;
+        for (i, element) in self.elements.iter().enumerate() {
+            if i > 0 {
+                write!(f, ", ")?;
+            }
+            element.fmt(f)?;
+        }
+        write!(f, ")")
+    }
+}
@@ -4,6 +4,7 @@ use std::borrow::{Borrow, Cow};
 use std::fmt::{self, Debug, Display};
 
 use crate::common::errors::*;
+use crate::common::score;
 
 use crate::konst2::{FloatingConst, IntegerConst};
 use crate::ty2::Type;
@@ -47,12 +48,13 @@ pub enum ConstError {
 }
 
 impl EmitError for ConstError {
-    type Output = ();
+    type Output = score::Error;
 
-    fn emit<C: DiagEmitter>(self, ctx: C) {
+    fn emit<C: DiagEmitter>(self, ctx: C) -> score::Error {
         match self {
             ConstError::OutOfRange => ctx.emit(DiagBuilder2::error("constant value out of range")),
         }
+        score::Error::Reported
     }
 }
 
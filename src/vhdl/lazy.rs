@@ -10,11 +10,15 @@ use std::collections::HashMap;
 use std::fmt;
 
 use crate::hir;
+use crate::konst::Const;
 use crate::score::*;
 use crate::score::{ScoreBoard, ScoreContext};
+use crate::trace::{Pass, PassTrace};
 use crate::ty::Ty;
 use crate::typeck::TypeckContext;
-use moore_common::score::{NodeStorage, Result};
+use moore_common::errors::*;
+use moore_common::score::{Error, NodeStorage, Result};
+use moore_common::source::Span;
 use moore_common::NodeId;
 
 /// A lazily evaluated node.
@@ -44,6 +48,24 @@ pub struct LazyPhaseTable<'sb, 'ast: 'sb, 'ctx: 'sb> {
     pub typeck: RefCell<LazyTypeckTable<'sb, 'ast, 'ctx>>,
     /// The lazy typeval table.
     pub typeval: RefCell<LazyTypevalTable<'sb, 'ast, 'ctx>>,
+    /// The lazy codegen table.
+    pub codegen: RefCell<LazyCodegenTable<'sb, 'ast, 'ctx>>,
+    /// The lazy constant evaluation table.
+    pub const_eval: RefCell<LazyConstEvalTable<'sb, 'ast, 'ctx>>,
+    /// The trace of scheduled and evaluated passes.
+    pub trace: PassTrace,
+    /// The passes that have been scheduled, together with the location they
+    /// have been scheduled from and whether they have been run.
+    scheduled: RefCell<HashMap<(Pass, NodeId), Scheduled>>,
+}
+
+/// A record of a scheduled pass.
+#[derive(Debug, Clone, Copy)]
+struct Scheduled {
+    /// The span of the node the pass was scheduled for.
+    span: Span,
+    /// Whether the pass has been run.
+    forced: bool,
 }
 
 impl<'sb, 'ast, 'ctx> LazyPhaseTable<'sb, 'ast, 'ctx> {
@@ -54,8 +76,75 @@ impl<'sb, 'ast, 'ctx> LazyPhaseTable<'sb, 'ast, 'ctx> {
             hir: LazyPhase::new(),
             typeck: RefCell::new(HashMap::new()),
             typeval: RefCell::new(HashMap::new()),
+            codegen: RefCell::new(HashMap::new()),
+            const_eval: RefCell::new(HashMap::new()),
+            trace: PassTrace::default(),
+            scheduled: RefCell::new(HashMap::new()),
+        }
+    }
+
+    /// Record that a pass has been scheduled for a node.
+    ///
+    /// Emits a compiler bug if the pass has already been scheduled for the
+    /// node before, since the second callback would silently replace the
+    /// first one, or never run if the first one already has.
+    pub fn record_schedule(&self, emitter: &dyn DiagEmitter, pass: Pass, id: NodeId, span: Span) {
+        let previous = self.scheduled.borrow_mut().insert(
+            (pass, id),
+            Scheduled {
+                span,
+                forced: false,
+            },
+        );
+        if let Some(previous) = previous {
+            emitter.emit(
+                DiagBuilder2::bug(format!("{} pass for {:?} scheduled twice", pass.name(), id))
+                    .span(span)
+                    .add_note(format!(
+                        "Previously scheduled here{}:",
+                        if previous.forced {
+                            " and already run"
+                        } else {
+                            ""
+                        }
+                    ))
+                    .span(previous.span),
+            );
+        }
+    }
+
+    /// Record that a scheduled pass has been run for a node.
+    pub fn record_forced(&self, pass: Pass, id: NodeId) {
+        if let Some(s) = self.scheduled.borrow_mut().get_mut(&(pass, id)) {
+            s.forced = true;
         }
     }
+
+    /// Report all passes that have been scheduled but never run.
+    ///
+    /// This is intended to be called at the end of compilation, to detect
+    /// type checks and other work that was silently dropped. Returns the
+    /// number of passes reported.
+    pub fn audit(&self, emitter: &dyn DiagEmitter, passes: &[Pass]) -> usize {
+        let scheduled = self.scheduled.borrow();
+        let mut missed: Vec<_> = scheduled
+            .iter()
+            .filter(|(&(pass, _), s)| !s.forced && passes.contains(&pass))
+            .map(|(&(pass, id), s)| (s.span, pass, id))
+            .collect();
+        missed.sort_by_key(|&(span, _, id)| (span.source, span.begin, id));
+        for &(span, pass, id) in &missed {
+            emitter.emit(
+                DiagBuilder2::warning(format!(
+                    "{} pass for {:?} was scheduled but never run",
+                    pass.name(),
+                    id
+                ))
+                .span(span),
+            );
+        }
+        missed.len()
+    }
 }
 
 /// A table of tasks needed to perform a compiler phase.
@@ -89,7 +178,7 @@ where
         ctx: &ScoreContext<'lazy, 'sb, 'ast, 'ctx>,
     ) -> Result<R>
     where
-        I: Copy + fmt::Debug,
+        I: Copy + fmt::Debug + Into<NodeId>,
         T: NodeStorage<
             I,
             Node = LazyNode<
@@ -98,8 +187,19 @@ where
         >,
     {
         let task = self.table.borrow_mut().set(id, LazyNode::Running);
+        ctx.lazy.record_forced(Pass::Hir, id.into());
+        let _trace = ctx.lazy.trace.begin(Pass::Hir, id);
         match task {
-            Some(LazyNode::Pending(f)) => f(ctx),
+            Some(LazyNode::Pending(f)) => {
+                let result = f(ctx);
+                // A cancelled task is scheduled again, such that it can be
+                // retried later on.
+                if let Err(Error::Cancelled) = result {
+                    self.table.borrow_mut().set(id, LazyNode::Pending(f));
+                    return Err(Error::Cancelled);
+                }
+                result
+            }
             Some(LazyNode::Running) => panic!("recursion when running task for {:?}", id),
             None => panic!("no task scheduled for {:?}", id),
         }
@@ -118,6 +218,19 @@ pub type LazyTypeck<'sb, 'ast, 'ctx> =
 pub type LazyTypeval<'sb, 'ast, 'ctx> =
     Box<for<'a, 'b, 'c> Fn(&'a TypeckContext<'b, 'c, 'sb, 'ast, 'ctx>) -> Result<&'ctx Ty> + 'sb>;
 
+/// A callback to lazily generate code for a node.
+pub type LazyCodegen<'sb, 'ast, 'ctx> = Box<
+    for<'a, 'b, 'c> Fn(
+            &'a ScoreContext<'b, 'sb, 'ast, 'ctx>,
+            &'c mut llhd::Entity,
+        ) -> Result<()>
+        + 'sb,
+>;
+
+/// A callback to lazily evaluate the constant value of a node.
+pub type LazyConstEval<'sb, 'ast, 'ctx> =
+    Box<for<'a, 'b> Fn(&'a ScoreContext<'b, 'sb, 'ast, 'ctx>) -> Result<&'ctx Const> + 'sb>;
+
 /// A table of pending or running HIR lowerings.
 node_storage!(LazyHirTable<'sb, 'ast, 'ctx> where ('ast: 'sb, 'ctx: 'sb):
     // Miscellaneous
@@ -147,6 +260,16 @@ node_storage!(LazyHirTable<'sb, 'ast, 'ctx> where ('ast: 'sb, 'ctx: 'sb):
     nexit_stmt:       NexitStmtRef     => LazyNode<LazyHir<'sb, 'ast, 'ctx, hir::Stmt<hir::NexitStmt>>>,
     return_stmt:      ReturnStmtRef    => LazyNode<LazyHir<'sb, 'ast, 'ctx, hir::Stmt<hir::ReturnStmt>>>,
     null_stmt:        NullStmtRef      => LazyNode<LazyHir<'sb, 'ast, 'ctx, hir::Stmt<hir::NullStmt>>>,
+
+    // Concurrent statements
+    block_stmts: BlockStmtRef => LazyNode<LazyHir<'sb, 'ast, 'ctx, hir::Stmt<hir::BlockStmt>>>,
+    conc_call_stmts: ConcCallStmtRef => LazyNode<LazyHir<'sb, 'ast, 'ctx, hir::Stmt<hir::CallStmt>>>,
+    conc_assert_stmts: ConcAssertStmtRef => LazyNode<LazyHir<'sb, 'ast, 'ctx, hir::Stmt<hir::ConcAssertStmt>>>,
+    conc_sig_assign_stmts: ConcSigAssignStmtRef => LazyNode<LazyHir<'sb, 'ast, 'ctx, hir::Stmt<hir::ConcSigAssignStmt>>>,
+    comp_inst_stmts: CompInstStmtRef => LazyNode<LazyHir<'sb, 'ast, 'ctx, hir::Stmt<hir::CompInstStmt>>>,
+    for_gen_stmts: ForGenStmtRef => LazyNode<LazyHir<'sb, 'ast, 'ctx, hir::Stmt<hir::ForGenStmt>>>,
+    if_gen_stmts: IfGenStmtRef => LazyNode<LazyHir<'sb, 'ast, 'ctx, hir::Stmt<hir::IfGenStmt>>>,
+    case_gen_stmts: CaseGenStmtRef => LazyNode<LazyHir<'sb, 'ast, 'ctx, hir::Stmt<hir::CaseGenStmt>>>,
 );
 
 impl<'sb, 'ast, 'ctx> Default for LazyHirTable<'sb, 'ast, 'ctx> {
@@ -161,3 +284,15 @@ pub type LazyTypeckTable<'sb, 'ast, 'ctx> = HashMap<NodeId, LazyNode<LazyTypeck<
 /// A table of pending or running type evaluations.
 pub type LazyTypevalTable<'sb, 'ast, 'ctx> =
     HashMap<NodeId, LazyNode<LazyTypeval<'sb, 'ast, 'ctx>>>;
+
+/// A table of pending or running code generations.
+///
+/// Unlike the other tables, a pending code generation remains in the table
+/// after it has run, since the code for a node may be generated into more than
+/// one entity.
+pub type LazyCodegenTable<'sb, 'ast, 'ctx> =
+    HashMap<NodeId, LazyNode<LazyCodegen<'sb, 'ast, 'ctx>>>;
+
+/// A table of pending or running constant evaluations.
+pub type LazyConstEvalTable<'sb, 'ast, 'ctx> =
+    HashMap<NodeId, LazyNode<LazyConstEval<'sb, 'ast, 'ctx>>>;
@@ -29,11 +29,14 @@ pub mod konst;
 pub mod konst2;
 pub mod lazy;
 pub mod make_ctx;
+pub mod math_real;
 pub mod op;
 pub mod overload_resolver;
+pub mod rename;
 pub mod scope;
 pub mod scope2;
 pub mod term;
+pub mod trace;
 pub mod ty;
 pub mod ty2;
 pub mod typeck;
@@ -13,6 +13,7 @@ use crate::arenas::Alloc;
 use crate::hir;
 use crate::lazy::*;
 use crate::score::{HirTable, ScoreContext};
+use crate::trace::Pass;
 use moore_common::score::NodeStorage;
 use moore_common::source::Span;
 use moore_common::NodeId;
@@ -39,7 +40,7 @@ where
         span: Span,
         id: I,
     ) -> MakeContext<'sbc, 'lazy, 'sb, 'ast, 'ctx, I> {
-        debugln!("make {:?} `{}`", id, {
+        ctx.lazy.trace.event(Pass::Make, id, || {
             let mut sp = span;
             let mut shortened = false;
             if sp.end - sp.begin > 32 {
@@ -52,7 +53,7 @@ where
             } else if shortened {
                 extract.push_str("...");
             }
-            extract
+            format!("`{}`", extract)
         });
         ctx.set_span(id, span);
         MakeContext {
@@ -74,6 +75,13 @@ where
     where
         LazyHirTable<'sb, 'ast, 'ctx>: NodeStorage<I, Node = LazyNode<LazyHir<'sb, 'ast, 'ctx, R>>>,
     {
+        self.ctx
+            .lazy
+            .trace
+            .event(Pass::Hir, self.id, || "scheduled".to_string());
+        self.ctx
+            .lazy
+            .record_schedule(self.ctx.sess, Pass::Hir, self.id.into(), self.span);
         self.ctx
             .lazy
             .hir
@@ -94,6 +102,13 @@ where
 
     /// Schedule a callback that type checks the node.
     pub fn typeck(&self, f: LazyTypeck<'sb, 'ast, 'ctx>) {
+        self.ctx
+            .lazy
+            .trace
+            .event(Pass::Typeck, self.id, || "scheduled".to_string());
+        self.ctx
+            .lazy
+            .record_schedule(self.ctx.sess, Pass::Typeck, self.id.into(), self.span);
         self.ctx
             .lazy
             .typeck
@@ -103,10 +118,49 @@ where
 
     /// Schedule a callback that evaluates the type of the node.
     pub fn typeval(&self, f: LazyTypeval<'sb, 'ast, 'ctx>) {
+        self.ctx
+            .lazy
+            .trace
+            .event(Pass::Typeval, self.id, || "scheduled".to_string());
+        self.ctx
+            .lazy
+            .record_schedule(self.ctx.sess, Pass::Typeval, self.id.into(), self.span);
         self.ctx
             .lazy
             .typeval
             .borrow_mut()
             .insert(self.id.into(), LazyNode::Pending(f));
     }
+
+    /// Schedule a callback that generates code for the node.
+    pub fn codegen(&self, f: LazyCodegen<'sb, 'ast, 'ctx>) {
+        self.ctx
+            .lazy
+            .trace
+            .event(Pass::Codegen, self.id, || "scheduled".to_string());
+        self.ctx
+            .lazy
+            .record_schedule(self.ctx.sess, Pass::Codegen, self.id.into(), self.span);
+        self.ctx
+            .lazy
+            .codegen
+            .borrow_mut()
+            .insert(self.id.into(), LazyNode::Pending(f));
+    }
+
+    /// Schedule a callback that evaluates the constant value of the node.
+    pub fn const_eval(&self, f: LazyConstEval<'sb, 'ast, 'ctx>) {
+        self.ctx
+            .lazy
+            .trace
+            .event(Pass::ConstEval, self.id, || "scheduled".to_string());
+        self.ctx
+            .lazy
+            .record_schedule(self.ctx.sess, Pass::ConstEval, self.id.into(), self.span);
+        self.ctx
+            .lazy
+            .const_eval
+            .borrow_mut()
+            .insert(self.id.into(), LazyNode::Pending(f));
+    }
 }
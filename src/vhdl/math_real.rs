@@ -0,0 +1,192 @@
+// Copyright (c) 2016-2020 Fabian Schuiki
+
+//! The functions and constants of the `IEEE.MATH_REAL` package.
+//!
+//! The package is provided as a builtin, such that generic computations like
+//! `integer(ceil(log2(real(DEPTH))))` can be evaluated during elaboration. See
+//! IEEE 1076.2-1996 and IEEE 1076-2008 section 16.3.
+
+use std::f64::consts;
+
+/// A function of the `MATH_REAL` package.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+#[allow(missing_docs)]
+pub enum MathRealFn {
+    Sign,
+    Ceil,
+    Floor,
+    Round,
+    Trunc,
+    Realmax,
+    Realmin,
+    Sqrt,
+    Cbrt,
+    Exp,
+    Log,
+    Log2,
+    Log10,
+    Sin,
+    Cos,
+    Tan,
+    Arcsin,
+    Arccos,
+    Arctan,
+    Sinh,
+    Cosh,
+    Tanh,
+    Arcsinh,
+    Arccosh,
+    Arctanh,
+    /// The `UNIFORM` procedure, which produces pseudo-random numbers and can
+    /// therefore not be evaluated during elaboration.
+    Uniform,
+}
+
+impl MathRealFn {
+    /// All functions of the package.
+    pub const ALL: [MathRealFn; 26] = [
+        MathRealFn::Sign,
+        MathRealFn::Ceil,
+        MathRealFn::Floor,
+        MathRealFn::Round,
+        MathRealFn::Trunc,
+        MathRealFn::Realmax,
+        MathRealFn::Realmin,
+        MathRealFn::Sqrt,
+        MathRealFn::Cbrt,
+        MathRealFn::Exp,
+        MathRealFn::Log,
+        MathRealFn::Log2,
+        MathRealFn::Log10,
+        MathRealFn::Sin,
+        MathRealFn::Cos,
+        MathRealFn::Tan,
+        MathRealFn::Arcsin,
+        MathRealFn::Arccos,
+        MathRealFn::Arctan,
+        MathRealFn::Sinh,
+        MathRealFn::Cosh,
+        MathRealFn::Tanh,
+        MathRealFn::Arcsinh,
+        MathRealFn::Arccosh,
+        MathRealFn::Arctanh,
+        MathRealFn::Uniform,
+    ];
+
+    /// The name of the function as declared in the package.
+    pub fn name(self) -> &'static str {
+        match self {
+            MathRealFn::Sign => "SIGN",
+            MathRealFn::Ceil => "CEIL",
+            MathRealFn::Floor => "FLOOR",
+            MathRealFn::Round => "ROUND",
+            MathRealFn::Trunc => "TRUNC",
+            MathRealFn::Realmax => "REALMAX",
+            MathRealFn::Realmin => "REALMIN",
+            MathRealFn::Sqrt => "SQRT",
+            MathRealFn::Cbrt => "CBRT",
+            MathRealFn::Exp => "EXP",
+            MathRealFn::Log => "LOG",
+            MathRealFn::Log2 => "LOG2",
+            MathRealFn::Log10 => "LOG10",
+            MathRealFn::Sin => "SIN",
+            MathRealFn::Cos => "COS",
+            MathRealFn::Tan => "TAN",
+            MathRealFn::Arcsin => "ARCSIN",
+            MathRealFn::Arccos => "ARCCOS",
+            MathRealFn::Arctan => "ARCTAN",
+            MathRealFn::Sinh => "SINH",
+            MathRealFn::Cosh => "COSH",
+            MathRealFn::Tanh => "TANH",
+            MathRealFn::Arcsinh => "ARCSINH",
+            MathRealFn::Arccosh => "ARCCOSH",
+            MathRealFn::Arctanh => "ARCTANH",
+            MathRealFn::Uniform => "UNIFORM",
+        }
+    }
+
+    /// The minimum and maximum number of arguments of the function.
+    ///
+    /// `LOG` and `ARCTAN` are overloaded with a second argument, the base of
+    /// the logarithm and the `X` coordinate, respectively.
+    pub fn arity(self) -> (usize, usize) {
+        match self {
+            MathRealFn::Realmax | MathRealFn::Realmin => (2, 2),
+            MathRealFn::Log | MathRealFn::Arctan => (1, 2),
+            MathRealFn::Uniform => (3, 3),
+            _ => (1, 1),
+        }
+    }
+
+    /// Evaluate the function.
+    ///
+    /// Returns `None` for the `UNIFORM` procedure, and if the arguments are
+    /// outside the domain of the function, e.g. `SQRT(-1.0)`.
+    pub fn apply(self, args: &[f64]) -> Option<f64> {
+        let x = args.get(0).cloned().unwrap_or(0.0);
+        let y = args.get(1).cloned();
+        let value = match self {
+            MathRealFn::Sign if x > 0.0 => 1.0,
+            MathRealFn::Sign if x < 0.0 => -1.0,
+            MathRealFn::Sign => 0.0,
+            MathRealFn::Ceil => x.ceil(),
+            MathRealFn::Floor => x.floor(),
+            MathRealFn::Round => x.round(),
+            MathRealFn::Trunc => x.trunc(),
+            MathRealFn::Realmax => x.max(y?),
+            MathRealFn::Realmin => x.min(y?),
+            MathRealFn::Sqrt => x.sqrt(),
+            MathRealFn::Cbrt => x.cbrt(),
+            MathRealFn::Exp => x.exp(),
+            MathRealFn::Log => match y {
+                Some(base) => x.log(base),
+                None => x.ln(),
+            },
+            MathRealFn::Log2 => x.log2(),
+            MathRealFn::Log10 => x.log10(),
+            MathRealFn::Sin => x.sin(),
+            MathRealFn::Cos => x.cos(),
+            MathRealFn::Tan => x.tan(),
+            MathRealFn::Arcsin => x.asin(),
+            MathRealFn::Arccos => x.acos(),
+            MathRealFn::Arctan => match y {
+                Some(y) => x.atan2(y),
+                None => x.atan(),
+            },
+            MathRealFn::Sinh => x.sinh(),
+            MathRealFn::Cosh => x.cosh(),
+            MathRealFn::Tanh => x.tanh(),
+            MathRealFn::Arcsinh => x.asinh(),
+            MathRealFn::Arccosh => x.acosh(),
+            MathRealFn::Arctanh => x.atanh(),
+            MathRealFn::Uniform => return None,
+        };
+        if value.is_finite() {
+            Some(value)
+        } else {
+            None
+        }
+    }
+}
+
+/// The constants of the `MATH_REAL` package.
+pub const MATH_REAL_CONSTANTS: [(&str, f64); 18] = [
+    ("MATH_E", consts::E),
+    ("MATH_1_OVER_E", 1.0 / consts::E),
+    ("MATH_PI", consts::PI),
+    ("MATH_2_PI", 2.0 * consts::PI),
+    ("MATH_1_OVER_PI", consts::FRAC_1_PI),
+    ("MATH_PI_OVER_2", consts::FRAC_PI_2),
+    ("MATH_PI_OVER_3", consts::FRAC_PI_3),
+    ("MATH_PI_OVER_4", consts::FRAC_PI_4),
+    ("MATH_3_PI_OVER_2", 3.0 * consts::FRAC_PI_2),
+    ("MATH_LOG_OF_2", consts::LN_2),
+    ("MATH_LOG_OF_10", consts::LN_10),
+    ("MATH_LOG2_OF_E", consts::LOG2_E),
+    ("MATH_LOG10_OF_E", consts::LOG10_E),
+    ("MATH_SQRT_2", consts::SQRT_2),
+    ("MATH_1_OVER_SQRT_2", consts::FRAC_1_SQRT_2),
+    ("MATH_SQRT_PI", 1.772_453_850_905_516),
+    ("MATH_DEG_TO_RAD", consts::PI / 180.0),
+    ("MATH_RAD_TO_DEG", 180.0 / consts::PI),
+];
@@ -0,0 +1,1066 @@
+// Copyright (c) 2016-2020 Fabian Schuiki
+
+//! Concurrent statements
+
+use num::{BigInt, One};
+
+use crate::common::errors::*;
+use crate::common::name::{get_name_table, Name};
+use crate::common::score::{Error, NodeRef, Result};
+use crate::common::source::{Span, Spanned};
+use crate::common::util::HasSpan;
+
+use crate::add_ctx::AddContext;
+use crate::hir;
+use crate::konst::{Const, ConstInt};
+use crate::nodes::seq_stmt::CondOrUncond;
+use crate::score::*;
+use crate::syntax::ast;
+use crate::term::TermContext;
+use crate::ty::Ty;
+use crate::typeck::{Typeck, TypeckContext};
+
+impl<'sbc, 'lazy, 'sb, 'ast, 'ctx> AddContext<'sbc, 'lazy, 'sb, 'ast, 'ctx> {
+    /// Add a concurrent assertion statement or PSL verification directive.
+    pub fn add_conc_assert_stmt(&self, stmt: &'ast ast::Stmt) -> Result<ConcAssertStmtRef> {
+        let (mk, id, scope) = self.make(stmt.span);
+        mk.lower_to_hir(Box::new(move |sbc| {
+            let ctx = AddContext::new(sbc, scope);
+            let (kind, seq, literal, report, severity) = match stmt.data {
+                ast::AssertStmt {
+                    ref cond,
+                    ref report,
+                    ref severity,
+                } => (
+                    hir::DirectiveKind::Assert,
+                    ctx.add_expr(cond).map(|x| vec![x]),
+                    bool_literal(cond),
+                    report,
+                    severity,
+                ),
+                ast::PslDirectiveStmt {
+                    kind,
+                    ref seq,
+                    ref report,
+                } => (
+                    match kind {
+                        ast::PslDirective::Assume => hir::DirectiveKind::Assume,
+                        ast::PslDirective::Cover => hir::DirectiveKind::Cover,
+                        ast::PslDirective::Restrict => hir::DirectiveKind::Restrict,
+                    },
+                    seq.iter().map(|x| ctx.add_expr(x)).collect(),
+                    None,
+                    report,
+                    &None,
+                ),
+                _ => unreachable!(),
+            };
+            let report_text = report.as_ref().and_then(string_literal);
+            let report = ctx.add_optional(report, AddContext::add_expr);
+            let severity = ctx.add_optional(severity, AddContext::add_expr);
+            let (seq, report, severity): (Vec<_>, _, _) = (seq?, report?, severity?);
+            for &id in &seq {
+                sbc.set_type_context(id, TypeCtx::Type(sbc.builtin_boolean_type()));
+            }
+            sbc.set_type_context_optional(report, sbc.builtin_string_type());
+            sbc.set_type_context_optional(severity, sbc.builtin_severity_type());
+            Ok(hir::Stmt {
+                parent: scope,
+                span: stmt.span,
+                label: stmt.label,
+                stmt: hir::ConcAssertStmt {
+                    kind: kind,
+                    seq: seq,
+                    literal: literal,
+                    report: report,
+                    report_text: report_text,
+                    severity: severity,
+                },
+            })
+        }));
+        mk.typeck(Box::new(move |tyc| {
+            let _hir = tyc.ctx.lazy_hir(id)?;
+            Ok(())
+        }));
+        Ok(mk.finish())
+    }
+
+    /// Add a concurrent procedure call statement.
+    ///
+    /// See IEEE 1076-2008 section 11.4.
+    pub fn add_conc_call_stmt(&self, stmt: &'ast ast::Stmt) -> Result<ConcCallStmtRef> {
+        let (mk, id, scope) = self.make::<ConcCallStmtRef>(stmt.span);
+        mk.lower_to_hir(Box::new(move |sbc| {
+            AddContext::new(sbc, scope).unpack_call_stmt(stmt)
+        }));
+        mk.typeck(Box::new(move |tyc| {
+            let hir = tyc.ctx.lazy_hir(id)?;
+            tyc.typeck_call(hir.stmt.subprog.value, &hir.stmt.params)
+        }));
+        Ok(mk.finish())
+    }
+
+    /// Add a concurrent signal assignment statement.
+    ///
+    /// See IEEE 1076-2008 section 11.6.
+    pub fn add_conc_sig_assign_stmt(&self, stmt: &'ast ast::Stmt) -> Result<ConcSigAssignStmtRef> {
+        let (mk, id, scope) = self.make::<ConcSigAssignStmtRef>(stmt.span);
+        let (target, dm, guarded, waves) = match stmt.data {
+            ast::AssignStmt {
+                ref target,
+                kind: ast::AssignKind::Signal,
+                guarded,
+                mode:
+                    Spanned {
+                        value: ast::AssignMode::Normal(ref dm, ref waves),
+                        ..
+                    },
+            } => (
+                target,
+                dm,
+                guarded,
+                match self.unpack_cond_or_uncond_waves(waves)? {
+                    CondOrUncond::Cond(conds, otherwise) => SigAssignWaves::Cond(conds, otherwise),
+                    CondOrUncond::Uncond(wave) => SigAssignWaves::Simple(wave),
+                },
+            ),
+            ast::SelectAssignStmt {
+                ref select,
+                qm,
+                ref target,
+                kind: ast::AssignKind::Signal,
+                guarded,
+                mode: ast::SelectAssignMode::Normal(ref dm),
+                ref waves,
+            } => (
+                target,
+                dm,
+                guarded,
+                SigAssignWaves::Sel {
+                    matching: qm,
+                    disc: select,
+                    waves: waves,
+                },
+            ),
+            _ => {
+                self.emit(
+                    DiagBuilder2::error(format!(
+                        "`{}` is not a valid concurrent signal assignment",
+                        stmt.span.extract()
+                    ))
+                    .span(stmt.human_span())
+                    .add_note(
+                        "Concurrent assignments cannot assign variables, or force or release \
+                         signals. See IEEE 1076-2008 section 11.6.",
+                    ),
+                );
+                return Err(Error::Reported);
+            }
+        };
+        mk.lower_to_hir(Box::new(move |sbc| {
+            let ctx = AddContext::new(sbc, scope);
+            let guard = match guarded {
+                true => Some(guard_signal(sbc, scope, stmt)?),
+                false => None,
+            };
+            let target_hir = sbc.unpack_signal_assign_target(scope, &target.value)?;
+            let target_node = match target_hir {
+                hir::SigAssignTarget::Name(sig) => sig.into(),
+                hir::SigAssignTarget::Part(_, expr) => expr.into(),
+                hir::SigAssignTarget::Aggregate => unreachable!(),
+            };
+            let kind = ctx.add_sig_assign_kind(dm, &waves, target_node)?;
+            Ok(hir::Stmt {
+                parent: scope,
+                span: stmt.span,
+                label: stmt.label,
+                stmt: hir::ConcSigAssignStmt {
+                    target: target_hir,
+                    target_span: target.span,
+                    kind: kind,
+                    guard: guard,
+                },
+            })
+        }));
+        mk.typeck(Box::new(move |tyc| {
+            let hir = tyc.ctx.lazy_hir(id)?;
+            let ty = match hir.stmt.target {
+                hir::SigAssignTarget::Name(sig) => tyc.ctx.ty(sig)?,
+                hir::SigAssignTarget::Part(_, expr) => tyc.lazy_typeval(expr)?,
+                hir::SigAssignTarget::Aggregate => unreachable!(),
+            };
+            tyc.typeck_sig_assign_kind(&hir.stmt.kind, ty)
+        }));
+        Ok(mk.finish())
+    }
+
+    /// Add a component instantiation statement.
+    ///
+    /// See IEEE 1076-2008 section 11.7.
+    pub fn add_comp_inst_stmt(&self, stmt: &'ast ast::Stmt) -> Result<CompInstStmtRef> {
+        let (mk, id, scope) = self.make::<CompInstStmtRef>(stmt.span);
+        let (target, name, generics, ports) = match stmt.data {
+            ast::InstOrCallStmt {
+                target,
+                ref name,
+                ref generics,
+                ref ports,
+            } => (target, name, generics, ports),
+            _ => unreachable!(),
+        };
+        if target == Some(ast::InstTarget::Cfg) {
+            self.emit(
+                DiagBuilder2::bug("instantiation of a configuration not implemented")
+                    .span(stmt.human_span()),
+            );
+            return Err(Error::Reported);
+        }
+        mk.lower_to_hir(Box::new(move |sbc| {
+            let termctx = TermContext::new(sbc, scope);
+
+            // Split off the architecture in `entity <name>(<arch>)`.
+            let mut unit_name = name.clone();
+            let arch_name = match unit_name.parts.last() {
+                Some(&ast::NamePart::Call(ref elems))
+                    if target == Some(ast::InstTarget::Entity) && elems.value.len() == 1 =>
+                {
+                    match primary_name(&elems.value[0].expr) {
+                        Some(ast::PrimaryNameKind::Ident(n))
+                            if elems.value[0].choices.value.is_empty() =>
+                        {
+                            Some(Spanned::new(n, elems.value[0].span))
+                        }
+                        _ => None,
+                    }
+                }
+                _ => None,
+            };
+            if arch_name.is_some() {
+                unit_name.parts.pop();
+            }
+
+            // Resolve the entity the instance is bound to, and the names by
+            // which its generics and ports are associated.
+            let unit = termctx.termify_compound_name(&unit_name)?;
+            let unit_span = unit.span;
+            let def = termctx.term_to_ident(unit)?;
+            let (entity, generic_formals, formals) = match (target, def.value) {
+                (Some(ast::InstTarget::Entity), Def::Entity(entity)) => {
+                    let formals = sbc
+                        .hir(entity)?
+                        .ports
+                        .iter()
+                        .map(|&port| Ok(sbc.hir(port)?.name.value))
+                        .collect::<Result<Vec<_>>>()?;
+                    (entity, generic_names(sbc, entity, unit_span)?, formals)
+                }
+                (Some(ast::InstTarget::Comp), Def::Comp(comp)) | (None, Def::Comp(comp)) => {
+                    let decl = sbc.ast(comp).1;
+                    let lib = match enclosing_arch(sbc, scope)? {
+                        Some(arch) => sbc.ast(arch).0,
+                        None => {
+                            sbc.emit(
+                                DiagBuilder2::bug(
+                                    "component instantiation outside of an architecture not \
+                                     implemented",
+                                )
+                                .span(stmt.human_span()),
+                            );
+                            return Err(Error::Reported);
+                        }
+                    };
+                    let entity = sbc
+                        .defs(ScopeRef::Lib(lib))?
+                        .get(&decl.name.value.into())
+                        .and_then(|defs| {
+                            defs.iter().rev().find_map(|def| match def.value {
+                                Def::Entity(id) => Some(id),
+                                _ => None,
+                            })
+                        });
+                    let entity = match entity {
+                        Some(entity) => entity,
+                        None => {
+                            sbc.emit(
+                                DiagBuilder2::error(format!(
+                                    "no entity `{}` to bind component `{}` to",
+                                    decl.name.value, decl.name.value
+                                ))
+                                .span(unit_span)
+                                .add_note("Component declared here:")
+                                .span(decl.name.span),
+                            );
+                            return Err(Error::Reported);
+                        }
+                    };
+                    let intf_names = |decls: &'ast Option<Spanned<Vec<ast::IntfDecl>>>| {
+                        decls
+                            .iter()
+                            .flat_map(|decls| decls.value.iter())
+                            .flat_map(|decl| match *decl {
+                                ast::IntfDecl::ObjDecl(ref decl) => decl.names.as_slice(),
+                                _ => &[],
+                            })
+                            .map(|ident| ident.name)
+                            .collect()
+                    };
+                    (entity, intf_names(&decl.generics), intf_names(&decl.ports))
+                }
+                _ => {
+                    sbc.emit(
+                        DiagBuilder2::error(format!(
+                            "`{}` is not {}",
+                            unit_span.extract(),
+                            match target {
+                                Some(ast::InstTarget::Entity) => "an entity",
+                                _ => "a component",
+                            }
+                        ))
+                        .span(unit_span)
+                        .add_note(format!("`{}` was defined here:", unit_span.extract()))
+                        .span(def.span),
+                    );
+                    return Err(Error::Reported);
+                }
+            };
+            let entity_hir = sbc.hir(entity)?;
+
+            // Determine the architecture, which is the most recently analyzed
+            // one unless named explicitly.
+            let archs = &sbc.archs(entity_hir.lib)?.by_entity[&entity];
+            let arch = match arch_name {
+                Some(name) => match archs.by_name.get(&name.value) {
+                    Some(&arch) => arch,
+                    None => {
+                        sbc.emit(
+                            DiagBuilder2::error(format!(
+                                "entity `{}` has no architecture `{}`",
+                                entity_hir.name.value, name.value
+                            ))
+                            .span(name.span),
+                        );
+                        return Err(Error::Reported);
+                    }
+                },
+                None => match archs.ordered.last() {
+                    Some(&arch) => arch,
+                    None => {
+                        sbc.emit(
+                            DiagBuilder2::error(format!(
+                                "entity `{}` has no architecture",
+                                entity_hir.name.value
+                            ))
+                            .span(unit_span),
+                        );
+                        return Err(Error::Reported);
+                    }
+                },
+            };
+
+            // Associate the actuals with the generics of the entity, and
+            // specialize the architecture for their values. Generics that are
+            // not associated keep their default.
+            let generic_names = generic_names(sbc, entity, unit_span)?;
+            let generic_elems = associate(
+                sbc,
+                "generic",
+                unit_span,
+                entity_hir,
+                &generic_formals,
+                &generic_names,
+                generics.iter().flat_map(|g| g.value.iter()),
+            )?;
+            let mut values = vec![];
+            for (&generic, elem) in entity_hir.generics.iter().zip(generic_elems) {
+                let (id, elem) = match (generic, elem) {
+                    (GenericRef::Const(id), Some(elem)) if elem.expr.data != ast::OpenExpr => {
+                        (id, elem)
+                    }
+                    _ => {
+                        values.push(None);
+                        continue;
+                    }
+                };
+                let expr = AddContext::new(sbc, scope).add_expr(&elem.expr)?;
+                sbc.set_type_context(expr, sbc.ty(id)?);
+                values.push(Some((sbc.const_value(expr)?, elem.expr.span)));
+            }
+            let (entity, arch) = if values.iter().any(Option::is_some) {
+                let arch = sbc.specialize_arch(arch, values)?;
+                (sbc.hir(arch)?.entity, arch)
+            } else {
+                (entity, arch)
+            };
+
+            // Associate the actuals with the ports of the entity. Ports that
+            // are not associated are left open.
+            let port_names = entity_hir
+                .ports
+                .iter()
+                .map(|&port| Ok(sbc.hir(port)?.name.value))
+                .collect::<Result<Vec<_>>>()?;
+            let port_elems = associate(
+                sbc,
+                "port",
+                unit_span,
+                entity_hir,
+                &formals,
+                &port_names,
+                ports.iter().flat_map(|p| p.value.iter()),
+            )?;
+            let actuals = port_elems
+                .into_iter()
+                .map(|elem| match elem {
+                    Some(elem) if elem.expr.data != ast::OpenExpr => Ok(Some(
+                        termctx.term_to_signal(termctx.termify_expr(&elem.expr)?)?,
+                    )),
+                    _ => Ok(None),
+                })
+                .collect::<Result<Vec<_>>>()?;
+
+            Ok(hir::Stmt {
+                parent: scope,
+                span: stmt.span,
+                label: stmt.label,
+                stmt: hir::CompInstStmt {
+                    entity: entity,
+                    arch: arch,
+                    ports: actuals,
+                },
+            })
+        }));
+        mk.typeck(Box::new(move |tyc| {
+            let hir = tyc.ctx.lazy_hir(id)?;
+            let entity = tyc.ctx.hir(hir.stmt.entity)?;
+            for (&port, actual) in entity.ports.iter().zip(hir.stmt.ports.iter()) {
+                if let Some(actual) = *actual {
+                    let exp = tyc.ctx.ty(SignalRef::from(port))?;
+                    let act = tyc.ctx.ty(actual.value)?;
+                    tyc.must_match(exp, act, actual.span);
+                }
+            }
+            Ok(())
+        }));
+        Ok(mk.finish())
+    }
+
+    /// Add a block statement.
+    ///
+    /// See IEEE 1076-2008 section 11.2. The guard condition, if any, is lowered
+    /// in the enclosing scope and defines the implicit `GUARD` signal visible
+    /// within the block.
+    pub fn add_block_stmt(&self, stmt: &'ast ast::Stmt) -> Result<BlockStmtRef> {
+        let (mk, id, scope) = self.make::<BlockStmtRef>(stmt.span);
+        let (guard, decls, stmts) = match stmt.data {
+            ast::BlockStmt {
+                ref guard,
+                ref decls,
+                ref stmts,
+            } => (guard, decls, stmts),
+            _ => unreachable!(),
+        };
+        mk.lower_to_hir(Box::new(move |sbc| {
+            let guard = match *guard {
+                Some(ref guard) => {
+                    let expr = AddContext::new(sbc, scope).add_expr(guard)?;
+                    sbc.set_type_context(expr, sbc.builtin_boolean_type());
+                    Some(expr)
+                }
+                None => None,
+            };
+            let block = GenBlockRef::alloc();
+            let decls = sbc.unpack_block_decls(block.into(), decls, "a block statement");
+            let stmts = sbc.unpack_concurrent_stmts(block.into(), stmts, "a block statement");
+            let (decls, stmts) = (decls?, stmts?);
+            sbc.set_hir(
+                block,
+                sbc.sb.arenas.hir.gen_block.alloc(hir::GenBlock {
+                    parent: scope,
+                    span: stmt.span,
+                    label: stmt.label,
+                    param: None,
+                    guard: guard,
+                    decls: decls,
+                    stmts: stmts,
+                }),
+            );
+            Ok(hir::Stmt {
+                parent: scope,
+                span: stmt.span,
+                label: stmt.label,
+                stmt: hir::BlockStmt { block: block },
+            })
+        }));
+        mk.typeck(Box::new(move |tyc| {
+            tyc.typeck(tyc.ctx.lazy_hir(id)?.stmt.block);
+            Ok(())
+        }));
+        Ok(mk.finish())
+    }
+
+    /// Add a for-generate statement.
+    ///
+    /// See IEEE 1076-2008 section 11.8. The range is evaluated when the
+    /// statement is lowered, and the body is lowered once for each value of
+    /// the generate parameter.
+    pub fn add_for_gen_stmt(&self, stmt: &'ast ast::Stmt) -> Result<ForGenStmtRef> {
+        let (mk, id, scope) = self.make::<ForGenStmtRef>(stmt.span);
+        let (param, range, body) = match stmt.data {
+            ast::ForGenStmt {
+                param,
+                ref range,
+                ref body,
+            } => (param, range, body),
+            _ => unreachable!(),
+        };
+        mk.lower_to_hir(Box::new(move |sbc| {
+            let ctx = AddContext::new(sbc, scope);
+            let range = ctx.add_discrete_range(range)?;
+            let tyc = TypeckContext::new(sbc);
+            let ty = match *sbc.deref_named_type(tyc.type_from_discrete_range(range.as_ref())?)? {
+                Ty::Int(ref ty) => ty.clone(),
+                ref ty => {
+                    sbc.emit(
+                        DiagBuilder2::error(format!(
+                            "`{}` is not an integer range",
+                            range.span.extract()
+                        ))
+                        .span(range.span)
+                        .add_note(format!("Type of the range is {}", ty)),
+                    );
+                    return Err(Error::Reported);
+                }
+            };
+
+            // Lower the body once for each value of the parameter.
+            let step = match ty.dir {
+                hir::Dir::To => BigInt::one(),
+                hir::Dir::Downto => -BigInt::one(),
+            };
+            let mut value = ty.left_bound.clone();
+            let mut blocks = vec![];
+            while match ty.dir {
+                hir::Dir::To => value <= ty.right_bound,
+                hir::Dir::Downto => value >= ty.right_bound,
+            } {
+                let param = hir::GenParam {
+                    name: param,
+                    value: ConstInt::new(Some(ty.clone()), value.clone()),
+                };
+                blocks.push(add_gen_block(sbc, scope, stmt, body, Some(param))?);
+                value = value + &step;
+            }
+            Ok(hir::Stmt {
+                parent: scope,
+                span: stmt.span,
+                label: stmt.label,
+                stmt: hir::ForGenStmt {
+                    param: param,
+                    blocks: blocks,
+                },
+            })
+        }));
+        mk.typeck(Box::new(move |tyc| {
+            let hir = tyc.ctx.lazy_hir(id)?;
+            for &block in &hir.stmt.blocks {
+                tyc.typeck(block);
+            }
+            Ok(())
+        }));
+        Ok(mk.finish())
+    }
+
+    /// Add an if-generate statement.
+    ///
+    /// See IEEE 1076-2008 section 11.8. The conditions are evaluated in order
+    /// when the statement is lowered, and only the body of the first
+    /// alternative whose condition holds is lowered.
+    pub fn add_if_gen_stmt(&self, stmt: &'ast ast::Stmt) -> Result<IfGenStmtRef> {
+        let (mk, id, scope) = self.make::<IfGenStmtRef>(stmt.span);
+        let (conds, alt) = match stmt.data {
+            ast::IfGenStmt { ref conds, ref alt } => (conds, alt),
+            _ => unreachable!(),
+        };
+        mk.lower_to_hir(Box::new(move |sbc| {
+            let ctx = AddContext::new(sbc, scope);
+            let mut selected = None;
+            for &(ref cond, ref body) in conds {
+                let expr = ctx.add_expr(cond)?;
+                sbc.set_type_context(expr, sbc.builtin_boolean_type());
+                match *sbc.const_value(expr)? {
+                    Const::Enum(ref k) if k.decl == BOOLEAN_TYPE.id => {
+                        if k.index == 1 {
+                            selected = Some(body);
+                            break;
+                        }
+                    }
+                    ref k => {
+                        sbc.emit(
+                            DiagBuilder2::error(format!(
+                                "condition `{}` is not a boolean",
+                                cond.span.extract()
+                            ))
+                            .span(cond.span)
+                            .add_note(format!("Value of the condition is {}", k)),
+                        );
+                        return Err(Error::Reported);
+                    }
+                }
+            }
+            let body = selected.or(alt.as_ref());
+            Ok(hir::Stmt {
+                parent: scope,
+                span: stmt.span,
+                label: stmt.label,
+                stmt: hir::IfGenStmt {
+                    block: body
+                        .map(|body| add_gen_block(sbc, scope, stmt, body, None))
+                        .transpose()?,
+                },
+            })
+        }));
+        mk.typeck(Box::new(move |tyc| {
+            if let Some(block) = tyc.ctx.lazy_hir(id)?.stmt.block {
+                tyc.typeck(block);
+            }
+            Ok(())
+        }));
+        Ok(mk.finish())
+    }
+
+    /// Add a case-generate statement.
+    ///
+    /// See IEEE 1076-2008 section 11.8. The expression is evaluated when the
+    /// statement is lowered, and only the body of the alternative whose
+    /// choices cover its value is lowered.
+    pub fn add_case_gen_stmt(&self, stmt: &'ast ast::Stmt) -> Result<CaseGenStmtRef> {
+        let (mk, id, scope) = self.make::<CaseGenStmtRef>(stmt.span);
+        let (switch, cases) = match stmt.data {
+            ast::CaseGenStmt {
+                ref switch,
+                ref cases,
+            } => (switch, cases),
+            _ => unreachable!(),
+        };
+        mk.lower_to_hir(Box::new(move |sbc| {
+            let ctx = AddContext::new(sbc, scope);
+            let tyc = TypeckContext::new(sbc);
+            let expr = ctx.add_expr(switch)?;
+            let ty = sbc.lazy_typeval(expr)?;
+            let value = sbc.const_value(expr)?;
+            let mut selected = None;
+            let mut others = None;
+            'outer: for &(ref choices, ref body) in cases {
+                let choices = ctx.add_choices(choices.as_ref().map(|c| c.iter()))?;
+                for choice in &choices.value {
+                    let covered = match choice.value {
+                        hir::Choice::Expr(choice) => {
+                            sbc.set_type_context(choice, ty);
+                            const_eq(sbc.const_value(choice)?, value)
+                        }
+                        hir::Choice::DiscreteRange(ref range) => {
+                            let range = Spanned::new(range, choice.span);
+                            let range = tyc.type_from_discrete_range(range)?;
+                            match (sbc.deref_named_type(range)?, value) {
+                                (&Ty::Int(ref range), &Const::Int(ref k)) => {
+                                    let (lo, hi) = match range.dir {
+                                        hir::Dir::To => (&range.left_bound, &range.right_bound),
+                                        hir::Dir::Downto => (&range.right_bound, &range.left_bound),
+                                    };
+                                    *lo <= k.value && k.value <= *hi
+                                }
+                                _ => false,
+                            }
+                        }
+                        hir::Choice::Others => {
+                            others = Some(body);
+                            false
+                        }
+                        hir::Choice::Element(_) => false,
+                    };
+                    if covered {
+                        selected = Some(body);
+                        break 'outer;
+                    }
+                }
+            }
+            let body = selected.or(others);
+            if body.is_none() {
+                sbc.emit(
+                    DiagBuilder2::error(format!(
+                        "no alternative covers `{}`",
+                        switch.span.extract()
+                    ))
+                    .span(switch.span)
+                    .add_note(format!("Value of the expression is {}", value)),
+                );
+                return Err(Error::Reported);
+            }
+            Ok(hir::Stmt {
+                parent: scope,
+                span: stmt.span,
+                label: stmt.label,
+                stmt: hir::CaseGenStmt {
+                    block: body
+                        .map(|body| add_gen_block(sbc, scope, stmt, body, None))
+                        .transpose()?,
+                },
+            })
+        }));
+        mk.typeck(Box::new(move |tyc| {
+            if let Some(block) = tyc.ctx.lazy_hir(id)?.stmt.block {
+                tyc.typeck(block);
+            }
+            Ok(())
+        }));
+        Ok(mk.finish())
+    }
+
+    /// Add the waveforms of a signal assignment.
+    ///
+    /// See IEEE 1076-2008 section 10.5. This is shared by sequential and
+    /// concurrent signal assignments.
+    pub(super) fn add_sig_assign_kind(
+        &self,
+        dm: &'ast Option<Spanned<ast::DelayMech>>,
+        waves: &SigAssignWaves<'ast>,
+        target: TypedNodeRef,
+    ) -> Result<hir::SigAssignKind> {
+        let dm = self.add_delay_mechanism(dm)?;
+        Ok(match *waves {
+            SigAssignWaves::Simple(wave) => {
+                hir::SigAssignKind::SimpleWave(dm, self.add_waveform(wave, target)?)
+            }
+            SigAssignWaves::Cond(ref conds, otherwise) => {
+                let when = conds
+                    .iter()
+                    .map(|&(wave, cond)| {
+                        let wave = self.add_waveform(wave, target);
+                        let cond = self.add_expr(cond);
+                        let (wave, cond) = (wave?, cond?);
+                        self.ctx
+                            .set_type_context(cond, self.ctx.builtin_boolean_type());
+                        Ok((wave, cond))
+                    })
+                    .collect::<Vec<Result<_>>>()
+                    .into_iter()
+                    .collect::<Result<Vec<_>>>();
+                let other = match otherwise {
+                    Some(wave) => Some(self.add_waveform(wave, target)?),
+                    None => None,
+                };
+                hir::SigAssignKind::CondWave(
+                    dm,
+                    hir::Cond {
+                        when: when?,
+                        other: other,
+                    },
+                )
+            }
+            SigAssignWaves::Sel {
+                matching,
+                disc,
+                waves,
+            } => {
+                let disc = self.add_expr(disc);
+                let when = waves
+                    .iter()
+                    .map(|&ast::SelectWave(ref wave, ref choices)| {
+                        let wave = self.add_waveform(wave, target);
+                        let choices = self.add_choices(choices.as_ref().map(|c| c.iter()));
+                        Ok((wave?, choices?))
+                    })
+                    .collect::<Vec<Result<_>>>()
+                    .into_iter()
+                    .collect::<Result<Vec<_>>>();
+                hir::SigAssignKind::SelWave(
+                    dm,
+                    hir::Sel {
+                        matching: matching,
+                        disc: disc?,
+                        when: when?,
+                    },
+                )
+            }
+        })
+    }
+
+    /// Add the delay mechanism of a signal assignment.
+    ///
+    /// See IEEE 1076-2008 section 10.5.2.1. If no mechanism is specified,
+    /// inertial is assumed.
+    pub fn add_delay_mechanism(
+        &self,
+        dm: &'ast Option<Spanned<ast::DelayMech>>,
+    ) -> Result<hir::DelayMechanism> {
+        Ok(match dm.as_ref().map(|dm| &dm.value) {
+            Some(ast::DelayMech::Transport) => hir::DelayMechanism::Transport,
+            Some(ast::DelayMech::Inertial) | None => hir::DelayMechanism::Inertial,
+            Some(ast::DelayMech::InertialReject(ref expr)) => {
+                let expr = self.add_expr(expr)?;
+                self.ctx
+                    .set_type_context(expr, self.ctx.builtin_time_type());
+                hir::DelayMechanism::RejectInertial(expr)
+            }
+        })
+    }
+
+    /// Add a waveform assigned to a signal.
+    ///
+    /// See IEEE 1076-2008 section 10.5.2.1. The values are expected to be of
+    /// the signal's type.
+    pub fn add_waveform(
+        &self,
+        wave: &'ast ast::Wave,
+        target: TypedNodeRef,
+    ) -> Result<hir::Waveform> {
+        wave.elems
+            .iter()
+            .flat_map(|elems| elems.iter())
+            .map(|&(ref value, ref after)| {
+                let value = match value.data {
+                    ast::NullExpr => None,
+                    _ => {
+                        let expr = self.add_expr(value)?;
+                        self.ctx.set_type_context(expr, TypeCtx::TypeOf(target));
+                        Some(expr)
+                    }
+                };
+                let after = self.add_optional(after, AddContext::add_expr)?;
+                self.ctx
+                    .set_type_context_optional(after, self.ctx.builtin_time_type());
+                Ok(hir::WaveElem {
+                    value: value,
+                    after: after,
+                })
+            })
+            .collect()
+    }
+}
+
+/// The waveforms of a signal assignment.
+pub(super) enum SigAssignWaves<'ast> {
+    /// A single waveform.
+    Simple(&'ast ast::Wave),
+    /// Waveform-condition pairs, and an optional unconditional waveform.
+    Cond(
+        Vec<(&'ast ast::Wave, &'ast ast::Expr)>,
+        Option<&'ast ast::Wave>,
+    ),
+    /// Waveforms selected by the value of an expression.
+    Sel {
+        matching: bool,
+        disc: &'ast ast::Expr,
+        waves: &'ast [ast::SelectWave],
+    },
+}
+
+/// Determine the names of the generics of an entity.
+///
+/// Only generic constants are supported for now.
+fn generic_names(sbc: &ScoreContext, entity: EntityRef, span: Span) -> Result<Vec<Name>> {
+    sbc.hir(entity)?
+        .generics
+        .iter()
+        .map(|&generic| match generic {
+            GenericRef::Const(id) => Ok(sbc.ast(id).3.name),
+            _ => {
+                sbc.emit(
+                    DiagBuilder2::bug("generic types, subprograms, and packages not implemented")
+                        .span(span),
+                );
+                Err(Error::Reported)
+            }
+        })
+        .collect()
+}
+
+/// Associate the elements of a generic or port map with the interface
+/// objects of an entity.
+///
+/// Positional elements are associated with the formal at their position in
+/// `positional`, which lists the generics or ports of the entity or of the
+/// component that is instantiated. Named elements are associated with the
+/// formal they name. Returns the element associated with each of `names`, if
+/// any. See IEEE 1076-2008 section 6.5.7.
+fn associate<'ast>(
+    sbc: &ScoreContext,
+    kind: &str,
+    unit_span: Span,
+    entity: &hir::Entity,
+    positional: &[Name],
+    names: &[Name],
+    elems: impl Iterator<Item = &'ast ast::ParenElem>,
+) -> Result<Vec<Option<&'ast ast::ParenElem>>> {
+    let mut associated = vec![None; names.len()];
+    for (index, elem) in elems.enumerate() {
+        let formal = match (elem.choices.value.as_slice(), positional.get(index)) {
+            (&[], Some(&name)) => Spanned::new(name, elem.span),
+            (&[], None) => {
+                sbc.emit(
+                    DiagBuilder2::error(format!(
+                        "`{}` has no {} at position {}",
+                        unit_span.extract(),
+                        kind,
+                        index + 1
+                    ))
+                    .span(elem.span),
+                );
+                return Err(Error::Reported);
+            }
+            (&[ref formal], _) => match primary_name(formal) {
+                Some(ast::PrimaryNameKind::Ident(name)) => Spanned::new(name, formal.span),
+                _ => {
+                    sbc.emit(
+                        DiagBuilder2::error(format!(
+                            "`{}` is not a valid formal {}",
+                            formal.span.extract(),
+                            kind
+                        ))
+                        .span(formal.span),
+                    );
+                    return Err(Error::Reported);
+                }
+            },
+            _ => {
+                sbc.emit(
+                    DiagBuilder2::error(
+                        "formal part of association element must be exactly one name",
+                    )
+                    .span(elem.span),
+                );
+                return Err(Error::Reported);
+            }
+        };
+        let index = match names.iter().position(|&n| n == formal.value) {
+            Some(index) => index,
+            None => {
+                sbc.emit(
+                    DiagBuilder2::error(format!(
+                        "entity `{}` has no {} `{}`",
+                        entity.name.value, kind, formal.value
+                    ))
+                    .span(formal.span)
+                    .add_note("Entity declared here:")
+                    .span(entity.name.span),
+                );
+                return Err(Error::Reported);
+            }
+        };
+        if associated[index].is_some() {
+            sbc.emit(
+                DiagBuilder2::error(format!(
+                    "{} `{}` is associated more than once",
+                    kind, formal.value
+                ))
+                .span(elem.span),
+            );
+            return Err(Error::Reported);
+        }
+        associated[index] = Some(elem);
+    }
+    Ok(associated)
+}
+
+/// Lower the body of a generate statement to a generated block.
+fn add_gen_block<'ast>(
+    sbc: &ScoreContext<'_, '_, 'ast, '_>,
+    scope: ScopeRef,
+    stmt: &'ast ast::Stmt,
+    body: &'ast ast::GenBody,
+    param: Option<hir::GenParam>,
+) -> Result<GenBlockRef> {
+    let block = GenBlockRef::alloc();
+    let decls = sbc.unpack_block_decls(block.into(), &body.decls, "a generate statement");
+    let stmts = sbc.unpack_concurrent_stmts(block.into(), &body.stmts, "a generate statement");
+    let (decls, stmts) = (decls?, stmts?);
+    sbc.set_hir(
+        block,
+        sbc.sb.arenas.hir.gen_block.alloc(hir::GenBlock {
+            parent: scope,
+            span: body.span,
+            label: stmt.label,
+            param: param,
+            guard: None,
+            decls: decls,
+            stmts: stmts,
+        }),
+    );
+    Ok(block)
+}
+
+/// Resolve the implicit `GUARD` signal controlling a guarded assignment.
+///
+/// See IEEE 1076-2008 section 11.6. The signal is declared by an enclosing
+/// block statement with a guard condition.
+fn guard_signal(sbc: &ScoreContext, scope: ScopeRef, stmt: &ast::Stmt) -> Result<SignalRef> {
+    let name = get_name_table().intern("GUARD", false);
+    let defs = sbc.resolve_name(Spanned::new(name.into(), stmt.span), scope, false, true)?;
+    match defs.last() {
+        Some(&Spanned {
+            value: Def::Signal(sig),
+            ..
+        }) => Ok(sig),
+        _ => {
+            sbc.emit(
+                DiagBuilder2::error("guarded assignment outside of a guarded block")
+                    .span(stmt.human_span())
+                    .add_note(
+                        "Guarded assignments must appear within a block statement that has a \
+                         guard condition. See IEEE 1076-2008 section 11.6.",
+                    ),
+            );
+            Err(Error::Reported)
+        }
+    }
+}
+
+/// Check whether two constant integers or enumeration literals are equal.
+fn const_eq(a: &Const, b: &Const) -> bool {
+    match (a, b) {
+        (&Const::Int(ref a), &Const::Int(ref b)) => a.value == b.value,
+        (&Const::Enum(ref a), &Const::Enum(ref b)) => a == b,
+        _ => false,
+    }
+}
+
+/// Determine the architecture a scope is nested in, looking through generated
+/// blocks.
+fn enclosing_arch(sbc: &ScoreContext, mut scope: ScopeRef) -> Result<Option<ArchRef>> {
+    loop {
+        match scope {
+            ScopeRef::Arch(arch) => return Ok(Some(arch)),
+            ScopeRef::GenBlock(block) => scope = sbc.existing_hir(block)?.parent,
+            _ => return Ok(None),
+        }
+    }
+}
+
+/// Determine the value of an expression which is the literal `true` or
+/// `false`.
+fn bool_literal(expr: &ast::Expr) -> Option<bool> {
+    match primary_name(expr)? {
+        ast::PrimaryNameKind::Ident(name) => match name.as_str().to_lowercase().as_str() {
+            "true" => Some(true),
+            "false" => Some(false),
+            _ => None,
+        },
+        _ => None,
+    }
+}
+
+/// Determine the text of an expression which is a string literal.
+pub(super) fn string_literal(expr: &ast::Expr) -> Option<Name> {
+    match primary_name(expr)? {
+        ast::PrimaryNameKind::String(text) => Some(text),
+        _ => None,
+    }
+}
+
+/// Determine the primary name of an expression which is a simple name.
+fn primary_name(expr: &ast::Expr) -> Option<ast::PrimaryNameKind> {
+    match expr.data {
+        ast::NameExpr(ref name) if name.parts.is_empty() => Some(name.primary.kind),
+        _ => None,
+    }
+}
@@ -6,13 +6,14 @@ use std::collections::HashMap;
 
 use crate::common::errors::*;
 use crate::common::name::Name;
-use crate::common::score::Result;
+use crate::common::score::{Error, Result};
 use crate::common::source::{Span, Spanned};
 use crate::common::Verbosity;
 
 use crate::add_ctx::AddContext;
 use crate::hir;
 use crate::make_ctx::MakeContext;
+use crate::op::BinaryOp;
 use crate::overload_resolver::*;
 use crate::score::*;
 use crate::syntax::ast;
@@ -173,7 +174,7 @@ impl<'sbc, 'lazy, 'sb, 'ast, 'ctx> AddContext<'sbc, 'lazy, 'sb, 'ast, 'ctx> {
             );
             debugln!("Aggregate kind is {:?}", hir.named);
             debugln!("Type context is {:?}", tyctx);
-            Err(())
+            Err(Error::Reported)
         }));
     }
 }
@@ -187,9 +188,17 @@ pub fn typeval_expr<'sbc, 'lazy: 'sbc, 'sb: 'lazy, 'ast: 'sb, 'ctx: 'sb>(
 ) -> Result<&'ctx Ty> {
     match hir.data {
         hir::ExprData::ConstName(id) => tyc.ctx.lazy_typeval(id),
+        hir::ExprData::GenParamName(id) => {
+            let value = &tyc.ctx.existing_hir(id)?.param.as_ref().unwrap().value;
+            match value.ty {
+                Some(ref ty) => Ok(tyc.ctx.intern_ty(ty.clone())),
+                None => Ok(tyc.ctx.intern_ty(Ty::UniversalInt)),
+            }
+        }
         hir::ExprData::SignalName(id) => tyc.ctx.ty(id),
         hir::ExprData::VarName(id) => tyc.ctx.lazy_typeval(id),
         hir::ExprData::FileName(id) => tyc.ctx.lazy_typeval(id),
+        hir::ExprData::IntfConstName(id) => tyc.ctx.ty(id),
         hir::ExprData::EnumName(ref defs) => {
             // Enums are generally overloaded. The type context is needed to
             // pick one of the available variants.
@@ -220,7 +229,7 @@ pub fn typeval_expr<'sbc, 'lazy: 'sbc, 'sb: 'lazy, 'ast: 'sb, 'ctx: 'sb>(
                         DiagBuilder2::error(format!("`{}` is ambiguous", hir.span.extract()))
                             .span(hir.span), // TODO: Show which definitions are available.
                     );
-                    Err(())
+                    Err(Error::Reported)
                 } else {
                     Ok(tyc.ctx.intern_ty(EnumTy::new(filtered[0])))
                 }
@@ -276,7 +285,7 @@ pub fn typeval_expr<'sbc, 'lazy: 'sbc, 'sb: 'lazy, 'ast: 'sb, 'ctx: 'sb>(
                         DiagBuilder2::error(format!("`{}` is ambiguous", hir.span.extract()))
                             .span(hir.span), // TODO: Show which definitions are available.
                     );
-                    return Err(());
+                    return Err(Error::Reported);
                 } else {
                     let index = ArrayIndex::Constrained(Box::new(index_ty));
                     Ok(tyc.ctx.intern_ty(ArrayTy::new(
@@ -301,7 +310,57 @@ pub fn typeval_expr<'sbc, 'lazy: 'sbc, 'sb: 'lazy, 'ast: 'sb, 'ctx: 'sb>(
             //     DiagBuilder2::error(format!("type of expression `{}` cannot be inferred from context", hir.span.extract()))
             //     .span(hir.span)
             // );
-            // Err(())
+            // Err(Error::Reported)
+        }
+        hir::ExprData::FloatLiteral(..) => Ok(tyc.ctx.intern_ty(Ty::UniversalReal)),
+        hir::ExprData::PhysicalLiteral(_, ref unit) => {
+            // The literal has the type which declares its unit.
+            let decl = unit.value.0;
+            if decl == TIME_TYPE.id {
+                return Ok(tyc.ctx.intern_ty(TIME_TYPE.named_ty()));
+            }
+            let name = tyc.ctx.lazy_hir(decl)?.name.value;
+            Ok(tyc.ctx.intern_ty(Ty::Named(name.into(), decl.into())))
+        }
+        hir::ExprData::BuiltinCall(_, ref args) => {
+            // All builtin functions take and return `REAL` values.
+            let ty = tyc.ctx.intern_ty(REAL_TYPE.named_ty());
+            for arg in &args.value {
+                if let hir::AssocActual::Expr(expr) = arg.actual.value {
+                    let expr_ty = tyc.lazy_typeval(expr)?;
+                    tyc.must_match(ty, expr_ty, arg.actual.span);
+                }
+            }
+            Ok(ty)
+        }
+        hir::ExprData::SubprogCall(ref subprog, ref args) => {
+            let subprog_ty = match *tyc.ctx.ty(subprog.value)? {
+                Ty::Subprog(ref ty) => ty,
+                _ => unreachable!(),
+            };
+            for (arg, param) in args.value.iter().zip(subprog_ty.args.iter()) {
+                if let hir::AssocActual::Expr(expr) = arg.actual.value {
+                    let expr_ty = tyc.lazy_typeval(expr)?;
+                    tyc.must_match(
+                        tyc.ctx.intern_ty(param.ty.clone()),
+                        expr_ty,
+                        arg.actual.span,
+                    );
+                }
+            }
+            match subprog_ty.ret {
+                Some(ref ret) => Ok(tyc.ctx.intern_ty((**ret).clone())),
+                None => {
+                    tyc.emit(
+                        DiagBuilder2::error(format!(
+                            "`{}` does not return a value",
+                            hir.span.extract()
+                        ))
+                        .span(hir.span),
+                    );
+                    Err(Error::Reported)
+                }
+            }
         }
         hir::ExprData::Qualified(ref tm, expr) => {
             let ty = tyc.ctx.intern_ty(Ty::Named(tm.span.into(), tm.value));
@@ -323,6 +382,66 @@ pub fn typeval_expr<'sbc, 'lazy: 'sbc, 'sb: 'lazy, 'ast: 'sb, 'ctx: 'sb>(
             tyc.must_cast(ty, expr_ty, tyc.ctx.span(expr).unwrap());
             Ok(ty)
         }
+        hir::ExprData::TypeAttrCall(ref tm, func, arg) => {
+            let ty = tyc.ctx.intern_ty(Ty::Named(tm.span.into(), tm.value));
+            let arg_ty = tyc.lazy_typeval(arg)?;
+            let arg_span = tyc.ctx.span(arg).unwrap();
+            match *tyc.ctx.deref_named_type(ty)? {
+                Ty::Int(_) | Ty::Enum(_) => (),
+                ref other => {
+                    tyc.emit(
+                        DiagBuilder2::error(format!(
+                            "`{}'{}` requires a discrete type",
+                            tm.span.extract(),
+                            func.value.name()
+                        ))
+                        .span(hir.span)
+                        .add_note(format!(
+                            "`{}` is {}",
+                            tm.span.extract(),
+                            other.kind_desc()
+                        )),
+                    );
+                    return Err(Error::Reported);
+                }
+            }
+
+            // `T'val` maps an integer to a value of the type, `T'pos` a value
+            // of the type to a universal integer, which takes on the integer
+            // type of the context. The others map a value of the type to
+            // another one. See IEEE 1076-2008 section 16.2.2.
+            match func.value {
+                hir::TypeAttrFn::Val => {
+                    if !tyc.ctx.deref_named_type(arg_ty)?.is_int() {
+                        tyc.emit(
+                            DiagBuilder2::error(format!(
+                                "argument of `{}'val` must be an integer",
+                                tm.span.extract()
+                            ))
+                            .span(arg_span)
+                            .add_note(format!(
+                                "`{}` is of type {}",
+                                arg_span.extract(),
+                                arg_ty
+                            )),
+                        );
+                        return Err(Error::Reported);
+                    }
+                    Ok(ty)
+                }
+                hir::TypeAttrFn::Pos => {
+                    tyc.must_match(ty, arg_ty, arg_span);
+                    match tyctx {
+                        Some(tyctx) if tyc.ctx.deref_named_type(tyctx)?.is_int() => Ok(tyctx),
+                        _ => Ok(tyc.ctx.intern_ty(Ty::UniversalInt)),
+                    }
+                }
+                _ => {
+                    tyc.must_match(ty, arg_ty, arg_span);
+                    Ok(ty)
+                }
+            }
+        }
         hir::ExprData::Aggregate(id) => {
             tyc.ctx
                 .set_type_context(id, TypeCtx::Inherit(expr_id.into()));
@@ -343,18 +462,66 @@ pub fn typeval_expr<'sbc, 'lazy: 'sbc, 'sb: 'lazy, 'ast: 'sb, 'ctx: 'sb>(
             // Resolve the overload.
             let def = resolve_overloads(tyc.ctx, defs, &req, hir.span)?;
             debugln!("unary operator `{}` resolved to {:?}", op.value, def);
-
-            tyc.emit(
-                DiagBuilder2::bug(format!(
-                    "typeval for unary operation `{}` not implemented",
-                    hir.span.extract()
-                ))
-                .span(hir.span),
-            );
-            debugln!("Defs are {:?}", defs);
-            Err(())
+            typeval_operator(tyc, def, hir.span)
         }
         hir::ExprData::Binary(op, ref defs, lhs, rhs) => {
+            let lhs_ty = tyc.lazy_typeval(lhs)?;
+            let rhs_ty = tyc.lazy_typeval(rhs)?;
+
+            // The relational operators are predefined for all integer types,
+            // with universal integers converted implicitly. See IEEE 1076-2008
+            // section 9.2.3.
+            if let BinaryOp::Rel(_) = op.value {
+                if are_int_types_compatible(tyc, lhs_ty, rhs_ty)? {
+                    return Ok(tyc.ctx.builtin_boolean_type());
+                }
+            }
+
+            // The relational and arithmetic operators are likewise predefined
+            // for all floating-point types, with universal reals converted
+            // implicitly. The exponent of `**` is an integer. See IEEE
+            // 1076-2008 sections 9.2.3 to 9.2.7.
+            match op.value {
+                BinaryOp::Rel(_)
+                | BinaryOp::Add
+                | BinaryOp::Sub
+                | BinaryOp::Mul
+                | BinaryOp::Div => {
+                    if let Some(ty) = common_real_type(tyc, lhs_ty, rhs_ty)? {
+                        return Ok(match op.value {
+                            BinaryOp::Rel(_) => tyc.ctx.builtin_boolean_type(),
+                            _ => ty,
+                        });
+                    }
+                }
+                BinaryOp::Pow => match *tyc.ctx.deref_named_type(lhs_ty)? {
+                    Ty::Real(_) | Ty::UniversalReal
+                        if are_int_types_compatible(tyc, rhs_ty, rhs_ty)? =>
+                    {
+                        return Ok(lhs_ty);
+                    }
+                    _ => (),
+                },
+                _ => (),
+            }
+
+            // The arithmetic operators are predefined for all integer types,
+            // again with universal integers converted implicitly. See IEEE
+            // 1076-2008 sections 9.2.5 and 9.2.7.
+            match op.value {
+                BinaryOp::Add
+                | BinaryOp::Sub
+                | BinaryOp::Mul
+                | BinaryOp::Div
+                | BinaryOp::Mod
+                | BinaryOp::Rem => {
+                    if let Some(ty) = common_int_type(tyc, lhs_ty, rhs_ty)? {
+                        return Ok(ty);
+                    }
+                }
+                _ => (),
+            }
+
             // Assemble an overload resolution requirement based on the
             // operator's types.
             let req = OverloadReq::Subprog(SignatureReq {
@@ -362,27 +529,54 @@ pub fn typeval_expr<'sbc, 'lazy: 'sbc, 'sb: 'lazy, 'ast: 'sb, 'ctx: 'sb>(
                     Some(tyctx) => TypeReq::One(tyctx),
                     None => TypeReq::Any,
                 },
-                positional: vec![
-                    TypeReq::One(tyc.lazy_typeval(lhs)?),
-                    TypeReq::One(tyc.lazy_typeval(rhs)?),
-                ],
+                positional: vec![TypeReq::One(lhs_ty), TypeReq::One(rhs_ty)],
                 named: HashMap::new(),
             });
 
             // Resolve the overload.
             let def = resolve_overloads(tyc.ctx, defs, &req, hir.span)?;
             debugln!("binary operator `{}` resolved to {:?}", op.value, def);
-
-            tyc.emit(
-                DiagBuilder2::bug(format!(
-                    "typeval for binary operation `{}` not implemented",
-                    hir.span.extract()
-                ))
-                .span(hir.span),
-            );
-            debugln!("Defs are {:?}", defs);
-            Err(())
+            typeval_operator(tyc, def, hir.span)
+        }
+        hir::ExprData::Select(prefix, name) => {
+            // Only records can be selected from in an expression. Selected
+            // names which denote declarations have been resolved before.
+            let prefix_ty = tyc.lazy_typeval(prefix)?;
+            let record_ty = match *tyc.ctx.deref_named_type(prefix_ty)? {
+                Ty::Record(ref ty) => ty,
+                _ => {
+                    let prefix_span = tyc.ctx.lazy_hir(prefix)?.span;
+                    tyc.emit(
+                        DiagBuilder2::error(format!("`{}` is not a record", prefix_span.extract()))
+                            .span(prefix_span)
+                            .add_note(format!(
+                                "`{}` is of type {}",
+                                prefix_span.extract(),
+                                prefix_ty
+                            )),
+                    );
+                    return Err(Error::Reported);
+                }
+            };
+            let index = match name.value {
+                ResolvableName::Ident(n) => record_ty.lookup.get(&n),
+                _ => None,
+            };
+            match index {
+                Some(&index) => Ok(record_ty.fields[index].1.as_ref()),
+                None => {
+                    tyc.emit(
+                        DiagBuilder2::error(format!(
+                            "`{}` is not a field of {}",
+                            name.value, prefix_ty
+                        ))
+                        .span(name.span),
+                    );
+                    Err(Error::Reported)
+                }
+            }
         }
+        hir::ExprData::Call(prefix, ref args) => typeval_index(tyc, hir, prefix, args),
         _ => {
             tyc.emit(
                 DiagBuilder2::bug(format!(
@@ -392,7 +586,220 @@ pub fn typeval_expr<'sbc, 'lazy: 'sbc, 'sb: 'lazy, 'ast: 'sb, 'ctx: 'sb>(
                 .span(hir.span),
             );
             debugln!("It is a {:#?}", hir.data);
-            Err(())
+            Err(Error::Reported)
+        }
+    }
+}
+
+/// Evaluate the type of an indexed or slice name, e.g. `a(3)` or
+/// `a(7 downto 4)`.
+///
+/// Function calls and casts have been resolved before, such that only arrays
+/// can be indexed here. See IEEE 1076-2008 sections 8.4 and 8.5.
+fn typeval_index<'sbc, 'lazy: 'sbc, 'sb: 'lazy, 'ast: 'sb, 'ctx: 'sb>(
+    tyc: &TypeckContext<'sbc, 'lazy, 'sb, 'ast, 'ctx>,
+    hir: &hir::Expr,
+    prefix: ExprRef,
+    args: &Spanned<hir::AssocList>,
+) -> Result<&'ctx Ty> {
+    let prefix_ty = tyc.lazy_typeval(prefix)?;
+    let array_ty = match *tyc.ctx.deref_named_type(prefix_ty)? {
+        Ty::Array(ref ty) => ty,
+        _ => {
+            let prefix_span = tyc.ctx.lazy_hir(prefix)?.span;
+            tyc.emit(
+                DiagBuilder2::error(format!("`{}` is not an array", prefix_span.extract()))
+                    .span(prefix_span)
+                    .add_note(format!(
+                        "`{}` is of type {}",
+                        prefix_span.extract(),
+                        prefix_ty
+                    )),
+            );
+            return Err(Error::Reported);
+        }
+    };
+    let indices = tyc.ctx.index_exprs(args)?;
+
+    // A single range selects a slice of a one-dimensional array.
+    if indices.len() == 1 {
+        if let hir::ExprData::Range(dir, lb, rb) = tyc.ctx.lazy_hir(indices[0])?.data {
+            let index_ty = match *tyc.ctx.deref_named_type(array_ty.indices[0].ty())? {
+                Ty::Int(ref ty) if array_ty.indices.len() == 1 => ty,
+                _ => {
+                    tyc.emit(
+                        DiagBuilder2::error(format!(
+                            "slices of array type `{}` are not supported",
+                            array_ty
+                        ))
+                        .span(hir.span)
+                        .add_note(
+                            "Only one-dimensional arrays with an integer index can be sliced",
+                        ),
+                    );
+                    return Err(Error::Reported);
+                }
+            };
+            typeck_index(tyc, lb, array_ty.indices[0].ty())?;
+            typeck_index(tyc, rb, array_ty.indices[0].ty())?;
+            if dir != index_ty.dir {
+                tyc.emit(
+                    DiagBuilder2::error(format!(
+                        "slice `{}` is in the opposite direction of the index of `{}`",
+                        hir.span.extract(),
+                        prefix_ty
+                    ))
+                    .span(hir.span),
+                );
+                return Err(Error::Reported);
+            }
+            let slice = IntTy::new(dir, tyc.ctx.index_value(lb)?, tyc.ctx.index_value(rb)?);
+            if let Ty::Int(ref slice) = slice.clone().maybe_null() {
+                for bound in &[&slice.left_bound, &slice.right_bound] {
+                    if tyc.ctx.array_index_position(array_ty, 0, bound)?.is_none() {
+                        tyc.emit(
+                            DiagBuilder2::error(format!(
+                                "slice `{}` is out of the index range of type {}",
+                                hir.span.extract(),
+                                prefix_ty
+                            ))
+                            .span(hir.span),
+                        );
+                        return Err(Error::Reported);
+                    }
+                }
+            }
+            return Ok(tyc.ctx.intern_ty(ArrayTy::new(
+                vec![ArrayIndex::Constrained(Box::new(slice.maybe_null()))],
+                array_ty.element.clone(),
+            )));
+        }
+    }
+
+    // Otherwise there is one index per dimension of the array.
+    if indices.len() != array_ty.indices.len() {
+        tyc.emit(
+            DiagBuilder2::error(format!(
+                "`{}` has {} indices, but array type `{}` has {}",
+                hir.span.extract(),
+                indices.len(),
+                array_ty,
+                array_ty.indices.len()
+            ))
+            .span(hir.span),
+        );
+        return Err(Error::Reported);
+    }
+    let mut had_fails = false;
+    for (&expr, index) in indices.iter().zip(array_ty.indices.iter()) {
+        if typeck_index(tyc, expr, index.ty()).is_err() {
+            had_fails = true;
+        }
+    }
+    if had_fails {
+        Err(Error::Reported)
+    } else {
+        Ok(array_ty.element.as_ref())
+    }
+}
+
+/// Check the type of an array index. The index type of an array is usually
+/// an anonymous subtype, such that integer indices may be of any integer type.
+fn typeck_index<'sbc, 'lazy: 'sbc, 'sb: 'lazy, 'ast: 'sb, 'ctx: 'sb>(
+    tyc: &TypeckContext<'sbc, 'lazy, 'sb, 'ast, 'ctx>,
+    expr: ExprRef,
+    index_ty: &'ctx Ty,
+) -> Result<()> {
+    tyc.ctx.set_type_context(expr, index_ty);
+    let ty = tyc.lazy_typeval(expr)?;
+    match (
+        tyc.ctx.deref_named_type(index_ty)?,
+        tyc.ctx.deref_named_type(ty)?,
+    ) {
+        (&Ty::Int(_), &Ty::Int(_)) => Ok(()),
+        _ if tyc.must_match(index_ty, ty, tyc.ctx.lazy_hir(expr)?.span) => Ok(()),
+        _ => Err(Error::Reported),
+    }
+}
+
+/// Check whether two types are integer types which can be operands of the
+/// same predefined operator, i.e. the same type or a universal integer.
+fn are_int_types_compatible<'sbc, 'lazy: 'sbc, 'sb: 'lazy, 'ast: 'sb, 'ctx: 'sb>(
+    tyc: &TypeckContext<'sbc, 'lazy, 'sb, 'ast, 'ctx>,
+    a: &'ctx Ty,
+    b: &'ctx Ty,
+) -> Result<bool> {
+    Ok(
+        match (tyc.ctx.deref_named_type(a)?, tyc.ctx.deref_named_type(b)?) {
+            (&Ty::Int(_), &Ty::Int(_)) => a == b,
+            (&Ty::Int(_), &Ty::UniversalInt)
+            | (&Ty::UniversalInt, &Ty::Int(_))
+            | (&Ty::UniversalInt, &Ty::UniversalInt) => true,
+            _ => false,
+        },
+    )
+}
+
+/// Determine the type of a predefined operator on two integer types, which
+/// must be the same type or a universal integer. Returns `None` if the types
+/// are not compatible.
+fn common_int_type<'sbc, 'lazy: 'sbc, 'sb: 'lazy, 'ast: 'sb, 'ctx: 'sb>(
+    tyc: &TypeckContext<'sbc, 'lazy, 'sb, 'ast, 'ctx>,
+    a: &'ctx Ty,
+    b: &'ctx Ty,
+) -> Result<Option<&'ctx Ty>> {
+    Ok(
+        match (tyc.ctx.deref_named_type(a)?, tyc.ctx.deref_named_type(b)?) {
+            (&Ty::Int(_), &Ty::Int(_)) if a == b => Some(a),
+            (&Ty::Int(_), &Ty::UniversalInt) | (&Ty::UniversalInt, &Ty::UniversalInt) => Some(a),
+            (&Ty::UniversalInt, &Ty::Int(_)) => Some(b),
+            _ => None,
+        },
+    )
+}
+
+/// Determine the type of a predefined operator on two floating-point types,
+/// which must be the same type or a universal real. Returns `None` if the
+/// types are not compatible.
+fn common_real_type<'sbc, 'lazy: 'sbc, 'sb: 'lazy, 'ast: 'sb, 'ctx: 'sb>(
+    tyc: &TypeckContext<'sbc, 'lazy, 'sb, 'ast, 'ctx>,
+    a: &'ctx Ty,
+    b: &'ctx Ty,
+) -> Result<Option<&'ctx Ty>> {
+    Ok(
+        match (tyc.ctx.deref_named_type(a)?, tyc.ctx.deref_named_type(b)?) {
+            (&Ty::Real(_), &Ty::Real(_)) if a == b => Some(a),
+            (&Ty::Real(_), &Ty::UniversalReal) | (&Ty::UniversalReal, &Ty::UniversalReal) => {
+                Some(a)
+            }
+            (&Ty::UniversalReal, &Ty::Real(_)) => Some(b),
+            _ => None,
+        },
+    )
+}
+
+/// Evaluate the type of an operator expression, given the operator it
+/// resolved to.
+fn typeval_operator<'sbc, 'lazy: 'sbc, 'sb: 'lazy, 'ast: 'sb, 'ctx: 'sb>(
+    tyc: &TypeckContext<'sbc, 'lazy, 'sb, 'ast, 'ctx>,
+    def: Spanned<Def>,
+    span: Span,
+) -> Result<&'ctx Ty> {
+    let ty = match def.value {
+        Def::BuiltinOp(id) => tyc.ctx.lazy_typeval(id)?,
+        Def::Subprog(id) => tyc.ctx.ty(SubprogRef::from(id))?,
+        _ => unreachable!(),
+    };
+    match *ty {
+        Ty::Subprog(SubprogTy {
+            ret: Some(ref ret), ..
+        }) => Ok(tyc.ctx.intern_ty((**ret).clone())),
+        _ => {
+            tyc.emit(
+                DiagBuilder2::error(format!("`{}` does not return a value", span.extract()))
+                    .span(span),
+            );
+            Err(Error::Reported)
         }
     }
 }
@@ -423,7 +830,7 @@ pub fn typeval_record_aggregate<'sbc, 'lazy: 'sbc, 'sb: 'lazy, 'ast: 'sb, 'ctx:
             ))
             .span(hir.span),
         );
-        return Err(());
+        return Err(Error::Reported);
     }
 
     // Build a correspondence map between the fields of the aggregate and the
@@ -486,7 +893,7 @@ pub fn typeval_record_aggregate<'sbc, 'lazy: 'sbc, 'sb: 'lazy, 'ast: 'sb, 'ctx:
                 DiagBuilder2::error("expected a record aggregate, found an array aggregate")
                     .span(hir.span),
             );
-            return Err(());
+            return Err(Error::Reported);
         }
     }
     if let Some(_others) = hir.others {
@@ -499,9 +906,30 @@ pub fn typeval_record_aggregate<'sbc, 'lazy: 'sbc, 'sb: 'lazy, 'ast: 'sb, 'ctx:
     }
     debugln!("aggregate: record type mapping {:?}", mapping);
 
+    // Make sure every field of the record is assigned.
+    for (index, &(name, _)) in record_ty.fields.iter().enumerate() {
+        if !mapping.contains_key(&index) {
+            tyc.emit(
+                DiagBuilder2::error(format!(
+                    "aggregate `{}` does not assign field `{}` of {}",
+                    hir.span.extract(),
+                    name,
+                    tyctx
+                ))
+                .span(hir.span)
+                .add_note(format!(
+                    "Add a value for `{}`, or an `others` choice. See IEEE 1076-2008 section \
+                     9.3.3.2.",
+                    name
+                )),
+            );
+            had_fails = true;
+        }
+    }
+
     // Forward the type context and check the type of elements.
     for (&type_index, &agg_index) in &mapping {
-        match (|| {
+        match (|| -> Result<()> {
             let ty = record_ty.fields[type_index].1.as_ref();
             match agg_index {
                 FieldIndex::Pos(index) => {
@@ -526,12 +954,12 @@ pub fn typeval_record_aggregate<'sbc, 'lazy: 'sbc, 'sb: 'lazy, 'ast: 'sb, 'ctx:
             Ok(())
         })() {
             Ok(()) => (),
-            Err(()) => had_fails = true,
+            Err(_) => had_fails = true,
         }
     }
 
     if had_fails {
-        Err(())
+        Err(Error::Reported)
     } else {
         Ok(tyctx)
     }
@@ -550,10 +978,7 @@ pub fn typeval_array_aggregate<'sbc, 'lazy: 'sbc, 'sb: 'lazy, 'ast: 'sb, 'ctx: '
     let (index, element) = if let Ty::Array(ref ty) = *tyctx_flat {
         let index = ty.indices[0].ty();
         let element = if ty.indices.len() > 1 {
-            tyc.ctx.intern_ty(ArrayTy::new(
-                ty.indices.iter().skip(1).cloned().collect(),
-                ty.element.clone(),
-            ))
+            tyc.ctx.intern_ty(ty.first_element())
         } else {
             ty.element.as_ref()
         };
@@ -565,14 +990,14 @@ pub fn typeval_array_aggregate<'sbc, 'lazy: 'sbc, 'sb: 'lazy, 'ast: 'sb, 'ctx: '
     // Forward the type context and check the index and element types.
     let mut had_fails = false;
     for &pos in &hir.positional {
-        match (|| {
+        match (|| -> Result<()> {
             tyc.ctx.set_type_context(pos.value, element);
             let ty = tyc.lazy_typeval(pos.value)?;
             tyc.must_match(element, ty, pos.span);
             Ok(())
         })() {
             Ok(()) => (),
-            Err(()) => had_fails = true,
+            Err(_) => had_fails = true,
         }
     }
     match hir.named {
@@ -581,7 +1006,7 @@ pub fn typeval_array_aggregate<'sbc, 'lazy: 'sbc, 'sb: 'lazy, 'ast: 'sb, 'ctx: '
             for field in fields {
                 match typeck_array_aggregate_element(tyc, field, index, element) {
                     Ok(()) => (),
-                    Err(()) => had_fails = true,
+                    Err(_) => had_fails = true,
                 }
             }
         }
@@ -590,22 +1015,22 @@ pub fn typeval_array_aggregate<'sbc, 'lazy: 'sbc, 'sb: 'lazy, 'ast: 'sb, 'ctx: '
                 DiagBuilder2::error("expected an array aggregate, found a record aggregate")
                     .span(hir.span),
             );
-            return Err(());
+            return Err(Error::Reported);
         }
     }
     if let Some(others) = hir.others {
-        match (|| {
+        match (|| -> Result<()> {
             tyc.ctx.set_type_context(others.value, element);
             let ty = tyc.lazy_typeval(others.value)?;
             tyc.must_match(element, ty, others.span);
             Ok(())
         })() {
             Ok(()) => (),
-            Err(()) => had_fails = true,
+            Err(_) => had_fails = true,
         }
     }
     if had_fails {
-        Err(())
+        Err(Error::Reported)
     } else {
         Ok(tyctx)
     }
@@ -622,14 +1047,14 @@ pub fn typeck_array_aggregate_element<'sbc, 'lazy: 'sbc, 'sb: 'lazy, 'ast: 'sb,
     for choice in &hir.value.0 {
         match typeck_array_aggregate_choice(tyc, choice, index_ty) {
             Ok(()) => (),
-            Err(()) => had_fails = true,
+            Err(_) => had_fails = true,
         }
     }
     tyc.ctx.set_type_context(hir.value.1.value, element_ty);
     let ty = tyc.lazy_typeval(hir.value.1.value)?;
     tyc.must_match(element_ty, ty, hir.span);
     if had_fails {
-        Err(())
+        Err(Error::Reported)
     } else {
         Ok(())
     }
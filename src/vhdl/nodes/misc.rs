@@ -42,6 +42,9 @@ impl<'sbc, 'lazy, 'sb, 'ast, 'ctx> AddContext<'sbc, 'lazy, 'sb, 'ast, 'ctx> {
             let inner = tyc
                 .ctx
                 .intern_ty(Ty::Named(hir.type_mark.span.into(), hir.type_mark.value));
+            if let Some(resol) = hir.resolution {
+                tyc.check_resolution_function(inner, resol)?;
+            }
             match hir.constraint {
                 None => Ok(inner),
                 Some(Spanned {
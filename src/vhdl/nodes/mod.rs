@@ -4,6 +4,7 @@
 
 #![deny(missing_docs)]
 
+mod conc_stmt;
 mod expr;
 mod misc;
 mod obj_decl;
@@ -6,15 +6,17 @@ use std::iter::FromIterator;
 
 use crate::common::errors::*;
 use crate::common::name::Name;
-use crate::common::score::Result;
+use crate::common::score::{Error, Result};
 use crate::common::source::Spanned;
 use crate::common::util::{HasDesc, HasSpan};
 
 use crate::add_ctx::AddContext;
 use crate::hir;
+use crate::nodes::conc_stmt::{string_literal, SigAssignWaves};
 use crate::score::*;
 use crate::syntax::ast;
 use crate::term::TermContext;
+use crate::ty::{SubprogTy, Ty};
 
 impl<'sbc, 'lazy, 'sb, 'ast, 'ctx> AddContext<'sbc, 'lazy, 'sb, 'ast, 'ctx> {
     /// Add multiple sequential statements.
@@ -28,7 +30,7 @@ impl<'sbc, 'lazy, 'sb, 'ast, 'ctx> AddContext<'sbc, 'lazy, 'sb, 'ast, 'ctx> {
             let iter = stmts.into_iter().filter_map(|stmt| {
                 match self.add_seq_stmt(stmt, container_name) {
                     Ok(i) => Some(i),
-                    Err(()) => {
+                    Err(_) => {
                         had_fails = true;
                         None
                     }
@@ -37,7 +39,7 @@ impl<'sbc, 'lazy, 'sb, 'ast, 'ctx> AddContext<'sbc, 'lazy, 'sb, 'ast, 'ctx> {
             iter.collect()
         };
         if had_fails {
-            Err(())
+            Err(Error::Reported)
         } else {
             Ok(result)
         }
@@ -86,7 +88,7 @@ impl<'sbc, 'lazy, 'sb, 'ast, 'ctx> AddContext<'sbc, 'lazy, 'sb, 'ast, 'ctx> {
                         container_name
                     )),
                 );
-                Err(())
+                Err(Error::Reported)
             }
         }
     }
@@ -145,6 +147,7 @@ impl<'sbc, 'lazy, 'sb, 'ast, 'ctx> AddContext<'sbc, 'lazy, 'sb, 'ast, 'ctx> {
         };
         mk.lower_to_hir(Box::new(move |sbc| {
             let ctx = AddContext::new(sbc, scope);
+            let report_text = report.as_ref().and_then(string_literal);
             let cond = ctx.add_expr(cond);
             let report = ctx.add_optional(report, AddContext::add_expr);
             let severity = ctx.add_optional(severity, AddContext::add_expr);
@@ -159,6 +162,7 @@ impl<'sbc, 'lazy, 'sb, 'ast, 'ctx> AddContext<'sbc, 'lazy, 'sb, 'ast, 'ctx> {
                 stmt: hir::AssertStmt {
                     cond: cond,
                     report: report,
+                    report_text: report_text,
                     severity: severity,
                 },
             })
@@ -185,6 +189,7 @@ impl<'sbc, 'lazy, 'sb, 'ast, 'ctx> AddContext<'sbc, 'lazy, 'sb, 'ast, 'ctx> {
         };
         mk.lower_to_hir(Box::new(move |sbc| {
             let ctx = AddContext::new(sbc, scope);
+            let report_text = string_literal(report);
             let report = ctx.add_expr(report);
             let severity = ctx.add_optional(severity, AddContext::add_expr);
             let (report, severity) = (report?, severity?);
@@ -196,6 +201,7 @@ impl<'sbc, 'lazy, 'sb, 'ast, 'ctx> AddContext<'sbc, 'lazy, 'sb, 'ast, 'ctx> {
                 label: stmt.label,
                 stmt: hir::ReportStmt {
                     report: report,
+                    report_text: report_text,
                     severity: severity,
                 },
             })
@@ -211,7 +217,85 @@ impl<'sbc, 'lazy, 'sb, 'ast, 'ctx> AddContext<'sbc, 'lazy, 'sb, 'ast, 'ctx> {
 
     /// Add a sig_assign statement.
     pub fn add_sig_assign_stmt(&self, stmt: &'ast ast::Stmt) -> Result<SigAssignStmtRef> {
-        self.unimp(stmt)
+        let (mk, id, scope) = self.make::<SigAssignStmtRef>(stmt.span);
+        let (target, dm, guarded, waves) = match stmt.data {
+            ast::AssignStmt {
+                ref target,
+                guarded,
+                mode:
+                    Spanned {
+                        value: ast::AssignMode::Normal(ref dm, ref waves),
+                        ..
+                    },
+                ..
+            } => (
+                target,
+                dm,
+                guarded,
+                match self.unpack_cond_or_uncond_waves(waves)? {
+                    CondOrUncond::Cond(conds, otherwise) => SigAssignWaves::Cond(conds, otherwise),
+                    CondOrUncond::Uncond(wave) => SigAssignWaves::Simple(wave),
+                },
+            ),
+            ast::SelectAssignStmt {
+                ref select,
+                qm,
+                ref target,
+                guarded,
+                mode: ast::SelectAssignMode::Normal(ref dm),
+                ref waves,
+                ..
+            } => (
+                target,
+                dm,
+                guarded,
+                SigAssignWaves::Sel {
+                    matching: qm,
+                    disc: select,
+                    waves: waves,
+                },
+            ),
+            _ => return self.unimp(stmt),
+        };
+        if guarded {
+            self.emit(
+                DiagBuilder2::warning("sequential signal assignment cannot be guarded")
+                    .span(stmt.human_span())
+                    .add_note(
+                        "Only concurrent signal assignments can be guarded. See IEEE 1076-2008 \
+                         section 11.6.",
+                    ),
+            );
+        }
+        mk.lower_to_hir(Box::new(move |sbc| {
+            let ctx = AddContext::new(sbc, scope);
+            let target_hir = sbc.unpack_signal_assign_target(scope, &target.value)?;
+            let target_node = match target_hir {
+                hir::SigAssignTarget::Name(sig) => sig.into(),
+                hir::SigAssignTarget::Part(_, expr) => expr.into(),
+                hir::SigAssignTarget::Aggregate => unreachable!(),
+            };
+            Ok(hir::Stmt {
+                parent: scope,
+                span: stmt.span,
+                label: stmt.label,
+                stmt: hir::SigAssignStmt {
+                    target: target_hir,
+                    target_span: target.span,
+                    kind: ctx.add_sig_assign_kind(dm, &waves, target_node)?,
+                },
+            })
+        }));
+        mk.typeck(Box::new(move |tyc| {
+            let hir = tyc.ctx.lazy_hir(id)?;
+            let ty = match hir.stmt.target {
+                hir::SigAssignTarget::Name(sig) => tyc.ctx.ty(sig)?,
+                hir::SigAssignTarget::Part(_, expr) => tyc.lazy_typeval(expr)?,
+                hir::SigAssignTarget::Aggregate => unreachable!(),
+            };
+            tyc.typeck_sig_assign_kind(&hir.stmt.kind, ty)
+        }));
+        Ok(mk.finish())
     }
 
     /// Add a var_assign statement.
@@ -270,7 +354,7 @@ impl<'sbc, 'lazy, 'sb, 'ast, 'ctx> AddContext<'sbc, 'lazy, 'sb, 'ast, 'ctx> {
                 self.emit(
                     DiagBuilder2::error(format!("invalid variable assignment")).span(stmt.span),
                 );
-                return Err(());
+                return Err(Error::Reported);
             }
         };
         mk.lower_to_hir(Box::new(move |sbc| {
@@ -341,9 +425,97 @@ impl<'sbc, 'lazy, 'sb, 'ast, 'ctx> AddContext<'sbc, 'lazy, 'sb, 'ast, 'ctx> {
         Ok(mk.finish())
     }
 
-    /// Add a call statement.
+    /// Add a procedure call statement.
+    ///
+    /// See IEEE 1076-2008 section 10.7.
     pub fn add_call_stmt(&self, stmt: &'ast ast::Stmt) -> Result<CallStmtRef> {
-        self.unimp(stmt)
+        let (mk, id, scope) = self.make::<CallStmtRef>(stmt.span);
+        mk.lower_to_hir(Box::new(move |sbc| {
+            AddContext::new(sbc, scope).unpack_call_stmt(stmt)
+        }));
+        mk.typeck(Box::new(move |tyc| {
+            let hir = tyc.ctx.lazy_hir(id)?;
+            tyc.typeck_call(hir.stmt.subprog.value, &hir.stmt.params)
+        }));
+        Ok(mk.finish())
+    }
+
+    /// Lower a sequential or concurrent procedure call statement to HIR.
+    ///
+    /// The actual of a signal parameter must be a signal name. See IEEE
+    /// 1076-2008 section 4.2.2.3.
+    pub fn unpack_call_stmt(&self, stmt: &'ast ast::Stmt) -> Result<hir::Stmt<hir::CallStmt>> {
+        let name = match stmt.data {
+            ast::InstOrCallStmt {
+                target: None,
+                ref name,
+                generics: None,
+                ports: None,
+            } => name,
+            _ => {
+                self.emit(
+                    DiagBuilder2::error(format!(
+                        "`{}` is not a valid procedure call",
+                        stmt.span.extract()
+                    ))
+                    .span(stmt.human_span()),
+                );
+                return Err(Error::Reported);
+            }
+        };
+        let termctx = TermContext::new(self.ctx, self.scope);
+        let term = termctx.termify_compound_name(name)?;
+        let (subprog, params) = termctx.term_to_proc_call(term)?;
+        let formals = self.ctx.subprog_params(subprog.value)?;
+        let mut had_fails = false;
+        for (&formal, arg) in formals.iter().zip(params.value.iter()) {
+            let actual = match arg.actual.value {
+                hir::AssocActual::Expr(expr) => expr,
+                _ => {
+                    self.emit(
+                        DiagBuilder2::error(format!(
+                            "`{}` is not a valid actual of a parameter",
+                            arg.actual.span.extract()
+                        ))
+                        .span(arg.actual.span),
+                    );
+                    had_fails = true;
+                    continue;
+                }
+            };
+            if let IntfObjRef::Signal(formal) = formal {
+                match self.ctx.lazy_hir(actual)?.data {
+                    hir::ExprData::SignalName(_) => (),
+                    _ => {
+                        self.emit(
+                            DiagBuilder2::error(format!(
+                                "`{}` is not a signal",
+                                arg.actual.span.extract()
+                            ))
+                            .span(arg.actual.span)
+                            .add_note(format!(
+                                "Signal parameter `{}` must be associated with a signal. See \
+                                 IEEE 1076-2008 section 4.2.2.3.",
+                                self.ctx.ast(formal).3.name
+                            )),
+                        );
+                        had_fails = true;
+                    }
+                }
+            }
+        }
+        if had_fails {
+            return Err(Error::Reported);
+        }
+        Ok(hir::Stmt {
+            parent: self.scope,
+            span: stmt.span,
+            label: stmt.label,
+            stmt: hir::CallStmt {
+                subprog: subprog,
+                params: params,
+            },
+        })
     }
 
     /// Add an if statement.
@@ -464,7 +636,7 @@ impl<'sbc, 'lazy, 'sb, 'ast, 'ctx> AddContext<'sbc, 'lazy, 'sb, 'ast, 'ctx> {
         mk.lower_to_hir(Box::new(move |sbc| {
             // TODO: Make a subscope here! No clue how...
             let ctx = AddContext::new(sbc, scope);
-            let scheme = (|| match *scheme {
+            let scheme: Result<_> = (|| match *scheme {
                 ast::LoopScheme::Loop => Ok(hir::LoopScheme::Loop),
                 ast::LoopScheme::While(ref cond) => Ok(hir::LoopScheme::While(ctx.add_expr(cond)?)),
                 ast::LoopScheme::For(name, ref range) => Ok(hir::LoopScheme::For(
@@ -523,7 +695,7 @@ impl<'sbc, 'lazy, 'sb, 'ast, 'ctx> AddContext<'sbc, 'lazy, 'sb, 'ast, 'ctx> {
                         DiagBuilder2::error(format!("`{}` is not a loop", span.extract()))
                             .span(span),
                     );
-                    return Err(());
+                    return Err(Error::Reported);
                 }
                 None => None,
             };
@@ -556,8 +728,9 @@ impl<'sbc, 'lazy, 'sb, 'ast, 'ctx> AddContext<'sbc, 'lazy, 'sb, 'ast, 'ctx> {
         mk.lower_to_hir(Box::new(move |sbc| {
             let ctx = AddContext::new(sbc, scope);
             let expr = ctx.add_optional(expr, AddContext::add_expr)?;
-            // TODO: Set a type context for the expression based on the outer
-            // function signature.
+            if let Some(ret) = subprog_return_type(sbc, scope)? {
+                sbc.set_type_context_optional(expr, ret);
+            }
             Ok(hir::Stmt {
                 parent: scope,
                 span: stmt.span,
@@ -566,8 +739,43 @@ impl<'sbc, 'lazy, 'sb, 'ast, 'ctx> AddContext<'sbc, 'lazy, 'sb, 'ast, 'ctx> {
             })
         }));
         mk.typeck(Box::new(move |tyc| {
-            let _hir = tyc.ctx.lazy_hir(id)?;
-            // TODO: Check the type of the expression.
+            let hir = tyc.ctx.lazy_hir(id)?;
+            let body = match hir.parent {
+                ScopeRef::SubprogBody(id) => id,
+                _ => return Ok(()),
+            };
+            let is_func = tyc.ctx.hir(body)?.spec.kind != hir::SubprogKind::Proc;
+            match (subprog_return_type(tyc.ctx, hir.parent)?, hir.stmt.expr) {
+                (Some(ret), Some(expr)) => {
+                    let ty = tyc.lazy_typeval(expr)?;
+                    if !tyc.must_match(ret, ty, tyc.ctx.span(expr).unwrap()) {
+                        return Err(Error::Reported);
+                    }
+                }
+                (None, Some(expr)) if !is_func => {
+                    tyc.emit(
+                        DiagBuilder2::error("a procedure cannot return a value")
+                            .span(tyc.ctx.span(expr).unwrap())
+                            .add_note(
+                                "Return statements in procedures must not have an expression. \
+                                 See IEEE 1076-2008 section 10.13.",
+                            ),
+                    );
+                    return Err(Error::Reported);
+                }
+                (_, None) if is_func => {
+                    tyc.emit(
+                        DiagBuilder2::error("a function must return a value")
+                            .span(hir.span)
+                            .add_note(
+                                "Return statements in functions must have an expression. See \
+                                 IEEE 1076-2008 section 10.13.",
+                            ),
+                    );
+                    return Err(Error::Reported);
+                }
+                _ => (),
+            }
             Ok(())
         }));
         Ok(mk.finish())
@@ -644,7 +852,7 @@ impl<'sbc, 'lazy, 'sb, 'ast, 'ctx> AddContext<'sbc, 'lazy, 'sb, 'ast, 'ctx> {
     ///
     /// Ensures that either the waves are all unconditional, or all have a
     /// condition except for the optional last one.
-    fn unpack_cond_or_uncond_waves(
+    pub(super) fn unpack_cond_or_uncond_waves(
         &self,
         ast: &'ast [ast::CondWave],
     ) -> Result<CondOrUncond<'ast>> {
@@ -653,7 +861,7 @@ impl<'sbc, 'lazy, 'sb, 'ast, 'ctx> AddContext<'sbc, 'lazy, 'sb, 'ast, 'ctx> {
         if any_cond {
             // Cut away the optional trailing else without condition.
             let (slice, otherwise) = match *ast.last().unwrap() {
-                ast::CondWave(ref wave, None) => (&ast[..ast.len() - 2], Some(wave)),
+                ast::CondWave(ref wave, None) => (&ast[..ast.len() - 1], Some(wave)),
                 _ => (&ast[..], None),
             };
             let conds = slice
@@ -672,7 +880,7 @@ impl<'sbc, 'lazy, 'sb, 'ast, 'ctx> AddContext<'sbc, 'lazy, 'sb, 'ast, 'ctx> {
                                  assignment can have a `when` condition.",
                             ),
                         );
-                        Err(())
+                        Err(Error::Reported)
                     }
                 })
                 .collect::<Vec<Result<_>>>()
@@ -689,7 +897,7 @@ impl<'sbc, 'lazy, 'sb, 'ast, 'ctx> AddContext<'sbc, 'lazy, 'sb, 'ast, 'ctx> {
                              expression.",
                         ),
                 );
-                Err(())
+                Err(Error::Reported)
             } else {
                 Ok(CondOrUncond::Uncond(&ast[0].0))
             }
@@ -728,7 +936,7 @@ impl<'sbc, 'lazy, 'sb, 'ast, 'ctx> AddContext<'sbc, 'lazy, 'sb, 'ast, 'ctx> {
             ))
             .span(ast.span),
         );
-        Err(())
+        Err(Error::Reported)
     }
 
     /// Unpack a sequence of selected waves as expressions.
@@ -752,7 +960,7 @@ impl<'sbc, 'lazy, 'sb, 'ast, 'ctx> AddContext<'sbc, 'lazy, 'sb, 'ast, 'ctx> {
 /// These are used for signal and variable assignments. Note that for variables
 /// further checking needs to be performed to ensure that the wave is actually
 /// just an expression.
-enum CondOrUncond<'ast> {
+pub(super) enum CondOrUncond<'ast> {
     /// A conditional slice of waves. Consists of wave-condition pairs, and an
     /// optional unconditional wave.
     Cond(
@@ -762,3 +970,20 @@ enum CondOrUncond<'ast> {
     /// An unconditional wave.
     Uncond(&'ast ast::Wave),
 }
+
+/// Determine the return type of the subprogram body a statement is in, if any.
+fn subprog_return_type<'lazy, 'sb, 'ast, 'ctx>(
+    ctx: &ScoreContext<'lazy, 'sb, 'ast, 'ctx>,
+    scope: ScopeRef,
+) -> Result<Option<&'ctx Ty>> {
+    let id = match scope {
+        ScopeRef::SubprogBody(id) => id,
+        _ => return Ok(None),
+    };
+    Ok(match *ctx.ty(SubprogRef::from(id))? {
+        Ty::Subprog(SubprogTy {
+            ret: Some(ref ret), ..
+        }) => Some(ctx.intern_ty((**ret).clone())),
+        _ => None,
+    })
+}
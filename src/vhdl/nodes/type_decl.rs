@@ -4,7 +4,7 @@
 
 use crate::common::errors::*;
 use crate::common::name::Name;
-use crate::common::score::{NodeRef, Result};
+use crate::common::score::{Error, NodeRef, Result};
 use crate::common::source::Spanned;
 
 use num::BigInt;
@@ -21,6 +21,37 @@ impl<'sbc, 'lazy, 'sb, 'ast, 'ctx> AddContext<'sbc, 'lazy, 'sb, 'ast, 'ctx> {
         let (mk, id, scope) = self.make(decl.span);
         self.ctx
             .define(scope, decl.name.map_into(), Def::Type(id))?;
+        // Enum literals and the units of a physical type are visible alongside
+        // the type itself.
+        match decl.data {
+            Some(Spanned {
+                value: ast::EnumType(ref paren_elems),
+                ..
+            }) => {
+                for (i, lit) in paren_elems.value.iter().enumerate() {
+                    if let ast::NameExpr(ref name) = lit.expr.data {
+                        self.ctx.define(
+                            scope,
+                            self.ctx.resolvable_from_primary_name(&name.primary)?,
+                            Def::Enum(EnumRef(id, i)),
+                        )?;
+                    }
+                }
+            }
+            Some(Spanned {
+                value: ast::RangeType(_, Some(ref units)),
+                ..
+            }) => {
+                for (i, unit) in units.iter().enumerate() {
+                    self.ctx.define(
+                        scope,
+                        Spanned::new(unit.0.name.into(), unit.0.span),
+                        Def::Unit(UnitRef(id, i)),
+                    )?;
+                }
+            }
+            _ => (),
+        }
         mk.lower_to_hir(Box::new(move |sbc| {
             let ctx = AddContext::new(sbc, scope);
             Ok(hir::TypeDecl {
@@ -68,7 +99,7 @@ impl<'sbc, 'lazy, 'sb, 'ast, 'ctx> AddContext<'sbc, 'lazy, 'sb, 'ast, 'ctx> {
                         self.emit(
                             DiagBuilder2::error("Invalid range expression").span(range_expr.span),
                         );
-                        return Err(());
+                        return Err(Error::Reported);
                     }
                 };
                 if let Some(ref units) = *units {
@@ -92,7 +123,7 @@ impl<'sbc, 'lazy, 'sb, 'ast, 'ctx> AddContext<'sbc, 'lazy, 'sb, 'ast, 'ctx> {
                                      `<name>;`. See IEEE 1076-2008 section 5.2.4.",
                                 ),
                             );
-                            return Err(());
+                            return Err(Error::Reported);
                         }
                     };
                     let mut had_fails = false;
@@ -111,7 +142,7 @@ impl<'sbc, 'lazy, 'sb, 'ast, 'ctx> AddContext<'sbc, 'lazy, 'sb, 'ast, 'ctx> {
                         had_fails = true;
                     }
                     if had_fails {
-                        return Err(());
+                        return Err(Error::Reported);
                     }
                     debugln!("primary unit {:#?}", primary);
 
@@ -134,7 +165,7 @@ impl<'sbc, 'lazy, 'sb, 'ast, 'ctx> AddContext<'sbc, 'lazy, 'sb, 'ast, 'ctx> {
                                             .span(term.span),
                                         );
                                         debugln!("It is a {:#?}", term.value);
-                                        return Err(());
+                                        return Err(Error::Reported);
                                     }
                                 };
                                 if unit.value.unwrap_old().0 != id {
@@ -273,8 +304,12 @@ impl<'sbc, 'lazy, 'sb, 'ast, 'ctx> AddContext<'sbc, 'lazy, 'sb, 'ast, 'ctx> {
             }
 
             ast::ProtectedType(..) => {
-                self.emit(DiagBuilder2::fatal("protected types not implemented").span(name.span));
-                return Err(());
+                self.emit(
+                    DiagBuilder2::fatal("protected types not supported")
+                        .span(name.span)
+                        .add_note("Shared variables must have a type that is not protected"),
+                );
+                return Err(Error::Reported);
             }
         };
         Ok(Spanned::new(td, data.span))
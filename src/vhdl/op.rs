@@ -9,7 +9,7 @@ use std::fmt;
 
 use crate::common::errors::*;
 use crate::common::name::*;
-use crate::common::score::Result;
+use crate::common::score::{Error, Result};
 use crate::common::source::Spanned;
 
 use crate::score::ResolvableName;
@@ -162,7 +162,7 @@ impl UnaryOp {
             ast::UnaryOp::Condition => UnaryOp::Cond,
             _ => {
                 ctx.emit(DiagBuilder2::error("invalid unary operator").span(ast.span));
-                return Err(());
+                return Err(Error::Reported);
             }
         };
         Ok(Spanned::new(op, ast.span))
@@ -253,7 +253,7 @@ impl BinaryOp {
             ast::BinaryOp::Pow => BinaryOp::Pow,
             _ => {
                 ctx.emit(DiagBuilder2::error("invalid binary operator").span(ast.span));
-                return Err(());
+                return Err(Error::Reported);
             }
         };
         Ok(Spanned::new(op, ast.span))
@@ -8,10 +8,10 @@ use std::collections::{HashMap, HashSet};
 
 use crate::common::errors::*;
 use crate::common::name::Name;
-use crate::common::score::Result;
+use crate::common::score::{Error, Result};
 use crate::common::source::{Span, Spanned};
 
-use crate::score::{Def, ScoreContext};
+use crate::score::{Def, ScoreContext, SubprogRef, TypeMarkRef};
 use crate::ty::Ty;
 
 /// A type requirement on an overloaded entity.
@@ -161,6 +161,10 @@ impl<'ctx> Default for TypeReq<'ctx> {
 fn are_types_matching(a: &Ty, b: &Ty) -> bool {
     match (a, b) {
         (&Ty::Named(_, ia), &Ty::Named(_, ib)) => ia == ib,
+        // Enum literals evaluate to the enum type itself rather than the named
+        // type.
+        (&Ty::Named(_, TypeMarkRef::Type(id)), &Ty::Enum(ref et))
+        | (&Ty::Enum(ref et), &Ty::Named(_, TypeMarkRef::Type(id))) => id == et.decl,
         (a, b) => a == b,
     }
 }
@@ -199,7 +203,7 @@ pub fn reduce_overloads(
                 match def.value {
                     Def::Enum(id) => ctx.lazy_typeval(id)?,
                     Def::BuiltinOp(id) => ctx.lazy_typeval(id)?,
-                    Def::Subprog(id) => ctx.lazy_typeval(id)?,
+                    Def::Subprog(id) => ctx.ty(SubprogRef::from(id))?,
                     _ => unreachable!(),
                 },
             ))
@@ -230,13 +234,13 @@ pub fn resolve_overloads(
             DiagBuilder2::error("no overload applies").span(span), // TODO: Show available implementations.
         );
         debugln!("available definitions: {:#?}", defs);
-        Err(())
+        Err(Error::Reported)
     } else if reduced.len() > 1 {
         ctx.emit(
             DiagBuilder2::error(format!("`{}` is ambiguous", span.extract())).span(span), // TODO: Show implementations that matched.
         );
         debugln!("matching definitions: {:#?}", reduced);
-        Err(())
+        Err(Error::Reported)
     } else {
         Ok(reduced.into_iter().next().unwrap())
     }
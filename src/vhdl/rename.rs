@@ -0,0 +1,50 @@
+// Copyright (c) 2016-2020 Fabian Schuiki
+
+//! Support for renaming declarations.
+//!
+//! This module finds the uses of a declaration, which is the basis for
+//! renaming a declaration and its uses in one go. Uses are found through name
+//! resolution rather than by comparing names, such that a declaration that
+//! shadows another one in an inner scope, or an unrelated declaration of the
+//! same name elsewhere, is left alone.
+
+use crate::score::{Def, LibRef, ScoreContext};
+use crate::typeck::{Typeck, TypeckContext};
+use moore_common::source::Span;
+
+/// Find all uses of the declaration whose name is at `decl`.
+///
+/// Type checks the library `lib` first, such that all names in it have been
+/// resolved. The result starts with `decl` itself, followed by the spans of
+/// all names that resolve to the declaration, in source order. A name that is
+/// overloaded, like a subprogram or an enumeration literal, counts as a use of
+/// every declaration it may refer to.
+///
+/// Resolved names are only recorded while `record_names` is set on the
+/// scoreboard. This function sets it, which suffices if the library has not
+/// been analyzed yet; otherwise the caller must set it before analysis.
+pub fn find_decl_uses(ctx: &ScoreContext, lib: LibRef, decl: Span) -> Vec<Span> {
+    ctx.sb.record_names.set(true);
+    TypeckContext::new(ctx).typeck(lib);
+    let resolved = ctx.sb.resolved_names.borrow();
+
+    // Identify the declaration by the definitions introduced at `decl`.
+    let targets: Vec<Def> = resolved
+        .values()
+        .flatten()
+        .filter(|def| def.span == decl)
+        .map(|def| def.value)
+        .collect();
+
+    // Collect the names that resolve to one of these definitions.
+    let mut spans: Vec<Span> = resolved
+        .iter()
+        .filter(|&(&span, defs)| {
+            span != decl && defs.iter().any(|def| targets.contains(&def.value))
+        })
+        .map(|(&span, _)| span)
+        .collect();
+    spans.sort();
+    spans.insert(0, decl);
+    spans
+}
@@ -7,7 +7,7 @@
 use std::collections::{HashMap, HashSet};
 
 use crate::common::errors::*;
-use crate::common::score::Result;
+use crate::common::score::{Error, Result};
 use crate::common::source::Spanned;
 use crate::common::Verbosity;
 
@@ -55,7 +55,7 @@ impl<'lazy, 'sb, 'ast, 'ctx> ScoreContext<'lazy, 'sb, 'ast, 'ctx> {
                     "scope {:?} does not exist`",
                     scope
                 )));
-                return Err(());
+                return Err(Error::Reported);
             }
         };
         f(scp)
@@ -76,7 +76,7 @@ impl<'lazy, 'sb, 'ast, 'ctx> ScoreContext<'lazy, 'sb, 'ast, 'ctx> {
         }
         self.with_scope(scope, |scope| match def {
             // Handle overloadable cases.
-            Def::Enum(_) => {
+            Def::Enum(_) | Def::Subprog(_) | Def::SubprogBody(_) => {
                 scope
                     .defs
                     .entry(name.value)
@@ -97,7 +97,7 @@ impl<'lazy, 'sb, 'ast, 'ctx> ScoreContext<'lazy, 'sb, 'ast, 'ctx> {
                             .add_note("Previous declaration was here:")
                             .span(existing.last().unwrap().span),
                     );
-                    Err(())
+                    Err(Error::Reported)
                 } else {
                     Ok(())
                 }
@@ -12,7 +12,7 @@ use std::fmt;
 use std::hash::{Hash, Hasher};
 
 use crate::common::errors::*;
-use crate::common::score::Result;
+use crate::common::score::{Error, Result};
 use crate::common::source::Spanned;
 use crate::common::{SessionContext, Verbosity};
 
@@ -23,7 +23,7 @@ use crate::score::ResolvableName;
 #[derive(Copy, Clone)]
 pub enum Def2<'t> {
     /// Any node.
-    Node(&'t hir::LatentNode<'t, Node<'t>>),
+    Node(&'t hir::LatentNode<'t, dyn hir::Node<'t> + 't>),
     /// A library.
     Lib(&'t hir::Library<'t>),
     /// A package.
@@ -152,7 +152,7 @@ impl<'t> ScopeData<'t> {
                             .add_note("Previous declaration was here:")
                             .span(existing.last().unwrap().span),
                     );
-                    Err(())
+                    Err(Error::Reported)
                 } else {
                     Ok(())
                 }
@@ -2,6 +2,8 @@
 
 //! This module implements constant value computation.
 
+use num::{FromPrimitive, Integer, Signed, ToPrimitive, Zero};
+
 use crate::score::*;
 
 // Calculate the constant value of an expression.
@@ -14,6 +16,19 @@ impl_make!(self, id: ExprRef => &Const {
         // Float literals.
         hir::ExprData::FloatLiteral(ref c) => self.intern_const(c.clone()),
 
+        // Physical literals, converted to the primary unit of their type.
+        hir::ExprData::PhysicalLiteral(ref value, unit) => {
+            let ty = match *self.deref_named_type(self.lazy_typeval(id)?)? {
+                Ty::Physical(ref ty) => ty,
+                _ => unreachable!(),
+            };
+            let abs = &ty.units[unit.value.1].abs;
+            self.intern_const(ConstPhysical::new(ty.clone(), value * abs))
+        }
+
+        // Qualified expressions.
+        hir::ExprData::Qualified(_, arg_id) => self.const_value(arg_id)?,
+
         // Unary operators.
         hir::ExprData::Unary(op, _, arg_id) => {
             let arg = self.const_value(arg_id)?;
@@ -22,12 +37,15 @@ impl_make!(self, id: ExprRef => &Const {
             match op.value {
                 UnaryOp::Pos => arg,
                 UnaryOp::Neg => self.intern_const(arg.clone().negate()),
+                UnaryOp::Not if bool_const(arg).is_some() => {
+                    self.intern_const(ConstEnum::new(BOOLEAN_TYPE.id, !bool_const(arg).unwrap() as usize))
+                }
                 _ => {
                     self.emit(
                         DiagBuilder2::bug(format!("constant unary operator {:?} not yet implemented", op.value))
                         .span(op.span)
                     );
-                    return Err(());
+                    return Err(Error::Reported);
                 }
             }
         }
@@ -50,7 +68,7 @@ impl_make!(self, id: ExprRef => &Const {
                         DiagBuilder2::error("left and right bound of range must both be integer or float")
                         .span(hir.span)
                     );
-                    return Err(());
+                    return Err(Error::Reported);
                 }
             }
         }
@@ -58,13 +76,382 @@ impl_make!(self, id: ExprRef => &Const {
         // Names.
         hir::ExprData::Name(Def::Enum(EnumRef(decl, index)), _) => self.intern_const(ConstEnum::new(decl, index)),
 
+        // Overloaded enum names, disambiguated by their type.
+        hir::ExprData::EnumName(ref lits) => {
+            let decl = match *self.deref_named_type(self.lazy_typeval(id)?)? {
+                Ty::Enum(ref ty) => ty.decl,
+                _ => unreachable!(),
+            };
+            let lit = lits.iter().find(|lit| lit.value.0 == decl).unwrap();
+            self.intern_const(ConstEnum::new(decl, lit.value.1))
+        }
+
+        // Arithmetic on integers and floating-point values, and addition and
+        // subtraction of physical values of the same type.
+        hir::ExprData::Binary(op, _, lhs_id, rhs_id) if is_arith(op.value) => {
+            let lhs = self.const_value(lhs_id)?;
+            let rhs = self.const_value(rhs_id)?;
+            match (op.value, lhs, rhs) {
+                (BinaryOp::Div, &Const::Int(_), &Const::Int(ref b))
+                | (BinaryOp::Mod, &Const::Int(_), &Const::Int(ref b))
+                | (BinaryOp::Rem, &Const::Int(_), &Const::Int(ref b)) if b.value.is_zero() => {
+                    self.emit(
+                        DiagBuilder2::error(format!("division by zero in `{}`", hir.span.extract()))
+                        .span(hir.span)
+                    );
+                    return Err(Error::Reported);
+                }
+                (BinaryOp::Pow, &Const::Int(_), &Const::Int(ref b)) if b.value.is_negative() => {
+                    self.emit(
+                        DiagBuilder2::error(format!("negative exponent in `{}`", hir.span.extract()))
+                        .span(hir.span)
+                        .add_note("Integers may only be raised to a non-negative power. See IEEE 1076-2008 section 9.2.8.")
+                    );
+                    return Err(Error::Reported);
+                }
+                (op, &Const::Int(ref a), &Const::Int(ref b)) if op != BinaryOp::Pow || b.value.to_usize().is_some() => {
+                    // Division truncates toward zero, `rem` takes the sign of
+                    // the left operand, and `mod` the sign of the right one.
+                    // See IEEE 1076-2008 section 9.2.7.
+                    self.intern_const(ConstInt::new(None, match op {
+                        BinaryOp::Add => &a.value + &b.value,
+                        BinaryOp::Sub => &a.value - &b.value,
+                        BinaryOp::Mul => &a.value * &b.value,
+                        BinaryOp::Div => &a.value / &b.value,
+                        BinaryOp::Rem => &a.value % &b.value,
+                        BinaryOp::Mod => a.value.mod_floor(&b.value),
+                        _ => num::pow(a.value.clone(), b.value.to_usize().unwrap()),
+                    }))
+                }
+                (BinaryOp::Add, &Const::Physical(ref a), &Const::Physical(ref b)) if a.ty.decl == b.ty.decl => {
+                    self.intern_const(ConstPhysical::new(a.ty.clone(), &a.value + &b.value))
+                }
+                (BinaryOp::Sub, &Const::Physical(ref a), &Const::Physical(ref b)) if a.ty.decl == b.ty.decl => {
+                    self.intern_const(ConstPhysical::new(a.ty.clone(), &a.value - &b.value))
+                }
+                (op, &Const::Float(ref a), &Const::Float(ref b)) if op != BinaryOp::Pow && op != BinaryOp::Mod && op != BinaryOp::Rem => {
+                    self.intern_const(ConstFloat::new(match op {
+                        BinaryOp::Add => a.value + b.value,
+                        BinaryOp::Sub => a.value - b.value,
+                        BinaryOp::Mul => a.value * b.value,
+                        _ => a.value / b.value,
+                    }))
+                }
+                (BinaryOp::Pow, &Const::Float(ref a), &Const::Int(ref b)) if b.value.to_i32().is_some() => {
+                    self.intern_const(ConstFloat::new(a.value.powi(b.value.to_i32().unwrap())))
+                }
+                _ => {
+                    self.emit(
+                        DiagBuilder2::bug(format!("constant binary operator `{}` on {} and {} not yet implemented", op.value, lhs, rhs))
+                        .span(hir.span)
+                    );
+                    return Err(Error::Reported);
+                }
+            }
+        }
+
+        // Relational operators on integers, floats, physical values, and
+        // enums, and logical operators on booleans.
+        hir::ExprData::Binary(op, _, lhs_id, rhs_id) => {
+            let lhs = self.const_value(lhs_id)?;
+            let rhs = self.const_value(rhs_id)?;
+            let value = match (op.value, lhs, rhs) {
+                (BinaryOp::Rel(op), &Const::Int(ref a), &Const::Int(ref b)) => compare(op, &a.value, &b.value),
+                (BinaryOp::Rel(op), &Const::Float(ref a), &Const::Float(ref b)) => compare(op, &a.value, &b.value),
+                (BinaryOp::Rel(op), &Const::Physical(ref a), &Const::Physical(ref b)) => {
+                    compare(op, &a.value, &b.value)
+                }
+                (BinaryOp::Rel(op), &Const::Enum(ref a), &Const::Enum(ref b)) if a.decl == b.decl => {
+                    compare(op, &a.index, &b.index)
+                }
+                (BinaryOp::Logical(op), _, _) if bool_const(lhs).is_some() && bool_const(rhs).is_some() => {
+                    let (a, b) = (bool_const(lhs).unwrap(), bool_const(rhs).unwrap());
+                    match op {
+                        LogicalOp::And => a && b,
+                        LogicalOp::Or => a || b,
+                        LogicalOp::Xor => a != b,
+                        LogicalOp::Nand => !(a && b),
+                        LogicalOp::Nor => !(a || b),
+                        LogicalOp::Xnor => a == b,
+                    }
+                }
+                _ => {
+                    self.emit(
+                        DiagBuilder2::bug(format!("constant binary operator `{}` on {} and {} not yet implemented", op.value, lhs, rhs))
+                        .span(hir.span)
+                    );
+                    return Err(Error::Reported);
+                }
+            };
+            self.intern_const(ConstEnum::new(BOOLEAN_TYPE.id, value as usize))
+        }
+
+        // Constant names.
+        hir::ExprData::ConstName(id) => {
+            let decl: &hir::Decl<hir::ConstDecl> = self.lazy_hir(id)?;
+            match decl.decl.init {
+                Some(init) => self.const_value(init)?,
+                None => {
+                    self.emit(
+                        DiagBuilder2::error(format!("deferred constant `{}` does not have a value", decl.name.value))
+                        .span(hir.span)
+                    );
+                    return Err(Error::Reported);
+                }
+            }
+        }
+
+        // Generate parameters, which have a fixed value in each block.
+        hir::ExprData::GenParamName(id) => {
+            let block = self.existing_hir(id)?;
+            self.intern_const(block.param.as_ref().unwrap().value.clone())
+        }
+
+        // Generics, which have a fixed value in each elaborated entity.
+        hir::ExprData::IntfConstName(id) => self.generic_value(id, hir.span)?,
+
+        // Type conversions between integer and floating-point types.
+        hir::ExprData::Cast(ref tm, arg_id) => {
+            let arg = self.const_value(arg_id)?;
+            let ty = self.deref_named_type(self.intern_ty(Ty::Named(tm.span.into(), tm.value)))?;
+            match (ty, arg) {
+                (&Ty::Int(ref ty), &Const::Int(ref k)) => {
+                    self.intern_const(ConstInt::new(Some(ty.clone()), k.value.clone()))
+                }
+                (&Ty::Int(ref ty), &Const::Float(ref k)) => {
+                    // Conversion to an integer rounds to the nearest value.
+                    let value = match BigInt::from_f64(k.value.round()) {
+                        Some(v) => v,
+                        None => {
+                            self.emit(
+                                DiagBuilder2::error(format!("`{}` is not a valid integer", k))
+                                .span(hir.span)
+                            );
+                            return Err(Error::Reported);
+                        }
+                    };
+                    self.intern_const(ConstInt::new(Some(ty.clone()), value))
+                }
+                (&Ty::Real(_), &Const::Int(ref k)) => {
+                    self.intern_const(ConstFloat::new(k.value.to_f64().unwrap()))
+                }
+                (&Ty::Real(_), &Const::Float(_)) => arg,
+                _ => {
+                    self.emit(
+                        DiagBuilder2::error(format!("constant cast `{}` not supported", hir.span.extract()))
+                        .span(hir.span)
+                    );
+                    return Err(Error::Reported);
+                }
+            }
+        }
+
+        // Attribute functions of discrete types. The position of an integer
+        // is its value, and that of an enum literal its index. See IEEE
+        // 1076-2008 section 16.2.2.
+        hir::ExprData::TypeAttrCall(ref tm, func, arg_id) => {
+            let arg = self.const_value(arg_id)?;
+            let ty = self.deref_named_type(self.intern_ty(Ty::Named(tm.span.into(), tm.value)))?;
+            let (left, right, dir) = match *ty {
+                Ty::Int(ref ty) => (ty.left_bound.clone(), ty.right_bound.clone(), ty.dir),
+                Ty::Enum(ref ty) => (BigInt::zero(), BigInt::from(self.enum_len(ty.decl)?) - 1, Dir::To),
+                _ => unreachable!(),
+            };
+            let pos = match *arg {
+                Const::Int(ref k) => k.value.clone(),
+                Const::Enum(ref k) => BigInt::from(k.index),
+                _ => unreachable!(),
+            };
+            let pos = match func.value {
+                hir::TypeAttrFn::Pos => return Ok(self.intern_const(ConstInt::new(None, pos))),
+                hir::TypeAttrFn::Val => pos,
+                hir::TypeAttrFn::Succ => pos + 1,
+                hir::TypeAttrFn::Pred => pos - 1,
+                hir::TypeAttrFn::Leftof if dir == Dir::To => pos - 1,
+                hir::TypeAttrFn::Rightof if dir == Dir::Downto => pos - 1,
+                hir::TypeAttrFn::Leftof | hir::TypeAttrFn::Rightof => pos + 1,
+            };
+            let (lo, hi) = match dir {
+                Dir::To => (&left, &right),
+                Dir::Downto => (&right, &left),
+            };
+            if pos < *lo || pos > *hi {
+                self.emit(
+                    DiagBuilder2::error(format!("`{}` is out of range", hir.span.extract()))
+                    .span(hir.span)
+                    .add_note(format!("The positions of `{}` range from {} {} {}", tm.span.extract(), left, dir, right))
+                );
+                return Err(Error::Reported);
+            }
+            match *ty {
+                Ty::Int(ref ty) => self.intern_const(ConstInt::new(Some(ty.clone()), pos)),
+                Ty::Enum(ref ty) => self.intern_const(ConstEnum::new(ty.decl, pos.to_usize().unwrap())),
+                _ => unreachable!(),
+            }
+        }
+
+        // Calls to builtin functions.
+        hir::ExprData::BuiltinCall(func, ref args) => {
+            let func = math_real_fn(func.value);
+            let mut values = Vec::new();
+            for arg in &args.value {
+                let value = match arg.actual.value {
+                    hir::AssocActual::Expr(id) if arg.formal.is_none() => self.const_value(id)?,
+                    _ => {
+                        self.emit(
+                            DiagBuilder2::error(format!("`{}` must be a positional argument", arg.span.extract()))
+                            .span(arg.span)
+                        );
+                        return Err(Error::Reported);
+                    }
+                };
+                values.push(match *value {
+                    Const::Float(ref k) => k.value,
+                    Const::Int(ref k) => k.value.to_f64().unwrap(),
+                    _ => {
+                        self.emit(
+                            DiagBuilder2::error(format!("`{}` is not a real value", arg.span.extract()))
+                            .span(arg.span)
+                        );
+                        return Err(Error::Reported);
+                    }
+                });
+            }
+            match func.apply(&values) {
+                Some(value) => self.intern_const(ConstFloat::new(value)),
+                None => {
+                    self.emit(
+                        DiagBuilder2::error(format!("`{}` cannot be evaluated during elaboration", hir.span.extract()))
+                        .span(hir.span)
+                    );
+                    return Err(Error::Reported);
+                }
+            }
+        }
+
+        // Record and array aggregates. Integer elements take on the type of
+        // the element, such that they map to the right width.
+        hir::ExprData::Aggregate(agg) => {
+            let retype = |value: &Const, ty: &Ty| -> Result<Const> {
+                Ok(match (self.deref_named_type(ty)?, value) {
+                    (&Ty::Int(ref ty), &Const::Int(ref k)) => ConstInt::new(Some(ty.clone()), k.value.clone()).into(),
+                    _ => value.clone(),
+                })
+            };
+            match *self.deref_named_type(self.lazy_typeval(id)?)? {
+                Ty::Record(ref record_ty) => {
+                    let exprs = self.record_aggregate_fields(agg, record_ty)?;
+                    let mut fields = Vec::new();
+                    for (expr, &(_, ref field_ty)) in exprs.into_iter().zip(record_ty.fields.iter()) {
+                        fields.push(retype(self.const_value(expr)?, field_ty)?);
+                    }
+                    self.intern_const(ConstRecord::new(fields))
+                }
+                Ty::Array(ref array_ty) => {
+                    let element_ty = array_ty.first_element();
+                    let mut elements = Vec::new();
+                    for expr in self.array_aggregate_elements(agg, array_ty)? {
+                        elements.push(retype(self.const_value(expr)?, &element_ty)?);
+                    }
+                    self.intern_const(ConstArray::new(elements))
+                }
+                _ => {
+                    self.emit(
+                        DiagBuilder2::bug(format!("constant value of aggregate `{}` not implemented", hir.span.extract()))
+                        .span(hir.span)
+                    );
+                    return Err(Error::Reported);
+                }
+            }
+        }
+
+        // Indexed and slice names of constant arrays.
+        hir::ExprData::Call(prefix, ref args) => {
+            let mut array_ty = match *self.deref_named_type(self.lazy_typeval(prefix)?)? {
+                Ty::Array(ref ty) => ty.clone(),
+                _ => unreachable!(),
+            };
+            let mut value = self.const_value(prefix)?;
+            let indices = self.index_exprs(args)?;
+            if let hir::ExprData::Range(_, lb, rb) = self.lazy_hir(indices[0])?.data {
+                let elements = match *value {
+                    Const::Array(ref k) => &k.elements,
+                    _ => unreachable!(),
+                };
+                let lb = self.array_index_position(&array_ty, 0, &self.index_value(lb)?)?;
+                let rb = self.array_index_position(&array_ty, 0, &self.index_value(rb)?)?;
+                return Ok(match (lb, rb) {
+                    (Some(lb), Some(rb)) if lb <= rb => {
+                        self.intern_const(ConstArray::new(elements[lb..=rb].to_vec()))
+                    }
+                    _ => self.intern_const(Const::Null),
+                });
+            }
+            for index in indices {
+                let position = self.array_index_position(&array_ty, 0, &self.index_value(index)?)?;
+                value = match (value, position) {
+                    (&Const::Array(ref k), Some(position)) => &k.elements[position],
+                    _ => {
+                        let span = self.lazy_hir(index)?.span;
+                        self.emit(
+                            DiagBuilder2::error(format!(
+                                "index `{}` is out of the range of array type `{}`",
+                                span.extract(),
+                                array_ty
+                            ))
+                            .span(span),
+                        );
+                        return Err(Error::Reported);
+                    }
+                };
+                if let Ty::Array(ty) = array_ty.first_element() {
+                    array_ty = ty;
+                }
+            }
+            value
+        }
+
         // All other expressions cannot be turned into a constant value.
         _ => {
             self.emit(
                 DiagBuilder2::error("expression does not have a constant value")
                 .span(hir.span)
             );
-            return Err(());
+            return Err(Error::Reported);
         }
     })
 });
+
+/// Determine the value of a constant boolean.
+fn bool_const(k: &Const) -> Option<bool> {
+    match *k {
+        Const::Enum(ref k) if k.decl == BOOLEAN_TYPE.id => Some(k.index == 1),
+        _ => None,
+    }
+}
+
+/// Check whether a binary operator is an arithmetic operator.
+fn is_arith(op: BinaryOp) -> bool {
+    match op {
+        BinaryOp::Add
+        | BinaryOp::Sub
+        | BinaryOp::Mul
+        | BinaryOp::Div
+        | BinaryOp::Mod
+        | BinaryOp::Rem
+        | BinaryOp::Pow => true,
+        _ => false,
+    }
+}
+
+/// Compare two values with a relational operator.
+fn compare<T: PartialOrd>(op: RelationalOp, a: &T, b: &T) -> bool {
+    match op {
+        RelationalOp::Eq => a == b,
+        RelationalOp::Neq => a != b,
+        RelationalOp::Lt => a < b,
+        RelationalOp::Leq => a <= b,
+        RelationalOp::Gt => a > b,
+        RelationalOp::Geq => a >= b,
+    }
+}
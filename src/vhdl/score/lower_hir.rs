@@ -12,6 +12,7 @@ use crate::make_ctx::MakeContext;
 use crate::op::*;
 use crate::score::*;
 use crate::syntax::lexer::token::Literal;
+use crate::syntax::visit::{Visitor, WalkVisitor};
 use crate::term::*;
 
 /// Emit a compiler bug and return `Err`.
@@ -21,13 +22,13 @@ macro_rules! unimp {
             "lowering to HIR of {:?} not implemented",
             $id
         )));
-        return Err(());
+        return Err(Error::Reported);
     }};
     ($slf:tt, $id:expr, $span:expr) => {{
         $slf.emit(
             DiagBuilder2::bug(format!("lowering to HIR of {:?} not implemented", $id)).span($span),
         );
-        return Err(());
+        return Err(Error::Reported);
     }};
 }
 
@@ -37,13 +38,13 @@ macro_rules! unimp_msg {
             "lowering to HIR: {} not implemented",
             $msg
         )));
-        return Err(());
+        return Err(Error::Reported);
     }};
     ($slf:tt, $msg:expr, $span:expr) => {{
         $slf.emit(
             DiagBuilder2::bug(format!("lowering to HIR: {} not implemented", $msg)).span($span),
         );
-        return Err(());
+        return Err(Error::Reported);
     }};
 }
 
@@ -247,7 +248,7 @@ impl<'lazy, 'sb, 'ast, 'ctx> ScoreContext<'lazy, 'sb, 'ast, 'ctx> {
         }
 
         if had_fails {
-            Err(())
+            Err(Error::Reported)
         } else {
             Ok(refs)
         }
@@ -387,7 +388,7 @@ impl<'lazy, 'sb, 'ast, 'ctx> ScoreContext<'lazy, 'sb, 'ast, 'ctx> {
         }
 
         if had_fails {
-            Err(())
+            Err(Error::Reported)
         } else {
             Ok(refs)
         }
@@ -523,7 +524,7 @@ impl<'lazy, 'sb, 'ast, 'ctx> ScoreContext<'lazy, 'sb, 'ast, 'ctx> {
         }
 
         if had_fails {
-            Err(())
+            Err(Error::Reported)
         } else {
             Ok(refs)
         }
@@ -540,44 +541,60 @@ impl<'lazy, 'sb, 'ast, 'ctx> ScoreContext<'lazy, 'sb, 'ast, 'ctx> {
     ) -> Result<Vec<ConcStmtRef>> {
         let mut refs = Vec::new();
         let mut had_fails = false;
-        let unimp = |s: &ast::Stmt| {
-            self.emit(
-                DiagBuilder2::bug(format!("{} not implemented", s.desc())).span(s.human_span()),
-            )
-        };
         for stmt in stmts {
             match stmt.data {
                 ast::BlockStmt { .. } => {
-                    unimp(stmt);
-                    had_fails = true;
+                    match AddContext::new(self, scope_id).add_block_stmt(stmt) {
+                        Ok(id) => refs.push(id.into()),
+                        Err(_) => had_fails = true,
+                    }
                 }
-                ast::InstOrCallStmt { .. } => {
-                    unimp(stmt);
-                    had_fails = true;
+                ast::InstOrCallStmt {
+                    target,
+                    ref generics,
+                    ref ports,
+                    ..
+                } if target.is_some() || generics.is_some() || ports.is_some() => {
+                    match AddContext::new(self, scope_id).add_comp_inst_stmt(stmt) {
+                        Ok(id) => refs.push(id.into()),
+                        Err(_) => had_fails = true,
+                    }
                 }
-                ast::AssertStmt { .. } => {
-                    unimp(stmt);
-                    had_fails = true;
+                ast::InstOrCallStmt { .. } => {
+                    match AddContext::new(self, scope_id).add_conc_call_stmt(stmt) {
+                        Ok(id) => refs.push(id.into()),
+                        Err(_) => had_fails = true,
+                    }
                 }
-                ast::AssignStmt { .. } => {
-                    unimp(stmt);
-                    had_fails = true;
+                ast::AssertStmt { .. } | ast::PslDirectiveStmt { .. } => {
+                    match AddContext::new(self, scope_id).add_conc_assert_stmt(stmt) {
+                        Ok(id) => refs.push(id.into()),
+                        Err(_) => had_fails = true,
+                    }
                 }
-                ast::SelectAssignStmt { .. } => {
-                    unimp(stmt);
-                    had_fails = true;
+                ast::AssignStmt { .. } | ast::SelectAssignStmt { .. } => {
+                    match AddContext::new(self, scope_id).add_conc_sig_assign_stmt(stmt) {
+                        Ok(id) => refs.push(id.into()),
+                        Err(_) => had_fails = true,
+                    }
                 }
                 ast::IfGenStmt { .. } => {
-                    unimp(stmt);
-                    had_fails = true;
+                    match AddContext::new(self, scope_id).add_if_gen_stmt(stmt) {
+                        Ok(id) => refs.push(id.into()),
+                        Err(_) => had_fails = true,
+                    }
                 }
                 ast::CaseGenStmt { .. } => {
-                    unimp(stmt);
-                    had_fails = true;
+                    match AddContext::new(self, scope_id).add_case_gen_stmt(stmt) {
+                        Ok(id) => refs.push(id.into()),
+                        Err(_) => had_fails = true,
+                    }
                 }
                 ast::ForGenStmt { .. } => {
-                    unimp(stmt);
-                    had_fails = true;
+                    match AddContext::new(self, scope_id).add_for_gen_stmt(stmt) {
+                        Ok(id) => refs.push(id.into()),
+                        Err(_) => had_fails = true,
+                    }
                 }
 
                 ast::ProcStmt { .. } => {
@@ -605,7 +622,7 @@ impl<'lazy, 'sb, 'ast, 'ctx> ScoreContext<'lazy, 'sb, 'ast, 'ctx> {
             }
         }
         if had_fails {
-            Err(())
+            Err(Error::Reported)
         } else {
             Ok(refs)
         }
@@ -633,18 +650,14 @@ impl<'lazy, 'sb, 'ast, 'ctx> ScoreContext<'lazy, 'sb, 'ast, 'ctx> {
         target: &'ast ast::AssignTarget,
     ) -> Result<hir::SigAssignTarget> {
         match *target {
+            // Names with a selection or index may denote a record field or
+            // array element, which only the term layer resolves.
+            ast::AssignTarget::Name(ref name) if !name.parts.is_empty() => {
+                self.unpack_signal_assign_part(scope_id, name)
+            }
             ast::AssignTarget::Name(ref name) => {
-                let (_res_name, mut defs, res_span, tail) =
+                let (_res_name, mut defs, res_span, _) =
                     self.resolve_compound_name(name, scope_id, false)?;
-                if !tail.is_empty() {
-                    self.emit(
-                        DiagBuilder2::bug(
-                            "handling of non-name signal assignment targets not implemented",
-                        )
-                        .span(name.span),
-                    );
-                    return Err(());
-                }
                 let sig = match defs.pop() {
                     Some(Spanned {
                         value: Def::Signal(id),
@@ -658,7 +671,7 @@ impl<'lazy, 'sb, 'ast, 'ctx> ScoreContext<'lazy, 'sb, 'ast, 'ctx> {
                             ))
                             .span(res_span),
                         );
-                        return Err(());
+                        return Err(Error::Reported);
                     }
                     None => unreachable!(),
                 };
@@ -667,7 +680,7 @@ impl<'lazy, 'sb, 'ast, 'ctx> ScoreContext<'lazy, 'sb, 'ast, 'ctx> {
                         DiagBuilder2::error(format!("`{}` is ambiguous", res_span.extract()))
                             .span(res_span),
                     );
-                    return Err(());
+                    return Err(Error::Reported);
                 }
                 Ok(hir::SigAssignTarget::Name(sig))
             }
@@ -676,7 +689,37 @@ impl<'lazy, 'sb, 'ast, 'ctx> ScoreContext<'lazy, 'sb, 'ast, 'ctx> {
                     DiagBuilder2::error("aggregate signal assignment not implemented")
                         .span(elems.span),
                 );
-                Err(())
+                Err(Error::Reported)
+            }
+        }
+    }
+
+    /// Unpack a signal assignment target that denotes a part of a signal, such
+    /// as `a(3)`, `a(7 downto 4)`, or `a.b`.
+    fn unpack_signal_assign_part(
+        &self,
+        scope_id: ScopeRef,
+        name: &'ast ast::CompoundName,
+    ) -> Result<hir::SigAssignTarget> {
+        let ctx = TermContext::new(self, scope_id);
+        let expr = ctx.term_to_expr(ctx.termify_compound_name(name)?)?;
+        let mut prefix = expr;
+        loop {
+            let hir = self.lazy_hir(prefix)?;
+            match hir.data {
+                hir::ExprData::Call(inner, _) | hir::ExprData::Select(inner, _) => prefix = inner,
+                // A selected name such as `pkg.sig` denotes the whole signal.
+                hir::ExprData::SignalName(sig) if prefix == expr => {
+                    return Ok(hir::SigAssignTarget::Name(sig))
+                }
+                hir::ExprData::SignalName(sig) => return Ok(hir::SigAssignTarget::Part(sig, expr)),
+                _ => {
+                    self.emit(
+                        DiagBuilder2::error(format!("`{}` is not a signal", hir.span.extract()))
+                            .span(hir.span),
+                    );
+                    return Err(Error::Reported);
+                }
             }
         }
     }
@@ -873,12 +916,9 @@ impl<'lazy, 'sb, 'ast, 'ctx> ScoreContext<'lazy, 'sb, 'ast, 'ctx> {
             );
         }
         let generic_map = vec![];
-        if ast.params.is_some() {
-            unimp_msg!(
-                self,
-                "lowering of parameters in subprogram specifications",
-                name.span
-            );
+        let mut params = Vec::new();
+        if let Some(ref decls) = ast.params {
+            self.unpack_params(scope_id, kind, decls, &mut params)?;
         }
         let return_type = match ast.retty {
             Some(ref name) => Some(self.unpack_type_mark(name.into(), scope_id)?),
@@ -893,14 +933,14 @@ impl<'lazy, 'sb, 'ast, 'ctx> ScoreContext<'lazy, 'sb, 'ast, 'ctx> {
                          type. See IEEE 1076-2008 section 4.2.1.",
                     ),
             );
-            return Err(());
+            return Err(Error::Reported);
         }
         Ok(hir::SubprogSpec {
             name: name,
             kind: kind,
             generics: generics,
             generic_map: generic_map,
-            params: vec![],
+            params: params,
             return_type: return_type,
         })
     }
@@ -921,7 +961,7 @@ impl<'lazy, 'sb, 'ast, 'ctx> ScoreContext<'lazy, 'sb, 'ast, 'ctx> {
                          section 4.2.1.",
                     ),
             );
-            return Err(());
+            return Err(Error::Reported);
         }
         if kind == hir::SubprogKind::Proc && !name.value.is_ident() {
             self.emit(
@@ -932,7 +972,7 @@ impl<'lazy, 'sb, 'ast, 'ctx> ScoreContext<'lazy, 'sb, 'ast, 'ctx> {
                          section 4.2.1.",
                     ),
             );
-            return Err(());
+            return Err(Error::Reported);
         }
         Ok(name)
     }
@@ -966,10 +1006,7 @@ impl<'lazy, 'sb, 'ast, 'ctx> ScoreContext<'lazy, 'sb, 'ast, 'ctx> {
                     into.push(id.into());
                 }
                 ast::IntfDecl::ObjDecl(
-                    ref
-                    decl
-                    @
-                    ast::IntfObjDecl {
+                    ref decl @ ast::IntfObjDecl {
                         kind: ast::IntfObjKind::Const,
                         ..
                     },
@@ -996,7 +1033,222 @@ impl<'lazy, 'sb, 'ast, 'ctx> ScoreContext<'lazy, 'sb, 'ast, 'ctx> {
             }
         }
         if had_fails {
-            Err(())
+            Err(Error::Reported)
+        } else {
+            Ok(())
+        }
+    }
+
+    /// Check the generic and port clauses of an entity.
+    ///
+    /// There may be at most one clause of each kind, with the generic clause
+    /// preceding the port clause. The default value of a generic may only
+    /// refer to the generics declared before it, and the default value of a
+    /// port to no ports at all. See IEEE 1076-2008 sections 3.2.2 and 6.5.6.
+    pub fn check_portgen_clauses(&self, decls: &'ast [ast::DeclItem]) -> Result<()> {
+        let mut generic_clause: Option<Span> = None;
+        let mut port_clause: Option<Span> = None;
+        let mut generics = vec![];
+        let mut ports = vec![];
+        let mut had_fails = false;
+        for decl in decls {
+            let (kind, intfs) = match *decl {
+                ast::DeclItem::PortgenClause(_, kind, ref intfs) => (kind, intfs),
+                _ => continue,
+            };
+            let (first, desc) = match kind.value {
+                ast::PortgenKind::Generic => (generic_clause, "generic"),
+                ast::PortgenKind::Port => (port_clause, "port"),
+            };
+            if let Some(first) = first {
+                self.emit(
+                    DiagBuilder2::error(format!("more than one {} clause", desc))
+                        .span(kind.span)
+                        .add_note(format!("The first {} clause is here:", desc))
+                        .span(first),
+                );
+                had_fails = true;
+                continue;
+            }
+            let objs = intfs.value.iter().filter_map(|intf| match *intf {
+                ast::IntfDecl::ObjDecl(ref decl) => Some(decl),
+                _ => None,
+            });
+            match kind.value {
+                ast::PortgenKind::Generic => {
+                    if let Some(port_clause) = port_clause {
+                        self.emit(
+                            DiagBuilder2::error("generic clause must precede the port clause")
+                                .span(kind.span)
+                                .add_note("The port clause is here:")
+                                .span(port_clause)
+                                .add_note("See IEEE 1076-2008 section 3.2.2."),
+                        );
+                        had_fails = true;
+                    }
+                    generic_clause = Some(kind.span);
+                    generics.extend(objs);
+                }
+                ast::PortgenKind::Port => {
+                    port_clause = Some(kind.span);
+                    ports.extend(objs);
+                }
+            }
+        }
+
+        // A generic's default may refer to the generics before it. Anything
+        // declared at or after it, including all ports, is off limits.
+        for (index, generic) in generics.iter().enumerate() {
+            let later = generics[index..]
+                .iter()
+                .chain(ports.iter())
+                .flat_map(|decl| decl.names.iter());
+            for (used, decl) in intf_default_uses(generic, later) {
+                self.emit(
+                    DiagBuilder2::error(format!(
+                        "default value of generic `{}` refers to `{}`, which is declared after it",
+                        generic.names[0].name, used.value
+                    ))
+                    .span(used.span)
+                    .add_note(format!("`{}` is declared here:", used.value))
+                    .span(decl)
+                    .add_note(
+                        "The default value of a generic may only refer to the generics \
+                         declared before it. See IEEE 1076-2008 section 6.5.6.2.",
+                    ),
+                );
+                had_fails = true;
+            }
+        }
+        for port in &ports {
+            let all = ports.iter().flat_map(|decl| decl.names.iter());
+            for (used, decl) in intf_default_uses(port, all) {
+                self.emit(
+                    DiagBuilder2::error(format!(
+                        "default value of port `{}` refers to port `{}`",
+                        port.names[0].name, used.value
+                    ))
+                    .span(used.span)
+                    .add_note(format!("`{}` is declared here:", used.value))
+                    .span(decl)
+                    .add_note(
+                        "The default value of a port may refer to generics, but not to \
+                         ports. See IEEE 1076-2008 section 6.5.6.3.",
+                    ),
+                );
+                had_fails = true;
+            }
+        }
+
+        if had_fails {
+            Err(Error::Reported)
+        } else {
+            Ok(())
+        }
+    }
+
+    /// Unpack the parameters of a subprogram from a list of interface
+    /// declarations.
+    ///
+    /// Parameters of mode `in` without an explicit class are constants. Signal
+    /// parameters are lowered like ports. See IEEE 1076-2008 section 4.2.2.1.
+    pub fn unpack_params(
+        &self,
+        scope_id: ScopeRef,
+        kind: hir::SubprogKind,
+        decls: &'ast [ast::IntfDecl],
+        into: &mut Vec<IntfObjRef>,
+    ) -> Result<()> {
+        let ctx = AddContext::new(self, scope_id);
+        let mut had_fails = false;
+        for decl in decls {
+            match *decl {
+                ast::IntfDecl::ObjDecl(ref decl) => {
+                    let is_const = match (decl.kind, decl.mode) {
+                        (ast::IntfObjKind::Const, _) => true,
+                        (ast::IntfObjKind::Var, None)
+                        | (ast::IntfObjKind::Var, Some(ast::IntfMode::In)) => true,
+                        _ => false,
+                    };
+                    let is_signal = decl.kind == ast::IntfObjKind::Signal;
+                    let bad_mode = match decl.mode {
+                        Some(ast::IntfMode::Buffer) => Some("buffer"),
+                        Some(ast::IntfMode::Linkage) => Some("linkage"),
+                        _ => None,
+                    };
+                    if let Some(mode) = bad_mode {
+                        self.emit(
+                            DiagBuilder2::error(format!(
+                                "parameters of a subprogram cannot be of mode `{}`",
+                                mode
+                            ))
+                            .span(decl.span)
+                            .add_note(
+                                "Subprogram parameters must be of mode `in`, `out`, or `inout`. \
+                                 See IEEE 1076-2008 section 4.2.2.1.",
+                            ),
+                        );
+                        had_fails = true;
+                        continue;
+                    }
+                    if !is_const && kind != hir::SubprogKind::Proc {
+                        if decl.mode.is_some() && decl.mode != Some(ast::IntfMode::In) {
+                            self.emit(
+                                DiagBuilder2::error(
+                                    "parameters of a function must be of mode `in`",
+                                )
+                                .span(decl.span)
+                                .add_note(
+                                    "Functions can only have parameters of mode `in`. Use a \
+                                     procedure instead. See IEEE 1076-2008 section 4.2.2.1.",
+                                ),
+                            );
+                            had_fails = true;
+                            continue;
+                        }
+                    }
+                    if !is_const && !is_signal {
+                        let what = match decl.kind {
+                            ast::IntfObjKind::File => "file parameters",
+                            _ => "variable parameters of mode `out` or `inout`",
+                        };
+                        self.emit(
+                            DiagBuilder2::error(format!("{} are not supported", what))
+                                .span(decl.span),
+                        );
+                        had_fails = true;
+                        continue;
+                    }
+                    if decl.default.is_some() {
+                        unimp_msg!(self, "default values of subprogram parameters", decl.span);
+                    }
+                    let ty = ctx.add_subtype_ind(&decl.ty)?;
+                    for name in &decl.names {
+                        if is_signal {
+                            let id = IntfSignalRef(NodeId::alloc());
+                            self.set_ast(id, (scope_id, decl, ty, name));
+                            into.push(id.into());
+                        } else {
+                            let id = IntfConstRef(NodeId::alloc());
+                            self.set_ast(id, (scope_id, decl, ty, name));
+                            into.push(id.into());
+                        }
+                    }
+                }
+                ref wrong => {
+                    self.emit(
+                        DiagBuilder2::error(format!(
+                            "a {} cannot appear in a parameter list",
+                            wrong.desc()
+                        ))
+                        .span(wrong.human_span()),
+                    );
+                    had_fails = true;
+                }
+            }
+        }
+        if had_fails {
+            Err(Error::Reported)
         } else {
             Ok(())
         }
@@ -1074,7 +1326,7 @@ impl_make!(self, id: EntityRef => &hir::Entity {
             }
         }
     }
-    // TODO(strict): Complain about multiple port and generic clauses.
+    self.check_portgen_clauses(&ast.decls)?;
     // TODO(strict): Complain when port and generic clauses are not the
     // first in the entity.
     Ok(self.sb.arenas.hir.entity.alloc(entity))
@@ -1127,6 +1379,7 @@ impl_make!(self, id: PkgDeclRef => &hir::Package {
                     ast::SubprogData::Decl => {
                         let subid = SubprogDeclRef(NodeId::alloc());
                         self.set_ast(subid, (scope, decl));
+                        self.define(scope, self.resolvable_from_primary_name(&decl.spec.name)?, Def::Subprog(subid))?;
                         decls.push(subid.into());
                     }
                     ast::SubprogData::Body{..} => {
@@ -1164,12 +1417,16 @@ impl_make!(self, id: PkgDeclRef => &hir::Package {
             ast::DeclItem::SubtypeDecl(ref decl) => {
                 let subid = SubtypeDeclRef(NodeId::alloc());
                 self.set_ast(subid, (scope, decl));
+                self.define(scope, decl.name.map_into(), Def::Subtype(subid))?;
                 decls.push(subid.into());
             }
             ast::DeclItem::ObjDecl(ref decl) => {
                 match decl.kind {
                     ast::ObjKind::Const => {
-                        decls.extend(ctx.add_const_decl::<DeclInPkgRef>(decl)?);
+                        for (name, subid) in decl.names.iter().zip(ctx.add_const_decl::<ConstDeclRef>(decl)?) {
+                            self.define(scope, Spanned::new(name.name.into(), name.span), Def::Const(subid))?;
+                            decls.push(subid.into());
+                        }
                     }
                     ast::ObjKind::Signal => {
                         decls.extend(ctx.add_signal_decl::<DeclInPkgRef>(decl)?);
@@ -1245,7 +1502,7 @@ impl_make!(self, id: PkgDeclRef => &hir::Package {
     }
 
     if had_fails {
-        Err(())
+        Err(Error::Reported)
     } else {
         Ok(self.sb.arenas.hir.package.alloc(hir::Package{
             parent: outer_scope,
@@ -1259,10 +1516,38 @@ impl_make!(self, id: PkgDeclRef => &hir::Package {
 // Lower a package body to HIR.
 impl_make!(self, id: PkgBodyRef => &hir::PackageBody {
     let (scope_id, ast) = self.ast(id);
-    let pkg = self.unpack_package_name((&ast.name).into(), scope_id)?;
+
+    // Find the package the body belongs to. A body at the top of a file is
+    // looked up among the primary units of its library, all of which are
+    // known before any secondary unit is lowered. The body may thus precede
+    // its package.
+    let lookup_scope = match scope_id {
+        ScopeRef::CtxItems(ctx_id) => self.ast(ctx_id).0,
+        other => other,
+    };
+    let pkg = match self.defs(lookup_scope)?.get(&ast.name.value.into()).and_then(|d| d.last()) {
+        Some(&Spanned{ value: Def::Pkg(pkg), .. }) => Spanned::new(pkg, ast.name.span),
+        Some(def) => {
+            self.emit(
+                DiagBuilder2::error(format!("`{}` is not a package", ast.name.value))
+                .span(ast.name.span)
+                .add_note(format!("`{}` defined here:", ast.name.value))
+                .span(def.span)
+            );
+            return Err(Error::Reported);
+        }
+        None => {
+            self.emit(
+                DiagBuilder2::error(format!("no package named `{}` found", ast.name.value))
+                .span(ast.name.span)
+            );
+            return Err(Error::Reported);
+        }
+    };
+
     let mut decls = Vec::new();
     let mut had_fails = false;
-    let ctx = AddContext::new(self, scope_id);
+    let ctx = AddContext::new(self, id.into());
     for decl in &ast.decls {
         match *decl {
             ast::DeclItem::SubprogDecl(ref decl) => {
@@ -1338,19 +1623,19 @@ impl_make!(self, id: PkgBodyRef => &hir::PackageBody {
             }
             ast::DeclItem::AliasDecl(ref decl) => {
                 let subid = AliasDeclRef(NodeId::alloc());
-                self.set_ast(subid, (scope_id, decl));
+                self.set_ast(subid, (id.into(), decl));
                 decls.push(subid.into());
             }
             ast::DeclItem::AttrDecl(ref decl) => {
                 match decl.data {
                     ast::AttrData::Decl(..) => {
                         let subid = AttrDeclRef(NodeId::alloc());
-                        self.set_ast(subid, (scope_id, decl));
+                        self.set_ast(subid, (id.into(), decl));
                         decls.push(subid.into());
                     }
                     ast::AttrData::Spec{..} => {
                         let subid = AttrSpecRef(NodeId::alloc());
-                        self.set_ast(subid, (scope_id, decl));
+                        self.set_ast(subid, (id.into(), decl));
                         decls.push(subid.into());
                     }
                 }
@@ -1359,12 +1644,12 @@ impl_make!(self, id: PkgBodyRef => &hir::PackageBody {
                 match decl.data {
                     ast::GroupData::Decl(..) => {
                         let subid = GroupDeclRef(NodeId::alloc());
-                        self.set_ast(subid, (scope_id, decl));
+                        self.set_ast(subid, (id.into(), decl));
                         decls.push(subid.into());
                     }
                     ast::GroupData::Temp{..} => {
                         let subid = GroupTempRef(NodeId::alloc());
-                        self.set_ast(subid, (scope_id, decl));
+                        self.set_ast(subid, (id.into(), decl));
                         decls.push(subid.into());
                     }
                 }
@@ -1382,7 +1667,7 @@ impl_make!(self, id: PkgBodyRef => &hir::PackageBody {
     }
 
     if had_fails {
-        Err(())
+        Err(Error::Reported)
     } else {
         Ok(self.sb.arenas.hir.package_body.alloc(hir::PackageBody {
             parent: scope_id,
@@ -1498,7 +1783,7 @@ impl_make!(self, id: SubtypeDeclRef => &hir::SubtypeDecl {
 //                         );
 //                         // TODO: Print the required type and the type of what
 //                         // has been found.
-//                         return Err(());
+//                         return Err(Error::Reported);
 //                     }
 //                     filtered
 //                 } else {
@@ -1518,7 +1803,7 @@ impl_make!(self, id: SubtypeDeclRef => &hir::SubtypeDecl {
 //                     DiagBuilder2::error(format!("`{}` is ambiguous", matched_span.extract()))
 //                     .span(matched_span)
 //                 );
-//                 return Err(());
+//                 return Err(Error::Reported);
 //             };
 //
 //             // Create the expression representation of the definition.
@@ -1575,7 +1860,7 @@ impl_make!(self, id: SubtypeDeclRef => &hir::SubtypeDecl {
 //                             DiagBuilder2::error("`.all` in an expression")
 //                             .span(span)
 //                         );
-//                         return Err(());
+//                         return Err(Error::Reported);
 //                     }
 //
 //                     // Disallow ranges in expressions.
@@ -1584,7 +1869,7 @@ impl_make!(self, id: SubtypeDeclRef => &hir::SubtypeDecl {
 //                             DiagBuilder2::error("range in an expression")
 //                             .span(expr.span)
 //                         );
-//                         return Err(());
+//                         return Err(Error::Reported);
 //                     }
 //                 }
 //             }
@@ -1599,7 +1884,7 @@ impl_make!(self, id: SubtypeDeclRef => &hir::SubtypeDecl {
 //                 DiagBuilder2::error("invalid expression")
 //                 .span(ast.span)
 //             );
-//             return Err(());
+//             return Err(Error::Reported);
 //         }
 //     };
 //     Ok(self.sb.arenas.hir.expr.alloc(hir::Expr{
@@ -1632,7 +1917,7 @@ impl_make!(self, id: SubtypeDeclRef => &hir::SubtypeDecl {
 //                             DiagBuilder2::error("Invalid range expression")
 //                             .span(range_expr.span)
 //                         );
-//                         return Err(());
+//                         return Err(Error::Reported);
 //                     }
 //                 };
 //                 if let Some(ref units) = *units {
@@ -1650,7 +1935,7 @@ impl_make!(self, id: SubtypeDeclRef => &hir::SubtypeDecl {
 //                                 .span(ast.span)
 //                                 .add_note("A physical type must have a primary unit of the form `<name>;`. See IEEE 1076-2008 section 5.2.4.")
 //                             );
-//                             return Err(());
+//                             return Err(Error::Reported);
 //                         }
 //                     };
 //                     let mut had_fails = false;
@@ -1663,7 +1948,7 @@ impl_make!(self, id: SubtypeDeclRef => &hir::SubtypeDecl {
 //                         had_fails = true;
 //                     }
 //                     if had_fails {
-//                         return Err(());
+//                         return Err(Error::Reported);
 //                     }
 //                     debugln!("primary unit {:#?}", primary);
 //
@@ -1683,7 +1968,7 @@ impl_make!(self, id: SubtypeDeclRef => &hir::SubtypeDecl {
 //                                             .span(term.span)
 //                                         );
 //                                         debugln!("It is a {:#?}", term.value);
-//                                         return Err(());
+//                                         return Err(Error::Reported);
 //                                     }
 //                                 };
 //                                 if unit.value.0 != id {
@@ -1827,7 +2112,11 @@ impl_make!(self, id: ArchRef => &hir::Arch {
     let (lib_id, ctx_id, ast) = self.ast(id);
     let decls = self.unpack_block_decls(id.into(), &ast.decls, "an architecture")?;
     let stmts = self.unpack_concurrent_stmts(id.into(), &ast.stmts, "an architecture")?;
-    let entity_id = *self.archs(lib_id)?.by_arch.get(&id).unwrap();
+    let spec_entity = self.sb.spec_entities.borrow().get(&id).map(|&(entity, _)| entity);
+    let entity_id = match spec_entity {
+        Some(entity) => entity,
+        None => *self.archs(lib_id)?.by_arch.get(&id).unwrap(),
+    };
     Ok(self.sb.arenas.hir.arch.alloc(hir::Arch{
         ctx_items: ctx_id,
         entity: entity_id,
@@ -1841,20 +2130,26 @@ impl_make!(self, id: ProcessStmtRef => &hir::ProcessStmt {
     let (scope_id, ast) = self.ast(id);
     match ast.data {
         ast::ProcStmt {
-            // ref sensitivity,
+            ref sensitivity,
             ref decls,
             ref stmts,
             postponed,
-            ..
         } => {
-            // TODO: map sensititivty
+            let sensitivity = match *sensitivity {
+                None => hir::ProcessSensitivity::None,
+                Some(ast::Sensitivity::All) => hir::ProcessSensitivity::All,
+                Some(ast::Sensitivity::List(ref names)) => hir::ProcessSensitivity::List(
+                    AddContext::new(self, scope_id)
+                        .add_sensitivity_list(Spanned::new(names.iter(), ast.span))?,
+                ),
+            };
             let decls = self.unpack_process_decls(id.into(), decls, "a process")?;
             let stmts = self.unpack_sequential_stmts(id.into(), stmts, "a process")?;
             Ok(self.sb.arenas.hir.process_stmt.alloc(hir::ProcessStmt {
                 parent: scope_id,
                 label: ast.label,
                 postponed: postponed,
-                sensitivity: hir::ProcessSensitivity::None,
+                sensitivity: sensitivity,
                 decls: decls,
                 stmts: stmts,
             }))
@@ -1863,42 +2158,6 @@ impl_make!(self, id: ProcessStmtRef => &hir::ProcessStmt {
     }
 });
 
-impl_make!(self, id: SigAssignStmtRef => &hir::SigAssignStmt {
-    let (scope_id, ast) = self.ast(id);
-    match ast.data {
-        ast::AssignStmt {
-            target: Spanned{ value: ref target, span: target_span },
-            ref mode,
-            guarded,
-            ..
-        } => {
-            let target = self.unpack_signal_assign_target(scope_id, target)?;
-            let tyctx = match target {
-                hir::SigAssignTarget::Name(id) => TypeCtx::TypeOf(id.into()),
-                hir::SigAssignTarget::Aggregate => unimplemented!(),
-            };
-            let kind = self.unpack_signal_assign_mode(scope_id, mode, &tyctx)?;
-            if guarded {
-                self.emit(
-                    DiagBuilder2::warning("sequential signal assignment cannot be guarded")
-                    .span(ast.human_span())
-                    .add_note("Only concurrent signal assignments can be guarded. See IEEE 1076-2008 section 11.6.")
-                );
-            }
-            Ok(self.sb.arenas.hir.sig_assign_stmt.alloc(hir::SigAssignStmt {
-                parent: scope_id,
-                span: ast.span,
-                label: ast.label,
-                target: target,
-                target_span: target_span,
-                kind: kind.value,
-                kind_span: kind.span,
-            }))
-        }
-        _ => unreachable!()
-    }
-});
-
 impl_make!(self, id: ArrayTypeIndexRef => &Spanned<hir::ArrayTypeIndex> {
     let (scope_id, ast) = self.ast(id);
     let ctx = TermContext::new(self, scope_id);
@@ -1926,7 +2185,7 @@ impl_make!(self, id: ArrayTypeIndexRef => &Spanned<hir::ArrayTypeIndex> {
                 .span(term.span)
             );
             debugln!("It is a {:#?}", term);
-            return Err(());
+            return Err(Error::Reported);
         }
     };
     Ok(self.sb.arenas.hir.array_type_index.alloc(Spanned::new(index, ast.span)))
@@ -2008,3 +2267,43 @@ impl_make!(self, id: LatentTypeMarkRef => Spanned<TypeMarkRef> {
     let term = ctx.termify_latent_name(ast)?;
     ctx.term_to_type_mark(term)
 });
+
+/// Find the names among `decls` which the default value of an interface
+/// declaration refers to.
+///
+/// Returns each use along with the location of the matching declaration.
+fn intf_default_uses<'a>(
+    intf: &'a ast::IntfObjDecl,
+    decls: impl Iterator<Item = &'a ast::Ident>,
+) -> Vec<(Spanned<Name>, Span)> {
+    let default = match intf.default {
+        Some(ref default) => default,
+        None => return vec![],
+    };
+    let mut finder = NameFinder(vec![]);
+    default.walk(&mut finder);
+    let decls: Vec<_> = decls.collect();
+    finder
+        .0
+        .into_iter()
+        .filter_map(|used| {
+            decls
+                .iter()
+                .find(|decl| decl.name == used.value)
+                .map(|decl| (used, decl.span))
+        })
+        .collect()
+}
+
+/// Collects the simple names an AST node refers to. Selected names such as
+/// `a.b` only contribute their prefix `a`.
+struct NameFinder(Vec<Spanned<Name>>);
+
+impl<'a> Visitor<'a> for NameFinder {
+    fn pre_visit_compound_name(&mut self, node: &'a ast::CompoundName) -> bool {
+        if let ast::PrimaryNameKind::Ident(name) = node.primary.kind {
+            self.0.push(Spanned::new(name, node.primary.span));
+        }
+        true
+    }
+}
@@ -12,14 +12,14 @@ use std::fmt::Debug;
 
 use moore_common::errors::*;
 use moore_common::name::*;
-use moore_common::score::{GenericContext, NodeMaker, NodeStorage, Result};
+use moore_common::score::{CancelToken, Error, GenericContext, NodeMaker, NodeStorage, Result};
 use moore_common::source::*;
 use moore_common::util::{HasDesc, HasSpan};
 use moore_common::NodeId;
 use moore_common::{Session, Verbosity};
 
 use llhd;
-use num::{BigInt, Signed};
+use num::{BigInt, Signed, ToPrimitive};
 use typed_arena::Arena;
 
 use crate::arenas::Alloc;
@@ -31,6 +31,7 @@ use crate::konst::*;
 use crate::lazy::*;
 use crate::op::*;
 use crate::syntax::ast;
+use crate::trace::Pass;
 use crate::ty::*;
 use crate::typeck::{Typeck, TypeckContext};
 
@@ -67,6 +68,8 @@ pub struct ScoreContext<'lazy, 'sb: 'lazy, 'ast: 'sb, 'ctx: 'sb> {
     pub sb: &'sb ScoreBoard<'ast, 'ctx>,
     /// The table of scheduled operations.
     pub lazy: &'lazy LazyPhaseTable<'sb, 'ast, 'ctx>,
+    /// The token through which running queries are cancelled.
+    pub cancel: &'lazy CancelToken,
 }
 
 /// The VHDL scoreboard that keeps track of compilation results.
@@ -89,8 +92,46 @@ pub struct ScoreBoard<'ast, 'ctx> {
     def_table: RefCell<HashMap<ScopeRef, &'ctx Defs>>,
     /// A table of architecture per entity and library.
     arch_table: RefCell<HashMap<LibRef, &'ctx ArchTable>>,
+    /// Whether resolved names are recorded in `resolved_names`. Only names
+    /// resolved after this has been set are recorded, so it must be set
+    /// before the design is analyzed.
+    pub record_names: Cell<bool>,
+    /// The definitions each resolved name refers to, by the span of the name.
+    /// This is used to find the uses of a declaration.
+    pub resolved_names: RefCell<HashMap<Span, Vec<Spanned<Def>>>>,
     /// The LLHD module into which code is emitted.
     pub llmod: RefCell<llhd::Module>,
+    /// The verification directives of the emitted entities. LLHD has no
+    /// notion of assertions, so these are kept alongside the module.
+    pub directives: RefCell<Vec<crate::codegen::Directive>>,
+    /// The LLHD values of the ports and signals of the emitted architectures,
+    /// through which the processes of an architecture read and drive them.
+    pub signals: RefCell<HashMap<SignalRef, llhd::ValueRef>>,
+    /// The LLHD values through which the processes drive the `inout` ports of
+    /// the emitted architectures. These ports are read through `signals`.
+    pub drivers: RefCell<HashMap<SignalRef, llhd::ValueRef>>,
+    /// The LLHD values of the shared variables of the emitted architectures.
+    pub shared_vars: RefCell<HashMap<VarDeclRef, llhd::ValueRef>>,
+    /// The LLHD values of the variables of the emitted processes, which point
+    /// to the memory holding their value.
+    pub vars: RefCell<HashMap<VarDeclRef, llhd::ValueRef>>,
+    /// The names and LLHD functions generated for the subprogram bodies called
+    /// by the emitted processes.
+    pub funcs: RefCell<HashMap<SubprogBodyRef, (String, llhd::ValueRef)>>,
+    /// The LLHD functions standing in for the runtime functions called by the
+    /// emitted processes, by name.
+    pub runtime_funcs: RefCell<HashMap<String, llhd::ValueRef>>,
+    /// The LLHD values of the parameters of the emitted functions.
+    pub params: RefCell<HashMap<IntfConstRef, llhd::ValueRef>>,
+    /// The values of the generics of the elaborated entities, as given in the
+    /// generic map of their instance or by their default.
+    pub generics: RefCell<HashMap<IntfConstRef, &'ctx Const>>,
+    /// The specializations of each architecture for the generic maps of its
+    /// instances, together with the values given in the generic map.
+    pub arch_specs: RefCell<HashMap<ArchRef, Vec<(Vec<Option<&'ctx Const>>, ArchRef)>>>,
+    /// The entity of each specialized architecture, and the number of the
+    /// specialization.
+    pub spec_entities: RefCell<HashMap<ArchRef, (EntityRef, usize)>>,
     /// A table of LLHD declarations (i.e. prototypes). These are useful for
     /// example when an entity needs so be instantiated, for which only the
     /// signature of the entity is required, but not its full definition with
@@ -126,7 +167,20 @@ impl<'ast, 'ctx> ScoreBoard<'ast, 'ctx> {
             hir_table: RefCell::new(HirTable::new()),
             def_table: RefCell::new(HashMap::new()),
             arch_table: RefCell::new(HashMap::new()),
+            record_names: Cell::new(false),
+            resolved_names: RefCell::new(HashMap::new()),
             llmod: RefCell::new(llhd::Module::new()),
+            directives: RefCell::new(Vec::new()),
+            signals: RefCell::new(HashMap::new()),
+            drivers: RefCell::new(HashMap::new()),
+            shared_vars: RefCell::new(HashMap::new()),
+            vars: RefCell::new(HashMap::new()),
+            funcs: RefCell::new(HashMap::new()),
+            runtime_funcs: RefCell::new(HashMap::new()),
+            params: RefCell::new(HashMap::new()),
+            generics: RefCell::new(HashMap::new()),
+            arch_specs: RefCell::new(HashMap::new()),
+            spec_entities: RefCell::new(HashMap::new()),
             lldecl_table: RefCell::new(HashMap::new()),
             lldef_table: RefCell::new(HashMap::new()),
             ty_table: RefCell::new(HashMap::new()),
@@ -243,6 +297,7 @@ impl<'lazy, 'sb, 'ast, 'ctx> ScoreContext<'lazy, 'sb, 'ast, 'ctx> {
         if let Some(&node) = self.sb.hir_table.borrow().get(&id) {
             return Ok(node);
         }
+        self.cancel.check()?;
         if self.sess.opts.trace_scoreboard {
             debugln!("[SB][VHDL] make hir for {:?}", id);
         }
@@ -274,7 +329,7 @@ impl<'lazy, 'sb, 'ast, 'ctx> ScoreContext<'lazy, 'sb, 'ast, 'ctx> {
             Some(&node) => Ok(node),
             None => {
                 self.emit(DiagBuilder2::bug(format!("hir for {:?} should exist", id)));
-                Err(())
+                Err(Error::Reported)
             }
         }
     }
@@ -286,7 +341,7 @@ impl<'lazy, 'sb, 'ast, 'ctx> ScoreContext<'lazy, 'sb, 'ast, 'ctx> {
     /// the corresponding closure.
     pub fn lazy_hir<I, R>(&self, id: I) -> Result<&'ctx R>
     where
-        I: Copy + Debug,
+        I: Copy + Debug + Into<NodeId>,
         R: Debug + 'ctx,
         LazyHirTable<'sb, 'ast, 'ctx>: NodeStorage<
             I,
@@ -304,6 +359,7 @@ impl<'lazy, 'sb, 'ast, 'ctx> ScoreContext<'lazy, 'sb, 'ast, 'ctx> {
 
         // Otherwise run the task scheduled in the lazy HIR table, then store
         // the result.
+        self.cancel.check()?;
         let hir = self.lazy.hir.run(id, self)?;
         let allocd = self.sb.arenas.hir.alloc(hir);
         self.sb.hir_table.borrow_mut().set(id, allocd);
@@ -315,6 +371,7 @@ impl<'lazy, 'sb, 'ast, 'ctx> ScoreContext<'lazy, 'sb, 'ast, 'ctx> {
         if let Some(&node) = self.sb.def_table.borrow().get(&id) {
             return Ok(node);
         }
+        self.cancel.check()?;
         if self.sess.opts.trace_scoreboard {
             debugln!("[SB][VHDL] make defs for {:?}", id);
         }
@@ -332,6 +389,7 @@ impl<'lazy, 'sb, 'ast, 'ctx> ScoreContext<'lazy, 'sb, 'ast, 'ctx> {
         if let Some(&node) = self.sb.arch_table.borrow().get(&id) {
             return Ok(node);
         }
+        self.cancel.check()?;
         if self.sess.opts.trace_scoreboard {
             debugln!("[SB][VHDL] make arch for {:?}", id);
         }
@@ -417,6 +475,7 @@ impl<'lazy, 'sb, 'ast, 'ctx> ScoreContext<'lazy, 'sb, 'ast, 'ctx> {
         if let Some(&node) = self.sb.typeval_table.borrow().get(&id.into()) {
             return node;
         }
+        self.cancel.check()?;
         if self.sess.opts.trace_scoreboard {
             debugln!("[SB][VHDL] make ty for {:?}", id);
         }
@@ -435,7 +494,7 @@ impl<'lazy, 'sb, 'ast, 'ctx> ScoreContext<'lazy, 'sb, 'ast, 'ctx> {
                 "type for {:?} already in the scoreboard",
                 id
             )));
-            return Err(());
+            return Err(Error::Reported);
         }
         Ok(node)
     }
@@ -446,14 +505,15 @@ impl<'lazy, 'sb, 'ast, 'ctx> ScoreContext<'lazy, 'sb, 'ast, 'ctx> {
     /// of that operation. Otherwise runs the task scheduled in the lazy table.
     pub fn lazy_typeck<I>(&self, id: I) -> Result<()>
     where
-        I: Into<NodeId>,
+        I: Into<NodeId> + Copy + Debug,
     {
         let ctx = TypeckContext::new(self);
         ctx.lazy_typeck(id);
         if ctx.finish() {
             Ok(())
         } else {
-            Err(())
+            self.cancel.check()?;
+            Err(Error::Reported)
         }
     }
 
@@ -464,14 +524,15 @@ impl<'lazy, 'sb, 'ast, 'ctx> ScoreContext<'lazy, 'sb, 'ast, 'ctx> {
     /// table.
     pub fn lazy_typeval<I>(&self, id: I) -> Result<&'ctx Ty>
     where
-        I: Into<NodeId>,
+        I: Into<NodeId> + Copy + Debug,
     {
         let ctx = TypeckContext::new(self);
         let result = ctx.lazy_typeval(id);
         if ctx.finish() {
             result
         } else {
-            Err(())
+            self.cancel.check()?;
+            Err(Error::Reported)
         }
     }
 
@@ -479,6 +540,7 @@ impl<'lazy, 'sb, 'ast, 'ctx> ScoreContext<'lazy, 'sb, 'ast, 'ctx> {
         if let Some(node) = self.sb.scope_table.borrow().get(&id.into()).cloned() {
             return Ok(node);
         }
+        self.cancel.check()?;
         if self.sess.opts.trace_scoreboard {
             debugln!("[SB][VHDL] make scope for {:?}", id);
         }
@@ -500,10 +562,41 @@ impl<'lazy, 'sb, 'ast, 'ctx> ScoreContext<'lazy, 'sb, 'ast, 'ctx> {
         if let Some(node) = self.sb.const_table.borrow().get(&id.into()).cloned() {
             return Ok(node);
         }
+        self.cancel.check()?;
         if self.sess.opts.trace_scoreboard {
             debugln!("[SB][VHDL] make const for {:?}", id);
         }
-        let node = self.make(id)?;
+        // Prefer an evaluation scheduled via the make context over the eager
+        // node maker.
+        let task = {
+            let mut table = self.lazy.const_eval.borrow_mut();
+            if table.contains_key(&id.into()) {
+                table.insert(id.into(), LazyNode::Running)
+            } else {
+                None
+            }
+        };
+        let node = match task {
+            Some(LazyNode::Pending(f)) => {
+                self.lazy.record_forced(Pass::ConstEval, id.into());
+                let result = {
+                    let _trace = self.lazy.trace.begin(Pass::ConstEval, id);
+                    f(self)
+                };
+                // A cancelled evaluation is scheduled again, such that it can
+                // be retried later on.
+                if let Err(Error::Cancelled) = result {
+                    self.lazy
+                        .const_eval
+                        .borrow_mut()
+                        .insert(id.into(), LazyNode::Pending(f));
+                    return Err(Error::Cancelled);
+                }
+                result?
+            }
+            Some(LazyNode::Running) => panic!("recursion when evaluating constant {:?}", id),
+            None => self.make(id)?,
+        };
         if self.sess.opts.trace_scoreboard {
             debugln!("[SB][VHDL] const for {:?} is {:?}", id, node);
         }
@@ -519,6 +612,44 @@ impl<'lazy, 'sb, 'ast, 'ctx> ScoreContext<'lazy, 'sb, 'ast, 'ctx> {
         Ok(node)
     }
 
+    /// Generate code for a node into an entity.
+    ///
+    /// Runs the code generation scheduled via the make context, or falls back
+    /// to the node's `Codegen` implementation if none has been scheduled. The
+    /// scheduled callback remains available afterwards, since a node may be
+    /// emitted into more than one entity.
+    pub fn lazy_codegen<I>(&self, id: I, ctx: &mut llhd::Entity) -> Result<()>
+    where
+        I: Copy + Debug + Into<NodeId>,
+        Self: Codegen<I, llhd::Entity>,
+    {
+        self.cancel.check()?;
+        let task = {
+            let mut table = self.lazy.codegen.borrow_mut();
+            if table.contains_key(&id.into()) {
+                table.insert(id.into(), LazyNode::Running)
+            } else {
+                None
+            }
+        };
+        match task {
+            Some(LazyNode::Pending(f)) => {
+                self.lazy.record_forced(Pass::Codegen, id.into());
+                let result = {
+                    let _trace = self.lazy.trace.begin(Pass::Codegen, id);
+                    f(self, ctx)
+                };
+                self.lazy
+                    .codegen
+                    .borrow_mut()
+                    .insert(id.into(), LazyNode::Pending(f));
+                result
+            }
+            Some(LazyNode::Running) => panic!("recursion when generating code for {:?}", id),
+            None => self.codegen(id, ctx),
+        }
+    }
+
     /// Obtain the type context for an expression.
     ///
     /// Returns `None` if no context information is available.
@@ -722,7 +853,7 @@ impl<'lazy, 'sb, 'ast, 'ctx> ScoreContext<'lazy, 'sb, 'ast, 'ctx> {
                 self.emit(
                     DiagBuilder2::error(format!("`{}` is not known", name.value)).span(name.span),
                 );
-                Err(())
+                Err(Error::Reported)
             }
         } else {
             if self.sess.opts.trace_scoreboard {
@@ -734,6 +865,12 @@ impl<'lazy, 'sb, 'ast, 'ctx> ScoreContext<'lazy, 'sb, 'ast, 'ctx> {
                         .span(name.span),
                 );
             }
+            if self.sb.record_names.get() {
+                self.sb
+                    .resolved_names
+                    .borrow_mut()
+                    .insert(name.span, found_defs.clone());
+            }
             Ok(found_defs)
         }
     }
@@ -779,7 +916,7 @@ impl<'lazy, 'sb, 'ast, 'ctx> ScoreContext<'lazy, 'sb, 'ast, 'ctx> {
                             d = d.span(def.span);
                         }
                         self.emit(d);
-                        return Err(());
+                        return Err(Error::Reported);
                     };
 
                     // Make sure that we can map the definition to a scope
@@ -795,7 +932,7 @@ impl<'lazy, 'sb, 'ast, 'ctx> ScoreContext<'lazy, 'sb, 'ast, 'ctx> {
                                 DiagBuilder2::error(format!("cannot select into {:?}", d))
                                     .span(pn.span),
                             );
-                            return Err(());
+                            return Err(Error::Reported);
                         }
                     };
 
@@ -820,12 +957,12 @@ impl<'lazy, 'sb, 'ast, 'ctx> ScoreContext<'lazy, 'sb, 'ast, 'ctx> {
 
     /// Get the builtin type `standard.boolean`.
     pub fn builtin_boolean_type(&self) -> &'ctx Ty {
-        self.intern_ty(Ty::Null)
+        self.intern_ty(BOOLEAN_TYPE.named_ty())
     }
 
     /// Get the builtin type `standard.time`.
     pub fn builtin_time_type(&self) -> &'ctx Ty {
-        self.intern_ty(Ty::Null)
+        self.intern_ty(TIME_TYPE.named_ty())
     }
 
     /// Get the builtin type `standard.string`.
@@ -916,7 +1053,7 @@ impl<'lazy, 'sb, 'ast, 'ctx> NodeMaker<LibRef, &'ctx ArchTable>
             res.by_arch.insert(arch_ref, entity);
         }
         if had_fails {
-            Err(())
+            Err(Error::Reported)
         } else {
             Ok(self.sb.arenas.archs.alloc(res))
         }
@@ -937,14 +1074,16 @@ impl<'lazy, 'sb, 'ast, 'ctx> NodeMaker<ArchRef, DefValueRef>
     for ScoreContext<'lazy, 'sb, 'ast, 'ctx>
 {
     fn make(&self, id: ArchRef) -> Result<DefValueRef> {
-        // Type check the entire library where the architecture is defined in.
+        // Type check the architecture and its entity. The other units of the
+        // library are only checked as far as they are used, and the
+        // architectures of instantiated entities are generated and checked on
+        // demand as their instances are encountered. Elaborating one top thus
+        // leaves the unrelated units of a large library alone.
         let typeck_ctx = TypeckContext::new(self);
-        typeck_ctx.typeck(self.ast(id).0); // typeck the entire library
+        typeck_ctx.typeck(id);
         if !typeck_ctx.finish() {
-            return Err(());
+            return Err(Error::Reported);
         }
-        // self.typeck(id)?;
-        // self.typeck(self.ast(id).0)?; // typeck the entire library
 
         let hir = self.hir(id)?;
         let entity = self.hir(hir.entity)?;
@@ -955,6 +1094,8 @@ impl<'lazy, 'sb, 'ast, 'ctx> NodeMaker<ArchRef, DefValueRef>
         let mut out_tys = Vec::new();
         let mut in_names = Vec::new();
         let mut out_names = Vec::new();
+        let mut in_ports = Vec::new();
+        let mut out_ports = Vec::new();
         for &port in &entity.ports {
             let hir = self.hir(port)?;
             let ty = self.map_type(self.ty(hir.ty)?)?;
@@ -965,6 +1106,7 @@ impl<'lazy, 'sb, 'ast, 'ctx> NodeMaker<ArchRef, DefValueRef>
                 | hir::IntfSignalMode::Linkage => {
                     in_tys.push(ty.clone());
                     in_names.push(hir.name.value);
+                    in_ports.push(port);
                 }
                 _ => (),
             }
@@ -974,6 +1116,7 @@ impl<'lazy, 'sb, 'ast, 'ctx> NodeMaker<ArchRef, DefValueRef>
                 | hir::IntfSignalMode::Buffer => {
                     out_tys.push(ty.clone());
                     out_names.push(hir.name.value);
+                    out_ports.push(port);
                 }
                 _ => (),
             }
@@ -981,16 +1124,39 @@ impl<'lazy, 'sb, 'ast, 'ctx> NodeMaker<ArchRef, DefValueRef>
         let ty = llhd::entity_ty(in_tys, out_tys);
 
         // Create a new entity into which we will generate all the code.
-        let name = format!("{}_{}", entity.name.value, hir.name.value);
+        // Architectures specialized for a generic map receive a distinct name.
+        let mut name = format!("{}_{}", entity.name.value, hir.name.value);
+        if let Some(&(_, index)) = self.sb.spec_entities.borrow().get(&id) {
+            name.push_str(&format!(".generic{}", index));
+        }
+        let name = llhd_name(&name);
         let mut entity = llhd::Entity::new(name, ty);
 
         // Assign names to the arguments. This is merely cosmetic, but makes the
         // emitted LLHD easier to read.
         for (arg, &name) in entity.inputs_mut().iter_mut().zip(in_names.iter()) {
-            arg.set_name(name.as_str().to_owned());
+            arg.set_name(llhd_name(&name.as_str()));
         }
         for (arg, &name) in entity.outputs_mut().iter_mut().zip(out_names.iter()) {
-            arg.set_name(name.as_str().to_owned());
+            arg.set_name(llhd_name(&name.as_str()));
+        }
+
+        // Make the ports available to the processes in the architecture. The
+        // processes read `in` and `inout` ports through the entity inputs, and
+        // drive `out` and `inout` ports through the entity outputs. A `buffer`
+        // port is read back from the output it drives.
+        {
+            let mut signals = self.sb.signals.borrow_mut();
+            let mut drivers = self.sb.drivers.borrow_mut();
+            for (arg, &port) in entity.inputs().iter().zip(in_ports.iter()) {
+                signals.insert(port.into(), arg.as_ref().into());
+            }
+            for (arg, &port) in entity.outputs().iter().zip(out_ports.iter()) {
+                match self.hir(port)?.mode {
+                    hir::IntfSignalMode::Inout => drivers.insert(port.into(), arg.as_ref().into()),
+                    _ => signals.insert(port.into(), arg.as_ref().into()),
+                };
+            }
         }
 
         // Generate the code for the declarations in the architecture.
@@ -1011,33 +1177,219 @@ impl<'lazy, 'sb, 'ast, 'ctx> NodeMaker<ArchRef, DefValueRef>
 }
 
 impl<'lazy, 'sb, 'ast, 'ctx> ScoreContext<'lazy, 'sb, 'ast, 'ctx> {
+    /// Determine the value of a generic of an elaborated entity.
+    ///
+    /// This is the value given in the generic map of the instance, or the
+    /// default value of the generic if it is not associated. See IEEE
+    /// 1076-2008 section 14.3.
+    pub fn generic_value(&self, id: IntfConstRef, span: Span) -> Result<&'ctx Const> {
+        if let Some(&value) = self.sb.generics.borrow().get(&id) {
+            return Ok(value);
+        }
+        let (scope, decl, _, name) = self.ast(id);
+        let default = match (scope, &decl.default) {
+            (ScopeRef::Entity(_), Some(default)) => default,
+            (ScopeRef::Entity(_), None) => {
+                self.emit(
+                    DiagBuilder2::error(format!("generic `{}` has no value", name.name))
+                        .span(span)
+                        .add_note(
+                            "Associate the generic in the generic map of the instance, or give \
+                             it a default value:",
+                        )
+                        .span(name.span),
+                );
+                return Err(Error::Reported);
+            }
+            _ => {
+                self.emit(
+                    DiagBuilder2::error("expression does not have a constant value").span(span),
+                );
+                return Err(Error::Reported);
+            }
+        };
+        let expr = crate::add_ctx::AddContext::new(self, scope).add_expr(default)?;
+        self.set_type_context(expr, self.ty(id)?);
+        let value = self.generic_const(id, self.const_value(expr)?, default.span)?;
+        self.sb.generics.borrow_mut().insert(id, value);
+        Ok(value)
+    }
+
+    /// Convert a value to the type of a generic.
+    ///
+    /// Integers take on the type of the generic, such that they map to the
+    /// right width, and must lie within its range.
+    fn generic_const(
+        &self,
+        id: IntfConstRef,
+        value: &'ctx Const,
+        span: Span,
+    ) -> Result<&'ctx Const> {
+        match (self.deref_named_type(self.ty(id)?)?, value) {
+            (&Ty::Int(ref ty), &Const::Int(ref k)) => {
+                let (lo, hi) = match ty.dir {
+                    hir::Dir::To => (&ty.left_bound, &ty.right_bound),
+                    hir::Dir::Downto => (&ty.right_bound, &ty.left_bound),
+                };
+                if &k.value < lo || &k.value > hi {
+                    self.emit(
+                        DiagBuilder2::error(format!(
+                            "value {} is out of the range {} of generic `{}`",
+                            k.value,
+                            ty,
+                            self.ast(id).3.name
+                        ))
+                        .span(span),
+                    );
+                    return Err(Error::Reported);
+                }
+                Ok(self.intern_const(ConstInt::new(Some(ty.clone()), k.value.clone())))
+            }
+            _ => Ok(value),
+        }
+    }
+
+    /// Specialize an architecture for the generic map of an instance.
+    ///
+    /// The entity and the architecture are lowered anew, with the generics of
+    /// the entity set to the given values. Generics without a value keep their
+    /// default. Instances with the same values share a specialization.
+    pub fn specialize_arch(
+        &self,
+        arch: ArchRef,
+        values: Vec<Option<(&'ctx Const, Span)>>,
+    ) -> Result<ArchRef> {
+        let key: Vec<_> = values.iter().map(|v| v.map(|(k, _)| k)).collect();
+        if let Some(specs) = self.sb.arch_specs.borrow().get(&arch) {
+            if let Some(&(_, spec)) = specs.iter().find(|&&(ref k, _)| *k == key) {
+                return Ok(spec);
+            }
+        }
+
+        // Lower the entity anew, such that its generics and ports are
+        // distinct from the ones of the original entity. The context items
+        // are copied as well, since their scope refers to the entity.
+        let copy_ctx = |id: CtxItemsRef| {
+            let copy = CtxItemsRef(NodeId::alloc());
+            self.set_ast(copy, self.ast(id));
+            copy
+        };
+        let entity = self.hir(arch)?.entity;
+        let (lib, ctx_id, decl) = self.ast(entity);
+        let spec_entity = EntityRef(NodeId::alloc());
+        self.set_ast(spec_entity, (lib, copy_ctx(ctx_id), decl));
+        let generics = &self.hir(spec_entity)?.generics;
+        for (&generic, value) in generics.iter().zip(values.into_iter()) {
+            if let (GenericRef::Const(id), Some((value, span))) = (generic, value) {
+                let value = self.generic_const(id, value, span)?;
+                self.sb.generics.borrow_mut().insert(id, value);
+            }
+        }
+
+        // Lower the architecture anew within the specialized entity.
+        let (lib, ctx_id, body) = self.ast(arch);
+        let spec = ArchRef(NodeId::alloc());
+        self.set_ast(spec, (lib, copy_ctx(ctx_id), body));
+        let mut specs = self.sb.arch_specs.borrow_mut();
+        let specs = specs.entry(arch).or_insert_with(Vec::new);
+        self.sb
+            .spec_entities
+            .borrow_mut()
+            .insert(spec, (spec_entity, specs.len()));
+        specs.push((key, spec));
+        Ok(spec)
+    }
+
+    /// Specialize an architecture for generic values given from the outside.
+    ///
+    /// This is used to elaborate a top-level entity with overridden generics,
+    /// e.g. on the command line during a parameter sweep. Generics are matched
+    /// by name, ignoring case, and may only be overridden if they are integers.
+    /// Generics not mentioned in `overrides` keep their default.
+    pub fn override_generics(&self, arch: ArchRef, overrides: &[(Name, i64)]) -> Result<ArchRef> {
+        let entity = self.hir(arch)?.entity;
+        let generics = &self.hir(entity)?.generics;
+        let mut values = vec![None; generics.len()];
+        for &(name, value) in overrides {
+            let found = generics
+                .iter()
+                .enumerate()
+                .find_map(|(index, &generic)| match generic {
+                    GenericRef::Const(id)
+                        if self
+                            .ast(id)
+                            .3
+                            .name
+                            .as_str()
+                            .eq_ignore_ascii_case(&name.as_str()) =>
+                    {
+                        Some((index, id))
+                    }
+                    _ => None,
+                });
+            let (index, id) = match found {
+                Some(x) => x,
+                None => {
+                    self.emit(DiagBuilder2::error(format!(
+                        "no generic `{}` in entity `{}`",
+                        name,
+                        self.ast(entity).2.name.value
+                    )));
+                    return Err(Error::Reported);
+                }
+            };
+            let span = self.ast(id).3.span;
+            let ty = self.ty(id)?;
+            match *self.deref_named_type(ty)? {
+                Ty::Int(_) => (),
+                _ => {
+                    self.emit(
+                        DiagBuilder2::error(format!(
+                            "generic `{}` of type `{}` cannot be overridden with an integer",
+                            name, ty
+                        ))
+                        .span(span),
+                    );
+                    return Err(Error::Reported);
+                }
+            }
+            let value = self.intern_const(ConstInt::new(None, BigInt::from(value)));
+            values[index] = Some((value, span));
+        }
+        self.specialize_arch(arch, values)
+    }
+
     /// Calculate the implicit default value for a type.
     pub fn default_value_for_type(&self, ty: &Ty) -> Result<&'ctx Const> {
         match *ty {
             Ty::Named(_, ty) => self.default_value_for_type(self.ty(ty)?),
             Ty::Null => Ok(self.intern_const(Const::Null)),
-            Ty::Enum(ref _ty) => {
-                // TODO: Replace with the first literal in the enum.
-                Ok(self.intern_const(Const::Null))
+            // Scalars default to their leftmost value, which is `'U'` for
+            // `std_ulogic`. See IEEE 1076-2008 section 6.4.2.3.
+            Ty::Enum(ref ty) => Ok(self.intern_const(ConstEnum::new(ty.decl, 0))),
+            Ty::Physical(ref ty) => {
+                Ok(self.intern_const(ConstPhysical::new(ty.clone(), ty.base.left_bound.clone())))
             }
-            Ty::Physical(ref ty) => Ok(self.intern_const(ConstInt::new(
-                Some(ty.base.clone()),
-                ty.base.left_bound.clone(),
-            ))),
             Ty::Int(ref ty) => {
                 Ok(self.intern_const(ConstInt::new(Some(ty.clone()), ty.left_bound.clone())))
             }
+            Ty::Real(ref ty) => Ok(self.intern_const(ConstFloat::new(ty.left_bound))),
             Ty::UniversalInt => panic!("universal integer has no default value"),
+            Ty::UniversalReal => panic!("universal real has no default value"),
             Ty::UnboundedInt => panic!("unbounded integer has no default value"),
             Ty::Subprog(..) => panic!("subprogram type has no default value"),
             Ty::Access(_) => Ok(self.intern_const(Const::Null)),
+            // Composite types default to the default value of each element.
             Ty::Array(ref ty) => {
-                self.emit(DiagBuilder2::bug(format!(
-                    "default value for type `{}` not implemented",
-                    ty
-                )));
-                // TODO: Use the correct default value.
-                Ok(self.intern_const(Const::Null))
+                let mut konst = self.default_value_for_type(&ty.element)?.clone();
+                for index in ty.indices.iter().rev() {
+                    let len = self.array_index_len(ty, index)?;
+                    if len == 0 {
+                        return Ok(self.intern_const(Const::Null));
+                    }
+                    konst = ConstArray::new(vec![konst; len]).into();
+                }
+                Ok(self.intern_const(konst))
             }
             Ty::File(ref ty) => {
                 self.emit(DiagBuilder2::bug(format!(
@@ -1048,14 +1400,259 @@ impl<'lazy, 'sb, 'ast, 'ctx> ScoreContext<'lazy, 'sb, 'ast, 'ctx> {
                 Ok(self.intern_const(Const::Null))
             }
             Ty::Record(ref ty) => {
-                self.emit(DiagBuilder2::bug(format!(
-                    "default value for type `{}` not implemented",
+                let fields = ty
+                    .fields
+                    .iter()
+                    .map(|&(_, ref ty)| Ok(self.default_value_for_type(ty)?.clone()))
+                    .collect::<Result<Vec<_>>>()?;
+                Ok(self.intern_const(ConstRecord::new(fields)))
+            }
+        }
+    }
+
+    /// Determine the number of literals of an enumeration type.
+    pub fn enum_len(&self, decl: TypeDeclRef) -> Result<usize> {
+        if let Some(len) = builtin::builtin_enum_len(decl) {
+            return Ok(len);
+        }
+        match self.lazy_hir(decl)?.data.as_ref().unwrap().value {
+            hir::TypeData::Enum(ref lits) => Ok(lits.len()),
+            _ => unreachable!(),
+        }
+    }
+
+    /// Determine the element of a record aggregate that provides the value of
+    /// each field, in the order the fields are declared in the record type.
+    ///
+    /// The aggregate must have been type checked against the record type,
+    /// which ensures that every field is assigned exactly once.
+    pub fn record_aggregate_fields(&self, id: AggregateRef, ty: &RecordTy) -> Result<Vec<ExprRef>> {
+        let hir = self.lazy_hir(id)?;
+        let mut fields: Vec<Option<ExprRef>> = vec![None; ty.fields.len()];
+        for (field, pos) in fields.iter_mut().zip(hir.positional.iter()) {
+            *field = Some(pos.value);
+        }
+        if let hir::AggregateKind::Record(ref named) = hir.named {
+            for elem in named {
+                for choice in &elem.value.0 {
+                    fields[ty.lookup[&choice.value]] = Some(elem.value.1.value);
+                }
+            }
+        }
+        Ok(fields
+            .into_iter()
+            .map(|field| field.or(hir.others.map(|e| e.value)).unwrap())
+            .collect())
+    }
+
+    /// Determine the element of an array aggregate that provides the value of
+    /// each element along the first index of the array type, from left to
+    /// right.
+    ///
+    /// The choices must be constant and within the index range, and every
+    /// element must be assigned, either explicitly or by an `others` choice.
+    pub fn array_aggregate_elements(&self, id: AggregateRef, ty: &ArrayTy) -> Result<Vec<ExprRef>> {
+        let hir = self.lazy_hir(id)?;
+        let len = self.array_index_len(ty, &ty.indices[0])?;
+        if hir.positional.len() > len {
+            self.emit(
+                DiagBuilder2::error(format!(
+                    "aggregate `{}` has {} elements, but array type `{}` only has {}",
+                    hir.span.extract(),
+                    hir.positional.len(),
+                    ty,
+                    len
+                ))
+                .span(hir.span),
+            );
+            return Err(Error::Reported);
+        }
+        let mut elements: Vec<Option<ExprRef>> = vec![None; len];
+        for (element, pos) in elements.iter_mut().zip(hir.positional.iter()) {
+            *element = Some(pos.value);
+        }
+        if let hir::AggregateKind::Array(ref named) = hir.named {
+            for elem in named {
+                for choice in &elem.value.0 {
+                    for index in self.array_choice_indices(ty, choice)? {
+                        elements[index] = Some(elem.value.1.value);
+                    }
+                }
+            }
+        }
+        if let Some(index) = elements
+            .iter()
+            .position(|element| element.is_none() && hir.others.is_none())
+        {
+            self.emit(
+                DiagBuilder2::error(format!(
+                    "aggregate `{}` does not assign element {} of array type `{}`",
+                    hir.span.extract(),
+                    index,
                     ty
-                )));
-                // TODO: Use the correct default value.
-                Ok(self.intern_const(Const::Null))
+                ))
+                .span(hir.span)
+                .add_note(
+                    "Add a value for every element, or an `others` choice. See IEEE 1076-2008 \
+                     section 9.3.3.3.",
+                ),
+            );
+            return Err(Error::Reported);
+        }
+        Ok(elements
+            .into_iter()
+            .map(|element| element.or(hir.others.map(|e| e.value)).unwrap())
+            .collect())
+    }
+
+    /// Determine the elements along the first index of an array type that an
+    /// aggregate choice selects. Elements are numbered from left to right.
+    fn array_choice_indices(
+        &self,
+        ty: &ArrayTy,
+        choice: &Spanned<hir::ArrayChoice>,
+    ) -> Result<Vec<usize>> {
+        // Enumeration literals are identified by their position.
+        let value_of = |expr: ExprRef| -> Result<BigInt> {
+            match *self.const_value(expr)? {
+                Const::Int(ref k) => Ok(k.value.clone()),
+                Const::Enum(ref k) => Ok(BigInt::from(k.index)),
+                ref k => {
+                    self.emit(
+                        DiagBuilder2::error(format!(
+                            "{} `{}` is not a valid choice",
+                            k.kind_desc(),
+                            k
+                        ))
+                        .span(choice.span),
+                    );
+                    Err(Error::Reported)
+                }
+            }
+        };
+        let (lo, hi) =
+            match choice.value {
+                hir::ArrayChoice::Expr(expr) => {
+                    let value = value_of(expr)?;
+                    (value.clone(), value)
+                }
+                hir::ArrayChoice::DiscreteRange(hir::DiscreteRange::Range(
+                    hir::Range::Immediate(dir, lb, rb),
+                )) => {
+                    let (lb, rb) = (value_of(lb)?, value_of(rb)?);
+                    match dir {
+                        hir::Dir::To => (lb, rb),
+                        hir::Dir::Downto => (rb, lb),
+                    }
+                }
+                hir::ArrayChoice::DiscreteRange(hir::DiscreteRange::Subtype(id)) => {
+                    match *self.deref_named_type(self.ty(id)?)? {
+                        Ty::Int(ref ty) => match ty.dir {
+                            hir::Dir::To => (ty.left_bound.clone(), ty.right_bound.clone()),
+                            hir::Dir::Downto => (ty.right_bound.clone(), ty.left_bound.clone()),
+                        },
+                        ref ty => {
+                            self.emit(
+                                DiagBuilder2::error(format!(
+                                    "choice of subtype `{}` not supported in array aggregates",
+                                    ty
+                                ))
+                                .span(choice.span),
+                            );
+                            return Err(Error::Reported);
+                        }
+                    }
+                }
+            };
+
+        // Map the chosen values to element positions, counted from the left
+        // bound of the index.
+        let mut indices = vec![];
+        let mut value = lo;
+        while value <= hi {
+            match self.array_index_position(ty, 0, &value)? {
+                Some(index) => indices.push(index),
+                None => {
+                    self.emit(
+                        DiagBuilder2::error(format!(
+                            "choice {} is out of the index range of array type `{}`",
+                            value, ty
+                        ))
+                        .span(choice.span),
+                    );
+                    return Err(Error::Reported);
+                }
             }
+            value = value + BigInt::from(1);
         }
+        Ok(indices)
+    }
+
+    /// Determine the indices of an indexed or slice name, e.g. `a(3)`. Each
+    /// index must be given as a positional expression.
+    pub fn index_exprs(&self, args: &Spanned<hir::AssocList>) -> Result<Vec<ExprRef>> {
+        let mut exprs = vec![];
+        for arg in &args.value {
+            match (arg.formal, &arg.actual.value) {
+                (None, &hir::AssocActual::Expr(expr)) => exprs.push(expr),
+                _ => {
+                    self.emit(
+                        DiagBuilder2::error(format!(
+                            "`{}` is not a valid index",
+                            arg.span.extract()
+                        ))
+                        .span(arg.span),
+                    );
+                    return Err(Error::Reported);
+                }
+            }
+        }
+        Ok(exprs)
+    }
+
+    /// Determine the value of a static index, i.e. an integer or the position
+    /// of an enumeration literal.
+    pub fn index_value(&self, expr: ExprRef) -> Result<BigInt> {
+        match *self.const_value(expr)? {
+            Const::Int(ref k) => Ok(k.value.clone()),
+            Const::Enum(ref k) => Ok(BigInt::from(k.index)),
+            ref k => {
+                let span = self.lazy_hir(expr)?.span;
+                self.emit(
+                    DiagBuilder2::error(format!("{} `{}` is not a valid index", k.kind_desc(), k))
+                        .span(span),
+                );
+                Err(Error::Reported)
+            }
+        }
+    }
+
+    /// Determine the left bound and the direction of an array index, from
+    /// which the positions of the elements are counted. Enumeration indices
+    /// count upwards from the position of the first literal.
+    pub fn array_index_left(&self, index: &ArrayIndex) -> Result<(BigInt, hir::Dir)> {
+        Ok(match *self.deref_named_type(index.ty())? {
+            Ty::Int(ref index) => (index.left_bound.clone(), index.dir),
+            _ => (BigInt::from(0), hir::Dir::To),
+        })
+    }
+
+    /// Determine the position of an element along an index of an array type,
+    /// counted from the left bound. Returns `None` if the value is outside of
+    /// the index range.
+    pub fn array_index_position(
+        &self,
+        ty: &ArrayTy,
+        dim: usize,
+        value: &BigInt,
+    ) -> Result<Option<usize>> {
+        let (left, dir) = self.array_index_left(&ty.indices[dim])?;
+        let len = self.array_index_len(ty, &ty.indices[dim])?;
+        let offset = match dir {
+            hir::Dir::To => value - &left,
+            hir::Dir::Downto => &left - value,
+        };
+        Ok(offset.to_usize().filter(|&index| index < len))
     }
 
     /// Internalize a constant.
@@ -1187,7 +1784,7 @@ impl ResolvableName {
                             .span(primary.span)
                             .add_note("see IEEE 1076-2008 section 9.2 for a list of operators"),
                     );
-                    Err(())
+                    Err(Error::Reported)
                 }
             },
         }
@@ -1322,6 +1919,7 @@ node_ref!(CompInstStmtRef);
 node_ref!(ForGenStmtRef);
 node_ref!(IfGenStmtRef);
 node_ref!(CaseGenStmtRef);
+node_ref!(GenBlockRef);
 node_ref!(ConstDeclRef);
 node_ref!(SignalDeclRef);
 node_ref!(VarDeclRef);
@@ -1341,6 +1939,8 @@ node_ref!(LatentTypeMarkRef);
 node_ref!(LatentPkgRef);
 node_ref!(LatentSubprogRef);
 node_ref!(BuiltinOpRef);
+node_ref!(BuiltinFnRef);
+node_ref!(BuiltinConstRef);
 
 /// A reference to an enumeration literal, expressed as the type declaration
 /// which defines the enumeration and the index of the literal.
@@ -1375,11 +1975,14 @@ node_ref_group!(
     PkgInst(PkgInstRef),
     BuiltinPkg(BuiltinPkgRef),
     BuiltinOp(BuiltinOpRef),
+    BuiltinFn(BuiltinFnRef),
+    BuiltinConst(BuiltinConstRef),
     Type(TypeDeclRef),
     Subtype(SubtypeDeclRef),
     Enum(EnumRef),
     Unit(UnitRef),
     Const(ConstDeclRef),
+    IntfConst(IntfConstRef),
     Signal(SignalRef),
     File(FileDeclRef),
     Var(VarDeclRef),
@@ -1389,8 +1992,10 @@ node_ref_group!(
     GroupTemp(GroupTempRef),
     Group(GroupDeclRef),
     Subprog(SubprogDeclRef),
+    SubprogBody(SubprogBodyRef),
     SubprogInst(SubprogInstRef),
     Stmt(StmtRef),
+    GenParam(GenBlockRef),
 );
 
 node_ref_group!(
@@ -1404,6 +2009,7 @@ node_ref_group!(
     Process(ProcessStmtRef),
     Subprog(SubprogDeclRef),
     SubprogBody(SubprogBodyRef),
+    GenBlock(GenBlockRef),
 );
 
 node_ref_group!(
@@ -1432,11 +2038,25 @@ impl From<TypeMarkRef> for Def {
     }
 }
 
-node_ref_group!(SignalRef: Intf(IntfSignalRef), Decl(SignalDeclRef),);
+node_ref_group!(SignalRef: Intf(IntfSignalRef), Decl(SignalDeclRef), Guard(GenBlockRef),);
 
 node_ref_group!(PkgRef: Decl(PkgDeclRef), Inst(PkgInstRef),);
 
-node_ref_group!(SubprogRef: Decl(SubprogDeclRef), Inst(SubprogInstRef),);
+node_ref_group!(
+    SubprogRef: Decl(SubprogDeclRef),
+    Body(SubprogBodyRef),
+    Inst(SubprogInstRef),
+);
+
+impl From<SubprogRef> for Def {
+    fn from(subprog: SubprogRef) -> Def {
+        match subprog {
+            SubprogRef::Decl(id) => id.into(),
+            SubprogRef::Body(id) => id.into(),
+            SubprogRef::Inst(id) => id.into(),
+        }
+    }
+}
 
 /// All declarations that may possibly appear in a package. See IEEE 1076-2008
 /// section 4.7.
@@ -1712,7 +2332,11 @@ node_ref_group!(
 node_ref_group!(StmtRef: Conc(ConcStmtRef), Seq(SeqStmtRef),);
 
 /// A reference to a node which has a type.
-node_ref_group!(TypedNodeRef: SubtypeInd(SubtypeIndRef), Signal(SignalRef),);
+node_ref_group!(
+    TypedNodeRef: SubtypeInd(SubtypeIndRef),
+    Signal(SignalRef),
+    Expr(ExprRef),
+);
 
 // Declare the node tables.
 node_storage!(AstTable<'ast>:
@@ -1755,7 +2379,6 @@ node_storage!(AstTable<'ast>:
 
     // Statements
     proc_stmts:       ProcessStmtRef   => (ScopeRef, &'ast ast::Stmt),
-    sig_assign_stmts: SigAssignStmtRef => (ScopeRef, &'ast ast::Stmt),
     var_assign_stmts: VarAssignStmtRef => (ScopeRef, &'ast ast::Stmt),
 
     array_type_indices: ArrayTypeIndexRef => (ScopeRef, &'ast ast::Expr),
@@ -1784,7 +2407,6 @@ node_storage!(HirTable<'ctx>:
     variable_decls:        VarDeclRef            => &'ctx hir::Decl<hir::VarDecl>,
     file_decls:            FileDeclRef           => &'ctx hir::Decl<hir::FileDecl>,
     process_stmts:         ProcessStmtRef        => &'ctx hir::ProcessStmt,
-    sig_assign_stmts:      SigAssignStmtRef      => &'ctx hir::SigAssignStmt,
     array_type_indices:    ArrayTypeIndexRef     => &'ctx Spanned<hir::ArrayTypeIndex>,
     subprogs:              SubprogDeclRef        => &'ctx hir::Subprog,
     subprog_bodies:        SubprogBodyRef        => &'ctx hir::SubprogBody,
@@ -1796,7 +2418,7 @@ node_storage!(HirTable<'ctx>:
     wait_stmts:            WaitStmtRef           => &'ctx hir::Stmt<hir::WaitStmt>,
     assert_stmts:          AssertStmtRef         => &'ctx hir::Stmt<hir::AssertStmt>,
     report_stmts:          ReportStmtRef         => &'ctx hir::Stmt<hir::ReportStmt>,
-    // sig_assign_stmts:      SigAssignStmtRef      => &'ctx hir::Stmt<hir::SigAssignStmt>,
+    sig_assign_stmts:      SigAssignStmtRef      => &'ctx hir::Stmt<hir::SigAssignStmt>,
     var_assign_stmts:      VarAssignStmtRef      => &'ctx hir::Stmt<hir::VarAssignStmt>,
     call_stmt:             CallStmtRef           => &'ctx hir::Stmt<hir::CallStmt>,
     if_stmt:               IfStmtRef             => &'ctx hir::Stmt<hir::IfStmt>,
@@ -1805,6 +2427,16 @@ node_storage!(HirTable<'ctx>:
     nexit_stmt:            NexitStmtRef          => &'ctx hir::Stmt<hir::NexitStmt>,
     return_stmt:           ReturnStmtRef         => &'ctx hir::Stmt<hir::ReturnStmt>,
     null_stmt:             NullStmtRef           => &'ctx hir::Stmt<hir::NullStmt>,
+    // Concurrent statements
+    block_stmts:           BlockStmtRef          => &'ctx hir::Stmt<hir::BlockStmt>,
+    conc_call_stmts:       ConcCallStmtRef       => &'ctx hir::Stmt<hir::CallStmt>,
+    conc_assert_stmts:     ConcAssertStmtRef     => &'ctx hir::Stmt<hir::ConcAssertStmt>,
+    conc_sig_assign_stmts: ConcSigAssignStmtRef  => &'ctx hir::Stmt<hir::ConcSigAssignStmt>,
+    comp_inst_stmts:       CompInstStmtRef       => &'ctx hir::Stmt<hir::CompInstStmt>,
+    for_gen_stmts:         ForGenStmtRef         => &'ctx hir::Stmt<hir::ForGenStmt>,
+    if_gen_stmts:          IfGenStmtRef          => &'ctx hir::Stmt<hir::IfGenStmt>,
+    case_gen_stmts:        CaseGenStmtRef        => &'ctx hir::Stmt<hir::CaseGenStmt>,
+    gen_blocks:            GenBlockRef           => &'ctx hir::GenBlock,
 );
 
 // node_storage!(LazyHirTable<'ast, 'ctx>:
@@ -1836,6 +2468,8 @@ lazy_static! {
             // );
             defs
         });
+        table.insert(*MATH_REAL_PKG_REF, MATH_REAL_PKG_SCOPE.defs.clone());
+        table.insert(*MATH_COMPLEX_PKG_REF, MATH_COMPLEX_PKG_SCOPE.defs.clone());
         table
     };
 }
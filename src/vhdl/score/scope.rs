@@ -29,6 +29,7 @@ impl_make_defs!(self, id: ScopeRef => {
         ScopeRef::Process(id)     => self.make(id),
         ScopeRef::Subprog(id)     => self.make(id),
         ScopeRef::SubprogBody(id) => self.make(id),
+        ScopeRef::GenBlock(id)    => self.make(id),
     }
 });
 
@@ -44,6 +45,7 @@ impl_make_scope!(self, id: ScopeRef => {
         ScopeRef::Process(id)     => self.make(id),
         ScopeRef::Subprog(id)     => self.make(id),
         ScopeRef::SubprogBody(id) => self.make(id),
+        ScopeRef::GenBlock(id)    => self.make(id),
     }
 });
 
@@ -88,7 +90,7 @@ impl_make_defs!(self, id: LibRef => {
         had_dups = true;
     }
     if had_dups {
-        return Err(());
+        return Err(Error::Reported);
     }
 
     // Return the definitions.
@@ -97,7 +99,7 @@ impl_make_defs!(self, id: LibRef => {
 
 // Definitions made by the context items that appear before design units.
 impl_make_defs!(self, id: CtxItemsRef => {
-    let (_, ast) = self.ast(id);
+    let (parent, ast) = self.ast(id);
     let mut defs = HashMap::new();
     let mut has_fails = false;
     for item in ast {
@@ -106,7 +108,16 @@ impl_make_defs!(self, id: CtxItemsRef => {
         match *item {
             ast::CtxItem::LibClause(Spanned{ value: ref names, .. }) => {
                 for ident in names {
-                    if let Some(&lib_id) = self.sb.lib_names.borrow().get(&ident.name) {
+                    // Fall back to the builtin `IEEE` library if no library
+                    // of that name has been added.
+                    let lib_id = self.sb.lib_names.borrow().get(&ident.name).cloned().or_else(|| {
+                        if ident.name == get_name_table().intern("IEEE", false) {
+                            Some(*IEEE_LIB_REF)
+                        } else {
+                            None
+                        }
+                    });
+                    if let Some(lib_id) = lib_id {
                         let defs = defs.entry(ident.name.into()).or_insert_with(||vec![]);
                         if !defs.is_empty() {
                             self.emit(
@@ -131,8 +142,14 @@ impl_make_defs!(self, id: CtxItemsRef => {
             _ => ()
         }
     }
+    // Every design unit implicitly refers to its own library as `WORK`. See
+    // IEEE 1076-2008 section 13.2.
+    if let ScopeRef::Lib(lib_id) = parent {
+        defs.entry(get_name_table().intern("WORK", false).into())
+            .or_insert_with(|| vec![Spanned::new(Def::Lib(lib_id), INVALID_SPAN)]);
+    }
     if has_fails {
-        Err(())
+        Err(Error::Reported)
     } else {
         Ok(self.sb.arenas.defs.alloc(defs))
     }
@@ -147,9 +164,12 @@ impl_make_scope!(self, _id: CtxItemsRef => {
 });
 
 // Definitions in an entity.
-impl_make_defs!(self, _id: EntityRef => {
-    // TODO: Implement this.
-    Ok(self.sb.arenas.defs.alloc(HashMap::new()))
+impl_make_defs!(self, id: EntityRef => {
+    let mut ctx = DefsContext::new(self);
+    let hir = self.hir(id)?;
+    ctx.declare_generics(&hir.generics);
+    ctx.declare_ports(&hir.ports);
+    Ok(self.sb.arenas.defs.alloc(ctx.finish()?))
 });
 
 // Definitions in an architecture.
@@ -245,6 +265,12 @@ impl<'lazy, 'sb, 'ast, 'ctx> ScoreContext<'lazy, 'sb, 'ast, 'ctx> {
                                 }) => {
                                     defs.push(id.into());
                                 }
+                                Some(Spanned {
+                                    value: Def::BuiltinPkg(id),
+                                    ..
+                                }) => {
+                                    defs.push(id.into());
+                                }
                                 Some(_) => {
                                     self.emit(
                                         DiagBuilder2::error(format!(
@@ -335,7 +361,7 @@ impl_make_scope!(self, id: PkgBodyRef => {
     let mut defs = Vec::new();
     defs.push(id.into());
     let parent = match hir.parent {
-        ScopeRef::CtxItems(id) => self.make_ctx_items_scope(id, None)?.into(),
+        ScopeRef::CtxItems(id) => self.make_ctx_items_scope(id, Some(hir.pkg.value.into()))?.into(),
         others => others
     };
     Ok(self.sb.arenas.scope.alloc(Scope{
@@ -397,6 +423,34 @@ impl_make_scope!(self, id: ProcessStmtRef => {
     }))
 });
 
+// Definitions in a generated block.
+impl_make_defs!(self, id: GenBlockRef => {
+    let mut ctx = DefsContext::new(self);
+    let hir = self.existing_hir(id)?;
+    if let Some(ref param) = hir.param {
+        ctx.declare(param.name.map_into(), Def::GenParam(id));
+    }
+    if hir.guard.is_some() {
+        let name = get_name_table().intern("GUARD", false);
+        ctx.declare(Spanned::new(name.into(), hir.span), Def::Signal(SignalRef::Guard(id)));
+    }
+    for &decl in &hir.decls {
+        ctx.declare_any_in_block(decl);
+    }
+    Ok(self.sb.arenas.defs.alloc(ctx.finish()?))
+});
+
+impl_make_scope!(self, id: GenBlockRef => {
+    let hir = self.existing_hir(id)?;
+    let mut defs = Vec::new();
+    defs.push(id.into());
+    Ok(self.sb.arenas.scope.alloc(Scope {
+        parent: Some(hir.parent),
+        defs: defs,
+        explicit_defs: HashMap::new(),
+    }))
+});
+
 // DeclInPkgRef::Pkg(id) => vec![(self.ast(id).1.name.map_into(), Def::Pkg(id))],
 // DeclInPkgRef::PkgInst(id) => vec![(self.ast(id).1.name.map_into(), Def::PkgInst(id))],
 // DeclInPkgRef::Type(id) => {
@@ -7,6 +7,8 @@ use std;
 use std::fmt;
 
 use crate::lexer::token::Literal;
+use crate::rewrite::{AcceptRewriter, WalkRewriter};
+use crate::visit::{AcceptVisitor, WalkVisitor};
 use moore_common::name::Name;
 use moore_common::source::{Span, Spanned};
 use moore_common::util::{HasDesc, HasSpan};
@@ -61,6 +63,8 @@ pub const DUMMY_NODE_ID: NodeId = NodeId(0);
 /// A design unit. Multiple design units make up a design file. Each unit
 /// consists of an optional context clause followed by a primary or secondary
 /// unit.
+#[moore_derive::visit_without_foreach]
+#[moore_derive::rewrite]
 #[derive(Clone, Debug, PartialEq, Eq)]
 pub struct DesignUnit {
     pub id: NodeId,
@@ -68,6 +72,8 @@ pub struct DesignUnit {
     pub data: DesignUnitData,
 }
 
+#[moore_derive::visit_without_foreach]
+#[moore_derive::rewrite]
 #[derive(Clone, Debug, PartialEq, Eq)]
 pub enum DesignUnitData {
     EntityDecl(EntityDecl),
@@ -80,6 +86,8 @@ pub enum DesignUnitData {
 }
 
 /// A context item, multiple of which make up a context clause.
+#[moore_derive::visit_without_foreach]
+#[moore_derive::rewrite]
 #[derive(Clone, Debug, PartialEq, Eq)]
 pub enum CtxItem {
     LibClause(Spanned<Vec<Ident>>),
@@ -88,6 +96,8 @@ pub enum CtxItem {
 }
 
 /// An identifier. Has a node ID such that it may be referenced later on.
+#[moore_derive::visit_without_foreach]
+#[moore_derive::rewrite]
 #[derive(Copy, Clone, Debug, PartialEq, Eq)]
 // #[deprecated(note="use `Spanned<Name>` instead of `Ident`")]
 pub struct Ident {
@@ -128,6 +138,8 @@ impl Into<Spanned<Name>> for Ident {
 /// | attribute_name      | `NamePart::Attribute`           |
 /// | external_name       | not implemented                 |
 ///
+#[moore_derive::visit_without_foreach]
+#[moore_derive::rewrite]
 #[derive(Clone, Debug, PartialEq, Eq)]
 pub struct CompoundName {
     pub id: NodeId,
@@ -148,6 +160,8 @@ impl HasDesc for CompoundName {
     }
 }
 
+#[moore_derive::visit_without_foreach]
+#[moore_derive::rewrite]
 #[derive(Copy, Clone, Debug, PartialEq, Eq)]
 pub struct PrimaryName {
     pub id: NodeId,
@@ -155,6 +169,8 @@ pub struct PrimaryName {
     pub kind: PrimaryNameKind,
 }
 
+#[moore_derive::visit_without_foreach]
+#[moore_derive::rewrite]
 #[derive(Copy, Clone, Debug, PartialEq, Eq)]
 pub enum PrimaryNameKind {
     Ident(Name),
@@ -162,6 +178,8 @@ pub enum PrimaryNameKind {
     String(Name),
 }
 
+#[moore_derive::visit_without_foreach]
+#[moore_derive::rewrite]
 #[derive(Clone, Debug, PartialEq, Eq)]
 pub enum NamePart {
     Select(PrimaryName),
@@ -173,6 +191,8 @@ pub enum NamePart {
 }
 
 /// A context declaration.
+#[moore_derive::visit_without_foreach]
+#[moore_derive::rewrite]
 #[derive(Clone, Debug, PartialEq, Eq)]
 pub struct CtxDecl {
     pub id: NodeId,
@@ -182,6 +202,8 @@ pub struct CtxDecl {
 }
 
 /// An entity declaration.
+#[moore_derive::visit_without_foreach]
+#[moore_derive::rewrite]
 #[derive(Clone, Debug, PartialEq, Eq)]
 pub struct EntityDecl {
     pub id: NodeId,
@@ -192,6 +214,8 @@ pub struct EntityDecl {
 }
 
 /// A configuration declaration.
+#[moore_derive::visit_without_foreach]
+#[moore_derive::rewrite]
 #[derive(Clone, Debug, PartialEq, Eq)]
 pub struct CfgDecl {
     pub id: NodeId,
@@ -202,6 +226,8 @@ pub struct CfgDecl {
 }
 
 /// An architecture body.
+#[moore_derive::visit_without_foreach]
+#[moore_derive::rewrite]
 #[derive(Clone, Debug, PartialEq, Eq)]
 pub struct ArchBody {
     pub id: NodeId,
@@ -213,6 +239,8 @@ pub struct ArchBody {
 }
 
 /// A package declaration.
+#[moore_derive::visit_without_foreach]
+#[moore_derive::rewrite]
 #[derive(Clone, Debug, PartialEq, Eq)]
 pub struct PkgDecl {
     pub id: NodeId,
@@ -222,6 +250,8 @@ pub struct PkgDecl {
 }
 
 /// A package body.
+#[moore_derive::visit_without_foreach]
+#[moore_derive::rewrite]
 #[derive(Clone, Debug, PartialEq, Eq)]
 pub struct PkgBody {
     pub id: NodeId,
@@ -231,6 +261,8 @@ pub struct PkgBody {
 }
 
 /// A package instantiation declaration.
+#[moore_derive::visit_without_foreach]
+#[moore_derive::rewrite]
 #[derive(Clone, Debug, PartialEq, Eq)]
 pub struct PkgInst {
     pub id: NodeId,
@@ -241,6 +273,8 @@ pub struct PkgInst {
 }
 
 /// An interface declaration.
+#[moore_derive::visit_without_foreach]
+#[moore_derive::rewrite]
 #[derive(Clone, Debug, PartialEq, Eq)]
 pub enum IntfDecl {
     TypeDecl(TypeDecl),
@@ -279,6 +313,8 @@ impl HasDesc for IntfDecl {
     }
 }
 
+#[moore_derive::visit_without_foreach]
+#[moore_derive::rewrite]
 #[derive(Clone, Debug, PartialEq, Eq)]
 pub struct IntfSubprogDecl {
     pub id: NodeId,
@@ -287,6 +323,8 @@ pub struct IntfSubprogDecl {
     pub default: Option<SubprogDefault>,
 }
 
+#[moore_derive::visit_without_foreach]
+#[moore_derive::rewrite]
 #[derive(Clone, Debug, PartialEq, Eq)]
 pub enum SubprogDefault {
     Any,
@@ -294,6 +332,8 @@ pub enum SubprogDefault {
 }
 
 /// An interface object declaration.
+#[moore_derive::visit_without_foreach]
+#[moore_derive::rewrite]
 #[derive(Clone, Debug, PartialEq, Eq)]
 pub struct IntfObjDecl {
     pub kind: IntfObjKind,
@@ -316,6 +356,8 @@ impl HasDesc for IntfObjDecl {
     }
 }
 
+#[moore_derive::visit_without_foreach]
+#[moore_derive::rewrite]
 #[derive(Clone, Copy, Debug, PartialEq, Eq)]
 pub enum IntfObjKind {
     Const,
@@ -324,6 +366,8 @@ pub enum IntfObjKind {
     File,
 }
 
+#[moore_derive::visit_without_foreach]
+#[moore_derive::rewrite]
 #[derive(Clone, Copy, Debug, PartialEq, Eq)]
 pub enum IntfMode {
     In,
@@ -334,6 +378,8 @@ pub enum IntfMode {
 }
 
 /// A declarative item.
+#[moore_derive::visit_without_foreach]
+#[moore_derive::rewrite]
 #[derive(Clone, Debug, PartialEq, Eq)]
 pub enum DeclItem {
     PkgBody(PkgBody),
@@ -452,12 +498,16 @@ impl HasDesc for DeclItem {
     }
 }
 
+#[moore_derive::visit_without_foreach]
+#[moore_derive::rewrite]
 #[derive(Copy, Clone, Debug, PartialEq, Eq)]
 pub enum PortgenKind {
     Port,
     Generic,
 }
 
+#[moore_derive::visit_without_foreach]
+#[moore_derive::rewrite]
 #[derive(Clone, Debug, PartialEq, Eq)]
 pub struct Subprog {
     pub id: NodeId,
@@ -482,6 +532,8 @@ impl HasDesc for Subprog {
     }
 }
 
+#[moore_derive::visit_without_foreach]
+#[moore_derive::rewrite]
 #[derive(Clone, Debug, PartialEq, Eq)]
 pub enum SubprogData {
     Decl,
@@ -505,6 +557,8 @@ impl HasDesc for SubprogData {
     }
 }
 
+#[moore_derive::visit_without_foreach]
+#[moore_derive::rewrite]
 #[derive(Clone, Debug, PartialEq, Eq)]
 pub struct SubprogSpec {
     pub span: Span,
@@ -517,18 +571,24 @@ pub struct SubprogSpec {
     pub retty: Option<CompoundName>,
 }
 
+#[moore_derive::visit_without_foreach]
+#[moore_derive::rewrite]
 #[derive(Copy, Clone, Debug, PartialEq, Eq)]
 pub enum SubprogPurity {
     Pure,
     Impure,
 }
 
+#[moore_derive::visit_without_foreach]
+#[moore_derive::rewrite]
 #[derive(Copy, Clone, Debug, PartialEq, Eq)]
 pub enum SubprogKind {
     Proc,
     Func,
 }
 
+#[moore_derive::visit_without_foreach]
+#[moore_derive::rewrite]
 #[derive(Clone, Debug, PartialEq, Eq)]
 pub struct SubtypeInd {
     pub span: Span,
@@ -548,6 +608,8 @@ impl HasDesc for SubtypeInd {
     }
 }
 
+#[moore_derive::visit_without_foreach]
+#[moore_derive::rewrite]
 #[derive(Clone, Debug, PartialEq, Eq)]
 pub struct SubtypeDecl {
     pub id: NodeId,
@@ -556,6 +618,8 @@ pub struct SubtypeDecl {
     pub subtype: SubtypeInd,
 }
 
+#[moore_derive::visit_without_foreach]
+#[moore_derive::rewrite]
 #[derive(Clone, Debug, PartialEq, Eq)]
 pub enum ResolInd {
     Exprs(ParenElems),
@@ -563,6 +627,8 @@ pub enum ResolInd {
 }
 
 /// An alias declaration.
+#[moore_derive::visit_without_foreach]
+#[moore_derive::rewrite]
 #[derive(Clone, Debug, PartialEq, Eq)]
 pub struct AliasDecl {
     pub id: NodeId,
@@ -572,6 +638,8 @@ pub struct AliasDecl {
     pub target: CompoundName,
 }
 
+#[moore_derive::visit_without_foreach]
+#[moore_derive::rewrite]
 #[derive(Clone, Debug, PartialEq, Eq)]
 pub struct ObjDecl {
     pub span: Span,
@@ -582,6 +650,8 @@ pub struct ObjDecl {
     pub init: Option<Expr>,
 }
 
+#[moore_derive::visit_without_foreach]
+#[moore_derive::rewrite]
 #[derive(Copy, Clone, Debug, PartialEq, Eq)]
 pub enum ObjKind {
     Const,
@@ -593,6 +663,8 @@ pub enum ObjKind {
 
 /// Additional mutually exclusive details that may be provided with an object
 /// declaration.
+#[moore_derive::visit_without_foreach]
+#[moore_derive::rewrite]
 #[derive(Clone, Debug, PartialEq, Eq)]
 pub enum ObjDetail {
     Register,
@@ -627,6 +699,8 @@ impl HasDesc for ObjDecl {
 }
 
 /// A component declaration.
+#[moore_derive::visit_without_foreach]
+#[moore_derive::rewrite]
 #[derive(Clone, Debug, PartialEq, Eq)]
 pub struct CompDecl {
     pub id: NodeId,
@@ -636,6 +710,8 @@ pub struct CompDecl {
     pub ports: Option<Spanned<Vec<IntfDecl>>>,
 }
 
+#[moore_derive::visit_without_foreach]
+#[moore_derive::rewrite]
 #[derive(Clone, Debug, PartialEq, Eq)]
 pub struct DisconSpec {
     pub span: Span,
@@ -644,6 +720,8 @@ pub struct DisconSpec {
     pub after: Expr,
 }
 
+#[moore_derive::visit_without_foreach]
+#[moore_derive::rewrite]
 #[derive(Clone, Debug, PartialEq, Eq)]
 pub enum DisconTarget {
     Others,
@@ -651,6 +729,8 @@ pub enum DisconTarget {
     Signals(Vec<CompoundName>),
 }
 
+#[moore_derive::visit_without_foreach]
+#[moore_derive::rewrite]
 #[derive(Clone, Debug, PartialEq, Eq)]
 pub struct BlockCompCfg {
     pub span: Span,
@@ -659,6 +739,8 @@ pub struct BlockCompCfg {
     pub decls: Vec<DeclItem>,
 }
 
+#[moore_derive::visit_without_foreach]
+#[moore_derive::rewrite]
 #[derive(Clone, Debug, PartialEq, Eq)]
 pub enum BlockCompSpec {
     CompOthers(CompoundName),
@@ -667,6 +749,8 @@ pub enum BlockCompSpec {
     Block(CompoundName),
 }
 
+#[moore_derive::visit_without_foreach]
+#[moore_derive::rewrite]
 #[derive(Clone, Debug, PartialEq, Eq)]
 pub struct BindingInd {
     pub span: Span,
@@ -675,6 +759,8 @@ pub struct BindingInd {
     pub ports: Option<ParenElems>,
 }
 
+#[moore_derive::visit_without_foreach]
+#[moore_derive::rewrite]
 #[derive(Clone, Debug, PartialEq, Eq)]
 pub enum EntityAspect {
     Entity(CompoundName),
@@ -682,6 +768,8 @@ pub enum EntityAspect {
     Open,
 }
 
+#[moore_derive::visit_without_foreach]
+#[moore_derive::rewrite]
 #[derive(Clone, Debug, PartialEq, Eq)]
 pub struct CfgSpec {
     pub span: Span,
@@ -690,6 +778,8 @@ pub struct CfgSpec {
     pub vunits: Vec<()>,
 }
 
+#[moore_derive::visit_without_foreach]
+#[moore_derive::rewrite]
 #[derive(Clone, Debug, PartialEq, Eq)]
 pub struct AttrDecl {
     pub id: NodeId,
@@ -698,6 +788,8 @@ pub struct AttrDecl {
     pub data: AttrData,
 }
 
+#[moore_derive::visit_without_foreach]
+#[moore_derive::rewrite]
 #[derive(Clone, Debug, PartialEq, Eq)]
 pub enum AttrData {
     Decl(CompoundName),
@@ -708,6 +800,8 @@ pub enum AttrData {
     },
 }
 
+#[moore_derive::visit_without_foreach]
+#[moore_derive::rewrite]
 #[derive(Clone, Debug, PartialEq, Eq)]
 pub enum AttrTarget {
     Others,
@@ -715,6 +809,8 @@ pub enum AttrTarget {
     List(Vec<(CompoundName, Option<Signature>)>),
 }
 
+#[moore_derive::visit_without_foreach]
+#[moore_derive::rewrite]
 #[derive(Copy, Clone, Debug, PartialEq, Eq)]
 pub enum EntityClass {
     Arch,
@@ -738,6 +834,8 @@ pub enum EntityClass {
     Var,
 }
 
+#[moore_derive::visit_without_foreach]
+#[moore_derive::rewrite]
 #[derive(Clone, Debug, PartialEq, Eq)]
 pub struct GroupDecl {
     pub id: NodeId,
@@ -746,6 +844,8 @@ pub struct GroupDecl {
     pub data: GroupData,
 }
 
+#[moore_derive::visit_without_foreach]
+#[moore_derive::rewrite]
 #[derive(Clone, Debug, PartialEq, Eq)]
 pub enum GroupData {
     /// A group declaration.
@@ -758,6 +858,8 @@ pub enum GroupData {
 /// A parenthesized expression element. A parenthesized expression contains
 /// elements of which each may either be a simple `<expr>`, or an association of
 /// the form `<choices> => <expr>`.
+#[moore_derive::visit_without_foreach]
+#[moore_derive::rewrite]
 #[derive(Clone, Debug, PartialEq, Eq)]
 pub struct ParenElem {
     pub span: Span,
@@ -770,6 +872,8 @@ pub struct ParenElem {
 pub type ParenElems = Spanned<Vec<ParenElem>>;
 
 /// An expression.
+#[moore_derive::visit_without_foreach]
+#[moore_derive::rewrite]
 #[derive(Clone, Debug, PartialEq, Eq)]
 pub struct Expr {
     pub span: Span,
@@ -789,6 +893,8 @@ impl HasDesc for Expr {
 }
 
 /// The data associated with a specific expression.
+#[moore_derive::visit_without_foreach]
+#[moore_derive::rewrite]
 #[derive(Clone, Debug, PartialEq, Eq)]
 pub enum ExprData {
     NullExpr,
@@ -818,6 +924,8 @@ impl HasDesc for ExprData {
     }
 }
 
+#[moore_derive::visit_without_foreach]
+#[moore_derive::rewrite]
 #[derive(Copy, Clone, Debug, PartialEq, Eq)]
 pub enum UnaryOp {
     Not,
@@ -828,6 +936,8 @@ pub enum UnaryOp {
     Condition,
 }
 
+#[moore_derive::visit_without_foreach]
+#[moore_derive::rewrite]
 #[derive(Copy, Clone, Debug, PartialEq, Eq)]
 pub enum BinaryOp {
     Dir(Dir),
@@ -845,6 +955,8 @@ pub enum BinaryOp {
     Pow,
 }
 
+#[moore_derive::visit_without_foreach]
+#[moore_derive::rewrite]
 #[derive(Copy, Clone, Debug, PartialEq, Eq)]
 pub enum Dir {
     To,
@@ -860,12 +972,16 @@ impl std::fmt::Display for Dir {
     }
 }
 
+#[moore_derive::visit_without_foreach]
+#[moore_derive::rewrite]
 #[derive(Copy, Clone, Debug, PartialEq, Eq)]
 pub enum Sign {
     Pos,
     Neg,
 }
 
+#[moore_derive::visit_without_foreach]
+#[moore_derive::rewrite]
 #[derive(Copy, Clone, Debug, PartialEq, Eq, Hash)]
 pub enum LogicalOp {
     And,
@@ -889,6 +1005,8 @@ impl fmt::Display for LogicalOp {
     }
 }
 
+#[moore_derive::visit_without_foreach]
+#[moore_derive::rewrite]
 #[derive(Copy, Clone, Debug, PartialEq, Eq, Hash)]
 pub enum RelationalOp {
     Eq,
@@ -912,6 +1030,8 @@ impl fmt::Display for RelationalOp {
     }
 }
 
+#[moore_derive::visit_without_foreach]
+#[moore_derive::rewrite]
 #[derive(Copy, Clone, Debug, PartialEq, Eq, Hash)]
 pub enum ShiftOp {
     Sll,
@@ -937,6 +1057,8 @@ impl fmt::Display for ShiftOp {
 
 /// A type declaration. If the `data` field is omitted, this is an incomplete
 /// declaration.
+#[moore_derive::visit_without_foreach]
+#[moore_derive::rewrite]
 #[derive(Clone, Debug, PartialEq, Eq)]
 pub struct TypeDecl {
     pub id: NodeId,
@@ -964,6 +1086,8 @@ impl HasDesc for TypeDecl {
     }
 }
 
+#[moore_derive::visit_without_foreach]
+#[moore_derive::rewrite]
 #[derive(Clone, Debug, PartialEq, Eq)]
 pub enum TypeData {
     EnumType(ParenElems),
@@ -989,6 +1113,8 @@ impl HasDesc for TypeData {
     }
 }
 
+#[moore_derive::visit_without_foreach]
+#[moore_derive::rewrite]
 #[derive(Clone, Debug, PartialEq, Eq)]
 pub struct Stmt {
     pub id: NodeId,
@@ -1016,6 +1142,8 @@ impl HasDesc for Stmt {
     }
 }
 
+#[moore_derive::visit_without_foreach]
+#[moore_derive::rewrite]
 #[derive(Clone, Debug, PartialEq, Eq)]
 pub enum StmtData {
     WaitStmt {
@@ -1032,6 +1160,13 @@ pub enum StmtData {
         msg: Expr,
         severity: Option<Expr>,
     },
+    PslDirectiveStmt {
+        kind: PslDirective,
+        /// The booleans that must hold in consecutive cycles. A bare boolean
+        /// is a sequence of length one.
+        seq: Vec<Expr>,
+        report: Option<Expr>,
+    },
     IfStmt {
         conds: Vec<(Expr, StmtBody)>,
         alt: Option<StmtBody>,
@@ -1105,6 +1240,7 @@ impl HasDesc for StmtData {
             StmtData::WaitStmt { .. } => "wait statement",
             StmtData::AssertStmt { .. } => "assertion statement",
             StmtData::ReportStmt { .. } => "report statement",
+            StmtData::PslDirectiveStmt { kind, .. } => kind.desc(),
             StmtData::IfStmt { .. } => "if statement",
             StmtData::CaseStmt { .. } => "case statement",
             StmtData::LoopStmt { .. } => "loop statement",
@@ -1131,12 +1267,16 @@ impl HasDesc for StmtData {
 }
 
 /// The body of an if, loop, or case statement.
+#[moore_derive::visit_without_foreach]
+#[moore_derive::rewrite]
 #[derive(Clone, Debug, PartialEq, Eq)]
 pub struct StmtBody {
     pub id: NodeId,
     pub stmts: Vec<Stmt>,
 }
 
+#[moore_derive::visit_without_foreach]
+#[moore_derive::rewrite]
 #[derive(Clone, Debug, PartialEq, Eq)]
 pub enum LoopScheme {
     While(Expr),
@@ -1144,12 +1284,39 @@ pub enum LoopScheme {
     Loop,
 }
 
+/// A PSL verification directive, other than `assert`.
+///
+/// Assumptions and restrictions constrain the environment of the design,
+/// while covers ask for a trace in which the sequence occurs.
+#[moore_derive::visit_without_foreach]
+#[moore_derive::rewrite]
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum PslDirective {
+    Assume,
+    Cover,
+    Restrict,
+}
+
+impl HasDesc for PslDirective {
+    fn desc(&self) -> &'static str {
+        match *self {
+            PslDirective::Assume => "assume directive",
+            PslDirective::Cover => "cover directive",
+            PslDirective::Restrict => "restrict directive",
+        }
+    }
+}
+
+#[moore_derive::visit_without_foreach]
+#[moore_derive::rewrite]
 #[derive(Copy, Clone, Debug, PartialEq, Eq)]
 pub enum NexitMode {
     Next,
     Exit,
 }
 
+#[moore_derive::visit_without_foreach]
+#[moore_derive::rewrite]
 #[derive(Clone, Debug, PartialEq, Eq)]
 pub struct GenBody {
     pub id: NodeId,
@@ -1159,18 +1326,24 @@ pub struct GenBody {
     pub stmts: Vec<Stmt>,
 }
 
+#[moore_derive::visit_without_foreach]
+#[moore_derive::rewrite]
 #[derive(Clone, Debug, PartialEq, Eq)]
 pub enum Sensitivity {
     All,
     List(Vec<CompoundName>),
 }
 
+#[moore_derive::visit_without_foreach]
+#[moore_derive::rewrite]
 #[derive(Clone, Debug, PartialEq, Eq)]
 pub enum AssignTarget {
     Name(CompoundName),
     Aggregate(ParenElems),
 }
 
+#[moore_derive::visit_without_foreach]
+#[moore_derive::rewrite]
 #[derive(Copy, Clone, Debug, PartialEq, Eq)]
 pub enum InstTarget {
     Comp,
@@ -1178,12 +1351,16 @@ pub enum InstTarget {
     Cfg,
 }
 
+#[moore_derive::visit_without_foreach]
+#[moore_derive::rewrite]
 #[derive(Copy, Clone, Debug, PartialEq, Eq)]
 pub enum AssignKind {
     Signal,
     Var,
 }
 
+#[moore_derive::visit_without_foreach]
+#[moore_derive::rewrite]
 #[derive(Clone, Debug, PartialEq, Eq)]
 pub enum AssignMode {
     Release(Option<Spanned<ForceMode>>),
@@ -1191,18 +1368,24 @@ pub enum AssignMode {
     Normal(Option<Spanned<DelayMech>>, Vec<CondWave>),
 }
 
+#[moore_derive::visit_without_foreach]
+#[moore_derive::rewrite]
 #[derive(Clone, Debug, PartialEq, Eq)]
 pub enum SelectAssignMode {
     Force(Option<Spanned<ForceMode>>),
     Normal(Option<Spanned<DelayMech>>),
 }
 
+#[moore_derive::visit_without_foreach]
+#[moore_derive::rewrite]
 #[derive(Copy, Clone, Debug, PartialEq, Eq)]
 pub enum ForceMode {
     In,
     Out,
 }
 
+#[moore_derive::visit_without_foreach]
+#[moore_derive::rewrite]
 #[derive(Clone, Debug, PartialEq, Eq)]
 pub enum DelayMech {
     Transport,
@@ -1210,6 +1393,8 @@ pub enum DelayMech {
     InertialReject(Expr),
 }
 
+#[moore_derive::visit_without_foreach]
+#[moore_derive::rewrite]
 #[derive(Clone, Debug, PartialEq, Eq)]
 pub struct Wave {
     pub span: Span,
@@ -1217,14 +1402,20 @@ pub struct Wave {
 }
 
 /// A conditional wave.
+#[moore_derive::visit_without_foreach]
+#[moore_derive::rewrite]
 #[derive(Clone, Debug, PartialEq, Eq)]
 pub struct CondWave(pub Wave, pub Option<Expr>);
 
 /// A selected wave. The second element of the tuple represents the choices for
 /// which this wave would be selected.
+#[moore_derive::visit_without_foreach]
+#[moore_derive::rewrite]
 #[derive(Clone, Debug, PartialEq, Eq)]
 pub struct SelectWave(pub Wave, pub Choices);
 
+#[moore_derive::visit_without_foreach]
+#[moore_derive::rewrite]
 #[derive(Clone, Debug, PartialEq, Eq)]
 pub struct Signature {
     pub span: Span,
@@ -1233,3 +1424,6 @@ pub struct Signature {
 }
 
 pub type Choices = Spanned<Vec<Expr>>;
+
+moore_derive::derive_visitor!();
+moore_derive::derive_rewriter!();
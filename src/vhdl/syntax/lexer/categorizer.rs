@@ -35,9 +35,13 @@ where
             Some(v) => v,
             None => return None,
         };
+        // Braces are not special characters in VHDL itself, but delimit the
+        // sequences of embedded PSL.
         let cat = match c {
             '"' | '#' | '&' | '\'' | '(' | ')' | '*' | '+' | ',' | '-' | '.' | '/' | ':' | ';'
-            | '<' | '=' | '>' | '?' | '@' | '[' | ']' | '_' | '`' | '|' => Category::Special,
+            | '<' | '=' | '>' | '?' | '@' | '[' | ']' | '_' | '`' | '|' | '{' | '}' => {
+                Category::Special
+            }
             c if c.is_alphabetic() => Category::Letter,
             c if c.is_digit(10) => Category::Digit,
             c if c.is_whitespace() => Category::Space,
@@ -399,7 +399,7 @@ mod test {
     fn symbols() {
         check(
             "
-            (    )
+            (    )    {    }
             .    ,    :    ;    '    &
             =>   ??   <>   :=   <<   >>
             =    /=   <    <=   >    >=
@@ -409,6 +409,8 @@ mod test {
             &[
                 OpenDelim(Paren),
                 CloseDelim(Paren),
+                OpenDelim(Brace),
+                CloseDelim(Brace),
                 Period,
                 Comma,
                 Colon,
@@ -65,6 +65,8 @@ impl Token {
             CloseDelim(Paren) => ")",
             OpenDelim(Brack) => "[",
             CloseDelim(Brack) => "]",
+            OpenDelim(Brace) => "{",
+            CloseDelim(Brace) => "}",
             Keyword(kw) => kw.as_str(),
 
             Period => ".",
@@ -162,6 +164,7 @@ impl Literal {
 pub enum DelimToken {
     Paren,
     Brack,
+    Brace,
 }
 
 #[derive(Clone, Copy, PartialEq, Eq, Hash, Debug)]
@@ -288,6 +288,8 @@ where
             ')' => Some(CloseDelim(Paren)),
             '[' => Some(OpenDelim(Brack)),
             ']' => Some(CloseDelim(Brack)),
+            '{' => Some(OpenDelim(Brace)),
+            '}' => Some(CloseDelim(Brace)),
             '.' => Some(Period),
             ',' => Some(Comma),
             ':' => Some(Colon),
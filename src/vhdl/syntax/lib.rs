@@ -8,6 +8,9 @@ extern crate moore_common;
 pub mod ast;
 pub mod lexer;
 pub mod parser;
+pub mod query;
+pub mod rewrite;
+pub mod visit;
 
 use moore_common::errors::*;
 use moore_common::grind::{self, Grinder};
@@ -390,6 +390,12 @@ where
                         n.value, msg, name.value
                     ))
                     .span(n.span)
+                    .add_note(format!(
+                        "Replace `{}` with `{}`, or remove the closing name",
+                        n.value, name.value
+                    ))
+                    .span(name.span)
+                    .add_note(format!("The {} is named `{}` here", msg, name.value))
                     .add_note(format!("see IEEE 1076-2008 {}", sec)),
                 );
             }
@@ -400,6 +406,10 @@ where
                     n.value, msg
                 ))
                 .span(n.span)
+                .add_note(format!(
+                    "Add `{}:` in front of the {}, or remove the closing label",
+                    n.value, msg
+                ))
                 .add_note(format!("see IEEE 1076-2008 {}", sec)),
             );
         }
@@ -2505,6 +2515,7 @@ pub fn parse_stmt<P: Parser>(p: &mut P) -> ReportedResult<ast::Stmt> {
         Keyword(Kw::Wait) => parse_wait_stmt(p)?,
         Keyword(Kw::Assert) => parse_assert_stmt(p)?,
         Keyword(Kw::Report) => parse_report_stmt(p)?,
+        Keyword(Kw::Assume) | Keyword(Kw::Cover) | Keyword(Kw::Restrict) => parse_psl_directive(p)?,
 
         // For the if statement, check if the `generate` or the `then` keyword
         // occurs earlier. This allows us to determine whether we should parse a
@@ -2698,6 +2709,54 @@ pub fn parse_assert_stmt<P: Parser>(p: &mut P) -> ReportedResult<ast::StmtData>
     })
 }
 
+/// Parse a PSL verification directive. See IEEE 1076-2008 section 11.1 and
+/// IEEE 1850-2010 section 7.
+///
+/// Only sequences of booleans are supported as operands.
+///
+/// ```text
+/// psl_directive := ("assume" | "restrict") psl_seq ";"
+///                | "cover" psl_seq ["report" expr] ";"
+/// psl_seq := "{" expr {";" expr} "}" | expr
+/// ```
+pub fn parse_psl_directive<P: Parser>(p: &mut P) -> ReportedResult<ast::StmtData> {
+    let kind = match p.peek(0).value {
+        Keyword(Kw::Assume) => ast::PslDirective::Assume,
+        Keyword(Kw::Cover) => ast::PslDirective::Cover,
+        Keyword(Kw::Restrict) => ast::PslDirective::Restrict,
+        _ => unreachable!(),
+    };
+    p.bump();
+
+    // Parse the sequence, or a single boolean.
+    let seq = match try_flanked(p, Brace, |p| {
+        Ok(separated_nonempty(
+            p,
+            Semicolon,
+            CloseDelim(Brace),
+            "boolean",
+            parse_expr,
+        )?)
+    })? {
+        Some(seq) => seq,
+        None => vec![parse_expr(p)?],
+    };
+
+    // Parse the optional "report" part of a cover directive.
+    let report = if kind == ast::PslDirective::Cover && accept(p, Keyword(Kw::Report)) {
+        Some(parse_expr(p)?)
+    } else {
+        None
+    };
+
+    require(p, Semicolon)?;
+    Ok(ast::PslDirectiveStmt {
+        kind: kind,
+        seq: seq,
+        report: report,
+    })
+}
+
 /// Parse a report statement. See IEEE 1076-2008 section 10.4.
 ///
 /// ```text
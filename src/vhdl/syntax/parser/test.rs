@@ -6,13 +6,17 @@ use crate::lexer::Lexer;
 use crate::parser::basic::BasicParser;
 use crate::parser::core::*;
 use crate::parser::rules::*;
+use crate::parser::TokenStream;
 use moore_common::errors::*;
 use moore_common::grind::{self, Grinder};
 use moore_common::source::*;
 use std::fmt::Debug;
 
 macro_rules! parse {
-    ($content:expr, $parse_fn:expr) => {{
+    ($content:expr, $parse_fn:expr) => {
+        parse!($content, $parse_fn, None)
+    };
+    ($content:expr, $parse_fn:expr, $severity:expr) => {{
         // Create an anonymous source file with the given content.
         let src = get_source_manager().add_anonymous($content);
 
@@ -23,8 +27,13 @@ macro_rules! parse {
         let tokens = Lexer::new(bytes, src);
         let mut parser = BasicParser::new(tokens);
 
-        // Check the result.
-        parse_impl(&mut parser, $parse_fn)
+        // Check the result, and optionally the severity of the diagnostics
+        // emitted along the way.
+        let result = parse_impl(&mut parser, $parse_fn);
+        if let Some(severity) = $severity {
+            assert_eq!(parser.severity(), severity);
+        }
+        result
     }};
 }
 
@@ -104,7 +113,11 @@ fn context_decl() {
     parse!("context foo is end;", parse_context_decl);
     parse!("context foo is end context;", parse_context_decl);
     parse!("context foo is end context foo;", parse_context_decl);
-    // parse!("context foo is end context bar;", parse_context_decl); // check if this emits a warning
+    parse!(
+        "context foo is end context bar;",
+        parse_context_decl,
+        Some(Severity::Warning)
+    );
     parse!(
         "
         context project_context is
@@ -123,10 +136,46 @@ fn entity_decl() {
     parse!("entity foo is end;", parse_entity_decl);
     parse!("entity foo is end entity;", parse_entity_decl);
     parse!("entity foo is end entity foo;", parse_entity_decl);
-    // parse!("entity foo is end entity bar;", parse_entity_decl); // check if this emits a warning
+    parse!(
+        "entity foo is end entity bar;",
+        parse_entity_decl,
+        Some(Severity::Warning)
+    );
     parse!("entity foo is begin end;", parse_entity_decl);
 }
 
+#[test]
+fn closing_names() {
+    let clean = Some(Severity::Note);
+    parse!("entity Foo is end entity foo;", parse_entity_decl, clean);
+    parse!(
+        "architecture rtl of foo is begin end architecture RTL;",
+        parse_arch_body,
+        clean
+    );
+    parse!("p: process begin end process p;", parse_stmt, clean);
+    parse!(
+        "architecture rtl of foo is begin end architecture tb;",
+        parse_arch_body,
+        Some(Severity::Warning)
+    );
+    parse!(
+        "p: process begin end process q;",
+        parse_stmt,
+        Some(Severity::Warning)
+    );
+    parse!(
+        "process begin end process p;",
+        parse_stmt,
+        Some(Severity::Warning)
+    );
+    parse!(
+        "package foo is end package bar;",
+        parse_package_decl,
+        Some(Severity::Warning)
+    );
+}
+
 #[test]
 fn entity_header() {
     parse!(
@@ -538,3 +587,13 @@ fn config_decl() {
         parse_config_decl
     );
 }
+
+#[test]
+fn psl_directive() {
+    parse!("assume req = '1';", parse_stmt);
+    parse!("restrict {req; not ack};", parse_stmt);
+    parse!(
+        "handshake: cover {req; ack; not req} report \"handshake seen\";",
+        parse_stmt
+    );
+}
@@ -0,0 +1,544 @@
+// Copyright (c) 2016-2020 Fabian Schuiki
+
+//! Where-used queries over the AST.
+//!
+//! This module answers questions such as "where is entity X instantiated",
+//! "which signals does process P drive", and "which units use package Q". The
+//! queries operate on parsed design units and are therefore available before
+//! elaboration, which makes them useful for impact analysis in large codebases
+//! that may not elaborate as a whole.
+
+use crate::ast::*;
+use crate::visit::{Visitor, WalkVisitor};
+use moore_common::name::{get_name_table, Name};
+use moore_common::source::{Span, Spanned};
+use std::collections::HashMap;
+
+/// A single query result.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct QueryMatch {
+    /// The name of the design unit the match was found in.
+    pub unit: Name,
+    /// The name of the matched item, e.g. an instance label or a signal.
+    pub name: Option<Name>,
+    /// The location of the match.
+    pub span: Span,
+}
+
+/// Get the name of a design unit.
+pub fn unit_name(unit: &DesignUnit) -> Spanned<Name> {
+    match unit.data {
+        DesignUnitData::EntityDecl(ref x) => x.name,
+        DesignUnitData::CfgDecl(ref x) => x.name,
+        DesignUnitData::PkgDecl(ref x) => x.name,
+        DesignUnitData::PkgInst(ref x) => x.name,
+        DesignUnitData::CtxDecl(ref x) => x.name,
+        DesignUnitData::ArchBody(ref x) => x.name,
+        DesignUnitData::PkgBody(ref x) => x.name,
+    }
+}
+
+/// Get the last simple name in a compound name.
+///
+/// For `work.foo` this is `foo`, for `foo(rtl)` it is `foo`.
+fn last_name(name: &CompoundName) -> Option<Name> {
+    let mut last = match name.primary.kind {
+        PrimaryNameKind::Ident(n) => Some(n),
+        _ => None,
+    };
+    for part in &name.parts {
+        if let NamePart::Select(PrimaryName {
+            kind: PrimaryNameKind::Ident(n),
+            ..
+        }) = *part
+        {
+            last = Some(n);
+        }
+    }
+    last
+}
+
+/// Check whether a compound name mentions `name` as one of its simple names.
+fn mentions(name: &CompoundName, needle: Name) -> bool {
+    name.primary.kind == PrimaryNameKind::Ident(needle)
+        || name.parts.iter().any(|p| match *p {
+            NamePart::Select(ref p) => p.kind == PrimaryNameKind::Ident(needle),
+            _ => false,
+        })
+}
+
+/// Find all instantiations of the entity or component `target`.
+///
+/// Both component instantiations (`u0: foo`) and direct entity instantiations
+/// (`u0: entity work.foo`) are reported, including those inside block and
+/// generate statements. The match name is the instance label.
+///
+/// The query only looks at the syntax. Instances are matched by the name they
+/// refer to, ignoring configurations and binding indications, and an instance
+/// inside a generate statement is reported once, regardless of how often the
+/// statement is elaborated.
+pub fn instances_of(units: &[DesignUnit], target: Name) -> Vec<QueryMatch> {
+    let mut finder = InstFinder {
+        target,
+        unit: None,
+        in_process: false,
+        matches: vec![],
+    };
+    for unit in units {
+        finder.unit = Some(unit_name(unit).value);
+        unit.walk(&mut finder);
+    }
+    finder.matches
+}
+
+struct InstFinder {
+    target: Name,
+    unit: Option<Name>,
+    in_process: bool,
+    matches: Vec<QueryMatch>,
+}
+
+impl<'a> Visitor<'a> for InstFinder {
+    fn pre_visit_stmt(&mut self, node: &'a Stmt) -> bool {
+        match node.data {
+            // Processes do not nest, so there is no outer state to restore.
+            ProcStmt { .. } => self.in_process = true,
+            InstOrCallStmt { ref name, .. } if !self.in_process => {
+                if last_name(name) == Some(self.target) {
+                    self.matches.push(QueryMatch {
+                        unit: self.unit.unwrap(),
+                        name: node.label.map(|l| l.value),
+                        span: node.span,
+                    });
+                }
+                return false;
+            }
+            _ => (),
+        }
+        true
+    }
+
+    fn post_visit_stmt(&mut self, node: &'a Stmt) {
+        if let ProcStmt { .. } = node.data {
+            self.in_process = false;
+        }
+    }
+
+    fn pre_visit_decl_item(&mut self, node: &'a DeclItem) -> bool {
+        // Subprogram bodies cannot contain instantiations.
+        match *node {
+            DeclItem::SubprogDecl(..) => false,
+            _ => true,
+        }
+    }
+}
+
+/// Find all signals driven by the process labeled `process`.
+///
+/// The match name is the driven signal, i.e. the simple name at the root of
+/// the assignment target.
+pub fn drivers_of(units: &[DesignUnit], process: Name) -> Vec<QueryMatch> {
+    let mut finder = DriverFinder {
+        process,
+        unit: None,
+        active: false,
+        matches: vec![],
+    };
+    for unit in units {
+        finder.unit = Some(unit_name(unit).value);
+        unit.walk(&mut finder);
+    }
+    finder.matches
+}
+
+struct DriverFinder {
+    process: Name,
+    unit: Option<Name>,
+    active: bool,
+    matches: Vec<QueryMatch>,
+}
+
+impl DriverFinder {
+    fn add_target(&mut self, target: &AssignTarget, span: Span) {
+        match *target {
+            AssignTarget::Name(ref name) => {
+                let name = match name.primary.kind {
+                    PrimaryNameKind::Ident(n) => n,
+                    _ => return,
+                };
+                if !self.matches.iter().any(|m| m.name == Some(name)) {
+                    self.matches.push(QueryMatch {
+                        unit: self.unit.unwrap(),
+                        name: Some(name),
+                        span,
+                    });
+                }
+            }
+            AssignTarget::Aggregate(ref elems) => {
+                for elem in &elems.value {
+                    if let NameExpr(ref name) = elem.expr.data {
+                        self.add_target(&AssignTarget::Name(name.clone()), elem.span);
+                    }
+                }
+            }
+        }
+    }
+}
+
+impl<'a> Visitor<'a> for DriverFinder {
+    fn pre_visit_stmt(&mut self, node: &'a Stmt) -> bool {
+        match node.data {
+            // Processes do not nest, so there is no outer state to restore.
+            ProcStmt { .. } => {
+                self.active = node.label.map(|l| l.value) == Some(self.process);
+                true
+            }
+            AssignStmt {
+                ref target,
+                kind: AssignKind::Signal,
+                ..
+            } if self.active => {
+                self.add_target(&target.value, target.span);
+                false
+            }
+            _ => true,
+        }
+    }
+
+    fn post_visit_stmt(&mut self, node: &'a Stmt) {
+        if let ProcStmt { .. } = node.data {
+            self.active = false;
+        }
+    }
+}
+
+/// Find all design units that use the package `pkg`.
+///
+/// Both use clauses in the context of a design unit and use clauses among its
+/// declarations are considered. The match name is the package that has been
+/// found.
+pub fn users_of(units: &[DesignUnit], pkg: Name) -> Vec<QueryMatch> {
+    let mut finder = UseFinder { pkg, found: None };
+    let mut matches = vec![];
+    for unit in units {
+        finder.found = None;
+        unit.walk(&mut finder);
+        if let Some(span) = finder.found {
+            matches.push(QueryMatch {
+                unit: unit_name(unit).value,
+                name: Some(pkg),
+                span,
+            });
+        }
+    }
+    matches
+}
+
+struct UseFinder {
+    pkg: Name,
+    found: Option<Span>,
+}
+
+impl UseFinder {
+    fn check(&mut self, names: &[CompoundName]) {
+        if self.found.is_some() {
+            return;
+        }
+        self.found = names.iter().find(|n| mentions(n, self.pkg)).map(|n| n.span);
+    }
+}
+
+impl<'a> Visitor<'a> for UseFinder {
+    fn pre_visit_ctx_item(&mut self, node: &'a CtxItem) -> bool {
+        if let CtxItem::UseClause(ref names) = *node {
+            self.check(&names.value);
+        }
+        false
+    }
+
+    fn pre_visit_decl_item(&mut self, node: &'a DeclItem) -> bool {
+        if let DeclItem::UseClause(_, ref names) = *node {
+            self.check(&names.value);
+        }
+        true
+    }
+}
+
+/// Find all signals matching a hierarchical path such as `top.*.state`.
+///
+/// The first segment of the path names an entity, each following segment an
+/// instance label in one of its architectures, and the last segment a port or
+/// signal. A `*` in a segment matches any sequence of characters within that
+/// segment, and matching is case-insensitive. The match name is the full path
+/// of the signal.
+///
+/// Like the other queries, this only looks at the syntax. The architectures of
+/// an entity are merged, and component instances refer to the entity of the
+/// same name. Paths into block and generate statements are rejected with an
+/// error, since their contents depend on elaboration.
+pub fn signals_at(units: &[DesignUnit], path: &str) -> Result<Vec<QueryMatch>, String> {
+    let mut entities = vec![];
+    for unit in units {
+        if let DesignUnitData::EntityDecl(ref decl) = unit.data {
+            let mut entity = HierEntity {
+                name: decl.name.value,
+                signals: vec![],
+                insts: vec![],
+                scopes: vec![],
+            };
+            entity.add_decls(&decl.decls);
+            entities.push(entity);
+        }
+    }
+    for unit in units {
+        if let DesignUnitData::ArchBody(ref body) = unit.data {
+            let target = last_name(&body.target);
+            if let Some(entity) = entities.iter_mut().find(|e| Some(e.name) == target) {
+                entity.add_decls(&body.decls);
+                for stmt in &body.stmts {
+                    let label = match stmt.label {
+                        Some(label) => label.value,
+                        None => continue,
+                    };
+                    match stmt.data {
+                        InstOrCallStmt { ref name, .. } => {
+                            if let Some(target) = last_name(name) {
+                                entity.insts.push((label, target));
+                            }
+                        }
+                        BlockStmt { .. } => entity.scopes.push((label, "block statement")),
+                        IfGenStmt { .. } | CaseGenStmt { .. } | ForGenStmt { .. } => {
+                            entity.scopes.push((label, "generate statement"))
+                        }
+                        _ => (),
+                    }
+                }
+            }
+        }
+    }
+    let by_name: HashMap<Name, &HierEntity> = entities.iter().map(|e| (e.name, e)).collect();
+    let segments: Vec<&str> = path.split('.').collect();
+    let mut matches = vec![];
+    for entity in &entities {
+        let name = entity.name.as_str();
+        if segment_matches(segments[0], &name) {
+            entity.find_signals(&by_name, &name, &segments[1..], &mut matches)?;
+        }
+    }
+    Ok(matches)
+}
+
+/// The ports, signals, and instances of an entity.
+struct HierEntity {
+    name: Name,
+    signals: Vec<(Name, Span)>,
+    /// The instance labels and the entities they instantiate.
+    insts: Vec<(Name, Name)>,
+    /// The labels of block and generate statements, and what they label.
+    scopes: Vec<(Name, &'static str)>,
+}
+
+impl HierEntity {
+    fn add_decls(&mut self, decls: &[DeclItem]) {
+        for decl in decls {
+            match *decl {
+                DeclItem::PortgenClause(_, ref kind, ref intfs)
+                    if kind.value == PortgenKind::Port =>
+                {
+                    for intf in &intfs.value {
+                        if let IntfDecl::ObjDecl(ref obj) = *intf {
+                            self.add_signals(&obj.names);
+                        }
+                    }
+                }
+                DeclItem::ObjDecl(ref obj) if obj.kind == ObjKind::Signal => {
+                    self.add_signals(&obj.names)
+                }
+                _ => (),
+            }
+        }
+    }
+
+    fn add_signals(&mut self, names: &[Ident]) {
+        for name in names {
+            self.signals.push((name.name, name.span));
+        }
+    }
+
+    fn find_signals(
+        &self,
+        entities: &HashMap<Name, &HierEntity>,
+        prefix: &str,
+        segments: &[&str],
+        matches: &mut Vec<QueryMatch>,
+    ) -> Result<(), String> {
+        let (first, rest) = match segments.split_first() {
+            Some(x) => x,
+            None => return Ok(()),
+        };
+        if rest.is_empty() {
+            for &(name, span) in &self.signals {
+                if segment_matches(first, &name.as_str()) {
+                    let path = format!("{}.{}", prefix, name);
+                    matches.push(QueryMatch {
+                        unit: self.name,
+                        name: Some(get_name_table().intern(&path, false)),
+                        span,
+                    });
+                }
+            }
+            return Ok(());
+        }
+        for &(name, kind) in &self.scopes {
+            if segment_matches(first, &name.as_str()) {
+                return Err(format!(
+                    "`{}.{}` is a {}; paths into block and generate statements are not supported",
+                    prefix, name, kind
+                ));
+            }
+        }
+        for &(name, target) in &self.insts {
+            if let Some(entity) = entities.get(&target) {
+                if segment_matches(first, &name.as_str()) {
+                    let prefix = format!("{}.{}", prefix, name);
+                    entity.find_signals(entities, &prefix, rest, matches)?;
+                }
+            }
+        }
+        Ok(())
+    }
+}
+
+/// Match a path segment against a pattern with `*` wildcards, ignoring case.
+fn segment_matches(pattern: &str, name: &str) -> bool {
+    match pattern.find('*') {
+        None => pattern.eq_ignore_ascii_case(name),
+        Some(i) => {
+            let (head, tail) = (&pattern[..i], &pattern[i + 1..]);
+            name.len() >= head.len()
+                && name.is_char_boundary(head.len())
+                && name[..head.len()].eq_ignore_ascii_case(head)
+                && (head.len()..=name.len())
+                    .any(|j| name.is_char_boundary(j) && segment_matches(tail, &name[j..]))
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use moore_common::name::*;
+    use moore_common::source::*;
+
+    const SRC: &str = "
+        library ieee;
+        use ieee.std_logic_1164.all;
+        entity foo is port (x : in bit); end;
+        architecture rtl of foo is begin end;
+        entity top is end;
+        architecture rtl of top is
+            signal a, b, c : bit;
+        begin
+            u0: foo;
+            u1: entity work.foo;
+            p0: process begin
+                a <= '1';
+                (b, c) <= \"01\";
+                wait;
+            end process;
+        end;
+    ";
+
+    fn name(s: &str) -> Name {
+        get_name_table().intern(s, false)
+    }
+
+    const NESTED: &str = "
+        entity foo is port (x : in bit); end;
+        architecture rtl of foo is begin end;
+        entity top is end;
+        architecture rtl of top is
+        begin
+            u0: foo;
+            b0: block begin
+                u1: foo;
+            end block;
+            g0: for i in 0 to 1 generate
+                u2: foo;
+            end generate;
+        end;
+    ";
+
+    fn parse(text: &str) -> Vec<DesignUnit> {
+        let src = get_source_manager().add_anonymous(text);
+        crate::parse(src).expect("parser failed")
+    }
+
+    #[test]
+    fn queries() {
+        let units = parse(SRC);
+        let insts: Vec<_> = instances_of(&units, name("foo"))
+            .into_iter()
+            .map(|m| m.name)
+            .collect();
+        assert_eq!(insts, vec![Some(name("u0")), Some(name("u1"))]);
+        let drivers: Vec<_> = drivers_of(&units, name("p0"))
+            .into_iter()
+            .map(|m| m.name)
+            .collect();
+        assert_eq!(
+            drivers,
+            vec![Some(name("a")), Some(name("b")), Some(name("c"))]
+        );
+        assert_eq!(users_of(&units, name("std_logic_1164")).len(), 1);
+    }
+
+    #[test]
+    fn signals() {
+        let units = parse(SRC);
+        let paths: Vec<_> = signals_at(&units, "TOP.*")
+            .unwrap()
+            .into_iter()
+            .map(|m| m.name.unwrap().as_str().to_lowercase())
+            .collect();
+        assert_eq!(paths, vec!["top.a", "top.b", "top.c"]);
+        let paths: Vec<_> = signals_at(&units, "top.u*.x")
+            .unwrap()
+            .into_iter()
+            .map(|m| m.name.unwrap().as_str().to_lowercase())
+            .collect();
+        assert_eq!(paths, vec!["top.u0.x", "top.u1.x"]);
+    }
+
+    #[test]
+    fn nested_instances() {
+        let units = parse(NESTED);
+        let insts: Vec<_> = instances_of(&units, name("foo"))
+            .into_iter()
+            .map(|m| m.name)
+            .collect();
+        assert_eq!(
+            insts,
+            vec![Some(name("u0")), Some(name("u1")), Some(name("u2"))]
+        );
+    }
+
+    #[test]
+    fn nested_scopes() {
+        let units = parse(NESTED);
+
+        // Paths may end next to a block or generate statement, but must not
+        // enter one.
+        assert_eq!(signals_at(&units, "top.u0.x").unwrap().len(), 1);
+        assert_eq!(signals_at(&units, "top.b0").unwrap(), vec![]);
+        assert!(signals_at(&units, "top.b0.u1.x")
+            .unwrap_err()
+            .contains("is a block statement"));
+        assert!(signals_at(&units, "top.*.x")
+            .unwrap_err()
+            .contains("is a block statement"));
+        assert!(signals_at(&units, "top.g0.u2.x")
+            .unwrap_err()
+            .contains("is a generate statement"));
+    }
+}
@@ -0,0 +1,176 @@
+// Copyright (c) 2016-2020 Fabian Schuiki
+
+//! A rewriter for the AST.
+//!
+//! The [`Rewriter`] trait is the owning counterpart to the
+//! [`Visitor`](crate::visit::Visitor) and is derived from the same nodes. Call
+//! `walk_rewrite()` on a node to consume it and rebuild it from its rewritten
+//! children. For every node the rewriter has a `pre_rewrite_*` function, which
+//! may skip the node's children, and a `post_rewrite_*` function, which returns
+//! the node that replaces it in the tree. Overriding a single function, e.g.
+//! `post_rewrite_expr`, is enough to transform every occurrence of that node
+//! throughout a design unit.
+
+use crate::ast::NodeId;
+use crate::lexer::token::Literal;
+use moore_common::name::Name;
+use moore_common::source::{Span, Spanned};
+
+pub use crate::ast::Rewriter;
+
+/// A node that accepts `Rewriter`s.
+pub trait AcceptRewriter<'a> {
+    /// Rebuild `self` from its children rewritten by a rewriter.
+    fn accept_rewrite(self, rewriter: &mut dyn Rewriter<'a>) -> Self;
+}
+
+/// A node that walks a `Rewriter` over itself.
+pub trait WalkRewriter<'a> {
+    /// Walk a rewriter over `self`, returning the rewritten node.
+    fn walk_rewrite(self, rewriter: &mut dyn Rewriter<'a>) -> Self;
+}
+
+impl<'a, T> WalkRewriter<'a> for Vec<T>
+where
+    T: WalkRewriter<'a>,
+{
+    fn walk_rewrite(self, rewriter: &mut dyn Rewriter<'a>) -> Self {
+        self.into_iter().map(|c| c.walk_rewrite(rewriter)).collect()
+    }
+}
+
+impl<'a, T> WalkRewriter<'a> for Option<T>
+where
+    T: WalkRewriter<'a>,
+{
+    fn walk_rewrite(self, rewriter: &mut dyn Rewriter<'a>) -> Self {
+        self.map(|c| c.walk_rewrite(rewriter))
+    }
+}
+
+impl<'a, T> WalkRewriter<'a> for Spanned<T>
+where
+    T: WalkRewriter<'a>,
+{
+    fn walk_rewrite(self, rewriter: &mut dyn Rewriter<'a>) -> Self {
+        self.map(|c| c.walk_rewrite(rewriter))
+    }
+}
+
+impl<'a, T> WalkRewriter<'a> for Box<T>
+where
+    T: WalkRewriter<'a>,
+{
+    fn walk_rewrite(self, rewriter: &mut dyn Rewriter<'a>) -> Self {
+        Box::new((*self).walk_rewrite(rewriter))
+    }
+}
+
+impl<'a, T0, T1> WalkRewriter<'a> for (T0, T1)
+where
+    T0: WalkRewriter<'a>,
+    T1: WalkRewriter<'a>,
+{
+    fn walk_rewrite(self, rewriter: &mut dyn Rewriter<'a>) -> Self {
+        (self.0.walk_rewrite(rewriter), self.1.walk_rewrite(rewriter))
+    }
+}
+
+macro_rules! leaf_impls {
+    ($($ty:ty),*) => {
+        $(
+            impl<'a> WalkRewriter<'a> for $ty {
+                fn walk_rewrite(self, _rewriter: &mut dyn Rewriter<'a>) -> Self {
+                    self
+                }
+            }
+        )*
+    };
+}
+
+leaf_impls!(NodeId, Span, Name, Literal, char, bool, ());
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::ast::*;
+    use crate::visit::tests::{names, parse, SRC};
+    use moore_common::name::get_name_table;
+
+    /// Renames every primary name `from` to `to`.
+    struct Rename(Name, Name);
+
+    impl<'a> Rewriter<'a> for Rename {
+        fn post_rewrite_primary_name(&mut self, node: PrimaryName) -> PrimaryName {
+            match node.kind {
+                PrimaryNameKind::Ident(name) if name == self.0 => PrimaryName {
+                    kind: PrimaryNameKind::Ident(self.1),
+                    ..node
+                },
+                _ => node,
+            }
+        }
+    }
+
+    /// Replaces every binary expression by its left operand.
+    struct Lhs;
+
+    impl<'a> Rewriter<'a> for Lhs {
+        fn post_rewrite_expr(&mut self, node: Expr) -> Expr {
+            match node.data {
+                BinaryExpr(_, lhs, _) => *lhs,
+                data => Expr { data, ..node },
+            }
+        }
+    }
+
+    /// Leaves processes untouched.
+    struct SkipProcs<R>(R);
+
+    impl<'a, R: Rewriter<'a>> Rewriter<'a> for SkipProcs<R> {
+        fn pre_rewrite_stmt(&mut self, node: &Stmt) -> bool {
+            match node.data {
+                ProcStmt { .. } => false,
+                _ => true,
+            }
+        }
+
+        fn post_rewrite_primary_name(&mut self, node: PrimaryName) -> PrimaryName {
+            self.0.post_rewrite_primary_name(node)
+        }
+    }
+
+    fn rename(from: &str, to: &str) -> Rename {
+        Rename(
+            get_name_table().intern(from, false),
+            get_name_table().intern(to, false),
+        )
+    }
+
+    #[test]
+    fn rewrite() {
+        let units = parse(SRC).walk_rewrite(&mut rename("x", "z"));
+        assert_eq!(
+            names(&units),
+            vec!["foo", "integer", "a", "b", "z", "c", "z", "d"]
+        );
+    }
+
+    #[test]
+    fn replace_nodes() {
+        let units = parse(SRC).walk_rewrite(&mut Lhs);
+        assert_eq!(
+            names(&units),
+            vec!["foo", "integer", "a", "x", "c", "x", "d"]
+        );
+    }
+
+    #[test]
+    fn skip_children() {
+        let units = parse(SRC).walk_rewrite(&mut SkipProcs(rename("x", "z")));
+        assert_eq!(
+            names(&units),
+            vec!["foo", "integer", "a", "b", "z", "c", "x", "d"]
+        );
+    }
+}
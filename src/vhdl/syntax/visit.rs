@@ -0,0 +1,183 @@
+// Copyright (c) 2016-2020 Fabian Schuiki
+
+//! An implementation of the visitor pattern for the AST.
+//!
+//! The [`Visitor`] trait is derived from the nodes in the [`ast`](crate::ast)
+//! module. For every node it has a `pre_visit_*` function, called before the
+//! node's children are visited, and a `post_visit_*` function, called after.
+//! Call `walk()` on a node to start visiting it.
+
+use crate::ast::NodeId;
+use crate::lexer::token::Literal;
+use moore_common::name::Name;
+use moore_common::source::{Span, Spanned};
+
+pub use crate::ast::Visitor;
+
+/// A node that accepts `Visitor`s.
+pub trait AcceptVisitor<'a> {
+    /// Walk a visitor over the contents of `self`.
+    fn accept(&'a self, visitor: &mut dyn Visitor<'a>);
+}
+
+/// A node that walks a `Visitor` over itself.
+pub trait WalkVisitor<'a> {
+    /// Walk a visitor over `self`.
+    fn walk(&'a self, visitor: &mut dyn Visitor<'a>);
+}
+
+impl<'a, T> WalkVisitor<'a> for Vec<T>
+where
+    T: WalkVisitor<'a>,
+{
+    fn walk(&'a self, visitor: &mut dyn Visitor<'a>) {
+        for c in self {
+            c.walk(visitor);
+        }
+    }
+}
+
+impl<'a, T> WalkVisitor<'a> for Option<T>
+where
+    T: WalkVisitor<'a>,
+{
+    fn walk(&'a self, visitor: &mut dyn Visitor<'a>) {
+        if let Some(c) = self {
+            c.walk(visitor);
+        }
+    }
+}
+
+impl<'a, T> WalkVisitor<'a> for Spanned<T>
+where
+    T: WalkVisitor<'a>,
+{
+    fn walk(&'a self, visitor: &mut dyn Visitor<'a>) {
+        self.value.walk(visitor);
+    }
+}
+
+impl<'a, T> WalkVisitor<'a> for Box<T>
+where
+    T: WalkVisitor<'a>,
+{
+    fn walk(&'a self, visitor: &mut dyn Visitor<'a>) {
+        self.as_ref().walk(visitor);
+    }
+}
+
+impl<'a, T0, T1> WalkVisitor<'a> for (T0, T1)
+where
+    T0: WalkVisitor<'a>,
+    T1: WalkVisitor<'a>,
+{
+    fn walk(&'a self, visitor: &mut dyn Visitor<'a>) {
+        self.0.walk(visitor);
+        self.1.walk(visitor);
+    }
+}
+
+macro_rules! leaf_impls {
+    ($($ty:ty),*) => {
+        $(
+            impl<'a> WalkVisitor<'a> for $ty {
+                fn walk(&'a self, _visitor: &mut dyn Visitor<'a>) {}
+            }
+        )*
+    };
+}
+
+leaf_impls!(NodeId, Span, Name, Literal, char, bool, ());
+
+#[cfg(test)]
+pub(crate) mod tests {
+    use super::*;
+    use crate::ast::*;
+    use moore_common::source::get_source_manager;
+
+    /// Collects the names referenced throughout the tree.
+    #[derive(Default)]
+    struct Names(Vec<String>);
+
+    impl<'a> Visitor<'a> for Names {
+        fn pre_visit_primary_name(&mut self, node: &'a PrimaryName) -> bool {
+            if let PrimaryNameKind::Ident(name) = node.kind {
+                self.0.push(name.as_str().to_string());
+            }
+            true
+        }
+    }
+
+    /// Counts the binary expressions in the tree.
+    #[derive(Default)]
+    struct Binaries(usize);
+
+    impl<'a> Visitor<'a> for Binaries {
+        fn pre_visit_expr(&mut self, node: &'a Expr) -> bool {
+            if let BinaryExpr(..) = node.data {
+                self.0 += 1;
+            }
+            true
+        }
+    }
+
+    /// Counts the statements in the tree, without descending into processes.
+    #[derive(Default)]
+    struct Stmts(usize);
+
+    impl<'a> Visitor<'a> for Stmts {
+        fn pre_visit_stmt(&mut self, node: &'a Stmt) -> bool {
+            self.0 += 1;
+            match node.data {
+                ProcStmt { .. } => false,
+                _ => true,
+            }
+        }
+    }
+
+    pub(crate) const SRC: &str = "
+        entity foo is end;
+        architecture bar of foo is
+            signal x : integer := a + b;
+        begin
+            x <= c;
+            process begin
+                x <= d;
+                wait;
+            end process;
+        end;
+    ";
+
+    pub(crate) fn parse(content: &str) -> Vec<DesignUnit> {
+        let src = get_source_manager().add_anonymous(content);
+        crate::parse(src).expect("parser failed")
+    }
+
+    pub(crate) fn names(units: &[DesignUnit]) -> Vec<String> {
+        let mut v = Names::default();
+        for u in units {
+            u.walk(&mut v);
+        }
+        v.0
+    }
+
+    #[test]
+    fn visit() {
+        let units = parse(SRC);
+        assert_eq!(
+            names(&units),
+            vec!["foo", "integer", "a", "b", "x", "c", "x", "d"]
+        );
+        let mut v = Binaries::default();
+        units.walk(&mut v);
+        assert_eq!(v.0, 1);
+    }
+
+    #[test]
+    fn skip_children() {
+        let units = parse(SRC);
+        let mut v = Stmts::default();
+        units.walk(&mut v);
+        assert_eq!(v.0, 2);
+    }
+}
@@ -7,11 +7,11 @@
 use std::collections::{HashMap, HashSet};
 use std::marker::PhantomData;
 
-use num::{BigInt, BigRational};
+use num::{BigInt, BigRational, ToPrimitive};
 
 use crate::common::errors::*;
 use crate::common::name::Name;
-use crate::common::score::Result;
+use crate::common::score::{Error, Result};
 use crate::common::source::*;
 use crate::common::util::*;
 use crate::common::SessionContext;
@@ -19,7 +19,7 @@ use crate::common::SessionContext;
 use crate::add_ctx::AddContext;
 use crate::arenas::Alloc;
 use crate::hir;
-use crate::konst::ConstInt;
+use crate::konst::{ConstFloat, ConstInt};
 use crate::op::*;
 use crate::scope2::{Def2, ScopeData, TypeVariantDef};
 use crate::score::*;
@@ -63,6 +63,8 @@ pub enum Term<'t> {
     Enum(Vec<Spanned<EnumRef>>),
     /// A term that refers to an enum variant.
     Enum2(Vec<Spanned<Def2<'t>>>),
+    /// A term that refers to one or more overloaded subprograms.
+    Subprog(Vec<Spanned<SubprogRef>>),
     /// A term of the form `T.<name>`.
     Select(Subterm<'t>, Spanned<ResolvableName>),
     /// A term of the form `T.all`.
@@ -94,6 +96,11 @@ pub enum Term<'t> {
     Binary(Spanned<BinaryOp>, Subterm<'t>, Subterm<'t>),
     /// A term of the form `T'T`.
     Qual(Subterm<'t>, Subterm<'t>),
+    /// A term of the form `T'<attribute>`, naming a predefined attribute
+    /// function.
+    Attr(Subterm<'t>, Spanned<hir::TypeAttrFn>),
+    /// A term of the form `T'base`.
+    Base(Subterm<'t>),
     /// A term of the form `new T`.
     New(Subterm<'t>),
 }
@@ -178,16 +185,17 @@ where
                 Literal::Abstract(base, int, frac, exp) => {
                     let base = match base {
                         Some(base) => match base.as_str().parse() {
-                            Ok(base) => base,
-                            Err(_) => {
+                            Ok(base) if base >= 2 && base <= 16 => base,
+                            _ => {
                                 self.emit(
                                     DiagBuilder2::error(format!(
                                         "`{}` is not a valid base for a number literal",
                                         base
                                     ))
-                                    .span(ast.span),
+                                    .span(ast.span)
+                                    .add_note("The base must be between 2 and 16"),
                                 );
-                                return Err(());
+                                return Err(Error::Reported);
                             }
                         },
                         None => 10,
@@ -202,7 +210,7 @@ where
                                 ))
                                 .span(ast.span),
                             );
-                            return Err(());
+                            return Err(Error::Reported);
                         }
                     };
                     let exp: isize = match exp {
@@ -222,7 +230,7 @@ where
                                     ))
                                     .span(ast.span),
                                 );
-                                return Err(());
+                                return Err(Error::Reported);
                             }
                         },
                         None => 0,
@@ -242,7 +250,7 @@ where
                                     ))
                                     .span(ast.span),
                                 );
-                                return Err(());
+                                return Err(Error::Reported);
                             }
                         };
                         let num = int * pow(BigInt::from(base), shift) + frac;
@@ -264,7 +272,7 @@ where
                                 ))
                                 .span(ast.span),
                             );
-                            return Err(());
+                            return Err(Error::Reported);
                         }
                         if exp > 0 {
                             use num::pow;
@@ -283,7 +291,7 @@ where
                         .span(ast.span)
                         .add_note(format!("{:?}", wrong)),
                     );
-                    return Err(());
+                    return Err(Error::Reported);
                 }
             },
             ast.span,
@@ -298,7 +306,7 @@ where
         match term.value {
             Term::Unresolved(name) => {
                 self.emit(DiagBuilder2::error(format!("`{}` is unknown", name)).span(term.span));
-                Err(())
+                Err(Error::Reported)
             }
             _ => Ok(term),
         }
@@ -339,7 +347,23 @@ where
                         ))
                         .span(sig.span),
                     );
-                    return Err(());
+                    return Err(Error::Reported);
+                }
+                ast::NamePart::Attribute(ident)
+                    if ident.name.as_str().eq_ignore_ascii_case("base") =>
+                {
+                    let sp = Span::union(term.span, ident.span);
+                    Spanned::new(Term::Base(Box::new(term)), sp)
+                }
+                ast::NamePart::Attribute(ident)
+                    if hir::TypeAttrFn::from_name(&ident.name.as_str()).is_some() =>
+                {
+                    let func = hir::TypeAttrFn::from_name(&ident.name.as_str()).unwrap();
+                    let sp = Span::union(term.span, ident.span);
+                    Spanned::new(
+                        Term::Attr(Box::new(term), Spanned::new(func, ident.span)),
+                        sp,
+                    )
                 }
                 ast::NamePart::Attribute(ident) => {
                     let attr = self.termify_name(Spanned::new(ident.name.into(), ident.span))?;
@@ -360,7 +384,7 @@ where
                                 .add_note("Declared here:")
                                 .span(other.span),
                             );
-                            return Err(());
+                            return Err(Error::Reported);
                         }
                         _ => unreachable!(),
                     }
@@ -479,7 +503,7 @@ where
                                 .span(unit.span),
                             );
                             debugln!("It is a {:#?}", unit.value);
-                            return Err(());
+                            return Err(Error::Reported);
                         }
                     };
                     let lit = match lit.value {
@@ -493,7 +517,7 @@ where
                                 .span(lit.span),
                             );
                             debugln!("It is a {:#?}", lit.value);
-                            return Err(());
+                            return Err(Error::Reported);
                         }
                     };
                     Term::PhysLit(lit, unit)
@@ -542,7 +566,7 @@ where
                     .span(ast.span)
                     .add_note(format!("{:?}", wrong)),
                 );
-                return Err(());
+                return Err(Error::Reported);
             }
         };
         Ok(self.fold(Spanned::new(term, ast.span)))
@@ -572,6 +596,20 @@ impl<'t, C: DiagEmitter, S> DefSpecificTermContext<'t, Def> for TermContext<C, S
             return Ok(name.map(Term::Unresolved));
         }
 
+        // Subprograms can be overloaded. Keep all of them around and pick the
+        // right one once the arguments are known.
+        fn as_subprog(def: &Spanned<Def>) -> Option<Spanned<SubprogRef>> {
+            match def.value {
+                Def::Subprog(id) => Some(Spanned::new(id.into(), def.span)),
+                Def::SubprogBody(id) => Some(Spanned::new(id.into(), def.span)),
+                Def::SubprogInst(id) => Some(Spanned::new(id.into(), def.span)),
+                _ => None,
+            }
+        }
+        if let Some(subprogs) = defs.iter().map(as_subprog).collect::<Option<Vec<_>>>() {
+            return Ok(self.fold(Spanned::new(Term::Subprog(subprogs), name.span)));
+        }
+
         fn is_enum(def: &Spanned<Def>) -> bool {
             match def.value {
                 Def::Enum(..) => true,
@@ -609,7 +647,7 @@ impl<'t, C: DiagEmitter, S> DefSpecificTermContext<'t, Def> for TermContext<C, S
                     }
                 }
                 self.emit(d);
-                return Err(());
+                return Err(Error::Reported);
             }
             _ => Term::Ident(first_def),
         };
@@ -665,7 +703,7 @@ impl<'t, C: DiagEmitter, S> DefSpecificTermContext<'t, Def2<'t>> for TermContext
                     }
                 }
                 self.emit(d);
-                return Err(());
+                return Err(Error::Reported);
             }
             _ => Term::Ident2(first_def),
         };
@@ -789,9 +827,20 @@ where
         let data = match term.value {
             Term::Unresolved(name) => {
                 self.emit(DiagBuilder2::error(format!("`{}` is unknown", name)).span(term.span));
-                return Err(());
+                return Err(Error::Reported);
             }
             Term::IntLit(value) => hir::ExprData::IntegerLiteral(ConstInt::new(None, value)),
+            Term::FloatLit(value) => {
+                let value = value.numer().to_f64().unwrap() / value.denom().to_f64().unwrap();
+                hir::ExprData::FloatLiteral(ConstFloat::new(value))
+            }
+            Term::PhysLit(
+                value,
+                Spanned {
+                    value: EitherUnit::Old(unit),
+                    span,
+                },
+            ) => hir::ExprData::PhysicalLiteral(value, Spanned::new(unit, span)),
             Term::StrLit(value) => {
                 // Create a set of characters used in the literal. Then resolve
                 // each as an individual bit literal. This yields multiple enums
@@ -812,7 +861,7 @@ where
                                     DiagBuilder2::error(format!("`{}` is unknown", name))
                                         .span(term.span),
                                 );
-                                Err(())
+                                Err(Error::Reported)
                             }
                             Term::Enum(ids) => Ok((chr, ids.into_iter().collect())),
                             _ => {
@@ -822,7 +871,7 @@ where
                                         .add_note(format!("`{}` has been defined here:", rn.value))
                                         .span(term.span),
                                 );
-                                Err(())
+                                Err(Error::Reported)
                             }
                         }
                     })
@@ -890,11 +939,20 @@ where
                 debugln!("resolved binary op `{}` to {:?}", name.value, defs);
                 hir::ExprData::Binary(op, defs, self.term_to_expr(*lhs)?, self.term_to_expr(*rhs)?)
             }
+            // Ranges occur as the argument of slice names, e.g. `a(7 downto 4)`.
+            Term::Range(dir, lb, rb) => {
+                hir::ExprData::Range(dir.value, self.term_to_expr(*lb)?, self.term_to_expr(*rb)?)
+            }
             Term::Ident(def) => match def.value {
                 Def::Const(id) => hir::ExprData::ConstName(id),
+                Def::GenParam(id) => hir::ExprData::GenParamName(id),
                 Def::Signal(id) => hir::ExprData::SignalName(id),
                 Def::Var(id) => hir::ExprData::VarName(id),
                 Def::File(id) => hir::ExprData::FileName(id),
+                Def::IntfConst(id) => hir::ExprData::IntfConstName(id),
+                Def::BuiltinConst(id) => {
+                    hir::ExprData::FloatLiteral(ConstFloat::new(builtin_const_value(id)))
+                }
                 _ => {
                     self.emit(
                         DiagBuilder2::error(format!(
@@ -905,10 +963,15 @@ where
                         .add_note(format!("`{}` was declared here:", term_span.extract()))
                         .span(def.span),
                     );
-                    return Err(());
+                    return Err(Error::Reported);
                 }
             },
             Term::Enum(defs) => hir::ExprData::EnumName(defs),
+            Term::Subprog(defs) => {
+                let args = Spanned::new(vec![], term_span.end().into());
+                let (def, args) = self.resolve_subprog_call(defs, args, term_span, false)?;
+                hir::ExprData::SubprogCall(def, args)
+            }
             Term::Select(term, name) => hir::ExprData::Select(self.term_to_expr(*term)?, name),
             Term::Paren(subterm) => {
                 // A parenthesis with only one element is just a parenthesized
@@ -971,7 +1034,7 @@ where
                                 ))
                                 .span(args.span),
                             );
-                            return Err(());
+                            return Err(Error::Reported);
                         }
                         let arg = args.value.into_iter().next().unwrap();
                         if let Some(formal) = arg.formal {
@@ -993,7 +1056,7 @@ where
                                     ))
                                     .span(arg.actual.span),
                                 );
-                                return Err(());
+                                return Err(Error::Reported);
                             }
                         };
                         self.ctx.set_type_context(
@@ -1002,6 +1065,73 @@ where
                         );
                         hir::ExprData::Cast(tm, arg)
                     }
+                    Term::Ident(Spanned {
+                        value: Def::BuiltinFn(id),
+                        span,
+                    }) => {
+                        let func = math_real_fn(id);
+                        let (min, max) = func.arity();
+                        if args.value.len() < min || args.value.len() > max {
+                            self.emit(
+                                DiagBuilder2::error(format!(
+                                    "`{}` takes {} arguments, but {} were given",
+                                    func.name(),
+                                    if min == max {
+                                        format!("{}", min)
+                                    } else {
+                                        format!("{} to {}", min, max)
+                                    },
+                                    args.value.len()
+                                ))
+                                .span(args.span),
+                            );
+                            return Err(Error::Reported);
+                        }
+                        let real_ty = self.ctx.intern_ty(REAL_TYPE.named_ty());
+                        for arg in &args.value {
+                            if let hir::AssocActual::Expr(id) = arg.actual.value {
+                                self.ctx.set_type_context(id, real_ty);
+                            }
+                        }
+                        hir::ExprData::BuiltinCall(Spanned::new(id, span), args)
+                    }
+                    Term::Attr(prefix, func) => {
+                        let tm = self.term_to_attr_prefix(*prefix)?;
+                        let arg = match args.value.as_slice() {
+                            [hir::AssocElement {
+                                formal: None,
+                                actual:
+                                    Spanned {
+                                        value: hir::AssocActual::Expr(id),
+                                        ..
+                                    },
+                                ..
+                            }] => *id,
+                            _ => {
+                                self.emit(
+                                    DiagBuilder2::error(format!(
+                                        "`{}` must have exactly one positional argument",
+                                        term_span.extract()
+                                    ))
+                                    .span(args.span),
+                                );
+                                return Err(Error::Reported);
+                            }
+                        };
+                        // All but `T'val` take a value of the type itself.
+                        if func.value != hir::TypeAttrFn::Val {
+                            self.ctx.set_type_context(
+                                arg,
+                                self.ctx.intern_ty(Ty::Named(tm.span.into(), tm.value)),
+                            );
+                        }
+                        hir::ExprData::TypeAttrCall(tm, func, arg)
+                    }
+                    Term::Subprog(defs) => {
+                        let (def, args) =
+                            self.resolve_subprog_call(defs, args, term_span, false)?;
+                        hir::ExprData::SubprogCall(def, args)
+                    }
                     other => hir::ExprData::Call(
                         self.term_to_expr(Spanned::new(other, callee.span))?,
                         args,
@@ -1018,7 +1148,7 @@ where
                     .span(term.span),
                 );
                 debugln!("It is a {:#?}", term);
-                return Err(());
+                return Err(Error::Reported);
             }
         };
         Ok(hir::Expr {
@@ -1028,10 +1158,134 @@ where
         })
     }
 
+    /// Map a term to the procedure and actuals of a procedure call statement.
+    pub fn term_to_proc_call(
+        &self,
+        term: Spanned<Term>,
+    ) -> Result<(Spanned<SubprogRef>, Spanned<hir::AssocList>)> {
+        let term_span = term.span;
+        match term.value {
+            Term::Subprog(defs) => {
+                let args = Spanned::new(vec![], term_span.end().into());
+                self.resolve_subprog_call(defs, args, term_span, true)
+            }
+            Term::SuffixParen(callee, args) => match callee.value {
+                Term::Subprog(defs) => {
+                    let args = self.term_to_assoc_list(*args)?;
+                    self.resolve_subprog_call(defs, args, callee.span, true)
+                }
+                _ => {
+                    self.emit(
+                        DiagBuilder2::error(format!(
+                            "`{}` is not a procedure",
+                            callee.span.extract()
+                        ))
+                        .span(callee.span),
+                    );
+                    Err(Error::Reported)
+                }
+            },
+            _ => {
+                self.emit(
+                    DiagBuilder2::error(format!("`{}` is not a procedure", term_span.extract()))
+                        .span(term_span),
+                );
+                Err(Error::Reported)
+            }
+        }
+    }
+
+    /// Resolve a call to an overloaded subprogram.
+    ///
+    /// Picks the one procedure or function whose parameter count matches the
+    /// arguments, preferring bodies over the declarations they complete, and
+    /// sets the type context of the arguments to the parameter types.
+    fn resolve_subprog_call(
+        &self,
+        defs: Vec<Spanned<SubprogRef>>,
+        args: Spanned<hir::AssocList>,
+        span: Span,
+        proc: bool,
+    ) -> Result<(Spanned<SubprogRef>, Spanned<hir::AssocList>)> {
+        if let Some(formal) = args.value.iter().filter_map(|a| a.formal).next() {
+            self.emit(
+                DiagBuilder2::bug(format!(
+                    "named association `{}` in subprogram calls not implemented",
+                    formal.span.extract()
+                ))
+                .span(formal.span),
+            );
+            return Err(Error::Reported);
+        }
+
+        // Determine the type of each candidate and drop the ones that do not
+        // take the given number of arguments.
+        let mut cands = vec![];
+        for def in defs {
+            match *self.ctx.ty(def.value)? {
+                Ty::Subprog(ref ty)
+                    if ty.args.len() == args.value.len() && ty.ret.is_none() == proc =>
+                {
+                    cands.push((def, ty))
+                }
+                _ => (),
+            }
+        }
+
+        // Declarations are completed by a body with the same profile. Only
+        // keep the latter in that case.
+        let bodies: Vec<_> = cands
+            .iter()
+            .filter(|(def, _)| match def.value {
+                SubprogRef::Body(_) => true,
+                _ => false,
+            })
+            .map(|&(_, ty)| ty)
+            .collect();
+        cands.retain(|(def, ty)| match def.value {
+            SubprogRef::Decl(_) => !bodies.iter().any(|b| b.same_profile(ty)),
+            _ => true,
+        });
+
+        let (def, ty) = match cands.len() {
+            1 => cands[0],
+            0 => {
+                self.emit(
+                    DiagBuilder2::error(format!(
+                        "no {} `{}` takes {} arguments",
+                        if proc { "procedure" } else { "function" },
+                        span.extract(),
+                        args.value.len()
+                    ))
+                    .span(span),
+                );
+                return Err(Error::Reported);
+            }
+            _ => {
+                let mut d = DiagBuilder2::error(format!("`{}` is ambiguous", span.extract()))
+                    .span(span)
+                    .add_note("Found the following subprograms:");
+                for (def, _) in cands {
+                    d = d.span(def.span);
+                }
+                self.emit(d);
+                return Err(Error::Reported);
+            }
+        };
+        for (arg, param) in args.value.iter().zip(ty.args.iter()) {
+            if let hir::AssocActual::Expr(id) = arg.actual.value {
+                self.ctx
+                    .set_type_context(id, self.ctx.intern_ty(param.ty.clone()));
+            }
+        }
+        Ok((def, args))
+    }
+
     /// Map a term to a type mark.
     pub fn term_to_type_mark(&self, term: Spanned<Term>) -> Result<Spanned<TypeMarkRef>> {
         match term.value {
             Term::TypeMark(tm) => Ok(tm),
+            Term::Base(_) => self.misplaced_base(term.span),
             _ => {
                 self.emit(
                     DiagBuilder2::error(format!(
@@ -1041,11 +1295,38 @@ where
                     .span(term.span),
                 );
                 debugln!("It is a {:#?}", term);
-                return Err(());
+                return Err(Error::Reported);
             }
         }
     }
 
+    /// Map a term to the type mark prefix of an attribute.
+    ///
+    /// Unlike other type marks, the prefix may be of the form `T'base`, which
+    /// denotes the base type of `T`. See IEEE 1076-2008 section 16.2.2.
+    pub fn term_to_attr_prefix(&self, term: Spanned<Term>) -> Result<Spanned<TypeMarkRef>> {
+        match term.value {
+            Term::Base(prefix) => {
+                let tm = self.term_to_attr_prefix(*prefix)?;
+                let base = self.ctx.base_type(tm.value)?;
+                Ok(Spanned::new(base.into(), term.span))
+            }
+            _ => self.term_to_type_mark(term),
+        }
+    }
+
+    /// Report a `T'base` term used outside of an attribute prefix.
+    fn misplaced_base<T>(&self, span: Span) -> Result<T> {
+        self.emit(
+            DiagBuilder2::error(format!(
+                "`{}` may only be used as the prefix of another attribute",
+                span.extract()
+            ))
+            .span(span),
+        );
+        Err(Error::Reported)
+    }
+
     /// Perform term folding expecting to yield a type.
     ///
     /// This is a pre-processing step on terms. It is applied as soon as it is
@@ -1056,7 +1337,7 @@ where
         let (new, new_term) = match term.value {
             Term::Unresolved(name) => {
                 self.emit(DiagBuilder2::error(format!("`{}` is unknown", name)).span(term.span));
-                return Err(());
+                return Err(Error::Reported);
             }
             Term::RangeSuffix(subterm, range) => {
                 let subterm = self.fold_term_as_type(*subterm)?;
@@ -1097,6 +1378,18 @@ where
                     ),
                 }
             }
+            Term::PrefixParen(resol, subterm) => {
+                let subterm = self.fold_term_as_type(*subterm)?;
+                match subterm.value {
+                    // Fold `R TypeMark` to `SubtypeInd`.
+                    Term::TypeMark(tm) => (true, Term::SubtypeInd(tm, Some(resol), None)),
+                    // Fold `R SubtypeInd` to `SubtypeInd`.
+                    Term::SubtypeInd(tm, None, con) => {
+                        (true, Term::SubtypeInd(tm, Some(resol), con))
+                    }
+                    _ => (false, Term::PrefixParen(resol, Box::new(subterm))),
+                }
+            }
             others => (false, others),
         };
         let new_term = Spanned::new(new_term, term.span);
@@ -1113,6 +1406,7 @@ where
         let (tm, resol, con) = match term.value {
             Term::SubtypeInd(tm, resol, con) => (tm, resol, con),
             Term::TypeMark(tm) => (tm, None, None),
+            Term::Base(_) => return self.misplaced_base(term.span),
             _ => {
                 self.emit(
                     DiagBuilder2::error(format!(
@@ -1122,10 +1416,10 @@ where
                     .span(term.span),
                 );
                 debugln!("It is a {:#?}", term);
-                return Err(());
+                return Err(Error::Reported);
             }
         };
-        let _resol = match resol {
+        let resol = match resol {
             Some(x) => Some(self.term_to_resolution_indication(*x)?),
             None => None,
         };
@@ -1137,8 +1431,8 @@ where
             hir::SubtypeInd {
                 span: term.span,
                 type_mark: tm,
-                // TODO: Track resolution indication.
                 constraint: con,
+                resolution: resol,
             },
             term.span,
         ))
@@ -1148,15 +1442,51 @@ where
     }
 
     /// Map a term to a resolution indication.
-    pub fn term_to_resolution_indication(&self, term: Spanned<Term>) -> Result<Spanned<()>> {
-        self.emit(
-            DiagBuilder2::bug(format!(
-                "interpretation of `{}` as a resolution indication not implemented",
-                term.span.extract()
-            ))
-            .span(term.span),
-        );
-        Err(())
+    ///
+    /// Only resolution function names are supported, not the element
+    /// resolutions of composite subtypes. See IEEE 1076-2008 section 6.3.
+    pub fn term_to_resolution_indication(
+        &self,
+        term: Spanned<Term>,
+    ) -> Result<Spanned<SubprogRef>> {
+        match term.value {
+            Term::Subprog(ref defs) if defs.len() == 1 => {
+                Ok(Spanned::new(defs[0].value, term.span))
+            }
+            Term::Subprog(ref defs) => {
+                let mut diag = DiagBuilder2::error(format!(
+                    "resolution function `{}` is ambiguous",
+                    term.span.extract()
+                ))
+                .span(term.span)
+                .add_note("Found the following subprograms:");
+                for def in defs {
+                    diag = diag.span(def.span);
+                }
+                self.emit(diag);
+                Err(Error::Reported)
+            }
+            Term::Paren(..) | Term::Aggregate(..) => {
+                self.emit(
+                    DiagBuilder2::bug(format!(
+                        "interpretation of `{}` as a resolution indication not implemented",
+                        term.span.extract()
+                    ))
+                    .span(term.span),
+                );
+                Err(Error::Reported)
+            }
+            _ => {
+                self.emit(
+                    DiagBuilder2::error(format!(
+                        "`{}` is not a resolution function",
+                        term.span.extract()
+                    ))
+                    .span(term.span),
+                );
+                Err(Error::Reported)
+            }
+        }
     }
 
     /// Map a term to a constraint.
@@ -1205,7 +1535,7 @@ where
                     ),
                 );
                 debugln!("It is a {:#?}", term);
-                return Err(());
+                return Err(Error::Reported);
             }
         }
     }
@@ -1219,7 +1549,7 @@ where
     ) -> Result<Spanned<hir::ArrayConstraint>> {
         if terms.is_empty() {
             self.emit(DiagBuilder2::error(format!("array constraint cannot be empty")).span(span));
-            return Err(());
+            return Err(Error::Reported);
         }
         let indices = if terms.len() == 1 && terms[0].value == Term::Open {
             vec![]
@@ -1251,7 +1581,7 @@ where
     ) -> Result<Spanned<hir::RecordConstraint>> {
         if terms.is_empty() {
             self.emit(DiagBuilder2::error(format!("record constraint cannot be empty")).span(span));
-            return Err(());
+            return Err(Error::Reported);
         }
         let mut fields = Vec::new();
         let mut has_fails = false;
@@ -1314,7 +1644,7 @@ where
             fields.push((name, Box::new(self.term_to_element_constraint(con)?)));
         }
         if has_fails {
-            return Err(());
+            return Err(Error::Reported);
         }
         Ok(Spanned::new(
             hir::RecordConstraint {
@@ -1348,7 +1678,7 @@ where
                         ),
                     );
                     debugln!("It is a {:#?}", con);
-                    return Err(());
+                    return Err(Error::Reported);
                 }
             },
             con.span,
@@ -1384,7 +1714,7 @@ where
                     .add_note("See IEEE 1076-2008 section 5.3.2.1."),
                 );
                 debugln!("It is a {:#?}", term);
-                return Err(());
+                return Err(Error::Reported);
             }
         })
     }
@@ -1413,7 +1743,7 @@ where
                         .add_note("See IEEE 1076-2008 section 5.2.1."),
                     );
                     debugln!("It is a {:#?}", term);
-                    return Err(());
+                    return Err(Error::Reported);
                 }
             },
             term.span,
@@ -1427,10 +1757,11 @@ where
         Ok(match term.value {
             Term::Unresolved(name) => {
                 self.emit(DiagBuilder2::error(format!("`{}` is unknown", name)).span(term.span));
-                return Err(());
+                return Err(Error::Reported);
             }
             Term::Ident(def) => def,
             Term::TypeMark(tm) => tm.map_into(),
+            Term::Subprog(ref defs) if defs.len() == 1 => defs[0].map_into(),
             Term::Enum(defs) => {
                 if defs.len() == 1 {
                     let e = defs.into_iter().next().unwrap();
@@ -1441,7 +1772,7 @@ where
                             .span(term.span),
                     );
                     debugln!("Its definitions are {:#?}", defs);
-                    return Err(());
+                    return Err(Error::Reported);
                 }
             }
             _ => {
@@ -1450,7 +1781,7 @@ where
                         .span(term.span),
                 );
                 debugln!("It is a {:#?}", term);
-                return Err(());
+                return Err(Error::Reported);
             }
         })
     }
@@ -1475,7 +1806,7 @@ where
                         .span(def.span),
                     );
                     debugln!("The definition is a {:?}", def.value);
-                    return Err(());
+                    return Err(Error::Reported);
                 }
             },
             span,
@@ -1497,7 +1828,7 @@ where
                             .span(def.span),
                     );
                     debugln!("The definition is a {:?}", def.value);
-                    return Err(());
+                    return Err(Error::Reported);
                 }
             },
             span,
@@ -1517,9 +1848,11 @@ where
                 Term::SubtypeInd(..) | Term::TypeMark(..) | Term::Range(..) => {
                     hir::Choice::DiscreteRange(self.term_to_discrete_range(term)?.value)
                 }
-                Term::IntLit(..) | Term::Unary(..) | Term::Binary(..) => {
-                    hir::Choice::Expr(self.term_to_expr(term)?)
-                }
+                Term::IntLit(..)
+                | Term::Enum(..)
+                | Term::Ident(..)
+                | Term::Unary(..)
+                | Term::Binary(..) => hir::Choice::Expr(self.term_to_expr(term)?),
                 _ => {
                     self.emit(
                         DiagBuilder2::error(format!(
@@ -1535,7 +1868,7 @@ where
                         .add_note("See IEEE 1076-2008 section 9.3.3.1."),
                     );
                     debugln!("It is a {:#?}", term);
-                    return Err(());
+                    return Err(Error::Reported);
                 }
             },
             term_span,
@@ -1588,7 +1921,7 @@ where
                     .add_note("See IEEE 1076-2008 section 9.3.3.1."),
                 );
                 debugln!("It is a {:#?}", term);
-                return Err(());
+                return Err(Error::Reported);
             }
         };
 
@@ -1616,7 +1949,7 @@ where
                          1076-2008 section 9.3.3.1.",
                     ),
                 );
-                return Err(());
+                return Err(Error::Reported);
             }
 
             // Handle positional elements.
@@ -1631,7 +1964,7 @@ where
                         .span(field.value.1.span)
                         .add_note("See IEEE 1076-2008 section 9.3.3.1."),
                     );
-                    return Err(());
+                    return Err(Error::Reported);
                 }
                 positional.push(field.value.1);
             }
@@ -1643,7 +1976,7 @@ where
                             .span(field.value.1.span)
                             .add_note("See IEEE 1076-2008 section 9.3.3.1."),
                     );
-                    return Err(());
+                    return Err(Error::Reported);
                 }
                 others = Some(field.value.1);
                 mode = Mode::Others;
@@ -1695,7 +2028,7 @@ where
                                                          IEEE 1076-2008 section 9.3.3.1.",
                                                     ),
                                                 );
-                                                return Err(());
+                                                return Err(Error::Reported);
                                             }
                                         };
                                         Ok(Spanned::new(choice, span))
@@ -1746,7 +2079,7 @@ where
                                                          IEEE 1076-2008 section 9.3.3.1.",
                                                     ),
                                                 );
-                                                return Err(());
+                                                return Err(Error::Reported);
                                             }
                                         };
                                         Ok(Spanned::new(choice, span))
@@ -1806,7 +2139,7 @@ where
                                 )
                                 .span(term_span),
                             );
-                            return Err(());
+                            return Err(Error::Reported);
                         }
                         let formal = formal.into_iter().next().unwrap();
                         let formal_span = formal.span;
@@ -1832,7 +2165,7 @@ where
                         .add_note("See IEEE 1076-2008 section 6.5.7."),
                     );
                     debugln!("It is a {:#?}", term);
-                    return Err(());
+                    return Err(Error::Reported);
                 }
             },
             term_span,
@@ -1883,7 +2216,7 @@ where
                     .add_note("See IEEE 1076-2008 section 5.2.1."),
             );
             debugln!("It is a {:#?}", term);
-            return Err(());
+            return Err(Error::Reported);
         }
     };
     Ok(Spanned::new(v, term.span))
@@ -1897,7 +2230,7 @@ where
     match term.value {
         Term::Unresolved(name) => {
             ctx.emit(DiagBuilder2::error(format!("`{}` is unknown", name)).span(term.span));
-            Err(())
+            Err(Error::Reported)
         }
         Term::IntLit(value) => Ok(ctx.alloc(hir::LitExpr::new_integer(term.span, value))),
         Term::FloatLit(value) => Ok(ctx.alloc(hir::LitExpr::new_float(term.span, value))),
@@ -1911,7 +2244,7 @@ where
                 .span(term.span),
             );
             debugln!("It is a {:#?}", term);
-            Err(())
+            Err(Error::Reported)
         }
     }
 }
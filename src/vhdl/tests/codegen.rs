@@ -0,0 +1,268 @@
+// Copyright (c) 2016-2020 Fabian Schuiki
+
+//! Snapshot tests of the VHDL code generation.
+//!
+//! Each `.vhd` file in `tests/codegen` is compiled, and all entities declared
+//! in it are elaborated with their last architecture. The resulting LLHD
+//! assembly, annotated with the verification directives of each entity, is
+//! compared against the `.llhd` file of the same name. Run the
+//! tests with `UPDATE_SNAPSHOTS=1` to write the current output to the `.llhd`
+//! files instead, e.g. after adding a new test or changing the codegen. The
+//! output must also define every unit once, and define every unit it
+//! instantiates.
+//!
+//! A file starting with `-- @two-state POLICY` is compiled with two-state
+//! lowering, as with the `--two-state` and `--x-policy` options. A file
+//! starting with `-- @severity FROM=TO` remaps the severity of its assertions,
+//! as with the `--severity` option. A file starting with `-- @vhdl-std YEAR`
+//! is compiled against that revision of the standard, as with the
+//! `--vhdl-std` option. A file starting with `-- @generics NAME=VALUES ...`
+//! elaborates each entity once for every combination of the comma-separated
+//! generic values, as with the `-P` option. A file starting with
+//! `-- @keep-going` skips the entities that fail to elaborate, as with the
+//! `--keep-going` option. A file starting with `-- @warnings N` must produce
+//! exactly N warnings; other files may produce any number.
+
+use moore_common::name::{get_name_table, Name};
+use moore_common::score::{CancelToken, GenericContext, NodeRef};
+use moore_common::source::get_source_manager;
+use moore_common::{MessageSeverity, NodeId, Scheduling, Session, VhdlStandard, XPolicy};
+use moore_vhdl::lazy::LazyPhaseTable;
+use moore_vhdl::score::{Arenas, Def, LibRef, ResolvableName, ScopeRef, ScoreBoard, ScoreContext};
+use std::path::{Path, PathBuf};
+
+struct Global;
+
+impl GenericContext for Global {}
+
+/// Compile a file and generate the LLHD assembly of all its entities.
+fn compile(path: &Path) -> Result<String, String> {
+    let mut sess = Session::new();
+    let mut sweep: Option<Vec<Vec<(Name, i64)>>> = None;
+    let mut keep_going = false;
+    let mut warnings = None;
+    let text = std::fs::read_to_string(path).map_err(|e| e.to_string())?;
+    if let Some(header) = text.lines().next() {
+        if let Some(policy) = directive(header, "two-state") {
+            sess.opts.two_state = match policy.trim() {
+                "" => Some(XPolicy::Zero),
+                name => Some(XPolicy::from_name(name).ok_or("invalid x-policy")?),
+            };
+        }
+        if let Some(mapping) = directive(header, "severity") {
+            let mut parts = mapping.trim().splitn(2, '=');
+            let mut severity = || {
+                parts
+                    .next()
+                    .and_then(MessageSeverity::from_name)
+                    .ok_or("invalid severity mapping")
+            };
+            let from = severity()?;
+            let to = severity()?;
+            sess.opts.severity_map.push((from, to));
+        }
+        if let Some(mode) = directive(header, "scheduling") {
+            sess.opts.scheduling =
+                Scheduling::from_name(mode.trim()).ok_or("invalid scheduling mode")?;
+        }
+        if let Some(year) = directive(header, "vhdl-std") {
+            sess.opts.vhdl_std =
+                VhdlStandard::from_year(year.trim()).ok_or("invalid VHDL standard")?;
+        }
+        if let Some(overrides) = directive(header, "generics") {
+            let mut configs = vec![vec![]];
+            for arg in overrides.split_whitespace() {
+                let mut parts = arg.splitn(2, '=');
+                let name = get_name_table().intern(parts.next().unwrap(), false);
+                let values = parts
+                    .next()
+                    .ok_or("invalid generic override")?
+                    .split(',')
+                    .map(|v| v.parse().map_err(|_| "invalid generic value"))
+                    .collect::<Result<Vec<i64>, _>>()?;
+                configs = configs
+                    .into_iter()
+                    .flat_map(|config: Vec<_>| {
+                        values.iter().map(move |&v| {
+                            let mut config = config.clone();
+                            config.push((name, v));
+                            config
+                        })
+                    })
+                    .collect();
+            }
+            sweep = Some(configs);
+        }
+        keep_going = directive(header, "keep-going").is_some();
+        if let Some(count) = directive(header, "warnings") {
+            warnings = Some(count.trim().parse().map_err(|_| "invalid warning count")?);
+        }
+    }
+    let source = get_source_manager()
+        .open(path.to_str().unwrap())
+        .ok_or_else(|| format!("cannot open {}", path.display()))?;
+    let units = moore_vhdl::syntax::parse(source).map_err(|_| "syntax error".to_string())?;
+    let arenas = Arenas::new();
+    let sb = ScoreBoard::new(&arenas);
+    let lazy = LazyPhaseTable::new(&sb);
+    let cancel = CancelToken::new();
+    let ctx = ScoreContext {
+        sess: &sess,
+        global: &Global,
+        sb: &sb,
+        lazy: &lazy,
+        cancel: &cancel,
+    };
+    let lib = LibRef::new(NodeId::alloc());
+    ctx.add_library(
+        get_name_table().intern("work", false),
+        lib,
+        units.iter().collect(),
+    );
+
+    // Elaborate the entities in the order they are declared in.
+    let failed = || format!("compilation of {} failed", path.display());
+    let defs = ctx.defs(ScopeRef::Lib(lib)).map_err(|_| failed())?;
+    let mut entities: Vec<_> = defs
+        .iter()
+        .filter(|&(name, _)| match *name {
+            ResolvableName::Ident(_) => true,
+            _ => false,
+        })
+        .flat_map(|(_, defs)| defs.iter())
+        .filter_map(|def| match def.value {
+            Def::Entity(id) => Some((def.span.begin, id)),
+            _ => None,
+        })
+        .collect();
+    entities.sort_by_key(|&(pos, _)| pos);
+    let archs = ctx.archs(lib).map_err(|_| failed())?;
+    let elaborate = |arch| match ctx.lldef(arch) {
+        Err(_) if keep_going => Ok(()),
+        result => result.map(|_| ()).map_err(|_| failed()),
+    };
+    for (_, entity) in entities {
+        if let Some(&arch) = archs.by_entity[&entity].ordered.last() {
+            match sweep {
+                Some(ref configs) => {
+                    for config in configs {
+                        let spec = ctx.override_generics(arch, config).map_err(|_| failed())?;
+                        elaborate(spec)?;
+                    }
+                }
+                None => elaborate(arch)?,
+            }
+        }
+    }
+    if sess.failed() && !keep_going {
+        return Err(failed());
+    }
+    if let Some(count) = warnings {
+        if sess.num_warnings() != count {
+            return Err(format!(
+                "{} produced {} warnings, expected {}",
+                path.display(),
+                sess.num_warnings(),
+                count
+            ));
+        }
+    }
+    let module = sb.llmod.borrow();
+    let directives = sb.directives.borrow();
+    Ok(moore_vhdl::codegen::write_annotated(&module, &directives))
+}
+
+/// Get the arguments of a `-- @NAME` directive if the header line is one.
+fn directive<'a>(header: &'a str, name: &str) -> Option<&'a str> {
+    let prefix = format!("-- @{}", name);
+    if header.starts_with(&prefix) {
+        Some(&header[prefix.len()..])
+    } else {
+        None
+    }
+}
+
+/// Check that the units in the output of a test file are defined once, and
+/// that every instance refers to one of them.
+fn check_units(path: &Path, output: &str) -> Result<(), String> {
+    let unit_name = |line: &str| line.split(' ').nth(1).unwrap_or("").to_string();
+    let mut defined = std::collections::HashSet::new();
+    let mut errors = vec![];
+    for line in output.lines() {
+        if line.starts_with("proc @") || line.starts_with("entity @") || line.starts_with("func @")
+        {
+            let name = unit_name(line);
+            if !defined.insert(name.clone()) {
+                errors.push(format!("{} is defined more than once", name));
+            }
+        }
+    }
+    for line in output.lines().map(str::trim) {
+        if line.starts_with("inst @") && !defined.contains(&unit_name(line)) {
+            errors.push(format!(
+                "{} is instantiated but not defined",
+                unit_name(line)
+            ));
+        }
+    }
+    if errors.is_empty() {
+        Ok(())
+    } else {
+        Err(format!(
+            "output of {} is inconsistent:\n{}",
+            path.display(),
+            errors.join("\n")
+        ))
+    }
+}
+
+/// Find the test files.
+fn fixtures() -> Vec<PathBuf> {
+    let dir = Path::new(env!("CARGO_MANIFEST_DIR")).join("tests/codegen");
+    let mut paths: Vec<_> = std::fs::read_dir(dir)
+        .unwrap()
+        .map(|entry| entry.unwrap().path())
+        .filter(|path| path.extension().map(|e| e == "vhd").unwrap_or(false))
+        .collect();
+    paths.sort();
+    paths
+}
+
+#[test]
+fn codegen_snapshots() {
+    let update = std::env::var_os("UPDATE_SNAPSHOTS").is_some();
+    let mut mismatches = vec![];
+    for path in fixtures() {
+        let actual = match compile(&path) {
+            Ok(x) => x,
+            Err(msg) => {
+                mismatches.push(msg);
+                continue;
+            }
+        };
+        if let Err(msg) = check_units(&path, &actual) {
+            mismatches.push(msg);
+        }
+        let golden = path.with_extension("llhd");
+        if update {
+            std::fs::write(&golden, &actual).unwrap();
+            continue;
+        }
+        let expected = std::fs::read_to_string(&golden).unwrap_or_default();
+        if actual != expected {
+            mismatches.push(format!(
+                "output of {} does not match {}\n--- expected\n{}--- actual\n{}",
+                path.display(),
+                golden.display(),
+                expected,
+                actual
+            ));
+        }
+    }
+    if !mismatches.is_empty() {
+        panic!(
+            "{}\n\nRun with `UPDATE_SNAPSHOTS=1` to accept the new output.",
+            mismatches.join("\n\n")
+        );
+    }
+}
@@ -0,0 +1,40 @@
+package agg_pkg is
+	type nibble is array (3 downto 0) of BIT;
+	type bytes is array (0 to 3) of INTEGER range 0 to 255;
+	type state is (idle, busy, done);
+	type costs is array (state) of INTEGER range 0 to 7;
+	type grid is array (0 to 1, 0 to 2) of BIT;
+	constant ZERO : nibble := (others => '0');
+	constant LOW : nibble := ('0', '0', '1', '1');
+	constant TOP : nibble := (3 => '1', others => '0');
+	constant RAMP : bytes := (0 => 1, 1 to 2 => 5, 3 => 9);
+	constant COST : costs := (idle => 0, busy => 4, done => 2);
+	constant DIAG : grid := (('1', '0', '0'), ('0', '1', '0'));
+end package;
+
+library work;
+use work.agg_pkg;
+
+entity agg_top is
+	port (
+		a_in : in BIT;
+		b_in : in BIT;
+		q_out : out agg_pkg.nibble;
+		r_out : out agg_pkg.bytes
+	);
+end;
+
+architecture rtl of agg_top is
+	signal zero_sig : agg_pkg.nibble := agg_pkg.ZERO;
+	signal low_sig : agg_pkg.nibble := agg_pkg.LOW;
+	signal top_sig : agg_pkg.nibble := agg_pkg.TOP;
+	signal cost_sig : agg_pkg.costs := agg_pkg.COST;
+	signal diag_sig : agg_pkg.grid := agg_pkg.DIAG;
+begin
+	process (a_in, b_in)
+	begin
+		q_out <= (3 => a_in, 0 => b_in, others => '1');
+	end process;
+
+	r_out <= agg_pkg.RAMP;
+end;
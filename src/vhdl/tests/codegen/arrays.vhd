@@ -0,0 +1,34 @@
+package array_pkg is
+	type word is array (7 downto 0) of BIT;
+	type nibble is array (0 to 3) of BIT;
+	type matrix is array (0 to 3, 1 downto 0) of BIT;
+	type words is array (1 to 2) of word;
+	type pair is record
+		lo : nibble;
+		hi : nibble;
+	end record;
+	type pairs is array (0 to 1) of pair;
+	type flags is array (BOOLEAN) of BIT;
+	type ranged is array (INTEGER range 2 to 5) of BIT;
+	type single is array (0 to 0) of BIT;
+end package;
+
+library work;
+use work.array_pkg;
+
+entity array_ports is
+	port (
+		w_in : in array_pkg.word;
+		n_in : in array_pkg.nibble;
+		m_in : in array_pkg.matrix;
+		ws_in : in array_pkg.words;
+		p_in : in array_pkg.pairs;
+		f_out : out array_pkg.flags;
+		r_out : out array_pkg.ranged;
+		o_out : out array_pkg.single
+	);
+end;
+
+architecture rtl of array_ports is
+begin
+end;
@@ -0,0 +1,23 @@
+entity latch is
+	port (
+		enable : in BIT;
+		data : in BIT;
+		held : out BIT;
+		passed : out BIT
+	);
+end;
+
+architecture rtl of latch is
+begin
+	store: block (enable = '1') is
+		signal value : BIT;
+	begin
+		value <= guarded data;
+		held <= value;
+	end block;
+
+	bypass: block is
+	begin
+		passed <= data;
+	end block;
+end;
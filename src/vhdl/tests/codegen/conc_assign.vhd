@@ -0,0 +1,17 @@
+entity gates is
+	port (
+		din0, din1, pick : in BIT;
+		inv_out, mux_out, sel_out : out BIT
+	);
+end;
+
+architecture rtl of gates is
+	signal nd : BIT;
+begin
+	nd <= din0 nand din1;
+	inv_out <= not nd;
+	mux : mux_out <= din0 when pick = '1' else din1;
+	with pick select sel_out <=
+		din0 when '0',
+		nd   when others;
+end;
@@ -0,0 +1,41 @@
+entity sel_unit is
+	port (
+		sel_in : in BIT;
+		sel_out : out BIT
+	);
+end;
+
+architecture behavioral of sel_unit is
+begin
+	polarity : if FALSE generate
+		sel_out <= sel_in;
+	elsif 3 > 1 and TRUE generate
+		sel_out <= not sel_in;
+	else generate
+		sel_out <= '0';
+	end generate;
+
+	unused : if not TRUE generate
+		signal never : BIT;
+	begin
+		never <= sel_in;
+	end generate;
+
+	depth : case 2 generate
+		when 0 =>
+			signal skipped : BIT;
+		begin
+			skipped <= sel_in;
+		end;
+		when 1 to 3 =>
+			signal staged : BIT;
+		begin
+			stage : staged <= sel_in;
+		end;
+		when others =>
+			signal other : BIT;
+		begin
+			other <= sel_in;
+		end;
+	end generate;
+end;
@@ -0,0 +1,17 @@
+entity counter_limit is
+	port (
+		count : in INTEGER;
+		en : in BIT;
+		at_limit, drive : out BIT
+	);
+end;
+
+architecture rtl of counter_limit is
+	constant LIMIT : INTEGER := 9;
+	constant LAST : INTEGER := LIMIT;
+	constant ACTIVE : BIT := '1';
+	constant ENABLED : BOOLEAN := TRUE;
+begin
+	at_limit <= '1' when count = LAST else '0';
+	drive <= ACTIVE when en = ACTIVE and ENABLED else '0';
+end;
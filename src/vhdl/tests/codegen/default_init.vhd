@@ -0,0 +1,26 @@
+package init_pkg is
+	type std_ulogic is ('U', 'X', '0', '1', 'Z', 'W', 'L', 'H', '-');
+	type state is (idle, busy, done);
+	type logic_vector is array (3 downto 0) of std_ulogic;
+	type grid is array (0 to 1, 0 to 2) of state;
+	type bus_t is record
+		data : logic_vector;
+		valid : BOOLEAN;
+		level : INTEGER range -4 to 3;
+	end record;
+end package;
+
+library work;
+use work.init_pkg;
+
+entity default_init is
+end;
+
+architecture sim of default_init is
+	signal init_bit : init_pkg.std_ulogic;
+	signal init_state : init_pkg.state;
+	signal init_vec : init_pkg.logic_vector;
+	signal init_grid : init_pkg.grid;
+	signal init_bus : init_pkg.bus_t;
+begin
+end;
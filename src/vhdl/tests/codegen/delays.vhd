@@ -0,0 +1,26 @@
+-- @warnings 1
+entity delay_line is
+	port (
+		dly_a : in BIT;
+		dly_sel : in BIT;
+		dly_q : out BIT;
+		dly_t : out BIT;
+		dly_r : out BIT
+	);
+end;
+
+architecture sim of delay_line is
+	signal dly_clk : BIT;
+	signal dly_pick : BIT;
+begin
+	dly_q <= dly_a after 2 ns;
+	dly_t <= transport dly_a after 1 ns;
+	dly_r <= reject 500 ps inertial not dly_a after 1 ns;
+	dly_pick <= dly_a after 3 ns when dly_sel = '1' else '0';
+
+	clock : process
+	begin
+		dly_clk <= '1', '0' after 5 ns;
+		wait for 10 ns;
+	end process;
+end;
@@ -0,0 +1,35 @@
+package enc_pkg is
+	type state is (idle, busy, done);
+	type offset is range -8 to 7;
+	type window is range 2 to 5;
+	constant start : state := busy;
+end package;
+
+library work;
+use work.enc_pkg;
+
+entity encoding is
+	port (
+		enc_a : in BIT;
+		enc_q : out BIT
+	);
+end;
+
+architecture sim of encoding is
+	constant enc_limit : INTEGER := 5;
+	signal enc_count : INTEGER := enc_limit;
+	signal enc_state : enc_pkg.state := enc_pkg.start;
+	signal enc_idle : enc_pkg.state;
+	signal enc_off : enc_pkg.offset;
+	signal enc_win : enc_pkg.window := 5;
+	signal enc_sev : SEVERITY_LEVEL := ERROR;
+begin
+	enc_q <= enc_a;
+
+	step : process
+	begin
+		enc_state <= enc_pkg.done;
+		enc_win <= 2;
+		wait for 1 ns;
+	end process;
+end;
@@ -0,0 +1,31 @@
+-- Netlists written by synthesis tools name cells and nets with extended
+-- identifiers, which are case sensitive and distinct from basic identifiers.
+entity \ext_cell$1\ is
+	port (
+		\ext_d[0]\ : in BIT;
+		\EXT_D[0]\ : in BIT;
+		ext_q : out BIT;
+		\ext_q\ : out BIT
+	);
+end;
+
+architecture \rtl/net\ of \ext_cell$1\ is
+	signal \ext n1\ : BIT;
+begin
+	\ext n1\ <= \ext_d[0]\ and \EXT_D[0]\;
+	ext_q <= \ext n1\;
+	\ext_q\ <= not \ext n1\;
+end;
+
+entity ext_top is
+	port (
+		ext_a, ext_b : in BIT;
+		ext_y, ext_z : out BIT
+	);
+end;
+
+architecture netlist of ext_top is
+begin
+	\u_cell/0\ : entity work.\ext_cell$1\
+		port map (\ext_d[0]\ => ext_a, \EXT_D[0]\ => ext_b, EXT_Q => ext_y, \ext_q\ => ext_z);
+end;
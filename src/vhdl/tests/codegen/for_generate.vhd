@@ -0,0 +1,38 @@
+entity inv_cell is
+	port (
+		cell_in : in BIT;
+		cell_out : out BIT
+	);
+end;
+
+architecture rtl of inv_cell is
+begin
+	cell_out <= not cell_in;
+end;
+
+entity inv_bank is
+	port (
+		bank_in : in BIT;
+		bank_out : out BIT
+	);
+end;
+
+architecture structural of inv_bank is
+begin
+	cells : for k in 0 to 2 generate
+		signal inverted : BIT;
+	begin
+		inv : entity work.inv_cell(rtl) port map (cell_in => bank_in, cell_out => inverted);
+	end generate;
+
+	rows : for r in 1 downto 0 generate
+	begin
+		cols : for c in 0 to 1 generate
+			signal tapped : BIT;
+		begin
+			tap : tapped <= not bank_in;
+		end generate;
+	end generate;
+
+	bank_out <= bank_in;
+end;
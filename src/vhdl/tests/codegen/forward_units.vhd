@@ -0,0 +1,22 @@
+architecture rtl of fwd_top is
+begin
+	fwd_q <= fwd_d;
+end;
+
+package body fwd_pkg is
+	constant FWD_ZERO : FWD_WORD := 0;
+end;
+
+library work;
+use work.fwd_pkg;
+
+entity fwd_top is
+	port (
+		fwd_d : in fwd_pkg.FWD_WORD;
+		fwd_q : out fwd_pkg.FWD_WORD
+	);
+end;
+
+package fwd_pkg is
+	type FWD_WORD is range 0 to 255;
+end package;
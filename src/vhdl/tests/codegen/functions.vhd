@@ -0,0 +1,47 @@
+package func_pkg is
+	function invert (x : BIT) return BIT;
+end package;
+
+package body func_pkg is
+	function invert (x : BIT) return BIT is
+	begin
+		return not x;
+	end;
+end;
+
+library work;
+use work.func_pkg;
+
+entity func_top is
+	port (
+		a_in : in BIT;
+		b_in : in BIT;
+		y_out : out BIT;
+		z_out : out BIT
+	);
+end;
+
+architecture rtl of func_top is
+	function majority (a, b, c : BIT) return BIT is
+	begin
+		if a = b then
+			return a;
+		end if;
+		return c;
+	end;
+
+	function pick (sel : BOOLEAN; x : BIT; y : BIT) return BIT is
+		variable result : BIT := y;
+	begin
+		if sel then
+			result := x;
+		end if;
+		return result;
+	end;
+begin
+	process (a_in, b_in)
+	begin
+		y_out <= majority(a_in, b_in, '0');
+		z_out <= func_pkg.invert(pick(a_in = b_in, a_in, majority(b_in, a_in, '1')));
+	end process;
+end;
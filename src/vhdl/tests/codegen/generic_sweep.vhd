@@ -0,0 +1,25 @@
+-- @generics WIDTH=2,4 DEPTH=1,3
+package sweep_pkg is
+	type bits is array (NATURAL range <>) of BIT;
+end package;
+
+library work;
+use work.sweep_pkg;
+
+entity sweep is
+	generic (
+		WIDTH : INTEGER := 8;
+		DEPTH : INTEGER := WIDTH / 2
+	);
+	port (
+		d_in : in sweep_pkg.bits(WIDTH - 1 downto 0);
+		q_out : out sweep_pkg.bits(WIDTH - 1 downto 0);
+		depth_out : out INTEGER
+	);
+end;
+
+architecture rtl of sweep is
+begin
+	q_out <= d_in;
+	depth_out <= DEPTH;
+end;
@@ -0,0 +1,80 @@
+package gen_pkg is
+	type bits is array (NATURAL range <>) of BIT;
+end package;
+
+library work;
+use work.gen_pkg;
+
+entity shift_reg is
+	generic (
+		WIDTH : INTEGER := 4;
+		DEPTH : INTEGER := WIDTH / 2;
+		INVERT : BOOLEAN := FALSE
+	);
+	port (
+		d_in : in gen_pkg.bits(WIDTH - 1 downto 0);
+		q_out : out gen_pkg.bits(WIDTH - 1 downto 0);
+		msb_out : out BIT;
+		depth_out : out INTEGER
+	);
+end;
+
+architecture rtl of shift_reg is
+	constant TOP : INTEGER := WIDTH - 1;
+begin
+	stages : for i in 1 to DEPTH generate
+		signal stage : gen_pkg.bits(TOP downto 0);
+	begin
+		stage <= d_in;
+	end generate;
+
+	plain : if not INVERT generate
+		msb_out <= d_in(TOP);
+	end generate;
+
+	inverted : if INVERT generate
+		msb_out <= not d_in(TOP);
+	end generate;
+
+	q_out <= d_in;
+	depth_out <= DEPTH + WIDTH mod 3;
+end;
+
+library work;
+use work.gen_pkg;
+
+entity gen_top is
+	port (
+		a_in : in gen_pkg.bits(7 downto 0);
+		b_in : in gen_pkg.bits(3 downto 0);
+		a_out : out gen_pkg.bits(7 downto 0);
+		b_out : out gen_pkg.bits(3 downto 0);
+		c_out : out gen_pkg.bits(3 downto 0);
+		msb_out : out BIT
+	);
+end;
+
+architecture structural of gen_top is
+	component shift_reg is
+		generic (
+			WIDTH : INTEGER := 4;
+			DEPTH : INTEGER := WIDTH / 2;
+			INVERT : BOOLEAN := FALSE
+		);
+		port (
+			d_in : in gen_pkg.bits(WIDTH - 1 downto 0);
+			q_out : out gen_pkg.bits(WIDTH - 1 downto 0);
+			msb_out : out BIT;
+			depth_out : out INTEGER
+		);
+	end component;
+begin
+	wide : entity work.shift_reg(rtl)
+		generic map (WIDTH => 2 * 4, INVERT => TRUE)
+		port map (d_in => a_in, q_out => a_out, msb_out => msb_out);
+	narrow : entity work.shift_reg(rtl)
+		port map (d_in => b_in, q_out => b_out);
+	deep : shift_reg
+		generic map (4, 3)
+		port map (b_in, c_out, open, open);
+end;
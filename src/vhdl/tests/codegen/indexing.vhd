@@ -0,0 +1,65 @@
+package idx_pkg is
+	type nibble is array (3 downto 0) of BIT;
+	type byte is array (7 downto 0) of BIT;
+	type word is array (0 to 3) of INTEGER range 0 to 255;
+	type grid is array (0 to 1, 0 to 2) of BIT;
+	type pair is record
+		lo : nibble;
+		hi : nibble;
+	end record;
+	constant ROM : word := (10, 20, 30, 40);
+	constant DIAG : grid := (('1', '0', '0'), ('0', '1', '0'));
+end package;
+
+library work;
+use work.idx_pkg;
+
+entity idx_top is
+	port (
+		a_in : in idx_pkg.byte;
+		w_in : in idx_pkg.word;
+		g_in : in idx_pkg.grid;
+		sel_in : in INTEGER range 0 to 3;
+		bit_out : out BIT;
+		nib_out : out idx_pkg.nibble;
+		int_out : out INTEGER range 0 to 255;
+		sum_out : out INTEGER range 0 to 255
+	);
+end;
+
+architecture rtl of idx_top is
+	signal rom_sig : INTEGER range 0 to 255 := idx_pkg.ROM(2);
+	signal diag_sig : BIT := idx_pkg.DIAG(1, 1);
+	signal byte_sig : idx_pkg.byte;
+	signal pair_sig : idx_pkg.pair;
+
+	signal patch_sig : idx_pkg.word;
+
+	function patch (w : idx_pkg.word) return idx_pkg.word is
+		variable acc : idx_pkg.word := w;
+	begin
+		acc(0) := 0;
+		acc(1 to 2) := w(2 to 3);
+		return acc;
+	end;
+begin
+	process (a_in, w_in, g_in, sel_in)
+	begin
+		bit_out <= a_in(7) xor g_in(1, 2);
+		nib_out <= a_in(5 downto 2);
+		int_out <= w_in(sel_in);
+		byte_sig(sel_in) <= a_in(0);
+		byte_sig(7 downto 4) <= a_in(3 downto 0);
+	end process;
+
+	pair_sig.hi(0) <= a_in(sel_in);
+	patch_sig <= patch(w_in);
+
+	process (patch_sig, sel_in)
+		variable acc : idx_pkg.word;
+	begin
+		acc := patch_sig;
+		acc(sel_in) := 0;
+		sum_out <= acc(3);
+	end process;
+end;
@@ -0,0 +1,31 @@
+entity half_adder is
+	port (
+		op_a, op_b : in BIT;
+		sum, carry : out BIT
+	);
+end;
+
+architecture rtl of half_adder is
+begin
+	sum <= op_a xor op_b;
+	carry <= op_a and op_b;
+end;
+
+entity adder_top is
+	port (
+		in_x, in_y : in BIT;
+		out_s, out_c : out BIT
+	);
+end;
+
+architecture structural of adder_top is
+	component half_adder is
+		port (
+			op_a, op_b : in BIT;
+			sum, carry : out BIT
+		);
+	end component;
+begin
+	ha0 : entity work.half_adder(rtl) port map (op_a => in_x, op_b => in_y, sum => out_s, carry => open);
+	ha1 : half_adder port map (in_y, in_x, open, out_c);
+end;
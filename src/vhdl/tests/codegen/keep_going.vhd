@@ -0,0 +1,28 @@
+-- @keep-going
+entity broken is
+	port (a_in : in BIT; q_out : out BIT);
+end;
+
+architecture rtl of broken is
+begin
+	fine : process (a_in)
+	begin
+		q_out <= a_in;
+	end process;
+	bad : process (a_in)
+	begin
+		q_out <= missing;
+	end process;
+end;
+
+entity working is
+	port (a_in : in BIT; q_out : out BIT);
+end;
+
+architecture rtl of working is
+begin
+	copy : process (a_in)
+	begin
+		q_out <= a_in;
+	end process;
+end;
@@ -0,0 +1,17 @@
+entity based_literals is
+	port (
+		lit_hex_exp, lit_bin, lit_dec_exp, lit_oct, lit_max, lit_real : out INTEGER
+	);
+end;
+
+architecture rtl of based_literals is
+	constant BIT_VALUE : INTEGER := 2#1111_1111#;
+	constant REAL_VALUE : INTEGER := INTEGER(2#1.1111_1111_111#E11);
+begin
+	lit_hex_exp <= 16#FF#E2;
+	lit_bin <= BIT_VALUE;
+	lit_dec_exp <= 1_000E3;
+	lit_oct <= 8#7_7_7#;
+	lit_max <= 16#7FFF_FFFF#;
+	lit_real <= REAL_VALUE;
+end;
@@ -0,0 +1,22 @@
+library ieee;
+use ieee.math_real.all;
+use ieee.math_complex.all;
+
+package pkg is
+	type ADDR is range 0 to integer(ceil(log2(real(1000))));
+	type ROOT is range 0 to integer(sqrt(2000.0));
+	type DIGIT is range 0 to integer(floor(MATH_PI));
+end package;
+
+library work;
+use work.pkg;
+
+entity foo is
+	port (
+		sel : in pkg.ADDR;
+		num : in pkg.DIGIT;
+		sq  : out pkg.ROOT
+	);
+end;
+
+architecture bar of foo is begin end;
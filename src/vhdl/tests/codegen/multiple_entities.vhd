@@ -0,0 +1,24 @@
+entity foo is
+end;
+
+architecture first of foo is begin end;
+
+architecture second of foo is
+begin
+	p : process
+	begin
+	end process;
+end;
+
+entity bar is
+end;
+
+architecture arch of bar is
+begin
+	a : process
+	begin
+	end process;
+	b : process
+	begin
+	end process;
+end;
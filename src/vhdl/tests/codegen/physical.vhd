@@ -0,0 +1,37 @@
+package phy_pkg is
+	type distance is range 0 to 1000000
+		units
+			um;
+			mm = 1000 um;
+			cm = 10 mm;
+		end units;
+	constant short : distance := 12 mm;
+	constant long : distance := 3 cm;
+end package;
+
+use work.phy_pkg;
+
+entity physical is
+	port (
+		phy_a : in BIT;
+		phy_q : out BIT
+	);
+end;
+
+architecture sim of physical is
+	constant phy_period : TIME := 1 us - 250 ns;
+	constant phy_half : TIME := TIME'(phy_period - 375000 ps);
+	signal phy_delay : TIME := 2 ns;
+	signal phy_length : phy_pkg.distance := phy_pkg.long;
+	signal phy_origin : phy_pkg.distance;
+	signal phy_late : BOOLEAN := phy_half > 300 ns;
+begin
+	phy_q <= phy_a after phy_half + 1 fs;
+
+	clock : process
+	begin
+		phy_delay <= phy_period;
+		phy_length <= phy_pkg.short;
+		wait for phy_period;
+	end process;
+end;
@@ -0,0 +1,61 @@
+package pkg is
+	type BIT is range 0 to 1;
+	type NIBBLE is range 0 to 15;
+end package;
+
+library work;
+use work.pkg;
+
+entity foo is
+	port (
+		CK : in pkg.BIT;
+		D  : in pkg.NIBBLE;
+		Q  : out pkg.NIBBLE
+	);
+end;
+
+architecture bar of foo is begin end;
+
+library work;
+use work.pkg;
+
+entity port_modes is
+	port (
+		clk_in : in BIT;
+		d_in : in pkg.NIBBLE;
+		q_out : out pkg.NIBBLE;
+		busy_buf : buffer BIT;
+		bus_io : inout BIT
+	);
+end;
+
+architecture rtl of port_modes is
+begin
+	process (clk_in, d_in, busy_buf)
+	begin
+		if clk_in = '1' then
+			q_out <= d_in;
+			busy_buf <= not busy_buf;
+		end if;
+	end process;
+
+	bus_io <= busy_buf and clk_in;
+end;
+
+library work;
+use work.pkg;
+
+entity port_wrap is
+	port (
+		clk_in : in BIT;
+		d_in : in pkg.NIBBLE;
+		bus_io : inout BIT
+	);
+end;
+
+architecture rtl of port_wrap is
+	signal q_sig : pkg.NIBBLE;
+	signal busy : BIT;
+begin
+	inner : entity work.port_modes(rtl) port map (clk_in, d_in, q_sig, busy, bus_io);
+end;
@@ -0,0 +1,48 @@
+package proc_pkg is
+	procedure set_bit (signal q : out BIT; constant value : in BIT);
+end package;
+
+package body proc_pkg is
+	procedure set_bit (signal q : out BIT; constant value : in BIT) is
+	begin
+		q <= value;
+	end;
+end;
+
+library work;
+use work.proc_pkg;
+
+entity proc_top is
+	port (
+		a_in : in BIT;
+		b_in : in BIT;
+		y_out : out BIT;
+		z_out : out BIT
+	);
+end;
+
+architecture rtl of proc_top is
+	signal toggle : BIT;
+
+	procedure copy (signal src : in BIT; signal dst : out BIT) is
+	begin
+		if src = '0' then
+			proc_pkg.set_bit(dst, '0');
+			return;
+		end if;
+		dst <= '1';
+	end;
+
+	procedure flip (signal s : inout BIT) is
+	begin
+		s <= not s;
+	end;
+begin
+	process (a_in, b_in)
+	begin
+		copy(a_in, y_out);
+		flip(toggle);
+	end process;
+
+	driver: copy(b_in, z_out);
+end;
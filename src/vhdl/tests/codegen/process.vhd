@@ -0,0 +1,9 @@
+entity foo is
+end;
+
+architecture bar of foo is
+begin
+	empty : process
+	begin
+	end process;
+end;
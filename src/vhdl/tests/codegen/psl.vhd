@@ -0,0 +1,21 @@
+package pkg is
+	type BIT is range 0 to 1;
+end package;
+
+library work;
+use work.pkg;
+
+entity handshake is
+	port (
+		req : in pkg.BIT;
+		ack : in pkg.BIT
+	);
+end;
+
+architecture formal of handshake is
+begin
+	req_stable: assume {req = 1; req = 1};
+	restrict {req = 0 or ack = 0};
+	assert req = 1 or ack = 0 report "ack without req" severity error;
+	handshake_seen: cover {req = 1; ack = 1} report "handshake";
+end;
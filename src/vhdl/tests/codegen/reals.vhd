@@ -0,0 +1,33 @@
+package real_pkg is
+	type voltage is range 0.0 to 5.0;
+	constant vdd : voltage := 3.3;
+end package;
+
+library work;
+use work.real_pkg;
+
+entity reals is
+	port (
+		r_in : in REAL;
+		r_out : out REAL
+	);
+end;
+
+architecture sim of reals is
+	constant r_step : REAL := 0.5 * 3.0 - 1.0 / 4.0;
+	constant r_square : REAL := r_step ** 2;
+	signal r_level : real_pkg.voltage := real_pkg.vdd;
+	signal r_low : real_pkg.voltage;
+	signal r_acc : REAL := -r_square;
+	signal r_high : BOOLEAN := r_step >= 1.25;
+	signal r_count : INTEGER := INTEGER(r_square * 4.0);
+begin
+	r_out <= r_in;
+
+	stim : process
+	begin
+		r_acc <= 2.5;
+		r_level <= real_pkg.vdd;
+		wait for 10 ns;
+	end process;
+end;
@@ -0,0 +1,39 @@
+package rec_pkg is
+	type pair is record
+		lo : BIT;
+		hi : BIT;
+	end record;
+	type point is record
+		x : INTEGER range 0 to 15;
+		y : INTEGER range 0 to 15;
+		valid : BOOLEAN;
+	end record;
+	constant ORIGIN : point := (x => 0, y => 3, valid => true);
+	constant ONES : pair := ('1', others => '1');
+end package;
+
+library work;
+use work.rec_pkg;
+
+entity rec_top is
+	port (
+		p_in : in rec_pkg.pair;
+		q_out : out rec_pkg.pair;
+		r_out : out rec_pkg.pair;
+		lo_out : out BIT;
+		v_out : out BOOLEAN
+	);
+end;
+
+architecture rtl of rec_top is
+	signal pt : rec_pkg.point := rec_pkg.ORIGIN;
+begin
+	process (p_in)
+	begin
+		q_out <= (lo => p_in.hi, hi => p_in.lo);
+		lo_out <= p_in.lo;
+	end process;
+
+	r_out <= rec_pkg.ONES;
+	v_out <= pt.valid;
+end;
@@ -0,0 +1,25 @@
+entity sens_latch is
+	port (
+		sens_en : in BIT;
+		sens_d : in BIT;
+		sens_q : out BIT
+	);
+end;
+
+architecture rtl of sens_latch is
+	signal sens_tick : BIT;
+begin
+	-- Waits on `sens_tick` even though the statements never read it, and only
+	-- once on `sens_en`.
+	latch : process (sens_en, sens_d, sens_tick, sens_en)
+	begin
+		if sens_en = '1' then
+			sens_q <= sens_d;
+		end if;
+	end process;
+
+	ticker : process (all)
+	begin
+		sens_tick <= not sens_tick;
+	end process;
+end;
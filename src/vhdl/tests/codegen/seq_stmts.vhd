@@ -0,0 +1,48 @@
+entity toggle is
+	port (
+		clk_in : in BIT;
+		rst_in : in BIT;
+		q_out : out BIT;
+		cnt_out : out BIT
+	);
+end;
+
+architecture rtl of toggle is
+	signal state : BIT;
+	signal next_state : BIT;
+begin
+	flip : process (clk_in, rst_in)
+	begin
+		if rst_in = '1' then
+			next_state <= '0';
+		elsif clk_in = '1' then
+			next_state <= not state;
+		else
+			null;
+		end if;
+		state <= next_state;
+		q_out <= state;
+	end process;
+
+	waiter : process
+	begin
+		wait until clk_in = '1';
+		case state is
+			when '0' => cnt_out <= '1';
+			when others => cnt_out <= '0';
+		end case;
+		while rst_in = '1' loop
+			wait on rst_in, clk_in;
+			next when clk_in = '0';
+			exit;
+		end loop;
+	end process;
+
+	checker : process (all)
+	begin
+		assert state = '0' or rst_in = '0' report "stuck in reset" severity warning;
+		if rst_in = '1' then
+			report "in reset";
+		end if;
+	end process;
+end;
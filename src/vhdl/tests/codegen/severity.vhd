@@ -0,0 +1,19 @@
+-- @severity error=warning
+package pkg is
+	type BIT is range 0 to 1;
+end package;
+
+library work;
+use work.pkg;
+
+entity checked is
+	port (
+		en : in pkg.BIT
+	);
+end;
+
+architecture rtl of checked is
+begin
+	assert false report "not implemented" severity error;
+	assert en = 1 severity failure;
+end;
@@ -0,0 +1,13 @@
+entity event_count is
+	port (
+		tick : in BIT;
+		seen : out BIT
+	);
+end;
+
+architecture sim of event_count is
+	shared variable count : INTEGER := 0;
+	shared variable flag : BIT;
+begin
+	seen <= tick;
+end;
@@ -0,0 +1,14 @@
+-- @scheduling stratified
+entity chain is
+	port (
+		din : in BIT;
+		dout : out BIT
+	);
+end;
+
+architecture rtl of chain is
+	signal mid : BIT;
+begin
+	mid <= not din;
+	dout <= not mid;
+end;
@@ -0,0 +1,36 @@
+package sub_pkg is
+	type wire_t is ('0', '1', 'Z');
+	type wire_vector is array (NATURAL range <>) of wire_t;
+	function resolve (drivers : wire_vector) return wire_t;
+	subtype bus_t is resolve wire_t;
+	subtype small_t is INTEGER range 0 to 3;
+	subtype same_t is small_t;
+	constant PAST_SMALL : INTEGER := small_t'base'succ(3);
+end package;
+
+package body sub_pkg is
+	function resolve (drivers : wire_vector) return wire_t is
+	begin
+		return 'Z';
+	end;
+end;
+
+library work;
+use work.sub_pkg;
+
+entity sub_top is
+	port (
+		n_in : in sub_pkg.small_t;
+		b_in : in sub_pkg.bus_t;
+		n_out : out sub_pkg.small_t;
+		b_out : out sub_pkg.bus_t;
+		k_out : out INTEGER
+	);
+end;
+
+architecture rtl of sub_top is
+begin
+	n_out <= sub_pkg.same_t'pred(n_in);
+	b_out <= b_in;
+	k_out <= sub_pkg.PAST_SMALL + sub_pkg.same_t'base'pos(2);
+end;
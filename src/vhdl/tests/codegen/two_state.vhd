@@ -0,0 +1,18 @@
+-- @two-state
+package logic is
+	type std_ulogic is ('U', 'X', '0', '1', 'Z', 'W', 'L', 'H', '-');
+end package;
+
+library work;
+use work.logic;
+
+entity buf is
+	port (
+		D : in logic.std_ulogic;
+		Q : out logic.std_ulogic
+	);
+end;
+
+architecture rtl of buf is
+	signal s : logic.std_ulogic;
+begin end;
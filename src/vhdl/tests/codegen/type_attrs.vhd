@@ -0,0 +1,44 @@
+package attr_pkg is
+	type state_t is (IDLE, LOAD, RUN, DONE);
+	type level_t is range -4 to 3;
+	type countdown_t is range 7 downto 0;
+	constant RUN_POS : INTEGER := state_t'pos(RUN);
+	constant AFTER_IDLE : state_t := state_t'succ(IDLE);
+	constant BEFORE_DONE : state_t := state_t'pred(DONE);
+	constant LAST : state_t := state_t'val(3);
+	constant LEFT_OF_3 : countdown_t := countdown_t'leftof(3);
+	constant RIGHT_OF_3 : countdown_t := countdown_t'rightof(3);
+end package;
+
+library work;
+use work.attr_pkg;
+
+entity attr_top is
+	port (
+		state_in : in attr_pkg.state_t;
+		n_in : in INTEGER range 0 to 7;
+		level_in : in attr_pkg.level_t;
+		count_in : in attr_pkg.countdown_t;
+		next_out : out attr_pkg.state_t;
+		prev_out : out attr_pkg.state_t;
+		pos_out : out INTEGER range 0 to 3;
+		val_out : out attr_pkg.state_t;
+		level_out : out INTEGER range -8 to 7;
+		count_out : out attr_pkg.countdown_t;
+		const_out : out INTEGER
+	);
+end;
+
+architecture rtl of attr_top is
+	signal first_sig : attr_pkg.state_t := attr_pkg.AFTER_IDLE;
+	signal last_sig : attr_pkg.state_t := attr_pkg.LAST;
+begin
+	next_out <= attr_pkg.state_t'succ(state_in);
+	prev_out <= attr_pkg.state_t'pred(state_in);
+	pos_out <= attr_pkg.state_t'pos(state_in);
+	val_out <= attr_pkg.state_t'val(n_in);
+	level_out <= attr_pkg.level_t'pos(level_in);
+	count_out <= attr_pkg.countdown_t'leftof(count_in);
+	const_out <= attr_pkg.RUN_POS + attr_pkg.countdown_t'pos(attr_pkg.LEFT_OF_3)
+		- attr_pkg.countdown_t'pos(attr_pkg.RIGHT_OF_3);
+end;
@@ -0,0 +1,39 @@
+entity var_top is
+	generic (INIT : INTEGER := 7);
+	port (
+		clk_in : in BIT;
+		a_in : in INTEGER;
+		y_out : out INTEGER;
+		flag_out : out BOOLEAN
+	);
+end;
+
+architecture rtl of var_top is
+	constant START : INTEGER := 5;
+begin
+	toggle : process
+		variable count : INTEGER := START;
+		variable seen, flag : BOOLEAN;
+		variable prev : INTEGER := INIT;
+		constant STEP : INTEGER := 3;
+	begin
+		wait until clk_in = '1';
+		flag := not flag;
+		if flag then
+			count := a_in;
+		else
+			count := prev;
+		end if;
+		prev := STEP;
+		y_out <= count;
+		flag_out <= flag;
+	end process;
+
+	stages : for i in 0 to 1 generate
+		process (clk_in)
+			variable v : BIT := '1';
+		begin
+			v := clk_in;
+		end process;
+	end generate;
+end;
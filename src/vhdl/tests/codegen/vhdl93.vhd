@@ -0,0 +1,25 @@
+-- @vhdl-std 93
+entity vhdl93_ports is
+	generic (
+		WIDTH : INTEGER := 4;
+		DEPTH : INTEGER := WIDTH * 2
+	);
+	port (
+		a_in : in BIT;
+		b_buf : buffer BIT;
+		c_io : inout BIT;
+		y_out : out BIT;
+		n_in : in INTEGER := DEPTH
+	);
+end;
+
+architecture rtl of vhdl93_ports is
+begin
+	process (a_in, b_buf, c_io)
+	begin
+		b_buf <= a_in;
+		y_out <= b_buf xor c_io;
+	end process;
+
+	c_io <= not b_buf;
+end;
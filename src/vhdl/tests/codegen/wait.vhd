@@ -0,0 +1,36 @@
+entity wait_forms is
+	port (
+		wait_clk : in BIT;
+		wait_req : in BIT;
+		wait_ack : out BIT;
+		wait_pulse : out BIT
+	);
+end;
+
+architecture sim of wait_forms is
+	constant WAIT_PERIOD : TIME := 5 ns;
+	signal wait_seen : BIT;
+begin
+	handshake : process
+	begin
+		wait until wait_req = '1';
+		wait_ack <= '1';
+		wait on wait_req;
+		wait_ack <= '0';
+	end process;
+
+	pulse : process
+	begin
+		wait_pulse <= '1';
+		wait for 10 ns;
+		wait_pulse <= '0';
+		wait for WAIT_PERIOD;
+	end process;
+
+	watchdog : process
+	begin
+		wait on wait_clk for 1 us;
+		wait_seen <= wait_clk;
+		wait;
+	end process;
+end;
@@ -0,0 +1,124 @@
+// Copyright (c) 2016-2020 Fabian Schuiki
+
+//! Tests of finding the uses of a declaration for renaming.
+//!
+//! Each test marks names in the source with a `/*N*/` comment in front of
+//! them, and checks that the uses found for the declaration marked `/*0*/` are
+//! exactly the marked names.
+
+use moore_common::name::get_name_table;
+use moore_common::score::{CancelToken, GenericContext, NodeRef};
+use moore_common::source::{get_source_manager, Span};
+use moore_common::{NodeId, Session};
+use moore_vhdl::lazy::LazyPhaseTable;
+use moore_vhdl::score::{Arenas, LibRef, ScoreBoard, ScoreContext};
+
+struct Global;
+
+impl GenericContext for Global {}
+
+/// Find the uses of the declaration marked `/*0*/` and check that they are
+/// the marked names.
+fn check_uses(text: &str) {
+    let sess = Session::new();
+    let source = get_source_manager().add_anonymous(text);
+    let units = moore_vhdl::syntax::parse(source).expect("parser failed");
+    let arenas = Arenas::new();
+    let sb = ScoreBoard::new(&arenas);
+    let lazy = LazyPhaseTable::new(&sb);
+    let cancel = CancelToken::new();
+    let ctx = ScoreContext {
+        sess: &sess,
+        global: &Global,
+        sb: &sb,
+        lazy: &lazy,
+        cancel: &cancel,
+    };
+    let lib = LibRef::new(NodeId::alloc());
+    ctx.add_library(
+        get_name_table().intern("work", false),
+        lib,
+        units.iter().collect(),
+    );
+
+    // Locate the marked names.
+    let marked: Vec<Span> = text
+        .match_indices("*/")
+        .map(|(i, _)| {
+            let begin = i + 2;
+            let len = text[begin..]
+                .find(|c: char| !c.is_alphanumeric() && c != '_')
+                .unwrap();
+            Span::new(source, begin, begin + len)
+        })
+        .collect();
+    let decl = marked[text
+        .find("/*0*/")
+        .map(|i| text[..i].matches("*/").count())
+        .unwrap()];
+
+    let uses = moore_vhdl::rename::find_decl_uses(&ctx, lib, decl);
+    assert!(!sess.failed(), "compilation failed");
+    let mut expected = marked.clone();
+    expected.retain(|&span| span != decl);
+    expected.insert(0, decl);
+    let extract = |spans: &[Span]| {
+        spans
+            .iter()
+            .map(|s| (s.begin, s.extract()))
+            .collect::<Vec<_>>()
+    };
+    assert_eq!(extract(&uses), extract(&expected));
+}
+
+// The builtin libraries are shared by all scoreboards but refer to names
+// interned on the thread that first compiled something, so all cases run on
+// the same thread.
+#[test]
+fn decl_uses() {
+    // Uses are matched case-insensitively.
+    check_uses(
+        "entity foo is end;
+        architecture bar of foo is
+            signal /*0*/a, b : bit;
+        begin
+            b <= /*1*/a;
+            process (/*2*/a) begin
+                b <= not /*3*/A;
+            end process;
+        end;",
+    );
+
+    // The signal declared in the block shadows the outer one, such that the
+    // uses inside the block are not uses of the outer signal.
+    check_uses(
+        "entity foo is end;
+        architecture bar of foo is
+            signal /*0*/a, b, c : bit;
+        begin
+            b <= /*1*/a;
+            blk: block
+                signal a : bit;
+            begin
+                c <= a;
+                a <= '1';
+            end block;
+        end;",
+    );
+
+    // Only the uses inside the block refer to the inner signal.
+    check_uses(
+        "entity foo is end;
+        architecture bar of foo is
+            signal a, b, c : bit;
+        begin
+            b <= a;
+            blk: block
+                signal /*0*/a : bit;
+            begin
+                c <= /*1*/a;
+                /*2*/a <= '1';
+            end block;
+        end;",
+    );
+}
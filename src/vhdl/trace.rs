@@ -0,0 +1,252 @@
+// Copyright (c) 2016-2020 Fabian Schuiki
+
+//! Tracing of the lazy compiler passes.
+//!
+//! Nodes are created via a `MakeContext`, which schedules callbacks for the
+//! individual passes. These callbacks only run once their result is needed.
+//! The pass trace records when nodes are made, when callbacks are scheduled,
+//! and when they run. Running callbacks are nested, such that the trace shows
+//! which node caused another node to be evaluated. This helps in spotting
+//! passes that are accidentally evaluated eagerly.
+//!
+//! The trace is printed to stderr, optionally restricted by a filter, and may
+//! additionally be written to a file in the Chrome trace event format, which
+//! can be viewed in `chrome://tracing` or similar tools.
+//!
+//! A filter is a comma-separated list of entries of the form `PASS[:KIND]`.
+//! `PASS` is one of `make`, `hir`, `typeck`, `typeval`, `codegen`, `const`, or
+//! `*`. `KIND` is the kind of node, e.g. `SignalDecl` or `SignalDeclRef`, and
+//! is matched without regard to case.
+
+use std::cell::{Cell, RefCell};
+use std::fmt::Debug;
+use std::fs::File;
+use std::io::Write;
+use std::path::Path;
+use std::time::Instant;
+
+/// A lazy compiler pass.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Pass {
+    /// The creation of a node.
+    Make,
+    /// The lowering of a node to HIR.
+    Hir,
+    /// The type check of a node.
+    Typeck,
+    /// The evaluation of the type of a node.
+    Typeval,
+    /// The generation of code for a node.
+    Codegen,
+    /// The evaluation of the constant value of a node.
+    ConstEval,
+}
+
+impl Pass {
+    /// The name of the pass as used in filters.
+    pub fn name(self) -> &'static str {
+        match self {
+            Pass::Make => "make",
+            Pass::Hir => "hir",
+            Pass::Typeck => "typeck",
+            Pass::Typeval => "typeval",
+            Pass::Codegen => "codegen",
+            Pass::ConstEval => "const",
+        }
+    }
+}
+
+/// A single filter entry.
+#[derive(Debug, Clone)]
+struct Filter {
+    pass: Option<String>,
+    kind: Option<String>,
+}
+
+/// A trace of the lazy compiler passes.
+///
+/// The default trace is disabled and records nothing.
+#[derive(Default)]
+pub struct PassTrace {
+    /// Whether to print the trace to stderr.
+    print: bool,
+    /// The filters restricting which events are traced. Empty if all events
+    /// are traced.
+    filters: Vec<Filter>,
+    /// The file receiving the Chrome trace events.
+    chrome: Option<RefCell<File>>,
+    /// The point in time the trace started.
+    start: Option<Instant>,
+    /// The number of passes currently running.
+    depth: Cell<usize>,
+}
+
+impl PassTrace {
+    /// Create a new pass trace.
+    ///
+    /// If `filter` is `Some`, matching events are printed to stderr. If
+    /// `chrome` is `Some`, matching events are written to that file in the
+    /// Chrome trace event format.
+    pub fn new(filter: Option<&str>, chrome: Option<&Path>) -> std::io::Result<PassTrace> {
+        let chrome = match chrome {
+            Some(path) => {
+                let mut file = File::create(path)?;
+                writeln!(file, "[")?;
+                Some(RefCell::new(file))
+            }
+            None => None,
+        };
+        let filters = filter
+            .into_iter()
+            .flat_map(|f| f.split(','))
+            .map(str::trim)
+            .filter(|f| !f.is_empty() && *f != "*")
+            .map(|f| {
+                let mut iter = f.splitn(2, ':');
+                let pass = iter.next().filter(|p| *p != "*").map(str::to_lowercase);
+                let kind = iter.next().map(normalize_kind);
+                Filter { pass, kind }
+            })
+            .collect();
+        Ok(PassTrace {
+            print: filter.is_some(),
+            filters,
+            chrome,
+            start: Some(Instant::now()),
+            depth: Cell::new(0),
+        })
+    }
+
+    /// Check whether the trace records anything at all.
+    pub fn is_active(&self) -> bool {
+        self.print || self.chrome.is_some()
+    }
+
+    /// Check whether events of a pass and node kind are traced.
+    fn matches(&self, pass: Pass, kind: &str) -> bool {
+        if !self.is_active() {
+            return false;
+        }
+        if self.filters.is_empty() {
+            return true;
+        }
+        let kind = normalize_kind(kind);
+        self.filters.iter().any(|f| {
+            f.pass.as_ref().map(|p| p == pass.name()).unwrap_or(true)
+                && f.kind.as_ref().map(|k| *k == kind).unwrap_or(true)
+        })
+    }
+
+    /// Record an instantaneous event for a node.
+    ///
+    /// The `detail` function is only called if the event is traced.
+    pub fn event<I: Debug>(&self, pass: Pass, id: I, detail: impl FnOnce() -> String) {
+        let kind = kind_of::<I>();
+        if !self.matches(pass, kind) {
+            return;
+        }
+        let detail = detail();
+        if self.print {
+            eprintln!(
+                "\x1B[34;1mtrace:\x1B[m {:indent$}{} {:?} {}",
+                "",
+                pass.name(),
+                id,
+                detail,
+                indent = 2 * self.depth.get()
+            );
+        }
+        self.chrome_event(pass, &format!("{:?} {}", id, detail), "i");
+    }
+
+    /// Record the evaluation of a pass for a node.
+    ///
+    /// The evaluation lasts until the returned guard is dropped. Evaluations
+    /// started in the meantime are nested within this one.
+    pub fn begin<I: Debug>(&self, pass: Pass, id: I) -> TraceGuard<'_> {
+        let kind = kind_of::<I>();
+        if !self.matches(pass, kind) {
+            return TraceGuard {
+                trace: self,
+                pass,
+                name: None,
+            };
+        }
+        let name = format!("{:?}", id);
+        if self.print {
+            eprintln!(
+                "\x1B[34;1mtrace:\x1B[m {:indent$}{} {} {{",
+                "",
+                pass.name(),
+                name,
+                indent = 2 * self.depth.get()
+            );
+        }
+        self.chrome_event(pass, &name, "B");
+        self.depth.set(self.depth.get() + 1);
+        TraceGuard {
+            trace: self,
+            pass,
+            name: Some(name),
+        }
+    }
+
+    /// Write an event to the Chrome trace file, if any.
+    fn chrome_event(&self, pass: Pass, name: &str, phase: &str) {
+        let chrome = match self.chrome {
+            Some(ref c) => c,
+            None => return,
+        };
+        let ts = self.start.map(|s| s.elapsed().as_micros()).unwrap_or(0);
+        let _ = writeln!(
+            chrome.borrow_mut(),
+            "{{\"name\":{:?},\"cat\":\"{}\",\"ph\":\"{}\",\"ts\":{},\"pid\":1,\"tid\":1{}}},",
+            name,
+            pass.name(),
+            phase,
+            ts,
+            if phase == "i" { ",\"s\":\"t\"" } else { "" }
+        );
+    }
+}
+
+/// A guard that marks the end of a pass evaluation when dropped.
+pub struct TraceGuard<'a> {
+    trace: &'a PassTrace,
+    pass: Pass,
+    name: Option<String>,
+}
+
+impl Drop for TraceGuard<'_> {
+    fn drop(&mut self) {
+        let name = match self.name {
+            Some(ref n) => n,
+            None => return,
+        };
+        self.trace.depth.set(self.trace.depth.get() - 1);
+        if self.trace.print {
+            eprintln!(
+                "\x1B[34;1mtrace:\x1B[m {:indent$}}}",
+                "",
+                indent = 2 * self.trace.depth.get()
+            );
+        }
+        self.trace.chrome_event(self.pass, name, "E");
+    }
+}
+
+/// Determine the kind of node an ID refers to, based on the type of the ID.
+pub fn kind_of<I>() -> &'static str {
+    let name = std::any::type_name::<I>();
+    name.rsplit("::").next().unwrap_or(name)
+}
+
+/// Normalize a node kind for comparison.
+fn normalize_kind(kind: &str) -> String {
+    let kind = kind.to_lowercase();
+    if kind.ends_with("ref") {
+        kind[..kind.len() - 3].to_string()
+    } else {
+        kind
+    }
+}
@@ -32,6 +32,10 @@ pub enum Ty {
     /// evaluated at compile time, e.g. as part of a range expression. Cannot be
     /// mapped to LLHD.
     UnboundedInt,
+    /// A floating-point type.
+    Real(RealTy),
+    /// A universal real type. This is the type of floating-point literals.
+    UniversalReal,
     /// An enumeration type.
     Enum(EnumTy),
     /// A physical type.
@@ -57,6 +61,7 @@ impl Ty {
             Ty::Named(..) => "named type",
             Ty::Null => "null type",
             Ty::Int(_) | Ty::UnboundedInt | Ty::UniversalInt => "integer type",
+            Ty::Real(_) | Ty::UniversalReal => "floating-point type",
             Ty::Enum(_) => "enumeration type",
             Ty::Physical(_) => "physical type",
             Ty::Access(_) => "access type",
@@ -78,6 +83,7 @@ impl Ty {
     /// Check if this type is a real.
     pub fn is_real(&self) -> bool {
         match *self {
+            Ty::Real(..) | Ty::UniversalReal => true,
             _ => false,
         }
     }
@@ -89,6 +95,12 @@ impl From<IntTy> for Ty {
     }
 }
 
+impl From<RealTy> for Ty {
+    fn from(t: RealTy) -> Ty {
+        Ty::Real(t)
+    }
+}
+
 impl From<EnumTy> for Ty {
     fn from(t: EnumTy) -> Ty {
         Ty::Enum(t)
@@ -127,6 +139,8 @@ impl fmt::Display for Ty {
             Ty::Int(ref ty) => write!(f, "{}", ty),
             Ty::UniversalInt => write!(f, "{{universal integer}}"),
             Ty::UnboundedInt => write!(f, "{{integer}}"),
+            Ty::Real(ref ty) => write!(f, "{}", ty),
+            Ty::UniversalReal => write!(f, "{{universal real}}"),
             Ty::Enum(ref ty) => write!(f, "{}", ty),
             Ty::Physical(ref ty) => write!(f, "{}", ty),
             Ty::Access(ref ty) => write!(f, "access {}", ty),
@@ -193,17 +207,17 @@ impl IntTy {
     /// if the range has a negative or zero length.
     pub fn maybe_null(self) -> Ty {
         match self.dir {
-            Dir::To if self.left_bound >= self.right_bound => Ty::Null,
-            Dir::Downto if self.left_bound <= self.right_bound => Ty::Null,
+            Dir::To if self.left_bound > self.right_bound => Ty::Null,
+            Dir::Downto if self.left_bound < self.right_bound => Ty::Null,
             _ => self.into(),
         }
     }
 
-    /// The length of the range.
+    /// The length of the range, which is zero or negative for a null range.
     pub fn len(&self) -> BigInt {
         match self.dir {
-            Dir::To => &self.left_bound + BigInt::one() - &self.right_bound,
-            Dir::Downto => &self.right_bound + BigInt::one() - &self.left_bound,
+            Dir::To => &self.right_bound + BigInt::one() - &self.left_bound,
+            Dir::Downto => &self.left_bound + BigInt::one() - &self.right_bound,
         }
     }
 }
@@ -214,6 +228,37 @@ impl fmt::Display for IntTy {
     }
 }
 
+/// A floating-point type.
+#[derive(Debug, Clone, PartialEq)]
+pub struct RealTy {
+    pub dir: Dir,
+    pub left_bound: f64,
+    pub right_bound: f64,
+}
+
+impl RealTy {
+    /// Create a new floating-point type.
+    pub fn new(dir: Dir, left_bound: f64, right_bound: f64) -> RealTy {
+        RealTy {
+            dir: dir,
+            left_bound: left_bound,
+            right_bound: right_bound,
+        }
+    }
+}
+
+impl Eq for RealTy {}
+
+impl fmt::Display for RealTy {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(
+            f,
+            "{:?} {} {:?}",
+            self.left_bound, self.dir, self.right_bound
+        )
+    }
+}
+
 /// An enumeration type. Rather than keeping track of each enumeration value in
 /// here, we simply point at the type declaration.
 #[derive(Debug, Clone, PartialEq, Eq)]
@@ -324,6 +369,19 @@ impl ArrayTy {
             element: element,
         }
     }
+
+    /// Get the type of the elements along the first index. For
+    /// multidimensional arrays, this is an array over the remaining indices.
+    pub fn first_element(&self) -> Ty {
+        if self.indices.len() > 1 {
+            Ty::Array(ArrayTy::new(
+                self.indices[1..].to_vec(),
+                self.element.clone(),
+            ))
+        } else {
+            (*self.element).clone()
+        }
+    }
 }
 
 impl fmt::Display for ArrayTy {
@@ -430,6 +488,31 @@ impl SubprogTy {
             ret: ret.map(|t| Box::new(t)),
         }
     }
+
+    /// Check whether two subprogram types have the same parameter and result
+    /// type profile.
+    ///
+    /// Named types are compared by the type mark they refer to. See IEEE
+    /// 1076-2008 section 4.5.1.
+    pub fn same_profile(&self, other: &SubprogTy) -> bool {
+        fn same_ty(a: &Ty, b: &Ty) -> bool {
+            match (a, b) {
+                (&Ty::Named(_, a), &Ty::Named(_, b)) => a == b,
+                (a, b) => a == b,
+            }
+        }
+        self.args.len() == other.args.len()
+            && self
+                .args
+                .iter()
+                .zip(other.args.iter())
+                .all(|(a, b)| same_ty(&a.ty, &b.ty))
+            && match (&self.ret, &other.ret) {
+                (Some(a), Some(b)) => same_ty(a, b),
+                (None, None) => true,
+                _ => false,
+            }
+    }
 }
 
 impl fmt::Display for SubprogTy {
@@ -7,13 +7,14 @@ use std::collections::HashMap;
 use std::fmt::Debug;
 
 use crate::common::errors::*;
-use crate::common::score::{NodeMaker, NodeStorage, Result};
+use crate::common::score::{Error, NodeMaker, NodeStorage, Result};
 use crate::common::source::{Span, Spanned, INVALID_SPAN};
 use crate::common::{NodeId, Verbosity};
 use crate::hir;
 use crate::konst::*;
 use crate::lazy::LazyNode;
 use crate::score::*;
+use crate::trace::Pass;
 use crate::ty::*;
 
 /// A context to typecheck things in.
@@ -56,8 +57,9 @@ impl<'sbc, 'lazy, 'sb, 'ast, 'ctx> TypeckContext<'sbc, 'lazy, 'sb, 'ast, 'ctx> {
     /// of that operation. Otherwise runs the task scheduled in the lazy table.
     pub fn lazy_typeck<I>(&self, id: I)
     where
-        I: Into<NodeId>,
+        I: Into<NodeId> + Copy + Debug,
     {
+        let typed_id = id;
         let id = id.into();
 
         // If the typeck has already been performed, return its result.
@@ -71,18 +73,35 @@ impl<'sbc, 'lazy, 'sb, 'ast, 'ctx> TypeckContext<'sbc, 'lazy, 'sb, 'ast, 'ctx> {
         // Otherwise run the task scheduled in the lazy typeck table, then store
         // the result.
         let task = self.ctx.lazy.typeck.borrow_mut().set(id, LazyNode::Running);
+        self.ctx.lazy.record_forced(Pass::Typeck, id);
+        let trace = self.ctx.lazy.trace.begin(Pass::Typeck, typed_id);
         let result = match task {
-            Some(LazyNode::Pending(f)) => f(self),
+            Some(LazyNode::Pending(f)) => {
+                let result = f(self);
+                // A cancelled type check is scheduled again, such that it can
+                // be retried later on.
+                if let Err(Error::Cancelled) = result {
+                    self.ctx
+                        .lazy
+                        .typeck
+                        .borrow_mut()
+                        .set(id, LazyNode::Pending(f));
+                    self.failed.set(true);
+                    return;
+                }
+                result
+            }
             Some(LazyNode::Running) => {
                 self.ctx.bug(id, format!("recursion on typeck of {:?}", id));
-                Err(())
+                Err(Error::Reported)
             }
             None => {
                 self.ctx
                     .bug(id, format!("no typeck scheduled for {:?}", id));
-                Err(())
+                Err(Error::Reported)
             }
         };
+        drop(trace);
         if result.is_err() {
             self.failed.set(true);
         }
@@ -96,8 +115,9 @@ impl<'sbc, 'lazy, 'sb, 'ast, 'ctx> TypeckContext<'sbc, 'lazy, 'sb, 'ast, 'ctx> {
     /// table.
     pub fn lazy_typeval<I>(&self, id: I) -> Result<&'ctx Ty>
     where
-        I: Into<NodeId>,
+        I: Into<NodeId> + Copy + Debug,
     {
+        let typed_id = id;
         let id = id.into();
 
         // If the typeval has already been performed, return its result.
@@ -113,19 +133,36 @@ impl<'sbc, 'lazy, 'sb, 'ast, 'ctx> TypeckContext<'sbc, 'lazy, 'sb, 'ast, 'ctx> {
             .typeval
             .borrow_mut()
             .set(id, LazyNode::Running);
+        self.ctx.lazy.record_forced(Pass::Typeval, id);
+        let trace = self.ctx.lazy.trace.begin(Pass::Typeval, typed_id);
         let result = match task {
-            Some(LazyNode::Pending(f)) => f(self),
+            Some(LazyNode::Pending(f)) => {
+                let result = f(self);
+                // A cancelled type evaluation is scheduled again, such that it
+                // can be retried later on.
+                if let Err(Error::Cancelled) = result {
+                    self.ctx
+                        .lazy
+                        .typeval
+                        .borrow_mut()
+                        .set(id, LazyNode::Pending(f));
+                    self.failed.set(true);
+                    return Err(Error::Cancelled);
+                }
+                result
+            }
             Some(LazyNode::Running) => {
                 self.ctx
                     .bug(id, format!("recursion on typeval of {:?}", id));
-                Err(())
+                Err(Error::Reported)
             }
             None => {
                 self.ctx
                     .bug(id, format!("no typeval scheduled for {:?}", id));
-                Err(())
+                Err(Error::Reported)
             }
         };
+        drop(trace);
         if result.is_err() {
             self.failed.set(true);
         }
@@ -171,27 +208,190 @@ impl<'sbc, 'lazy, 'sb, 'ast, 'ctx> TypeckContext<'sbc, 'lazy, 'sb, 'ast, 'ctx> {
             (e, a) if e == a => return true,
             // (e,a) if a.is_subtype_of(e) => return true,
             (&Ty::Int(..), &Ty::UniversalInt) => return true,
+            (&Ty::Real(..), &Ty::UniversalReal) => return true,
+            // Arrays such as slices are implicitly converted to an array
+            // subtype with as many elements. See IEEE 1076-2008 section
+            // 14.7.3.4.
+            (&Ty::Array(ref e), &Ty::Array(ref a)) if self.same_array_shape(e, a) => return true,
             _ => (),
         }
+        let mut diag = DiagBuilder2::error(format!(
+            "expected type {}, but `{}` has type {}",
+            exp,
+            span.extract(),
+            act
+        ))
+        .span(span)
+        .add_note(format!("expected type: {}", exp_flat))
+        .add_note(format!("  actual type: {}", act_flat));
+        for ty in &[exp, act] {
+            if let Some(note) = self.subtype_note(ty) {
+                diag = diag.add_note(note);
+            }
+        }
+        self.emit(diag);
+        false
+    }
+
+    /// Describe the base type of a named subtype.
+    ///
+    /// Returns `None` if the type does not name a subtype.
+    fn subtype_note(&self, ty: &Ty) -> Option<String> {
+        let (name, id) = match *ty {
+            Ty::Named(ref name, TypeMarkRef::Subtype(id)) => (name, id),
+            _ => return None,
+        };
+        let base = self.ctx.base_type(id.into()).ok()?;
+        let base_name = match crate::builtin::builtin_type_name(base) {
+            Some(name) => name.to_string(),
+            None => self.ctx.lazy_hir(base).ok()?.name.value.to_string(),
+        };
+        Some(format!("`{}` is a subtype of `{}`", name, base_name))
+    }
+
+    /// Ensure that a subprogram can act as the resolution function of a type.
+    ///
+    /// The function must take a single argument and return a value of the
+    /// resolved type. See IEEE 1076-2008 section 4.6.
+    pub fn check_resolution_function(
+        &self,
+        ty: &'ctx Ty,
+        resol: Spanned<SubprogRef>,
+    ) -> Result<()> {
+        let valid = match *self.ctx.ty(resol.value)? {
+            Ty::Subprog(ref sty) if sty.args.len() == 1 => match sty.ret {
+                Some(ref ret) => {
+                    self.ctx.deref_named_type(ret)? == self.ctx.deref_named_type(ty)?
+                }
+                None => false,
+            },
+            _ => false,
+        };
+        if valid {
+            return Ok(());
+        }
         self.emit(
             DiagBuilder2::error(format!(
-                "expected type {}, but `{}` has type {}",
-                exp,
-                span.extract(),
-                act
+                "`{}` is not a resolution function for type {}",
+                resol.span.extract(),
+                ty
             ))
-            .span(span)
-            .add_note(format!("expected type: {}", exp_flat))
-            .add_note(format!("  actual type: {}", act_flat)),
+            .span(resol.span)
+            .add_note("A resolution function takes one argument and returns the resolved type. See IEEE 1076-2008 section 4.6."),
         );
-        false
+        Err(Error::Reported)
+    }
+
+    /// Check whether two array types only differ in the bounds of their
+    /// indices, but not in the number of elements along each index.
+    fn same_array_shape(&self, exp: &ArrayTy, act: &ArrayTy) -> bool {
+        let element = match (
+            self.ctx.deref_named_type(&exp.element),
+            self.ctx.deref_named_type(&act.element),
+        ) {
+            (Ok(e), Ok(a)) => e == a,
+            _ => false,
+        };
+        element
+            && exp.indices.len() == act.indices.len()
+            && exp
+                .indices
+                .iter()
+                .zip(act.indices.iter())
+                .all(|pair| match pair {
+                    (&ArrayIndex::Constrained(ref e), &ArrayIndex::Constrained(ref a)) => {
+                        match (self.ctx.deref_named_type(e), self.ctx.deref_named_type(a)) {
+                            (Ok(&Ty::Int(ref e)), Ok(&Ty::Int(ref a))) => e.len() == a.len(),
+                            (Ok(e), Ok(a)) => e == a,
+                            _ => false,
+                        }
+                    }
+                    (e, a) => e == a,
+                })
     }
 
     /// Ensure that one type can be cast into the other.
     pub fn must_cast(&self, into: &'ctx Ty, from: &'ctx Ty, span: Span) -> bool {
+        // Conversions between integer and floating-point types are always
+        // possible. See IEEE 1076-2008 section 9.3.6.
+        if let (Ok(into_flat), Ok(from_flat)) = (
+            self.ctx.deref_named_type(into),
+            self.ctx.deref_named_type(from),
+        ) {
+            if (into_flat.is_int() || into_flat.is_real())
+                && (from_flat.is_int() || from_flat.is_real())
+            {
+                return true;
+            }
+        }
         self.must_match(into, from, span)
     }
 
+    /// Type check the waveforms of a signal assignment to a target of the
+    /// given type.
+    pub fn typeck_sig_assign_kind(
+        &self,
+        kind: &'ctx hir::SigAssignKind,
+        ty: &'ctx Ty,
+    ) -> Result<()> {
+        let typeck_expr = |expr: ExprRef, exp: &'ctx Ty| -> Result<()> {
+            let act = self.lazy_typeval(expr)?;
+            self.must_match(exp, act, self.ctx.span(expr).unwrap());
+            Ok(())
+        };
+        let typeck_waveform = |wave: &hir::Waveform| -> Result<()> {
+            for value in wave.iter().flat_map(|elem| elem.value) {
+                typeck_expr(value, ty)?;
+            }
+            Ok(())
+        };
+        match *kind {
+            hir::SigAssignKind::SimpleWave(_, ref wave) => typeck_waveform(wave)?,
+            hir::SigAssignKind::CondWave(_, ref cond) => {
+                for &(ref wave, cond) in &cond.when {
+                    typeck_waveform(wave)?;
+                    typeck_expr(cond, self.ctx.builtin_boolean_type())?;
+                }
+                if let Some(ref wave) = cond.other {
+                    typeck_waveform(wave)?;
+                }
+            }
+            hir::SigAssignKind::SelWave(_, ref sel) => {
+                let disc_ty = self.lazy_typeval(sel.disc)?;
+                for &(ref wave, ref choices) in &sel.when {
+                    typeck_waveform(wave)?;
+                    for choice in &choices.value {
+                        if let hir::Choice::Expr(expr) = choice.value {
+                            typeck_expr(expr, disc_ty)?;
+                        }
+                    }
+                }
+            }
+            _ => unreachable!(),
+        }
+        Ok(())
+    }
+
+    /// Type check the actuals of a procedure call against the parameters of
+    /// the procedure.
+    pub fn typeck_call(
+        &self,
+        subprog: SubprogRef,
+        params: &'ctx Spanned<hir::AssocList>,
+    ) -> Result<()> {
+        let subprog_ty = match *self.ctx.ty(subprog)? {
+            Ty::Subprog(ref ty) => ty,
+            _ => unreachable!(),
+        };
+        for (arg, param) in params.value.iter().zip(subprog_ty.args.iter()) {
+            if let hir::AssocActual::Expr(expr) = arg.actual.value {
+                let act = self.lazy_typeval(expr)?;
+                self.must_match(self.ctx.intern_ty(param.ty.clone()), act, arg.actual.span);
+            }
+        }
+        Ok(())
+    }
+
     /// Type check the time expression in a delay mechanism.
     pub fn typeck_delay_mechanism(&self, _node: &'ctx hir::DelayMechanism) {
         // TODO: implement this
@@ -283,7 +483,7 @@ impl<'sbc, 'lazy, 'sb, 'ast, 'ctx> TypeckContext<'sbc, 'lazy, 'sb, 'ast, 'ctx> {
                             ))
                             .span(con.span),
                         );
-                        return Err(());
+                        return Err(Error::Reported);
                     }
                 };
 
@@ -296,7 +496,7 @@ impl<'sbc, 'lazy, 'sb, 'ast, 'ctx> TypeckContext<'sbc, 'lazy, 'sb, 'ast, 'ctx> {
                         ))
                         .span(con.span),
                     );
-                    return Err(());
+                    return Err(Error::Reported);
                 }
 
                 // Create the new type.
@@ -314,7 +514,7 @@ impl<'sbc, 'lazy, 'sb, 'ast, 'ctx> TypeckContext<'sbc, 'lazy, 'sb, 'ast, 'ctx> {
                     ))
                     .span(con.span),
                 );
-                return Err(());
+                return Err(Error::Reported);
             }
         }
     }
@@ -344,7 +544,7 @@ impl<'sbc, 'lazy, 'sb, 'ast, 'ctx> TypeckContext<'sbc, 'lazy, 'sb, 'ast, 'ctx> {
                                 con.span.extract()
                             )),
                         );
-                        return Err(());
+                        return Err(Error::Reported);
                     }
                     ty.indices
                         .iter()
@@ -381,7 +581,7 @@ impl<'sbc, 'lazy, 'sb, 'ast, 'ctx> TypeckContext<'sbc, 'lazy, 'sb, 'ast, 'ctx> {
                     ))
                     .span(con.span),
                 );
-                return Err(());
+                return Err(Error::Reported);
             }
         }
     }
@@ -431,7 +631,7 @@ impl<'sbc, 'lazy, 'sb, 'ast, 'ctx> TypeckContext<'sbc, 'lazy, 'sb, 'ast, 'ctx> {
                     };
                 }
                 if had_fails {
-                    return Err(());
+                    return Err(Error::Reported);
                 }
                 let fields = fields
                     .into_iter()
@@ -448,7 +648,7 @@ impl<'sbc, 'lazy, 'sb, 'ast, 'ctx> TypeckContext<'sbc, 'lazy, 'sb, 'ast, 'ctx> {
                     ))
                     .span(con.span),
                 );
-                return Err(());
+                return Err(Error::Reported);
             }
         }
     }
@@ -483,30 +683,17 @@ impl<'sbc, 'lazy, 'sb, 'ast, 'ctx> TypeckContext<'sbc, 'lazy, 'sb, 'ast, 'ctx> {
         match (deref, self.ctx.deref_named_type(subty.value)?) {
             (&Ty::Int(ref ty), &Ty::Int(ref subty)) => {
                 use std::cmp::{max, min};
-                if ty.dir != subty.dir {
-                    self.emit(
-                        DiagBuilder2::error(format!(
-                            "directions disagree; `{}` and `{}`",
-                            subty, ty
-                        ))
-                        .span(span),
-                    );
-                    return Err(());
+                // The subtype takes on the direction of the range constraint,
+                // which need not agree with the one of the type. See IEEE
+                // 1076-2008 section 5.2.1.
+                fn bounds(ty: &IntTy) -> (&num::BigInt, &num::BigInt) {
+                    match ty.dir {
+                        Dir::To => (&ty.left_bound, &ty.right_bound),
+                        Dir::Downto => (&ty.right_bound, &ty.left_bound),
+                    }
                 }
-                let (ty_lo, ty_hi, subty_lo, subty_hi) = match ty.dir {
-                    Dir::To => (
-                        &ty.left_bound,
-                        &ty.right_bound,
-                        &subty.left_bound,
-                        &subty.right_bound,
-                    ),
-                    Dir::Downto => (
-                        &ty.right_bound,
-                        &ty.left_bound,
-                        &subty.right_bound,
-                        &subty.left_bound,
-                    ),
-                };
+                let (ty_lo, ty_hi) = bounds(ty);
+                let (subty_lo, subty_hi) = bounds(subty);
                 if ty_lo > subty_lo || ty_hi < subty_hi {
                     self.emit(
                         DiagBuilder2::error(format!("`{}` is not a subrange of `{}`", subty, ty))
@@ -519,11 +706,11 @@ impl<'sbc, 'lazy, 'sb, 'ast, 'ctx> TypeckContext<'sbc, 'lazy, 'sb, 'ast, 'ctx> {
                 }
                 let lo = max(ty_lo, subty_lo);
                 let hi = min(ty_hi, subty_hi);
-                let (lb, rb) = match ty.dir {
+                let (lb, rb) = match subty.dir {
                     Dir::To => (lo, hi),
                     Dir::Downto => (hi, lo),
                 };
-                let new_ty: Ty = IntTy::new(ty.dir, lb.clone(), rb.clone()).into();
+                let new_ty: Ty = IntTy::new(subty.dir, lb.clone(), rb.clone()).into();
                 if &new_ty == deref {
                     Ok(orig_ty)
                 } else {
@@ -539,7 +726,7 @@ impl<'sbc, 'lazy, 'sb, 'ast, 'ctx> TypeckContext<'sbc, 'lazy, 'sb, 'ast, 'ctx> {
                     ))
                     .span(span),
                 );
-                return Err(());
+                return Err(Error::Reported);
             }
         }
     }
@@ -573,7 +760,7 @@ impl<'sbc, 'lazy, 'sb, 'ast, 'ctx> TypeckContext<'sbc, 'lazy, 'sb, 'ast, 'ctx> {
                             ))
                             .span(range.span),
                         );
-                        return Err(());
+                        return Err(Error::Reported);
                     }
                 }
             }
@@ -627,7 +814,7 @@ where
     fn typeck(&self, id: I) {
         match ScoreContext::make(self.ctx, id) {
             Ok(_) => (),
-            Err(()) => self.failed.set(true),
+            Err(_) => self.failed.set(true),
         }
     }
 }
@@ -650,7 +837,7 @@ where
                 "typecheck failed, expected {:?}, got {:?}",
                 expected, actual
             )));
-            Err(())
+            Err(Error::Reported)
         } else {
             Ok(())
         }
@@ -673,14 +860,7 @@ macro_rules! unimp_err {
             "typeck of {:?} not implemented",
             $id
         )));
-        return Err(());
-    }};
-}
-
-macro_rules! unimpmsg {
-    ($slf:tt, $span:expr, $msg:expr) => {{
-        $slf.emit(DiagBuilder2::bug(format!("{} not implemented", $msg)).span($span));
-        return Err(());
+        return Err(Error::Reported);
     }};
 }
 
@@ -730,6 +910,17 @@ impl_typeck_err!(self, id: EntityRef => {
     }
     for &port in &hir.ports {
         self.typeck(port);
+        let port = self.ctx.hir(port)?;
+        if let hir::IntfSignalMode::Linkage = port.mode {
+            self.emit(
+                DiagBuilder2::error(format!("linkage port `{}` not supported", port.name.value))
+                    .span(port.name.span)
+                    .add_note(
+                        "Linkage ports connect to foreign models and cannot be read or driven \
+                         from VHDL. Use a port of mode `in`, `out`, or `inout` instead.",
+                    ),
+            );
+        }
     }
     Ok(())
 });
@@ -775,8 +966,7 @@ impl_typeck!(self, id: IntfPkgRef => {
 });
 
 impl_make!(self, id: IntfConstRef => &Ty {
-    // TODO: Implement this.
-    unimp_err!(self, id)
+    self.ty(self.ast(id).2)
 });
 
 impl_make!(self, id: IntfVarRef => &Ty {
@@ -979,8 +1169,9 @@ impl_typeck!(self, id: AliasDeclRef => {
     unimp!(self, id)
 });
 
-impl_typeck!(self, id: CompDeclRef => {
-    unimp!(self, id)
+impl_typeck!(self, _id: CompDeclRef => {
+    // The ports of a component are checked where it is instantiated, against
+    // the entity it is bound to.
 });
 
 impl_typeck!(self, id: AttrDeclRef => {
@@ -1008,7 +1199,7 @@ impl_typeck!(self, id: GroupDeclRef => {
 });
 
 impl_typeck!(self, id: BlockStmtRef => {
-    unimp!(self, id)
+    self.lazy_typeck(id)
 });
 
 impl_typeck_err!(self, id: ProcessStmtRef => {
@@ -1023,70 +1214,42 @@ impl_typeck_err!(self, id: ProcessStmtRef => {
 });
 
 impl_typeck!(self, id: ConcCallStmtRef => {
-    unimp!(self, id)
+    self.lazy_typeck(id)
 });
 
 impl_typeck!(self, id: ConcAssertStmtRef => {
-    unimp!(self, id)
+    self.lazy_typeck(id)
 });
 
 impl_typeck!(self, id: ConcSigAssignStmtRef => {
-    unimp!(self, id)
+    self.lazy_typeck(id)
 });
 
 impl_typeck!(self, id: CompInstStmtRef => {
-    unimp!(self, id)
+    self.lazy_typeck(id)
 });
 
 impl_typeck!(self, id: ForGenStmtRef => {
-    unimp!(self, id)
+    self.lazy_typeck(id)
 });
 
-impl_typeck!(self, id: IfGenStmtRef => {
-    unimp!(self, id)
+impl_typeck_err!(self, id: GenBlockRef => {
+    let hir = self.ctx.existing_hir(id)?;
+    if let Some(guard) = hir.guard {
+        let ty = self.lazy_typeval(guard)?;
+        self.must_match(self.ctx.builtin_boolean_type(), ty, self.ctx.span(guard).unwrap());
+    }
+    self.typeck_slice(&hir.decls);
+    self.typeck_slice(&hir.stmts);
+    Ok(())
 });
 
-impl_typeck!(self, id: CaseGenStmtRef => {
-    unimp!(self, id)
+impl_typeck!(self, id: IfGenStmtRef => {
+    self.lazy_typeck(id)
 });
 
-impl_typeck_err!(self, id: SigAssignStmtRef => {
-    let hir = self.ctx.hir(id)?;
-    let lhs_ty = match hir.target {
-        hir::SigAssignTarget::Name(sig) => self.ctx.ty(sig)?,
-        hir::SigAssignTarget::Aggregate => unimpmsg!(self, hir.target_span, "assignment to aggregate signal"),
-    };
-    // let mut ctx = TypeckContext::new(self);
-    // let typeck_dm = |dm| match dm {
-    //     // TODO: typeck time expression
-    //     // &hir::DelayMechanism::RejectInertial(expr) => self.typeck_node(expr, self.intern_ty(/* time type */))?,
-    //     _ => Ok(()),
-    // };
-    match hir.kind {
-        hir::SigAssignKind::SimpleWave(ref dm, ref wave) => {
-            self.typeck_delay_mechanism(dm);
-            self.typeck_waveform(wave, lhs_ty);
-        }
-        hir::SigAssignKind::SimpleForce(_, _expr) => {
-            // self.typeck_node(expr, lhs_ty)?;
-        }
-        hir::SigAssignKind::SimpleRelease(_) => (),
-        hir::SigAssignKind::CondWave(ref dm, ref _cond) => {
-            self.typeck_delay_mechanism(dm);
-            // self.typeck_node(cond, lhs_ty)?;
-        }
-        hir::SigAssignKind::CondForce(_, ref _cond) => {
-            // self.typeck_node(cond, lhs_ty)?;
-        }
-        hir::SigAssignKind::SelWave(ref dm, ref _sel) => {
-            self.typeck_delay_mechanism(dm);
-            // self.typeck_node(sel, lhs_ty)?;
-        }
-        hir::SigAssignKind::SelForce(_, ref _sel) => {
-            // self.typeck_node(sel, lhs_ty)?;
-        }
-    }
-    Ok(())
+impl_typeck!(self, id: CaseGenStmtRef => {
+    self.lazy_typeck(id)
 });
 
 impl<'lazy, 'sb, 'ast, 'ctx> ScoreContext<'lazy, 'sb, 'ast, 'ctx> {
@@ -1103,6 +1266,59 @@ impl<'lazy, 'sb, 'ast, 'ctx> ScoreContext<'lazy, 'sb, 'ast, 'ctx> {
             other => Ok(other),
         }
     }
+
+    /// Determine the parameters of a subprogram.
+    pub fn subprog_params(&self, id: SubprogRef) -> Result<&'ctx [IntfObjRef]> {
+        Ok(match id {
+            SubprogRef::Decl(id) => &self.hir(id)?.spec.params,
+            SubprogRef::Body(id) => &self.hir(id)?.spec.params,
+            SubprogRef::Inst(id) => unimp_err!(self, id),
+        })
+    }
+
+    /// Determine the base type of a type or subtype.
+    pub fn base_type(&self, tm: TypeMarkRef) -> Result<TypeDeclRef> {
+        Ok(self.subtype_info(tm)?.base)
+    }
+
+    /// Break down the subtype denoted by a type mark.
+    ///
+    /// A subtype that has no constraint or resolution function of its own
+    /// inherits the one of the type mark it was declared with.
+    pub fn subtype_info(&self, tm: TypeMarkRef) -> Result<SubtypeInfo<'ctx>> {
+        match tm {
+            TypeMarkRef::Type(id) => Ok(SubtypeInfo {
+                base: id,
+                constraint: None,
+                resolution: None,
+            }),
+            TypeMarkRef::Subtype(id) => self.subtype_ind_info(self.hir(id)?.subty),
+        }
+    }
+
+    /// Break down the subtype denoted by a subtype indication.
+    pub fn subtype_ind_info(&self, id: SubtypeIndRef) -> Result<SubtypeInfo<'ctx>> {
+        let hir = self.lazy_hir(id)?;
+        let inner = self.subtype_info(hir.type_mark.value)?;
+        Ok(SubtypeInfo {
+            base: inner.base,
+            constraint: hir.constraint.as_ref().or(inner.constraint),
+            resolution: hir.resolution.or(inner.resolution),
+        })
+    }
+}
+
+/// The components of a subtype.
+///
+/// See IEEE 1076-2008 section 6.3.
+#[derive(Debug, Clone, Copy)]
+pub struct SubtypeInfo<'ctx> {
+    /// The base type.
+    pub base: TypeDeclRef,
+    /// The constraint, if any.
+    pub constraint: Option<&'ctx Spanned<hir::Constraint>>,
+    /// The resolution function, if any.
+    pub resolution: Option<Spanned<SubprogRef>>,
 }
 
 /// Determine the type of a type mark.
@@ -1144,7 +1360,7 @@ impl_make!(self, id: TypeDeclRef => &Ty {
                 DiagBuilder2::error(format!("declaration of type `{}` is incomplete", hir.name.value))
                 .span(hir.name.span)
             );
-            return Err(());
+            return Err(Error::Reported);
         }
     };
     match data.value {
@@ -1188,7 +1404,7 @@ impl_make!(self, id: TypeDeclRef => &Ty {
             for &index_id in index_ids {
                 let hir = match self.hir(index_id) {
                     Ok(h) => h,
-                    Err(()) => { had_fails = true; continue; }
+                    Err(_) => { had_fails = true; continue; }
                 };
                 indices.push(match hir.value {
                     hir::ArrayTypeIndex::Unbounded(tm) => {
@@ -1205,7 +1421,7 @@ impl_make!(self, id: TypeDeclRef => &Ty {
                 });
             }
             if had_fails {
-                return Err(());
+                return Err(Error::Reported);
             }
             let elem_ty = self.ty(elem_ty)?.clone();
             Ok(self.intern_ty(ArrayTy::new(indices, Box::new(elem_ty))))
@@ -1235,7 +1451,7 @@ impl_make!(self, id: TypeDeclRef => &Ty {
                 mapped_fields.push((name.value, Box::new(self.ty(subty)?.clone())))
             }
             if had_fails {
-                return Err(());
+                return Err(Error::Reported);
             }
             Ok(self.intern_ty(RecordTy::new(mapped_fields)))
         }
@@ -1264,16 +1480,15 @@ impl<'lazy, 'sb, 'ast, 'ctx> ScoreContext<'lazy, 'sb, 'ast, 'ctx> {
                 self.intern_ty(IntTy::new(dir, lb.value.clone(), rb.value.clone()).maybe_null())
             }
 
-            (&Const::Float(ref _lb), &Const::Float(ref _rb)) => {
-                self.emit(DiagBuilder2::error("Float range bounds not yet supported").span(span));
-                return Err(());
+            (&Const::Float(ref lb), &Const::Float(ref rb)) => {
+                self.intern_ty(RealTy::new(dir, lb.value, rb.value))
             }
 
             _ => {
                 self.emit(
                     DiagBuilder2::error("Bounds of range are not of the same type").span(span),
                 );
-                return Err(());
+                return Err(Error::Reported);
             }
         })
     }
@@ -1310,7 +1525,7 @@ impl_make!(self, id: SubtypeDeclRef => &Ty {
 //                 DiagBuilder2::error(format!("cannot infer type of `{}` from context", hir.span.extract()))
 //                 .span(hir.span)
 //             );
-//             Err(())
+//             Err(Error::Reported)
 //         }
 //
 //         hir::ExprData::FloatLiteral(ref _c) => {
@@ -1329,7 +1544,7 @@ impl_make!(self, id: SubtypeDeclRef => &Ty {
 //             //     DiagBuilder2::error("cannot infer type of float literal from context")
 //             //     .span(hir.span)
 //             // );
-//             // Err(())
+//             // Err(Error::Reported)
 //         }
 //
 //         _ => unimp_err!(self, id),
@@ -1341,6 +1556,7 @@ impl_make!(self, id: TypedNodeRef => &Ty {
     match id {
         TypedNodeRef::SubtypeInd(id) => self.make(id),
         TypedNodeRef::Signal(id)     => self.make(id),
+        TypedNodeRef::Expr(id)       => self.lazy_typeval(id),
     }
 });
 
@@ -1348,6 +1564,7 @@ impl_make!(self, id: SignalRef => &Ty {
     match id {
         SignalRef::Intf(id) => self.make(id),
         SignalRef::Decl(id) => self.lazy_typeval(id),
+        SignalRef::Guard(_) => Ok(self.builtin_boolean_type()),
     }
 });
 
@@ -1364,9 +1581,31 @@ impl_make!(self, id: LatentTypeMarkRef => &Ty {
     self.ty(self.hir(id)?.value)
 });
 
-// impl_make!(self, id: LatentSubprogRef => &Ty {
-//     self.ty(self.hir(id)?.value)
-// });
+/// Determine the type of a subprogram.
+impl_make!(self, id: SubprogRef => &Ty {
+    let spec = match id {
+        SubprogRef::Decl(id) => &self.hir(id)?.spec,
+        SubprogRef::Body(id) => &self.hir(id)?.spec,
+        SubprogRef::Inst(id) => unimp_err!(self, id),
+    };
+    let mut args = Vec::new();
+    for &param in &spec.params {
+        let name = match param {
+            IntfObjRef::Const(id) => self.ast(id).3.name,
+            IntfObjRef::Signal(id) => self.ast(id).3.name,
+            _ => unimp_err!(self, param),
+        };
+        args.push(SubprogTyArg::named(self.ty(param)?.clone(), name));
+    }
+    let ret = match spec.return_type {
+        Some(ty) => {
+            let tm = self.hir(ty.value)?;
+            Some(Ty::Named(tm.span.into(), tm.value))
+        }
+        None => None,
+    };
+    Ok(self.intern_ty(SubprogTy::new(args, ret)))
+});
 //
 // impl_make!(self, id: SubprogRef => &Ty {
 //     match id {
@@ -0,0 +1,107 @@
+// Copyright (c) 2016-2020 Fabian Schuiki
+
+//! Continuous recompilation of a design whenever its inputs change.
+//!
+//! Watch mode keeps the compiler running between changes. The source manager
+//! and the name table persist, VHDL files are only parsed again if they
+//! changed, and a unit is only elaborated again if one of the files it depends
+//! on changed. The diagnostics of each run are compared against the ones of the
+//! previous run. Only new or changed diagnostics are shown, such that the
+//! output stays focused on what the last edit caused.
+
+use crate::errors::{DiagBuilder2, DiagEmitter};
+use crate::inputs::{expand_inputs, LanguageMap};
+use std::collections::{BTreeMap, HashSet};
+use std::path::{Path, PathBuf};
+use std::time::SystemTime;
+
+/// Determine the files and directories to watch for a set of inputs.
+///
+/// Besides the input files themselves, this includes the directories that
+/// contain them, such that new files are noticed.
+pub fn watched_paths<'a>(
+    inputs: impl IntoIterator<Item = &'a str>,
+    map: &LanguageMap,
+) -> Vec<PathBuf> {
+    let mut paths = vec![];
+    for input in expand_inputs(inputs, map, &Quiet) {
+        if let Some(parent) = input.path.parent() {
+            paths.push(parent.to_path_buf());
+        }
+        paths.push(input.path);
+    }
+    paths.sort();
+    paths.dedup();
+    paths
+}
+
+/// A diagnostic emitter that discards everything. Parsing the inputs reports
+/// problems with them.
+struct Quiet;
+
+impl DiagEmitter for Quiet {
+    fn emit(&self, _: DiagBuilder2) {}
+}
+
+/// The modification times of a set of files.
+///
+/// Two snapshots compare unequal if a file has been modified, created, or
+/// removed in between.
+#[derive(Debug, Clone, PartialEq, Eq, Default)]
+pub struct Snapshot(BTreeMap<PathBuf, Option<SystemTime>>);
+
+impl Snapshot {
+    /// Record the modification times of a set of files or directories.
+    ///
+    /// Directories are recorded as well, since adding or removing a file
+    /// changes their modification time.
+    pub fn take<P: AsRef<Path>>(paths: impl IntoIterator<Item = P>) -> Snapshot {
+        Snapshot(
+            paths
+                .into_iter()
+                .map(|p| {
+                    let p = p.as_ref();
+                    let mtime = std::fs::metadata(p).and_then(|m| m.modified()).ok();
+                    (p.to_path_buf(), mtime)
+                })
+                .collect(),
+        )
+    }
+}
+
+/// The diagnostics seen in the previous run.
+#[derive(Debug, Default)]
+pub struct DiagnosticLog {
+    seen: HashSet<String>,
+}
+
+/// The difference between the diagnostics of two runs.
+#[derive(Debug)]
+pub struct DiagnosticDiff {
+    /// The diagnostics that were not present in the previous run.
+    pub new: Vec<String>,
+    /// The number of diagnostics of the previous run that are gone.
+    pub resolved: usize,
+    /// The total number of diagnostics in this run.
+    pub total: usize,
+}
+
+impl DiagnosticLog {
+    /// Record the diagnostics of a run and determine what changed.
+    pub fn update(&mut self, diags: Vec<String>) -> DiagnosticDiff {
+        let current: HashSet<String> = diags.iter().cloned().collect();
+        let resolved = self.seen.difference(&current).count();
+        let mut new = vec![];
+        for diag in diags {
+            if !self.seen.contains(&diag) && !new.contains(&diag) {
+                new.push(diag);
+            }
+        }
+        self.seen = current;
+        DiagnosticDiff {
+            new,
+            resolved,
+            total: self.seen.len(),
+        }
+    }
+}
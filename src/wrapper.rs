@@ -0,0 +1,999 @@
+// Copyright (c) 2016-2020 Fabian Schuiki
+
+//! Generation of wrappers and testbenches for entities and modules.
+//!
+//! A wrapper is a new entity or module with the same generics and ports as an
+//! existing one, which instantiates the latter and connects each of its ports
+//! to the identically named port of the wrapper. Wrappers can be generated in
+//! either language, which makes them a convenient starting point when
+//! integrating IP written in the other one. Types with an obvious counterpart,
+//! such as `std_logic_vector(7 downto 0)` and `logic [7:0]`, are translated;
+//! all other types and expressions are copied verbatim.
+//!
+//! Generics are passed across languages as parameters and vice versa, with
+//! `integer` mapping to `int`, `string` to `string`, `real` to `real`, and
+//! `boolean` to `bit`. The default values of booleans and single bits are
+//! translated along with them. Generics that cannot be passed across, such as
+//! VHDL type generics, SystemVerilog type parameters, or values of a type
+//! without a counterpart, are reported by `bridge_errors`.
+//!
+//! Skeleton testbenches are generated the same way, with signals in place of
+//! the wrapper's ports.
+
+use crate::common::name::get_name_table;
+use crate::inputs::Language;
+use crate::svlog::ast as svlog_ast;
+use crate::vhdl::syntax::ast as vhdl_ast;
+use std::fmt::Write;
+
+/// The generics and ports of an entity or module.
+#[derive(Debug, Clone)]
+pub struct Interface {
+    /// The name of the entity or module.
+    pub name: String,
+    /// The language the entity or module is written in.
+    pub lang: Language,
+    /// The generics or parameters.
+    pub generics: Vec<Generic>,
+    /// The generics or parameters which are not values, such as types, by
+    /// name and kind.
+    pub other_generics: Vec<(String, &'static str)>,
+    /// The ports.
+    pub ports: Vec<Port>,
+}
+
+/// A generic or parameter.
+#[derive(Debug, Clone)]
+pub struct Generic {
+    /// The name of the generic.
+    pub name: String,
+    /// The type of the generic.
+    pub ty: PortType,
+    /// The source text of the default value.
+    pub default: Option<String>,
+}
+
+/// A port.
+#[derive(Debug, Clone)]
+pub struct Port {
+    /// The name of the port.
+    pub name: String,
+    /// The direction of the port.
+    pub dir: PortDir,
+    /// The type of the port.
+    pub ty: PortType,
+}
+
+/// The direction of a port.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[allow(missing_docs)]
+pub enum PortDir {
+    In,
+    Out,
+    Inout,
+}
+
+/// The type of a port or generic.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum PortType {
+    /// A single bit, such as `std_logic` or `logic`.
+    Bit,
+    /// A bit vector with the source text of its left and right bound.
+    Vector(String, String),
+    /// An integer, such as `integer` or `int`.
+    Integer,
+    /// A boolean, such as `boolean` or a `bit` parameter.
+    Boolean,
+    /// A real number, such as `real`.
+    Real,
+    /// A string, such as `string`.
+    String,
+    /// Any other type, as VHDL source text.
+    Vhdl(String),
+    /// Any other type, as SystemVerilog source text.
+    Svlog(String),
+}
+
+/// Find the interface of the entity or module `name`.
+///
+/// VHDL entities are matched case-insensitively, unless `name` is an extended
+/// identifier such as `\cell$1\`, which also names the module `cell$1`.
+pub fn find_interface(
+    vhdl_units: &[vhdl_ast::DesignUnit],
+    svlog_files: &[svlog_ast::SourceFile],
+    name: &str,
+) -> Option<Interface> {
+    let (text, extended) = split_extended(name);
+    let vhdl_name = get_name_table().intern(name, extended);
+    let svlog_name = get_name_table().intern(&text, true);
+    for unit in vhdl_units {
+        if let vhdl_ast::DesignUnitData::EntityDecl(ref decl) = unit.data {
+            if decl.name.value == vhdl_name {
+                return Some(vhdl_interface(decl));
+            }
+        }
+    }
+    for file in svlog_files {
+        for item in &file.items {
+            if let svlog_ast::ItemData::ModuleDecl(ref module) = item.data {
+                if module.name.value == svlog_name {
+                    return Some(svlog_interface(module));
+                }
+            }
+        }
+    }
+    None
+}
+
+fn vhdl_interface(decl: &vhdl_ast::EntityDecl) -> Interface {
+    let mut intf = Interface {
+        name: decl.name.value.to_string(),
+        lang: Language::Vhdl,
+        generics: vec![],
+        other_generics: vec![],
+        ports: vec![],
+    };
+    for item in &decl.decls {
+        let (kind, decls) = match *item {
+            vhdl_ast::DeclItem::PortgenClause(_, ref kind, ref decls) => (kind.value, decls),
+            _ => continue,
+        };
+        for decl in &decls.value {
+            let obj = match *decl {
+                vhdl_ast::IntfDecl::ObjDecl(ref obj) => obj,
+                vhdl_ast::IntfDecl::TypeDecl(ref decl) => {
+                    intf.other_generics
+                        .push((decl.name.value.to_string(), "type generic"));
+                    continue;
+                }
+                vhdl_ast::IntfDecl::SubprogSpec(ref decl) => {
+                    intf.other_generics
+                        .push((decl.spec.name.span.extract(), "subprogram generic"));
+                    continue;
+                }
+                vhdl_ast::IntfDecl::PkgInst(ref decl) => {
+                    intf.other_generics
+                        .push((decl.name.value.to_string(), "package generic"));
+                    continue;
+                }
+            };
+            let ty = vhdl_type(&obj.ty.span.extract());
+            for name in &obj.names {
+                match kind {
+                    vhdl_ast::PortgenKind::Generic => intf.generics.push(Generic {
+                        name: name.name.to_string(),
+                        ty: ty.clone(),
+                        default: obj.default.as_ref().map(|e| e.span.extract()),
+                    }),
+                    vhdl_ast::PortgenKind::Port => intf.ports.push(Port {
+                        name: name.name.to_string(),
+                        dir: match obj.mode {
+                            Some(vhdl_ast::IntfMode::Out) | Some(vhdl_ast::IntfMode::Buffer) => {
+                                PortDir::Out
+                            }
+                            Some(vhdl_ast::IntfMode::Inout) => PortDir::Inout,
+                            _ => PortDir::In,
+                        },
+                        ty: ty.clone(),
+                    }),
+                }
+            }
+        }
+    }
+    intf
+}
+
+/// Classify the source text of a VHDL subtype indication.
+fn vhdl_type(text: &str) -> PortType {
+    let lower = text.to_lowercase();
+    match lower.as_str() {
+        "std_logic" | "std_ulogic" | "bit" => return PortType::Bit,
+        "integer" | "natural" | "positive" => return PortType::Integer,
+        "boolean" => return PortType::Boolean,
+        "real" => return PortType::Real,
+        "string" => return PortType::String,
+        _ => (),
+    }
+    let vectors = ["std_logic_vector", "std_ulogic_vector", "bit_vector"];
+    if let (Some(open), Some(close)) = (lower.find('('), lower.rfind(')')) {
+        if vectors.contains(&lower[..open].trim()) {
+            let range = &text[open + 1..close];
+            let lower_range = range.to_lowercase();
+            for sep in &[" downto ", " to "] {
+                if let Some(pos) = lower_range.find(sep) {
+                    return PortType::Vector(
+                        range[..pos].trim().to_string(),
+                        range[pos + sep.len()..].trim().to_string(),
+                    );
+                }
+            }
+        }
+    }
+    PortType::Vhdl(text.to_string())
+}
+
+fn svlog_interface(module: &svlog_ast::Module) -> Interface {
+    let mut intf = Interface {
+        name: module.name.value.to_string(),
+        lang: Language::SystemVerilog,
+        generics: vec![],
+        other_generics: vec![],
+        ports: vec![],
+    };
+    let body_params = module.items.iter().filter_map(|item| match item.data {
+        svlog_ast::ItemData::ParamDecl(ref decl) => Some(decl),
+        _ => None,
+    });
+    for param in module.params.iter().chain(body_params) {
+        let decls = match param.kind {
+            _ if param.local => continue,
+            svlog_ast::ParamKind::Value(ref decls) => decls,
+            svlog_ast::ParamKind::Type(ref decls) => {
+                for decl in decls {
+                    intf.other_generics
+                        .push((decl.name.value.to_string(), "type parameter"));
+                }
+                continue;
+            }
+        };
+        for decl in decls {
+            intf.generics.push(Generic {
+                name: decl.name.value.to_string(),
+                ty: match svlog_type(&decl.ty) {
+                    _ if decl.ty.is_implicit() && decl.ty.dims.is_empty() => PortType::Integer,
+                    PortType::Bit => PortType::Boolean,
+                    ty => ty,
+                },
+                default: decl.expr.as_ref().map(|e| e.span.extract()),
+            });
+        }
+    }
+    // Ports without a direction and type continue the previous port's.
+    let mut last = (svlog_ast::PortDir::Input, PortType::Bit);
+    for port in &module.ports {
+        if let svlog_ast::PortData::Named {
+            dir, ref ty, name, ..
+        } = port.data
+        {
+            if dir.is_some() || !ty.is_implicit() || !ty.dims.is_empty() {
+                last = (dir.unwrap_or(last.0), svlog_type(ty));
+            }
+            intf.ports.push(Port {
+                name: name.value.to_string(),
+                dir: svlog_dir(last.0),
+                ty: last.1.clone(),
+            });
+        }
+    }
+
+    // Non-ANSI modules declare their ports among the items, which replace
+    // the bare names in the port list.
+    for item in &module.items {
+        if let svlog_ast::ItemData::PortDecl(ref decl) = item.data {
+            for name in &decl.names {
+                let port = Port {
+                    name: name.name.to_string(),
+                    dir: svlog_dir(decl.dir),
+                    ty: svlog_type(&decl.ty),
+                };
+                match intf.ports.iter_mut().find(|p| p.name == port.name) {
+                    Some(existing) => *existing = port,
+                    None => intf.ports.push(port),
+                }
+            }
+        }
+    }
+    intf
+}
+
+fn svlog_dir(dir: svlog_ast::PortDir) -> PortDir {
+    match dir {
+        svlog_ast::PortDir::Input => PortDir::In,
+        svlog_ast::PortDir::Output => PortDir::Out,
+        svlog_ast::PortDir::Inout | svlog_ast::PortDir::Ref => PortDir::Inout,
+    }
+}
+
+/// Classify a SystemVerilog type.
+fn svlog_type(ty: &svlog_ast::Type) -> PortType {
+    let bit = match ty.kind.data {
+        svlog_ast::ImplicitType | svlog_ast::LogicType | svlog_ast::BitType => true,
+        svlog_ast::RegType => true,
+        svlog_ast::IntType | svlog_ast::IntegerType if ty.dims.is_empty() => {
+            return PortType::Integer
+        }
+        svlog_ast::RealType | svlog_ast::ShortRealType if ty.dims.is_empty() => {
+            return PortType::Real
+        }
+        svlog_ast::StringType if ty.dims.is_empty() => return PortType::String,
+        _ => false,
+    };
+    match ty.dims.as_slice() {
+        [] if bit => PortType::Bit,
+        [svlog_ast::TypeDim::Range(left, right)] if bit => {
+            PortType::Vector(left.span.extract(), right.span.extract())
+        }
+        _ => PortType::Svlog(ty.span.extract()),
+    }
+}
+
+/// Check whether the generics of an entity or module can be passed to it from
+/// the given language.
+///
+/// Returns a message for every generic that cannot be, which is empty if the
+/// unit is written in that language.
+pub fn bridge_errors(intf: &Interface, lang: Language) -> Vec<String> {
+    if is_vhdl(intf.lang) == is_vhdl(lang) {
+        return vec![];
+    }
+    let mut errors = vec![];
+    for (name, kind) in &intf.other_generics {
+        errors.push(format!(
+            "{} `{}` of `{}` cannot be passed from {}",
+            kind,
+            name,
+            intf.name,
+            lang_name(lang)
+        ));
+    }
+    for g in &intf.generics {
+        match g.ty {
+            PortType::Vhdl(ref ty) | PortType::Svlog(ref ty) => errors.push(format!(
+                "generic `{}` of `{}` has type `{}`, which has no counterpart in {}",
+                g.name,
+                intf.name,
+                ty,
+                lang_name(lang)
+            )),
+            _ => (),
+        }
+    }
+    errors
+}
+
+fn is_vhdl(lang: Language) -> bool {
+    lang == Language::Vhdl
+}
+
+fn lang_name(lang: Language) -> &'static str {
+    match lang {
+        Language::Vhdl => "VHDL",
+        Language::Verilog | Language::SystemVerilog => "SystemVerilog",
+    }
+}
+
+/// Translate the default value of a generic into the given language.
+///
+/// Booleans and single bits are spelled differently in the two languages. All
+/// other values are copied verbatim.
+fn bridge_default(intf: &Interface, g: &Generic, lang: Language) -> Option<String> {
+    let default = g.default.as_ref()?;
+    if is_vhdl(intf.lang) == is_vhdl(lang) {
+        return Some(default.clone());
+    }
+    let value = match default
+        .to_lowercase()
+        .replace(char::is_whitespace, "")
+        .as_str()
+    {
+        "true" | "'1'" | "1" | "'1" | "1'b1" | "'b1" => Some(true),
+        "false" | "'0'" | "0" | "'0" | "1'b0" | "'b0" => Some(false),
+        _ => None,
+    };
+    let bridged = match (&g.ty, value, lang) {
+        (PortType::Boolean, Some(v), Language::Vhdl) => v.to_string(),
+        (PortType::Bit, Some(v), Language::Vhdl) => format!("'{}'", v as u8),
+        (PortType::Boolean, Some(v), _) | (PortType::Bit, Some(v), _) => {
+            format!("1'b{}", v as u8)
+        }
+        _ => default.clone(),
+    };
+    Some(bridged)
+}
+
+/// Split a VHDL extended identifier into its text and whether it is one.
+fn split_extended(name: &str) -> (String, bool) {
+    match strip_prefix(name, "\\").and_then(|n| strip_suffix(n, "\\")) {
+        Some(inner) => (inner.replace("\\\\", "\\"), true),
+        None => (name.to_string(), false),
+    }
+}
+
+/// Spell a name of an interface as an identifier of the given language.
+///
+/// Names which are not basic identifiers of the language, as is common in
+/// netlists, are written as VHDL extended or SystemVerilog escaped
+/// identifiers. VHDL extended identifiers remain extended, since they are
+/// distinct from the basic identifier of the same spelling.
+fn identifier(name: &str, lang: Language) -> String {
+    let (text, extended) = split_extended(name);
+    if is_vhdl(lang) {
+        let basic = text.chars().all(|c| c.is_ascii_alphanumeric() || c == '_')
+            && text.starts_with(|c: char| c.is_ascii_alphabetic())
+            && !text.ends_with('_')
+            && !text.contains("__");
+        if basic && !extended {
+            text
+        } else {
+            format!("\\{}\\", text.replace('\\', "\\\\"))
+        }
+    } else {
+        let simple = text
+            .chars()
+            .all(|c| c.is_ascii_alphanumeric() || c == '_' || c == '$')
+            && text.starts_with(|c: char| c.is_ascii_alphabetic() || c == '_');
+        if simple {
+            text
+        } else {
+            format!("\\{} ", text)
+        }
+    }
+}
+
+/// Add a prefix and suffix to an identifier, within the delimiters of an
+/// extended or escaped identifier.
+fn affix(ident: &str, prefix: &str, suffix: &str) -> String {
+    let (open, inner, close) = match strip_prefix(ident, "\\") {
+        Some(rest) => match strip_suffix(rest, "\\").or_else(|| strip_suffix(rest, " ")) {
+            Some(inner) => ("\\", inner, &ident[ident.len() - 1..]),
+            None => ("", ident, ""),
+        },
+        None => ("", ident, ""),
+    };
+    format!("{}{}{}{}{}", open, prefix, inner, suffix, close)
+}
+
+/// Spell all names of an interface as identifiers of the given language.
+fn with_identifiers(intf: &Interface, lang: Language) -> Interface {
+    let mut intf = intf.clone();
+    intf.name = identifier(&intf.name, lang);
+    for g in &mut intf.generics {
+        g.name = identifier(&g.name, lang);
+    }
+    for p in &mut intf.ports {
+        p.name = identifier(&p.name, lang);
+    }
+    intf
+}
+
+/// Render a wrapper around an entity or module in the given language.
+pub fn render(intf: &Interface, lang: Language) -> String {
+    let intf = &with_identifiers(intf, lang);
+    match lang {
+        Language::Vhdl => render_vhdl(intf),
+        Language::Verilog | Language::SystemVerilog => render_svlog(intf),
+    }
+}
+
+/// Render a skeleton testbench for an entity or module in the given language.
+///
+/// The testbench declares a signal for every port and instantiates the unit
+/// with its default generics, which are repeated as constants such that port
+/// widths depending on them resolve. Single-bit inputs named like a clock or
+/// reset are driven with a 10 ns clock and a reset released after 20 ns. The
+/// stimulus process waits for the reset to be released and does nothing else.
+pub fn render_testbench(intf: &Interface, lang: Language) -> String {
+    let intf = &with_identifiers(intf, lang);
+    match lang {
+        Language::Vhdl => render_vhdl_testbench(intf),
+        Language::Verilog | Language::SystemVerilog => render_svlog_testbench(intf),
+    }
+}
+
+/// The role of an input port in a testbench.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Role {
+    Clock,
+    Reset { active_low: bool },
+    Other,
+}
+
+fn port_role(port: &Port) -> Role {
+    if port.dir != PortDir::In || port.ty != PortType::Bit {
+        return Role::Other;
+    }
+    let name = port.name.to_lowercase();
+    if let Some(base) = strip_suffix(&name, "_ni") {
+        return reset_role(base, true);
+    }
+    let name = strip_suffix(&name, "_i").unwrap_or(&name);
+    if name == "clk" || name == "clock" || name.ends_with("_clk") || name.ends_with("_clock") {
+        return Role::Clock;
+    }
+    let low = strip_suffix(name, "_n")
+        .or_else(|| strip_suffix(name, "n"))
+        .or_else(|| strip_prefix(name, "n"));
+    match low.map(|base| reset_role(base, true)) {
+        Some(role @ Role::Reset { .. }) => role,
+        _ => reset_role(name, false),
+    }
+}
+
+fn reset_role(name: &str, active_low: bool) -> Role {
+    if name == "rst" || name == "reset" || name.ends_with("_rst") || name.ends_with("_reset") {
+        Role::Reset { active_low }
+    } else {
+        Role::Other
+    }
+}
+
+/// Remove a prefix from a string, like `str::strip_prefix`, which needs Rust
+/// 1.45.
+fn strip_prefix<'a>(s: &'a str, prefix: &str) -> Option<&'a str> {
+    if s.starts_with(prefix) {
+        Some(&s[prefix.len()..])
+    } else {
+        None
+    }
+}
+
+/// Remove a suffix from a string, like `str::strip_suffix`, which needs Rust
+/// 1.45.
+fn strip_suffix<'a>(s: &'a str, suffix: &str) -> Option<&'a str> {
+    if s.ends_with(suffix) {
+        Some(&s[..s.len() - suffix.len()])
+    } else {
+        None
+    }
+}
+
+/// The source text of a type in the given language.
+pub fn type_name(ty: &PortType, lang: Language) -> String {
+    match lang {
+        Language::Vhdl => vhdl_type_name(ty),
+        Language::Verilog | Language::SystemVerilog => svlog_type_name(ty),
+    }
+}
+
+fn vhdl_type_name(ty: &PortType) -> String {
+    match *ty {
+        PortType::Bit => "std_logic".to_string(),
+        PortType::Vector(ref l, ref r) => format!("std_logic_vector({} downto {})", l, r),
+        PortType::Integer => "integer".to_string(),
+        PortType::Boolean => "boolean".to_string(),
+        PortType::Real => "real".to_string(),
+        PortType::String => "string".to_string(),
+        PortType::Vhdl(ref s) | PortType::Svlog(ref s) => s.clone(),
+    }
+}
+
+fn svlog_type_name(ty: &PortType) -> String {
+    match *ty {
+        PortType::Bit => "logic".to_string(),
+        PortType::Vector(ref l, ref r) => format!("logic [{}:{}]", l, r),
+        PortType::Integer => "int".to_string(),
+        PortType::Boolean => "bit".to_string(),
+        PortType::Real => "real".to_string(),
+        PortType::String => "string".to_string(),
+        PortType::Vhdl(ref s) | PortType::Svlog(ref s) => s.clone(),
+    }
+}
+
+/// Write a VHDL instantiation of `intf` with every generic and port connected
+/// to the identically named object.
+fn write_vhdl_inst(out: &mut String, intf: &Interface, label: &str) {
+    write!(out, "    {}: entity work.{}", label, intf.name).unwrap();
+    let maps = [
+        (
+            "generic",
+            intf.generics.iter().map(|g| &g.name).collect::<Vec<_>>(),
+        ),
+        ("port", intf.ports.iter().map(|p| &p.name).collect()),
+    ];
+    for &(kind, ref names) in &maps {
+        if names.is_empty() {
+            continue;
+        }
+        writeln!(out).unwrap();
+        writeln!(out, "        {} map (", kind).unwrap();
+        for (i, name) in names.iter().enumerate() {
+            let sep = separator(i, names.len(), ",");
+            writeln!(out, "            {0} => {0}{1}", name, sep).unwrap();
+        }
+        write!(out, "        )").unwrap();
+    }
+    writeln!(out, ";").unwrap();
+}
+
+/// Write a SystemVerilog instantiation of `intf` with every parameter and port
+/// connected to the identically named object.
+fn write_svlog_inst(out: &mut String, intf: &Interface, label: &str) {
+    write!(out, "    {}", intf.name).unwrap();
+    if !intf.generics.is_empty() {
+        writeln!(out, " #(").unwrap();
+        for (i, g) in intf.generics.iter().enumerate() {
+            let sep = separator(i, intf.generics.len(), ",");
+            writeln!(out, "        .{0}({0}){1}", g.name, sep).unwrap();
+        }
+        write!(out, "    )").unwrap();
+    }
+    writeln!(out, " {} (", label).unwrap();
+    for (i, p) in intf.ports.iter().enumerate() {
+        let sep = separator(i, intf.ports.len(), ",");
+        writeln!(out, "        .{0}({0}){1}", p.name, sep).unwrap();
+    }
+    writeln!(out, "    );").unwrap();
+}
+
+fn render_vhdl(intf: &Interface) -> String {
+    let mut out = String::new();
+    let name = affix(&intf.name, "", "_wrapper");
+    writeln!(out, "library ieee;").unwrap();
+    writeln!(out, "use ieee.std_logic_1164.all;").unwrap();
+    writeln!(out).unwrap();
+    writeln!(out, "entity {} is", name).unwrap();
+    if !intf.generics.is_empty() {
+        writeln!(out, "    generic (").unwrap();
+        for (i, g) in intf.generics.iter().enumerate() {
+            write!(out, "        {} : {}", g.name, vhdl_type_name(&g.ty)).unwrap();
+            if let Some(default) = bridge_default(intf, g, Language::Vhdl) {
+                write!(out, " := {}", default).unwrap();
+            }
+            writeln!(out, "{}", separator(i, intf.generics.len(), ";")).unwrap();
+        }
+        writeln!(out, "    );").unwrap();
+    }
+    if !intf.ports.is_empty() {
+        writeln!(out, "    port (").unwrap();
+        for (i, p) in intf.ports.iter().enumerate() {
+            let dir = match p.dir {
+                PortDir::In => "in",
+                PortDir::Out => "out",
+                PortDir::Inout => "inout",
+            };
+            writeln!(
+                out,
+                "        {} : {} {}{}",
+                p.name,
+                dir,
+                vhdl_type_name(&p.ty),
+                separator(i, intf.ports.len(), ";")
+            )
+            .unwrap();
+        }
+        writeln!(out, "    );").unwrap();
+    }
+    writeln!(out, "end entity;").unwrap();
+    writeln!(out).unwrap();
+    writeln!(out, "architecture rtl of {} is", name).unwrap();
+    writeln!(out, "begin").unwrap();
+    write_vhdl_inst(&mut out, intf, &affix(&intf.name, "i_", ""));
+    writeln!(out, "end architecture;").unwrap();
+    out
+}
+
+fn render_svlog(intf: &Interface) -> String {
+    let mut out = String::new();
+    write!(out, "module {}", affix(&intf.name, "", "_wrapper")).unwrap();
+    if !intf.generics.is_empty() {
+        writeln!(out, " #(").unwrap();
+        for (i, g) in intf.generics.iter().enumerate() {
+            write!(out, "    parameter {} {}", svlog_type_name(&g.ty), g.name).unwrap();
+            if let Some(default) = bridge_default(intf, g, Language::SystemVerilog) {
+                write!(out, " = {}", default).unwrap();
+            }
+            writeln!(out, "{}", separator(i, intf.generics.len(), ",")).unwrap();
+        }
+        write!(out, ")").unwrap();
+    }
+    writeln!(out, " (").unwrap();
+    for (i, p) in intf.ports.iter().enumerate() {
+        let dir = match (p.dir, &p.ty) {
+            (PortDir::In, _) => "input",
+            (PortDir::Out, _) => "output",
+            (PortDir::Inout, PortType::Bit) | (PortDir::Inout, PortType::Vector(..)) => {
+                "inout wire"
+            }
+            (PortDir::Inout, _) => "inout",
+        };
+        let sep = separator(i, intf.ports.len(), ",");
+        writeln!(
+            out,
+            "    {} {} {}{}",
+            dir,
+            svlog_type_name(&p.ty),
+            p.name,
+            sep
+        )
+        .unwrap();
+    }
+    writeln!(out, ");").unwrap();
+    write_svlog_inst(&mut out, intf, &affix(&intf.name, "i_", ""));
+    writeln!(out, "endmodule").unwrap();
+    out
+}
+
+fn render_vhdl_testbench(intf: &Interface) -> String {
+    let mut out = String::new();
+    let name = affix(&intf.name, "", "_tb");
+    writeln!(out, "library ieee;").unwrap();
+    writeln!(out, "use ieee.std_logic_1164.all;").unwrap();
+    writeln!(out).unwrap();
+    writeln!(out, "entity {} is", name).unwrap();
+    writeln!(out, "end entity;").unwrap();
+    writeln!(out).unwrap();
+    writeln!(out, "architecture sim of {} is", name).unwrap();
+    for g in &intf.generics {
+        if let Some(default) = bridge_default(intf, g, Language::Vhdl) {
+            let ty = vhdl_type_name(&g.ty);
+            writeln!(out, "    constant {} : {} := {};", g.name, ty, default).unwrap();
+        }
+    }
+    let mut resets = vec![];
+    for p in &intf.ports {
+        write!(out, "    signal {} : {}", p.name, vhdl_type_name(&p.ty)).unwrap();
+        match port_role(p) {
+            Role::Clock => write!(out, " := '0'").unwrap(),
+            Role::Reset { active_low } => {
+                write!(out, " := '{}'", if active_low { 0 } else { 1 }).unwrap();
+                resets.push((&p.name, active_low));
+            }
+            Role::Other => (),
+        }
+        writeln!(out, ";").unwrap();
+    }
+    writeln!(out, "begin").unwrap();
+    for p in &intf.ports {
+        match port_role(p) {
+            Role::Clock => writeln!(out, "    {0} <= not {0} after 5 ns;", p.name).unwrap(),
+            Role::Reset { active_low } => {
+                let value = if active_low { 1 } else { 0 };
+                writeln!(out, "    {} <= '{}' after 20 ns;", p.name, value).unwrap();
+            }
+            Role::Other => (),
+        }
+    }
+    writeln!(out).unwrap();
+    write_vhdl_inst(&mut out, intf, "i_dut");
+    writeln!(out).unwrap();
+    writeln!(out, "    stimulus: process").unwrap();
+    writeln!(out, "    begin").unwrap();
+    for (name, active_low) in resets {
+        let value = if active_low { 1 } else { 0 };
+        writeln!(out, "        wait until {} = '{}';", name, value).unwrap();
+    }
+    writeln!(out, "        wait;").unwrap();
+    writeln!(out, "    end process;").unwrap();
+    writeln!(out, "end architecture;").unwrap();
+    out
+}
+
+fn render_svlog_testbench(intf: &Interface) -> String {
+    let mut out = String::new();
+    writeln!(out, "module {};", affix(&intf.name, "", "_tb")).unwrap();
+    for g in &intf.generics {
+        if let Some(default) = bridge_default(intf, g, Language::SystemVerilog) {
+            let ty = svlog_type_name(&g.ty);
+            writeln!(out, "    localparam {} {} = {};", ty, g.name, default).unwrap();
+        }
+    }
+    let mut resets = vec![];
+    for p in &intf.ports {
+        let ty = svlog_type_name(&p.ty);
+        match port_role(p) {
+            Role::Clock => writeln!(out, "    {} {} = 0;", ty, p.name).unwrap(),
+            Role::Reset { active_low } => {
+                let value = if active_low { 0 } else { 1 };
+                writeln!(out, "    {} {} = {};", ty, p.name, value).unwrap();
+                resets.push((&p.name, active_low));
+            }
+            Role::Other if p.dir == PortDir::Inout => {
+                writeln!(out, "    wire {} {};", ty, p.name).unwrap()
+            }
+            Role::Other => writeln!(out, "    {} {};", ty, p.name).unwrap(),
+        }
+    }
+    writeln!(out).unwrap();
+    for p in &intf.ports {
+        match port_role(p) {
+            Role::Clock => writeln!(out, "    always #5ns {0} = ~{0};", p.name).unwrap(),
+            Role::Reset { active_low } => {
+                let value = if active_low { 1 } else { 0 };
+                writeln!(out, "    initial #20ns {} = {};", p.name, value).unwrap();
+            }
+            Role::Other => (),
+        }
+    }
+    writeln!(out).unwrap();
+    write_svlog_inst(&mut out, intf, "i_dut");
+    writeln!(out).unwrap();
+    writeln!(out, "    initial begin").unwrap();
+    for (name, active_low) in resets {
+        let cond = if active_low { "" } else { "!" };
+        writeln!(out, "        wait ({}{});", cond, name).unwrap();
+    }
+    writeln!(out, "        $finish;").unwrap();
+    writeln!(out, "    end").unwrap();
+    writeln!(out, "endmodule").unwrap();
+    out
+}
+
+/// The separator to place after the `i`th of `len` list elements.
+fn separator(i: usize, len: usize, sep: &'static str) -> &'static str {
+    if i + 1 < len {
+        sep
+    } else {
+        ""
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn interface(lang: Language) -> Interface {
+        Interface {
+            name: "fifo".to_string(),
+            lang,
+            generics: vec![
+                Generic {
+                    name: "WIDTH".to_string(),
+                    ty: PortType::Integer,
+                    default: Some("8".to_string()),
+                },
+                Generic {
+                    name: "BYPASS".to_string(),
+                    ty: PortType::Boolean,
+                    default: Some("false".to_string()),
+                },
+            ],
+            other_generics: vec![],
+            ports: vec![
+                Port {
+                    name: "clk_i".to_string(),
+                    dir: PortDir::In,
+                    ty: PortType::Bit,
+                },
+                Port {
+                    name: "rst_ni".to_string(),
+                    dir: PortDir::In,
+                    ty: PortType::Bit,
+                },
+                Port {
+                    name: "data".to_string(),
+                    dir: PortDir::Out,
+                    ty: PortType::Vector("WIDTH-1".to_string(), "0".to_string()),
+                },
+            ],
+        }
+    }
+
+    #[test]
+    fn vhdl_types() {
+        assert_eq!(vhdl_type("std_logic"), PortType::Bit);
+        assert_eq!(vhdl_type("NATURAL"), PortType::Integer);
+        assert_eq!(
+            vhdl_type("std_logic_vector(WIDTH-1 DOWNTO 0)"),
+            PortType::Vector("WIDTH-1".to_string(), "0".to_string())
+        );
+        assert_eq!(
+            vhdl_type("bit_vector (0 to 3)"),
+            PortType::Vector("0".to_string(), "3".to_string())
+        );
+        assert_eq!(
+            vhdl_type("unsigned(7 downto 0)"),
+            PortType::Vhdl("unsigned(7 downto 0)".to_string())
+        );
+    }
+
+    #[test]
+    fn identifiers() {
+        assert_eq!(identifier("data", Language::Vhdl), "data");
+        assert_eq!(identifier("_data", Language::Vhdl), "\\_data\\");
+        assert_eq!(identifier("a__b", Language::Vhdl), "\\a__b\\");
+        assert_eq!(identifier("\\cell$1\\", Language::Vhdl), "\\cell$1\\");
+        assert_eq!(identifier("\\cell$1\\", Language::SystemVerilog), "cell$1");
+        assert_eq!(identifier("a.b", Language::SystemVerilog), "\\a.b ");
+        assert_eq!(affix("data", "s_", "_q"), "s_data_q");
+        assert_eq!(affix("\\a.b\\", "s_", ""), "\\s_a.b\\");
+        assert_eq!(affix("\\a.b ", "", "_q"), "\\a.b_q ");
+    }
+
+    #[test]
+    fn port_roles() {
+        let role = |name: &str| {
+            port_role(&Port {
+                name: name.to_string(),
+                dir: PortDir::In,
+                ty: PortType::Bit,
+            })
+        };
+        assert_eq!(role("clk_i"), Role::Clock);
+        assert_eq!(role("sys_clock"), Role::Clock);
+        assert_eq!(role("rst_ni"), Role::Reset { active_low: true });
+        assert_eq!(role("nreset"), Role::Reset { active_low: true });
+        assert_eq!(role("rst"), Role::Reset { active_low: false });
+        assert_eq!(role("valid"), Role::Other);
+    }
+
+    #[test]
+    fn bridged_generics() {
+        let intf = interface(Language::Vhdl);
+        assert_eq!(
+            bridge_default(&intf, &intf.generics[1], Language::SystemVerilog),
+            Some("1'b0".to_string())
+        );
+        assert_eq!(
+            bridge_default(&intf, &intf.generics[0], Language::SystemVerilog),
+            Some("8".to_string())
+        );
+
+        let mut intf = intf;
+        intf.other_generics.push(("T".to_string(), "type generic"));
+        intf.generics[0].ty = PortType::Vhdl("unsigned".to_string());
+        assert!(bridge_errors(&intf, Language::Vhdl).is_empty());
+        assert_eq!(
+            bridge_errors(&intf, Language::SystemVerilog),
+            vec![
+                "type generic `T` of `fifo` cannot be passed from SystemVerilog",
+                "generic `WIDTH` of `fifo` has type `unsigned`, which has no counterpart in SystemVerilog",
+            ]
+        );
+    }
+
+    #[test]
+    fn svlog_wrapper_of_vhdl() {
+        assert_eq!(
+            render(&interface(Language::Vhdl), Language::SystemVerilog),
+            "module fifo_wrapper #(
+    parameter int WIDTH = 8,
+    parameter bit BYPASS = 1'b0
+) (
+    input logic clk_i,
+    input logic rst_ni,
+    output logic [WIDTH-1:0] data
+);
+    fifo #(
+        .WIDTH(WIDTH),
+        .BYPASS(BYPASS)
+    ) i_fifo (
+        .clk_i(clk_i),
+        .rst_ni(rst_ni),
+        .data(data)
+    );
+endmodule
+"
+        );
+    }
+
+    #[test]
+    fn vhdl_testbench() {
+        assert_eq!(
+            render_testbench(&interface(Language::SystemVerilog), Language::Vhdl),
+            "library ieee;
+use ieee.std_logic_1164.all;
+
+entity fifo_tb is
+end entity;
+
+architecture sim of fifo_tb is
+    constant WIDTH : integer := 8;
+    constant BYPASS : boolean := false;
+    signal clk_i : std_logic := '0';
+    signal rst_ni : std_logic := '0';
+    signal data : std_logic_vector(WIDTH-1 downto 0);
+begin
+    clk_i <= not clk_i after 5 ns;
+    rst_ni <= '1' after 20 ns;
+
+    i_dut: entity work.fifo
+        generic map (
+            WIDTH => WIDTH,
+            BYPASS => BYPASS
+        )
+        port map (
+            clk_i => clk_i,
+            rst_ni => rst_ni,
+            data => data
+        );
+
+    stimulus: process
+    begin
+        wait until rst_ni = '1';
+        wait;
+    end process;
+end architecture;
+"
+        );
+    }
+}
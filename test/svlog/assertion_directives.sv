@@ -0,0 +1,21 @@
+// RUN: moore test/svlog/assertion_directives.sv -e foo
+// Assumptions and restrictions constrain the inputs, assertions and covers
+// are checked against the design.
+module foo (input logic clk, input logic req, output logic ack);
+	always_ff @(posedge clk) ack <= req;
+
+	req_stable: assume property (@(posedge clk) req |=> req);
+	assume property (@(posedge clk) disable iff (!req) ##1 req);
+	restrict property (@(posedge clk) !(req && ack));
+	ack_follows: assert property (@(posedge clk) req |=> ack);
+	cover property (@(posedge clk) req ##1 ack);
+endmodule
+
+// The directives precede the entity they belong to. The source path is
+// relative in the RUN line such that it can be checked.
+// CHECK: ; assume property `req_stable` at test/svlog/assertion_directives.sv:7:14
+// CHECK: ; assume property at test/svlog/assertion_directives.sv:8:2
+// CHECK: ; restrict property at test/svlog/assertion_directives.sv:9:2
+// CHECK: ; assert property `ack_follows` at test/svlog/assertion_directives.sv:10:15
+// CHECK: ; cover property at test/svlog/assertion_directives.sv:11:2
+// CHECK: entity @foo (i1$ %clk, i1$ %req) -> (i1$ %ack) {
@@ -0,0 +1,27 @@
+// RUN: moore %s -e cfg_top_fast
+// A configuration rebinds the instances of `cfg_leaf` to `cfg_leaf_fast`,
+// except for `u1`, which keeps the original module.
+module cfg_top (input logic [7:0] a, output logic [7:0] z0, z1);
+	cfg_leaf u0 (.a(a), .z(z0));
+	cfg_leaf u1 (.a(a), .z(z1));
+endmodule
+
+module cfg_leaf (input logic [7:0] a, output logic [7:0] z);
+	assign z = a + 1;
+endmodule
+
+module cfg_leaf_fast (input logic [7:0] a, output logic [7:0] z);
+	assign z = a - 1;
+endmodule
+
+config cfg_top_fast;
+	design work.cfg_top;
+	default liblist work;
+	cell cfg_leaf use cfg_leaf_fast;
+	instance cfg_top.u1 use work.cfg_leaf;
+endconfig : cfg_top_fast
+
+// The instances are bound to different modules, and `u0` is bound first.
+// CHECK: entity @cfg_leaf_fast.param1 (i8$ %a) -> (i8$ %z) {
+// CHECK: entity @cfg_leaf.param2 (i8$ %a) -> (i8$ %z) {
+// CHECK: entity @cfg_top (i8$ %a) -> (i8$ %z0, i8$ %z1) {
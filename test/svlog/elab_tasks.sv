@@ -0,0 +1,22 @@
+// RUN: moore %s -e foo
+// Elaboration system tasks are evaluated with the parameters of the instance.
+// Those in unselected generate blocks are not reported.
+module foo;
+	bar #(8) a();
+endmodule
+
+module bar #(parameter int WIDTH = 4);
+	$info("elaborating %m with WIDTH=%0d", WIDTH);
+	if (WIDTH < 1) begin
+		$fatal(1, "WIDTH must be positive, got %0d", WIDTH);
+	end
+	if (WIDTH % 8 != 0) begin
+		$error("WIDTH=%0d is not a multiple of 8", WIDTH);
+	end else begin
+		$warning("WIDTH=%0d uses %0d bytes", WIDTH, WIDTH / 8);
+	end
+endmodule
+
+// The parameters select the informational message and the warning.
+// CHECK: note: elaborating bar.param1 with WIDTH=8
+// CHECK: warning: WIDTH=8 uses 1 bytes
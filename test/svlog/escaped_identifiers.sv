@@ -0,0 +1,11 @@
+// Netlists written by synthesis tools name cells and nets with escaped
+// identifiers.
+module \cell$1 (input logic \d[0] , input logic \D[0] , output logic \q/out );
+    logic \n1+n2 ;
+    assign \n1+n2 = \d[0] & \D[0] ;
+    assign \q/out = ~\n1+n2 ;
+endmodule
+
+module \top.netlist (input logic a, input logic b, output logic y);
+    \cell$1 \u_cell[0] (.\d[0] (a), .\D[0] (b), .\q/out (y));
+endmodule
@@ -0,0 +1,18 @@
+// RUN: moore %s -e foo -O0
+
+module foo;
+    int v0 = $sqrt(16);
+    // CHECK: %0 = const i32 4
+    int v1 = $sqrt(2);
+    // CHECK: %1 = const i32 1
+    int v2 = $sqrt(2.25);
+    // CHECK: %2 = const i32 2
+    int v3 = $ceil(2.1);
+    // CHECK: %3 = const i32 3
+    int v4 = $pow(2, 10);
+    // CHECK: %4 = const i32 1024
+    int v5 = $floor($ln(100) / $ln(10));
+    // CHECK: %5 = const i32 2
+    real v6 = $sqrt(2);
+    // CHECK: const i64 4609047870845172685
+endmodule
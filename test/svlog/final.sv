@@ -0,0 +1,30 @@
+// RUN: moore %s -e A -O0
+module A;
+	bit clk;
+	int cycles;
+	int errors;
+
+	initial forever #5 clk = ~clk;
+
+	always_ff @(posedge clk) cycles <= cycles + 1;
+
+	// End the test after a few cycles.
+	initial begin
+		#100;
+		$finish;
+	end
+
+	// Summarize the test once it has ended.
+	final begin
+		if (cycles != 10)
+			errors = errors + 1;
+	end
+endmodule
+
+// The final procedure runs at the end of time, or once `$finish` raises the
+// finish signal of the entity.
+// CHECK:     %endtimes = const time 9001s
+// CHECK:     wait %body for %endtimes, %finish
+// CHECK: body:
+// CHECK: entity @A () -> () {
+// CHECK:     %finish = sig i1 %2
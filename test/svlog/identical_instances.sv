@@ -0,0 +1,17 @@
+// RUN: moore %s -e inst_top
+// Instances with the same parameters and port types share one emitted unit,
+// while `u2` gets its own.
+module inst_top (input logic [7:0] a, output logic [7:0] z0, z1, z2);
+	inst_leaf #(.N(1)) u0 (.a(a), .z(z0));
+	inst_leaf #(.N(1)) u1 (.a(a), .z(z1));
+	inst_leaf #(.N(2)) u2 (.a(a), .z(z2));
+endmodule
+
+module inst_leaf #(parameter int N = 0) (input logic [7:0] a, output logic [7:0] z);
+	assign z = a + N;
+endmodule
+
+// `u1` reuses the unit emitted for `u0` instead of emitting `param2`.
+// CHECK: entity @inst_leaf.param1 (i8$ %a) -> (i8$ %z) {
+// CHECK: entity @inst_leaf.param3 (i8$ %a) -> (i8$ %z) {
+// CHECK: entity @inst_top (i8$ %a) -> (i8$ %z0, i8$ %z1, i8$ %z2) {
@@ -0,0 +1,19 @@
+// @elab A
+module A;
+	bit clk;
+	int a;
+	int b;
+
+	// Clock generator with a delay in the default time unit.
+	initial forever #5 clk = ~clk;
+
+	// Stimulus mixing delays with and without a time unit.
+	initial begin
+		a = 0;
+		#10 a = 1;
+		#2ns a = 2;
+		b <= #3 a;
+		#(2 * 5);
+		a = b;
+	end
+endmodule
@@ -0,0 +1,18 @@
+// RUN: moore %s -e foo -P N=1,2,8 -P M=0,1
+
+// Elaborate the same top over a cartesian product of parameter values.
+module foo #(parameter int N = 4, parameter int M = 0) (
+	input logic [N-1:0] a,
+	output logic [N-1:0] z
+);
+	assign z = M ? ~a : a;
+endmodule
+
+// Each configuration is emitted as a distinct entity, in the order N=1 M=0,
+// N=1 M=1, N=2 M=0, N=2 M=1, N=8 M=0, N=8 M=1.
+// CHECK: entity @foo.param1 (i1$ %a) -> (i1$ %z) {
+// CHECK: entity @foo.param2 (i1$ %a) -> (i1$ %z) {
+// CHECK: entity @foo.param3 (i2$ %a) -> (i2$ %z) {
+// CHECK: entity @foo.param4 (i2$ %a) -> (i2$ %z) {
+// CHECK: entity @foo.param5 (i8$ %a) -> (i8$ %z) {
+// CHECK: entity @foo.param6 (i8$ %a) -> (i8$ %z) {
@@ -0,0 +1,12 @@
+// @elab plusargs
+// The plusargs of the simulation run are queried through the runtime.
+module plusargs;
+	int cycles = 100;
+	bit verbose;
+	initial begin
+		verbose = $test$plusargs("VERBOSE");
+		if ($value$plusargs("CYCLES=%d", cycles))
+			verbose = 1;
+		$value$plusargs("SEED=%d", cycles);
+	end
+endmodule
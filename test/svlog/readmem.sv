@@ -0,0 +1,38 @@
+// RUN: moore %s -e readmem -O0
+module readmem;
+	logic [7:0] rom [0:15];
+	logic [3:0] lut [7:0];
+
+	initial begin
+		$readmemh("readmem.hex", rom);
+		$readmemb("readmem.bin", lut, 2, 5);
+	end
+endmodule
+
+// The words of the files are inserted at their addresses.
+// CHECK:     %rom.prb = prb [16 x i8]$ %rom
+// CHECK:     %1 = const i8 0
+// CHECK:     %2 = insf [16 x i8] %rom.prb, i8 %1, 0
+// CHECK:     %3 = const i8 1
+// CHECK:     %4 = insf [16 x i8] %2, i8 %3, 1
+// CHECK:     %5 = const i8 2
+// CHECK:     %6 = insf [16 x i8] %4, i8 %5, 2
+// CHECK:     %7 = const i8 3
+// CHECK:     %8 = insf [16 x i8] %6, i8 %7, 3
+// CHECK:     %9 = const i8 171
+// CHECK:     %10 = insf [16 x i8] %8, i8 %9, 4
+// CHECK:     %11 = const i8 255
+// CHECK:     %12 = insf [16 x i8] %10, i8 %11, 15
+// CHECK:     %13 = const time 0s 1e
+// CHECK:     drv [16 x i8]$ %rom, %12, %13
+// CHECK:     %lut.prb = prb [8 x i4]$ %lut
+// CHECK:     %14 = const i4 1
+// CHECK:     %15 = insf [8 x i4] %lut.prb, i4 %14, 2
+// CHECK:     %16 = const i4 2
+// CHECK:     %17 = insf [8 x i4] %15, i4 %16, 3
+// CHECK:     %18 = const i4 4
+// CHECK:     %19 = insf [8 x i4] %17, i4 %18, 4
+// CHECK:     %20 = const i4 8
+// CHECK:     %21 = insf [8 x i4] %19, i4 %20, 5
+// CHECK:     %22 = const time 0s 1e
+// CHECK:     drv [8 x i4]$ %lut, %21, %22
@@ -0,0 +1,10 @@
+// RUN: moore %s -e foo --size-report
+
+module foo (input logic [7:0] a, output logic [7:0] z);
+	bar b0(a[3:0], z[3:0]);
+	bar b1(a[7:4], z[7:4]);
+endmodule
+
+module bar (input logic [3:0] a, output logic [3:0] z);
+	assign z = ~a;
+endmodule
@@ -0,0 +1,33 @@
+// RUN: moore test/svlog/sva_sequences.sv -e A
+// Sequences with delay ranges, repetitions, and local variables.
+module A (input logic clk, rst, req, gnt, ack, busy, input logic [7:0] data, result);
+	sequence s_transfer(a, b);
+		int x;
+		(a, x = data) ##1 b[*2] ##[1:3] (result == x);
+	endsequence
+
+	property p_hold;
+		logic [7:0] v;
+		@(posedge clk) disable iff (rst)
+			(req, v = data) |=> busy throughout (ack[->1] ##1 result == v);
+	endproperty : p_hold
+
+	assert property (@(posedge clk) req |-> ##[1:$] gnt);
+	assert property (@(posedge clk) req ##1 gnt[*1:3] ##[+] ack);
+	assume property (@(posedge clk) disable iff (rst) (req ##[0:2] gnt) |-> ##2 ack[=2]);
+	cover property (@(posedge clk) req[*2] ##[*] ack);
+	assert property (@(posedge clk) req[->2] within (gnt ##1 ack[+]));
+	assert property (@(posedge clk) data[0] ##1 data[1:0] == 2'b1 intersect gnt[*2]);
+endmodule
+
+// The assertions are parsed and kept as directives, while the declarations
+// are skipped during elaboration.
+// CHECK: ; assert property at test/svlog/sva_sequences.sv:15:2
+// CHECK: ; assert property at test/svlog/sva_sequences.sv:16:2
+// CHECK: ; assume property at test/svlog/sva_sequences.sv:17:2
+// CHECK: ; cover property at test/svlog/sva_sequences.sv:18:2
+// CHECK: ; assert property at test/svlog/sva_sequences.sv:19:2
+// CHECK: ; assert property at test/svlog/sva_sequences.sv:20:2
+// CHECK: entity @A (i1$ %clk, i1$ %rst, i1$ %req, i1$ %gnt, i1$ %ack, i1$ %busy, i8$ %data, i8$ %result) -> () {
+// CHECK: warning: unsupported: sequence declaration; ignored
+// CHECK: warning: unsupported: property declaration; ignored
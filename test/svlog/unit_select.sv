@@ -0,0 +1,16 @@
+// RUN: moore %s --unit foo -e foo
+
+// Only `foo` and `bar` are processed. The unrelated `baz` refers to an
+// undeclared name and would otherwise abort compilation.
+
+module foo (input logic [7:0] a, output logic [7:0] z);
+	bar b0(a, z);
+endmodule
+
+module bar (input logic [7:0] a, output logic [7:0] z);
+	assign z = ~a;
+endmodule
+
+module baz;
+	assign undeclared = 0;
+endmodule
@@ -0,0 +1,76 @@
+// Copyright (c) 2016-2020 Fabian Schuiki
+
+//! Tests of elaboration, run against the compiler binary.
+
+use std::path::PathBuf;
+use std::process::Command;
+
+/// Locate the compiler binary, which is built next to the test binaries.
+fn moore_exe() -> PathBuf {
+    let mut path = std::env::current_exe().unwrap();
+    path.pop();
+    if path.ends_with("deps") {
+        path.pop();
+    }
+    path.join(format!("moore{}", std::env::consts::EXE_SUFFIX))
+}
+
+/// Compile a file with the given name and contents, and return the output of
+/// the compiler.
+fn compile(file_name: &str, text: &str, args: &[&str]) -> String {
+    let dir = std::env::temp_dir().join(format!(
+        "moore-elaborate-{}-{}",
+        std::process::id(),
+        file_name
+    ));
+    std::fs::create_dir_all(&dir).unwrap();
+    let file = dir.join(file_name);
+    std::fs::write(&file, text).unwrap();
+    let output = Command::new(moore_exe())
+        .arg(&file)
+        .args(args)
+        .output()
+        .unwrap();
+    std::fs::remove_dir_all(&dir).unwrap();
+    let stdout = String::from_utf8(output.stdout).unwrap();
+    assert!(
+        output.status.success(),
+        "{}{}",
+        stdout,
+        String::from_utf8_lossy(&output.stderr)
+    );
+    stdout
+}
+
+#[test]
+fn vhdl_generic_sweep() {
+    let output = compile(
+        "sweep.vhd",
+        "entity sweep is\n\
+         \x20 generic (WIDTH : INTEGER := 8);\n\
+         \x20 port (d_in : in BIT_VECTOR(WIDTH - 1 downto 0);\n\
+         \x20   q_out : out BIT_VECTOR(WIDTH - 1 downto 0));\n\
+         end;\n\
+         architecture rtl of sweep is\n\
+         begin\n\
+         \x20 q_out <= d_in;\n\
+         end;\n",
+        &["-e", "sweep", "-P", "WIDTH=2,4,8"],
+    );
+
+    // Every point of the sweep is emitted as an entity of its own.
+    let entities: Vec<_> = output
+        .lines()
+        .filter(|line| line.starts_with("entity @"))
+        .collect();
+    assert_eq!(
+        entities,
+        vec![
+            "entity @sweep_rtl.generic0 ([2 x i1] %d_in) ([2 x i1] %q_out) {",
+            "entity @sweep_rtl.generic1 ([4 x i1] %d_in) ([4 x i1] %q_out) {",
+            "entity @sweep_rtl.generic2 ([8 x i1] %d_in) ([8 x i1] %q_out) {",
+        ],
+        "{}",
+        output
+    );
+}
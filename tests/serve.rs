@@ -0,0 +1,128 @@
+// Copyright (c) 2016-2020 Fabian Schuiki
+
+//! Tests of the compile server, run against the compiler binary.
+
+use moore::server::Json;
+use std::io::{BufRead, BufReader, Write};
+use std::net::TcpStream;
+use std::path::PathBuf;
+use std::process::{Command, Stdio};
+
+/// Locate the compiler binary, which is built next to the test binaries.
+fn moore_exe() -> PathBuf {
+    let mut path = std::env::current_exe().unwrap();
+    path.pop();
+    if path.ends_with("deps") {
+        path.pop();
+    }
+    path.join(format!("moore{}", std::env::consts::EXE_SUFFIX))
+}
+
+/// Start a server on a file with the given contents, send it the requests
+/// produced for the file's path, and return the replies and the server's
+/// output.
+fn serve(text: &str, requests: impl FnOnce(&str) -> Vec<String>) -> (Vec<Json>, String) {
+    let dir =
+        std::env::temp_dir().join(format!("moore-serve-{}-{}", std::process::id(), text.len()));
+    std::fs::create_dir_all(&dir).unwrap();
+    let file = dir.join("top.sv");
+    std::fs::write(&file, text).unwrap();
+
+    // Listen on any free port, which the server announces on stderr.
+    let mut server = Command::new(moore_exe())
+        .args(&["serve", "--listen", "127.0.0.1:0"])
+        .arg(&file)
+        .stdout(Stdio::piped())
+        .stderr(Stdio::piped())
+        .spawn()
+        .unwrap();
+    let mut stderr = BufReader::new(server.stderr.take().unwrap());
+    let mut line = String::new();
+    stderr.read_line(&mut line).unwrap();
+    let addr = line.trim().trim_start_matches("listening on ").to_string();
+
+    let mut stream = TcpStream::connect(addr.as_str()).unwrap();
+    let mut reader = BufReader::new(stream.try_clone().unwrap());
+    let mut request = |request: &str| {
+        writeln!(stream, "{}", request).unwrap();
+        let mut reply = String::new();
+        reader.read_line(&mut reply).unwrap();
+        Json::parse(&reply).unwrap()
+    };
+    let replies = requests(file.to_str().unwrap())
+        .iter()
+        .map(|r| request(r))
+        .collect();
+    request(r#"{"jsonrpc": "2.0", "id": 0, "method": "shutdown"}"#);
+    let status = server.wait().unwrap();
+    std::fs::remove_dir_all(&dir).unwrap();
+    assert!(status.success());
+    let mut stdout = String::new();
+    std::io::Read::read_to_string(&mut server.stdout.take().unwrap(), &mut stdout).unwrap();
+    (replies, stdout)
+}
+
+#[test]
+fn elaborate_returns_output() {
+    let (replies, stdout) = serve(
+        "module top (input logic a, output logic z);\n  assign z = a;\nendmodule\n",
+        |_| {
+            vec![
+                r#"{"jsonrpc": "2.0", "id": 1, "method": "elaborate", "params": {"name": "top"}}"#
+                    .to_string(),
+            ]
+        },
+    );
+    let reply = &replies[0];
+
+    // The generated code is part of the reply, and not written to the
+    // server's own output.
+    let result = reply.get("result").expect("expected a result");
+    assert_eq!(result.get("errors"), Some(&Json::Number(0.0)), "{}", reply);
+    let output = result.get("output").and_then(Json::as_str).unwrap_or("");
+    assert!(output.contains("entity @top"), "{}", reply);
+    assert_eq!(stdout, "");
+}
+
+#[test]
+fn uses_of_declaration() {
+    // The `a` declared in the block shadows the port, and the `a` of `bar` is
+    // unrelated.
+    let (replies, _) = serve(
+        "module top (input logic a, output logic z, y);\n\
+         \x20 assign z = a;\n\
+         \x20 always_comb begin\n\
+         \x20   logic a;\n\
+         \x20   a = 0;\n\
+         \x20   y = a;\n\
+         \x20 end\n\
+         endmodule\n\
+         module bar (input logic a);\n\
+         endmodule\n\
+         module baz;\n\
+         \x20 logic x, y, z;\n\
+         \x20 top t (.a(x), .z(y), .y(z));\n\
+         endmodule\n",
+        |file| {
+            vec![format!(
+                r#"{{"jsonrpc": "2.0", "id": 1, "method": "uses", "params": {{"file": {:?}, "line": 1, "column": 25}}}}"#,
+                file
+            )]
+        },
+    );
+    let reply = &replies[0];
+    let uses: Vec<_> = reply
+        .get("result")
+        .and_then(Json::as_array)
+        .expect("expected a list of uses")
+        .iter()
+        .map(|u| (u.get("line").cloned(), u.get("column").cloned()))
+        .collect();
+    let at = |line: f64, column: f64| (Some(Json::Number(line)), Some(Json::Number(column)));
+    assert_eq!(
+        uses,
+        vec![at(1.0, 25.0), at(2.0, 14.0), at(13.0, 11.0)],
+        "{}",
+        reply
+    );
+}